@@ -0,0 +1,55 @@
+//! `cargo bench` entry point: times the `punch bench` workloads with
+//! criterion on the same synthetic deck.
+
+use std::hint::black_box;
+
+use criterion::{Criterion, Throughput, criterion_group, criterion_main};
+use punchcard::bench::{BenchOptions, synthetic_deck};
+use punchcard::{
+    CardImageStyle, CardSide, CornerCut, Deck, IBM029, ImageRenderOptions, PageLayout, RenderStyle,
+    render_card_image,
+};
+
+fn deck_paths(c: &mut Criterion) {
+    let options = BenchOptions::default();
+    let mut deck = synthetic_deck(options.cards).expect("synthetic deck");
+    let punched = deck.to_punch_deck(&IBM029).expect("synthetic deck punches");
+    let dir = tempfile::tempdir().expect("bench directory");
+    let path = dir.path().join("bench.deck");
+
+    let mut group = c.benchmark_group("deck");
+    group.throughput(Throughput::Elements(options.cards as u64));
+    group.bench_function("encode", |b| {
+        b.iter(|| deck.to_punch_deck(black_box(&IBM029)).unwrap())
+    });
+    group.bench_function("listing", |b| {
+        b.iter(|| punched.render(black_box(RenderStyle::AsciiX)))
+    });
+    group.bench_function("save", |b| b.iter(|| deck.save(black_box(&path)).unwrap()));
+    group.bench_function("load", |b| b.iter(|| Deck::load(black_box(&path)).unwrap()));
+    group.finish();
+}
+
+fn render(c: &mut Criterion) {
+    let options = BenchOptions::default();
+    let deck = synthetic_deck(1).expect("synthetic deck");
+    let card = deck.cards[0].to_punch_card(&IBM029).expect("card punches");
+    let image_options = ImageRenderOptions {
+        style: CardImageStyle::Interpreter,
+        dpi: options.dpi,
+        layout: PageLayout::Card,
+        per_page: None,
+        side: CardSide::Front,
+        corner_cut: CornerCut::None,
+    };
+
+    let mut group = c.benchmark_group("image");
+    group.throughput(Throughput::Elements(1));
+    group.bench_function("render", |b| {
+        b.iter(|| render_card_image(black_box(&card), &image_options).unwrap())
+    });
+    group.finish();
+}
+
+criterion_group!(hot_paths, deck_paths, render);
+criterion_main!(hot_paths);
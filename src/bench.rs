@@ -0,0 +1,137 @@
+//! Throughput measurements for the hot paths (`punch bench`; `cargo bench`
+//! times the same workloads with criterion).
+//!
+//! Each workload runs on a synthetic deck so results are comparable across
+//! machines and revisions; rates are reported per second of wall time.
+
+use std::time::Instant;
+
+use anyhow::Result;
+use serde::Serialize;
+
+use crate::core::deck::{CardType, Deck, DeckHeader, EncodingKind};
+use crate::core::encoding::IBM029;
+use crate::core::punchcards::RenderStyle;
+use crate::image::{
+    CardImageStyle, CardSide, CornerCut, ImageRenderOptions, PageLayout, render_card_image,
+};
+
+/// Sizes of the synthetic workloads.
+#[derive(Debug, Clone, Copy)]
+pub struct BenchOptions {
+    /// Cards in the generated deck (encode, listing, save and load).
+    pub cards: usize,
+    /// Card images rendered.
+    pub renders: usize,
+    pub dpi: u32,
+}
+
+impl Default for BenchOptions {
+    fn default() -> Self {
+        Self {
+            cards: 10_000,
+            renders: 20,
+            dpi: 150,
+        }
+    }
+}
+
+/// Timing of one workload.
+#[derive(Debug, Clone, Serialize)]
+pub struct BenchResult {
+    pub name: &'static str,
+    /// Items processed, in `unit`.
+    pub items: usize,
+    pub unit: &'static str,
+    pub seconds: f64,
+    pub per_second: f64,
+}
+
+impl BenchResult {
+    fn timed<F: FnMut() -> Result<()>>(
+        name: &'static str,
+        items: usize,
+        unit: &'static str,
+        mut work: F,
+    ) -> Result<Self> {
+        let start = Instant::now();
+        work()?;
+        let seconds = start.elapsed().as_secs_f64();
+        Ok(Self {
+            name,
+            items,
+            unit,
+            seconds,
+            per_second: items as f64 / seconds.max(f64::EPSILON),
+        })
+    }
+}
+
+/// Deck of `cards` FORTRAN-like code cards, numbered in columns 73–80.
+pub fn synthetic_deck(cards: usize) -> Result<Deck> {
+    let lines: Vec<String> = (0..cards)
+        .map(|n| format!("      X{} = Y{} * {} + 1.5E0", n % 100, n % 37, n))
+        .collect();
+    let mut deck = Deck::from_lines(
+        DeckHeader::new(Some("fortran".to_string()), None, Vec::new()),
+        lines.iter().map(String::as_str),
+        EncodingKind::Hollerith,
+        CardType::Code,
+    )?;
+    deck.number_sequence(10, 10)?;
+    Ok(deck)
+}
+
+/// Run every workload: encode, ASCII listing, save, load and image render.
+pub fn run(options: &BenchOptions) -> Result<Vec<BenchResult>> {
+    let mut deck = synthetic_deck(options.cards)?;
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("bench.deck");
+    let mut results = vec![BenchResult::timed(
+        "encode",
+        options.cards,
+        "cards",
+        || {
+            deck.to_punch_deck(&IBM029)?;
+            Ok(())
+        },
+    )?];
+    let punched = deck.to_punch_deck(&IBM029)?;
+    results.push(BenchResult::timed(
+        "listing",
+        options.cards,
+        "cards",
+        || {
+            punched.render(RenderStyle::AsciiX);
+            Ok(())
+        },
+    )?);
+    results.push(BenchResult::timed("save", options.cards, "cards", || {
+        deck.save(&path)
+    })?);
+    results.push(BenchResult::timed("load", options.cards, "cards", || {
+        Deck::load(&path).map(drop)
+    })?);
+
+    let card = deck.cards[0].to_punch_card(&IBM029)?;
+    let image_options = ImageRenderOptions {
+        style: CardImageStyle::Interpreter,
+        dpi: options.dpi,
+        layout: PageLayout::Card,
+        per_page: None,
+        side: CardSide::Front,
+        corner_cut: CornerCut::None,
+    };
+    results.push(BenchResult::timed(
+        "render",
+        options.renders,
+        "images",
+        || {
+            for _ in 0..options.renders {
+                render_card_image(&card, &image_options)?;
+            }
+            Ok(())
+        },
+    )?);
+    Ok(results)
+}
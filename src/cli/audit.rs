@@ -1,18 +1,28 @@
 //! Audit and hashing commands (`punch audit ...`).
 
-use std::path::PathBuf;
+use std::fs;
+use std::path::{Path, PathBuf};
 
-use crate::cli::utils::load_deck;
-use anyhow::Result;
+use crate::cli::common::ForceArg;
+use crate::cli::exit::{CheckFailed, EXIT_DIFFERENCES, EXIT_VIOLATIONS};
+use crate::cli::utils::{allow_edit, load_deck, read_deck, save_deck};
+use anyhow::{Context, Result};
 use clap::{Args, Subcommand};
+use punchcard::signing::{public_key_hex, signing_key_from_pem, verifying_key_from_pem};
+use punchcard::{Deck, SignatureStatus};
 
 /// Audit subcommands.
 #[derive(Subcommand, Debug)]
 pub enum AuditCommand {
-    /// Compute SHA-256 hash over deck content.
+    /// Compute SHA-256 hash over deck content and check every card against
+    /// its stored hash.
     Hash(AuditHashArgs),
     /// Show audited history events.
     Log(AuditLogArgs),
+    /// Sign the deck's content hash with an Ed25519 key.
+    Sign(AuditSignArgs),
+    /// Check the deck's signatures against its current content.
+    VerifySignature(AuditVerifySignatureArgs),
 }
 
 /// Arguments for `punch audit hash`.
@@ -20,6 +30,13 @@ pub enum AuditCommand {
 pub struct AuditHashArgs {
     /// Deck file to hash.
     pub deck: PathBuf,
+    /// Also print the Merkle root and every card's hash.
+    #[arg(long = "per-card")]
+    pub per_card: bool,
+    /// List the cards whose hashes differ from this deck's (exit 1 if any).
+    /// Cards that fail their stored hash, in either deck, also exit 1.
+    #[arg(long, value_name = "DECK")]
+    pub against: Option<PathBuf>,
 }
 
 /// Arguments for `punch audit log`.
@@ -29,19 +46,109 @@ pub struct AuditLogArgs {
     pub deck: PathBuf,
 }
 
+/// Arguments for `punch audit sign`.
+#[derive(Args, Debug)]
+pub struct AuditSignArgs {
+    /// Deck file to sign.
+    pub deck: PathBuf,
+    /// Ed25519 private key in PKCS#8 PEM form
+    /// (`openssl genpkey -algorithm ed25519 -out key.pem`).
+    #[arg(long, value_name = "PEM")]
+    pub key: PathBuf,
+    #[command(flatten)]
+    pub force: ForceArg,
+}
+
+/// Arguments for `punch audit verify-signature`.
+#[derive(Args, Debug)]
+pub struct AuditVerifySignatureArgs {
+    /// Deck file to check.
+    pub deck: PathBuf,
+    /// Require a valid signature by this public (or private) key PEM.
+    #[arg(long, value_name = "PEM")]
+    pub key: Option<PathBuf>,
+}
+
 /// Execute an audit command.
 pub fn handle(command: AuditCommand) -> Result<()> {
     match command {
         AuditCommand::Hash(args) => hash(args),
         AuditCommand::Log(args) => log(args),
+        AuditCommand::Sign(args) => sign(args),
+        AuditCommand::VerifySignature(args) => verify_signature(args),
     }
 }
 
 fn hash(args: AuditHashArgs) -> Result<()> {
-    let deck = load_deck(args.deck.as_path())?;
+    let deck = read_deck(args.deck.as_path())?;
     let digest = deck.hash()?;
     println!("{}", digest);
-    Ok(())
+    if args.per_card {
+        println!("merkle root {}", deck.merkle_root()?);
+        for (idx, card_hash) in deck.card_hashes()?.iter().enumerate() {
+            println!("card {:>4} {}", idx + 1, card_hash);
+        }
+    }
+    let mut tampered = report_hash_mismatches(&args.deck, &deck);
+    let mut problems = Vec::new();
+    if let Some(other_path) = &args.against {
+        let other = read_deck(other_path)?;
+        tampered += report_hash_mismatches(other_path, &other);
+        if deck.merkle_root()? == other.merkle_root()? {
+            println!(
+                "Cards match {} ({} card(s))",
+                other_path.display(),
+                deck.cards.len()
+            );
+        } else {
+            let changed = deck.changed_cards(&other)?;
+            for idx in &changed {
+                let what = if *idx >= other.cards.len() {
+                    format!("only in {}", args.deck.display())
+                } else if *idx >= deck.cards.len() {
+                    format!("only in {}", other_path.display())
+                } else {
+                    "changed".to_string()
+                };
+                println!("card {:>4} {}", idx + 1, what);
+            }
+            problems.push(format!(
+                "{} card(s) differ from {}",
+                changed.len(),
+                other_path.display()
+            ));
+        }
+    }
+    if tampered > 0 {
+        problems.insert(
+            0,
+            format!("{} card(s) do not match their stored sha256", tampered),
+        );
+    }
+    if problems.is_empty() {
+        return Ok(());
+    }
+    Err(CheckFailed {
+        code: EXIT_DIFFERENCES,
+        summary: problems.join("; "),
+    }
+    .into())
+}
+
+/// Print each card of `deck` that failed its stored hash on load, returning
+/// how many did.
+fn report_hash_mismatches(path: &Path, deck: &Deck) -> usize {
+    for mismatch in &deck.hash_mismatches {
+        println!(
+            "{}: card {:>4} (line {}) stored sha256 {} but hashes to {}",
+            path.display(),
+            mismatch.card + 1,
+            mismatch.line,
+            mismatch.stored,
+            mismatch.computed
+        );
+    }
+    deck.hash_mismatches.len()
 }
 
 fn log(args: AuditLogArgs) -> Result<()> {
@@ -51,7 +158,98 @@ fn log(args: AuditLogArgs) -> Result<()> {
     } else {
         for event in &deck.header.history {
             println!("{} {} - {}", event.timestamp, event.actor, event.action);
+            for change in &event.changes {
+                println!("    {}", change);
+            }
         }
     }
     Ok(())
 }
+
+fn read_pem(path: &Path) -> Result<String> {
+    fs::read_to_string(path).with_context(|| format!("failed to read key {}", path.display()))
+}
+
+/// Signing leaves the history alone: an entry for it would change the very
+/// hash being signed.
+fn sign(args: AuditSignArgs) -> Result<()> {
+    let key = signing_key_from_pem(&read_pem(&args.key)?)
+        .with_context(|| format!("invalid key {}", args.key.display()))?;
+    let mut deck = load_deck(args.deck.as_path())?;
+    allow_edit(&mut deck, args.force, "audit sign")?;
+    let signature = deck.sign(&key)?.clone();
+    save_deck(&mut deck, &args.deck)?;
+    println!("Signed {}", args.deck.display());
+    println!("  hash {}", signature.hash);
+    println!("  key  {}", signature.public_key);
+    Ok(())
+}
+
+fn verify_signature(args: AuditVerifySignatureArgs) -> Result<()> {
+    let required = args
+        .key
+        .as_deref()
+        .map(|path| {
+            read_pem(path)
+                .and_then(|pem| verifying_key_from_pem(&pem))
+                .with_context(|| format!("invalid key {}", path.display()))
+                .map(|key| public_key_hex(&key))
+        })
+        .transpose()?;
+    let deck = load_deck(args.deck.as_path())?;
+    let checked = deck.check_signatures()?;
+    for (signature, status) in &checked {
+        let status = match status {
+            SignatureStatus::Valid => "valid",
+            SignatureStatus::Stale => "stale (deck changed since signing)",
+            SignatureStatus::Forged => "FORGED",
+        };
+        println!(
+            "{} {} signed {}: {}",
+            signature.public_key,
+            signature.hash,
+            signature.signed_at.format("%Y-%m-%d %H:%M"),
+            status
+        );
+    }
+
+    let forged = checked
+        .iter()
+        .filter(|(_, status)| *status == SignatureStatus::Forged)
+        .count();
+    let stale = checked
+        .iter()
+        .filter(|(_, status)| *status == SignatureStatus::Stale)
+        .count();
+    let failure = if checked.is_empty() {
+        Some((EXIT_VIOLATIONS, "deck is not signed".to_string()))
+    } else if forged > 0 {
+        Some((
+            EXIT_VIOLATIONS,
+            format!("{} signature(s) do not verify", forged),
+        ))
+    } else if let Some(key) = required.filter(|key| {
+        !checked
+            .iter()
+            .any(|(sig, status)| sig.public_key == *key && *status == SignatureStatus::Valid)
+    }) {
+        Some((
+            EXIT_VIOLATIONS,
+            format!("no valid signature by key {}", key),
+        ))
+    } else if stale > 0 {
+        Some((
+            EXIT_DIFFERENCES,
+            format!("{} signature(s) predate changes to the deck", stale),
+        ))
+    } else {
+        None
+    };
+    match failure {
+        Some((code, summary)) => Err(CheckFailed { code, summary }.into()),
+        None => {
+            println!("{} signature(s) valid", checked.len());
+            Ok(())
+        }
+    }
+}
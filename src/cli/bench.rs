@@ -0,0 +1,43 @@
+//! Hot-path throughput measurements (`punch bench`).
+
+use anyhow::Result;
+use clap::Args;
+use punchcard::bench::{self, BenchOptions};
+
+/// Arguments for `punch bench`.
+#[derive(Args, Debug)]
+pub struct BenchArgs {
+    /// Cards in the synthetic deck used for encode, listing, save and load.
+    #[arg(long, default_value_t = BenchOptions::default().cards)]
+    pub cards: usize,
+    /// Card images to render.
+    #[arg(long, default_value_t = BenchOptions::default().renders)]
+    pub renders: usize,
+    /// Resolution of rendered images.
+    #[arg(long, default_value_t = BenchOptions::default().dpi)]
+    pub dpi: u32,
+    /// Print the results as JSON.
+    #[arg(long)]
+    pub json: bool,
+}
+
+/// Run the benchmarks and print their rates.
+pub fn handle(args: BenchArgs) -> Result<()> {
+    let options = BenchOptions {
+        cards: args.cards.max(1),
+        renders: args.renders.max(1),
+        dpi: args.dpi.clamp(72, 1200),
+    };
+    let results = bench::run(&options)?;
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&results)?);
+        return Ok(());
+    }
+    for result in results {
+        println!(
+            "{:<8} {:>8} {:<6} {:>9.3}s {:>12.1} {}/s",
+            result.name, result.items, result.unit, result.seconds, result.per_second, result.unit
+        );
+    }
+    Ok(())
+}
@@ -1,15 +1,19 @@
 //! Card-level operations (`punch card ...`).
 
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use anyhow::{Context, Result, anyhow};
-use clap::{Args, Subcommand};
+use clap::{Args, Subcommand, ValueEnum};
 use punchcard::{
-    CardMeta, CardRecord, CardType, EncodingKind, Ibm029Encoder, RenderStyle, TemplateRegistry,
+    CardMeta, CardRecord, CardType, Deck, DelimiterKind, EncodingKind, PatchTarget, RenderStyle,
+    TemplateRegistry, delimiter_card, diff_columns, formats, pending,
 };
 
-use crate::cli::common::CardTypeArg;
-use crate::cli::utils::{load_deck, read_stdin, read_text_arg, split_lines_fixed};
+use crate::cli::common::{CardTypeArg, EncodingArg, ForceArg, OverflowArg};
+use crate::cli::utils::{
+    allow_edit, load_deck, parse_range_expression, read_stdin, read_text_arg, save_deck,
+    split_input,
+};
 
 /// Supported `punch card` subcommands.
 #[derive(Subcommand, Debug)]
@@ -24,6 +28,14 @@ pub enum CardCommand {
     Show(CardShowArgs),
     /// Insert a separator/comment card.
     Patch(CardPatchArgs),
+    /// Append a correctly punched delimiter card (EOF, end of job, `/*`).
+    Delimiter(CardDelimiterArgs),
+    /// Remove one card or a range of cards.
+    Remove(CardRemoveArgs),
+    /// Move a card to another position.
+    Move(CardMoveArgs),
+    /// Change the stored encoding of a range of cards.
+    SetEncoding(CardSetEncodingArgs),
 }
 
 /// Arguments for `punch card add`.
@@ -37,9 +49,16 @@ pub struct CardAddArgs {
     /// Load card text from file (`-` for stdin).
     #[arg(long = "from")]
     pub from: Option<PathBuf>,
+    /// Punch holes directly as COLUMN:ROWS items, e.g. "1:12-1 2:11-3-8"
+    /// (for binary or control cards with no text).
+    #[arg(long, value_name = "SPEC", conflicts_with_all = ["text", "from", "template"])]
+    pub punches: Option<String>,
     /// Apply template defaults.
     #[arg(long)]
     pub template: Option<String>,
+    /// Fill a template field, e.g. --field seq=000100 (repeatable; needs --template).
+    #[arg(long = "field", value_name = "NAME=VALUE", requires = "template")]
+    pub fields: Vec<String>,
     /// Explicit card type.
     #[arg(long = "type", default_value_t = CardTypeArg::Code, value_enum)]
     pub card_type: CardTypeArg,
@@ -52,6 +71,15 @@ pub struct CardAddArgs {
     /// Insert at 1-based position (defaults to append).
     #[arg(long)]
     pub position: Option<usize>,
+    /// What to do with lines wider than 80 columns.
+    #[arg(long, default_value_t = OverflowArg::Error, value_enum)]
+    pub overflow: OverflowArg,
+    /// Stage the cards in the deck's pending file instead of rewriting the deck;
+    /// `punch deck flush` adds them all in one save.
+    #[arg(long = "defer-save", conflicts_with = "position")]
+    pub defer_save: bool,
+    #[command(flatten)]
+    pub force: ForceArg,
 }
 
 /// Arguments for `punch card type`.
@@ -71,6 +99,15 @@ pub struct CardTypeArgs {
     /// Optional color hint.
     #[arg(long)]
     pub color: Option<String>,
+    /// What to do with lines wider than 80 columns.
+    #[arg(long, default_value_t = OverflowArg::Error, value_enum)]
+    pub overflow: OverflowArg,
+    /// Stage the cards in the deck's pending file instead of rewriting the deck;
+    /// `punch deck flush` adds them all in one save.
+    #[arg(long = "defer-save")]
+    pub defer_save: bool,
+    #[command(flatten)]
+    pub force: ForceArg,
 }
 
 /// Arguments for `punch card replace`.
@@ -91,6 +128,11 @@ pub struct CardReplaceArgs {
     pub color: Option<String>,
     #[arg(long = "type", value_enum)]
     pub card_type: Option<CardTypeArg>,
+    /// Print the columns that changed (they are always recorded in the audit log).
+    #[arg(long)]
+    pub show_diff: bool,
+    #[command(flatten)]
+    pub force: ForceArg,
 }
 
 /// Arguments for `punch card show`.
@@ -104,6 +146,92 @@ pub struct CardShowArgs {
     /// Render punched rows using ASCII art.
     #[arg(long)]
     pub interpret: bool,
+    /// Break the card into the named template's fields.
+    #[arg(long)]
+    pub template: Option<String>,
+}
+
+/// Arguments for `punch card remove`.
+#[derive(Args, Debug)]
+pub struct CardRemoveArgs {
+    /// Deck file to modify.
+    pub deck: PathBuf,
+    /// 1-based index of the card to remove.
+    #[arg(
+        short = 'i',
+        long = "index",
+        required_unless_present = "range",
+        conflicts_with = "range"
+    )]
+    pub index: Option<usize>,
+    /// Cards to remove, e.g. "12..30" or "1..10,25,30..$" (1-based).
+    #[arg(short = 'r', long = "range")]
+    pub range: Option<String>,
+    #[command(flatten)]
+    pub force: ForceArg,
+}
+
+/// Arguments for `punch card move`.
+#[derive(Args, Debug)]
+pub struct CardMoveArgs {
+    /// Deck file to modify.
+    pub deck: PathBuf,
+    /// 1-based index of the card to move.
+    #[arg(long)]
+    pub from: usize,
+    /// 1-based position the card ends up at.
+    #[arg(long)]
+    pub to: usize,
+    #[command(flatten)]
+    pub force: ForceArg,
+}
+
+/// Arguments for `punch card set-encoding`.
+#[derive(Args, Debug)]
+pub struct CardSetEncodingArgs {
+    /// Deck file to modify.
+    pub deck: PathBuf,
+    /// Cards to change, e.g. "12..30" or "1..10,25,30..$" (1-based).
+    #[arg(short = 'i', long = "index", value_name = "RANGE")]
+    pub range: String,
+    /// Encoding the cards' text is punched in from now on.
+    #[arg(long, value_enum)]
+    pub encoding: EncodingArg,
+    #[command(flatten)]
+    pub force: ForceArg,
+}
+
+/// Arguments for `punch card delimiter`.
+#[derive(Args, Debug)]
+pub struct CardDelimiterArgs {
+    /// Deck file to modify.
+    pub deck: PathBuf,
+    /// Delimiter to append.
+    #[arg(long, value_enum)]
+    pub kind: DelimiterArg,
+    #[command(flatten)]
+    pub force: ForceArg,
+}
+
+/// Delimiter cards understood by `punch card delimiter`.
+#[derive(ValueEnum, Debug, Clone, Copy)]
+pub enum DelimiterArg {
+    /// 6-7-9 multipunch end-of-file card.
+    Eof,
+    /// `//` end-of-job (null) statement.
+    Eoj,
+    /// `/*` end-of-data delimiter.
+    SlashAsterisk,
+}
+
+impl From<DelimiterArg> for DelimiterKind {
+    fn from(value: DelimiterArg) -> Self {
+        match value {
+            DelimiterArg::Eof => DelimiterKind::Eof,
+            DelimiterArg::Eoj => DelimiterKind::Eoj,
+            DelimiterArg::SlashAsterisk => DelimiterKind::SlashAsterisk,
+        }
+    }
 }
 
 /// Arguments for `punch card patch`.
@@ -118,6 +246,14 @@ pub struct CardPatchArgs {
     pub from: Option<PathBuf>,
     #[arg(long)]
     pub note: Option<String>,
+    /// Card (1-based) this patch corrects; recorded by sequence number when it has one.
+    #[arg(long, conflicts_with = "target_seq")]
+    pub target: Option<usize>,
+    /// Sequence number of the card this patch corrects.
+    #[arg(long = "target-seq")]
+    pub target_seq: Option<usize>,
+    #[command(flatten)]
+    pub force: ForceArg,
 }
 
 /// Execute a card command.
@@ -128,11 +264,16 @@ pub fn handle(command: CardCommand) -> Result<()> {
         CardCommand::Replace(args) => replace(args),
         CardCommand::Show(args) => show(args),
         CardCommand::Patch(args) => patch(args),
+        CardCommand::Delimiter(args) => delimiter(args),
+        CardCommand::Remove(args) => remove(args),
+        CardCommand::Move(args) => move_card(args),
+        CardCommand::SetEncoding(args) => set_encoding(args),
     }
 }
 
 fn add(args: CardAddArgs) -> Result<()> {
     let mut deck = load_deck(args.deck.as_path())?;
+    allow_edit(&mut deck, args.force, "card add")?;
     let template = match &args.template {
         Some(name) => Some(
             TemplateRegistry::get(name)
@@ -140,18 +281,53 @@ fn add(args: CardAddArgs) -> Result<()> {
         ),
         None => None,
     };
-    let text = read_text_arg(args.text.clone(), args.from.clone())?;
-    let lines = split_lines_fixed(&text);
+    let fields = args
+        .fields
+        .iter()
+        .map(|field| {
+            field
+                .split_once('=')
+                .ok_or_else(|| anyhow!("--field expects NAME=VALUE, got '{}'", field))
+        })
+        .collect::<Result<Vec<(&str, &str)>>>()?;
     let chosen_type: CardType = args.card_type.into();
-    for (i, line) in lines.iter().enumerate() {
-        let mut record = if let Some(tpl) = template {
-            tpl.apply(line)?
+    let records = if let Some(spec) = &args.punches {
+        let columns = formats::parse_punch_spec(spec).context("invalid --punches")?;
+        vec![CardRecord::from_punches(&columns, chosen_type)]
+    } else {
+        let text = if fields.is_empty() || args.text.is_some() || args.from.is_some() {
+            read_text_arg(args.text.clone(), args.from.clone())?
         } else {
-            CardRecord::from_text(line, EncodingKind::Hollerith, chosen_type.clone())?
+            String::new()
         };
+        split_input(&text, args.overflow)?
+            .iter()
+            .map(|line| match template {
+                Some(tpl) => tpl.build_card(line, &fields),
+                None => CardRecord::from_text(line, EncodingKind::Hollerith, chosen_type.clone()),
+            })
+            .collect::<Result<Vec<_>>>()?
+    };
+    let count = records.len();
+    if args.defer_save {
+        let cards = records
+            .into_iter()
+            .map(|mut record| {
+                record.meta = CardMeta {
+                    note: args.note.clone(),
+                    color: args.color.clone(),
+                    ..CardMeta::default()
+                };
+                record
+            })
+            .collect();
+        return defer(&deck, &args.deck, "card add", cards);
+    }
+    for (i, mut record) in records.into_iter().enumerate() {
         record.meta = CardMeta {
             note: args.note.clone(),
             color: args.color.clone(),
+            ..CardMeta::default()
         };
         if let Some(pos) = args.position {
             let idx = pos.saturating_sub(1) + i;
@@ -161,13 +337,14 @@ fn add(args: CardAddArgs) -> Result<()> {
         }
     }
     deck.log_action("card add");
-    deck.save(&args.deck)?;
-    println!("Added {} card(s) into {}", lines.len(), args.deck.display());
+    save_deck(&mut deck, &args.deck)?;
+    println!("Added {} card(s) into {}", count, args.deck.display());
     Ok(())
 }
 
 fn type_cards(args: CardTypeArgs) -> Result<()> {
     let mut deck = load_deck(args.deck.as_path())?;
+    allow_edit(&mut deck, args.force, "card type")?;
     let template = match &args.template {
         Some(name) => Some(
             TemplateRegistry::get(name)
@@ -176,8 +353,9 @@ fn type_cards(args: CardTypeArgs) -> Result<()> {
         None => None,
     };
     let buffer = read_stdin()?;
-    let lines = split_lines_fixed(&buffer);
+    let lines = split_input(&buffer, args.overflow)?;
     let chosen_type: CardType = args.card_type.into();
+    let mut staged = Vec::new();
     for line in lines {
         let mut record = if let Some(tpl) = template {
             tpl.apply(&line)?
@@ -187,17 +365,45 @@ fn type_cards(args: CardTypeArgs) -> Result<()> {
         record.meta = CardMeta {
             note: args.note.clone(),
             color: args.color.clone(),
+            ..CardMeta::default()
         };
-        deck.append_card(record)?;
+        if args.defer_save {
+            staged.push(record);
+        } else {
+            deck.append_card(record)?;
+        }
+    }
+    if args.defer_save {
+        return defer(&deck, &args.deck, "card type", staged);
     }
     deck.log_action("card type");
-    deck.save(&args.deck)?;
+    save_deck(&mut deck, &args.deck)?;
     println!("Typed cards appended to {}", args.deck.display());
     Ok(())
 }
 
+/// Stage `cards` for `punch deck flush` instead of saving the deck.
+fn defer(deck: &Deck, path: &Path, action: &str, cards: Vec<CardRecord>) -> Result<()> {
+    let count = cards.len();
+    pending::stage(
+        deck,
+        path,
+        &pending::PendingBatch {
+            action: action.to_string(),
+            cards,
+        },
+    )?;
+    println!(
+        "Staged {} card(s) for {}; run `punch deck flush` to add them",
+        count,
+        path.display()
+    );
+    Ok(())
+}
+
 fn replace(args: CardReplaceArgs) -> Result<()> {
     let mut deck = load_deck(args.deck.as_path())?;
+    allow_edit(&mut deck, args.force, "card replace")?;
     if args.index == 0 || args.index > deck.cards.len() {
         return Err(anyhow!(
             "card index {} out of range 1..{}",
@@ -214,11 +420,20 @@ fn replace(args: CardReplaceArgs) -> Result<()> {
     record.meta = CardMeta {
         note: args.note.clone(),
         color: args.color.clone(),
+        ..CardMeta::default()
     };
+    let before = deck.card_text(args.index - 1)?.into_owned();
     deck.replace_card(args.index - 1, record)?;
-    deck.log_action(format!("card replace {}", args.index));
-    deck.save(&args.deck)?;
+    let after = deck.card_text(args.index - 1)?.into_owned();
+    let changes = diff_columns(&before, &after);
+    deck.log_change(format!("card replace {}", args.index), changes.clone());
+    save_deck(&mut deck, &args.deck)?;
     println!("Replaced card {} in {}", args.index, args.deck.display());
+    if args.show_diff {
+        for change in &changes {
+            println!("  {}", change);
+        }
+    }
     Ok(())
 }
 
@@ -234,6 +449,9 @@ fn show(args: CardShowArgs) -> Result<()> {
     let card = &deck.cards[args.index - 1];
     println!("Card {} of {}", args.index, deck.cards.len());
     println!("Type: {:?}", card.card_type);
+    if card.text.is_some() && card.encoding != EncodingKind::Hollerith {
+        println!("Encoding: {:?}", card.encoding);
+    }
     if let Some(seq) = card.seq {
         println!("Sequence: {}", seq);
     }
@@ -250,24 +468,160 @@ fn show(args: CardShowArgs) -> Result<()> {
         None => println!("(card stored as punches)"),
     }
     if args.interpret {
-        let encoder = Ibm029Encoder::new();
-        let punch = card.to_punch_card(&encoder)?;
+        let punch = card.to_punch_card(deck.encoder()?)?;
         println!("{}", punch.render(RenderStyle::AsciiX));
     }
+    if let Some(name) = args.template.as_deref() {
+        let tpl = TemplateRegistry::get(name)?;
+        let punch = card.to_punch_card(deck.encoder()?)?;
+        println!("Fields ({}):", tpl.name);
+        for (column, (label, value)) in tpl.columns.iter().zip(tpl.extract_fields(&punch)?) {
+            println!(
+                "  {:>2}-{:>2} {}: {}",
+                column.range.start, column.range.end, label, value
+            );
+        }
+    }
     Ok(())
 }
 
 fn patch(args: CardPatchArgs) -> Result<()> {
     let mut deck = load_deck(args.deck.as_path())?;
+    allow_edit(&mut deck, args.force, "card patch")?;
     let text = read_text_arg(args.text.clone(), args.from.clone())?;
+    let patches = match (args.target, args.target_seq) {
+        (Some(card), _) => {
+            let index = card
+                .checked_sub(1)
+                .ok_or_else(|| anyhow!("card index 0 out of range 1..{}", deck.cards.len()))?;
+            Some(deck.patch_target(index)?)
+        }
+        (None, Some(seq)) => {
+            let target = PatchTarget::Seq(seq);
+            deck.resolve_patch_target(target)
+                .ok_or_else(|| anyhow!("no card has sequence number {}", seq))?;
+            Some(target)
+        }
+        (None, None) => None,
+    };
     let mut record = CardRecord::from_text(&text, EncodingKind::Hollerith, CardType::Patch)?;
     record.meta = CardMeta {
         note: args.note.clone().or_else(|| Some("patch card".to_string())),
         color: Some("amber".to_string()),
+        patches,
     };
     deck.append_card(record)?;
-    deck.log_action("card patch");
-    deck.save(&args.deck)?;
-    println!("Appended patch card to {}", args.deck.display());
+    match patches {
+        Some(target) => {
+            deck.log_action(format!("card patch {}", target));
+            println!(
+                "Appended patch card for {} to {}",
+                target,
+                args.deck.display()
+            );
+        }
+        None => {
+            deck.log_action("card patch");
+            println!("Appended patch card to {}", args.deck.display());
+        }
+    }
+    save_deck(&mut deck, &args.deck)?;
+    Ok(())
+}
+
+fn delimiter(args: CardDelimiterArgs) -> Result<()> {
+    let mut deck = load_deck(args.deck.as_path())?;
+    allow_edit(&mut deck, args.force, "card delimiter")?;
+    let kind: DelimiterKind = args.kind.into();
+    deck.append_card(delimiter_card(kind))?;
+    deck.log_action(format!("card delimiter {:?}", kind));
+    save_deck(&mut deck, &args.deck)?;
+    println!(
+        "Appended {:?} delimiter as card {} in {}",
+        kind,
+        deck.cards.len(),
+        args.deck.display()
+    );
+    Ok(())
+}
+
+fn remove(args: CardRemoveArgs) -> Result<()> {
+    let mut deck = load_deck(args.deck.as_path())?;
+    allow_edit(&mut deck, args.force, "card remove")?;
+    deck.ensure_mutable()?;
+    let mut indexes = match (&args.range, args.index) {
+        (Some(expr), _) => parse_range_expression(expr, deck.cards.len())?,
+        (None, Some(index)) => {
+            if index == 0 || index > deck.cards.len() {
+                return Err(anyhow!(
+                    "card index {} out of range 1..{}",
+                    index,
+                    deck.cards.len()
+                ));
+            }
+            vec![index - 1]
+        }
+        (None, None) => return Err(anyhow!("pass --index or --range")),
+    };
+    indexes.sort_unstable();
+    indexes.dedup();
+    // Remove from the back so earlier indexes stay valid; the audit log keeps
+    // each card's text, or the hex of its holes for a punch-only card, so a
+    // removal can be undone by hand.
+    for &idx in indexes.iter().rev() {
+        let card = deck.remove_card(idx)?;
+        let content = match (&card.text, &card.punches) {
+            (Some(text), _) => text.trim_end().to_string(),
+            (None, Some(hex)) => format!("punches {}", hex),
+            (None, None) => String::new(),
+        };
+        deck.log_action(format!("card remove {}: {}", idx + 1, content));
+    }
+    save_deck(&mut deck, &args.deck)?;
+    println!(
+        "Removed {} card(s) from {}",
+        indexes.len(),
+        args.deck.display()
+    );
+    Ok(())
+}
+
+fn move_card(args: CardMoveArgs) -> Result<()> {
+    let mut deck = load_deck(args.deck.as_path())?;
+    allow_edit(&mut deck, args.force, "card move")?;
+    let len = deck.cards.len();
+    for index in [args.from, args.to] {
+        if index == 0 || index > len {
+            return Err(anyhow!("card index {} out of range 1..{}", index, len));
+        }
+    }
+    deck.move_card(args.from - 1, args.to - 1)?;
+    deck.log_action(format!("card move {} -> {}", args.from, args.to));
+    save_deck(&mut deck, &args.deck)?;
+    println!(
+        "Moved card {} to position {} in {}",
+        args.from,
+        args.to,
+        args.deck.display()
+    );
+    Ok(())
+}
+
+fn set_encoding(args: CardSetEncodingArgs) -> Result<()> {
+    let mut deck = load_deck(args.deck.as_path())?;
+    allow_edit(&mut deck, args.force, "card set-encoding")?;
+    deck.ensure_mutable()?;
+    let indexes = parse_range_expression(&args.range, deck.cards.len())?;
+    let kind: EncodingKind = args.encoding.into();
+    let changed = deck.set_encoding(&indexes, kind)?;
+    deck.log_action(format!("card set-encoding {} {:?}", args.range, kind));
+    save_deck(&mut deck, &args.deck)?;
+    println!(
+        "Set {} of {} card(s) to {:?} in {}",
+        changed,
+        indexes.len(),
+        kind,
+        args.deck.display()
+    );
     Ok(())
 }
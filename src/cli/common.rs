@@ -1,7 +1,19 @@
 //! Shared clap helper types for CLI commands.
 
-use clap::ValueEnum;
-use punchcard::{CardImageStyle, CardType, EncodingKind, PageLayout, RenderStyle};
+use std::fmt;
+use std::path::PathBuf;
+
+use anyhow::Result;
+use clap::{Args, ValueEnum};
+use punchcard::{
+    ASCII, Captions, CardImageStyle, CardSide, CardType, ColumnRange, CornerCut, Deck, EBCDIC,
+    EncodingKind, IBM026, IBM026_FORTRAN, IBM029, Imposition, Locale, MaskFile, MaskSet,
+    PageLayout, PunchEncoding, RenderStyle,
+};
+
+use punchcard::text::Overflow;
+
+use crate::cli::utils::parse_column_range;
 
 /// Supported encoding flags accepted by CLI commands.
 #[derive(ValueEnum, Debug, Clone, Copy)]
@@ -21,6 +33,51 @@ impl From<EncodingArg> for EncodingKind {
     }
 }
 
+/// Punch charts selectable with `--encoder`.
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EncoderArg {
+    Ibm029,
+    /// IBM 026 commercial chart.
+    Ibm026,
+    /// IBM 026 FORTRAN chart.
+    Ibm026Fortran,
+    /// EBCDIC (code page 037) in the System/360 card code.
+    Ebcdic,
+    /// 7-bit ASCII in the System/360 card code.
+    Ascii,
+}
+
+impl EncoderArg {
+    /// The encoder implementing this chart.
+    pub fn encoder(self) -> &'static dyn PunchEncoding {
+        match self {
+            EncoderArg::Ibm029 => &IBM029,
+            EncoderArg::Ibm026 => &IBM026,
+            EncoderArg::Ibm026Fortran => &IBM026_FORTRAN,
+            EncoderArg::Ebcdic => &EBCDIC,
+            EncoderArg::Ascii => &ASCII,
+        }
+    }
+}
+
+/// The chart picked by an optional `--encoder` flag, falling back to the one
+/// the deck declares.
+pub fn resolve_encoder(arg: Option<EncoderArg>, deck: &Deck) -> Result<&'static dyn PunchEncoding> {
+    match arg {
+        Some(arg) => Ok(arg.encoder()),
+        None => deck.encoder(),
+    }
+}
+
+impl fmt::Display for EncoderArg {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let value = self
+            .to_possible_value()
+            .expect("encoder variants are not skipped");
+        write!(f, "{}", value.get_name())
+    }
+}
+
 /// Card type selector used by several commands.
 #[derive(ValueEnum, Debug, Clone, Copy)]
 pub enum CardTypeArg {
@@ -45,6 +102,28 @@ impl From<CardTypeArg> for CardType {
     }
 }
 
+/// Policy for input lines wider than 80 columns.
+#[derive(ValueEnum, Debug, Clone, Copy, Default)]
+pub enum OverflowArg {
+    /// Reject the input and name the offending line.
+    #[default]
+    Error,
+    /// Drop everything past column 80.
+    Truncate,
+    /// Continue the remainder on following cards.
+    Wrap,
+}
+
+impl From<OverflowArg> for Overflow {
+    fn from(value: OverflowArg) -> Overflow {
+        match value {
+            OverflowArg::Error => Overflow::Error,
+            OverflowArg::Truncate => Overflow::Truncate,
+            OverflowArg::Wrap => Overflow::Wrap,
+        }
+    }
+}
+
 /// Render styles available for ASCII punch views.
 #[derive(ValueEnum, Debug, Clone, Copy)]
 pub enum RenderStyleArg {
@@ -52,6 +131,10 @@ pub enum RenderStyleArg {
     AsciiX,
     #[value(name = "ascii-01")]
     Ascii01,
+    /// Half blocks, two rows per line.
+    Block,
+    /// Braille dots, four rows and two columns per character.
+    Braille,
 }
 
 impl From<RenderStyleArg> for RenderStyle {
@@ -59,6 +142,8 @@ impl From<RenderStyleArg> for RenderStyle {
         match value {
             RenderStyleArg::AsciiX => RenderStyle::AsciiX,
             RenderStyleArg::Ascii01 => RenderStyle::Ascii01,
+            RenderStyleArg::Block => RenderStyle::Block,
+            RenderStyleArg::Braille => RenderStyle::Braille,
         }
     }
 }
@@ -86,6 +171,7 @@ impl From<CardImageStyleArg> for CardImageStyle {
 pub enum PageLayoutArg {
     Card,
     A4,
+    Letter,
 }
 
 impl From<PageLayoutArg> for PageLayout {
@@ -93,6 +179,146 @@ impl From<PageLayoutArg> for PageLayout {
         match value {
             PageLayoutArg::Card => PageLayout::Card,
             PageLayoutArg::A4 => PageLayout::A4,
+            PageLayoutArg::Letter => PageLayout::Letter,
+        }
+    }
+}
+
+/// Card faces accepted by `render image --side`.
+#[derive(ValueEnum, Debug, Clone, Copy)]
+pub enum CardSideArg {
+    Front,
+    Back,
+}
+
+impl From<CardSideArg> for CardSide {
+    fn from(value: CardSideArg) -> CardSide {
+        match value {
+            CardSideArg::Front => CardSide::Front,
+            CardSideArg::Back => CardSide::Back,
+        }
+    }
+}
+
+/// Corner cut positions accepted by `render image --corner-cut`.
+#[derive(ValueEnum, Debug, Clone, Copy)]
+pub enum CornerCutArg {
+    None,
+    UpperLeft,
+    UpperRight,
+}
+
+impl From<CornerCutArg> for CornerCut {
+    fn from(value: CornerCutArg) -> CornerCut {
+        match value {
+            CornerCutArg::None => CornerCut::None,
+            CornerCutArg::UpperLeft => CornerCut::UpperLeft,
+            CornerCutArg::UpperRight => CornerCut::UpperRight,
+        }
+    }
+}
+
+/// Sheet imposition options for A4 image rendering.
+#[derive(ValueEnum, Debug, Clone, Copy)]
+pub enum ImpositionArg {
+    CutStack,
+    Duplex,
+}
+
+impl From<ImpositionArg> for Imposition {
+    fn from(value: ImpositionArg) -> Imposition {
+        match value {
+            ImpositionArg::CutStack => Imposition::CutStack,
+            ImpositionArg::Duplex => Imposition::Duplex,
+        }
+    }
+}
+
+/// `--force` for commands that change a deck.
+#[derive(Args, Debug, Clone, Copy, Default)]
+pub struct ForceArg {
+    /// Change the deck even if it is locked readonly (recorded in its history).
+    #[arg(long)]
+    pub force: bool,
+}
+
+/// Column masking flags shared by commands that compare or export decks.
+#[derive(Args, Debug, Clone, Default)]
+pub struct MaskArgs {
+    /// Ignore specified column ranges (repeatable, e.g. --mask 73-80).
+    #[arg(long = "mask", value_parser = parse_column_range)]
+    pub mask: Vec<ColumnRange>,
+    /// TOML file of named mask sets (ranges, template fields, card types).
+    #[arg(long = "mask-file", value_name = "FILE")]
+    pub mask_file: Option<PathBuf>,
+    /// Mask set to use from --mask-file (defaults to its only or `default` set).
+    #[arg(long = "mask-set", value_name = "NAME", requires = "mask_file")]
+    pub mask_set: Option<String>,
+}
+
+impl MaskArgs {
+    /// Combine the selected mask set with any ad-hoc `--mask` ranges.
+    pub fn resolve(&self) -> Result<MaskSet> {
+        let mut set = match &self.mask_file {
+            Some(path) => MaskFile::load(path)?
+                .select(self.mask_set.as_deref())?
+                .clone(),
+            None => MaskSet::default(),
+        };
+        set.ranges.extend_from_slice(&self.mask);
+        Ok(set)
+    }
+}
+
+/// Caption languages accepted by `--lang`.
+#[derive(ValueEnum, Debug, Clone, Copy)]
+pub enum LocaleArg {
+    En,
+    De,
+    Fr,
+}
+
+impl From<LocaleArg> for Locale {
+    fn from(value: LocaleArg) -> Locale {
+        match value {
+            LocaleArg::En => Locale::English,
+            LocaleArg::De => Locale::German,
+            LocaleArg::Fr => Locale::French,
+        }
+    }
+}
+
+/// Banner and caption flags shared by the ASCII renderers.
+#[derive(Args, Debug, Clone)]
+pub struct CaptionArgs {
+    /// Language of the banner and listing captions.
+    #[arg(long, value_enum, default_value_t = LocaleArg::En)]
+    pub lang: LocaleArg,
+    /// TOML file of captions (banner, row_labels, card, seq, ...); missing
+    /// keys keep their English value.
+    #[arg(long = "captions", value_name = "FILE", conflicts_with = "lang")]
+    pub captions: Option<PathBuf>,
+    /// Replace the "IBM 5081" banner printed above each card.
+    #[arg(long, conflicts_with = "no_banner")]
+    pub banner: Option<String>,
+    /// Leave the banner line out.
+    #[arg(long = "no-banner")]
+    pub no_banner: bool,
+}
+
+impl CaptionArgs {
+    /// Build the caption table these flags select.
+    pub fn resolve(&self) -> Result<Captions> {
+        let mut captions = match &self.captions {
+            Some(path) => Captions::load(path)?,
+            None => Captions::for_locale(self.lang.into()),
+        };
+        if let Some(banner) = &self.banner {
+            captions.banner = banner.clone();
+        }
+        if self.no_banner {
+            captions.banner.clear();
         }
+        Ok(captions)
     }
 }
@@ -1,16 +1,31 @@
 //! Deck lifecycle commands (`punch deck ...`).
 
 use std::fmt;
-use std::path::PathBuf;
+use std::io::Write;
+use std::path::{Path, PathBuf};
 
 use anyhow::{Context, Result, anyhow};
+use chrono::{Local, NaiveDate};
 use clap::{Args, Subcommand, ValueEnum};
+use punchcard::formats::CardDump;
 use punchcard::{
-    CardRecord, CardType, ColumnRange, Deck, DeckHeader, EncodingKind, TemplateRegistry,
+    BoxLabel, CardDiff, CardMatch, CardRecord, CardType, ColumnRange, ColumnStats, CutUnit, Deck,
+    DeckHeader, DeckKey, DeckState, EncoderRegistry, EncodingKind, InvalidCharMode, LabelKind,
+    LanguageRegistry, LoadLimits, MachineRules, Query, ROW_NAMES, TargetMachine, TemplateRegistry,
+    audit_sidecar_path, detect_shifts, diff_cards, formats, holes_csv, holes_gcode, image::metrics,
+    infer_card_types, journal, pending, redact_card, render_labels_pdf, scrub_history, simh,
+    unshift_card,
 };
 
-use crate::cli::common::{CardTypeArg, EncodingArg};
-use crate::cli::utils::{load_deck, parse_column_range, parse_range_expression, write_output};
+use crate::cli::common::{
+    CardTypeArg, EncoderArg, EncodingArg, ForceArg, MaskArgs, OverflowArg, resolve_encoder,
+};
+use crate::cli::exit::{CheckFailed, CheckOutcome, EXIT_DIFFERENCES};
+use crate::cli::utils::{
+    allow_edit, load_deck, open_output, parse_column_range, parse_range_expression, save_deck,
+    split_input, write_output,
+};
+use crate::cli::verify;
 
 /// Supported `punch deck` subcommands.
 #[derive(Subcommand, Debug)]
@@ -19,7 +34,7 @@ pub enum DeckCommand {
     Init(DeckInitArgs),
     /// Import 80-column text into a deck file.
     Import(DeckImportArgs),
-    /// Export an existing deck into another format.
+    /// Export an existing deck into another format, blanking masked columns.
     Export(DeckExportArgs),
     /// Show deck metadata summary.
     Info(DeckInfoArgs),
@@ -27,6 +42,66 @@ pub enum DeckCommand {
     Merge(DeckMergeArgs),
     /// Slice a deck by card indices or ranges.
     Slice(DeckSliceArgs),
+    /// Preview the first N cards (listing, or a deck with -o).
+    Head(DeckPreviewArgs),
+    /// Preview the last N cards (listing, or a deck with -o).
+    Tail(DeckPreviewArgs),
+    /// Preview N randomly chosen cards in deck order (listing, or a deck with -o).
+    Sample(DeckSampleArgs),
+    /// Edit the deck as 80-column text in $EDITOR and re-import it.
+    Edit(DeckEditArgs),
+    /// Switch a deck to (or from) git-friendly canonical storage.
+    Canonical(DeckCanonicalArgs),
+    /// Print box end labels or drawer guide cards for a deck.
+    Boxlabel(DeckBoxLabelArgs),
+    /// Change the card type of every card matching a range, type or pattern.
+    RenumberTypes(DeckRenumberTypesArgs),
+    /// Show or edit the deck description and notes.
+    Meta(DeckMetaArgs),
+    /// Show or change the deck lifecycle state (draft, verified, frozen, submitted).
+    State(DeckStateArgs),
+    /// Run the deck language checks and, with --punches, hole-pattern validity.
+    Validate(DeckValidateArgs),
+    /// Append a card punched with the card count, date and a check hash, or check it.
+    SummaryCard(DeckSummaryCardArgs),
+    /// Show or set the target machine and how it treats invalid hole patterns.
+    Machine(DeckMachineArgs),
+    /// List the characters a deck uses and which punch charts can encode them.
+    Charset(DeckCharsetArgs),
+    /// Repunch every card under another punch chart, e.g. an 026 deck for an 029.
+    ConvertEncoding(DeckConvertEncodingArgs),
+    /// Chart the characters and punch rows found in a column or range.
+    Colstats(DeckColstatsArgs),
+    /// Fold linked patch cards into the cards they correct.
+    ApplyPatches(DeckApplyPatchesArgs),
+    /// Find blocks of cards punched a few columns off their template layout.
+    Shifts(DeckShiftsArgs),
+    /// Move the content of shifted cards back into place.
+    Unshift(DeckUnshiftArgs),
+    /// Overwrite a column range on selected cards before publishing a deck.
+    Redact(DeckRedactArgs),
+    /// Compare two decks card by card.
+    Diff(DeckDiffArgs),
+    /// Search card text, optionally within a column field or card types.
+    Grep(DeckGrepArgs),
+    /// Find columns punched with a multipunch combination such as 12-0.
+    GrepPunches(DeckGrepPunchesArgs),
+    /// Write a new random deck encryption key.
+    Keygen(DeckKeygenArgs),
+    /// Encrypt a deck at rest (ChaCha20-Poly1305).
+    Encrypt(DeckCryptArgs),
+    /// Decrypt an encrypted deck back to plain JSONL.
+    Decrypt(DeckCryptArgs),
+    /// Restore the deck to its state before the last change.
+    Undo(DeckJournalArgs),
+    /// Reapply the last change taken back with `deck undo`.
+    Redo(DeckJournalArgs),
+    /// Add the cards staged with `--defer-save` to the deck in one save.
+    Flush(DeckFlushArgs),
+    /// Mark the deck readonly so commands refuse to change it.
+    Lock(DeckLockArgs),
+    /// Clear the readonly mark set by `deck lock` or `deck import --readonly`.
+    Unlock(DeckLockArgs),
 }
 
 /// Arguments for `punch deck init`.
@@ -34,7 +109,7 @@ pub enum DeckCommand {
 pub struct DeckInitArgs {
     /// Output deck path (JSONL).
     pub path: PathBuf,
-    /// Logical language template (fortran/cobol/jcl/assembler)
+    /// Deck language (fortran/cobol/jcl/assembler); also picks the default template.
     #[arg(short = 'l', long)]
     pub language: Option<String>,
     /// Column template shortcut.
@@ -43,6 +118,13 @@ pub struct DeckInitArgs {
     /// Protected column ranges, e.g. --protect 73-80
     #[arg(long = "protect", value_parser = parse_column_range)]
     pub protect: Vec<ColumnRange>,
+    /// Store the deck in canonical form (stable keys, audit data in a sidecar).
+    #[arg(long)]
+    pub canonical: bool,
+    /// Keypunch chart the deck is punched on: a built-in name (IBM029, IBM026,
+    /// IBM026-FORTRAN, EBCDIC, ASCII) or a .toml/.csv mapping file.
+    #[arg(long, value_name = "NAME")]
+    pub encoder: Option<String>,
 }
 
 /// Arguments for `punch deck import`.
@@ -53,12 +135,72 @@ pub struct DeckImportArgs {
     /// Output deck file.
     #[arg(short = 'o', long = "output")]
     pub output: PathBuf,
-    /// Encoding to mark on imported cards.
+    /// Encoding to mark on imported cards (Hercules files carry their own).
     #[arg(long, default_value_t = EncodingArg::Hollerith, value_enum)]
     pub encoding: EncodingArg,
-    /// Card type for imported lines.
+    /// Card type for imported lines (with --infer-types, for lines no rule classifies).
     #[arg(long = "type", default_value_t = CardTypeArg::Code, value_enum)]
     pub card_type: CardTypeArg,
+    /// Classify each card as JCL, comment, data or code from its text and the
+    /// deck language instead of giving every card --type.
+    #[arg(long)]
+    pub infer_types: bool,
+    /// Deck language (fortran/cobol/jcl/assembler); also picks the default template.
+    #[arg(short = 'l', long)]
+    pub language: Option<String>,
+    /// Column template shortcut.
+    #[arg(short = 't', long)]
+    pub template: Option<String>,
+    /// Source format (text80, ibm1130, ibm1130-words, hex, bits, hercules,
+    /// hercules-ebcdic, simh-bin, simh-cbn)
+    #[arg(long, default_value_t = DeckImportFormat::Text80, value_enum)]
+    pub format: DeckImportFormat,
+    /// What to do with text80 lines wider than 80 columns.
+    #[arg(long, default_value_t = OverflowArg::Error, value_enum)]
+    pub overflow: OverflowArg,
+    /// Keypunch chart the deck is punched on: a built-in name (IBM029, IBM026,
+    /// IBM026-FORTRAN, EBCDIC, ASCII) or a .toml/.csv mapping file.
+    #[arg(long, value_name = "NAME")]
+    pub encoder: Option<String>,
+}
+
+/// Import format for deck content.
+#[derive(ValueEnum, Debug, Clone, Copy)]
+pub enum DeckImportFormat {
+    /// One 80-column line per card.
+    Text80,
+    /// IBM 1130 emulator column-binary deck (80 little-endian words per card).
+    Ibm1130,
+    /// One line of up to 54 hex words per card, packed as 1130 binary cards.
+    Ibm1130Words,
+    /// Hex column dump: three hex digits per column, one card per line.
+    Hex,
+    /// Bit-string dump: 960 `0`/`1` characters per card, one card per line.
+    Bits,
+    /// Hercules card reader ASCII file: one line per card.
+    Hercules,
+    /// Hercules EBCDIC card images (`.crd`): 80 bytes per card.
+    HerculesEbcdic,
+    /// simh card deck in `bin` mode: a 16-bit word per column.
+    SimhBin,
+    /// simh card deck in `cbn` mode (7090 column binary with parity).
+    SimhCbn,
+}
+
+impl fmt::Display for DeckImportFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DeckImportFormat::Text80 => write!(f, "text80"),
+            DeckImportFormat::Ibm1130 => write!(f, "ibm1130"),
+            DeckImportFormat::Ibm1130Words => write!(f, "ibm1130-words"),
+            DeckImportFormat::Hex => write!(f, "hex"),
+            DeckImportFormat::Bits => write!(f, "bits"),
+            DeckImportFormat::Hercules => write!(f, "hercules"),
+            DeckImportFormat::HerculesEbcdic => write!(f, "hercules-ebcdic"),
+            DeckImportFormat::SimhBin => write!(f, "simh-bin"),
+            DeckImportFormat::SimhCbn => write!(f, "simh-cbn"),
+        }
+    }
 }
 
 /// Arguments for `punch deck export`.
@@ -69,9 +211,15 @@ pub struct DeckExportArgs {
     /// Output file path (`-` for stdout).
     #[arg(short = 'o', long = "output")]
     pub output: PathBuf,
-    /// Export format (text80, deck)
+    /// Export format (text80, deck, keypunch, ibm1130, ibm1130-words, hex, bits,
+    /// hercules, hercules-ebcdic, simh-bin, simh-cbn, holes-csv, gcode)
     #[arg(long, default_value_t = DeckExportFormat::Text80, value_enum)]
     pub format: DeckExportFormat,
+    /// Length unit for holes-csv and gcode coordinates.
+    #[arg(long, default_value_t = CutUnitArg::Mm, value_enum)]
+    pub unit: CutUnitArg,
+    #[command(flatten)]
+    pub masks: MaskArgs,
 }
 
 /// Export format for deck content.
@@ -79,6 +227,45 @@ pub struct DeckExportArgs {
 pub enum DeckExportFormat {
     Text80,
     Deck,
+    /// Uppercase trimmed text for keypunch emulators that take pasted text
+    /// (no emulator-specific deck format; re-import with text80).
+    Keypunch,
+    /// IBM 1130 emulator column-binary deck (80 little-endian words per card).
+    Ibm1130,
+    /// One line of 54 hex data words per card (1130 binary card contents).
+    Ibm1130Words,
+    /// Hex column dump: three hex digits per column, one card per line.
+    Hex,
+    /// Bit-string dump: 960 `0`/`1` characters per card, one card per line.
+    Bits,
+    /// Hercules card reader ASCII file: one line per card.
+    Hercules,
+    /// Hercules EBCDIC card images (`.crd`): 80 bytes per card.
+    HerculesEbcdic,
+    /// simh card deck in `bin` mode: a 16-bit word per column.
+    SimhBin,
+    /// simh card deck in `cbn` mode (7090 column binary with parity).
+    SimhCbn,
+    /// Hole centre coordinates per card for laser/CNC cutting.
+    HolesCsv,
+    /// G-code tracing every hole outline, pausing between cards.
+    Gcode,
+}
+
+/// Coordinate unit for cutting exports.
+#[derive(ValueEnum, Debug, Clone, Copy)]
+pub enum CutUnitArg {
+    Mm,
+    In,
+}
+
+impl From<CutUnitArg> for CutUnit {
+    fn from(value: CutUnitArg) -> Self {
+        match value {
+            CutUnitArg::Mm => CutUnit::Millimetres,
+            CutUnitArg::In => CutUnit::Inches,
+        }
+    }
 }
 
 impl fmt::Display for DeckExportFormat {
@@ -86,6 +273,17 @@ impl fmt::Display for DeckExportFormat {
         match self {
             DeckExportFormat::Text80 => write!(f, "text80"),
             DeckExportFormat::Deck => write!(f, "deck"),
+            DeckExportFormat::Keypunch => write!(f, "keypunch"),
+            DeckExportFormat::Ibm1130 => write!(f, "ibm1130"),
+            DeckExportFormat::Ibm1130Words => write!(f, "ibm1130-words"),
+            DeckExportFormat::Hex => write!(f, "hex"),
+            DeckExportFormat::Bits => write!(f, "bits"),
+            DeckExportFormat::Hercules => write!(f, "hercules"),
+            DeckExportFormat::HerculesEbcdic => write!(f, "hercules-ebcdic"),
+            DeckExportFormat::SimhBin => write!(f, "simh-bin"),
+            DeckExportFormat::SimhCbn => write!(f, "simh-cbn"),
+            DeckExportFormat::HolesCsv => write!(f, "holes-csv"),
+            DeckExportFormat::Gcode => write!(f, "gcode"),
         }
     }
 }
@@ -121,6 +319,499 @@ pub struct DeckSliceArgs {
     pub output: PathBuf,
 }
 
+/// Arguments for `punch deck head` and `punch deck tail`.
+#[derive(Args, Debug)]
+pub struct DeckPreviewArgs {
+    /// Source deck file.
+    pub deck: PathBuf,
+    /// Number of cards to take.
+    #[arg(short = 'n', long = "count", default_value_t = 10)]
+    pub count: usize,
+    /// Write the selected cards as a new deck instead of printing a listing.
+    #[arg(short = 'o', long = "output")]
+    pub output: Option<PathBuf>,
+}
+
+/// Arguments for `punch deck sample`.
+#[derive(Args, Debug)]
+pub struct DeckSampleArgs {
+    #[command(flatten)]
+    pub preview: DeckPreviewArgs,
+    /// Random seed; the same seed picks the same cards.
+    #[arg(long, default_value_t = 0)]
+    pub seed: u64,
+}
+
+/// Arguments for `punch deck edit`.
+#[derive(Args, Debug)]
+pub struct DeckEditArgs {
+    /// Deck file to edit in place.
+    pub deck: PathBuf,
+    /// Editor command (defaults to $VISUAL, then $EDITOR, then `vi`).
+    #[arg(long)]
+    pub editor: Option<String>,
+    #[command(flatten)]
+    pub force: ForceArg,
+}
+
+/// Arguments for `punch deck canonical`.
+#[derive(Args, Debug)]
+pub struct DeckCanonicalArgs {
+    /// Deck file to convert in place.
+    pub deck: PathBuf,
+    /// Return to regular storage, folding the audit sidecar back into the deck.
+    #[arg(long)]
+    pub off: bool,
+    #[command(flatten)]
+    pub force: ForceArg,
+}
+
+/// Arguments for `punch deck meta`.
+#[derive(Args, Debug)]
+pub struct DeckMetaArgs {
+    /// Deck file to inspect or update.
+    pub deck: PathBuf,
+    /// Set the one-line description (empty string clears it).
+    #[arg(long)]
+    pub description: Option<String>,
+    /// Replace the notes (empty string clears them).
+    #[arg(long, conflicts_with = "add_note")]
+    pub notes: Option<String>,
+    /// Append a line to the notes.
+    #[arg(long = "add-note", value_name = "TEXT")]
+    pub add_note: Option<String>,
+    #[command(flatten)]
+    pub force: ForceArg,
+}
+
+/// Arguments for `punch deck state`.
+#[derive(Args, Debug)]
+pub struct DeckStateArgs {
+    /// Deck file to inspect or update.
+    pub deck: PathBuf,
+    /// Move the deck to this state.
+    #[arg(long, value_enum)]
+    pub to: Option<DeckStateArg>,
+    #[command(flatten)]
+    pub force: ForceArg,
+}
+
+/// Arguments for `punch deck validate`.
+#[derive(Args, Debug)]
+pub struct DeckValidateArgs {
+    /// Deck file to check.
+    pub deck: PathBuf,
+    /// Also flag stored hole patterns that punch no character of the encoding.
+    #[arg(long)]
+    pub punches: bool,
+    /// Load with the hardened parser and size limits meant for untrusted files.
+    #[arg(long)]
+    pub untrusted: bool,
+    /// Tolerate up to N language-check warnings before failing with exit code 1.
+    #[arg(long = "max-warnings", value_name = "N")]
+    pub max_warnings: Option<usize>,
+    /// Apply the language's safe corrections and write the result to --output;
+    /// the corrected copy is what gets validated.
+    #[arg(long, requires = "output")]
+    pub fix: bool,
+    /// Where --fix writes the corrected deck.
+    #[arg(short = 'o', long = "output", requires = "fix")]
+    pub output: Option<PathBuf>,
+}
+
+/// Arguments for `punch deck summary-card`.
+#[derive(Args, Debug)]
+pub struct DeckSummaryCardArgs {
+    /// Deck file to summarize.
+    pub deck: PathBuf,
+    /// Check the existing summary card instead of writing one; exits 1 when it
+    /// is missing or no longer matches the cards before it.
+    #[arg(long)]
+    pub check: bool,
+    /// Date punched on the card, as YYYY-MM-DD (defaults to today).
+    #[arg(long, value_name = "DATE", conflicts_with = "check")]
+    pub date: Option<NaiveDate>,
+    #[command(flatten)]
+    pub force: ForceArg,
+}
+
+/// Arguments for `punch deck machine`.
+#[derive(Args, Debug)]
+pub struct DeckMachineArgs {
+    /// Deck file to inspect or update.
+    pub deck: PathBuf,
+    /// Machine the deck will be fed to.
+    #[arg(long, value_enum, conflicts_with = "clear")]
+    pub target: Option<TargetMachineArg>,
+    /// Stop at the first invalid column, or read it as --substitute-char.
+    #[arg(long = "on-invalid", value_enum, conflicts_with = "clear")]
+    pub on_invalid: Option<InvalidCharArg>,
+    /// Character read in place of an invalid column in substitute mode.
+    #[arg(long = "substitute-char", value_name = "CHAR", default_value_t = ' ')]
+    pub substitute_char: char,
+    /// Remove the target machine from the deck.
+    #[arg(long)]
+    pub clear: bool,
+    #[command(flatten)]
+    pub force: ForceArg,
+}
+
+/// Arguments for `punch deck convert-encoding`.
+#[derive(Args, Debug)]
+pub struct DeckConvertEncodingArgs {
+    /// Deck file to convert.
+    pub deck: PathBuf,
+    /// Chart the deck was punched with (defaults to the deck's encoder).
+    #[arg(long, value_name = "NAME")]
+    pub from: Option<String>,
+    /// Chart to repunch the deck with: a built-in name or a .toml/.csv mapping file.
+    #[arg(long, value_name = "NAME")]
+    pub to: String,
+    /// Write the converted deck here instead of updating DECK in place.
+    #[arg(short = 'o', long = "output")]
+    pub output: Option<PathBuf>,
+    /// Stop when a character has no target punch code, or punch --substitute-char
+    /// (defaults to the deck's machine rules, else halt).
+    #[arg(long = "on-missing", value_enum)]
+    pub on_missing: Option<InvalidCharArg>,
+    /// Character punched in place of one with no target code in substitute mode.
+    #[arg(long = "substitute-char", value_name = "CHAR", default_value_t = ' ')]
+    pub substitute_char: char,
+    #[command(flatten)]
+    pub force: ForceArg,
+}
+
+/// Arguments for `punch deck charset`.
+#[derive(Args, Debug)]
+pub struct DeckCharsetArgs {
+    /// Deck file to inspect.
+    pub deck: PathBuf,
+    /// List the cards that would fail under this chart.
+    #[arg(long, value_enum)]
+    pub encoder: Option<EncoderArg>,
+}
+
+/// Arguments for `punch deck colstats`.
+#[derive(Args, Debug)]
+pub struct DeckColstatsArgs {
+    /// Deck file to inspect.
+    pub deck: PathBuf,
+    /// Column or range to tally, e.g. 7 or 73-80.
+    #[arg(long = "col", value_parser = parse_column_range)]
+    pub col: ColumnRange,
+    /// Punch chart used to derive the hole rows (defaults to the deck's encoder).
+    #[arg(long, value_enum)]
+    pub encoder: Option<EncoderArg>,
+    /// Print the statistics as JSON instead of bar charts.
+    #[arg(long)]
+    pub json: bool,
+}
+
+/// Arguments for `punch deck apply-patches`.
+#[derive(Args, Debug)]
+pub struct DeckApplyPatchesArgs {
+    /// Deck file to update.
+    pub deck: PathBuf,
+    /// Write the patched deck here instead of updating DECK in place.
+    #[arg(short = 'o', long = "output")]
+    pub output: Option<PathBuf>,
+    #[command(flatten)]
+    pub force: ForceArg,
+}
+
+/// Arguments for `punch deck shifts`.
+#[derive(Args, Debug)]
+pub struct DeckShiftsArgs {
+    /// Deck file to inspect.
+    pub deck: PathBuf,
+    /// Template whose fields define the column profile (defaults to the
+    /// deck's template, or the whole card).
+    #[arg(short = 't', long)]
+    pub template: Option<String>,
+    /// Largest shift to try, in columns.
+    #[arg(long = "max-shift", default_value_t = 3)]
+    pub max_shift: usize,
+    /// Report only runs of at least N consecutive shifted cards.
+    #[arg(long = "min-run", value_name = "N", default_value_t = 2)]
+    pub min_run: usize,
+    /// Punch chart used to read column-binary cards (defaults to the deck's encoder).
+    #[arg(long, value_enum)]
+    pub encoder: Option<EncoderArg>,
+}
+
+/// Arguments for `punch deck unshift`.
+#[derive(Args, Debug)]
+pub struct DeckUnshiftArgs {
+    /// Deck file to correct.
+    pub deck: PathBuf,
+    /// Cards to correct, e.g. 12..30
+    #[arg(short = 'r', long = "range")]
+    pub range: String,
+    /// Columns the content sits right of its place; negative moves it right.
+    #[arg(long, allow_negative_numbers = true)]
+    pub by: isize,
+    /// Only shift this column range, e.g. 1-72 to keep sequence numbers.
+    #[arg(long, value_parser = parse_column_range, default_value = "1-80")]
+    pub cols: ColumnRange,
+    /// Write the corrected deck here instead of updating DECK in place.
+    #[arg(short = 'o', long = "output")]
+    pub output: Option<PathBuf>,
+    #[command(flatten)]
+    pub force: ForceArg,
+}
+
+/// Arguments for `punch deck redact`.
+#[derive(Args, Debug)]
+pub struct DeckRedactArgs {
+    /// Deck file to redact.
+    pub deck: PathBuf,
+    /// Columns to overwrite, e.g. 21-40.
+    #[arg(long, value_parser = parse_column_range)]
+    pub cols: ColumnRange,
+    /// Character written into every redacted column.
+    #[arg(long = "with", default_value_t = 'X')]
+    pub with: char,
+    /// Cards to redact, e.g. 12..30 (defaults to every card).
+    #[arg(short = 'r', long = "range")]
+    pub range: Option<String>,
+    /// Write the redacted deck here instead of updating DECK in place.
+    #[arg(short = 'o', long = "output")]
+    pub output: Option<PathBuf>,
+    #[command(flatten)]
+    pub force: ForceArg,
+}
+
+/// Arguments for `punch deck lock` and `punch deck unlock`.
+#[derive(Args, Debug)]
+pub struct DeckLockArgs {
+    /// Deck file to lock or unlock.
+    pub deck: PathBuf,
+}
+
+/// Arguments for `punch deck undo` and `punch deck redo`.
+#[derive(Args, Debug)]
+pub struct DeckJournalArgs {
+    /// Deck file to step back or forward.
+    pub deck: PathBuf,
+    /// Number of changes to step over.
+    #[arg(short = 'n', long, default_value_t = 1)]
+    pub steps: usize,
+}
+
+/// Arguments for `punch deck flush`.
+#[derive(Args, Debug)]
+pub struct DeckFlushArgs {
+    /// Deck file whose staged cards to add.
+    pub deck: PathBuf,
+    /// Drop the staged cards instead of adding them.
+    #[arg(long)]
+    pub discard: bool,
+    #[command(flatten)]
+    pub force: ForceArg,
+}
+
+/// Output formats for `punch deck diff`.
+#[derive(ValueEnum, Debug, Clone, Copy)]
+pub enum DiffFormatArg {
+    /// Human-readable report with `^` under changed columns.
+    Text,
+    /// JSON array of added/removed/changed entries (zero-based indexes).
+    Json,
+}
+
+/// Arguments for `punch deck diff`.
+#[derive(Args, Debug)]
+pub struct DeckDiffArgs {
+    /// Original deck.
+    pub left: PathBuf,
+    /// Deck to compare against it.
+    pub right: PathBuf,
+    /// Report format.
+    #[arg(long, default_value_t = DiffFormatArg::Text, value_enum)]
+    pub format: DiffFormatArg,
+    #[command(flatten)]
+    pub masks: MaskArgs,
+}
+
+/// Arguments for `punch deck grep`.
+#[derive(Args, Debug)]
+pub struct DeckGrepArgs {
+    /// Deck file to search.
+    pub deck: PathBuf,
+    /// Text to look for (a regular expression with --regex).
+    pub pattern: String,
+    /// Treat PATTERN as a regular expression.
+    #[arg(short = 'E', long)]
+    pub regex: bool,
+    /// Ignore letter case.
+    #[arg(short = 'i', long = "ignore-case")]
+    pub ignore_case: bool,
+    /// Only search these columns, e.g. 7-72.
+    #[arg(long, value_parser = parse_column_range)]
+    pub cols: Option<ColumnRange>,
+    /// Only search cards of these types, e.g. code,comment.
+    #[arg(long = "type", value_enum, value_delimiter = ',')]
+    pub card_types: Vec<CardTypeArg>,
+    /// Print only the matching card numbers, as a range list for `-r`.
+    #[arg(long)]
+    pub indexes: bool,
+}
+
+/// Arguments for `punch deck grep-punches`.
+#[derive(Args, Debug)]
+pub struct DeckGrepPunchesArgs {
+    /// Deck file to search.
+    pub deck: PathBuf,
+    /// Rows to look for in chart notation, e.g. 12-0 or 12-11.
+    #[arg(short = 'p', long)]
+    pub pattern: String,
+    /// Only match columns punched with exactly these rows and no others.
+    #[arg(long)]
+    pub exact: bool,
+    /// Only search these columns, e.g. 1-72.
+    #[arg(long, value_parser = parse_column_range)]
+    pub cols: Option<ColumnRange>,
+    /// Only search cards of these types, e.g. data,jcl.
+    #[arg(long = "type", value_enum, value_delimiter = ',')]
+    pub card_types: Vec<CardTypeArg>,
+    /// Print only the matching card numbers, as a range list for `-r`.
+    #[arg(long)]
+    pub indexes: bool,
+}
+
+/// Arguments for `punch deck keygen`.
+#[derive(Args, Debug)]
+pub struct DeckKeygenArgs {
+    /// Key file to create (64 hex digits; never overwritten).
+    #[arg(short = 'o', long = "output")]
+    pub output: PathBuf,
+}
+
+/// Arguments for `punch deck encrypt` and `punch deck decrypt`.
+#[derive(Args, Debug)]
+pub struct DeckCryptArgs {
+    /// Deck file to convert.
+    pub deck: PathBuf,
+    /// Key file; defaults to PUNCH_DECK_KEY or the file named by PUNCH_DECK_KEYFILE.
+    #[arg(long, value_name = "FILE")]
+    pub keyfile: Option<PathBuf>,
+    /// Write the result here instead of updating DECK in place.
+    #[arg(short = 'o', long = "output")]
+    pub output: Option<PathBuf>,
+    #[command(flatten)]
+    pub force: ForceArg,
+}
+
+/// Machines accepted by `deck machine --target`.
+#[derive(ValueEnum, Debug, Clone, Copy)]
+pub enum TargetMachineArg {
+    #[value(name = "1401")]
+    Ibm1401,
+    #[value(name = "1620")]
+    Ibm1620,
+}
+
+impl From<TargetMachineArg> for TargetMachine {
+    fn from(value: TargetMachineArg) -> TargetMachine {
+        match value {
+            TargetMachineArg::Ibm1401 => TargetMachine::Ibm1401,
+            TargetMachineArg::Ibm1620 => TargetMachine::Ibm1620,
+        }
+    }
+}
+
+/// Invalid-character behaviours accepted by `deck machine --on-invalid` and
+/// `deck convert-encoding --on-missing`.
+#[derive(ValueEnum, Debug, Clone, Copy)]
+pub enum InvalidCharArg {
+    Halt,
+    Substitute,
+}
+
+/// Lifecycle states accepted by `deck state --to`.
+#[derive(ValueEnum, Debug, Clone, Copy)]
+pub enum DeckStateArg {
+    Draft,
+    Verified,
+    Frozen,
+    Submitted,
+}
+
+impl From<DeckStateArg> for DeckState {
+    fn from(value: DeckStateArg) -> DeckState {
+        match value {
+            DeckStateArg::Draft => DeckState::Draft,
+            DeckStateArg::Verified => DeckState::Verified,
+            DeckStateArg::Frozen => DeckState::Frozen,
+            DeckStateArg::Submitted => DeckState::Submitted,
+        }
+    }
+}
+
+/// Arguments for `punch deck boxlabel`.
+#[derive(Args, Debug)]
+pub struct DeckBoxLabelArgs {
+    /// Deck file to label.
+    pub deck: PathBuf,
+    /// Output path (.pdf for printable labels, anything else or - for text).
+    #[arg(short = 'o', long = "output", default_value = "-")]
+    pub output: PathBuf,
+    /// Label title (defaults to the deck file name).
+    #[arg(long)]
+    pub title: Option<String>,
+    /// Label stock to print on.
+    #[arg(long, default_value_t = LabelKindArg::Box, value_enum)]
+    pub kind: LabelKindArg,
+    /// Only print the label for box N (1-based).
+    #[arg(long = "box")]
+    pub box_number: Option<usize>,
+}
+
+/// Arguments for `punch deck renumber-types`.
+#[derive(Args, Debug)]
+pub struct DeckRenumberTypesArgs {
+    /// Deck file to update in place.
+    pub deck: PathBuf,
+    /// New card type for every matching card.
+    #[arg(long = "to", value_enum)]
+    pub to: CardTypeArg,
+    /// Only cards in this range expression, e.g. 1..10,25,30..$
+    #[arg(short = 'r', long = "range")]
+    pub range: Option<String>,
+    /// Only cards currently of this type.
+    #[arg(long = "where", value_enum)]
+    pub where_type: Option<CardTypeArg>,
+    /// Only cards whose text starts with this prefix, e.g. //*
+    #[arg(long)]
+    pub prefix: Option<String>,
+    /// Only cards whose text contains this substring.
+    #[arg(long)]
+    pub contains: Option<String>,
+    /// List the matching cards without saving.
+    #[arg(long)]
+    pub dry_run: bool,
+    #[command(flatten)]
+    pub force: ForceArg,
+}
+
+/// Label stock for `punch deck boxlabel`.
+#[derive(ValueEnum, Debug, Clone, Copy)]
+pub enum LabelKindArg {
+    /// End-of-box label.
+    Box,
+    /// Card-sized drawer guide card.
+    Drawer,
+}
+
+impl From<LabelKindArg> for LabelKind {
+    fn from(value: LabelKindArg) -> Self {
+        match value {
+            LabelKindArg::Box => LabelKind::Box,
+            LabelKindArg::Drawer => LabelKind::Drawer,
+        }
+    }
+}
+
 /// Execute a deck command.
 pub fn handle(command: DeckCommand) -> Result<()> {
     match command {
@@ -130,6 +821,36 @@ pub fn handle(command: DeckCommand) -> Result<()> {
         DeckCommand::Info(args) => info(args),
         DeckCommand::Merge(args) => merge(args),
         DeckCommand::Slice(args) => slice(args),
+        DeckCommand::Head(args) => preview(args, Preview::Head),
+        DeckCommand::Tail(args) => preview(args, Preview::Tail),
+        DeckCommand::Sample(args) => preview(args.preview, Preview::Sample(args.seed)),
+        DeckCommand::Edit(args) => edit(args),
+        DeckCommand::Canonical(args) => canonical(args),
+        DeckCommand::Boxlabel(args) => boxlabel(args),
+        DeckCommand::RenumberTypes(args) => renumber_types(args),
+        DeckCommand::Meta(args) => meta(args),
+        DeckCommand::State(args) => state(args),
+        DeckCommand::Validate(args) => validate(args),
+        DeckCommand::SummaryCard(args) => summary_card(args),
+        DeckCommand::Machine(args) => machine(args),
+        DeckCommand::Charset(args) => charset(args),
+        DeckCommand::ConvertEncoding(args) => convert_encoding(args),
+        DeckCommand::Colstats(args) => colstats(args),
+        DeckCommand::ApplyPatches(args) => apply_patches(args),
+        DeckCommand::Shifts(args) => shifts(args),
+        DeckCommand::Unshift(args) => unshift(args),
+        DeckCommand::Redact(args) => redact(args),
+        DeckCommand::Diff(args) => diff(args),
+        DeckCommand::Grep(args) => grep(args),
+        DeckCommand::GrepPunches(args) => grep_punches(args),
+        DeckCommand::Keygen(args) => keygen(args),
+        DeckCommand::Encrypt(args) => encrypt(args),
+        DeckCommand::Decrypt(args) => decrypt(args),
+        DeckCommand::Undo(args) => step_journal(args, Step::Undo),
+        DeckCommand::Redo(args) => step_journal(args, Step::Redo),
+        DeckCommand::Flush(args) => flush(args),
+        DeckCommand::Lock(args) => set_readonly(args, true),
+        DeckCommand::Unlock(args) => set_readonly(args, false),
     }
 }
 
@@ -137,64 +858,213 @@ fn init(args: DeckInitArgs) -> Result<()> {
     if let Some(tpl) = &args.template {
         TemplateRegistry::get(tpl).with_context(|| format!("template '{}' not found", tpl))?;
     }
+    let language = args
+        .language
+        .as_deref()
+        .map(LanguageRegistry::get)
+        .transpose()?;
+    let language_name = language.map(|l| l.name.to_string());
+    let template = args
+        .template
+        .clone()
+        .or_else(|| language.map(|l| l.template.to_string()));
     let header = DeckHeader::new(
-        args.language.clone(),
-        args.template.clone(),
+        language_name.clone(),
+        template.clone(),
         args.protect.clone(),
     );
     let mut deck = Deck::new(header);
+    deck.header.canonical = args.canonical;
+    deck.header.encoder = declared_encoder(args.encoder.as_deref(), &args.path)?;
     deck.log_action("deck init");
-    deck.save(&args.path)?;
+    save_deck(&mut deck, &args.path)?;
     println!(
         "Created deck {} (language: {:?}, template: {:?})",
         args.path.display(),
-        args.language,
-        args.template
+        language_name,
+        template
     );
     Ok(())
 }
 
+/// Check an `--encoder` name against the registry; built-in charts are stored
+/// under their canonical name, mapping files relative to the directory of
+/// the deck at `deck_path` (absolute when outside it), where
+/// [`Deck::encoder`] looks for them.
+fn declared_encoder(name: Option<&str>, deck_path: &Path) -> Result<Option<String>> {
+    let Some(name) = name else {
+        return Ok(None);
+    };
+    let encoder = EncoderRegistry::get(name)?;
+    if !EncoderRegistry::is_mapping_file(name) {
+        return Ok(Some(encoder.name().to_string()));
+    }
+    let file = std::path::absolute(name)
+        .with_context(|| format!("failed to resolve encoder file {}", name))?;
+    let deck_dir = std::path::absolute(deck_path)
+        .with_context(|| format!("failed to resolve {}", deck_path.display()))?;
+    let stored = match deck_dir.parent().map(|dir| file.strip_prefix(dir)) {
+        Some(Ok(relative)) => relative,
+        _ => file.as_path(),
+    };
+    Ok(Some(stored.to_string_lossy().into_owned()))
+}
+
 fn import(args: DeckImportArgs) -> Result<()> {
-    let contents = std::fs::read_to_string(&args.source)
+    let bytes = std::fs::read(&args.source)
         .with_context(|| format!("failed to read {}", args.source.display()))?;
-    let mut deck = Deck::new(DeckHeader::new(None, None, Vec::new()));
+    let contents = match args.format {
+        DeckImportFormat::Ibm1130
+        | DeckImportFormat::HerculesEbcdic
+        | DeckImportFormat::SimhBin
+        | DeckImportFormat::SimhCbn => "",
+        _ => std::str::from_utf8(&bytes)
+            .with_context(|| format!("{} is not UTF-8 text", args.source.display()))?,
+    };
     let encoding: EncodingKind = args.encoding.into();
     let card_type: CardType = args.card_type.into();
-    for (idx, line) in contents.lines().enumerate() {
-        let record =
-            CardRecord::from_text(line, encoding, card_type.clone()).with_context(|| {
-                format!(
-                    "line {} in {} exceeds 80 columns",
-                    idx + 1,
-                    args.source.display()
-                )
-            })?;
-        deck.append_card(record)?;
+    let records = match args.format {
+        DeckImportFormat::Text80 if contents.is_empty() => Ok(Vec::new()),
+        DeckImportFormat::Text80 => split_input(contents, args.overflow).and_then(|lines| {
+            lines
+                .into_iter()
+                .map(|line| CardRecord::from_text(line, encoding, card_type.clone()))
+                .collect::<Result<Vec<_>>>()
+        }),
+        DeckImportFormat::Ibm1130 => formats::from_1130_binary(&bytes, card_type),
+        DeckImportFormat::Ibm1130Words => formats::from_1130_word_lines(contents, card_type),
+        DeckImportFormat::Hex => formats::from_card_dump(contents, CardDump::Hex, card_type),
+        DeckImportFormat::Bits => formats::from_card_dump(contents, CardDump::Bits, card_type),
+        DeckImportFormat::Hercules => formats::from_hercules_ascii(contents, card_type),
+        DeckImportFormat::HerculesEbcdic => formats::from_hercules_ebcdic(&bytes, card_type),
+        DeckImportFormat::SimhBin => simh::from_simh(&bytes, simh::SimhFormat::Binary, card_type),
+        DeckImportFormat::SimhCbn => simh::from_simh(&bytes, simh::SimhFormat::Cbn, card_type),
     }
-    deck.log_action(format!(
-        "import from {} as {:?}",
-        args.source.display(),
-        encoding
-    ));
-    deck.save(&args.output)?;
+    .with_context(|| format!("failed to import {}", args.source.display()))?;
+    let keep_encoding = matches!(
+        args.format,
+        DeckImportFormat::Hercules | DeckImportFormat::HerculesEbcdic
+    );
+    let records = records.into_iter().map(|mut record| {
+        if !keep_encoding {
+            record.encoding = encoding;
+        }
+        record
+    });
+    if let Some(tpl) = &args.template {
+        TemplateRegistry::get(tpl).with_context(|| format!("template '{}' not found", tpl))?;
+    }
+    let language = args
+        .language
+        .as_deref()
+        .map(LanguageRegistry::get)
+        .transpose()?;
+    let template = args
+        .template
+        .clone()
+        .or_else(|| language.map(|l| l.template.to_string()));
+    let header = DeckHeader::new(language.map(|l| l.name.to_string()), template, Vec::new());
+    let mut deck = Deck::from_records(header, records)?;
+    deck.path = Some(args.output.clone());
+    deck.header.encoder = declared_encoder(args.encoder.as_deref(), &args.output)?;
+    let mut action = format!("import from {} as {:?}", args.source.display(), encoding);
+    if args.infer_types {
+        // A template named after a language implies it.
+        let language = language.or_else(|| {
+            let template = deck.header.template.as_deref()?;
+            LanguageRegistry::list()
+                .into_iter()
+                .find(|l| l.template.eq_ignore_ascii_case(template))
+        });
+        let texts = deck.as_text()?;
+        let types = infer_card_types(&texts, language, args.card_type.into());
+        for (card, card_type) in deck.cards.iter_mut().zip(types) {
+            card.card_type = card_type;
+        }
+        action.push_str(" with inferred card types");
+    }
+    deck.log_action(action);
+    save_deck(&mut deck, &args.output)?;
     println!(
         "Imported {} cards into {}",
         deck.cards.len(),
         args.output.display()
     );
+    if args.infer_types {
+        let mut counts: Vec<(&CardType, usize)> = Vec::new();
+        for card in &deck.cards {
+            match counts.iter_mut().find(|(kind, _)| **kind == card.card_type) {
+                Some((_, count)) => *count += 1,
+                None => counts.push((&card.card_type, 1)),
+            }
+        }
+        for (card_type, count) in counts {
+            println!("  {:<8} {}", format!("{}:", card_type), count);
+        }
+    }
     Ok(())
 }
 
 fn export(args: DeckExportArgs) -> Result<()> {
-    let deck = load_deck(args.deck.as_path())?;
+    let mut deck = load_deck(args.deck.as_path())?;
+    let masks = args.masks.resolve()?;
+    if !masks.is_empty() {
+        masks.blank(&mut deck)?;
+    }
+    let encoder = deck.encoder()?;
     match args.format {
         DeckExportFormat::Text80 => {
-            let text = deck.as_text().join("\n");
+            let text = deck.as_text()?.join("\n");
             write_output(&args.output, &text)?;
         }
         DeckExportFormat::Deck => {
             let mut clone = deck.clone();
-            clone.save(&args.output)?;
+            save_deck(&mut clone, &args.output)?;
+        }
+        DeckExportFormat::Keypunch => {
+            write_output(&args.output, &formats::to_keypunch_text(&deck, encoder)?)?;
+        }
+        DeckExportFormat::Ibm1130 => {
+            let mut out = open_output(&args.output)?;
+            out.write_all(&formats::to_1130_binary(&deck, encoder)?)?;
+            out.flush()?;
+        }
+        DeckExportFormat::Ibm1130Words => {
+            write_output(&args.output, &formats::to_1130_word_lines(&deck, encoder)?)?;
+        }
+        DeckExportFormat::Hex => {
+            let dump = formats::to_card_dump(&deck, encoder, CardDump::Hex)?;
+            write_output(&args.output, &dump)?;
+        }
+        DeckExportFormat::Bits => {
+            let dump = formats::to_card_dump(&deck, encoder, CardDump::Bits)?;
+            write_output(&args.output, &dump)?;
+        }
+        DeckExportFormat::Hercules => {
+            write_output(&args.output, &formats::to_hercules_ascii(&deck, encoder)?)?;
+        }
+        DeckExportFormat::HerculesEbcdic => {
+            let mut out = open_output(&args.output)?;
+            out.write_all(&formats::to_hercules_ebcdic(&deck, encoder)?)?;
+            out.flush()?;
+        }
+        DeckExportFormat::SimhBin | DeckExportFormat::SimhCbn => {
+            let format = match args.format {
+                DeckExportFormat::SimhCbn => simh::SimhFormat::Cbn,
+                _ => simh::SimhFormat::Binary,
+            };
+            let mut out = open_output(&args.output)?;
+            out.write_all(&simh::to_simh(&deck, encoder, format)?)?;
+            out.flush()?;
+        }
+        DeckExportFormat::HolesCsv => {
+            write_output(&args.output, &holes_csv(&deck, encoder, args.unit.into())?)?;
+        }
+        DeckExportFormat::Gcode => {
+            write_output(
+                &args.output,
+                &holes_gcode(&deck, encoder, args.unit.into())?,
+            )?;
         }
     }
     println!(
@@ -210,16 +1080,57 @@ fn info(args: DeckInfoArgs) -> Result<()> {
     let deck = load_deck(args.deck.as_path())?;
     println!("Deck: {}", args.deck.display());
     println!("Cards: {}", deck.cards.len());
+    let staged: usize = pending::read_pending(&args.deck)?
+        .iter()
+        .map(|batch| batch.cards.len())
+        .sum();
+    if staged > 0 {
+        println!("Staged: {} card(s) waiting for `punch deck flush`", staged);
+    }
     println!(
         "Language: {}",
-        deck.header
-            .language
-            .as_deref()
-            .unwrap_or_else(|| "(unspecified)")
+        deck.header.language.as_deref().unwrap_or("(unspecified)")
     );
+    if let Some(name) = &deck.header.language {
+        match LanguageRegistry::get(name) {
+            Ok(language) => {
+                let checks: Vec<String> = language.checks.iter().map(|c| c.to_string()).collect();
+                let findings = language.check_deck(&deck);
+                println!(
+                    "Default checks: {} ({} finding(s))",
+                    checks.join(", "),
+                    findings.len()
+                );
+                for finding in findings.iter().take(10) {
+                    println!(
+                        "  card {}: {}: {}",
+                        finding.card + 1,
+                        finding.check,
+                        finding.message
+                    );
+                }
+                if findings.len() > 10 {
+                    println!("  ... {} more", findings.len() - 10);
+                }
+            }
+            Err(_) => println!("Default checks: none (language not recognised)"),
+        }
+    }
     if let Some(template) = &deck.header.template {
         println!("Template: {}", template);
     }
+    if let Some(encoder) = &deck.header.encoder {
+        println!("Encoder: {}", encoder);
+    }
+    if let Some(description) = &deck.header.description {
+        println!("Description: {}", description);
+    }
+    if let Some(notes) = &deck.header.notes {
+        println!("Notes:");
+        for line in notes.lines() {
+            println!("  {}", line);
+        }
+    }
     if !deck.header.protected_cols.is_empty() {
         let ranges: Vec<String> = deck
             .header
@@ -229,7 +1140,66 @@ fn info(args: DeckInfoArgs) -> Result<()> {
             .collect();
         println!("Protected cols: {}", ranges.join(", "));
     }
+    if let Some(rules) = &deck.header.machine {
+        println!("Machine: {}", rules);
+    }
+    if deck.header.readonly {
+        println!("Readonly: yes (`punch deck unlock` to allow changes)");
+    }
+    match deck.header.state_since {
+        Some(since) => println!(
+            "State: {} (since {})",
+            deck.header.state,
+            since.format("%Y-%m-%d %H:%M")
+        ),
+        None => println!("State: {}", deck.header.state),
+    }
+    if let Some(verification) = &deck.header.verification {
+        let names: Vec<&str> = verification
+            .baselines
+            .iter()
+            .map(|b| b.name.as_str())
+            .collect();
+        println!(
+            "Verification: {} baseline(s){}",
+            names.len(),
+            if names.is_empty() {
+                String::new()
+            } else {
+                format!(" ({})", names.join(", "))
+            }
+        );
+        if let Some(pass) = verification.latest_pass() {
+            println!("  Last pass: {}", verify::pass_summary(pass));
+        }
+    }
+    let chains = deck.patch_chains();
+    if !chains.is_empty() {
+        println!("Patch chains:");
+        for chain in &chains {
+            let by: Vec<String> = chain
+                .patches
+                .iter()
+                .map(|p| format!("card {}", p + 1))
+                .collect();
+            match chain.card {
+                Some(card) => println!(
+                    "  card {} ({}) <- {}",
+                    card + 1,
+                    chain.target,
+                    by.join(" <- ")
+                ),
+                None => println!("  {} (missing) <- {}", chain.target, by.join(" <- ")),
+            }
+        }
+    }
     println!("History entries: {}", deck.header.history.len());
+    if deck.header.canonical {
+        println!(
+            "Storage: canonical (audit in {})",
+            audit_sidecar_path(&args.deck).display()
+        );
+    }
     Ok(())
 }
 
@@ -254,7 +1224,7 @@ fn merge(args: DeckMergeArgs) -> Result<()> {
         args.inputs.len(),
         args.output.display()
     ));
-    result.save(&args.output)?;
+    save_deck(&mut result, &args.output)?;
     println!(
         "Merged {} cards into {}",
         result.cards.len(),
@@ -268,7 +1238,7 @@ fn slice(args: DeckSliceArgs) -> Result<()> {
     let indexes = parse_range_expression(&args.range, source.cards.len())?;
     let mut sliced = source.slice_indices(&indexes)?;
     sliced.log_action(format!("slice {} -> {}", args.range, args.output.display()));
-    sliced.save(&args.output)?;
+    save_deck(&mut sliced, &args.output)?;
     println!(
         "Sliced {} cards into {}",
         sliced.cards.len(),
@@ -276,3 +1246,1168 @@ fn slice(args: DeckSliceArgs) -> Result<()> {
     );
     Ok(())
 }
+
+/// Which cards `deck head`, `tail` and `sample` select.
+enum Preview {
+    Head,
+    Tail,
+    Sample(u64),
+}
+
+fn preview(args: DeckPreviewArgs, kind: Preview) -> Result<()> {
+    let source = load_deck(args.deck.as_path())?;
+    let len = source.cards.len();
+    let count = args.count.min(len);
+    let (indexes, what) = match kind {
+        Preview::Head => ((0..count).collect::<Vec<_>>(), "head".to_string()),
+        Preview::Tail => ((len - count..len).collect(), "tail".to_string()),
+        Preview::Sample(seed) => (
+            source.sample_indices(count, seed),
+            format!("sample seed={}", seed),
+        ),
+    };
+    let Some(output) = args.output else {
+        let text = source.as_text()?;
+        for idx in indexes {
+            println!("{:>6} {}", idx + 1, text[idx].trim_end());
+        }
+        return Ok(());
+    };
+    let mut selected = source.slice_indices(&indexes)?;
+    selected.log_action(format!("{} -n {} -> {}", what, count, output.display()));
+    save_deck(&mut selected, &output)?;
+    println!(
+        "Wrote {} of {} cards into {}",
+        selected.cards.len(),
+        len,
+        output.display()
+    );
+    Ok(())
+}
+
+fn canonical(args: DeckCanonicalArgs) -> Result<()> {
+    let mut deck = load_deck(args.deck.as_path())?;
+    allow_edit(&mut deck, args.force, "deck canonical")?;
+    let enable = !args.off;
+    if deck.header.canonical == enable {
+        println!(
+            "{} is already in {} form",
+            args.deck.display(),
+            if enable { "canonical" } else { "regular" }
+        );
+        return Ok(());
+    }
+    deck.header.canonical = enable;
+    deck.log_action(if enable {
+        "deck canonical on"
+    } else {
+        "deck canonical off"
+    });
+    save_deck(&mut deck, &args.deck)?;
+    let sidecar = audit_sidecar_path(&args.deck);
+    if enable {
+        println!(
+            "{} now uses canonical storage; audit data lives in {}",
+            args.deck.display(),
+            sidecar.display()
+        );
+    } else {
+        if sidecar.exists() {
+            std::fs::remove_file(&sidecar)
+                .with_context(|| format!("failed to remove {}", sidecar.display()))?;
+        }
+        println!("{} now uses regular storage", args.deck.display());
+    }
+    Ok(())
+}
+
+fn boxlabel(args: DeckBoxLabelArgs) -> Result<()> {
+    let deck = load_deck(args.deck.as_path())?;
+    let title = args.title.clone().unwrap_or_else(|| {
+        args.deck
+            .file_stem()
+            .map(|s| s.to_string_lossy().into_owned())
+            .unwrap_or_else(|| "DECK".to_string())
+    });
+    let subtitle = match (&deck.header.language, &deck.header.template) {
+        (Some(lang), Some(template)) => Some(format!("{} / {}", lang, template)),
+        (Some(value), None) | (None, Some(value)) => Some(value.clone()),
+        (None, None) => None,
+    };
+    let hash = deck.hash()?;
+    let date = deck
+        .header
+        .created_at
+        .map(|created| created.format("%Y-%m-%d").to_string())
+        .unwrap_or_else(|| "undated".to_string());
+    let total = deck.cards.len();
+    let box_count = metrics::boxes_needed(total);
+    let boxes: Vec<usize> = match args.box_number {
+        Some(n) if n == 0 || n > box_count => {
+            return Err(anyhow!(
+                "box {} out of range (deck fills {} boxes)",
+                n,
+                box_count
+            ));
+        }
+        Some(n) => vec![n],
+        None => (1..=box_count).collect(),
+    };
+    let labels: Vec<BoxLabel> = boxes
+        .into_iter()
+        .map(|n| {
+            let first = (n - 1) * metrics::BOX_CAPACITY;
+            BoxLabel {
+                title: title.clone(),
+                subtitle: subtitle.clone(),
+                first_card: (first + 1).min(total),
+                last_card: (first + metrics::BOX_CAPACITY).min(total),
+                total_cards: total,
+                date: date.clone(),
+                hash: hash.clone(),
+                box_number: n,
+                box_count,
+            }
+        })
+        .collect();
+
+    let is_pdf = args
+        .output
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("pdf"));
+    if is_pdf {
+        let bytes = render_labels_pdf(&labels, args.kind.into());
+        std::fs::write(&args.output, bytes)
+            .with_context(|| format!("failed to write {}", args.output.display()))?;
+        println!(
+            "Wrote {} label(s) to {}",
+            labels.len(),
+            args.output.display()
+        );
+    } else {
+        let text: String = labels.iter().map(BoxLabel::render_text).collect();
+        write_output(&args.output, &text)?;
+    }
+    Ok(())
+}
+
+fn renumber_types(args: DeckRenumberTypesArgs) -> Result<()> {
+    if args.range.is_none()
+        && args.where_type.is_none()
+        && args.prefix.is_none()
+        && args.contains.is_none()
+    {
+        return Err(anyhow!(
+            "select cards with --range, --where, --prefix or --contains"
+        ));
+    }
+    let mut deck = load_deck(args.deck.as_path())?;
+    allow_edit(&mut deck, args.force, "deck renumber-types")?;
+    deck.ensure_mutable()?;
+    let in_range = match args.range.as_deref() {
+        Some(expr) => Some(parse_range_expression(expr, deck.cards.len())?),
+        None => None,
+    };
+    let where_type: Option<CardType> = args.where_type.map(Into::into);
+    let target: CardType = args.to.into();
+    let texts = deck.as_text()?;
+    let matches: Vec<usize> = deck
+        .cards
+        .iter()
+        .enumerate()
+        .filter(|(idx, _)| {
+            let card = &deck.cards[*idx];
+            let text = &texts[*idx];
+            in_range.as_ref().is_none_or(|r| r.contains(idx))
+                && where_type.as_ref().is_none_or(|t| &card.card_type == t)
+                && args.prefix.as_deref().is_none_or(|p| text.starts_with(p))
+                && args.contains.as_deref().is_none_or(|c| text.contains(c))
+                && card.card_type != target
+        })
+        .map(|(idx, _)| idx)
+        .collect();
+
+    if args.dry_run {
+        for idx in &matches {
+            let card = &deck.cards[*idx];
+            println!(
+                "{:>5} {:<9} -> {:<9} |{}",
+                idx + 1,
+                card.card_type,
+                target,
+                texts[*idx].trim_end()
+            );
+        }
+        println!("{} card(s) would change to {}", matches.len(), target);
+        return Ok(());
+    }
+    if matches.is_empty() {
+        println!("No cards matched; {} unchanged", args.deck.display());
+        return Ok(());
+    }
+    for idx in &matches {
+        deck.cards[*idx].card_type = target.clone();
+    }
+    deck.log_action(format!(
+        "deck renumber-types {} cards -> {}",
+        matches.len(),
+        target
+    ));
+    save_deck(&mut deck, &args.deck)?;
+    println!(
+        "Reclassified {} card(s) as {} in {}",
+        matches.len(),
+        target,
+        args.deck.display()
+    );
+    Ok(())
+}
+
+fn edit(args: DeckEditArgs) -> Result<()> {
+    let mut deck = load_deck(args.deck.as_path())?;
+    allow_edit(&mut deck, args.force, "deck edit")?;
+    deck.ensure_mutable()?;
+    let original = deck.edit_buffer(&args.deck.display().to_string())?;
+    let mut buffer = tempfile::Builder::new()
+        .prefix("punch-edit-")
+        .suffix(".txt")
+        .tempfile()
+        .context("failed to create edit buffer")?;
+    buffer
+        .write_all(original.as_bytes())
+        .and_then(|()| buffer.flush())
+        .context("failed to write edit buffer")?;
+
+    let editor = args
+        .editor
+        .or_else(|| std::env::var("VISUAL").ok())
+        .or_else(|| std::env::var("EDITOR").ok())
+        .filter(|e| !e.trim().is_empty())
+        .unwrap_or_else(|| "vi".to_string());
+    // Split on whitespace so editors configured with flags (e.g. `code --wait`)
+    // work without handing the command to a shell.
+    let mut words = editor.split_whitespace();
+    let program = words.next().unwrap_or("vi");
+    let status = std::process::Command::new(program)
+        .args(words)
+        .arg(buffer.path())
+        .status()
+        .with_context(|| format!("failed to launch editor '{}'", editor))?;
+    if !status.success() {
+        let (_, kept) = buffer.keep().context("failed to keep edit buffer")?;
+        return Err(anyhow!(
+            "editor '{}' exited with {}; edits kept in {}",
+            editor,
+            status,
+            kept.display()
+        ));
+    }
+
+    let edited = std::fs::read_to_string(buffer.path())
+        .with_context(|| format!("failed to read {}", buffer.path().display()))?;
+    if edited == original {
+        println!("No changes made to {}", args.deck.display());
+        return Ok(());
+    }
+    let summary = match deck.apply_edit_buffer(&edited) {
+        Ok(summary) => summary,
+        Err(err) => {
+            let (_, kept) = buffer.keep().context("failed to keep edit buffer")?;
+            return Err(err.context(format!(
+                "edited deck rejected; your edits are kept in {}",
+                kept.display()
+            )));
+        }
+    };
+    deck.log_action(format!(
+        "deck edit: {} changed, {} added, {} removed",
+        summary.changed, summary.added, summary.removed
+    ));
+    save_deck(&mut deck, &args.deck)?;
+    println!(
+        "Updated {}: {} changed, {} added, {} removed",
+        args.deck.display(),
+        summary.changed,
+        summary.added,
+        summary.removed
+    );
+    Ok(())
+}
+
+fn meta(args: DeckMetaArgs) -> Result<()> {
+    let mut deck = load_deck(args.deck.as_path())?;
+    let non_empty = |value: String| (!value.trim().is_empty()).then_some(value);
+    let mut changed = Vec::new();
+    if let Some(description) = args.description {
+        deck.header.description = non_empty(description);
+        changed.push("description");
+    }
+    if let Some(notes) = args.notes {
+        deck.header.notes = non_empty(notes);
+        changed.push("notes");
+    }
+    if let Some(line) = args.add_note {
+        deck.header.notes = Some(match deck.header.notes.take() {
+            Some(notes) => format!("{}\n{}", notes, line),
+            None => line,
+        });
+        changed.push("notes");
+    }
+    if changed.is_empty() {
+        println!(
+            "Description: {}",
+            deck.header.description.as_deref().unwrap_or("(none)")
+        );
+        println!("Notes:");
+        for line in deck.header.notes.as_deref().unwrap_or("(none)").lines() {
+            println!("  {}", line);
+        }
+        return Ok(());
+    }
+    allow_edit(&mut deck, args.force, "deck meta")?;
+    changed.dedup();
+    deck.log_action(format!("deck meta {}", changed.join(",")));
+    save_deck(&mut deck, &args.deck)?;
+    println!(
+        "Updated {} of {}",
+        changed.join(" and "),
+        args.deck.display()
+    );
+    Ok(())
+}
+
+fn state(args: DeckStateArgs) -> Result<()> {
+    let mut deck = load_deck(args.deck.as_path())?;
+    let Some(next) = args.to else {
+        println!("{}: {}", args.deck.display(), deck.header.state);
+        return Ok(());
+    };
+    allow_edit(&mut deck, args.force, "deck state")?;
+    let previous = deck.header.state;
+    deck.transition(next.into())?;
+    save_deck(&mut deck, &args.deck)?;
+    println!(
+        "{}: {} -> {}",
+        args.deck.display(),
+        previous,
+        deck.header.state
+    );
+    Ok(())
+}
+
+fn validate(args: DeckValidateArgs) -> Result<()> {
+    let mut deck = if args.untrusted {
+        Deck::load_strict(&args.deck, &LoadLimits::default())
+            .with_context(|| format!("rejected deck {}", args.deck.display()))?
+    } else {
+        load_deck(args.deck.as_path())?
+    };
+    if let Some(output) = args.output.as_deref() {
+        let language = deck.header.language.as_deref().ok_or_else(|| {
+            anyhow!("--fix needs a deck language (set with `deck init --language`)")
+        })?;
+        let language = LanguageRegistry::get(language)?;
+        let fixes = language.fix_deck(&mut deck)?;
+        for fix in &fixes {
+            println!(
+                "fixed: card {}: {}: {}",
+                fix.card + 1,
+                fix.check,
+                fix.message
+            );
+            deck.log_change(
+                format!("validate --fix card {}: {}", fix.card + 1, fix.check),
+                fix.changes.clone(),
+            );
+        }
+        save_deck(&mut deck, output)?;
+        println!(
+            "wrote {} correction(s) to {}",
+            fixes.len(),
+            output.display()
+        );
+    }
+    let mut outcome = CheckOutcome::default();
+    if let Some(language) = deck.header.language.as_deref() {
+        match LanguageRegistry::get(language) {
+            Ok(language) => {
+                for finding in language.check_deck(&deck) {
+                    println!(
+                        "warning: card {}: {}: {}",
+                        finding.card + 1,
+                        finding.check,
+                        finding.message
+                    );
+                    outcome.warnings += 1;
+                }
+            }
+            Err(_) => println!("note: language {} has no default checks", language),
+        }
+    }
+    if let Some(rules) = deck.header.machine {
+        let read = rules.feed(&deck, deck.encoder()?)?;
+        if let Some(halt) = read.halted_at {
+            println!(
+                "violation: card {} column {}: {} reader halts on holes {}; {} card(s) unread",
+                halt.card + 1,
+                halt.column,
+                rules.target,
+                halt.mask,
+                deck.cards.len() - read.cards_read
+            );
+            outcome.violations += 1;
+        }
+        let substitute = match rules.on_invalid {
+            InvalidCharMode::Substitute(ch) => ch,
+            InvalidCharMode::Halt => ' ',
+        };
+        for sub in &read.substituted {
+            println!(
+                "warning: card {} column {}: {} reads holes {} as '{}'",
+                sub.card + 1,
+                sub.column,
+                rules.target,
+                sub.mask,
+                substitute
+            );
+            outcome.warnings += 1;
+        }
+    } else if args.punches {
+        let encoder = deck.encoder()?;
+        for invalid in deck.invalid_punches(encoder)? {
+            println!(
+                "violation: card {} column {}: holes {} punch no {} character",
+                invalid.card + 1,
+                invalid.column,
+                invalid.mask,
+                encoder.name()
+            );
+            outcome.violations += 1;
+        }
+    }
+    if outcome.warnings == 0 && outcome.violations == 0 {
+        let checked = args.output.as_deref().unwrap_or(&args.deck);
+        println!("{} is valid", checked.display());
+    }
+    outcome.finish("deck validate", args.max_warnings)
+}
+
+fn machine(args: DeckMachineArgs) -> Result<()> {
+    let mut deck = load_deck(args.deck.as_path())?;
+    if args.clear {
+        deck.header.machine = None;
+    } else if args.target.is_some() || args.on_invalid.is_some() {
+        let target = match (args.target, deck.header.machine) {
+            (Some(target), _) => target.into(),
+            (None, Some(rules)) => rules.target,
+            (None, None) => return Err(anyhow!("deck has no target machine; pass --target")),
+        };
+        let on_invalid = match args.on_invalid {
+            Some(InvalidCharArg::Halt) => InvalidCharMode::Halt,
+            Some(InvalidCharArg::Substitute) => InvalidCharMode::Substitute(args.substitute_char),
+            None => deck
+                .header
+                .machine
+                .map(|rules| rules.on_invalid)
+                .unwrap_or_default(),
+        };
+        deck.header.machine = Some(MachineRules { target, on_invalid });
+    } else {
+        match &deck.header.machine {
+            Some(rules) => println!("{}: {}", args.deck.display(), rules),
+            None => println!("{}: no target machine", args.deck.display()),
+        }
+        return Ok(());
+    }
+    allow_edit(&mut deck, args.force, "deck machine")?;
+    let summary = deck
+        .header
+        .machine
+        .map_or_else(|| "none".to_string(), |rules| rules.to_string());
+    deck.log_action(format!("deck machine {}", summary));
+    save_deck(&mut deck, &args.deck)?;
+    println!("{}: {}", args.deck.display(), summary);
+    Ok(())
+}
+
+/// Append or refresh the deck's summary card, or with --check verify it.
+fn summary_card(args: DeckSummaryCardArgs) -> Result<()> {
+    let mut deck = load_deck(args.deck.as_path())?;
+    if args.check {
+        let Some(check) = deck.check_summary_card()? else {
+            return Err(CheckFailed {
+                code: EXIT_DIFFERENCES,
+                summary: format!("{} has no summary card", args.deck.display()),
+            }
+            .into());
+        };
+        let card = check.index + 1;
+        println!("Summary card {}: {}", card, check.found.text());
+        if check.found.cards != check.expected.cards {
+            println!(
+                "  counts {} card(s), {} precede it",
+                check.found.cards, check.expected.cards
+            );
+        }
+        if check.found.hash != check.expected.hash {
+            println!(
+                "  hash {} does not match the cards before it ({})",
+                check.found.hash, check.expected.hash
+            );
+        }
+        if check.trailing > 0 {
+            println!("  {} card(s) follow the summary card", check.trailing);
+        }
+        if !check.is_valid() {
+            return Err(CheckFailed {
+                code: EXIT_DIFFERENCES,
+                summary: format!("summary card {} does not match the deck", card),
+            }
+            .into());
+        }
+        println!("Summary card matches {} card(s)", check.found.cards);
+        return Ok(());
+    }
+    allow_edit(&mut deck, args.force, "deck summary-card")?;
+    let date = args.date.unwrap_or_else(|| Local::now().date_naive());
+    let replaced = deck.append_summary_card(date)?;
+    let card = deck.cards.len();
+    let text = deck.card_text(card - 1)?.trim_end().to_string();
+    deck.log_action(format!("deck summary-card {}", card));
+    save_deck(&mut deck, &args.deck)?;
+    println!(
+        "{} summary card {} in {}: {}",
+        if replaced { "Refreshed" } else { "Appended" },
+        card,
+        args.deck.display(),
+        text
+    );
+    Ok(())
+}
+
+fn convert_encoding(args: DeckConvertEncodingArgs) -> Result<()> {
+    let mut deck = load_deck(args.deck.as_path())?;
+    allow_edit(&mut deck, args.force, "deck convert-encoding")?;
+    let from = match &args.from {
+        Some(name) => EncoderRegistry::get(name)?,
+        None => deck.encoder()?,
+    };
+    let to = EncoderRegistry::get(&args.to)?;
+    let on_missing = match args.on_missing {
+        Some(InvalidCharArg::Halt) => InvalidCharMode::Halt,
+        Some(InvalidCharArg::Substitute) => InvalidCharMode::Substitute(args.substitute_char),
+        None => deck
+            .header
+            .machine
+            .map(|rules| rules.on_invalid)
+            .unwrap_or_default(),
+    };
+    let report = deck.convert_encoding(from, to, on_missing)?;
+    for missing in &report.unmapped {
+        let shown = match (missing.ch, missing.mask.0) {
+            (Some(ch), 0) => format!("{:?}", ch),
+            (Some(ch), _) => format!("{:?} ({})", ch, missing.mask),
+            (None, _) => format!("unreadable holes {}", missing.mask),
+        };
+        println!(
+            "  card {} column {}: {} has no {} punch code",
+            missing.card + 1,
+            missing.column,
+            shown,
+            to.name()
+        );
+    }
+    if !report.applied {
+        return Err(CheckFailed {
+            code: EXIT_DIFFERENCES,
+            summary: format!(
+                "{} column(s) have no {} punch code; deck left unchanged (use --on-missing substitute)",
+                report.unmapped.len(),
+                to.name()
+            ),
+        }
+        .into());
+    }
+    deck.header.encoder = declared_encoder(Some(&args.to), &args.deck)?;
+    deck.log_action(format!(
+        "deck convert-encoding {} -> {}",
+        from.name(),
+        to.name()
+    ));
+    let output = args.output.as_deref().unwrap_or(&args.deck);
+    save_deck(&mut deck, output)?;
+    println!(
+        "Repunched {} card(s) from {} to {} in {}",
+        report.repunched,
+        from.name(),
+        to.name(),
+        output.display()
+    );
+    if let InvalidCharMode::Substitute(ch) = on_missing
+        && !report.unmapped.is_empty()
+    {
+        println!(
+            "Substituted {:?} in {} column(s)",
+            ch,
+            report.unmapped.len()
+        );
+    }
+    Ok(())
+}
+
+fn charset(args: DeckCharsetArgs) -> Result<()> {
+    let deck = load_deck(args.deck.as_path())?;
+    let usage = deck.char_usage();
+    println!("Distinct characters: {}", usage.len());
+    for (ch, count) in &usage {
+        println!("  {:?} {:>6}", ch, count);
+    }
+    let supporting: Vec<String> = EncoderArg::value_variants()
+        .iter()
+        .filter(|arg| usage.keys().all(|ch| arg.encoder().is_supported(*ch)))
+        .map(|arg| arg.to_string())
+        .collect();
+    if supporting.is_empty() {
+        println!("Charts supporting every character: none");
+    } else {
+        println!(
+            "Charts supporting every character: {}",
+            supporting.join(", ")
+        );
+    }
+    if let Some(arg) = args.encoder {
+        let failing = deck.unsupported_chars(arg.encoder());
+        if failing.is_empty() {
+            println!("Every card encodes under {}", arg);
+        } else {
+            println!("Cards that fail under {}: {}", arg, failing.len());
+            for (idx, missing) in failing {
+                let missing: String = missing.into_iter().collect();
+                println!("  card {}: {:?}", idx + 1, missing);
+            }
+        }
+    }
+    Ok(())
+}
+
+fn apply_patches(args: DeckApplyPatchesArgs) -> Result<()> {
+    let mut deck = load_deck(args.deck.as_path())?;
+    allow_edit(&mut deck, args.force, "deck apply-patches")?;
+    let dangling: Vec<_> = deck
+        .patch_chains()
+        .into_iter()
+        .filter(|chain| chain.card.is_none())
+        .collect();
+    let folded = deck.apply_patches()?;
+    for fold in &folded {
+        println!(
+            "card {}: folded {} patch card(s)",
+            fold.card + 1,
+            fold.patches
+        );
+        deck.log_change(
+            format!("deck apply-patches card {}", fold.card + 1),
+            fold.changes.clone(),
+        );
+    }
+    for chain in dangling {
+        println!(
+            "warning: {} not found; kept {} patch card(s)",
+            chain.target,
+            chain.patches.len()
+        );
+    }
+    let output = args.output.as_deref().unwrap_or(&args.deck);
+    save_deck(&mut deck, output)?;
+    println!(
+        "Applied {} patch chain(s); {} card(s) in {}",
+        folded.len(),
+        deck.cards.len(),
+        output.display()
+    );
+    Ok(())
+}
+
+fn shifts(args: DeckShiftsArgs) -> Result<()> {
+    let deck = load_deck(args.deck.as_path())?;
+    let template = match args.template.as_deref().or(deck.header.template.as_deref()) {
+        Some(name) => Some(
+            TemplateRegistry::get(name)
+                .with_context(|| format!("template '{}' not found", name))?,
+        ),
+        None => None,
+    };
+    let fields: Vec<ColumnRange> = match template {
+        Some(tpl) => tpl.columns.iter().map(|col| col.range).collect(),
+        None => vec![ColumnRange { start: 1, end: 80 }],
+    };
+    let blocks = detect_shifts(
+        &deck,
+        &fields,
+        resolve_encoder(args.encoder, &deck)?,
+        args.max_shift,
+        args.min_run,
+    )?;
+    let profile = template.map_or("whole card", |tpl| tpl.name);
+    if blocks.is_empty() {
+        println!(
+            "No shifted blocks in {} ({} profile)",
+            args.deck.display(),
+            profile
+        );
+        return Ok(());
+    }
+    for block in &blocks {
+        let side = if block.by > 0 { "right" } else { "left" };
+        println!(
+            "cards {}..{}: shifted {} column(s) {} (+{} field columns)",
+            block.first + 1,
+            block.last + 1,
+            block.by.unsigned_abs(),
+            side,
+            block.gain
+        );
+        println!(
+            "  fix: punch deck unshift {} --range {}..{} --by {}",
+            args.deck.display(),
+            block.first + 1,
+            block.last + 1,
+            block.by
+        );
+    }
+    println!(
+        "{} shifted block(s) in {} ({} profile)",
+        blocks.len(),
+        args.deck.display(),
+        profile
+    );
+    Ok(())
+}
+
+fn unshift(args: DeckUnshiftArgs) -> Result<()> {
+    let mut deck = load_deck(args.deck.as_path())?;
+    allow_edit(&mut deck, args.force, "deck unshift")?;
+    deck.ensure_mutable()?;
+    let indexes = parse_range_expression(&args.range, deck.cards.len())?;
+    for idx in &indexes {
+        let card = unshift_card(&deck.cards[*idx], args.by, args.cols)?;
+        deck.replace_card(*idx, card)
+            .with_context(|| format!("card {}", idx + 1))?;
+    }
+    deck.log_action(format!(
+        "deck unshift {} by {} (columns {}-{})",
+        args.range, args.by, args.cols.start, args.cols.end
+    ));
+    let output = args.output.as_deref().unwrap_or(&args.deck);
+    save_deck(&mut deck, output)?;
+    println!(
+        "Shifted {} card(s) {} column(s) {} in {}",
+        indexes.len(),
+        args.by.unsigned_abs(),
+        if args.by > 0 { "left" } else { "right" },
+        output.display()
+    );
+    Ok(())
+}
+
+/// Refuse `command` while cards are staged for the deck: they sit in plain
+/// text beside it and would escape the encryption or redaction.
+fn refuse_staged(deck: &Path, command: &str) -> Result<()> {
+    let staged: usize = pending::read_pending(deck)?
+        .iter()
+        .map(|batch| batch.cards.len())
+        .sum();
+    if staged > 0 {
+        return Err(anyhow!(
+            "{} has {} staged card(s); run `punch deck flush` (or `--discard`) before `{}`",
+            deck.display(),
+            staged,
+            command
+        ));
+    }
+    Ok(())
+}
+
+fn redact(args: DeckRedactArgs) -> Result<()> {
+    refuse_staged(&args.deck, "deck redact")?;
+    let mut deck = load_deck(args.deck.as_path())?;
+    allow_edit(&mut deck, args.force, "deck redact")?;
+    deck.ensure_mutable()?;
+    let encoder = deck.encoder()?;
+    let indexes = match &args.range {
+        Some(expr) => parse_range_expression(expr, deck.cards.len())?,
+        None => (0..deck.cards.len()).collect(),
+    };
+    for &idx in &indexes {
+        let card = redact_card(&deck.cards[idx], args.cols, args.with, encoder)
+            .with_context(|| format!("card {}", idx + 1))?;
+        card.to_punch_card(encoder)
+            .with_context(|| format!("card {}: {:?} cannot be punched", idx + 1, args.with))?;
+        deck.replace_card(idx, card)
+            .with_context(|| format!("card {}", idx + 1))?;
+    }
+    let scrubbed = scrub_history(&mut deck.header.history, args.cols, args.with);
+    let cards = args.range.as_deref().unwrap_or("all cards");
+    deck.log_action(format!(
+        "deck redact columns {}-{} with {:?} on {}",
+        args.cols.start, args.cols.end, args.with, cards
+    ));
+    let output = args.output.as_deref().unwrap_or(&args.deck);
+    deck.save(output)?;
+    // Undo snapshots still hold the redacted text.
+    journal::clear(output)?;
+    println!(
+        "Redacted columns {}-{} on {} card(s) and {} history entr{} in {}",
+        args.cols.start,
+        args.cols.end,
+        indexes.len(),
+        scrubbed,
+        if scrubbed == 1 { "y" } else { "ies" },
+        output.display()
+    );
+    Ok(())
+}
+
+/// Card-by-card comparison; exits 1 when the decks differ, like `diff`.
+fn diff(args: DeckDiffArgs) -> Result<()> {
+    let mut left = load_deck(args.left.as_path())?;
+    let mut right = load_deck(args.right.as_path())?;
+    let masks = args.masks.resolve()?;
+    if !masks.is_empty() {
+        masks.blank(&mut left)?;
+        masks.blank(&mut right)?;
+    }
+    let found = diff_cards(&left.cards, &right.cards, left.text_encoder())?;
+    match args.format {
+        DiffFormatArg::Json => println!("{}", serde_json::to_string_pretty(&found)?),
+        DiffFormatArg::Text => {
+            println!("--- {} ({} cards)", args.left.display(), left.cards.len());
+            println!("+++ {} ({} cards)", args.right.display(), right.cards.len());
+            for entry in &found {
+                print_card_diff(entry, &left, &right)?;
+            }
+        }
+    }
+    if found.is_empty() {
+        eprintln!("Decks are identical card by card");
+        return Ok(());
+    }
+    let count = |pick: fn(&CardDiff) -> bool| found.iter().filter(|d| pick(d)).count();
+    let summary = format!(
+        "{} changed, {} added, {} removed",
+        count(|d| matches!(d, CardDiff::Changed { .. })),
+        count(|d| matches!(d, CardDiff::Added { .. })),
+        count(|d| matches!(d, CardDiff::Removed { .. }))
+    );
+    Err(CheckFailed {
+        code: EXIT_DIFFERENCES,
+        summary,
+    }
+    .into())
+}
+
+/// Matching cards, one per line with `^` under the matches; exits 1 when
+/// nothing matches, like `grep`.
+fn grep(args: DeckGrepArgs) -> Result<()> {
+    let deck = load_deck(args.deck.as_path())?;
+    let mut query = if args.regex {
+        Query::regex(&args.pattern, args.ignore_case)?
+    } else {
+        Query::text(&args.pattern, args.ignore_case)
+    };
+    if let Some(cols) = args.cols {
+        query = query.in_cols(cols);
+    }
+    query = query.of_types(args.card_types.into_iter().map(Into::into).collect());
+    let found = deck.find(&query)?;
+    print_matches(&deck, &found, args.indexes)?;
+    if found.is_empty() {
+        return Err(CheckFailed {
+            code: EXIT_DIFFERENCES,
+            summary: format!("no card in {} matches", args.deck.display()),
+        }
+        .into());
+    }
+    Ok(())
+}
+
+/// Cards with a column punched with the given rows; exits 1 when none is.
+fn grep_punches(args: DeckGrepPunchesArgs) -> Result<()> {
+    let deck = load_deck(args.deck.as_path())?;
+    let mut query = Query::punches(&args.pattern, args.exact)?;
+    if let Some(cols) = args.cols {
+        query = query.in_cols(cols);
+    }
+    query = query.of_types(args.card_types.into_iter().map(Into::into).collect());
+    let found = deck.find(&query)?;
+    print_matches(&deck, &found, args.indexes)?;
+    if found.is_empty() {
+        return Err(CheckFailed {
+            code: EXIT_DIFFERENCES,
+            summary: format!(
+                "no column in {} is punched {}{}",
+                args.deck.display(),
+                if args.exact { "exactly " } else { "" },
+                args.pattern
+            ),
+        }
+        .into());
+    }
+    if !args.indexes {
+        let columns: usize = found
+            .iter()
+            .flat_map(|hit| &hit.columns)
+            .map(|cols| cols.end - cols.start + 1)
+            .sum();
+        println!("{} column(s) on {} card(s)", columns, found.len());
+    }
+    Ok(())
+}
+
+/// Print each match with `^` under its columns, or with `indexes` only the
+/// card numbers as a list for `-r`.
+fn print_matches(deck: &Deck, found: &[CardMatch], indexes: bool) -> Result<()> {
+    if indexes {
+        let cards: Vec<String> = found.iter().map(|m| (m.card + 1).to_string()).collect();
+        if !cards.is_empty() {
+            println!("{}", cards.join(","));
+        }
+        return Ok(());
+    }
+    for hit in found {
+        let text = deck.card_text(hit.card)?;
+        let label = match hit.seq {
+            Some(seq) => format!("card {} (seq {})", hit.card + 1, seq),
+            None => format!("card {}", hit.card + 1),
+        };
+        println!("{}: |{}|", label, text.trim_end());
+        let mut marks = vec![' '; 80];
+        for cols in &hit.columns {
+            marks[cols.start - 1..cols.end].fill('^');
+        }
+        let indent = " ".repeat(label.len() + 3);
+        println!("{}{}", indent, marks.iter().collect::<String>().trim_end());
+    }
+    Ok(())
+}
+
+fn print_card_diff(entry: &CardDiff, left: &Deck, right: &Deck) -> Result<()> {
+    match entry {
+        CardDiff::Added { right, text } => println!("+ card {}: |{}|", right + 1, text),
+        CardDiff::Removed { left, text } => println!("- card {}: |{}|", left + 1, text),
+        CardDiff::Changed {
+            left: l,
+            right: r,
+            columns,
+            fields,
+        } => {
+            let mut summary: Vec<String> = columns.iter().map(ToString::to_string).collect();
+            summary.extend(fields.iter().map(|field| format!("{} changed", field)));
+            println!("~ card {} -> {}: {}", l + 1, r + 1, summary.join("; "));
+            if columns.is_empty() {
+                return Ok(());
+            }
+            let mut marks = vec![' '; 80];
+            for change in columns {
+                marks[change.columns.start - 1..change.columns.end].fill('^');
+            }
+            println!("    - |{}|", left.card_text(*l)?);
+            println!("    + |{}|", right.card_text(*r)?);
+            println!("       {}", marks.iter().collect::<String>().trim_end());
+        }
+    }
+    Ok(())
+}
+
+fn keygen(args: DeckKeygenArgs) -> Result<()> {
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    let mut file = options
+        .open(&args.output)
+        .with_context(|| format!("failed to create key file {}", args.output.display()))?;
+    writeln!(file, "{}", DeckKey::generate().to_hex())?;
+    println!(
+        "Wrote deck key to {}; keep it safe, encrypted decks cannot be recovered without it",
+        args.output.display()
+    );
+    Ok(())
+}
+
+/// Key from `--keyfile`, else from the environment.
+fn crypt_key(keyfile: Option<&std::path::Path>) -> Result<Option<DeckKey>> {
+    match keyfile {
+        Some(path) => DeckKey::from_file(path).map(Some),
+        None => DeckKey::from_env(),
+    }
+}
+
+fn encrypt(args: DeckCryptArgs) -> Result<()> {
+    let key = crypt_key(args.keyfile.as_deref())?.ok_or_else(|| {
+        anyhow!("no key: pass --keyfile or set PUNCH_DECK_KEY or PUNCH_DECK_KEYFILE")
+    })?;
+    refuse_staged(&args.deck, "deck encrypt")?;
+    let mut deck = Deck::load_with_key(&args.deck, Some(&key))
+        .with_context(|| format!("failed to read deck {}", args.deck.display()))?;
+    if deck.encryption.is_some() {
+        return Err(anyhow!("{} is already encrypted", args.deck.display()));
+    }
+    allow_edit(&mut deck, args.force, "deck encrypt")?;
+    deck.encryption = Some(key);
+    deck.log_action("deck encrypt");
+    let output = args.output.as_deref().unwrap_or(&args.deck);
+    save_deck(&mut deck, output)?;
+    println!(
+        "Encrypted {} into {}",
+        args.deck.display(),
+        output.display()
+    );
+    // The sealed deck carries its own history; a plaintext sidecar next to it
+    // would defeat the encryption.
+    let sidecar = audit_sidecar_path(output);
+    if deck.header.canonical && sidecar.exists() {
+        std::fs::remove_file(&sidecar)
+            .with_context(|| format!("failed to remove {}", sidecar.display()))?;
+        println!("Removed audit sidecar {}", sidecar.display());
+    }
+    Ok(())
+}
+
+fn decrypt(args: DeckCryptArgs) -> Result<()> {
+    let key = crypt_key(args.keyfile.as_deref())?;
+    let mut deck = Deck::load_with_key(&args.deck, key.as_ref())
+        .with_context(|| format!("failed to read deck {}", args.deck.display()))?;
+    let Some(old_key) = deck.encryption.take() else {
+        return Err(anyhow!("{} is not encrypted", args.deck.display()));
+    };
+    allow_edit(&mut deck, args.force, "deck decrypt")?;
+    deck.log_action("deck decrypt");
+    let output = args.output.as_deref().unwrap_or(&args.deck);
+    // The journal is sealed with the old key and comes out in plain text.
+    journal::save_rekeyed(&mut deck, output, Some(&old_key))?;
+    println!(
+        "Decrypted {} into {}",
+        args.deck.display(),
+        output.display()
+    );
+    Ok(())
+}
+
+fn colstats(args: DeckColstatsArgs) -> Result<()> {
+    let deck = load_deck(args.deck.as_path())?;
+    let stats = ColumnStats::collect(&deck, args.col, resolve_encoder(args.encoder, &deck)?)?;
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&stats)?);
+        return Ok(());
+    }
+    let cols = if args.col.start == args.col.end {
+        format!("column {}", args.col.start)
+    } else {
+        format!("columns {}-{}", args.col.start, args.col.end)
+    };
+    println!("{} over {} card(s)", cols, stats.cards);
+    let chars: Vec<(String, usize)> = stats
+        .chars
+        .iter()
+        .map(|(ch, count)| (format!("{:?}", ch), *count))
+        .collect();
+    print_bars("Characters", &chars);
+    let rows: Vec<(String, usize)> = ROW_NAMES
+        .iter()
+        .map(|row| (format!("{:>3}", row), stats.rows[row]))
+        .collect();
+    print_bars("Punch rows", &rows);
+    Ok(())
+}
+
+/// Print `label count` lines with bars scaled to the largest count.
+fn print_bars(title: &str, entries: &[(String, usize)]) {
+    const WIDTH: usize = 40;
+    println!("{}:", title);
+    let max = entries
+        .iter()
+        .map(|(_, count)| *count)
+        .max()
+        .unwrap_or(0)
+        .max(1);
+    for (label, count) in entries {
+        let bar = (count * WIDTH).div_ceil(max);
+        println!("  {} {:>6} {}", label, count, "#".repeat(bar));
+    }
+}
+
+fn set_readonly(args: DeckLockArgs, readonly: bool) -> Result<()> {
+    let mut deck = load_deck(args.deck.as_path())?;
+    let state = if readonly { "locked" } else { "unlocked" };
+    if !deck.set_readonly(readonly) {
+        println!("{} is already {}", args.deck.display(), state);
+        return Ok(());
+    }
+    save_deck(&mut deck, &args.deck)?;
+    println!("{} is now {}", args.deck.display(), state);
+    Ok(())
+}
+
+#[derive(Clone, Copy)]
+enum Step {
+    Undo,
+    Redo,
+}
+
+/// Apply (or drop) the cards staged by `--defer-save` commands.
+fn flush(args: DeckFlushArgs) -> Result<()> {
+    let batches = pending::read_pending(&args.deck)?;
+    let staged: usize = batches.iter().map(|batch| batch.cards.len()).sum();
+    if args.discard {
+        pending::discard(&args.deck)?;
+        println!(
+            "Discarded {} staged card(s) for {}",
+            staged,
+            args.deck.display()
+        );
+        return Ok(());
+    }
+    if batches.is_empty() {
+        println!("No staged cards for {}", args.deck.display());
+        return Ok(());
+    }
+    let mut deck = load_deck(args.deck.as_path())?;
+    allow_edit(&mut deck, args.force, "deck flush")?;
+    let added = deck.apply_pending(&batches)?;
+    save_deck(&mut deck, &args.deck)?;
+    pending::discard(&args.deck)?;
+    println!(
+        "Added {} card(s) from {} deferred command(s) into {}",
+        added,
+        batches.len(),
+        args.deck.display()
+    );
+    Ok(())
+}
+
+fn step_journal(args: DeckJournalArgs, step: Step) -> Result<()> {
+    let (verb, other) = match step {
+        Step::Undo => ("Undid", "undo"),
+        Step::Redo => ("Redid", "redo"),
+    };
+    let mut done = 0;
+    while done < args.steps {
+        let action = match step {
+            Step::Undo => journal::undo(&args.deck)?,
+            Step::Redo => journal::redo(&args.deck)?,
+        };
+        let Some(action) = action else {
+            break;
+        };
+        println!("{} {}", verb, action);
+        done += 1;
+    }
+    if done == 0 {
+        return Err(anyhow!("nothing to {} for {}", other, args.deck.display()));
+    }
+    if done < args.steps {
+        println!("Stopped after {} step(s): nothing more to {}", done, other);
+    }
+    Ok(())
+}
@@ -1,10 +1,18 @@
 //! Encoding helpers (`punch encode ...`).
 
-use std::path::PathBuf;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
 
-use anyhow::Result;
+use anyhow::{Context, Result};
 use clap::{Args, Subcommand};
-use punchcard::{Ibm029Encoder, RenderStyle, encode_text_to_deck};
+use punchcard::{
+    Captions, CardSide, CornerCut, DeckImageOptions, DeckPdfOptions, ImageRenderOptions,
+    PageLayout, PunchEncoding, RenderStyle, TableEncoder, encode_text_to_deck, render_card_image,
+    render_card_svg, render_deck_image, render_deck_pdf, render_deck_svg,
+};
+
+use crate::cli::common::{CardImageStyleArg, EncoderArg};
 
 use crate::cli::utils::read_text_arg;
 
@@ -13,6 +21,8 @@ use crate::cli::utils::read_text_arg;
 pub enum EncodeCommand {
     /// Encode text into punch card deck.
     Text(EncodeTextArgs),
+    /// Render text straight to a card picture (PNG, SVG or PDF), no deck needed.
+    Banner(EncodeBannerArgs),
 }
 
 /// Arguments for `punch encode text`.
@@ -27,21 +37,74 @@ pub struct EncodeTextArgs {
     /// Render ASCII representation.
     #[arg(long)]
     pub render: bool,
+    /// Punch chart used to encode card text.
+    #[arg(long, value_enum, default_value_t = EncoderArg::Ibm029)]
+    pub encoder: EncoderArg,
+    /// Encode with a chart loaded from a TOML or CSV mapping file instead.
+    #[arg(long = "encoder-file", value_name = "FILE", conflicts_with = "encoder")]
+    pub encoder_file: Option<PathBuf>,
+}
+
+/// Arguments for `punch encode banner`.
+#[derive(Args, Debug)]
+pub struct EncodeBannerArgs {
+    /// Text to punch; lines longer than 80 columns continue on further cards.
+    #[arg(long)]
+    pub text: Option<String>,
+    /// Read the text from a file (`-` for stdin).
+    #[arg(long = "from", conflicts_with = "text")]
+    pub from: Option<PathBuf>,
+    /// Output image; the extension picks the format (.png, .svg or .pdf).
+    #[arg(short = 'o', long = "output")]
+    pub output: PathBuf,
+    /// Visual style applied to the card face.
+    #[arg(long, default_value_t = CardImageStyleArg::Interpreter, value_enum)]
+    pub style: CardImageStyleArg,
+    /// Dots per inch for PNG output.
+    #[arg(long, default_value_t = 300)]
+    pub dpi: u32,
+    /// Add sequence numbers in columns 73-80.
+    #[arg(long)]
+    pub seq: bool,
+    /// Punch chart used to encode the text.
+    #[arg(long, value_enum, default_value_t = EncoderArg::Ibm029)]
+    pub encoder: EncoderArg,
+    /// Encode with a chart loaded from a TOML or CSV mapping file instead.
+    #[arg(long = "encoder-file", value_name = "FILE", conflicts_with = "encoder")]
+    pub encoder_file: Option<PathBuf>,
 }
 
 /// Execute an encode command.
 pub fn handle(command: EncodeCommand) -> Result<()> {
     match command {
         EncodeCommand::Text(args) => text(args),
+        EncodeCommand::Banner(args) => banner(args),
     }
 }
 
+/// Mapping file chart, when `--encoder-file` was given.
+fn load_table(path: Option<&Path>) -> Result<Option<TableEncoder>> {
+    path.map(TableEncoder::from_path).transpose()
+}
+
 fn text(args: EncodeTextArgs) -> Result<()> {
     let text = read_text_arg(args.text.clone(), args.from.clone())?;
-    let encoder = Ibm029Encoder::new();
-    let deck = encode_text_to_deck(&encoder, &text, true)?;
+    let table = load_table(args.encoder_file.as_deref())?;
+    let encoder: &dyn PunchEncoding = match &table {
+        Some(table) => table,
+        None => args.encoder.encoder(),
+    };
+    let deck = encode_text_to_deck(encoder, &text, true)?;
     if args.render {
-        println!("{}", deck.render(RenderStyle::AsciiX));
+        let mut captions = Captions::english().clone();
+        captions.name_encoder(encoder.name());
+        let mut out = std::io::stdout().lock();
+        for (idx, card) in deck.cards.iter().enumerate() {
+            if idx > 0 {
+                writeln!(out)?;
+            }
+            card.render_captioned_to(&mut out, RenderStyle::AsciiX, &[], None, &captions)?;
+        }
     } else {
         println!(
             "Encoded {} columns into {} cards",
@@ -51,3 +114,69 @@ fn text(args: EncodeTextArgs) -> Result<()> {
     }
     Ok(())
 }
+
+fn banner(args: EncodeBannerArgs) -> Result<()> {
+    let text = read_text_arg(args.text.clone(), args.from.clone())?;
+    let table = load_table(args.encoder_file.as_deref())?;
+    let encoder: &dyn PunchEncoding = match &table {
+        Some(table) => table,
+        None => args.encoder.encoder(),
+    };
+    let deck = encode_text_to_deck(encoder, text.trim_end_matches('\n'), args.seq)?;
+    let style = args.style.into();
+    let extension = args
+        .output
+        .extension()
+        .map(|ext| ext.to_string_lossy().to_ascii_lowercase());
+    let write = |bytes: &[u8]| {
+        fs::write(&args.output, bytes)
+            .with_context(|| format!("failed to write {}", args.output.display()))
+    };
+    match (extension.as_deref(), deck.cards.as_slice()) {
+        (Some("svg"), [card]) => write(render_card_svg(card, style).as_bytes())?,
+        (Some("svg"), _) => {
+            let options = DeckImageOptions {
+                style,
+                ..DeckImageOptions::default()
+            };
+            write(render_deck_svg(&deck, &options)?.as_bytes())?
+        }
+        (Some("pdf"), _) => {
+            let options = DeckPdfOptions {
+                style,
+                layout: PageLayout::Card,
+                per_page: None,
+            };
+            write(&render_deck_pdf(&deck.cards, &options)?)?
+        }
+        (_, [card]) => {
+            let options = ImageRenderOptions {
+                style,
+                dpi: args.dpi,
+                layout: PageLayout::Card,
+                per_page: None,
+                side: CardSide::Front,
+                corner_cut: CornerCut::None,
+            };
+            render_card_image(card, &options)?
+                .save(&args.output)
+                .with_context(|| format!("failed to write {}", args.output.display()))?
+        }
+        (_, _) => {
+            let options = DeckImageOptions {
+                style,
+                dpi: args.dpi,
+                ..DeckImageOptions::default()
+            };
+            render_deck_image(&deck, &options)?
+                .save(&args.output)
+                .with_context(|| format!("failed to write {}", args.output.display()))?
+        }
+    }
+    println!(
+        "Rendered {} card(s) to {}",
+        deck.cards.len(),
+        args.output.display()
+    );
+    Ok(())
+}
@@ -0,0 +1,103 @@
+//! Exit-code contract shared by check-style commands.
+//!
+//! Commands that compare, validate or search decks (`verify pass`, `seq check`, `deck diff`,
+//! `deck grep`) report their outcome through the process exit status so CI jobs can gate on
+//! them:
+//!
+//! | code | meaning                                   |
+//! |------|-------------------------------------------|
+//! | 0    | clean, or warnings within `--max-warnings` |
+//! | 1    | differences or warnings                   |
+//! | 2    | violations (hard failures)                |
+//! | 3    | operational error (bad input, I/O, usage) |
+
+use std::process::ExitCode;
+
+use anyhow::{Error, Result};
+use thiserror::Error;
+
+/// Nothing to report.
+pub const EXIT_CLEAN: u8 = 0;
+/// Differences or warnings exceeded the allowed budget.
+pub const EXIT_DIFFERENCES: u8 = 1;
+/// At least one violation was found.
+pub const EXIT_VIOLATIONS: u8 = 2;
+/// The command could not run to completion.
+pub const EXIT_ERROR: u8 = 3;
+
+/// Error carrying a check outcome rather than an operational failure.
+#[derive(Debug, Error)]
+#[error("{summary}")]
+pub struct CheckFailed {
+    pub code: u8,
+    pub summary: String,
+}
+
+/// Tally of findings produced by a check command.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct CheckOutcome {
+    pub warnings: usize,
+    pub violations: usize,
+}
+
+impl CheckOutcome {
+    /// Convert the tally into a result, failing with the matching exit code.
+    ///
+    /// Without `max_warnings` any warning fails the check.
+    pub fn finish(self, what: &str, max_warnings: Option<usize>) -> Result<()> {
+        let allowed = max_warnings.unwrap_or(0);
+        let code = if self.violations > 0 {
+            EXIT_VIOLATIONS
+        } else if self.warnings > allowed {
+            EXIT_DIFFERENCES
+        } else {
+            return Ok(());
+        };
+        let mut summary = format!(
+            "{}: {} violation(s), {} warning(s)",
+            what, self.violations, self.warnings
+        );
+        if let Some(max) = max_warnings {
+            summary.push_str(&format!(" (max {})", max));
+        }
+        Err(CheckFailed { code, summary }.into())
+    }
+}
+
+/// Print a command failure and map it to the documented exit status.
+pub fn report(err: Error) -> ExitCode {
+    if let Some(check) = err.downcast_ref::<CheckFailed>() {
+        eprintln!("{}", check.summary);
+        return ExitCode::from(check.code);
+    }
+    eprintln!("Error: {:?}", err);
+    ExitCode::from(EXIT_ERROR)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn code(outcome: CheckOutcome, max: Option<usize>) -> u8 {
+        match outcome.finish("check", max) {
+            Ok(()) => EXIT_CLEAN,
+            Err(err) => err.downcast_ref::<CheckFailed>().unwrap().code,
+        }
+    }
+
+    #[test]
+    fn outcome_maps_to_exit_codes() {
+        let warned = CheckOutcome {
+            warnings: 2,
+            violations: 0,
+        };
+        assert_eq!(code(CheckOutcome::default(), None), EXIT_CLEAN);
+        assert_eq!(code(warned, None), EXIT_DIFFERENCES);
+        assert_eq!(code(warned, Some(2)), EXIT_CLEAN);
+        let violated = CheckOutcome {
+            violations: 1,
+            ..warned
+        };
+        assert_eq!(code(violated, Some(10)), EXIT_VIOLATIONS);
+    }
+}
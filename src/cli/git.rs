@@ -0,0 +1,124 @@
+//! Git integration helpers (`punch git-textconv`, `punch git-merge`).
+//!
+//! Wire them up with `.gitattributes` (`*.deck diff=punch merge=punch`) and:
+//!
+//! ```text
+//! [diff "punch"]
+//!     textconv = punch git-textconv
+//! [merge "punch"]
+//!     name = punch card-level merge
+//!     driver = punch git-merge %O %A %B
+//! ```
+
+use std::io::{self, Write};
+use std::path::PathBuf;
+
+use anyhow::{Result, anyhow};
+use clap::Args;
+use punchcard::{merge3, merge3_header};
+
+use crate::cli::utils::load_deck;
+
+/// Arguments for `punch git-textconv`.
+#[derive(Args, Debug)]
+pub struct GitTextconvArgs {
+    /// Deck file handed over by git.
+    pub file: PathBuf,
+}
+
+/// Arguments for `punch git-merge`.
+#[derive(Args, Debug)]
+pub struct GitMergeArgs {
+    /// Common ancestor version (`%O`).
+    pub base: PathBuf,
+    /// Current branch version (`%A`); receives the merge result.
+    pub ours: PathBuf,
+    /// Other branch version (`%B`).
+    pub theirs: PathBuf,
+}
+
+/// Print a stable, diff-friendly listing of a deck for `git diff`.
+pub fn textconv(args: GitTextconvArgs) -> Result<()> {
+    let deck = load_deck(args.file.as_path())?;
+    let stdout = io::stdout();
+    let mut out = stdout.lock();
+    let header = &deck.header;
+    writeln!(
+        out,
+        "# language: {}",
+        header.language.as_deref().unwrap_or("-")
+    )?;
+    writeln!(
+        out,
+        "# template: {}",
+        header.template.as_deref().unwrap_or("-")
+    )?;
+    if !header.protected_cols.is_empty() {
+        let ranges: Vec<String> = header
+            .protected_cols
+            .iter()
+            .map(|r| format!("{}-{}", r.start, r.end))
+            .collect();
+        writeln!(out, "# protected: {}", ranges.join(", "))?;
+    }
+    if header.readonly {
+        writeln!(out, "# readonly")?;
+    }
+    if let Some(description) = &header.description {
+        writeln!(out, "# description: {}", description)?;
+    }
+    for line in header.notes.iter().flat_map(|n| n.lines()) {
+        writeln!(out, "# note: {}", line)?;
+    }
+    for (idx, card) in deck.cards.iter().enumerate() {
+        let text = deck.card_text(idx)?;
+        write!(out, "{:04} {:<9} |{}|", idx + 1, card.card_type, text)?;
+        if let Some(color) = &card.meta.color {
+            write!(out, " color={}", color)?;
+        }
+        if let Some(note) = &card.meta.note {
+            write!(out, " note={:?}", note)?;
+        }
+        writeln!(out)?;
+    }
+    Ok(())
+}
+
+/// Three-way card-level merge driver; writes the result over `ours`.
+pub fn merge(args: GitMergeArgs) -> Result<()> {
+    let base = load_deck(args.base.as_path())?;
+    let mut ours = load_deck(args.ours.as_path())?;
+    let theirs = load_deck(args.theirs.as_path())?;
+
+    let outcome = merge3(&base.cards, &ours.cards, &theirs.cards);
+    ours.cards = outcome.cards;
+    let header = merge3_header(&base.header, &ours.header, &theirs.header)?;
+    ours.header = header.header;
+    ours.log_action(format!(
+        "git merge ({} conflict(s))",
+        outcome.conflicts + header.conflicts.len()
+    ));
+    // `ours` is git's temporary file: leave no journal or audit sidecar.
+    ours.save_detached(&args.ours)?;
+    let mut problems = Vec::new();
+    if outcome.conflicts > 0 {
+        problems.push(format!(
+            "{} conflicting region(s) left as marker cards",
+            outcome.conflicts
+        ));
+    }
+    if !header.conflicts.is_empty() {
+        problems.push(format!(
+            "header field(s) {} changed on both sides (ours kept)",
+            header.conflicts.join(", ")
+        ));
+    }
+    if !problems.is_empty() {
+        return Err(anyhow!(
+            "{} in {}",
+            problems.join("; "),
+            args.ours.display()
+        ));
+    }
+    Ok(())
+}
@@ -0,0 +1,125 @@
+//! Serial card reader/punch commands (`punch hardware ...`, feature `hardware`).
+
+use std::path::PathBuf;
+
+use anyhow::{Context, Result};
+use clap::{Args, Subcommand, ValueEnum};
+use punchcard::hardware::{CardProtocol, HexLineProtocol, TextLineProtocol, open_port};
+use punchcard::{Deck, DeckHeader};
+
+use crate::cli::utils::{load_deck, save_deck};
+
+/// Hardware subcommands.
+#[derive(Subcommand, Debug)]
+pub enum HardwareCommand {
+    /// Read cards from a serial card reader into a new deck.
+    Read(HardwareReadArgs),
+    /// Send a deck to a serial card punch.
+    Punch(HardwarePunchArgs),
+}
+
+/// Wire protocols understood by the built-in drivers.
+#[derive(ValueEnum, Debug, Clone, Copy)]
+pub enum ProtocolArg {
+    /// One line of interpreted text per card.
+    Text,
+    /// One line of 240 hex digits (12-bit mask per column) per card.
+    Hex,
+}
+
+impl ProtocolArg {
+    fn driver(self) -> Box<dyn CardProtocol> {
+        match self {
+            ProtocolArg::Text => Box::new(TextLineProtocol),
+            ProtocolArg::Hex => Box::new(HexLineProtocol),
+        }
+    }
+}
+
+/// Arguments for `punch hardware read`.
+#[derive(Args, Debug)]
+pub struct HardwareReadArgs {
+    /// Serial device path, e.g. /dev/ttyUSB0.
+    #[arg(long)]
+    pub port: PathBuf,
+    /// Output deck file.
+    #[arg(short = 'o', long = "output")]
+    pub output: PathBuf,
+    /// Wire protocol spoken by the reader.
+    #[arg(long, default_value_t = ProtocolArg::Hex, value_enum)]
+    pub protocol: ProtocolArg,
+    /// Stop after this many cards (default: until the hopper is empty).
+    #[arg(long)]
+    pub count: Option<usize>,
+}
+
+/// Arguments for `punch hardware punch`.
+#[derive(Args, Debug)]
+pub struct HardwarePunchArgs {
+    /// Deck file to punch.
+    pub deck: PathBuf,
+    /// Serial device path, e.g. /dev/ttyUSB0.
+    #[arg(long)]
+    pub port: PathBuf,
+    /// Wire protocol spoken by the punch.
+    #[arg(long, default_value_t = ProtocolArg::Hex, value_enum)]
+    pub protocol: ProtocolArg,
+}
+
+/// Execute a hardware command.
+pub fn handle(command: HardwareCommand) -> Result<()> {
+    match command {
+        HardwareCommand::Read(args) => read(args),
+        HardwareCommand::Punch(args) => punch(args),
+    }
+}
+
+fn read(args: HardwareReadArgs) -> Result<()> {
+    let (mut reader, _) = open_port(&args.port)?;
+    let mut driver = args.protocol.driver();
+    let mut deck = Deck::new(DeckHeader::new(None, None, Vec::new()));
+    while args.count.is_none_or(|limit| deck.cards.len() < limit) {
+        let card = driver
+            .read_card(&mut reader)
+            .with_context(|| format!("failed to read card {}", deck.cards.len() + 1))?;
+        match card {
+            Some(card) => deck.cards.push(card),
+            None => break,
+        }
+    }
+    deck.log_action(format!(
+        "hardware read {} via {}",
+        args.port.display(),
+        driver.name()
+    ));
+    save_deck(&mut deck, &args.output)?;
+    println!(
+        "Read {} card(s) from {} into {}",
+        deck.cards.len(),
+        args.port.display(),
+        args.output.display()
+    );
+    Ok(())
+}
+
+fn punch(args: HardwarePunchArgs) -> Result<()> {
+    let deck = load_deck(args.deck.as_path())?;
+    let (_, mut writer) = open_port(&args.port)?;
+    let mut driver = args.protocol.driver();
+    let encoder = deck.encoder()?;
+    for (idx, record) in deck.cards.iter().enumerate() {
+        let card = record
+            .to_punch_card(encoder)
+            .with_context(|| format!("failed to encode card {}", idx + 1))?;
+        driver
+            .punch_card(&mut writer, &card)
+            .with_context(|| format!("failed to punch card {}", idx + 1))?;
+    }
+    println!(
+        "Punched {} card(s) from {} on {}",
+        deck.cards.len(),
+        args.deck.display(),
+        args.port.display()
+    );
+    Ok(())
+}
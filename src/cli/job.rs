@@ -0,0 +1,71 @@
+//! Job submission commands (`punch job ...`).
+
+use std::path::PathBuf;
+
+use anyhow::Result;
+use clap::{Args, Subcommand};
+use punchcard::{JobOptions, assemble_job};
+
+use crate::cli::utils::{load_deck, save_deck};
+
+/// Job subcommands.
+#[derive(Subcommand, Debug)]
+pub enum JobCommand {
+    /// Combine JCL, program and data decks into one submittable job deck.
+    Assemble(JobAssembleArgs),
+}
+
+/// Arguments for `punch job assemble`.
+#[derive(Args, Debug)]
+pub struct JobAssembleArgs {
+    /// Deck holding the JOB and EXEC statements.
+    #[arg(long)]
+    pub jcl: PathBuf,
+    /// Program source deck.
+    #[arg(long)]
+    pub program: Option<PathBuf>,
+    /// Input data deck.
+    #[arg(long)]
+    pub data: Option<PathBuf>,
+    /// Output job deck.
+    #[arg(short = 'o', long = "output")]
+    pub output: PathBuf,
+    /// DD statement inserted before the program when the JCL does not end with one.
+    #[arg(long, default_value_t = JobOptions::default().program_dd)]
+    pub program_dd: String,
+    /// DD statement inserted before the data when the data deck does not start with one.
+    #[arg(long, default_value_t = JobOptions::default().data_dd)]
+    pub data_dd: String,
+}
+
+/// Execute a job command.
+pub fn handle(command: JobCommand) -> Result<()> {
+    match command {
+        JobCommand::Assemble(args) => assemble(args),
+    }
+}
+
+fn assemble(args: JobAssembleArgs) -> Result<()> {
+    let jcl = load_deck(args.jcl.as_path())?;
+    let program = args.program.as_deref().map(load_deck).transpose()?;
+    let data = args.data.as_deref().map(load_deck).transpose()?;
+    let options = JobOptions {
+        program_dd: args.program_dd,
+        data_dd: args.data_dd,
+    };
+    let mut job = assemble_job(&jcl, program.as_ref(), data.as_ref(), &options)?;
+    let inputs: Vec<String> = [Some(&args.jcl), args.program.as_ref(), args.data.as_ref()]
+        .into_iter()
+        .flatten()
+        .map(|p| p.display().to_string())
+        .collect();
+    job.log_action(format!("job assemble {}", inputs.join(" + ")));
+    save_deck(&mut job, &args.output)?;
+    println!(
+        "Assembled {} cards from {} deck(s) into {}",
+        job.cards.len(),
+        inputs.len(),
+        args.output.display()
+    );
+    Ok(())
+}
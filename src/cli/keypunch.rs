@@ -0,0 +1,136 @@
+//! Keypunch emulation (`punch keypunch`).
+
+use std::fs;
+use std::io::{self, BufRead, Write};
+use std::path::PathBuf;
+
+use anyhow::{Context, Result};
+use clap::Args;
+use punchcard::{CardRecord, CardType, DrumCard, EncodingKind, Keypunch, TemplateRegistry};
+
+use crate::cli::common::{CardTypeArg, ForceArg};
+use crate::cli::utils::{allow_edit, load_deck, save_deck};
+
+/// Arguments for `punch keypunch`.
+#[derive(Args, Debug)]
+pub struct KeypunchArgs {
+    /// Deck file the punched cards are appended to.
+    pub deck: PathBuf,
+    /// Program card file: its first line is the drum card, punched with
+    /// `&` (field), `-` (skip), `0` (dup) and `1`/`A`/`J`/`/` (alphabetic).
+    #[arg(long, value_name = "FILE")]
+    pub drum: Option<PathBuf>,
+    /// Derive the drum card from a template (name or TOML file).
+    #[arg(long, conflicts_with = "drum")]
+    pub template: Option<String>,
+    /// Card type of the punched cards (defaults to the template's, or code).
+    #[arg(long = "type", value_enum)]
+    pub card_type: Option<CardTypeArg>,
+    /// Print the drum card and its fields, then stop.
+    #[arg(long = "show-drum")]
+    pub show_drum: bool,
+    #[command(flatten)]
+    pub force: ForceArg,
+}
+
+/// Key cards from stdin, one line per card: Tab is the SKIP key and the end
+/// of the line the REL key.
+pub fn handle(args: KeypunchArgs) -> Result<()> {
+    let template = args
+        .template
+        .as_deref()
+        .map(|name| {
+            TemplateRegistry::get(name).with_context(|| format!("template '{}' not found", name))
+        })
+        .transpose()?;
+    let drum = match (&args.drum, template) {
+        (Some(path), _) => {
+            let body = fs::read_to_string(path)
+                .with_context(|| format!("failed to read {}", path.display()))?;
+            DrumCard::parse(body.lines().next().unwrap_or(""))
+                .with_context(|| format!("invalid drum card {}", path.display()))?
+        }
+        (None, Some(template)) => DrumCard::from_template(template),
+        (None, None) => DrumCard::free(),
+    };
+    if args.show_drum {
+        println!("{}", drum.text());
+        for field in &drum.fields {
+            println!(
+                "  {:>2}-{:<2}  {:<6}  {}",
+                field.range.start,
+                field.range.end,
+                field.action,
+                if field.alpha { "alpha" } else { "numeric" }
+            );
+        }
+        return Ok(());
+    }
+
+    let mut deck = load_deck(args.deck.as_path())?;
+    allow_edit(&mut deck, args.force, "keypunch")?;
+    let card_type: CardType = match (args.card_type, template) {
+        (Some(kind), _) => kind.into(),
+        (None, Some(template)) => template.default_type.clone(),
+        (None, None) => CardType::Code,
+    };
+    let mut punch = Keypunch::new(drum)?;
+    println!(
+        "Keying into {} (Tab skips a field, Enter releases the card, end of input stops)",
+        args.deck.display()
+    );
+
+    let stdin = io::stdin();
+    let mut lines = stdin.lock().lines();
+    let mut out = io::stdout();
+    let mut keyed = 0;
+    loop {
+        write!(out, "card {} col {}> ", keyed + 1, punch.column())?;
+        out.flush()?;
+        let Some(line) = lines.next().transpose()? else {
+            println!();
+            break;
+        };
+        let ejected = punch.cards().len();
+        let mut refused = None;
+        for ch in line.chars() {
+            let result = match ch {
+                '\t' => {
+                    punch.skip();
+                    Ok(())
+                }
+                ch => punch.key(ch),
+            };
+            if let Err(err) = result {
+                refused = Some(err);
+                break;
+            }
+        }
+        if let Some(err) = refused {
+            println!("  {:#}; card thrown out, key it again", err);
+            punch.restart();
+        } else if punch.cards().len() == ejected || !punch.is_fresh() {
+            punch.release();
+        }
+        for text in punch.take_cards() {
+            let appended = CardRecord::from_text(&text, EncodingKind::Hollerith, card_type.clone())
+                .and_then(|record| deck.append_card(record));
+            match appended {
+                Ok(()) => {
+                    keyed += 1;
+                    println!("  {:>4} |{}", keyed, text);
+                }
+                Err(err) => println!("  {:#}; card thrown out, key it again", err),
+            }
+        }
+    }
+
+    if keyed == 0 {
+        println!("No cards keyed");
+        return Ok(());
+    }
+    deck.log_action(format!("keypunch {} card(s)", keyed));
+    save_deck(&mut deck, &args.deck)?;
+    println!("Punched {} card(s) into {}", keyed, args.deck.display());
+    Ok(())
+}
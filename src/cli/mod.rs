@@ -7,15 +7,29 @@ use anyhow::Result;
 use clap::{Parser, Subcommand};
 
 pub mod audit;
+pub mod bench;
 pub mod card;
 pub mod common;
 pub mod deck;
 pub mod encode;
+pub mod exit;
+pub mod git;
+#[cfg(feature = "hardware")]
+pub mod hardware;
+pub mod job;
+pub mod keypunch;
+pub mod notch;
+pub mod pipeline;
+pub mod play;
+pub mod read;
+pub mod reconcile;
 pub mod render;
 pub mod seq;
 pub mod template;
+pub mod trainer;
 pub mod utils;
 pub mod verify;
+pub mod workspace;
 
 /// Parsed CLI entrypoint for the `punch` binary.
 #[derive(Parser, Debug)]
@@ -45,6 +59,34 @@ pub enum Command {
     Audit(audit::AuditCommand),
     #[command(subcommand)]
     Verify(verify::VerifyCommand),
+    #[command(subcommand)]
+    Job(job::JobCommand),
+    #[command(subcommand)]
+    Pipeline(pipeline::PipelineCommand),
+    #[command(subcommand)]
+    Notch(notch::NotchCommand),
+    #[command(subcommand)]
+    Workspace(workspace::WorkspaceCommand),
+    /// Measure encode, listing, save/load and render throughput.
+    Bench(bench::BenchArgs),
+    /// Animate a deck in the terminal like a card reader feeding cards.
+    Play(play::PlayArgs),
+    /// Key cards on an emulated 029 keypunch under a program drum card.
+    Keypunch(keypunch::KeypunchArgs),
+    /// Drill Hollerith codes: name the character for a hole pattern, or punch a character.
+    Trainer(trainer::TrainerArgs),
+    /// Emit a deck as a card reader's output stream, optionally with injected faults.
+    Read(read::ReadArgs),
+    /// Align two keyings of the same deck and report where they disagree.
+    Reconcile(reconcile::ReconcileArgs),
+    /// Print a diff-friendly deck listing (git textconv driver).
+    GitTextconv(git::GitTextconvArgs),
+    /// Three-way card-level merge of deck files (git merge driver).
+    GitMerge(git::GitMergeArgs),
+    /// Talk to serial card readers and punches.
+    #[cfg(feature = "hardware")]
+    #[command(subcommand)]
+    Hardware(hardware::HardwareCommand),
 }
 
 /// Execute the requested command.
@@ -58,5 +100,19 @@ pub fn run(cli: Cli) -> Result<()> {
         Command::Encode(cmd) => encode::handle(cmd),
         Command::Audit(cmd) => audit::handle(cmd),
         Command::Verify(cmd) => verify::handle(cmd),
+        Command::Job(cmd) => job::handle(cmd),
+        Command::Pipeline(cmd) => pipeline::handle(cmd),
+        Command::Notch(cmd) => notch::handle(cmd),
+        Command::Workspace(cmd) => workspace::handle(cmd),
+        Command::Bench(args) => bench::handle(args),
+        Command::Play(args) => play::handle(args),
+        Command::Keypunch(args) => keypunch::handle(args),
+        Command::Trainer(args) => trainer::handle(args),
+        Command::Read(args) => read::handle(args),
+        Command::Reconcile(args) => reconcile::handle(args),
+        Command::GitTextconv(args) => git::textconv(args),
+        Command::GitMerge(args) => git::merge(args),
+        #[cfg(feature = "hardware")]
+        Command::Hardware(cmd) => hardware::handle(cmd),
     }
 }
@@ -0,0 +1,195 @@
+//! Edge-notched card commands (`punch notch ...`).
+
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result, ensure};
+use clap::{Args, Subcommand};
+use punchcard::{CardRecord, CardType, Deck, EncodingKind, Keep, NotchLayout};
+
+use crate::cli::common::ForceArg;
+use crate::cli::utils::{allow_edit, load_deck, save_deck};
+
+/// Supported `punch notch` subcommands.
+#[derive(Subcommand, Debug)]
+pub enum NotchCommand {
+    /// Append an edge-notched card described by its notches.
+    Add(NotchAddArgs),
+    /// Draw notched cards with their hole positions.
+    Show(NotchShowArgs),
+    /// Simulate needle-sorting the deck and list the selected cards.
+    Sort(NotchSortArgs),
+}
+
+/// Layout selection shared by the notch commands.
+#[derive(Args, Debug)]
+pub struct LayoutArgs {
+    /// TOML notch layout (defaults to a plain 56-hole keysort card).
+    #[arg(long, value_name = "FILE")]
+    pub layout: Option<PathBuf>,
+}
+
+impl LayoutArgs {
+    fn resolve(&self) -> Result<NotchLayout> {
+        match &self.layout {
+            Some(path) => NotchLayout::load(path),
+            None => Ok(NotchLayout::keysort()),
+        }
+    }
+}
+
+/// Arguments for `punch notch add`.
+#[derive(Args, Debug)]
+pub struct NotchAddArgs {
+    /// Deck file to modify.
+    pub deck: PathBuf,
+    /// Notches as FIELD=VALUE[,VALUE] and #HOLE items, e.g. "subject=optics year=7".
+    pub notches: String,
+    #[command(flatten)]
+    pub layout: LayoutArgs,
+    /// Optional human note.
+    #[arg(long)]
+    pub note: Option<String>,
+    #[command(flatten)]
+    pub force: ForceArg,
+}
+
+/// Arguments for `punch notch show`.
+#[derive(Args, Debug)]
+pub struct NotchShowArgs {
+    /// Deck of notched cards.
+    pub deck: PathBuf,
+    #[command(flatten)]
+    pub layout: LayoutArgs,
+    /// 1-based card to draw (defaults to every card).
+    #[arg(short = 'i', long = "index")]
+    pub index: Option<usize>,
+}
+
+/// Arguments for `punch notch sort`.
+#[derive(Args, Debug)]
+pub struct NotchSortArgs {
+    /// Deck of notched cards.
+    pub deck: PathBuf,
+    /// Terms every selected card must match, e.g. "subject=optics year=7".
+    pub query: String,
+    #[command(flatten)]
+    pub layout: LayoutArgs,
+    /// Write the selected cards to a new deck.
+    #[arg(short = 'o', long = "output")]
+    pub output: Option<PathBuf>,
+}
+
+/// Execute a notch command.
+pub fn handle(command: NotchCommand) -> Result<()> {
+    match command {
+        NotchCommand::Add(args) => add(args),
+        NotchCommand::Show(args) => show(args),
+        NotchCommand::Sort(args) => sort(args),
+    }
+}
+
+fn add(args: NotchAddArgs) -> Result<()> {
+    let layout = args.layout.resolve()?;
+    let holes = layout.notches(&args.notches)?;
+    let mut deck = load_deck(args.deck.as_path())?;
+    allow_edit(&mut deck, args.force, "notch add")?;
+    let mut record =
+        CardRecord::from_text(args.notches.trim(), EncodingKind::Hollerith, CardType::Data)
+            .context("notch text must fit one card")?;
+    record.meta.note = args.note;
+    deck.append_card(record)?;
+    deck.log_action(format!("notch add ({} notches)", holes.len()));
+    save_deck(&mut deck, &args.deck)?;
+    println!(
+        "Added a {} card with {} notch(es) to {}",
+        layout.name,
+        holes.len(),
+        args.deck.display()
+    );
+    Ok(())
+}
+
+fn show(args: NotchShowArgs) -> Result<()> {
+    let layout = args.layout.resolve()?;
+    let deck = load_deck(args.deck.as_path())?;
+    let cards = layout.deck_notches(&deck)?;
+    let shown: Vec<usize> = match args.index {
+        Some(index) => {
+            ensure!(
+                (1..=cards.len()).contains(&index),
+                "card {} out of range 1..={}",
+                index,
+                cards.len()
+            );
+            vec![index - 1]
+        }
+        None => (0..cards.len()).collect(),
+    };
+    for (n, idx) in shown.into_iter().enumerate() {
+        if n > 0 {
+            println!();
+        }
+        let holes: Vec<String> = cards[idx].iter().map(|h| h.to_string()).collect();
+        println!(
+            "Card {} ({}): notched {}",
+            idx + 1,
+            deck.cards[idx].text.as_deref().unwrap_or_default().trim(),
+            if holes.is_empty() {
+                "-".to_string()
+            } else {
+                holes.join(", ")
+            }
+        );
+        print!("{}", layout.render(&cards[idx]));
+    }
+    Ok(())
+}
+
+fn sort(args: NotchSortArgs) -> Result<()> {
+    let layout = args.layout.resolve()?;
+    let deck = load_deck(args.deck.as_path())?;
+    let result = layout.needle_sort(&deck, &args.query)?;
+    for (n, pass) in result.passes.iter().enumerate() {
+        let keep = match pass.needle.keep {
+            Keep::Dropped => "keep dropped",
+            Keep::Retained => "keep retained",
+        };
+        println!(
+            "pass {}: needle hole {:>3}: {} dropped, {} retained -> {}",
+            n + 1,
+            pass.needle.hole,
+            pass.dropped,
+            pass.retained,
+            keep
+        );
+    }
+    println!(
+        "Selected {} of {} card(s) in {} pass(es)",
+        result.selected.len(),
+        deck.cards.len(),
+        result.passes.len()
+    );
+    for idx in &result.selected {
+        println!(
+            "  card {:>4}: {}",
+            idx + 1,
+            deck.cards[*idx].text.as_deref().unwrap_or_default().trim()
+        );
+    }
+    if let Some(output) = args.output {
+        write_selection(&deck, &result.selected, &args.query, &output)?;
+    }
+    Ok(())
+}
+
+fn write_selection(deck: &Deck, selected: &[usize], query: &str, output: &Path) -> Result<()> {
+    let mut picked = deck.slice_indices(selected)?;
+    picked.log_action(format!("notch sort \"{}\"", query));
+    save_deck(&mut picked, output)?;
+    println!(
+        "Wrote {} selected card(s) to {}",
+        picked.cards.len(),
+        output.display()
+    );
+    Ok(())
+}
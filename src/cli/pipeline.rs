@@ -0,0 +1,57 @@
+//! Deck preparation pipelines (`punch pipeline ...`).
+
+use std::path::PathBuf;
+
+use anyhow::Result;
+use clap::{Args, Subcommand};
+use punchcard::pipeline::Pipeline;
+
+use crate::cli::common::ForceArg;
+use crate::cli::utils::{allow_edit, load_deck, save_deck};
+
+/// Pipeline subcommands.
+#[derive(Subcommand, Debug)]
+pub enum PipelineCommand {
+    /// Apply the steps of a TOML pipeline file to a deck.
+    Run(PipelineRunArgs),
+}
+
+/// Arguments for `punch pipeline run`.
+#[derive(Args, Debug)]
+pub struct PipelineRunArgs {
+    /// Pipeline definition (TOML).
+    pub pipeline: PathBuf,
+    /// Deck file to transform.
+    pub deck: PathBuf,
+    /// Write the result here instead of updating the deck in place.
+    #[arg(short = 'o', long = "output")]
+    pub output: Option<PathBuf>,
+    #[command(flatten)]
+    pub force: ForceArg,
+}
+
+/// Dispatch pipeline subcommands.
+pub fn handle(cmd: PipelineCommand) -> Result<()> {
+    match cmd {
+        PipelineCommand::Run(args) => run(args),
+    }
+}
+
+fn run(args: PipelineRunArgs) -> Result<()> {
+    let pipeline = Pipeline::load(&args.pipeline)?;
+    let mut deck = load_deck(args.deck.as_path())?;
+    allow_edit(&mut deck, args.force, "pipeline run")?;
+    let applied = pipeline.run(&mut deck)?;
+    let target = args.output.unwrap_or(args.deck);
+    save_deck(&mut deck, &target)?;
+    for step in &applied {
+        println!("  {}", step);
+    }
+    println!(
+        "Ran pipeline {} ({} step(s)) into {}",
+        pipeline.name,
+        applied.len(),
+        target.display()
+    );
+    Ok(())
+}
@@ -0,0 +1,57 @@
+//! Terminal deck playback (`punch play ...`).
+
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::thread;
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use clap::Args;
+
+use crate::cli::common::RenderStyleArg;
+use crate::cli::utils::load_deck;
+
+/// ANSI sequence that clears the screen and homes the cursor.
+const CLEAR_SCREEN: &str = "\x1b[2J\x1b[H";
+
+/// Arguments for `punch play`.
+#[derive(Args, Debug)]
+pub struct PlayArgs {
+    /// Deck file to play back.
+    pub deck: PathBuf,
+    /// Reader speed in cards per minute (e.g. 300 for a 2540 at half speed).
+    #[arg(long, default_value_t = 300, value_parser = clap::value_parser!(u32).range(1..=6000))]
+    pub cpm: u32,
+    /// Rendering style for each card.
+    #[arg(long, default_value_t = RenderStyleArg::AsciiX, value_enum)]
+    pub style: RenderStyleArg,
+    /// Keep the previous cards on screen instead of clearing between cards.
+    #[arg(long = "no-clear")]
+    pub no_clear: bool,
+}
+
+/// Animate the deck in the terminal, one card at a time.
+pub fn handle(args: PlayArgs) -> Result<()> {
+    let deck = load_deck(args.deck.as_path())?;
+    let encoder = deck.encoder()?;
+    let punch_deck = deck
+        .to_punch_deck(encoder)
+        .with_context(|| format!("failed to render deck with {} encoder", encoder.name()))?;
+    let delay = Duration::from_millis(60_000 / u64::from(args.cpm));
+    let total = punch_deck.cards.len();
+    let stdout = io::stdout();
+    let mut out = stdout.lock();
+    for (idx, card) in punch_deck.cards.iter().enumerate() {
+        if !args.no_clear {
+            write!(out, "{}", CLEAR_SCREEN)?;
+        }
+        writeln!(out, "Card {} of {} @ {} cpm", idx + 1, total, args.cpm)?;
+        write!(out, "{}", card.render(args.style.into()))?;
+        out.flush()?;
+        if idx + 1 < total {
+            thread::sleep(delay);
+        }
+    }
+    writeln!(out, "Played {} card(s) from {}", total, args.deck.display())?;
+    Ok(())
+}
@@ -0,0 +1,91 @@
+//! Two-keying reconciliation (`punch reconcile ...`).
+
+use std::path::PathBuf;
+
+use anyhow::Result;
+use clap::Args;
+use punchcard::{ColumnRange, SEQUENCE_FIELD, reconcile};
+
+use crate::cli::utils::{load_deck, parse_column_range, save_deck};
+
+/// Arguments for `punch reconcile`.
+#[derive(Args, Debug)]
+pub struct ReconcileArgs {
+    /// First keying of the deck.
+    pub a: PathBuf,
+    /// Second keying of the same deck.
+    pub b: PathBuf,
+    /// Columns holding the key cards are aligned on.
+    #[arg(long = "key-cols", value_parser = parse_column_range, default_value = "73-80")]
+    pub key_cols: ColumnRange,
+    /// Write the consensus deck (disputed cards flagged red) to this file.
+    #[arg(short = 'o', long = "output")]
+    pub output: Option<PathBuf>,
+}
+
+/// Compare two keyings and optionally write their consensus deck.
+pub fn handle(args: ReconcileArgs) -> Result<()> {
+    let a = load_deck(args.a.as_path())?;
+    let b = load_deck(args.b.as_path())?;
+    let mut result = reconcile(&a, &b, args.key_cols)?;
+    let key_desc = if args.key_cols == SEQUENCE_FIELD {
+        "the sequence field".to_string()
+    } else {
+        format!("columns {}-{}", args.key_cols.start, args.key_cols.end)
+    };
+    println!(
+        "Aligned {} card(s) on {}; {} only in A, {} only in B",
+        result.matched,
+        key_desc,
+        result.only_a.len(),
+        result.only_b.len()
+    );
+    for key in &result.only_a {
+        println!("  only in A: key '{}'", key);
+    }
+    for key in &result.only_b {
+        println!("  only in B: key '{}'", key);
+    }
+    println!("Disputed cards: {}", result.disputed.len());
+    for dispute in &result.disputed {
+        let cols: Vec<String> = dispute.columns.iter().map(|c| c.to_string()).collect();
+        println!(
+            "  key '{}' (consensus card {}): columns {}",
+            dispute.key,
+            dispute.card + 1,
+            cols.join(", ")
+        );
+    }
+    let weak: Vec<usize> = (1..=80)
+        .filter(|col| result.agreement[col - 1] < result.matched)
+        .collect();
+    if weak.is_empty() {
+        println!("Column agreement: 100% in every column");
+    } else {
+        println!("Column agreement below 100%:");
+        for col in weak {
+            println!(
+                "  col {:>2}: {:>5.1}% ({}/{})",
+                col,
+                result.agreement_pct(col),
+                result.agreement[col - 1],
+                result.matched
+            );
+        }
+    }
+    if let Some(output) = args.output {
+        result.consensus.log_action(format!(
+            "reconcile {} {} ({} disputed)",
+            args.a.display(),
+            args.b.display(),
+            result.disputed.len()
+        ));
+        save_deck(&mut result.consensus, &output)?;
+        println!(
+            "Wrote consensus deck with {} card(s) to {}",
+            result.consensus.cards.len(),
+            output.display()
+        );
+    }
+    Ok(())
+}
@@ -1,26 +1,51 @@
 //! Rendering commands (`punch render ...`).
 
+use std::borrow::Cow;
 use std::fs;
-use std::path::PathBuf;
+use std::io::Write;
+use std::path::{Path, PathBuf};
 
 use anyhow::{Context, Result, anyhow};
-use clap::{Args, Subcommand};
-use punchcard::{Ibm029Encoder, ImageRenderOptions, render_card_image};
+use clap::{Args, Subcommand, ValueEnum};
+use punchcard::{
+    AlignedCard, AnimationOptions, Captions, CardDeck, CardDiff, CardPainter, CardRecord,
+    ColumnChange, Deck, DeckImageOptions, DeckPdfOptions, FormStyle, ImageRenderOptions,
+    Imposition, IsometricOptions, PatchTarget, PunchCard, PunchEncoding, StockColors, Template,
+    TemplateRegistry, TintedStyle, align_cards, render_card_image_with, render_card_svg,
+    render_deck_gif, render_deck_image, render_deck_isometric, render_deck_isometric_svg,
+    render_deck_pdf, render_deck_svg, render_imposed_pages, render_sheet_pages,
+};
+use rayon::prelude::*;
 
 use crate::cli::utils::load_deck;
 
-use crate::cli::common::{CardImageStyleArg, PageLayoutArg, RenderStyleArg};
-use crate::cli::utils::write_output;
+use crate::cli::common::{
+    CaptionArgs, CardImageStyleArg, CardSideArg, CardTypeArg, CornerCutArg, EncoderArg,
+    ImpositionArg, PageLayoutArg, RenderStyleArg, resolve_encoder,
+};
+use crate::cli::utils::{open_output, stage_output};
 
 /// Available render subcommands.
 #[derive(Subcommand, Debug)]
 pub enum RenderCommand {
     /// Render rich PNG images of the card faces.
     Image(RenderImageArgs),
+    /// Print the deck to a multi-page PDF at true card size.
+    Pdf(RenderPdfArgs),
+    /// Animate the deck as a GIF flipping through the card faces.
+    Animate(RenderAnimateArgs),
     /// Produce interpreter-style listing.
     Interpret(RenderInterpretArgs),
     /// Emit a card-by-card textual listing.
     Listing(RenderListingArgs),
+    /// Publish the deck as a small static website (index, SVG cards, listing).
+    Site(RenderSiteArgs),
+    /// Publish two decks side by side as a static review page with column diffs.
+    Compare(RenderCompareArgs),
+    /// Stack the whole deck into one tall PNG or SVG image.
+    Stack(RenderStackArgs),
+    /// Draw the deck edge-on as an isometric stack (PNG or SVG).
+    Isometric(RenderIsometricArgs),
 }
 
 /// Args for `punch render image`.
@@ -40,6 +65,55 @@ pub struct RenderImageArgs {
     /// Dots per inch used when rasterising.
     #[arg(long, default_value_t = 300)]
     pub dpi: u32,
+    /// Print three cards per A4 sheet in cut-stack order (duplex adds numbered backs).
+    #[arg(long, value_enum)]
+    pub impose: Option<ImpositionArg>,
+    /// Paginate the deck in order, this many cards per A4 or Letter page with cut marks.
+    #[arg(long, conflicts_with = "impose")]
+    pub per_page: Option<usize>,
+    /// Card face to draw; the back shows the holes mirrored and nothing printed.
+    #[arg(long, default_value_t = CardSideArg::Front, value_enum)]
+    pub side: CardSideArg,
+    /// Corner cut as seen from the front.
+    #[arg(long = "corner-cut", default_value_t = CornerCutArg::None, value_enum)]
+    pub corner_cut: CornerCutArg,
+    /// Stock colour for a card type, e.g. jcl=pink or data=#c9dcf2 (`none` for plain).
+    #[arg(long = "stock", value_name = "TYPE=COLOR", value_parser = parse_stock_override)]
+    pub stock: Vec<(CardTypeArg, String)>,
+    /// Draw every card on the style's own stock, ignoring card colours and types.
+    #[arg(long = "plain-stock", conflicts_with = "stock")]
+    pub plain_stock: bool,
+    /// Print the deck template's fields on the card like a pre-printed coding form.
+    #[arg(long)]
+    pub form: bool,
+    /// Render this many cards at once (defaults to one per CPU).
+    #[arg(short = 'j', long, value_parser = clap::value_parser!(u16).range(1..))]
+    pub jobs: Option<u16>,
+    /// Punch chart used to encode card text (defaults to the deck's encoder).
+    #[arg(long, value_enum)]
+    pub encoder: Option<EncoderArg>,
+}
+
+/// Args for `punch render pdf`.
+#[derive(Args, Debug)]
+pub struct RenderPdfArgs {
+    /// Deck file to print.
+    pub deck: PathBuf,
+    /// Output PDF file (`-` for stdout).
+    #[arg(short = 'o', long = "output")]
+    pub output: PathBuf,
+    /// Visual style applied to the card faces.
+    #[arg(long, default_value_t = CardImageStyleArg::Interpreter, value_enum)]
+    pub style: CardImageStyleArg,
+    /// Page size: one card-sized page per card, or cards stacked on A4 or Letter sheets.
+    #[arg(long = "pagesize", default_value_t = PageLayoutArg::A4, value_enum)]
+    pub pagesize: PageLayoutArg,
+    /// Cards per sheet (defaults to as many as fit, three).
+    #[arg(long)]
+    pub per_page: Option<usize>,
+    /// Punch chart used to encode card text (defaults to the deck's encoder).
+    #[arg(long, value_enum)]
+    pub encoder: Option<EncoderArg>,
 }
 
 /// Args for `punch render interpret`.
@@ -53,6 +127,17 @@ pub struct RenderInterpretArgs {
     /// Rendering style.
     #[arg(long, default_value_t = RenderStyleArg::AsciiX, value_enum)]
     pub style: RenderStyleArg,
+    /// Mark columns matching this text (case-insensitive) beneath each card.
+    #[arg(long)]
+    pub highlight: Option<String>,
+    /// Add a row naming the deck template's fields above the column ruler.
+    #[arg(long)]
+    pub field_ruler: bool,
+    /// Punch chart used to encode card text (defaults to the deck's encoder).
+    #[arg(long, value_enum)]
+    pub encoder: Option<EncoderArg>,
+    #[command(flatten)]
+    pub captions: CaptionArgs,
 }
 
 /// Args for `punch render listing`.
@@ -63,17 +148,154 @@ pub struct RenderListingArgs {
     /// Output file (`-` for stdout)
     #[arg(short = 'o', long = "output")]
     pub output: Option<PathBuf>,
+    /// Listing layout: plain text, or Markdown/AsciiDoc for documentation.
+    #[arg(long, default_value_t = ListingFormatArg::Text, value_enum)]
+    pub format: ListingFormatArg,
     /// Rendering style for punch visualization.
     #[arg(long, default_value_t = RenderStyleArg::AsciiX, value_enum)]
     pub style: RenderStyleArg,
+    /// Mark columns matching this text (case-insensitive) beneath each card.
+    #[arg(long)]
+    pub highlight: Option<String>,
+    /// Add a row naming the deck template's fields above the column ruler.
+    #[arg(long)]
+    pub field_ruler: bool,
+    /// Punch chart used to encode card text (defaults to the deck's encoder).
+    #[arg(long, value_enum)]
+    pub encoder: Option<EncoderArg>,
+    #[command(flatten)]
+    pub captions: CaptionArgs,
+}
+
+/// Layouts for `punch render listing`.
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ListingFormatArg {
+    /// Plain text.
+    Text,
+    /// A heading, a metadata table and fenced code blocks per card.
+    Markdown,
+    /// The same as Markdown, in AsciiDoc markup.
+    Asciidoc,
+}
+
+/// Args for `punch render site`.
+#[derive(Args, Debug)]
+pub struct RenderSiteArgs {
+    /// Deck file to publish.
+    pub deck: PathBuf,
+    /// Output directory for the generated site.
+    #[arg(short = 'o', long = "output")]
+    pub output: PathBuf,
+    /// Visual style applied to the card SVGs.
+    #[arg(long, default_value_t = CardImageStyleArg::Interpreter, value_enum)]
+    pub style: CardImageStyleArg,
+    /// Page title (defaults to the deck file name).
+    #[arg(long)]
+    pub title: Option<String>,
+    /// Punch chart used to encode card text (defaults to the deck's encoder).
+    #[arg(long, value_enum)]
+    pub encoder: Option<EncoderArg>,
+}
+
+/// Args for `punch render animate`.
+#[derive(Args, Debug)]
+pub struct RenderAnimateArgs {
+    /// Deck file to animate.
+    pub deck: PathBuf,
+    /// Output GIF (`-` for stdout).
+    #[arg(short = 'o', long = "output")]
+    pub output: PathBuf,
+    /// Visual style applied to the card faces.
+    #[arg(long, default_value_t = CardImageStyleArg::Interpreter, value_enum)]
+    pub style: CardImageStyleArg,
+    /// Dots per inch of each frame.
+    #[arg(long, default_value_t = 100)]
+    pub dpi: u32,
+    /// Milliseconds each card stays up.
+    #[arg(long, default_value_t = 500, value_parser = clap::value_parser!(u32).range(10..=60_000))]
+    pub delay: u32,
+    /// Stop on the last card instead of starting over.
+    #[arg(long)]
+    pub once: bool,
+    /// Punch chart used to encode card text (defaults to the deck's encoder).
+    #[arg(long, value_enum)]
+    pub encoder: Option<EncoderArg>,
+}
+
+/// Args for `punch render compare`.
+#[derive(Args, Debug)]
+pub struct RenderCompareArgs {
+    /// Original deck.
+    pub left: PathBuf,
+    /// Deck to compare against it.
+    pub right: PathBuf,
+    /// Output directory for the generated page.
+    #[arg(short = 'o', long = "output")]
+    pub output: PathBuf,
+    /// Visual style applied to the card SVGs.
+    #[arg(long, default_value_t = CardImageStyleArg::Interpreter, value_enum)]
+    pub style: CardImageStyleArg,
+    /// Page title (defaults to the two deck file names).
+    #[arg(long)]
+    pub title: Option<String>,
+    /// Punch chart used to encode card text (defaults to each deck's encoder).
+    #[arg(long, value_enum)]
+    pub encoder: Option<EncoderArg>,
+}
+
+/// Args for `punch render stack`.
+#[derive(Args, Debug)]
+pub struct RenderStackArgs {
+    /// Deck file to render.
+    pub deck: PathBuf,
+    /// Output image (.png or .svg).
+    #[arg(short = 'o', long = "output")]
+    pub output: PathBuf,
+    /// Visual style applied to the card faces.
+    #[arg(long, default_value_t = CardImageStyleArg::Interpreter, value_enum)]
+    pub style: CardImageStyleArg,
+    /// Dots per inch for PNG output.
+    #[arg(long, default_value_t = 150)]
+    pub dpi: u32,
+    /// Space between cards in inches (negative overlaps them).
+    #[arg(long, default_value_t = 0.15, allow_negative_numbers = true)]
+    pub gap: f32,
+    /// Horizontal offset per card in inches for a fanned look.
+    #[arg(long, default_value_t = 0.0)]
+    pub fan: f32,
+    /// Punch chart used to encode card text (defaults to the deck's encoder).
+    #[arg(long, value_enum)]
+    pub encoder: Option<EncoderArg>,
+}
+
+/// Args for `punch render isometric`.
+#[derive(Args, Debug)]
+pub struct RenderIsometricArgs {
+    /// Deck file to draw.
+    pub deck: PathBuf,
+    /// Output image (.png or .svg).
+    #[arg(short = 'o', long = "output")]
+    pub output: PathBuf,
+    /// Pixels per inch.
+    #[arg(long, default_value_t = IsometricOptions::default().scale)]
+    pub scale: f32,
+    /// Card thickness multiplier so small decks stay visible.
+    #[arg(long, default_value_t = IsometricOptions::default().exaggeration)]
+    pub exaggerate: f32,
 }
 
 /// Execute a render command.
 pub fn handle(command: RenderCommand) -> Result<()> {
     match command {
         RenderCommand::Image(args) => image(args),
+        RenderCommand::Pdf(args) => pdf(args),
+        RenderCommand::Animate(args) => animate(args),
         RenderCommand::Interpret(args) => interpret(args),
         RenderCommand::Listing(args) => listing(args),
+        RenderCommand::Site(args) => site(args),
+        RenderCommand::Compare(args) => compare(args),
+        RenderCommand::Stack(args) => stack(args),
+        RenderCommand::Isometric(args) => isometric(args),
     }
 }
 
@@ -84,9 +306,43 @@ fn image(args: RenderImageArgs) -> Result<()> {
         style: args.style.into(),
         dpi,
         layout: args.pagesize.into(),
+        per_page: args.per_page,
+        side: args.side.into(),
+        corner_cut: args.corner_cut.into(),
+    };
+    let form = args
+        .form
+        .then(|| deck_template(&deck, "--form"))
+        .transpose()?;
+    let form_style = form.map(|template| FormStyle {
+        base: &options.style,
+        template,
+    });
+    let page_painter: &dyn CardPainter = match &form_style {
+        Some(form_style) => form_style,
+        None => &options.style,
     };
 
     let output_path = args.output;
+    if let Some(mode) = args.impose {
+        if !matches!(args.pagesize, PageLayoutArg::A4) {
+            return Err(anyhow!("--impose needs --pagesize a4"));
+        }
+        return image_imposed(
+            &deck,
+            &output_path,
+            page_painter,
+            &options,
+            mode.into(),
+            args.encoder,
+        );
+    }
+    if args.per_page.is_some() {
+        if matches!(args.pagesize, PageLayoutArg::Card) {
+            return Err(anyhow!("--per-page needs --pagesize a4 or letter"));
+        }
+        return image_sheets(&deck, &output_path, page_painter, &options, args.encoder);
+    }
     let is_single_file_target = output_path
         .extension()
         .map(|ext| ext.eq_ignore_ascii_case("png"))
@@ -99,12 +355,12 @@ fn image(args: RenderImageArgs) -> Result<()> {
     }
 
     if is_single_file_target {
-        if let Some(parent) = output_path.parent() {
-            if !parent.as_os_str().is_empty() {
-                fs::create_dir_all(parent).with_context(|| {
-                    format!("failed to create output directory {}", parent.display())
-                })?;
-            }
+        if let Some(parent) = output_path.parent()
+            && !parent.as_os_str().is_empty()
+        {
+            fs::create_dir_all(parent).with_context(|| {
+                format!("failed to create output directory {}", parent.display())
+            })?;
         }
     } else {
         fs::create_dir_all(&output_path).with_context(|| {
@@ -115,23 +371,53 @@ fn image(args: RenderImageArgs) -> Result<()> {
         })?;
     }
 
-    let encoder = Ibm029Encoder::new();
-    let punch_deck = deck
-        .to_punch_deck(&encoder)
-        .context("failed to render deck with IBM029 encoder")?;
-
-    for (idx, card) in punch_deck.cards.iter().enumerate() {
-        let target_path = if is_single_file_target {
-            output_path.clone()
-        } else {
-            output_path.join(format!("card_{:04}.png", idx + 1))
-        };
-        let image = render_card_image(card, &options)?;
-        image
-            .save(&target_path)
-            .with_context(|| format!("failed to write {}", target_path.display()))?;
+    let punch_deck = encode_deck(&deck, args.encoder)?;
+    let mut stock = if args.plain_stock {
+        StockColors::plain()
+    } else {
+        StockColors::default()
+    };
+    for (card_type, color) in &args.stock {
+        stock.set((*card_type).into(), color)?;
     }
 
+    // Each card is written as soon as it is drawn, so memory stays at one
+    // image per thread however large the deck.
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(args.jobs.map_or(0, usize::from))
+        .build()
+        .context("failed to start render threads")?;
+    pool.install(|| {
+        punch_deck
+            .cards
+            .par_iter()
+            .enumerate()
+            .try_for_each(|(idx, card)| -> Result<()> {
+                let target_path = if is_single_file_target {
+                    output_path.clone()
+                } else {
+                    output_path.join(format!("card_{:04}.png", idx + 1))
+                };
+                let tinted = TintedStyle {
+                    style: options.style,
+                    stock: stock.tint(&deck.cards[idx]),
+                };
+                let image = match form {
+                    Some(template) => {
+                        let painter = FormStyle {
+                            base: &tinted,
+                            template,
+                        };
+                        render_card_image_with(card, &painter, &options)?
+                    }
+                    None => render_card_image_with(card, &tinted, &options)?,
+                };
+                image
+                    .save(&target_path)
+                    .with_context(|| format!("failed to write {}", target_path.display()))
+            })
+    })?;
+
     if is_single_file_target {
         println!(
             "Rendered card image to {} at {} DPI",
@@ -149,81 +435,652 @@ fn image(args: RenderImageArgs) -> Result<()> {
     Ok(())
 }
 
+fn pdf(args: RenderPdfArgs) -> Result<()> {
+    let deck = load_deck(args.deck.as_path())?;
+    let options = DeckPdfOptions {
+        style: args.style.into(),
+        layout: args.pagesize.into(),
+        per_page: args.per_page,
+    };
+    let punch_deck = encode_deck(&deck, args.encoder)?;
+    let pdf = render_deck_pdf(&punch_deck.cards, &options)?;
+    let mut out = open_output(&args.output)?;
+    out.write_all(&pdf)?;
+    out.flush()?;
+    let per_page = options.cards_per_page()?;
+    eprintln!(
+        "Printed {} card(s) on {} page(s) to {}",
+        deck.cards.len(),
+        deck.cards.len().div_ceil(per_page),
+        args.output.display()
+    );
+    Ok(())
+}
+
+fn animate(args: RenderAnimateArgs) -> Result<()> {
+    let deck = load_deck(args.deck.as_path())?;
+    let options = AnimationOptions {
+        style: args.style.into(),
+        dpi: args.dpi.clamp(72, 1200),
+        delay_ms: args.delay,
+        looping: !args.once,
+    };
+    let punch_deck = encode_deck(&deck, args.encoder)?;
+    let gif = render_deck_gif(&punch_deck.cards, &options)?;
+    let mut out = open_output(&args.output)?;
+    out.write_all(&gif)?;
+    out.flush()?;
+    eprintln!(
+        "Animated {} card(s) at {} ms per card to {}",
+        deck.cards.len(),
+        args.delay,
+        args.output.display()
+    );
+    Ok(())
+}
+
+/// Parse a `TYPE=COLOR` stock override.
+fn parse_stock_override(input: &str) -> Result<(CardTypeArg, String), String> {
+    let (kind, color) = input
+        .split_once('=')
+        .ok_or_else(|| format!("expected TYPE=COLOR, got '{}'", input))?;
+    let kind = CardTypeArg::from_str(kind.trim(), true)?;
+    Ok((kind, color.trim().to_string()))
+}
+
+fn image_imposed(
+    deck: &Deck,
+    output: &Path,
+    painter: &dyn CardPainter,
+    options: &ImageRenderOptions,
+    mode: Imposition,
+    encoder: Option<EncoderArg>,
+) -> Result<()> {
+    fs::create_dir_all(output)
+        .with_context(|| format!("failed to create output directory {}", output.display()))?;
+    let punch_deck = encode_deck(deck, encoder)?;
+    let pages = render_imposed_pages(&punch_deck.cards, painter, options, mode)?;
+    let duplex = matches!(mode, Imposition::Duplex);
+    for (idx, page) in pages.iter().enumerate() {
+        let name = if duplex {
+            let side = if idx % 2 == 0 { "front" } else { "back" };
+            format!("sheet_{:04}_{}.png", idx / 2 + 1, side)
+        } else {
+            format!("sheet_{:04}.png", idx + 1)
+        };
+        let target = output.join(name);
+        page.save(&target)
+            .with_context(|| format!("failed to write {}", target.display()))?;
+    }
+    let sheets = if duplex { pages.len() / 2 } else { pages.len() };
+    println!(
+        "Imposed {} card(s) on {} sheet(s) in {}; cut each sheet and stack the top pile first",
+        deck.cards.len(),
+        sheets,
+        output.display()
+    );
+    Ok(())
+}
+
+fn image_sheets(
+    deck: &Deck,
+    output: &Path,
+    painter: &dyn CardPainter,
+    options: &ImageRenderOptions,
+    encoder: Option<EncoderArg>,
+) -> Result<()> {
+    options.layout.cards_per_page(options.per_page)?;
+    fs::create_dir_all(output)
+        .with_context(|| format!("failed to create output directory {}", output.display()))?;
+    let punch_deck = encode_deck(deck, encoder)?;
+    let pages = render_sheet_pages(&punch_deck.cards, painter, options)?;
+    for (idx, page) in pages.iter().enumerate() {
+        let target = output.join(format!("page_{:04}.png", idx + 1));
+        page.save(&target)
+            .with_context(|| format!("failed to write {}", target.display()))?;
+    }
+    println!(
+        "Rendered {} card(s) on {} page(s) in {} at {} DPI",
+        deck.cards.len(),
+        pages.len(),
+        output.display(),
+        options.dpi
+    );
+    Ok(())
+}
+
+fn stack(args: RenderStackArgs) -> Result<()> {
+    let deck = load_deck(args.deck.as_path())?;
+    let punch_deck = encode_deck(&deck, args.encoder)?;
+    let options = DeckImageOptions {
+        style: args.style.into(),
+        dpi: args.dpi,
+        gap_in: args.gap,
+        fan_in: args.fan,
+        ..DeckImageOptions::default()
+    };
+    let is_svg = args
+        .output
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("svg"));
+    if is_svg {
+        fs::write(&args.output, render_deck_svg(&punch_deck, &options)?)
+            .with_context(|| format!("failed to write {}", args.output.display()))?;
+    } else {
+        render_deck_image(&punch_deck, &options)?
+            .save(&args.output)
+            .with_context(|| format!("failed to write {}", args.output.display()))?;
+    }
+    println!(
+        "Rendered {} stacked card(s) to {}",
+        punch_deck.cards.len(),
+        args.output.display()
+    );
+    Ok(())
+}
+
+fn isometric(args: RenderIsometricArgs) -> Result<()> {
+    let deck = load_deck(args.deck.as_path())?;
+    let options = IsometricOptions {
+        scale: args.scale,
+        exaggeration: args.exaggerate,
+    };
+    let is_svg = args
+        .output
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("svg"));
+    if is_svg {
+        fs::write(&args.output, render_deck_isometric_svg(&deck, &options)?)
+            .with_context(|| format!("failed to write {}", args.output.display()))?;
+    } else {
+        render_deck_isometric(&deck, &options)?
+            .save(&args.output)
+            .with_context(|| format!("failed to write {}", args.output.display()))?;
+    }
+    println!(
+        "Drew {} card(s) as an isometric stack in {}",
+        deck.cards.len(),
+        args.output.display()
+    );
+    Ok(())
+}
+
 fn interpret(args: RenderInterpretArgs) -> Result<()> {
     let deck = load_deck(args.deck.as_path())?;
-    let encoder = Ibm029Encoder::new();
-    let punch_deck = deck
-        .to_punch_deck(&encoder)
-        .context("failed to render deck with IBM029 encoder")?;
-    let mut output = String::new();
-    for (idx, card) in punch_deck.cards.iter().enumerate() {
+    let ruler = field_ruler(&deck, args.field_ruler)?;
+    let mut captions = args.captions.resolve()?;
+    let encoder = resolve_encoder(args.encoder, &deck)?;
+    captions.name_encoder(encoder.name());
+    let mut out = stage_output(args.output.as_deref().unwrap_or(Path::new("-")))?;
+    for (idx, record) in deck.cards.iter().enumerate() {
+        let card = encode_card(record, idx, encoder)?;
         if idx > 0 {
-            output.push('\n');
-        }
-        output.push_str(&card.render(args.style.into()));
-    }
-    match args.output {
-        Some(path) => {
-            write_output(&path, &output)?;
-            println!(
-                "Wrote interpreted listing for {} to {}",
-                args.deck.display(),
-                path.display()
-            );
-        }
-        None => {
-            print!("{}", output);
+            writeln!(out)?;
         }
+        render_card(
+            &mut out,
+            &card,
+            args.style,
+            args.highlight.as_deref(),
+            ruler.as_deref(),
+            &captions,
+        )?;
+    }
+    out.finish()?;
+    if let Some(path) = args.output.filter(|path| path.as_os_str() != "-") {
+        eprintln!(
+            "Wrote interpreted listing for {} to {}",
+            args.deck.display(),
+            path.display()
+        );
     }
     Ok(())
 }
 
 fn listing(args: RenderListingArgs) -> Result<()> {
     let deck = load_deck(args.deck.as_path())?;
-    let encoder = Ibm029Encoder::new();
-    let punch_deck = deck
-        .to_punch_deck(&encoder)
-        .context("failed to render deck with IBM029 encoder")?;
-    let mut output = String::new();
-    for (idx, (record, card)) in deck.cards.iter().zip(punch_deck.cards.iter()).enumerate() {
-        if idx > 0 {
-            output.push_str("\n\n");
-        }
+    let ruler = field_ruler(&deck, args.field_ruler)?;
+    let mut captions = args.captions.resolve()?;
+    let encoder = resolve_encoder(args.encoder, &deck)?;
+    captions.name_encoder(encoder.name());
+    let c = &captions;
+    let chains = deck.patch_chains();
+    let mut out = stage_output(args.output.as_deref().unwrap_or(Path::new("-")))?;
+    for (idx, record) in deck.cards.iter().enumerate() {
+        let card = encode_card(record, idx, encoder)?;
         let label = record
             .seq
             .map(|s| s.to_string())
-            .unwrap_or_else(|| "(none)".to_string());
-        output.push_str(&format!(
-            "Card {:>4} | seq {} | type {:?}\n",
-            idx + 1,
-            label,
-            record.card_type
-        ));
+            .unwrap_or_else(|| c.no_seq.clone());
+        let mut fields = vec![
+            (c.seq.clone(), label),
+            (c.card_type.clone(), format!("{:?}", record.card_type)),
+        ];
         if let Some(note) = record.meta.note.as_ref() {
-            output.push_str(&format!("Note: {}\n", note));
+            fields.push((c.note.clone(), note.clone()));
         }
         if let Some(color) = record.meta.color.as_ref() {
-            output.push_str(&format!("Color: {}\n", color));
+            fields.push((c.color.clone(), color.clone()));
         }
-        let text = record.text.as_deref().unwrap_or("(stored punches)");
-        output.push_str("Text:\n");
-        output.push_str(text);
-        output.push('\n');
-        output.push_str("Punches:\n");
-        output.push_str(&card.render(args.style.into()));
-    }
-    match args.output {
-        Some(path) => {
-            write_output(&path, &output)?;
-            println!(
-                "Wrote listing for {} to {}",
-                args.deck.display(),
-                path.display()
-            );
+        if let Some(target) = record.meta.patches {
+            let named = match target {
+                PatchTarget::Seq(seq) => format!("{} {}", c.seq, seq),
+                PatchTarget::Index(card) => format!("{} {}", c.card, card + 1),
+            };
+            let value = match (target, deck.resolve_patch_target(target)) {
+                (PatchTarget::Seq(_), Some(card)) => {
+                    format!("{} ({} {})", named, c.card, card + 1)
+                }
+                (PatchTarget::Index(_), Some(_)) => named,
+                (_, None) => format!("{} {}", named, c.missing),
+            };
+            fields.push((c.patches.clone(), value));
         }
-        None => {
-            print!("{}", output);
+        if let Some(chain) = chains.iter().find(|chain| chain.card == Some(idx)) {
+            let by: Vec<String> = chain.patches.iter().map(|p| (p + 1).to_string()).collect();
+            fields.push((
+                c.patched_by.clone(),
+                format!("{} {}", c.card, by.join(", ")),
+            ));
+        }
+        let text = record.text.as_deref().unwrap_or(&c.stored_punches);
+        let mut punches = Vec::new();
+        render_card(
+            &mut punches,
+            &card,
+            args.style,
+            args.highlight.as_deref(),
+            ruler.as_deref(),
+            c,
+        )?;
+        let punches = String::from_utf8(punches)?;
+        let heading = format!("{} {}", c.card, idx + 1);
+        match args.format {
+            ListingFormatArg::Text => {
+                if idx > 0 {
+                    write!(out, "\n\n")?;
+                }
+                let (seq, kind) = (&fields[0], &fields[1]);
+                writeln!(
+                    out,
+                    "{} {:>4} | {} {} | {} {}",
+                    c.card,
+                    idx + 1,
+                    seq.0,
+                    seq.1,
+                    kind.0,
+                    kind.1
+                )?;
+                for (name, value) in &fields[2..] {
+                    writeln!(out, "{}: {}", name, value)?;
+                }
+                writeln!(out, "{}:\n{}", c.text, text)?;
+                write!(out, "{}:\n{}", c.punches, punches)?;
+            }
+            ListingFormatArg::Markdown => {
+                writeln!(out, "### {}\n", heading)?;
+                let cell = |value: &str| value.replace('|', "\\|");
+                let names: Vec<String> = fields.iter().map(|(name, _)| cell(name)).collect();
+                let values: Vec<String> = fields.iter().map(|(_, value)| cell(value)).collect();
+                writeln!(out, "| {} |", names.join(" | "))?;
+                writeln!(out, "|{}", "---|".repeat(fields.len()))?;
+                writeln!(out, "| {} |\n", values.join(" | "))?;
+                for block in [text, punches.trim_end_matches('\n')] {
+                    let fence = markdown_fence(block);
+                    writeln!(out, "{}text\n{}\n{}\n", fence, block, fence)?;
+                }
+            }
+            ListingFormatArg::Asciidoc => {
+                writeln!(out, "=== {}\n", heading)?;
+                let cell = |value: &str| value.replace('|', "\\|");
+                writeln!(out, "[%header]\n|===")?;
+                for row in [
+                    fields
+                        .iter()
+                        .map(|(name, _)| cell(name))
+                        .collect::<Vec<_>>(),
+                    fields.iter().map(|(_, value)| cell(value)).collect(),
+                ] {
+                    writeln!(out, "|{}", row.join(" |"))?;
+                }
+                writeln!(out, "|===\n")?;
+                for block in [text, punches.trim_end_matches('\n')] {
+                    writeln!(out, "[listing]\n----\n{}\n----\n", block)?;
+                }
+            }
         }
     }
+    out.finish()?;
+    if let Some(path) = args.output.filter(|path| path.as_os_str() != "-") {
+        eprintln!(
+            "Wrote listing for {} to {}",
+            args.deck.display(),
+            path.display()
+        );
+    }
     Ok(())
 }
+
+/// Backtick fence longer than any backtick run inside `block`.
+fn markdown_fence(block: &str) -> String {
+    let longest = block.split(|ch| ch != '`').map(str::len).max().unwrap_or(0);
+    "`".repeat(longest.max(2) + 1)
+}
+
+fn site(args: RenderSiteArgs) -> Result<()> {
+    let deck = load_deck(args.deck.as_path())?;
+    let cards_dir = args.output.join("cards");
+    fs::create_dir_all(&cards_dir)
+        .with_context(|| format!("failed to create output directory {}", cards_dir.display()))?;
+    let title = args.title.clone().unwrap_or_else(|| {
+        args.deck
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_else(|| "deck".to_string())
+    });
+
+    let encoder = resolve_encoder(args.encoder, &deck)?;
+    let mut rows = String::new();
+    for (idx, record) in deck.cards.iter().enumerate() {
+        let card = encode_card(record, idx, encoder)?;
+        let name = format!("card_{:04}.svg", idx + 1);
+        let svg_path = cards_dir.join(&name);
+        fs::write(&svg_path, render_card_svg(&card, args.style.into()))
+            .with_context(|| format!("failed to write {}", svg_path.display()))?;
+        let text = record
+            .text_view(encoder)
+            .with_context(|| format!("card {}", idx + 1))?;
+        rows.push_str(&format!(
+            "<tr id=\"card-{n}\"><td>{n}</td><td>{seq}</td><td>{kind}</td><td><code>{text}</code>{note}</td>\
+             <td><a href=\"cards/{name}\"><img src=\"cards/{name}\" width=\"369\" alt=\"card {n}\"></a></td></tr>\n",
+            n = idx + 1,
+            seq = record.seq.map(|s| s.to_string()).unwrap_or_default(),
+            kind = record.card_type,
+            text = escape_html(text.trim_end()),
+            note = record
+                .meta
+                .note
+                .as_deref()
+                .map(|n| format!("<br><em>{}</em>", escape_html(n)))
+                .unwrap_or_default(),
+        ));
+    }
+
+    let listing_path = args.output.join("listing.txt");
+    fs::write(&listing_path, deck.as_text()?.join("\n") + "\n")
+        .with_context(|| format!("failed to write {}", listing_path.display()))?;
+    let meta_path = args.output.join("deck.json");
+    let meta = serde_json::to_string_pretty(&deck.header).context("failed to serialize header")?;
+    fs::write(&meta_path, meta + "\n")
+        .with_context(|| format!("failed to write {}", meta_path.display()))?;
+
+    let header = &deck.header;
+    let protected: Vec<String> = header
+        .protected_cols
+        .iter()
+        .map(|r| format!("{}-{}", r.start, r.end))
+        .collect();
+    let index = format!(
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n<title>{title}</title>\n\
+         <style>body{{font-family:sans-serif;margin:2em}}table{{border-collapse:collapse}}\
+         td,th{{border:1px solid #ccc;padding:4px;vertical-align:top}}code{{white-space:pre}}</style>\n\
+         </head>\n<body>\n<h1>{title}</h1>\n{description}<dl>\n\
+         <dt>Cards</dt><dd>{count}</dd>\n<dt>Language</dt><dd>{language}</dd>\n\
+         <dt>Template</dt><dd>{template}</dd>\n<dt>Protected columns</dt><dd>{protected}</dd>\n\
+         <dt>Created</dt><dd>{created}</dd>\n<dt>SHA-256</dt><dd><code>{hash}</code></dd>\n</dl>\n{notes}\
+         <p><a href=\"listing.txt\">80/80 listing</a> &middot; <a href=\"deck.json\">deck metadata</a></p>\n\
+         <table>\n<tr><th>#</th><th>Seq</th><th>Type</th><th>Text</th><th>Card</th></tr>\n{rows}</table>\n\
+         </body>\n</html>\n",
+        title = escape_html(&title),
+        description = header
+            .description
+            .as_deref()
+            .map(|d| format!("<p>{}</p>\n", escape_html(d)))
+            .unwrap_or_default(),
+        notes = header
+            .notes
+            .as_deref()
+            .map(|n| format!("<h2>Notes</h2>\n<pre>{}</pre>\n", escape_html(n)))
+            .unwrap_or_default(),
+        count = deck.cards.len(),
+        language = escape_html(header.language.as_deref().unwrap_or("-")),
+        template = escape_html(header.template.as_deref().unwrap_or("-")),
+        protected = if protected.is_empty() {
+            "-".to_string()
+        } else {
+            protected.join(", ")
+        },
+        created = header
+            .created_at
+            .map(|created| created.to_rfc3339())
+            .unwrap_or_else(|| "unknown".to_string()),
+        hash = deck.hash()?,
+        rows = rows,
+    );
+    let index_path = args.output.join("index.html");
+    fs::write(&index_path, index)
+        .with_context(|| format!("failed to write {}", index_path.display()))?;
+    println!(
+        "Published {} card(s) from {} to {}",
+        deck.cards.len(),
+        args.deck.display(),
+        index_path.display()
+    );
+    Ok(())
+}
+
+/// Write both decks' card SVGs and an index page pairing them row by row,
+/// changed columns marked, for reviewing one deck against another.
+fn compare(args: RenderCompareArgs) -> Result<()> {
+    let left = load_deck(args.left.as_path())?;
+    let right = load_deck(args.right.as_path())?;
+    let file_name = |path: &Path| {
+        path.file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_else(|| "deck".to_string())
+    };
+    let title = args
+        .title
+        .clone()
+        .unwrap_or_else(|| format!("{} vs {}", file_name(&args.left), file_name(&args.right)));
+    for (deck, side) in [(&left, "left"), (&right, "right")] {
+        let dir = args.output.join(side);
+        fs::create_dir_all(&dir)
+            .with_context(|| format!("failed to create output directory {}", dir.display()))?;
+        let encoder = resolve_encoder(args.encoder, deck)?;
+        for (idx, record) in deck.cards.iter().enumerate() {
+            let card = encode_card(record, idx, encoder)?;
+            let path = dir.join(format!("card_{:04}.svg", idx + 1));
+            fs::write(&path, render_card_svg(&card, args.style.into()))
+                .with_context(|| format!("failed to write {}", path.display()))?;
+        }
+    }
+
+    let encoder = left.text_encoder();
+    let text = |deck: &Deck, idx: usize| {
+        deck.cards[idx]
+            .text_view(encoder)
+            .map(Cow::into_owned)
+            .with_context(|| format!("card {}", idx + 1))
+    };
+    let image = |side: &str, idx: usize| {
+        format!(
+            "<a href=\"{side}/card_{n:04}.svg\"><img src=\"{side}/card_{n:04}.svg\" width=\"369\" alt=\"{side} card {n}\"></a>",
+            n = idx + 1
+        )
+    };
+    let (mut changed, mut added, mut removed) = (0, 0, 0);
+    let mut rows = String::new();
+    for row in align_cards(&left.cards, &right.cards, encoder)? {
+        let (class, l, r, columns, fields) = match &row {
+            AlignedCard::Same { left: l, right: r } => {
+                ("same", Some(*l), Some(*r), &[][..], &[][..])
+            }
+            AlignedCard::Differs(CardDiff::Changed {
+                left: l,
+                right: r,
+                columns,
+                fields,
+            }) => {
+                changed += 1;
+                ("changed", Some(*l), Some(*r), &columns[..], &fields[..])
+            }
+            AlignedCard::Differs(CardDiff::Removed { left: l, .. }) => {
+                removed += 1;
+                ("removed", Some(*l), None, &[][..], &[][..])
+            }
+            AlignedCard::Differs(CardDiff::Added { right: r, .. }) => {
+                added += 1;
+                ("added", None, Some(*r), &[][..], &[][..])
+            }
+        };
+        let number = |idx: Option<usize>| idx.map(|i| (i + 1).to_string()).unwrap_or_default();
+        let fields = if fields.is_empty() {
+            String::new()
+        } else {
+            format!("<br><em>also differs: {}</em>", fields.join(", "))
+        };
+        let lt = l
+            .map(|i| text(&left, i).map(|t| marked_text(&t, columns, |c| &c.before)))
+            .transpose()?
+            .unwrap_or_default();
+        let rt = r
+            .map(|i| text(&right, i).map(|t| marked_text(&t, columns, |c| &c.after)))
+            .transpose()?
+            .unwrap_or_default();
+        rows.push_str(&format!(
+            "<tr class=\"{class}\"><td>{ln}</td><td><code>{lt}</code></td><td>{li}</td>\
+             <td>{ri}</td><td><code>{rt}</code>{fields}</td><td>{rn}</td></tr>\n",
+            ln = number(l),
+            rn = number(r),
+            li = l.map(|i| image("left", i)).unwrap_or_default(),
+            ri = r.map(|i| image("right", i)).unwrap_or_default(),
+        ));
+    }
+
+    let index = format!(
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n<title>{title}</title>\n\
+         <style>body{{font-family:sans-serif;margin:2em}}table{{border-collapse:collapse}}\
+         td,th{{border:1px solid #ccc;padding:4px;vertical-align:top}}code{{white-space:pre}}\
+         mark{{background:#ffd75e}}tr.changed{{background:#fff8e0}}tr.added{{background:#e6f6e6}}\
+         tr.removed{{background:#fbe4e4}}#only:checked~table tr.same{{display:none}}</style>\n\
+         </head>\n<body>\n<h1>{title}</h1>\n<dl>\n\
+         <dt>Left</dt><dd>{left_name} ({left_count} cards, <code>{left_hash}</code>)</dd>\n\
+         <dt>Right</dt><dd>{right_name} ({right_count} cards, <code>{right_hash}</code>)</dd>\n\
+         <dt>Differences</dt><dd>{changed} changed, {added} added, {removed} removed</dd>\n</dl>\n\
+         <input type=\"checkbox\" id=\"only\"> <label for=\"only\">Only differences</label>\n\
+         <table>\n<tr><th>#</th><th>Left</th><th>Card</th><th>Card</th><th>Right</th><th>#</th></tr>\n{rows}</table>\n\
+         </body>\n</html>\n",
+        title = escape_html(&title),
+        left_name = escape_html(&args.left.display().to_string()),
+        right_name = escape_html(&args.right.display().to_string()),
+        left_count = left.cards.len(),
+        right_count = right.cards.len(),
+        left_hash = left.hash()?,
+        right_hash = right.hash()?,
+    );
+    let index_path = args.output.join("index.html");
+    fs::write(&index_path, index)
+        .with_context(|| format!("failed to write {}", index_path.display()))?;
+    println!(
+        "Compared {} and {} ({} changed, {} added, {} removed) in {}",
+        args.left.display(),
+        args.right.display(),
+        changed,
+        added,
+        removed,
+        index_path.display()
+    );
+    Ok(())
+}
+
+/// Card text for HTML with the columns of `changes` wrapped in `<mark>`;
+/// `side` picks the characters shown for the runs.
+fn marked_text(text: &str, changes: &[ColumnChange], side: fn(&ColumnChange) -> &String) -> String {
+    let mut chars: Vec<char> = text.chars().collect();
+    chars.resize(chars.len().max(80), ' ');
+    let shown = changes
+        .iter()
+        .map(|change| change.columns.end)
+        .fold(text.trim_end().chars().count(), usize::max);
+    let mut out = String::new();
+    let mut col = 1;
+    for change in changes {
+        let plain: String = chars[col - 1..change.columns.start - 1].iter().collect();
+        out.push_str(&escape_html(&plain));
+        out.push_str("<mark>");
+        out.push_str(&escape_html(side(change)));
+        out.push_str("</mark>");
+        col = change.columns.end + 1;
+    }
+    let rest: String = chars[col - 1..shown.max(col - 1)].iter().collect();
+    out.push_str(&escape_html(&rest));
+    out
+}
+
+/// Minimal HTML escaping for text placed in element bodies and attributes.
+fn escape_html(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for ch in text.chars() {
+        match ch {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            other => out.push(other),
+        }
+    }
+    out
+}
+
+/// Encode the whole deck with the chosen punch chart.
+fn encode_deck(deck: &Deck, encoder: Option<EncoderArg>) -> Result<CardDeck> {
+    let encoder = resolve_encoder(encoder, deck)?;
+    deck.to_punch_deck(encoder)
+        .with_context(|| format!("failed to render deck with {} encoder", encoder.name()))
+}
+
+/// Encode a single deck card, naming its 1-based position on failure.
+fn encode_card(record: &CardRecord, idx: usize, encoder: &dyn PunchEncoding) -> Result<PunchCard> {
+    record.to_punch_card(encoder).with_context(|| {
+        format!(
+            "failed to encode card {} with {} encoder",
+            idx + 1,
+            encoder.name()
+        )
+    })
+}
+
+/// Stream a card in ASCII, marking columns that match the optional highlight pattern.
+fn render_card(
+    out: &mut dyn Write,
+    card: &PunchCard,
+    style: RenderStyleArg,
+    highlight: Option<&str>,
+    ruler: Option<&str>,
+    captions: &Captions,
+) -> Result<()> {
+    let hits = highlight.map(|p| card.find(p)).unwrap_or_default();
+    card.render_captioned_to(out, style.into(), &hits, ruler, captions)?;
+    Ok(())
+}
+
+/// The deck template's field ruler when `--field-ruler` is requested.
+fn field_ruler(deck: &Deck, enabled: bool) -> Result<Option<String>> {
+    if !enabled {
+        return Ok(None);
+    }
+    Ok(Some(deck_template(deck, "--field-ruler")?.field_ruler()))
+}
+
+/// The template named in the deck header, which `flag` needs.
+fn deck_template(deck: &Deck, flag: &str) -> Result<&'static Template> {
+    let name = deck.header.template.as_deref().ok_or_else(|| {
+        anyhow!(
+            "{} needs a deck template; set one with `punch deck init --template`",
+            flag
+        )
+    })?;
+    TemplateRegistry::get(name)
+}
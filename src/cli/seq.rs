@@ -1,10 +1,14 @@
 //! Sequence number operations (`punch seq ...`).
 
+use std::collections::HashSet;
 use std::path::PathBuf;
 
-use crate::cli::utils::load_deck;
-use anyhow::Result;
+use crate::cli::common::ForceArg;
+use crate::cli::exit::CheckOutcome;
+use crate::cli::utils::{allow_edit, load_deck, save_deck};
+use anyhow::{Context, Result, anyhow};
 use clap::{Args, Subcommand};
+use punchcard::RenumberPlan;
 
 /// Sequence-related subcommands.
 #[derive(Subcommand, Debug)]
@@ -13,6 +17,14 @@ pub enum SeqCommand {
     Number(SeqNumberArgs),
     /// Sort cards by existing sequence numbers.
     Sort(SeqSortArgs),
+    /// Propose free sequence numbers for cards inserted between two others.
+    Suggest(SeqSuggestArgs),
+    /// Check sequence numbers for gaps, duplicates and out-of-order cards.
+    Check(SeqCheckArgs),
+    /// Write a reviewable old-to-new renumbering plan without changing the deck.
+    Plan(SeqPlanArgs),
+    /// Renumber a deck as a plan from `seq plan` says.
+    Apply(SeqApplyArgs),
 }
 
 /// Arguments for numbering a deck.
@@ -26,6 +38,8 @@ pub struct SeqNumberArgs {
     /// Step applied between cards.
     #[arg(long, default_value_t = 10)]
     pub step: usize,
+    #[command(flatten)]
+    pub force: ForceArg,
 }
 
 /// Arguments for sorting cards by sequence number.
@@ -33,6 +47,59 @@ pub struct SeqNumberArgs {
 pub struct SeqSortArgs {
     /// Deck file to update.
     pub deck: PathBuf,
+    #[command(flatten)]
+    pub force: ForceArg,
+}
+
+/// Arguments for suggesting insertion sequence numbers.
+#[derive(Args, Debug)]
+pub struct SeqSuggestArgs {
+    /// Deck file to inspect.
+    pub deck: PathBuf,
+    /// Existing sequence numbers to insert between, e.g. --between 120 130
+    #[arg(long, num_args = 2, value_names = ["AFTER", "BEFORE"], required = true)]
+    pub between: Vec<usize>,
+    /// Number of cards to be inserted.
+    #[arg(long, default_value_t = 1)]
+    pub count: usize,
+}
+
+/// Arguments for checking deck sequence numbers.
+#[derive(Args, Debug)]
+pub struct SeqCheckArgs {
+    /// Deck file to inspect.
+    pub deck: PathBuf,
+    /// Tolerate up to N unnumbered cards before failing with exit code 1.
+    #[arg(long = "max-warnings", value_name = "N")]
+    pub max_warnings: Option<usize>,
+}
+
+/// Arguments for planning a renumbering.
+#[derive(Args, Debug)]
+pub struct SeqPlanArgs {
+    /// Deck file to plan for.
+    pub deck: PathBuf,
+    /// Starting sequence value.
+    #[arg(long, default_value_t = 10)]
+    pub start: usize,
+    /// Step applied between cards.
+    #[arg(long, default_value_t = 10)]
+    pub step: usize,
+    /// Write the plan (JSON) here instead of stdout.
+    #[arg(short = 'o', long = "output")]
+    pub output: Option<PathBuf>,
+}
+
+/// Arguments for applying a renumbering plan.
+#[derive(Args, Debug)]
+pub struct SeqApplyArgs {
+    /// Plan written by `punch seq plan`.
+    pub plan: PathBuf,
+    /// Deck to renumber (defaults to the deck named in the plan).
+    #[arg(long)]
+    pub deck: Option<PathBuf>,
+    #[command(flatten)]
+    pub force: ForceArg,
 }
 
 /// Execute a sequence command.
@@ -40,17 +107,23 @@ pub fn handle(command: SeqCommand) -> Result<()> {
     match command {
         SeqCommand::Number(args) => number(args),
         SeqCommand::Sort(args) => sort(args),
+        SeqCommand::Suggest(args) => suggest(args),
+        SeqCommand::Check(args) => check(args),
+        SeqCommand::Plan(args) => plan(args),
+        SeqCommand::Apply(args) => apply(args),
     }
 }
 
 fn number(args: SeqNumberArgs) -> Result<()> {
     let mut deck = load_deck(args.deck.as_path())?;
-    deck.number_sequence(args.start, args.step);
+    allow_edit(&mut deck, args.force, "seq number")?;
+    deck.ensure_mutable()?;
+    deck.number_sequence(args.start, args.step)?;
     deck.log_action(format!(
         "seq number start={} step={}",
         args.start, args.step
     ));
-    deck.save(&args.deck)?;
+    save_deck(&mut deck, &args.deck)?;
     println!(
         "Applied sequence numbers (start {}, step {}) to {}",
         args.start,
@@ -62,9 +135,113 @@ fn number(args: SeqNumberArgs) -> Result<()> {
 
 fn sort(args: SeqSortArgs) -> Result<()> {
     let mut deck = load_deck(args.deck.as_path())?;
+    allow_edit(&mut deck, args.force, "seq sort")?;
+    deck.ensure_mutable()?;
     deck.sort_by_sequence();
     deck.log_action("seq sort");
-    deck.save(&args.deck)?;
+    save_deck(&mut deck, &args.deck)?;
     println!("Sorted {} by sequence numbers", args.deck.display());
     Ok(())
 }
+
+fn suggest(args: SeqSuggestArgs) -> Result<()> {
+    let deck = load_deck(args.deck.as_path())?;
+    let (after, before) = (args.between[0], args.between[1]);
+    let numbers = deck
+        .suggest_sequence(after, before, args.count)
+        .with_context(|| {
+            format!(
+                "cannot fit {} card(s) in {}",
+                args.count,
+                args.deck.display()
+            )
+        })?;
+    let listed: Vec<String> = numbers.iter().map(|n| n.to_string()).collect();
+    println!(
+        "Free sequence numbers between {} and {}: {}",
+        after,
+        before,
+        listed.join(" ")
+    );
+    Ok(())
+}
+
+/// Report unnumbered cards as warnings and duplicate or descending numbers as
+/// violations.
+fn check(args: SeqCheckArgs) -> Result<()> {
+    let deck = load_deck(args.deck.as_path())?;
+    let mut outcome = CheckOutcome::default();
+    let mut seen = HashSet::new();
+    let mut previous: Option<usize> = None;
+    for (idx, card) in deck.cards.iter().enumerate() {
+        let Some(seq) = card.seq else {
+            println!("warning: card {} has no sequence number", idx + 1);
+            outcome.warnings += 1;
+            continue;
+        };
+        if !seen.insert(seq) {
+            println!("violation: card {} repeats sequence {}", idx + 1, seq);
+            outcome.violations += 1;
+        } else if previous.is_some_and(|prev| seq < prev) {
+            println!(
+                "violation: card {} sequence {} follows {}",
+                idx + 1,
+                seq,
+                previous.unwrap_or_default()
+            );
+            outcome.violations += 1;
+        }
+        previous = Some(seq);
+    }
+    if outcome.warnings == 0 && outcome.violations == 0 {
+        println!("Sequence of {} is clean", args.deck.display());
+    }
+    outcome.finish("seq check", args.max_warnings)
+}
+
+fn plan(args: SeqPlanArgs) -> Result<()> {
+    let deck = load_deck(args.deck.as_path())?;
+    let plan = RenumberPlan::new(&deck, args.start, args.step);
+    let body = serde_json::to_string_pretty(&plan)?;
+    let changed = plan.changes().count();
+    match &args.output {
+        Some(path) => {
+            std::fs::write(path, body + "\n")
+                .with_context(|| format!("failed to write {}", path.display()))?;
+            for entry in plan.changes() {
+                let old = entry.old.map_or("none".to_string(), |seq| seq.to_string());
+                println!("card {}: {} -> {}", entry.card, old, entry.new);
+            }
+            println!(
+                "Planned {} of {} card(s) to change number; review {} and run `punch seq apply`",
+                changed,
+                plan.cards.len(),
+                path.display()
+            );
+        }
+        None => println!("{}", body),
+    }
+    Ok(())
+}
+
+fn apply(args: SeqApplyArgs) -> Result<()> {
+    let raw = std::fs::read_to_string(&args.plan)
+        .with_context(|| format!("failed to read {}", args.plan.display()))?;
+    let plan: RenumberPlan = serde_json::from_str(&raw)
+        .with_context(|| format!("failed to parse plan {}", args.plan.display()))?;
+    let path = args
+        .deck
+        .or_else(|| plan.deck.clone())
+        .ok_or_else(|| anyhow!("the plan names no deck; pass --deck"))?;
+    let mut deck = load_deck(&path)?;
+    allow_edit(&mut deck, args.force, "seq apply")?;
+    let changed = plan.apply(&mut deck)?;
+    deck.log_action(format!(
+        "seq apply {} ({} renumbered)",
+        args.plan.display(),
+        changed
+    ));
+    save_deck(&mut deck, &path)?;
+    println!("Renumbered {} card(s) in {}", changed, path.display());
+    Ok(())
+}
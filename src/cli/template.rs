@@ -1,8 +1,14 @@
 //! Template discovery commands (`punch template ...`).
 
+use std::path::PathBuf;
+
 use anyhow::{Context, Result};
 use clap::{Args, Subcommand};
-use punchcard::TemplateRegistry;
+use punchcard::{
+    CardType, FieldKind, FieldSpec, TemplateRegistry, TemplateSpec, render_template_layout_pdf,
+};
+
+use crate::cli::utils::write_output;
 
 /// Template subcommands.
 #[derive(Subcommand, Debug)]
@@ -11,6 +17,10 @@ pub enum TemplateCommand {
     List,
     /// Show column rules for a template.
     Show(TemplateShowArgs),
+    /// Draw the multiple-card layout form for a template.
+    Layout(TemplateLayoutArgs),
+    /// Scaffold an editable TOML template, optionally based on an existing one.
+    New(TemplateNewArgs),
 }
 
 /// Arguments for `punch template show`.
@@ -20,11 +30,36 @@ pub struct TemplateShowArgs {
     pub name: String,
 }
 
+/// Arguments for `punch template layout`.
+#[derive(Args, Debug)]
+pub struct TemplateLayoutArgs {
+    /// Template name to draw.
+    pub name: String,
+    /// Output path (.pdf for a printable form, anything else or - for text).
+    #[arg(short = 'o', long = "output", default_value = "-")]
+    pub output: PathBuf,
+}
+
+/// Arguments for `punch template new`.
+#[derive(Args, Debug)]
+pub struct TemplateNewArgs {
+    /// Name of the new template.
+    pub name: String,
+    /// Start from this template's fields instead of a blank layout.
+    #[arg(long)]
+    pub like: Option<String>,
+    /// Output path for the TOML definition (`-` for stdout).
+    #[arg(short = 'o', long = "output", default_value = "-")]
+    pub output: PathBuf,
+}
+
 /// Execute a template command.
 pub fn handle(command: TemplateCommand) -> Result<()> {
     match command {
         TemplateCommand::List => list(),
         TemplateCommand::Show(args) => show(args),
+        TemplateCommand::Layout(args) => layout(args),
+        TemplateCommand::New(args) => new(args),
     }
 }
 
@@ -42,9 +77,80 @@ fn show(args: TemplateShowArgs) -> Result<()> {
     println!("Template: {}", tpl.name);
     println!("{}", tpl.description);
     for column in tpl.columns {
+        match column.kind {
+            FieldKind::Text => println!(
+                "  {:>2}-{:>2} {:<12} {}",
+                column.range.start, column.range.end, column.name, column.label
+            ),
+            kind => println!(
+                "  {:>2}-{:>2} {:<12} {} [{}]",
+                column.range.start, column.range.end, column.name, column.label, kind
+            ),
+        }
+    }
+    Ok(())
+}
+
+fn layout(args: TemplateLayoutArgs) -> Result<()> {
+    let tpl = TemplateRegistry::get(&args.name)
+        .with_context(|| format!("template '{}' not found", args.name))?;
+    let is_pdf = args
+        .output
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("pdf"));
+    if is_pdf {
+        std::fs::write(&args.output, render_template_layout_pdf(tpl))
+            .with_context(|| format!("failed to write {}", args.output.display()))?;
+        println!(
+            "Wrote layout form for {} to {}",
+            tpl.name,
+            args.output.display()
+        );
+    } else {
+        write_output(&args.output, &tpl.layout_form())?;
+    }
+    Ok(())
+}
+
+fn new(args: TemplateNewArgs) -> Result<()> {
+    let mut spec = match &args.like {
+        Some(like) => {
+            let base = TemplateRegistry::get(like)
+                .with_context(|| format!("template '{}' not found", like))?;
+            let mut spec = TemplateSpec::from_template(base);
+            spec.description = format!("Custom layout based on {}.", base.name);
+            spec
+        }
+        None => TemplateSpec {
+            name: String::new(),
+            description: "Custom layout.".to_string(),
+            default_type: CardType::Code,
+            fields: vec![
+                FieldSpec {
+                    name: "body".to_string(),
+                    label: "Statement".to_string(),
+                    columns: "1-72".to_string(),
+                    kind: FieldKind::Text,
+                },
+                FieldSpec {
+                    name: "seq".to_string(),
+                    label: "Sequence number".to_string(),
+                    columns: "73-80".to_string(),
+                    kind: FieldKind::Sequence,
+                },
+            ],
+        },
+    };
+    spec.name = args.name.clone();
+    // Round-trip through validation so the scaffold is loadable as written.
+    spec.clone().into_template()?;
+    write_output(&args.output, &spec.to_toml()?)?;
+    if args.output.as_os_str() != "-" {
         println!(
-            "  {:>2}-{:>2}: {}",
-            column.range.start, column.range.end, column.label
+            "Wrote template '{}' to {}; use it with --template {}",
+            args.name,
+            args.output.display(),
+            args.output.display()
         );
     }
     Ok(())
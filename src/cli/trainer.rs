@@ -0,0 +1,145 @@
+//! Interactive Hollerith code drills (`punch trainer`).
+
+use std::io::{self, BufRead, Write};
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::Result;
+use clap::{Args, ValueEnum};
+use punchcard::{CellMask, PunchEncoding, Question, Quiz, QuizMode, ROW_NAMES, TableEncoder};
+
+use crate::cli::common::EncoderArg;
+
+/// Arguments for `punch trainer`.
+#[derive(Args, Debug)]
+pub struct TrainerArgs {
+    /// Ask for the character of a hole pattern, the holes of a character, or both.
+    #[arg(long, value_enum, default_value_t = QuizModeArg::Mixed)]
+    pub mode: QuizModeArg,
+    /// Number of questions; answer `quit` to stop early.
+    #[arg(short = 'n', long, default_value_t = 20)]
+    pub rounds: usize,
+    /// Only drill these characters, e.g. "&-/.<(+".
+    #[arg(long)]
+    pub chars: Option<String>,
+    /// Punch chart the questions come from.
+    #[arg(long, value_enum, default_value_t = EncoderArg::Ibm029)]
+    pub encoder: EncoderArg,
+    /// Drill a chart loaded from a TOML or CSV mapping file instead.
+    #[arg(long = "encoder-file", value_name = "FILE", conflicts_with = "encoder")]
+    pub encoder_file: Option<PathBuf>,
+    /// Random seed; the same seed asks the same questions (defaults to the clock).
+    #[arg(long)]
+    pub seed: Option<u64>,
+}
+
+/// Question kinds accepted by `trainer --mode`.
+#[derive(ValueEnum, Debug, Clone, Copy)]
+pub enum QuizModeArg {
+    Read,
+    Punch,
+    Mixed,
+}
+
+impl From<QuizModeArg> for QuizMode {
+    fn from(value: QuizModeArg) -> QuizMode {
+        match value {
+            QuizModeArg::Read => QuizMode::Read,
+            QuizModeArg::Punch => QuizMode::Punch,
+            QuizModeArg::Mixed => QuizMode::Mixed,
+        }
+    }
+}
+
+/// Run a drill on the terminal, reading one answer per line.
+pub fn handle(args: TrainerArgs) -> Result<()> {
+    let table = args
+        .encoder_file
+        .as_deref()
+        .map(TableEncoder::from_path)
+        .transpose()?;
+    let encoder: &dyn PunchEncoding = match &table {
+        Some(table) => table,
+        None => args.encoder.encoder(),
+    };
+    let seed = args.seed.unwrap_or_else(|| {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_nanos() as u64)
+    });
+    let mut quiz = Quiz::new(encoder, args.mode.into(), args.chars.as_deref(), seed)?;
+    println!(
+        "{} drill: {} characters, {} questions (quit to stop)",
+        encoder.name(),
+        quiz.pool_size(),
+        args.rounds
+    );
+
+    let stdin = io::stdin();
+    let mut lines = stdin.lock().lines();
+    let mut out = io::stdout();
+    for round in 1..=args.rounds {
+        let question = quiz.next_question();
+        println!();
+        match question {
+            Question::Read { mask, .. } => {
+                println!(
+                    "{}/{}  Which character is punched here?",
+                    round, args.rounds
+                );
+                print!("{}", column_diagram(mask));
+                print!("character> ");
+            }
+            Question::Punch { ch, .. } => {
+                println!("{}/{}  Which rows punch {:?}?", round, args.rounds, ch);
+                print!("rows (e.g. 12-1)> ");
+            }
+        }
+        out.flush()?;
+        let Some(line) = lines.next().transpose()? else {
+            println!();
+            break;
+        };
+        if line.trim().eq_ignore_ascii_case("quit") {
+            break;
+        }
+        if quiz.answer(&question, &line) {
+            println!("Correct: {:?} is {}", question.ch(), question.mask());
+        } else {
+            println!("No: {:?} is {}", question.ch(), question.mask());
+        }
+    }
+
+    println!();
+    if quiz.asked == 0 {
+        println!("No questions answered");
+        return Ok(());
+    }
+    println!(
+        "Score: {}/{} ({:.0}%)",
+        quiz.correct,
+        quiz.asked,
+        quiz.accuracy() * 100.0
+    );
+    let missed = quiz.missed();
+    if !missed.is_empty() {
+        let list: Vec<String> = missed
+            .iter()
+            .map(|(ch, n)| format!("{:?} x{}", ch, n))
+            .collect();
+        println!("Practice: {}", list.join(", "));
+    }
+    Ok(())
+}
+
+/// One card column drawn top to bottom, `#` for a hole.
+fn column_diagram(mask: CellMask) -> String {
+    let rows = mask.rows();
+    ROW_NAMES
+        .iter()
+        .map(|row| {
+            let mark = if rows.contains(row) { '#' } else { '.' };
+            format!("  {:>2} {}\n", row, mark)
+        })
+        .collect()
+}
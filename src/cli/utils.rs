@@ -1,11 +1,15 @@
 //! Convenience helpers shared across command handlers.
 
 use std::fs;
-use std::io::{self, Read, Write};
+use std::io::{self, BufWriter, Read, Write};
 use std::path::{Path, PathBuf};
 
 use anyhow::{Context, Result, anyhow};
-use punchcard::{ColumnRange, Deck};
+use punchcard::{ColumnRange, Deck, VerifyMismatch, journal, text};
+
+use tempfile::NamedTempFile;
+
+use crate::cli::common::{ForceArg, OverflowArg};
 
 /// Resolve plain-text input for commands that accept either inline strings or files.
 pub fn read_text_arg(text: Option<String>, from: Option<PathBuf>) -> Result<String> {
@@ -40,19 +44,81 @@ pub fn write_output(path: &Path, content: &str) -> Result<()> {
     fs::write(path, content).with_context(|| format!("failed to write {}", path.display()))
 }
 
-/// Clap-friendly column range parser for strings like `73-80`.
-pub fn parse_column_range(input: &str) -> Result<ColumnRange, String> {
-    let parts: Vec<&str> = input.split('-').collect();
-    if parts.len() != 2 {
-        return Err("column range must be START-END".to_string());
+/// Open a buffered writer for a file, or stdout when `-` is provided.
+pub fn open_output(path: &Path) -> Result<Box<dyn Write>> {
+    if path.as_os_str() == "-" {
+        return Ok(Box::new(BufWriter::new(io::stdout().lock())));
+    }
+    let file =
+        fs::File::create(path).with_context(|| format!("failed to write {}", path.display()))?;
+    Ok(Box::new(BufWriter::new(file)))
+}
+
+/// Output that replaces its file only once complete: a file target is
+/// written to a temporary file beside it and renamed into place by
+/// [`StagedOutput::finish`], so an error part-way leaves the old file alone.
+pub enum StagedOutput {
+    Stdout(BufWriter<io::StdoutLock<'static>>),
+    File {
+        writer: BufWriter<NamedTempFile>,
+        path: PathBuf,
+    },
+}
+
+/// Open a [`StagedOutput`] for a file, or stdout when `-` is provided.
+pub fn stage_output(path: &Path) -> Result<StagedOutput> {
+    if path.as_os_str() == "-" {
+        return Ok(StagedOutput::Stdout(BufWriter::new(io::stdout().lock())));
+    }
+    let dir = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    let file = NamedTempFile::new_in(dir)
+        .with_context(|| format!("failed to write {}", path.display()))?;
+    Ok(StagedOutput::File {
+        writer: BufWriter::new(file),
+        path: path.to_path_buf(),
+    })
+}
+
+impl StagedOutput {
+    /// Flush the output and move a staged file into place.
+    pub fn finish(self) -> Result<()> {
+        match self {
+            StagedOutput::Stdout(mut out) => Ok(out.flush()?),
+            StagedOutput::File { writer, path } => {
+                let file = writer
+                    .into_inner()
+                    .map_err(|err| err.into_error())
+                    .with_context(|| format!("failed to write {}", path.display()))?;
+                file.persist(&path)
+                    .with_context(|| format!("failed to write {}", path.display()))?;
+                Ok(())
+            }
+        }
+    }
+}
+
+impl Write for StagedOutput {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            StagedOutput::Stdout(out) => out.write(buf),
+            StagedOutput::File { writer, .. } => writer.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            StagedOutput::Stdout(out) => out.flush(),
+            StagedOutput::File { writer, .. } => writer.flush(),
+        }
     }
-    let start: usize = parts[0]
-        .parse()
-        .map_err(|_| "start column must be a number".to_string())?;
-    let end: usize = parts[1]
-        .parse()
-        .map_err(|_| "end column must be a number".to_string())?;
-    ColumnRange::new(start, end).map_err(|err| err.to_string())
+}
+
+/// Clap-friendly column range parser for strings like `73-80` or `7`.
+pub fn parse_column_range(input: &str) -> Result<ColumnRange, String> {
+    input.parse().map_err(|err: anyhow::Error| err.to_string())
 }
 
 /// Expand range expressions such as `1..10,25,40..$` into zero-based card indices.
@@ -118,90 +184,103 @@ fn parse_range_bound(token: &str, deck_len: usize) -> Result<usize> {
     Ok(value)
 }
 
-/// Split arbitrary input into 80-column padded card strings.
-pub fn split_lines_fixed(input: &str) -> Vec<String> {
-    let mut lines = Vec::new();
-    for raw in input.lines() {
-        let mut chars: Vec<char> = raw.chars().collect();
-        if chars.len() > 80 {
-            chars.truncate(80);
-        }
-        while chars.len() < 80 {
-            chars.push(' ');
-        }
-        lines.push(chars.into_iter().collect());
-    }
-    if lines.is_empty() {
-        lines.push(" ".repeat(80));
-    }
-    lines
-}
-
-/// Location for storing the verification baseline for a given deck.
-pub fn verify_snapshot_path(deck: &Path) -> PathBuf {
-    let mut path = deck.to_path_buf();
-    path.set_extension("verify.base");
-    path
-}
-
-/// Location for storing the latest verification diff for a deck.
-pub fn verify_diff_path(deck: &Path) -> PathBuf {
-    let mut path = deck.to_path_buf();
-    path.set_extension("verify.diff");
-    path
+/// Split typed or imported text into 80-column card lines under `overflow`.
+pub fn split_input(input: &str, overflow: OverflowArg) -> Result<Vec<String>> {
+    text::split_80(input, overflow.into())
+        .map_err(|err| anyhow!("{}; use --overflow truncate or wrap to keep it", err))
 }
 
 /// Produce a human-readable diff, respecting optional masked column ranges.
-pub fn diff_text(expected: &str, actual: &str, mask: &[ColumnRange]) -> (String, bool) {
+///
+/// `mask_for_line` yields the ignored ranges for each zero-based line, so
+/// callers can vary them per card. Returns the report together with the
+/// lines that differ.
+pub fn diff_text(
+    expected: &str,
+    actual: &str,
+    mask_for_line: impl Fn(usize) -> Vec<ColumnRange>,
+) -> (String, Vec<VerifyMismatch>) {
     let exp_lines: Vec<&str> = expected.lines().collect();
     let act_lines: Vec<&str> = actual.lines().collect();
     let max = exp_lines.len().max(act_lines.len());
     let mut output = String::new();
-    let mut changed = false;
+    let mut mismatches = Vec::new();
     for i in 0..max {
         let exp = exp_lines.get(i).copied().unwrap_or("");
         let act = act_lines.get(i).copied().unwrap_or("");
-        if !lines_match_with_mask(exp, act, mask) {
-            changed = true;
+        let columns = masked_differences(exp, act, &mask_for_line(i));
+        if !columns.is_empty() {
             output.push_str(&format!("line {:>4}:\n", i + 1));
             output.push_str(&format!("  expected |{}|\n", exp));
             output.push_str(&format!("  actual   |{}|\n", act));
+            mismatches.push(VerifyMismatch {
+                card: i + 1,
+                columns,
+            });
         }
     }
-    if !changed {
+    if mismatches.is_empty() {
         output.push_str("verification passed: no differences\n");
     }
-    (output, changed)
+    (output, mismatches)
 }
 
-fn lines_match_with_mask(expected: &str, actual: &str, mask: &[ColumnRange]) -> bool {
+/// One-based columns where the lines differ outside `mask`. Lines are
+/// padded with blanks out to the last masked column; past that, a column
+/// only one of the lines reaches counts as different.
+fn masked_differences(expected: &str, actual: &str, mask: &[ColumnRange]) -> Vec<usize> {
     if expected == actual && mask.is_empty() {
-        return true;
+        return Vec::new();
     }
-    let mut exp_chars: Vec<char> = expected.chars().collect();
-    let mut act_chars: Vec<char> = actual.chars().collect();
     let required_len = mask.iter().map(|r| r.end).max().unwrap_or(0);
-    while exp_chars.len() < required_len {
-        exp_chars.push(' ');
-    }
-    while act_chars.len() < required_len {
-        act_chars.push(' ');
-    }
-    for range in mask {
-        for col in range.start..=range.end {
-            let idx = col - 1;
-            if idx < exp_chars.len() {
-                exp_chars[idx] = '_';
-            }
-            if idx < act_chars.len() {
-                act_chars[idx] = '_';
+    let prepare = |line: &str| {
+        let mut chars: Vec<char> = line.chars().collect();
+        if chars.len() < required_len {
+            chars.resize(required_len, ' ');
+        }
+        for range in mask {
+            for col in range.start..=range.end {
+                chars[col - 1] = '_';
             }
         }
-    }
-    exp_chars == act_chars
+        chars
+    };
+    let (exp_chars, act_chars) = (prepare(expected), prepare(actual));
+    (1..=exp_chars.len().max(act_chars.len()))
+        .filter(|&col| exp_chars.get(col - 1) != act_chars.get(col - 1))
+        .collect()
 }
 
 /// Load a deck file, attaching path context to any error.
 pub fn load_deck(path: &Path) -> Result<Deck> {
+    let deck = read_deck(path)?;
+    if !deck.hash_mismatches.is_empty() {
+        eprintln!(
+            "warning: {} card(s) in {} do not match their stored sha256 (edited by hand?); \
+             `punch audit hash` lists them",
+            deck.hash_mismatches.len(),
+            path.display()
+        );
+    }
+    Ok(deck)
+}
+
+/// [`load_deck`] without the warning about cards that fail their stored hash,
+/// for commands that report those cards themselves.
+pub fn read_deck(path: &Path) -> Result<Deck> {
     Deck::load(path).with_context(|| format!("failed to read deck {}", path.display()))
 }
+
+/// Save a deck a command changed, journaling the change for `deck undo`.
+pub fn save_deck(deck: &mut Deck, path: &Path) -> Result<()> {
+    journal::save_journaled(deck, path)
+}
+
+/// Fail if `deck` is locked readonly; with `force`, let `command` through and
+/// record the override in the deck's history.
+pub fn allow_edit(deck: &mut Deck, force: ForceArg, command: &str) -> Result<()> {
+    if force.force {
+        deck.override_readonly(command);
+    }
+    deck.ensure_unlocked()
+}
@@ -1,16 +1,20 @@
 //! Verification workflow (`punch verify ...`).
 
-use std::path::PathBuf;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
 
 use anyhow::{Context, Result, anyhow};
-use clap::{Args, Subcommand};
-use punchcard::ColumnRange;
-
-use crate::cli::utils::{
-    diff_text, load_deck, parse_column_range, read_text_arg, verify_diff_path,
-    verify_snapshot_path, write_output,
+use clap::{Args, Subcommand, ValueEnum};
+use punchcard::{
+    Baseline, ColumnRange, DEFAULT_BASELINE, Deck, PunchCard, VerifyMismatch, VerifyPass,
+    diff_punches, ocr_cross_check,
 };
 
+use crate::cli::common::{EncoderArg, ForceArg, MaskArgs, resolve_encoder};
+use crate::cli::exit::CheckOutcome;
+use crate::cli::utils::{allow_edit, diff_text, load_deck, open_output, read_text_arg, save_deck};
+
 /// Verification subcommands.
 #[derive(Subcommand, Debug)]
 pub enum VerifyCommand {
@@ -20,6 +24,10 @@ pub enum VerifyCommand {
     Pass(VerifyPassArgs),
     /// Display the latest verification diff.
     Report(VerifyReportArgs),
+    /// List or delete the named baselines recorded for a deck.
+    Baselines(VerifyBaselinesArgs),
+    /// Cross-check OCR'd interpretation lines of scanned cards against their holes.
+    Ocr(VerifyOcrArgs),
 }
 
 /// Arguments for `punch verify start`.
@@ -27,6 +35,14 @@ pub enum VerifyCommand {
 pub struct VerifyStartArgs {
     /// Deck file to snapshot.
     pub deck: PathBuf,
+    /// Baseline name; capturing an existing name replaces it.
+    #[arg(long, default_value = DEFAULT_BASELINE)]
+    pub name: String,
+    /// Free-form note stored with the baseline.
+    #[arg(long)]
+    pub label: Option<String>,
+    #[command(flatten)]
+    pub force: ForceArg,
 }
 
 /// Arguments for `punch verify pass`.
@@ -37,12 +53,23 @@ pub struct VerifyPassArgs {
     /// Text file to compare (`-` for stdin).
     #[arg(long = "from")]
     pub from: Option<PathBuf>,
-    /// Treat any difference as an error.
+    /// Baseline to compare against.
+    #[arg(long, default_value = DEFAULT_BASELINE)]
+    pub baseline: String,
+    /// Compare the deck's hole patterns with the keyed text instead of the
+    /// text baseline (for column-binary or scanned decks).
+    #[arg(long, conflicts_with = "baseline")]
+    pub punches: bool,
+    /// Treat any difference as a violation (exit code 2).
     #[arg(long)]
     pub strict: bool,
-    /// Ignore specified column ranges during comparison.
-    #[arg(long = "mask", value_parser = parse_column_range)]
-    pub mask: Vec<ColumnRange>,
+    /// Tolerate up to N differing lines before failing with exit code 1.
+    #[arg(long = "max-warnings", value_name = "N")]
+    pub max_warnings: Option<usize>,
+    #[command(flatten)]
+    pub masks: MaskArgs,
+    #[command(flatten)]
+    pub force: ForceArg,
 }
 
 /// Arguments for `punch verify report`.
@@ -50,6 +77,52 @@ pub struct VerifyPassArgs {
 pub struct VerifyReportArgs {
     /// Deck file to inspect.
     pub deck: PathBuf,
+    /// Report format.
+    #[arg(long, default_value_t = VerifyReportFormatArg::Text, value_enum)]
+    pub format: VerifyReportFormatArg,
+    /// Write the report here instead of stdout (`-` for stdout).
+    #[arg(short = 'o', long = "output")]
+    pub output: Option<PathBuf>,
+}
+
+/// Output formats for `punch verify report`.
+#[derive(ValueEnum, Debug, Clone, Copy)]
+pub enum VerifyReportFormatArg {
+    /// The pass's diff report.
+    Text,
+    /// Pass summary with a pass/fail entry and differing columns per card.
+    Json,
+    /// JUnit XML, one test case per card, for CI test reporters.
+    Junit,
+}
+
+/// Arguments for `punch verify baselines`.
+#[derive(Args, Debug)]
+pub struct VerifyBaselinesArgs {
+    /// Deck file whose baselines to manage.
+    pub deck: PathBuf,
+    /// Delete the named baseline instead of listing.
+    #[arg(long, value_name = "NAME")]
+    pub delete: Option<String>,
+    #[command(flatten)]
+    pub force: ForceArg,
+}
+
+/// Arguments for `punch verify ocr`.
+#[derive(Args, Debug)]
+pub struct VerifyOcrArgs {
+    /// Deck ingested from card scans.
+    pub deck: PathBuf,
+    /// OCR output of the printed interpretation lines, one line per card
+    /// (`-` for stdin).
+    #[arg(long = "from")]
+    pub from: Option<PathBuf>,
+    /// Code the holes were punched in (defaults to the deck's encoder).
+    #[arg(long, value_enum)]
+    pub encoder: Option<EncoderArg>,
+    /// Tolerate up to N cards that differ only in look-alike glyphs.
+    #[arg(long = "max-warnings", value_name = "N")]
+    pub max_warnings: Option<usize>,
 }
 
 /// Execute a verification command.
@@ -58,64 +131,265 @@ pub fn handle(command: VerifyCommand) -> Result<()> {
         VerifyCommand::Start(args) => start(args),
         VerifyCommand::Pass(args) => pass(args),
         VerifyCommand::Report(args) => report(args),
+        VerifyCommand::Baselines(args) => baselines(args),
+        VerifyCommand::Ocr(args) => ocr(args),
     }
 }
 
+/// Load a deck with the verification state of any legacy sidecars moved
+/// into its header, returning the sidecars to remove once it is saved.
+fn load_with_verification(path: &Path) -> Result<(Deck, Vec<PathBuf>)> {
+    let mut deck = load_deck(path)?;
+    let adopted = deck.adopt_verify_sidecars(path)?;
+    Ok((deck, adopted))
+}
+
+/// Save the deck, then remove the sidecars its verification state came from.
+fn save_verification(deck: &mut Deck, path: &Path, adopted: &[PathBuf]) -> Result<()> {
+    save_deck(deck, path)?;
+    for sidecar in adopted {
+        fs::remove_file(sidecar)
+            .with_context(|| format!("failed to remove {}", sidecar.display()))?;
+    }
+    Ok(())
+}
+
 fn start(args: VerifyStartArgs) -> Result<()> {
-    let deck = load_deck(args.deck.as_path())?;
-    let snapshot_path = verify_snapshot_path(&args.deck);
-    let text = deck.as_text().join("\n");
-    write_output(&snapshot_path, &text)?;
+    let (mut deck, adopted) = load_with_verification(&args.deck)?;
+    allow_edit(&mut deck, args.force, "verify start")?;
+    let baseline = Baseline::capture(&args.name, args.label, deck.as_text()?.join("\n"));
     println!(
-        "Stored verification baseline at {}",
-        snapshot_path.display()
+        "Stored verification baseline '{}' ({} cards, sha256 {}) in {}",
+        baseline.name,
+        baseline.card_count(),
+        &baseline.hash[..12],
+        args.deck.display()
     );
-    Ok(())
+    deck.verification_mut().upsert(baseline);
+    deck.log_action(format!("verify start {}", args.name));
+    save_verification(&mut deck, &args.deck, &adopted)
 }
 
 fn pass(args: VerifyPassArgs) -> Result<()> {
-    load_deck(args.deck.as_path())?;
-    let snapshot_path = verify_snapshot_path(&args.deck);
-    if !snapshot_path.exists() {
-        return Err(anyhow!(
-            "no verification snapshot found at {}. Run `punch verify start` first.",
-            snapshot_path.display()
-        ));
-    }
-    let expected = std::fs::read_to_string(&snapshot_path)
-        .with_context(|| format!("failed to read {}", snapshot_path.display()))?;
+    let (mut deck, adopted) = load_with_verification(&args.deck)?;
+    allow_edit(&mut deck, args.force, "verify pass")?;
+    let masks = args.masks.resolve()?;
+    let mask_for = |idx: usize| match deck.cards.get(idx) {
+        Some(card) => masks.ranges_for(&card.card_type),
+        None => masks.ranges.clone(),
+    };
     let actual = read_text_arg(None, args.from.clone())?;
-    let (diff, changed) = diff_text(&expected, &actual, &args.mask);
-    let diff_path = verify_diff_path(&args.deck);
-    write_output(&diff_path, &diff)?;
-    if args.strict && changed {
-        return Err(anyhow!(
-            "verification failed; see diff at {}",
-            diff_path.display()
-        ));
-    }
-    if changed {
-        println!("Verification diff written to {}", diff_path.display());
+    let (baseline, diff, cards, mismatches) = if args.punches {
+        let (diff, cards, mismatches) = diff_deck_punches(&deck, &actual, mask_for)?;
+        (None, diff, cards, mismatches)
     } else {
+        let verification = deck.header.verification.as_ref();
+        if verification.is_none_or(|v| v.baselines.is_empty()) {
+            return Err(anyhow!(
+                "no verification baseline for {}. Run `punch verify start` first.",
+                args.deck.display()
+            ));
+        }
+        let baseline = verification
+            .expect("checked above")
+            .get(&args.baseline)?
+            .clone();
+        let expected = baseline.verified_text()?;
+        let cards = expected.lines().count().max(actual.lines().count());
+        let (diff, mismatches) = diff_text(expected, &actual, mask_for);
+        (Some(baseline), diff, cards, mismatches)
+    };
+    let changed = mismatches.len();
+    deck.verification_mut().record_pass(VerifyPass::record(
+        baseline.as_ref(),
+        cards,
+        mismatches,
+        diff,
+    ));
+    deck.log_action("verify pass");
+    save_verification(&mut deck, &args.deck, &adopted)?;
+    if changed > 0 {
         println!(
-            "Verification passed with ignored masks; diff stored at {}",
-            diff_path.display()
+            "Verification diff recorded in {}; `punch verify report` shows it",
+            args.deck.display()
+        );
+    } else {
+        println!(
+            "Verification passed with ignored masks; recorded in {}",
+            args.deck.display()
         );
     }
-    Ok(())
+    let outcome = if args.strict {
+        CheckOutcome {
+            violations: changed,
+            ..CheckOutcome::default()
+        }
+    } else {
+        CheckOutcome {
+            warnings: changed,
+            ..CheckOutcome::default()
+        }
+    };
+    outcome.finish("verification", args.max_warnings)
+}
+
+/// Hole-level diff of the deck against keyed text, one line per differing
+/// column. Returns the report, the number of cards compared and the cards
+/// that differ.
+fn diff_deck_punches(
+    deck: &Deck,
+    keyed: &str,
+    mask_for: impl Fn(usize) -> Vec<ColumnRange>,
+) -> Result<(String, usize, Vec<VerifyMismatch>)> {
+    let encoder = deck.encoder()?;
+    let expected = deck.to_punch_deck(encoder)?.cards;
+    let actual = keyed
+        .lines()
+        .enumerate()
+        .map(|(idx, line)| {
+            PunchCard::from_str(encoder, line)
+                .with_context(|| format!("keyed line {} cannot be punched", idx + 1))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let mismatches = diff_punches(&expected, &actual, mask_for);
+    let mut output = String::new();
+    for m in &mismatches {
+        output.push_str(&format!(
+            "card {:>4} col {:>2}: deck {:<8} keyed {}\n",
+            m.card + 1,
+            m.column,
+            m.expected,
+            m.actual
+        ));
+    }
+    let mut cards: Vec<VerifyMismatch> = Vec::new();
+    for m in &mismatches {
+        match cards.last_mut() {
+            Some(last) if last.card == m.card + 1 => last.columns.push(m.column),
+            _ => cards.push(VerifyMismatch {
+                card: m.card + 1,
+                columns: vec![m.column],
+            }),
+        }
+    }
+    if cards.is_empty() {
+        output.push_str("verification passed: no punch differences\n");
+    }
+    Ok((output, expected.len().max(actual.len()), cards))
 }
 
 fn report(args: VerifyReportArgs) -> Result<()> {
-    let diff_path = verify_diff_path(&args.deck);
-    if !diff_path.exists() {
+    let (deck, _) = load_with_verification(&args.deck)?;
+    let Some(pass) = deck
+        .header
+        .verification
+        .as_ref()
+        .and_then(|v| v.latest_pass())
+    else {
         println!(
-            "No verification diff at {}. Run `punch verify pass` first.",
-            diff_path.display()
+            "No verification pass recorded for {}. Run `punch verify pass` first.",
+            args.deck.display()
         );
         return Ok(());
+    };
+    let body = match args.format {
+        VerifyReportFormatArg::Text => {
+            format!("Last pass: {}\n{}\n", pass_summary(pass), pass.diff)
+        }
+        VerifyReportFormatArg::Json => {
+            let report = pass.report(&args.deck.display().to_string());
+            serde_json::to_string_pretty(&report)? + "\n"
+        }
+        VerifyReportFormatArg::Junit => pass.report(&args.deck.display().to_string()).to_junit(),
+    };
+    let mut out = open_output(args.output.as_deref().unwrap_or(Path::new("-")))?;
+    out.write_all(body.as_bytes())?;
+    out.flush()?;
+    Ok(())
+}
+
+/// One-line description of a pass, as `verify report` and `deck info` show it.
+pub fn pass_summary(pass: &VerifyPass) -> String {
+    let against = match &pass.baseline {
+        Some(name) => format!("baseline '{}'", name),
+        None => "holes".to_string(),
+    };
+    let result = if pass.passed() {
+        "passed".to_string()
+    } else {
+        format!("{} differing", pass.differing)
+    };
+    format!(
+        "{} by {} against {}: {}",
+        pass.at.format("%Y-%m-%d %H:%M"),
+        pass.operator,
+        against,
+        result
+    )
+}
+
+fn baselines(args: VerifyBaselinesArgs) -> Result<()> {
+    let (mut deck, adopted) = load_with_verification(&args.deck)?;
+    if let Some(name) = args.delete {
+        allow_edit(&mut deck, args.force, "verify baselines --delete")?;
+        deck.verification_mut().remove(&name)?;
+        deck.log_action(format!("verify delete baseline {}", name));
+        save_verification(&mut deck, &args.deck, &adopted)?;
+        println!("Deleted verification baseline '{}'", name);
+        return Ok(());
+    }
+    let baselines = deck
+        .header
+        .verification
+        .as_ref()
+        .map_or(&[][..], |v| &v.baselines);
+    if baselines.is_empty() {
+        println!("No verification baselines for {}", args.deck.display());
+        return Ok(());
+    }
+    for baseline in baselines {
+        println!(
+            "{:<12} {}  {:<10} {:>5} cards  {}  {}",
+            baseline.name,
+            baseline.created_at.format("%Y-%m-%d %H:%M"),
+            baseline.operator,
+            baseline.card_count(),
+            &baseline.hash[..12],
+            baseline.label.as_deref().unwrap_or("")
+        );
     }
-    let diff = std::fs::read_to_string(&diff_path)
-        .with_context(|| format!("failed to read {}", diff_path.display()))?;
-    println!("{}", diff);
     Ok(())
 }
+
+/// Disagreements made only of look-alike glyphs are warnings (likely OCR
+/// misreads); anything else means the card was annotated or re-punched.
+fn ocr(args: VerifyOcrArgs) -> Result<()> {
+    let deck = load_deck(args.deck.as_path())?;
+    let printed = read_text_arg(None, args.from)?;
+    let extra = printed.lines().count().saturating_sub(deck.cards.len());
+    let mismatches = ocr_cross_check(&deck, &printed, resolve_encoder(args.encoder, &deck)?)?;
+    let mut outcome = CheckOutcome::default();
+    for m in &mismatches {
+        let columns: Vec<String> = m.columns.iter().map(|c| c.to_string()).collect();
+        let kind = if m.confusable {
+            outcome.warnings += 1;
+            "warning"
+        } else {
+            outcome.violations += 1;
+            "violation"
+        };
+        println!("{}: card {:>4} col {}", kind, m.card + 1, columns.join(","));
+        println!("  printed: {}", m.printed.trim_end());
+        println!("  punched: {}", m.punched.trim_end());
+    }
+    if extra > 0 {
+        println!("warning: {} OCR line(s) beyond the last card", extra);
+        outcome.warnings += 1;
+    }
+    println!(
+        "{} of {} card(s) disagree with their interpretation",
+        mismatches.len(),
+        deck.cards.len()
+    );
+    outcome.finish("ocr cross-check", args.max_warnings)
+}
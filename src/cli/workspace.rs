@@ -0,0 +1,209 @@
+//! Commands over every deck of a workspace manifest (`punch workspace ...`).
+
+use std::path::PathBuf;
+
+use anyhow::{Result, anyhow};
+use clap::{Args, Subcommand};
+use punchcard::{Deck, DeckRole, LanguageRegistry, Workspace, assemble_job};
+
+use crate::cli::exit::CheckOutcome;
+use crate::cli::utils::{load_deck, save_deck};
+
+/// Workspace subcommands.
+#[derive(Subcommand, Debug)]
+pub enum WorkspaceCommand {
+    /// List the workspace's decks with their role, size and state.
+    Status(WorkspaceArgs),
+    /// Check the manifest and run every deck's language checks.
+    Validate(WorkspaceValidateArgs),
+    /// Assemble the JCL, program and data decks into one job deck.
+    Assemble(WorkspaceAssembleArgs),
+}
+
+/// Manifest selection shared by workspace commands.
+#[derive(Args, Debug)]
+pub struct WorkspaceArgs {
+    /// Workspace manifest (defaults to the nearest punchcard.workspace.toml).
+    #[arg(long, value_name = "FILE")]
+    pub manifest: Option<PathBuf>,
+}
+
+/// Arguments for `punch workspace validate`.
+#[derive(Args, Debug)]
+pub struct WorkspaceValidateArgs {
+    #[command(flatten)]
+    pub workspace: WorkspaceArgs,
+    /// Tolerate up to N warnings before failing with exit code 1.
+    #[arg(long = "max-warnings", value_name = "N")]
+    pub max_warnings: Option<usize>,
+}
+
+/// Arguments for `punch workspace assemble`.
+#[derive(Args, Debug)]
+pub struct WorkspaceAssembleArgs {
+    #[command(flatten)]
+    pub workspace: WorkspaceArgs,
+    /// Output job deck.
+    #[arg(short = 'o', long = "output")]
+    pub output: PathBuf,
+}
+
+impl WorkspaceArgs {
+    fn load(&self) -> Result<(PathBuf, Workspace)> {
+        let path = match &self.manifest {
+            Some(path) => path.clone(),
+            None => Workspace::find(&std::env::current_dir()?)?,
+        };
+        let workspace = Workspace::load(&path)?;
+        Ok((path, workspace))
+    }
+}
+
+/// Execute a workspace command.
+pub fn handle(command: WorkspaceCommand) -> Result<()> {
+    match command {
+        WorkspaceCommand::Status(args) => status(args),
+        WorkspaceCommand::Validate(args) => validate(args),
+        WorkspaceCommand::Assemble(args) => assemble(args),
+    }
+}
+
+fn status(args: WorkspaceArgs) -> Result<()> {
+    let (path, workspace) = args.load()?;
+    println!(
+        "Workspace {} ({})",
+        workspace.name.as_deref().unwrap_or("(unnamed)"),
+        path.display()
+    );
+    let order = workspace.order()?;
+    let width = order.iter().map(|d| d.name.len()).max().unwrap_or(0);
+    let path_width = order
+        .iter()
+        .map(|d| d.path.display().to_string().len())
+        .max()
+        .unwrap_or(0);
+    for entry in order {
+        let deck_path = workspace.deck_path(entry);
+        let detail = match Deck::load(&deck_path) {
+            Ok(deck) => {
+                let hash = deck.hash()?;
+                let mut detail = format!(
+                    "{:>5} cards  {:<9} {}",
+                    deck.cards.len(),
+                    deck.header.state,
+                    &hash[..12]
+                );
+                if deck.header.readonly {
+                    detail.push_str("  readonly");
+                }
+                detail
+            }
+            Err(_) if !deck_path.exists() => "missing".to_string(),
+            Err(err) => format!("unreadable: {:#}", err),
+        };
+        println!(
+            "  {:<width$}  {:<8} {:<path_width$}  {}",
+            entry.name,
+            entry.role,
+            entry.path.display().to_string(),
+            detail
+        );
+    }
+    for problem in workspace.problems() {
+        println!("problem: {}", problem);
+    }
+    Ok(())
+}
+
+fn validate(args: WorkspaceValidateArgs) -> Result<()> {
+    let (_, workspace) = args.workspace.load()?;
+    let mut outcome = CheckOutcome::default();
+    for problem in workspace.problems() {
+        println!("violation: {}", problem);
+        outcome.violations += 1;
+    }
+    for entry in &workspace.decks {
+        let deck_path = workspace.deck_path(entry);
+        if !deck_path.exists() {
+            println!(
+                "violation: {}: {} is missing",
+                entry.name,
+                deck_path.display()
+            );
+            outcome.violations += 1;
+            continue;
+        }
+        let deck = match load_deck(&deck_path) {
+            Ok(deck) => deck,
+            Err(err) => {
+                println!("violation: {}: {:#}", entry.name, err);
+                outcome.violations += 1;
+                continue;
+            }
+        };
+        let language = deck.header.language.as_deref();
+        if entry.role == DeckRole::Jcl && language.is_some_and(|l| l != "jcl") {
+            println!(
+                "warning: {}: jcl deck is marked as {}",
+                entry.name,
+                language.unwrap_or_default()
+            );
+            outcome.warnings += 1;
+        }
+        if let Some(language) = language.and_then(|l| LanguageRegistry::get(l).ok()) {
+            for finding in language.check_deck(&deck) {
+                println!(
+                    "warning: {}: card {}: {}: {}",
+                    entry.name,
+                    finding.card + 1,
+                    finding.check,
+                    finding.message
+                );
+                outcome.warnings += 1;
+            }
+        }
+    }
+    if outcome.warnings == 0 && outcome.violations == 0 {
+        println!("{} deck(s) valid", workspace.decks.len());
+    }
+    outcome.finish("workspace validate", args.max_warnings)
+}
+
+fn assemble(args: WorkspaceAssembleArgs) -> Result<()> {
+    let (_, workspace) = args.workspace.load()?;
+    if let Some(problem) = workspace.problems().into_iter().next() {
+        return Err(anyhow!("cannot assemble: {}", problem));
+    }
+    let order = workspace.order()?;
+    let jcl = order
+        .iter()
+        .find(|d| d.role == DeckRole::Jcl)
+        .ok_or_else(|| anyhow!("the workspace has no deck with the jcl role"))?;
+    let jcl = load_deck(&workspace.deck_path(jcl))?;
+    // Decks sharing a role are concatenated in dependency order.
+    let section = |role: DeckRole| -> Result<Option<Deck>> {
+        let mut combined: Option<Deck> = None;
+        for entry in order.iter().filter(|d| d.role == role) {
+            let deck = load_deck(&workspace.deck_path(entry))?;
+            match combined.as_mut() {
+                Some(all) => all.cards.extend(deck.cards),
+                None => combined = Some(deck),
+            }
+        }
+        Ok(combined)
+    };
+    let program = section(DeckRole::Program)?;
+    let data = section(DeckRole::Data)?;
+    let mut job = assemble_job(&jcl, program.as_ref(), data.as_ref(), &workspace.job)?;
+    let name = workspace.name.as_deref().unwrap_or("workspace");
+    job.log_action(format!("workspace assemble {}", name));
+    save_deck(&mut job, &args.output)?;
+    let decks = order.iter().filter(|d| d.role != DeckRole::Library).count();
+    println!(
+        "Assembled {} cards from {} deck(s) into {}",
+        job.cards.len(),
+        decks,
+        args.output.display()
+    );
+    Ok(())
+}
@@ -0,0 +1,518 @@
+//! Verification baselines and pass results, kept in the deck header.
+//!
+//! Each baseline records who captured it, when, and a SHA-256 of the
+//! captured text so a tampered or truncated baseline is caught before a
+//! verification pass relies on it. Decks from older releases kept this state
+//! in sidecar files; [`Deck::adopt_verify_sidecars`] moves it into the
+//! header.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result, anyhow};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::core::deck::{Deck, current_actor};
+
+/// Name used when a baseline is captured without `--name`.
+pub const DEFAULT_BASELINE: &str = "default";
+
+/// One captured verification snapshot.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Baseline {
+    pub name: String,
+    pub created_at: DateTime<Utc>,
+    pub operator: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    pub hash: String,
+    pub text: String,
+}
+
+impl Baseline {
+    /// Capture `text` under `name`, stamping the current time and OS user.
+    pub fn capture(name: &str, label: Option<String>, text: String) -> Self {
+        Self {
+            name: name.to_string(),
+            created_at: Utc::now(),
+            operator: current_actor(),
+            label,
+            hash: text_hash(&text),
+            text,
+        }
+    }
+
+    /// Number of card lines in the snapshot.
+    pub fn card_count(&self) -> usize {
+        self.text.lines().count()
+    }
+
+    /// Return the snapshot text after checking it still matches its hash.
+    pub fn verified_text(&self) -> Result<&str> {
+        if text_hash(&self.text) != self.hash {
+            return Err(anyhow!(
+                "baseline '{}' does not match its recorded hash",
+                self.name
+            ));
+        }
+        Ok(&self.text)
+    }
+}
+
+/// Passes kept in a deck header; older results are dropped.
+pub const PASS_HISTORY: usize = 20;
+
+/// Report lines kept for a pass; the rest are summarized in one line.
+pub const PASS_DIFF_LINES: usize = 200;
+
+/// Result of one verification pass.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct VerifyPass {
+    pub at: DateTime<Utc>,
+    pub operator: String,
+    /// Baseline compared against; `None` for a hole-level pass (and for a
+    /// report adopted from a legacy `.verify.diff`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub baseline: Option<String>,
+    /// Hash of that baseline when the pass ran.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub baseline_hash: Option<String>,
+    /// Cards (or lines) compared.
+    #[serde(default)]
+    pub cards: usize,
+    /// Cards (or lines) that differ outside the masked columns.
+    pub differing: usize,
+    /// Which cards differ, and where; empty for a report adopted from a
+    /// legacy `.verify.diff`, which did not record them.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub mismatches: Vec<VerifyMismatch>,
+    /// The report `punch verify report` shows, cut to [`PASS_DIFF_LINES`]
+    /// lines. Only the latest pass keeps it.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub diff: String,
+}
+
+/// A card that failed verification.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct VerifyMismatch {
+    /// One-based card (or line) number.
+    pub card: usize,
+    /// One-based columns that differ outside the masks.
+    pub columns: Vec<usize>,
+}
+
+impl VerifyPass {
+    /// Record a pass of `cards` cards against `baseline`, stamping the
+    /// current time and OS user. A `diff` longer than [`PASS_DIFF_LINES`]
+    /// is cut short; `mismatches` still names every differing card.
+    pub fn record(
+        baseline: Option<&Baseline>,
+        cards: usize,
+        mismatches: Vec<VerifyMismatch>,
+        diff: String,
+    ) -> Self {
+        Self {
+            at: Utc::now(),
+            operator: current_actor(),
+            baseline: baseline.map(|b| b.name.clone()),
+            baseline_hash: baseline.map(|b| b.hash.clone()),
+            cards,
+            differing: mismatches.len(),
+            mismatches,
+            diff: cap_diff(diff),
+        }
+    }
+
+    pub fn passed(&self) -> bool {
+        self.differing == 0
+    }
+
+    /// Machine-readable form of the pass for the deck named `deck`.
+    pub fn report(&self, deck: &str) -> VerifyReport {
+        let results = (1..=self.cards)
+            .map(|card| {
+                let columns = self
+                    .mismatches
+                    .iter()
+                    .find(|m| m.card == card)
+                    .map(|m| m.columns.clone());
+                CardResult {
+                    card,
+                    passed: columns.is_none(),
+                    columns: columns.unwrap_or_default(),
+                }
+            })
+            .collect();
+        VerifyReport {
+            deck: deck.to_string(),
+            at: self.at,
+            operator: self.operator.clone(),
+            baseline: self.baseline.clone(),
+            baseline_hash: self.baseline_hash.clone(),
+            cards: self.cards,
+            passed: self.passed(),
+            differing: self.differing,
+            results,
+        }
+    }
+}
+
+/// Verification result of one card in a [`VerifyReport`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CardResult {
+    pub card: usize,
+    pub passed: bool,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub columns: Vec<usize>,
+}
+
+/// A verification pass laid out for CI: `punch verify report --format json`
+/// serializes it and [`VerifyReport::to_junit`] renders it for test
+/// reporters.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct VerifyReport {
+    pub deck: String,
+    pub at: DateTime<Utc>,
+    pub operator: String,
+    pub baseline: Option<String>,
+    pub baseline_hash: Option<String>,
+    pub cards: usize,
+    pub passed: bool,
+    pub differing: usize,
+    /// One entry per compared card, in order.
+    pub results: Vec<CardResult>,
+}
+
+impl VerifyReport {
+    /// JUnit XML with the deck as the test suite and each card as a test
+    /// case. A pass without per-card results (adopted from a legacy
+    /// report) is a single case for the whole deck.
+    pub fn to_junit(&self) -> String {
+        let failures = if self.results.is_empty() {
+            usize::from(!self.passed)
+        } else {
+            self.differing
+        };
+        let tests = self.results.len().max(1);
+        let suite = xml_escape(&self.deck);
+        let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        xml.push_str(&format!(
+            "<testsuites name=\"punch verify\" tests=\"{}\" failures=\"{}\">\n",
+            tests, failures
+        ));
+        xml.push_str(&format!(
+            "  <testsuite name=\"{}\" tests=\"{}\" failures=\"{}\" timestamp=\"{}\">\n",
+            suite,
+            tests,
+            failures,
+            self.at.format("%Y-%m-%dT%H:%M:%S")
+        ));
+        if self.results.is_empty() {
+            xml.push_str(&format!(
+                "    <testcase classname=\"{}\" name=\"deck\"",
+                suite
+            ));
+            if self.passed {
+                xml.push_str("/>\n");
+            } else {
+                xml.push_str(&format!(
+                    ">\n      <failure message=\"{} card(s) differ\"/>\n    </testcase>\n",
+                    self.differing
+                ));
+            }
+        }
+        for result in &self.results {
+            xml.push_str(&format!(
+                "    <testcase classname=\"{}\" name=\"card {:04}\"",
+                suite, result.card
+            ));
+            if result.passed {
+                xml.push_str("/>\n");
+                continue;
+            }
+            let columns: Vec<String> = result.columns.iter().map(|c| c.to_string()).collect();
+            let message = match columns.as_slice() {
+                [column] => format!("column {} differs", column),
+                _ => format!("columns {} differ", columns.join(",")),
+            };
+            xml.push_str(&format!(
+                ">\n      <failure message=\"{}\"/>\n    </testcase>\n",
+                message
+            ));
+        }
+        xml.push_str("  </testsuite>\n</testsuites>\n");
+        xml
+    }
+}
+
+/// Keep the first [`PASS_DIFF_LINES`] lines of a pass report.
+fn cap_diff(diff: String) -> String {
+    let total = diff.lines().count();
+    if total <= PASS_DIFF_LINES {
+        return diff;
+    }
+    let mut capped: String = diff
+        .lines()
+        .take(PASS_DIFF_LINES)
+        .flat_map(|line| [line, "\n"])
+        .collect();
+    capped.push_str(&format!(
+        "... {} more report line(s) not kept\n",
+        total - PASS_DIFF_LINES
+    ));
+    capped
+}
+
+fn xml_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Verification state kept in the deck header: the named baselines and the
+/// latest passes against them.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Verification {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub baselines: Vec<Baseline>,
+    /// Most recent passes, oldest first (at most [`PASS_HISTORY`]).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub passes: Vec<VerifyPass>,
+}
+
+impl Verification {
+    pub fn is_empty(&self) -> bool {
+        self.baselines.is_empty() && self.passes.is_empty()
+    }
+
+    /// Look up a baseline by name.
+    pub fn get(&self, name: &str) -> Result<&Baseline> {
+        self.baselines
+            .iter()
+            .find(|b| b.name == name)
+            .ok_or_else(|| anyhow!("no verification baseline named '{}'", name))
+    }
+
+    /// Add a baseline, replacing any existing one with the same name.
+    pub fn upsert(&mut self, baseline: Baseline) {
+        self.baselines.retain(|b| b.name != baseline.name);
+        self.baselines.push(baseline);
+    }
+
+    /// Remove and return a baseline by name.
+    pub fn remove(&mut self, name: &str) -> Result<Baseline> {
+        let idx = self
+            .baselines
+            .iter()
+            .position(|b| b.name == name)
+            .ok_or_else(|| anyhow!("no verification baseline named '{}'", name))?;
+        Ok(self.baselines.remove(idx))
+    }
+
+    /// Append a pass, dropping the oldest beyond [`PASS_HISTORY`]. Earlier
+    /// passes keep their counts and mismatches but not their report.
+    pub fn record_pass(&mut self, pass: VerifyPass) {
+        for earlier in &mut self.passes {
+            earlier.diff.clear();
+        }
+        self.passes.push(pass);
+        let excess = self.passes.len().saturating_sub(PASS_HISTORY);
+        self.passes.drain(..excess);
+    }
+
+    pub fn latest_pass(&self) -> Option<&VerifyPass> {
+        self.passes.last()
+    }
+}
+
+/// Sidecars older releases kept verification state in, for `prog.deck`:
+/// `prog.verify.json` (named baselines), `prog.verify.base` (a bare default
+/// snapshot) and `prog.verify.diff` (the latest pass report).
+pub fn legacy_sidecar_paths(deck: &Path) -> [PathBuf; 3] {
+    ["verify.json", "verify.base", "verify.diff"].map(|ext| {
+        let mut path = deck.to_path_buf();
+        path.set_extension(ext);
+        path
+    })
+}
+
+impl Deck {
+    /// The header's verification section, created empty if missing.
+    pub fn verification_mut(&mut self) -> &mut Verification {
+        self.header.verification.get_or_insert_default()
+    }
+
+    /// Move verification state from the legacy sidecars of the deck at
+    /// `path` into the header. Baselines already in the header win over
+    /// sidecar ones of the same name, and a sidecar report is only adopted
+    /// when the header has no passes. Returns the sidecars read, for the
+    /// caller to remove once the deck is saved.
+    pub fn adopt_verify_sidecars(&mut self, path: &Path) -> Result<Vec<PathBuf>> {
+        let [store, base, diff] = legacy_sidecar_paths(path);
+        let mut adopted = Vec::new();
+        if store.exists() {
+            let raw = fs::read_to_string(&store)
+                .with_context(|| format!("failed to read {}", store.display()))?;
+            let baselines: Vec<Baseline> = serde_json::from_str(&raw)
+                .with_context(|| format!("failed to parse {}", store.display()))?;
+            let verification = self.verification_mut();
+            for baseline in baselines {
+                if verification.get(&baseline.name).is_err() {
+                    verification.baselines.push(baseline);
+                }
+            }
+            adopted.push(store);
+        }
+        if base.exists() {
+            let verification = self.verification_mut();
+            if verification.get(DEFAULT_BASELINE).is_err() {
+                let text = fs::read_to_string(&base)
+                    .with_context(|| format!("failed to read {}", base.display()))?;
+                let mut baseline = Baseline::capture(DEFAULT_BASELINE, None, text);
+                baseline.operator = "unknown".to_string();
+                if let Some(modified) = modified_at(&base) {
+                    baseline.created_at = modified;
+                }
+                baseline.label = Some(format!("imported from {}", base.display()));
+                verification.baselines.push(baseline);
+            }
+            adopted.push(base);
+        }
+        if diff.exists() {
+            let verification = self.verification_mut();
+            if verification.passes.is_empty() {
+                let report = fs::read_to_string(&diff)
+                    .with_context(|| format!("failed to read {}", diff.display()))?;
+                // Legacy reports start each difference on an unindented line
+                // and say "verification passed" when there were none.
+                let differing = if report.contains("verification passed") {
+                    0
+                } else {
+                    report
+                        .lines()
+                        .filter(|line| !line.is_empty() && !line.starts_with(' '))
+                        .count()
+                };
+                let mut pass = VerifyPass::record(None, 0, Vec::new(), report);
+                pass.differing = differing;
+                pass.operator = "unknown".to_string();
+                if let Some(modified) = modified_at(&diff) {
+                    pass.at = modified;
+                }
+                verification.record_pass(pass);
+            }
+            adopted.push(diff);
+        }
+        Ok(adopted)
+    }
+}
+
+fn modified_at(path: &Path) -> Option<DateTime<Utc>> {
+    fs::metadata(path)
+        .and_then(|m| m.modified())
+        .ok()
+        .map(Into::into)
+}
+
+fn text_hash(text: &str) -> String {
+    format!("{:02x}", Sha256::digest(text.as_bytes()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn upsert_replaces_and_hash_detects_tampering() {
+        let mut verification = Verification::default();
+        verification.upsert(Baseline::capture("default", None, "A\nB".into()));
+        verification.upsert(Baseline::capture(
+            "default",
+            Some("retake".into()),
+            "A\nC".into(),
+        ));
+        assert_eq!(verification.baselines.len(), 1);
+        let mut baseline = verification.get("default").unwrap().clone();
+        assert_eq!(baseline.verified_text().unwrap(), "A\nC");
+        baseline.text.push('!');
+        assert!(baseline.verified_text().is_err());
+        assert!(verification.remove("other").is_err());
+
+        for cards in 0..PASS_HISTORY + 2 {
+            verification.record_pass(VerifyPass::record(
+                Some(&baseline),
+                cards,
+                Vec::new(),
+                String::new(),
+            ));
+        }
+        assert_eq!(verification.passes.len(), PASS_HISTORY);
+        assert_eq!(verification.latest_pass().unwrap().cards, PASS_HISTORY + 1);
+        assert_eq!(verification.passes[0].cards, 2);
+    }
+
+    #[test]
+    fn only_the_latest_pass_keeps_a_capped_report() {
+        let long: String = (1..=PASS_DIFF_LINES + 5)
+            .map(|n| format!("line {}\n", n))
+            .collect();
+        let mut verification = Verification::default();
+        verification.record_pass(VerifyPass::record(None, 1, Vec::new(), long.clone()));
+        let diff = &verification.latest_pass().unwrap().diff;
+        assert_eq!(diff.lines().count(), PASS_DIFF_LINES + 1);
+        assert!(diff.ends_with("... 5 more report line(s) not kept\n"));
+
+        verification.record_pass(VerifyPass::record(None, 1, Vec::new(), "short\n".into()));
+        assert!(verification.passes[0].diff.is_empty());
+        assert_eq!(verification.passes[1].diff, "short\n");
+    }
+
+    #[test]
+    fn reports_list_every_card() {
+        let mismatch = VerifyMismatch {
+            card: 2,
+            columns: vec![7, 9],
+        };
+        let pass = VerifyPass::record(None, 3, vec![mismatch], String::new());
+        let report = pass.report("a&b.deck");
+        let passed: Vec<bool> = report.results.iter().map(|r| r.passed).collect();
+        assert_eq!(passed, [true, false, true]);
+        assert_eq!(report.results[1].columns, [7, 9]);
+
+        let xml = report.to_junit();
+        assert!(xml.contains("<testsuite name=\"a&amp;b.deck\" tests=\"3\" failures=\"1\""));
+        assert!(
+            xml.contains("name=\"card 0002\">\n      <failure message=\"columns 7,9 differ\"/>")
+        );
+        assert_eq!(xml.matches("<testcase ").count(), 3);
+    }
+
+    #[test]
+    fn legacy_sidecars_move_into_the_header() {
+        use crate::core::deck::DeckHeader;
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("prog.deck");
+        let [_, base, diff] = legacy_sidecar_paths(&path);
+        fs::write(&base, "HELLO").unwrap();
+        fs::write(&diff, "line    1:\n  expected |A|\n  actual   |B|\n").unwrap();
+
+        let mut deck = Deck::new(DeckHeader::new(None, None, Vec::new()));
+        let adopted = deck.adopt_verify_sidecars(&path).unwrap();
+        assert_eq!(adopted, [base, diff]);
+        let verification = deck.header.verification.as_ref().unwrap();
+        assert_eq!(
+            verification
+                .get(DEFAULT_BASELINE)
+                .unwrap()
+                .verified_text()
+                .unwrap(),
+            "HELLO"
+        );
+        let pass = verification.latest_pass().unwrap();
+        assert_eq!((pass.differing, pass.passed()), (1, false));
+    }
+}
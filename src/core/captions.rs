@@ -0,0 +1,187 @@
+//! Fixed strings printed by the ASCII card renderer and `render listing`.
+//!
+//! Downstream tools parse this output and exhibits want it in the visitor's
+//! language, so the banner, row labels and listing captions come from a
+//! [`Captions`] table: a built-in [`Locale`] or a TOML file.
+
+use std::path::Path;
+use std::sync::LazyLock;
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+/// Built-in caption tables.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Locale {
+    #[default]
+    English,
+    German,
+    French,
+}
+
+/// Strings used when rendering cards as text. Keys missing from a captions
+/// file keep their English value.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Captions {
+    /// First line of each ASCII card; empty leaves it out.
+    pub banner: String,
+    /// Labels for rows 12, 11, 0–9, printed right-aligned in three columns.
+    pub row_labels: [String; 12],
+    pub card: String,
+    pub seq: String,
+    pub card_type: String,
+    pub note: String,
+    pub color: String,
+    pub text: String,
+    pub punches: String,
+    /// Shown for a card without a sequence number.
+    pub no_seq: String,
+    /// Shown instead of text for column-binary cards.
+    pub stored_punches: String,
+    pub patches: String,
+    pub patched_by: String,
+    pub missing: String,
+}
+
+static ENGLISH: LazyLock<Captions> = LazyLock::new(|| Captions::for_locale(Locale::English));
+
+impl Default for Captions {
+    fn default() -> Self {
+        Self::for_locale(Locale::English)
+    }
+}
+
+impl Captions {
+    /// Shared English table, used when no captions are given.
+    pub fn english() -> &'static Captions {
+        &ENGLISH
+    }
+
+    /// Built-in table for `locale`.
+    pub fn for_locale(locale: Locale) -> Self {
+        let (banner, words) = match locale {
+            Locale::English => (
+                "IBM 5081 (80 cols) [IBM029]",
+                [
+                    "Card",
+                    "seq",
+                    "type",
+                    "Note",
+                    "Color",
+                    "Text",
+                    "Punches",
+                    "(none)",
+                    "(stored punches)",
+                    "Patches",
+                    "Patched by",
+                    "(missing)",
+                ],
+            ),
+            Locale::German => (
+                "IBM 5081 (80 Spalten) [IBM029]",
+                [
+                    "Karte",
+                    "Folge",
+                    "Typ",
+                    "Notiz",
+                    "Farbe",
+                    "Text",
+                    "Lochungen",
+                    "(keine)",
+                    "(gespeicherte Lochungen)",
+                    "Korrigiert",
+                    "Korrigiert durch",
+                    "(fehlt)",
+                ],
+            ),
+            Locale::French => (
+                "IBM 5081 (80 colonnes) [IBM029]",
+                [
+                    "Carte",
+                    "séq",
+                    "type",
+                    "Note",
+                    "Couleur",
+                    "Texte",
+                    "Perforations",
+                    "(aucun)",
+                    "(perforations enregistrées)",
+                    "Corrige",
+                    "Corrigée par",
+                    "(absente)",
+                ],
+            ),
+        };
+        let [
+            card,
+            seq,
+            card_type,
+            note,
+            color,
+            text,
+            punches,
+            no_seq,
+            stored_punches,
+            patches,
+            patched_by,
+            missing,
+        ] = words.map(str::to_string);
+        Self {
+            banner: banner.to_string(),
+            row_labels: ["12", "11", "0", "1", "2", "3", "4", "5", "6", "7", "8", "9"]
+                .map(str::to_string),
+            card,
+            seq,
+            card_type,
+            note,
+            color,
+            text,
+            punches,
+            no_seq,
+            stored_punches,
+            patches,
+            patched_by,
+            missing,
+        }
+    }
+
+    /// Name `encoder` in place of the `[IBM029]` tag of the built-in banners.
+    pub fn name_encoder(&mut self, encoder: &str) {
+        self.banner = self.banner.replace("[IBM029]", &format!("[{}]", encoder));
+    }
+
+    /// Parse a TOML captions table.
+    pub fn parse(text: &str) -> Result<Self> {
+        toml::from_str(text).context("invalid captions file")
+    }
+
+    /// Read a TOML captions file.
+    pub fn load(path: &Path) -> Result<Self> {
+        let text = std::fs::read_to_string(path)
+            .with_context(|| format!("failed to read captions file {}", path.display()))?;
+        Self::parse(&text).with_context(|| format!("failed to load {}", path.display()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn captions_file_overrides_english_defaults() {
+        let captions = Captions::parse(
+            r#"
+            card = "Lochkarte"
+            row_labels = ["Y", "X", "0", "1", "2", "3", "4", "5", "6", "7", "8", "9"]
+            "#,
+        )
+        .unwrap();
+        assert_eq!(captions.card, "Lochkarte");
+        assert_eq!(captions.row_labels[0], "Y");
+        assert_eq!(captions.banner, Captions::english().banner);
+        assert!(Captions::parse("banner = \"\"").unwrap().banner.is_empty());
+        assert!(Captions::parse("banner_text = \"x\"").is_err());
+        assert_eq!(Captions::for_locale(Locale::German).seq, "Folge");
+    }
+}
@@ -0,0 +1,176 @@
+//! Deck encryption at rest (`punch deck encrypt/decrypt`).
+//!
+//! Decks keyed from historical records can carry personal data, so archives
+//! may keep them encrypted with ChaCha20-Poly1305. An encrypted deck file is
+//! [`MAGIC`], a 12-byte random nonce and the sealed JSONL. [`Deck::load`]
+//! recognises the magic and decrypts with the key from [`KEY_ENV`] or
+//! [`KEYFILE_ENV`]; a deck loaded that way is re-encrypted on save.
+//!
+//! [`Deck::load`]: crate::core::deck::Deck::load
+
+use std::fmt;
+use std::path::Path;
+
+use anyhow::{Context, Result, anyhow, bail};
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng, Payload};
+use chacha20poly1305::{ChaCha20Poly1305, Nonce};
+
+/// First bytes of every encrypted deck file.
+pub const MAGIC: &[u8] = b"PUNCHCARD-ENC1\n";
+
+/// Environment variable holding the key as 64 hex digits.
+pub const KEY_ENV: &str = "PUNCH_DECK_KEY";
+
+/// Environment variable naming a key file.
+pub const KEYFILE_ENV: &str = "PUNCH_DECK_KEYFILE";
+
+const NONCE_LEN: usize = 12;
+
+/// 256-bit deck encryption key.
+#[derive(Clone, PartialEq, Eq)]
+pub struct DeckKey([u8; 32]);
+
+impl fmt::Debug for DeckKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("DeckKey(..)")
+    }
+}
+
+impl DeckKey {
+    /// Fresh random key from the operating system.
+    pub fn generate() -> Self {
+        Self(ChaCha20Poly1305::generate_key(&mut OsRng).into())
+    }
+
+    /// Parse 64 hex digits, ignoring surrounding whitespace.
+    pub fn from_hex(text: &str) -> Result<Self> {
+        from_hex(text.trim())
+            .ok()
+            .and_then(|bytes| bytes.try_into().ok())
+            .map(Self)
+            .ok_or_else(|| anyhow!("a deck key is 64 hex digits"))
+    }
+
+    /// Key as 64 lowercase hex digits, the format key files are written in.
+    pub fn to_hex(&self) -> String {
+        to_hex(&self.0)
+    }
+
+    /// Read a key file holding 64 hex digits or 32 raw bytes.
+    pub fn from_file(path: &Path) -> Result<Self> {
+        let raw = std::fs::read(path)
+            .with_context(|| format!("failed to read key file {}", path.display()))?;
+        if let Ok(key) = <[u8; 32]>::try_from(raw.as_slice()) {
+            return Ok(Self(key));
+        }
+        let text = String::from_utf8(raw).map_err(|_| anyhow!("key file is not hex text"));
+        text.and_then(|text| Self::from_hex(&text))
+            .with_context(|| format!("invalid key file {}", path.display()))
+    }
+
+    /// Key from [`KEY_ENV`], else from the file named by [`KEYFILE_ENV`];
+    /// `None` when neither is set.
+    pub fn from_env() -> Result<Option<Self>> {
+        if let Ok(hex) = std::env::var(KEY_ENV) {
+            return Self::from_hex(&hex)
+                .map(Some)
+                .with_context(|| format!("invalid {}", KEY_ENV));
+        }
+        match std::env::var_os(KEYFILE_ENV) {
+            Some(path) => Self::from_file(Path::new(&path)).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    fn cipher(&self) -> ChaCha20Poly1305 {
+        ChaCha20Poly1305::new(&self.0.into())
+    }
+}
+
+/// Bytes as lowercase hex digits, the form keys and signatures are stored in.
+pub(crate) fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// Bytes from hex digits in either case.
+pub(crate) fn from_hex(text: &str) -> Result<Vec<u8>> {
+    if !text.len().is_multiple_of(2) || !text.bytes().all(|b| b.is_ascii_hexdigit()) {
+        bail!("expected hex digits");
+    }
+    (0..text.len())
+        .step_by(2)
+        .map(|idx| Ok(u8::from_str_radix(&text[idx..idx + 2], 16)?))
+        .collect()
+}
+
+/// Whether `data` is an encrypted deck.
+pub fn is_encrypted(data: &[u8]) -> bool {
+    data.starts_with(MAGIC)
+}
+
+/// Seal `plaintext` under `key` with a fresh nonce.
+pub fn encrypt(plaintext: &[u8], key: &DeckKey) -> Result<Vec<u8>> {
+    let nonce = ChaCha20Poly1305::generate_nonce(&mut OsRng);
+    let sealed = key
+        .cipher()
+        .encrypt(
+            &nonce,
+            Payload {
+                msg: plaintext,
+                aad: MAGIC,
+            },
+        )
+        .map_err(|_| anyhow!("deck encryption failed"))?;
+    let mut out = Vec::with_capacity(MAGIC.len() + NONCE_LEN + sealed.len());
+    out.extend_from_slice(MAGIC);
+    out.extend_from_slice(&nonce);
+    out.extend_from_slice(&sealed);
+    Ok(out)
+}
+
+/// Open an encrypted deck; fails on a wrong key or a tampered file.
+pub fn decrypt(data: &[u8], key: &DeckKey) -> Result<Vec<u8>> {
+    let body = data
+        .strip_prefix(MAGIC)
+        .ok_or_else(|| anyhow!("not an encrypted deck"))?;
+    if body.len() < NONCE_LEN {
+        bail!("encrypted deck is truncated");
+    }
+    let (nonce, sealed) = body.split_at(NONCE_LEN);
+    let nonce: [u8; NONCE_LEN] = nonce.try_into().expect("split at nonce length");
+    key.cipher()
+        .decrypt(
+            &Nonce::from(nonce),
+            Payload {
+                msg: sealed,
+                aad: MAGIC,
+            },
+        )
+        .map_err(|_| anyhow!("cannot decrypt deck: wrong key or corrupted file"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sealed_decks_open_only_with_their_key() {
+        let key = DeckKey::generate();
+        let plain = b"{\"kind\":\"header\"}\n";
+        let sealed = encrypt(plain, &key).unwrap();
+        assert!(is_encrypted(&sealed));
+        assert_ne!(encrypt(plain, &key).unwrap(), sealed);
+        assert_eq!(decrypt(&sealed, &key).unwrap(), plain);
+
+        assert!(decrypt(&sealed, &DeckKey::generate()).is_err());
+        let mut tampered = sealed.clone();
+        *tampered.last_mut().unwrap() ^= 1;
+        assert!(decrypt(&tampered, &key).is_err());
+
+        assert_eq!(DeckKey::from_hex(&key.to_hex()).unwrap(), key);
+        assert!(DeckKey::from_hex("abc").is_err());
+        assert!(DeckKey::from_hex(&"0g".repeat(32)).is_err());
+        assert_eq!(from_hex("00Ff").unwrap(), [0x00, 0xff]);
+        assert_eq!(to_hex(&[0x00, 0xff]), "00ff");
+    }
+}
@@ -1,16 +1,42 @@
-use crate::core::encoding::{EncodeError, PunchEncoding};
+use crate::core::baseline::Verification;
+use crate::core::crypt::{self, DeckKey};
+use crate::core::diff::ColumnChange;
+use crate::core::encoding::{CellMask, EncodeError, PunchEncoding};
+use crate::core::machine::MachineRules;
+use crate::core::merkle::{self, CardHashMismatch};
+use crate::core::patches::PatchTarget;
+use crate::core::rng::SplitMix64;
+use crate::core::signing::DeckSignature;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
-use std::collections::VecDeque;
+use std::borrow::Cow;
 use std::fmt;
 use std::fs::OpenOptions;
-use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::io::{BufRead, BufReader, BufWriter, Read, Write};
 use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::{Mutex, PoisonError};
 
 use anyhow::{Context, Result, anyhow};
 
-const DECK_VERSION: u8 = 1;
+/// Version 2 stores each card's hash on its line (see [`crate::core::merkle`]).
+pub(crate) const DECK_VERSION: u8 = 2;
+/// Header fields [`Deck::hash`] leaves out: when the deck was made and what
+/// was done to it are not its content, and a canonical deck copied without
+/// its audit sidecar lacks both. Signatures sign a hash themselves.
+const UNHASHED_HEADER_FIELDS: &[&str] = &["created_at", "history", "signatures"];
+/// Header fields [`Deck::signing_hash`] leaves out as well: the file format,
+/// the readonly lock, where the deck is in its workflow and its verification
+/// records all change while the cards and their description stay put.
+const UNSIGNED_HEADER_FIELDS: &[&str] = &[
+    "version",
+    "readonly",
+    "state",
+    "state_since",
+    "verification",
+    "canonical",
+];
 const MAX_COLS: usize = 80;
 
 /// Inclusive column range that can be marked as protected.
@@ -36,10 +62,45 @@ impl ColumnRange {
     }
 }
 
+impl FromStr for ColumnRange {
+    type Err = anyhow::Error;
+
+    /// Parse `START-END`, or a single column `N`.
+    fn from_str(s: &str) -> Result<Self> {
+        let (start, end) = s.split_once('-').unwrap_or((s, s));
+        let start: usize = start
+            .trim()
+            .parse()
+            .map_err(|_| anyhow!("start column must be a number"))?;
+        let end: usize = end
+            .trim()
+            .parse()
+            .map_err(|_| anyhow!("end column must be a number"))?;
+        Self::new(start, end)
+    }
+}
+
+/// Columns 73–80, the conventional sequence/identification field.
+pub const SEQUENCE_FIELD: ColumnRange = ColumnRange { start: 73, end: 80 };
+
+/// Format a sequence number right-justified to fill [`SEQUENCE_FIELD`].
+///
+/// Values wider than the field are refused: keeping only their low-order
+/// digits would silently repeat numbers already used earlier in the deck.
+pub fn sequence_field(value: usize) -> Result<String, EncodeError> {
+    let width = SEQUENCE_FIELD.end - SEQUENCE_FIELD.start + 1;
+    let repr = format!("{:>width$}", value);
+    if repr.len() > width {
+        return Err(EncodeError::SequenceOverflow(value));
+    }
+    Ok(repr)
+}
+
 /// High-level classification for cards stored in a deck.
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
 #[serde(rename_all = "snake_case")]
 pub enum CardType {
+    #[default]
     Code,
     Data,
     Jcl,
@@ -48,12 +109,6 @@ pub enum CardType {
     Patch,
 }
 
-impl Default for CardType {
-    fn default() -> Self {
-        CardType::Code
-    }
-}
-
 /// Extra metadata such as color or inline notes.
 #[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq, Eq)]
 pub struct CardMeta {
@@ -61,23 +116,21 @@ pub struct CardMeta {
     pub color: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub note: Option<String>,
+    /// Card this patch card corrects.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub patches: Option<PatchTarget>,
 }
 
 /// Encoding choices made while capturing the card.
-#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
 #[serde(rename_all = "lowercase")]
 pub enum EncodingKind {
+    #[default]
     Hollerith,
     Ascii,
     Ebcdic,
 }
 
-impl Default for EncodingKind {
-    fn default() -> Self {
-        EncodingKind::Hollerith
-    }
-}
-
 /// Single card stored in a deck file.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct CardRecord {
@@ -116,18 +169,88 @@ impl CardRecord {
         })
     }
 
+    /// Construct a column-binary card that stores raw hole patterns instead of text.
+    pub fn from_punches(columns: &[CellMask], card_type: CardType) -> Self {
+        Self {
+            text: None,
+            punches: Some(crate::core::formats::punches_to_hex(columns)),
+            encoding: EncodingKind::Hollerith,
+            seq: None,
+            card_type,
+            protected_cols: Vec::new(),
+            meta: CardMeta::default(),
+        }
+    }
+
     /// Update the optional sequence number attached to the card.
     pub fn ensure_seq(&mut self, seq: Option<usize>) {
         self.seq = seq;
     }
 
+    /// Number the card `value`, punching it into [`SEQUENCE_FIELD`] of text
+    /// cards. Fails, leaving the card untouched, if `value` is wider than the
+    /// field.
+    pub fn set_sequence(&mut self, value: usize) -> Result<(), EncodeError> {
+        let field = sequence_field(value)?;
+        self.seq = Some(value);
+        if let Some(text) = self.text.as_mut() {
+            let mut chars: Vec<char> = text.chars().collect();
+            while chars.len() < MAX_COLS {
+                chars.push(' ');
+            }
+            let start_idx = SEQUENCE_FIELD.start - 1;
+            for (offset, ch) in field.chars().enumerate() {
+                chars[start_idx + offset] = ch;
+            }
+            *text = chars.into_iter().collect();
+        }
+        Ok(())
+    }
+
+    /// Card text as read by `encoder`: the stored text, or for punch-only
+    /// cards the interpretation of their holes, with blanks where a column has
+    /// no character under `encoder`. Decoding happens on each call; nothing is
+    /// written back to the card. Cards with neither text nor punches read as
+    /// blank; stored punches that are not a valid column dump are an error.
+    pub fn text_view<E: PunchEncoding + ?Sized>(&self, encoder: &E) -> Result<Cow<'_, str>> {
+        Ok(match (&self.text, &self.punches) {
+            (Some(text), _) => Cow::Borrowed(text),
+            (None, Some(_)) => Cow::Owned(self.to_punch_card(encoder)?.text().iter().collect()),
+            (None, None) => Cow::Owned(" ".repeat(MAX_COLS)),
+        })
+    }
+
     /// Materialize a [`PunchCard`](crate::core::punchcards::PunchCard) representation using the supplied encoder.
+    ///
+    /// Cards stored only as `punches` (see [`formats::punches_from_hex`](crate::core::formats::punches_from_hex))
+    /// keep their exact holes. `encoder` punches and interprets Hollerith
+    /// cards; ASCII and EBCDIC cards use the chart
+    /// [`EncoderRegistry::for_encoding`] gives their encoding either way.
+    ///
+    /// [`EncoderRegistry::for_encoding`]: crate::core::encoding::EncoderRegistry::for_encoding
     pub fn to_punch_card<E: PunchEncoding + ?Sized>(
         &self,
         encoder: &E,
     ) -> Result<crate::core::punchcards::PunchCard, EncodeError> {
-        let text = self.text.as_deref().unwrap_or_else(|| "");
-        crate::core::punchcards::PunchCard::from_str(encoder, text)
+        match self.encoding {
+            EncodingKind::Hollerith => self.punch_with(encoder),
+            kind => self.punch_with(crate::core::encoding::EncoderRegistry::for_encoding(kind)),
+        }
+    }
+
+    fn punch_with<E: PunchEncoding + ?Sized>(
+        &self,
+        chart: &E,
+    ) -> Result<crate::core::punchcards::PunchCard, EncodeError> {
+        if let (None, Some(hex)) = (&self.text, &self.punches) {
+            let masks = crate::core::formats::punches_from_hex(hex)
+                .map_err(|err| EncodeError::InvalidPunches(err.to_string()))?;
+            return Ok(crate::core::punchcards::PunchCard::from_columns(
+                &masks,
+                |mask| chart.decode(mask),
+            ));
+        }
+        crate::core::punchcards::PunchCard::from_str(chart, self.text.as_deref().unwrap_or(""))
     }
 }
 
@@ -135,17 +258,49 @@ impl CardRecord {
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct DeckHeader {
     pub version: u8,
-    pub created_at: DateTime<Utc>,
+    /// Canonical decks keep this in the audit sidecar instead of the deck
+    /// file, so a canonical deck copied without its sidecar has none.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created_at: Option<DateTime<Utc>>,
     #[serde(default)]
     pub language: Option<String>,
     #[serde(default)]
     pub template: Option<String>,
+    /// One-line summary of what the deck is.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Free-text deck notes, the deck-level counterpart of [`CardMeta::note`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub notes: Option<String>,
     #[serde(default)]
     pub protected_cols: Vec<ColumnRange>,
     #[serde(default)]
     pub readonly: bool,
+    /// Machine the deck will be fed to, and how it treats invalid characters.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub machine: Option<MachineRules>,
+    /// Keypunch chart the Hollerith cards were punched on, as an
+    /// [`EncoderRegistry`](crate::core::encoding::EncoderRegistry) name
+    /// (IBM029 when unset).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub encoder: Option<String>,
+    /// Lifecycle state; frozen and submitted decks reject card changes.
+    #[serde(default, skip_serializing_if = "DeckState::is_draft")]
+    pub state: DeckState,
+    /// When `state` last changed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub state_since: Option<DateTime<Utc>>,
     #[serde(default)]
     pub history: Vec<AuditEvent>,
+    /// Verification baselines and recent pass results.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub verification: Option<Verification>,
+    /// Ed25519 signatures over the content hash (see [`crate::core::signing`]).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub signatures: Vec<DeckSignature>,
+    /// Save in git-friendly canonical form (see [`Deck::save`]).
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub canonical: bool,
 }
 
 impl DeckHeader {
@@ -157,44 +312,158 @@ impl DeckHeader {
     ) -> Self {
         Self {
             version: DECK_VERSION,
-            created_at: Utc::now(),
+            created_at: Some(Utc::now()),
             language,
             template,
+            description: None,
+            notes: None,
             protected_cols,
             readonly: false,
+            machine: None,
+            encoder: None,
+            state: DeckState::Draft,
+            state_since: None,
             history: Vec::new(),
+            verification: None,
+            signatures: Vec::new(),
+            canonical: false,
         }
     }
 }
 
+/// Production lifecycle of a deck, as card shops controlled release decks.
+///
+/// `draft -> verified -> frozen -> submitted`; a verified deck can be reopened
+/// to draft and a frozen one thawed back to verified. Submitted is final.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum DeckState {
+    #[default]
+    Draft,
+    Verified,
+    Frozen,
+    Submitted,
+}
+
+impl DeckState {
+    pub fn is_draft(&self) -> bool {
+        *self == DeckState::Draft
+    }
+
+    /// Frozen and submitted decks behave as readonly.
+    pub fn is_locked(self) -> bool {
+        matches!(self, DeckState::Frozen | DeckState::Submitted)
+    }
+
+    /// Whether the lifecycle allows moving from `self` to `next`.
+    pub fn can_transition_to(self, next: DeckState) -> bool {
+        use DeckState::*;
+        matches!(
+            (self, next),
+            (Draft, Verified)
+                | (Verified, Draft)
+                | (Verified, Frozen)
+                | (Frozen, Verified)
+                | (Frozen, Submitted)
+        )
+    }
+}
+
+/// A stored column whose holes punch no character of the checked encoding.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InvalidPunch {
+    /// Zero-based card index.
+    pub card: usize,
+    /// One-based column number.
+    pub column: usize,
+    pub mask: CellMask,
+}
+
 /// Describes how the deck has changed over time.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct AuditEvent {
     pub timestamp: DateTime<Utc>,
     pub actor: String,
     pub action: String,
+    /// Column-level text changes recorded alongside the action, if any.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub changes: Vec<ColumnChange>,
 }
 
 impl AuditEvent {
     /// Create an audit entry using the OS user (if available).
     pub fn new<S: Into<String>>(action: S) -> Self {
-        let actor = std::env::var("USER")
-            .or_else(|_| std::env::var("USERNAME"))
-            .unwrap_or_else(|_| "unknown".to_string());
         Self {
             timestamp: Utc::now(),
-            actor,
+            actor: current_actor(),
             action: action.into(),
+            changes: Vec::new(),
         }
     }
 }
 
+/// Name of the OS user running the process, or `unknown`.
+pub(crate) fn current_actor() -> String {
+    std::env::var("USER")
+        .or_else(|_| std::env::var("USERNAME"))
+        .unwrap_or_else(|_| "unknown".to_string())
+}
+
 /// In-memory representation of a deck file.
 #[derive(Debug, Clone)]
 pub struct Deck {
     pub header: DeckHeader,
     pub cards: Vec<CardRecord>,
     pub path: Option<PathBuf>,
+    /// Key the deck is sealed with on save; set when it was loaded encrypted
+    /// (see [`crypt`](crate::core::crypt)).
+    pub encryption: Option<DeckKey>,
+    /// Set by [`override_readonly`](Self::override_readonly): changes pass the
+    /// readonly lock for this session. Never saved.
+    pub readonly_override: bool,
+    /// Cards whose stored sha256 did not match when the deck was read.
+    /// Never saved; saving stores fresh hashes.
+    pub hash_mismatches: Vec<CardHashMismatch>,
+    pub(crate) resolved_encoder: ResolvedEncoder,
+}
+
+/// Chart last resolved from [`DeckHeader::encoder`], with the name it was
+/// resolved from, so reading card text does not look a mapping file up for
+/// every card.
+#[derive(Default)]
+pub(crate) struct ResolvedEncoder(Mutex<Option<(String, &'static dyn PunchEncoding)>>);
+
+impl ResolvedEncoder {
+    fn get(&self, name: &str) -> Option<&'static dyn PunchEncoding> {
+        match &*self.0.lock().unwrap_or_else(PoisonError::into_inner) {
+            Some((resolved, encoder)) if resolved == name => Some(*encoder),
+            _ => None,
+        }
+    }
+
+    fn set(&self, name: &str, encoder: &'static dyn PunchEncoding) {
+        *self.0.lock().unwrap_or_else(PoisonError::into_inner) = Some((name.to_string(), encoder));
+    }
+}
+
+impl Clone for ResolvedEncoder {
+    fn clone(&self) -> Self {
+        Self(Mutex::new(
+            self.0
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .clone(),
+        ))
+    }
+}
+
+impl fmt::Debug for ResolvedEncoder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let resolved = self.0.lock().unwrap_or_else(PoisonError::into_inner);
+        f.debug_tuple("ResolvedEncoder")
+            .field(&resolved.as_ref().map(|(_, encoder)| encoder.name()))
+            .finish()
+    }
 }
 
 impl Deck {
@@ -204,81 +473,293 @@ impl Deck {
             header,
             cards: Vec::new(),
             path: None,
+            encryption: None,
+            readonly_override: false,
+            hash_mismatches: Vec::new(),
+            resolved_encoder: ResolvedEncoder::default(),
+        }
+    }
+
+    /// Build a deck from ready-made records, validating each card as
+    /// [`Deck::append_card`] does.
+    pub fn from_records(
+        header: DeckHeader,
+        records: impl IntoIterator<Item = CardRecord>,
+    ) -> Result<Self> {
+        let mut deck = Self::new(header);
+        for (idx, card) in records.into_iter().enumerate() {
+            deck.append_card(card)
+                .with_context(|| format!("card {} rejected", idx + 1))?;
         }
+        Ok(deck)
+    }
+
+    /// Build a deck with one text card per line; lines wider than 80 columns
+    /// are rejected with their line number.
+    pub fn from_lines<'a>(
+        header: DeckHeader,
+        lines: impl IntoIterator<Item = &'a str>,
+        encoding: EncodingKind,
+        card_type: CardType,
+    ) -> Result<Self> {
+        let records = lines
+            .into_iter()
+            .enumerate()
+            .map(|(idx, line)| {
+                CardRecord::from_text(line, encoding, card_type.clone())
+                    .with_context(|| format!("line {}", idx + 1))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Self::from_records(header, records)
     }
 
     pub fn load(path: &Path) -> Result<Self> {
-        let file = OpenOptions::new()
+        Self::load_with_key(path, None)
+    }
+
+    /// Load a deck, decrypting it with `key` if the file is encrypted. Without
+    /// a key, encrypted decks use the key from the environment
+    /// ([`DeckKey::from_env`]).
+    pub fn load_with_key(path: &Path, key: Option<&DeckKey>) -> Result<Self> {
+        let mut file = OpenOptions::new()
             .read(true)
             .open(path)
             .with_context(|| format!("failed to open deck file {}", path.display()))?;
-        let reader = BufReader::new(file);
-        let mut lines = reader.lines();
-        let header_line = lines
-            .next()
-            .ok_or_else(|| anyhow!("deck file {} is empty", path.display()))??;
-        let deck_line: DeckLine = serde_json::from_str(&header_line)
-            .with_context(|| format!("failed to parse deck header in {}", path.display()))?;
-        let header = match deck_line {
-            DeckLine::Header(header) => header,
-            DeckLine::Card(_) => return Err(anyhow!("expected deck header as first line")),
-        };
-
-        let mut cards = Vec::new();
-        for (idx, raw) in lines.enumerate() {
-            let raw = raw?;
-            if raw.trim().is_empty() {
-                continue;
-            }
-            let line: DeckLine = serde_json::from_str(&raw).with_context(|| {
-                format!(
-                    "failed to parse card record at line {} in {}",
-                    idx + 2,
-                    path.display()
-                )
-            })?;
-            match line {
-                DeckLine::Header(_) => {
-                    return Err(anyhow!(
-                        "multiple deck headers found in {} at line {}",
-                        path.display(),
-                        idx + 2
-                    ));
-                }
-                DeckLine::Card(card) => cards.push(card),
+        let mut raw = Vec::new();
+        file.read_to_end(&mut raw)
+            .with_context(|| format!("failed to read deck file {}", path.display()))?;
+        let mut encryption = None;
+        if crypt::is_encrypted(&raw) {
+            let key = match key {
+                Some(key) => key.clone(),
+                None => DeckKey::from_env()?.ok_or_else(|| {
+                    anyhow!(
+                        "deck is encrypted; set {} or {} to its key",
+                        crypt::KEY_ENV,
+                        crypt::KEYFILE_ENV
+                    )
+                })?,
+            };
+            raw = crypt::decrypt(&raw, &key)?;
+            encryption = Some(key);
+        }
+        let (mut header, cards, hash_mismatches) = parse_jsonl(
+            BufReader::new(raw.as_slice()).lines(),
+            &path.display().to_string(),
+        )?;
+        // An encrypted deck keeps its history inside; a plaintext sidecar
+        // would leak it.
+        if header.canonical && encryption.is_none() {
+            let sidecar = audit_sidecar_path(path);
+            if sidecar.exists() {
+                let raw = std::fs::read_to_string(&sidecar)
+                    .with_context(|| format!("failed to read {}", sidecar.display()))?;
+                let audit: AuditSidecar = serde_json::from_str(&raw)
+                    .with_context(|| format!("failed to parse {}", sidecar.display()))?;
+                header.created_at = audit.created_at;
+                header.history = audit.history;
             }
         }
 
-        Ok(Self {
+        let deck = Self {
             header,
             cards,
             path: Some(path.to_path_buf()),
+            encryption,
+            readonly_override: false,
+            hash_mismatches,
+            resolved_encoder: ResolvedEncoder::default(),
+        };
+        deck.encoder()?;
+        Ok(deck)
+    }
+
+    /// Parse a deck from JSONL text already in memory, such as a file pulled
+    /// in with [`embed!`](crate::embed). Canonical decks keep their header
+    /// history empty since there is no audit sidecar to read.
+    pub fn from_jsonl(source: &str) -> Result<Self> {
+        let lines = source.lines().map(|line| Ok(line.to_string()));
+        let (header, cards, hash_mismatches) = parse_jsonl(lines, "embedded deck")?;
+        Ok(Self {
+            header,
+            cards,
+            path: None,
+            encryption: None,
+            readonly_override: false,
+            hash_mismatches,
+            resolved_encoder: ResolvedEncoder::default(),
         })
     }
 
+    /// Write the deck as JSONL.
+    ///
+    /// When the header is marked canonical, volatile fields (`created_at` and the
+    /// audit history) move to a sidecar next to the deck (see [`audit_sidecar_path`])
+    /// and every line is written with sorted keys, so unchanged cards produce
+    /// byte-identical lines and version-control diffs stay minimal.
+    ///
+    /// Decks with an [`encryption`](Self::encryption) key are written sealed,
+    /// history included, and never get a sidecar.
+    ///
+    /// Nothing is journaled; [`save_journaled`](crate::core::journal::save_journaled)
+    /// saves with undo.
     pub fn save(&mut self, path: &Path) -> Result<()> {
+        self.write_deck(path, true)
+    }
+
+    /// Write the deck to a file another tool owns, such as the temporary
+    /// files git hands a merge driver: no audit sidecar, so a canonical
+    /// deck's history is not written at all.
+    pub fn save_detached(&mut self, path: &Path) -> Result<()> {
+        self.write_deck(path, false)
+    }
+
+    fn write_deck(&mut self, path: &Path, sidecar: bool) -> Result<()> {
+        self.header.version = DECK_VERSION;
         let file = OpenOptions::new()
             .write(true)
             .create(true)
             .truncate(true)
             .open(path)
             .with_context(|| format!("failed to write deck file {}", path.display()))?;
+        if let Some(key) = &self.encryption {
+            let mut plain = Vec::new();
+            self.write_plain(&mut plain)?;
+            let mut writer = BufWriter::new(file);
+            writer.write_all(&crypt::encrypt(&plain, key)?)?;
+            writer.flush()?;
+            self.path = Some(path.to_path_buf());
+            return Ok(());
+        }
         let mut writer = BufWriter::new(file);
-        serde_json::to_writer(&mut writer, &DeckLine::Header(self.header.clone()))
+        if self.header.canonical {
+            self.write_canonical(&mut writer)?;
+        } else {
+            self.write_plain(&mut writer)?;
+        }
+        writer.flush()?;
+        if self.header.canonical && sidecar {
+            let sidecar = audit_sidecar_path(path);
+            let audit = AuditSidecarRef {
+                created_at: self.header.created_at.as_ref(),
+                history: &self.header.history,
+            };
+            let body =
+                serde_json::to_string_pretty(&audit).context("failed to serialize audit log")?;
+            std::fs::write(&sidecar, body + "\n")
+                .with_context(|| format!("failed to write {}", sidecar.display()))?;
+        }
+        self.path = Some(path.to_path_buf());
+        Ok(())
+    }
+
+    /// The deck as plain JSONL, history included, whatever its canonical flag.
+    pub(crate) fn to_jsonl(&self) -> Result<String> {
+        let mut plain = Vec::new();
+        self.write_plain(&mut plain)?;
+        String::from_utf8(plain).context("deck JSONL is not UTF-8")
+    }
+
+    fn write_plain<W: Write>(&self, writer: &mut W) -> Result<()> {
+        serde_json::to_writer(&mut *writer, &DeckLineRef::Header(&self.header))
+            .context("failed to serialize deck header")?;
+        writer.write_all(b"\n")?;
+        let mut line = Vec::new();
+        for card in &self.cards {
+            line.clear();
+            serde_json::to_writer(&mut line, &DeckLineRef::Card(card))
+                .context("failed to serialize deck card")?;
+            // The stored hash covers the line as it is before the hash is added.
+            let digest = Sha256::digest(&line);
+            line.pop();
+            writer.write_all(&line)?;
+            writeln!(writer, ",\"sha256\":\"{:02x}\"}}", digest)?;
+        }
+        Ok(())
+    }
+
+    fn write_canonical<W: Write>(&self, writer: &mut W) -> Result<()> {
+        // Round-tripping through `Value` sorts object keys (serde_json maps are ordered).
+        let mut header = serde_json::to_value(DeckLineRef::Header(&self.header))
             .context("failed to serialize deck header")?;
+        if let Some(map) = header.as_object_mut() {
+            map.remove("created_at");
+            map.remove("history");
+        }
+        serde_json::to_writer(&mut *writer, &header).context("failed to serialize deck header")?;
         writer.write_all(b"\n")?;
         for card in &self.cards {
-            serde_json::to_writer(&mut writer, &DeckLine::Card(card.clone()))
+            let mut value = serde_json::to_value(DeckLineRef::Card(card))
                 .context("failed to serialize deck card")?;
+            if let Some(map) = value.as_object_mut() {
+                let digest = merkle::card_digest(card)?;
+                map.insert("sha256".into(), format!("{:02x}", digest).into());
+            }
+            serde_json::to_writer(&mut *writer, &value).context("failed to serialize deck card")?;
             writer.write_all(b"\n")?;
         }
-        writer.flush()?;
-        self.path = Some(path.to_path_buf());
+        Ok(())
+    }
+
+    /// Fail unless the deck's cards may be changed (not readonly, frozen or submitted).
+    pub fn ensure_mutable(&self) -> Result<()> {
+        self.ensure_unlocked()?;
+        if self.header.state.is_locked() {
+            return Err(anyhow!(
+                "deck is {}; move it back with `punch deck state --to verified` first",
+                self.header.state
+            ));
+        }
+        Ok(())
+    }
+
+    /// Fail if the deck is locked readonly, unless the lock was overridden.
+    pub fn ensure_unlocked(&self) -> Result<()> {
+        if self.header.readonly && !self.readonly_override {
+            return Err(anyhow!(
+                "deck is readonly; run `punch deck unlock` or pass --force"
+            ));
+        }
+        Ok(())
+    }
+
+    /// Let changes through the readonly lock for this session, recording the
+    /// override for `command` in the history. Does nothing on unlocked decks.
+    pub fn override_readonly(&mut self, command: &str) {
+        if self.header.readonly && !self.readonly_override {
+            self.readonly_override = true;
+            self.log_action(format!("readonly override: {}", command));
+        }
+    }
+
+    /// Lock or unlock the deck, logging the change. Returns `false` when it
+    /// already was in that state.
+    pub fn set_readonly(&mut self, readonly: bool) -> bool {
+        if self.header.readonly == readonly {
+            return false;
+        }
+        self.header.readonly = readonly;
+        self.readonly_override = false;
+        self.log_action(if readonly { "deck lock" } else { "deck unlock" });
+        true
+    }
+
+    /// Move the deck to lifecycle state `next`, stamping the time and logging
+    /// the transition.
+    pub fn transition(&mut self, next: DeckState) -> Result<()> {
+        let current = self.header.state;
+        if !current.can_transition_to(next) {
+            return Err(anyhow!("cannot move a {} deck to {}", current, next));
+        }
+        self.header.state = next;
+        self.header.state_since = Some(Utc::now());
+        self.log_action(format!("deck state {} -> {}", current, next));
         Ok(())
     }
 
     /// Append a card to the deck, enforcing protected-column constraints.
     pub fn append_card(&mut self, card: CardRecord) -> Result<()> {
+        self.ensure_mutable()?;
         self.enforce_protection(None, &card)?;
         self.cards.push(card);
         Ok(())
@@ -292,6 +773,7 @@ impl Deck {
                 self.cards.len()
             ));
         }
+        self.ensure_mutable()?;
         self.enforce_protection(None, &card)?;
         self.cards.insert(index, card);
         Ok(())
@@ -306,12 +788,46 @@ impl Deck {
                 self.cards.len().saturating_sub(1)
             ));
         }
+        self.ensure_mutable()?;
         let original = &self.cards[index];
         self.enforce_protection(Some(original), &card)?;
         self.cards[index] = card;
         Ok(())
     }
 
+    /// Remove and return the card at the specified zero-based index.
+    pub fn remove_card(&mut self, index: usize) -> Result<CardRecord> {
+        if index >= self.cards.len() {
+            return Err(anyhow!(
+                "card index {} out of range 0..{}",
+                index,
+                self.cards.len().saturating_sub(1)
+            ));
+        }
+        self.ensure_mutable()?;
+        self.enforce_removal(&self.cards[index])
+            .with_context(|| format!("card {} cannot be removed", index + 1))?;
+        Ok(self.cards.remove(index))
+    }
+
+    /// Move the card at zero-based `from` so it ends up at zero-based `to`.
+    pub fn move_card(&mut self, from: usize, to: usize) -> Result<()> {
+        let last = self.cards.len().saturating_sub(1);
+        if from >= self.cards.len() || to >= self.cards.len() {
+            return Err(anyhow!(
+                "card index {} out of range 0..{}",
+                from.max(to),
+                last
+            ));
+        }
+        self.ensure_mutable()?;
+        self.enforce_removal(&self.cards[from])
+            .with_context(|| format!("card {} cannot be moved", from + 1))?;
+        let card = self.cards.remove(from);
+        self.cards.insert(to, card);
+        Ok(())
+    }
+
     /// Create a new deck from a contiguous range of cards.
     pub fn slice(&self, range: std::ops::Range<usize>) -> Result<Self> {
         if range.end > self.cards.len() {
@@ -327,27 +843,64 @@ impl Deck {
     }
 
     /// Populate sequence numbers and update the 73–80 columns accordingly.
-    pub fn number_sequence(&mut self, start: usize, step: usize) {
-        let mut value = start;
-        for card in &mut self.cards {
-            card.seq = Some(value);
-            if let Some(text) = card.text.as_mut() {
-                let mut chars: Vec<char> = text.chars().collect();
-                while chars.len() < MAX_COLS {
-                    chars.push(' ');
-                }
-                let seq_str = format!("{:>8}", value);
-                let start_idx = MAX_COLS.saturating_sub(seq_str.len());
-                for (offset, ch) in seq_str.chars().enumerate() {
-                    let idx = start_idx + offset;
-                    if idx < chars.len() {
-                        chars[idx] = ch;
-                    }
-                }
-                *text = chars.into_iter().collect();
+    /// Nothing is numbered if the last number would not fit in the field.
+    pub fn number_sequence(&mut self, start: usize, step: usize) -> Result<()> {
+        let values: Vec<usize> = std::iter::successors(Some(start), |v| v.checked_add(step))
+            .take(self.cards.len())
+            .collect();
+        if values.len() < self.cards.len() {
+            return Err(EncodeError::SequenceOverflow(usize::MAX))
+                .with_context(|| format!("card {}", values.len() + 1));
+        }
+        if let Some(&last) = values.last() {
+            sequence_field(last).with_context(|| format!("card {}", values.len()))?;
+        }
+        for (card, value) in self.cards.iter_mut().zip(values) {
+            card.set_sequence(value)?;
+        }
+        Ok(())
+    }
+
+    /// Propose `count` unused sequence numbers strictly between the existing
+    /// numbers `after` and `before`, spread evenly across the free gap.
+    ///
+    /// Errors name both cards when they are missing and, when the gap is too
+    /// small, recommend a `seq number` step that would leave enough room.
+    pub fn suggest_sequence(
+        &self,
+        after: usize,
+        before: usize,
+        count: usize,
+    ) -> Result<Vec<usize>> {
+        if count == 0 {
+            return Err(anyhow!("--count must be at least 1"));
+        }
+        if after >= before {
+            return Err(anyhow!("sequence {} must be lower than {}", after, before));
+        }
+        let used: Vec<usize> = self.cards.iter().filter_map(|c| c.seq).collect();
+        for bound in [after, before] {
+            if !used.contains(&bound) {
+                return Err(anyhow!("no card is numbered {}", bound));
             }
-            value += step;
         }
+        let free: Vec<usize> = (after + 1..before).filter(|n| !used.contains(n)).collect();
+        if free.len() < count {
+            let step = (count + 1).div_ceil(10) * 10;
+            let start = used.iter().min().copied().unwrap_or(step);
+            return Err(anyhow!(
+                "only {} free sequence number(s) between {} and {}, need {}; renumber first, e.g. `punch seq number <deck> --start {} --step {}`",
+                free.len(),
+                after,
+                before,
+                count,
+                start,
+                step
+            ));
+        }
+        Ok((1..=count)
+            .map(|i| free[i * (free.len() + 1) / (count + 1) - 1])
+            .collect())
     }
 
     pub fn sort_by_sequence(&mut self) {
@@ -359,19 +912,88 @@ impl Deck {
         });
     }
 
-    /// Compute a SHA-256 hash representing deck contents.
+    /// Columns of punch-only cards (binary imports, scans) whose hole pattern
+    /// is not a legal character of `encoder`. Text cards are punched by the
+    /// encoder itself, so they are always legal and are not inspected.
+    pub fn invalid_punches(&self, encoder: &dyn PunchEncoding) -> Result<Vec<InvalidPunch>> {
+        let mut invalid = Vec::new();
+        for (card, record) in self.cards.iter().enumerate() {
+            let (None, Some(hex)) = (&record.text, &record.punches) else {
+                continue;
+            };
+            let masks = crate::core::formats::punches_from_hex(hex)
+                .with_context(|| format!("card {} has unreadable punches", card + 1))?;
+            for (idx, mask) in masks.into_iter().enumerate() {
+                if !encoder.is_legal(mask) {
+                    invalid.push(InvalidPunch {
+                        card,
+                        column: idx + 1,
+                        mask,
+                    });
+                }
+            }
+        }
+        Ok(invalid)
+    }
+
+    /// Occurrences of every character on the deck's text cards, trailing
+    /// padding included. Punch-only cards carry no characters and are skipped.
+    pub fn char_usage(&self) -> std::collections::BTreeMap<char, usize> {
+        let mut usage = std::collections::BTreeMap::new();
+        for text in self.cards.iter().filter_map(|card| card.text.as_deref()) {
+            for ch in text.chars() {
+                *usage.entry(ch).or_insert(0) += 1;
+            }
+        }
+        usage
+    }
+
+    /// Text cards containing characters `encoder` cannot punch, as
+    /// `(card index, distinct unsupported characters)`.
+    pub fn unsupported_chars(&self, encoder: &dyn PunchEncoding) -> Vec<(usize, Vec<char>)> {
+        let mut failing = Vec::new();
+        for (idx, card) in self.cards.iter().enumerate() {
+            let Some(text) = card.text.as_deref() else {
+                continue;
+            };
+            let mut missing: Vec<char> = text
+                .chars()
+                .filter(|ch| !encoder.is_supported(*ch))
+                .collect();
+            missing.sort_unstable();
+            missing.dedup();
+            if !missing.is_empty() {
+                failing.push((idx, missing));
+            }
+        }
+        failing
+    }
+
+    /// Compute a SHA-256 hash representing deck contents: the header less
+    /// its [`UNHASHED_HEADER_FIELDS`], followed by the
+    /// [`merkle_root`](Self::merkle_root) of the cards.
     pub fn hash(&self) -> Result<String> {
+        self.hash_without(UNHASHED_HEADER_FIELDS)
+    }
+
+    /// The hash signatures cover: like [`hash`](Self::hash), but also
+    /// without the [`UNSIGNED_HEADER_FIELDS`], so locking, verifying or
+    /// moving a deck through its states leaves its signatures valid.
+    pub fn signing_hash(&self) -> Result<String> {
+        self.hash_without(&[UNHASHED_HEADER_FIELDS, UNSIGNED_HEADER_FIELDS].concat())
+    }
+
+    fn hash_without(&self, fields: &[&str]) -> Result<String> {
         let mut hasher = Sha256::new();
-        let mut buffer = Vec::new();
-        serde_json::to_writer(&mut buffer, &DeckLine::Header(self.header.clone()))
+        let mut header = serde_json::to_value(DeckLineRef::Header(&self.header))
             .context("failed to hash deck header")?;
-        hasher.update(&buffer);
-        buffer.clear();
-        for card in &self.cards {
-            serde_json::to_writer(&mut buffer, &DeckLine::Card(card.clone()))?;
-            hasher.update(&buffer);
-            buffer.clear();
+        if let Some(map) = header.as_object_mut() {
+            for field in fields {
+                map.remove(*field);
+            }
         }
+        hasher.update(header.to_string().as_bytes());
+        hasher.update(self.merkle_root()?.as_bytes());
         let digest = hasher.finalize();
         Ok(format!("{digest:02x}"))
     }
@@ -381,14 +1003,41 @@ impl Deck {
         self.header.history.push(AuditEvent::new(action));
     }
 
+    /// Append an audit entry that carries the column-level changes it made.
+    pub fn log_change<S: Into<String>>(&mut self, action: S, changes: Vec<ColumnChange>) {
+        let mut event = AuditEvent::new(action);
+        event.changes = changes;
+        self.header.history.push(event);
+    }
+
     /// Render cards as 80-column strings, padding blanks for empty cards.
-    pub fn as_text(&self) -> Vec<String> {
-        self.cards
-            .iter()
-            .map(|card| card.text.clone().unwrap_or_else(|| " ".repeat(MAX_COLS)))
+    ///
+    /// Column-binary cards contribute their interpretation under the deck's
+    /// encoder, with blanks where a hole pattern has no printable character;
+    /// a card whose stored punches are corrupt fails the whole call.
+    pub fn as_text(&self) -> Result<Vec<String>> {
+        (0..self.cards.len())
+            .map(|idx| self.card_text(idx).map(Cow::into_owned))
             .collect()
     }
 
+    /// Text of the zero-based card `idx` (see [`CardRecord::text_view`]),
+    /// reading punch-only cards with the deck's encoder.
+    pub fn card_text(&self, idx: usize) -> Result<Cow<'_, str>> {
+        self.cards[idx]
+            .text_view(self.text_encoder())
+            .with_context(|| format!("card {}", idx + 1))
+    }
+
+    /// Chart punch-only cards are read with: the deck's [`encoder`](Self::encoder).
+    ///
+    /// Loading a deck already fails when its chart cannot be loaded; a header
+    /// changed in memory to name a missing mapping file reads as IBM 029 here,
+    /// and [`encoder`](Self::encoder) reports the error.
+    pub fn text_encoder(&self) -> &'static dyn PunchEncoding {
+        self.encoder().unwrap_or(&crate::core::encoding::IBM029)
+    }
+
     pub fn to_punch_deck(
         &self,
         encoder: &dyn PunchEncoding,
@@ -401,6 +1050,62 @@ impl Deck {
         Ok(crate::core::punchcards::CardDeck { cards })
     }
 
+    /// The chart named by the header, or IBM029 when the deck names none.
+    ///
+    /// A relative mapping file is found next to the deck file. The chart is
+    /// resolved once and kept until the header names another.
+    pub fn encoder(&self) -> Result<&'static dyn PunchEncoding> {
+        let Some(name) = &self.header.encoder else {
+            return Ok(&crate::core::encoding::IBM029);
+        };
+        if let Some(encoder) = self.resolved_encoder.get(name) {
+            return Ok(encoder);
+        }
+        let file = Path::new(name);
+        let encoder = match self.path.as_deref().and_then(Path::parent) {
+            Some(dir)
+                if file.is_relative()
+                    && crate::core::encoding::EncoderRegistry::is_mapping_file(name) =>
+            {
+                crate::core::encoding::EncoderRegistry::get(&dir.join(file).to_string_lossy())
+            }
+            _ => crate::core::encoding::EncoderRegistry::get(name),
+        }
+        .with_context(|| format!("deck encoder '{}'", name))?;
+        self.resolved_encoder.set(name, encoder);
+        Ok(encoder)
+    }
+
+    /// Store the given zero-based cards under `kind`, so they are punched with
+    /// its chart from now on. Every text card must punch under the new chart;
+    /// punches-only cards keep their holes and are left untouched. Returns the
+    /// number of cards whose encoding changed.
+    pub fn set_encoding(&mut self, indices: &[usize], kind: EncodingKind) -> Result<usize> {
+        self.ensure_mutable()?;
+        let encoder = self.encoder()?;
+        let mut changed = Vec::new();
+        for &idx in indices {
+            let card = self
+                .cards
+                .get(idx)
+                .ok_or_else(|| anyhow!("card index {} out of range", idx + 1))?;
+            if card.text.is_none() || card.encoding == kind {
+                continue;
+            }
+            let mut updated = card.clone();
+            updated.encoding = kind;
+            updated
+                .to_punch_card(encoder)
+                .with_context(|| format!("card {} cannot be punched as {:?}", idx + 1, kind))?;
+            changed.push((idx, updated));
+        }
+        let count = changed.len();
+        for (idx, card) in changed {
+            self.replace_card(idx, card)?;
+        }
+        Ok(count)
+    }
+
     /// Merge cards and history from another deck after validating compatibility.
     pub fn merge_from(&mut self, other: &Deck) -> Result<()> {
         if self.header.protected_cols != other.header.protected_cols {
@@ -416,6 +1121,9 @@ impl Deck {
         if self.header.language != other.header.language {
             return Err(anyhow!("languages differ between decks"));
         }
+        if self.header.encoder != other.header.encoder {
+            return Err(anyhow!("encoders differ between decks"));
+        }
         self.cards.extend(other.cards.clone());
         self.header.history.extend_from_slice(&other.header.history);
         Ok(())
@@ -437,8 +1145,32 @@ impl Deck {
         Ok(new)
     }
 
+    /// Pick `count` card indices uniformly at random, in deck order.
+    ///
+    /// The same `seed` always selects the same cards, so previews can be
+    /// reproduced. Asking for more cards than the deck holds returns all of them.
+    pub fn sample_indices(&self, count: usize, seed: u64) -> Vec<usize> {
+        // Selection sampling (Knuth's Algorithm S) driven by SplitMix64.
+        let mut rng = SplitMix64::new(seed);
+        let total = self.cards.len();
+        let mut picked = Vec::with_capacity(count.min(total));
+        for idx in 0..total {
+            let needed = count.saturating_sub(picked.len()) as u64;
+            let remaining = (total - idx) as u64;
+            if rng.below(remaining) < needed {
+                picked.push(idx);
+            }
+        }
+        picked
+    }
+
     /// Guard protected columns from modification to preserve sequence numbers or constants.
-    fn enforce_protection(
+    ///
+    /// `original` is the card being replaced, or `None` when `updated` is a new card.
+    /// Punch-only cards are checked through their [`CardRecord::text_view`]
+    /// and, when both cards can be punched, by comparing the holes themselves,
+    /// so a column with no printable character is guarded too.
+    pub fn enforce_protection(
         &self,
         original: Option<&CardRecord>,
         updated: &CardRecord,
@@ -446,24 +1178,27 @@ impl Deck {
         if self.header.protected_cols.is_empty() {
             return Ok(());
         }
-        let new_text = updated
-            .text
-            .as_deref()
-            .ok_or_else(|| anyhow!("card without text cannot be checked for protection"))?;
-        let old_text = original.and_then(|c| c.text.as_deref());
+        let encoder = self.text_encoder();
+        let new_text: Vec<char> = updated.text_view(encoder)?.chars().collect();
+        let old_text: Option<Vec<char>> = original
+            .map(|c| c.text_view(encoder).map(|text| text.chars().collect()))
+            .transpose()?;
+        let holes = |card: &CardRecord| card.to_punch_card(encoder).ok();
+        let (new_holes, old_holes) = (holes(updated), original.and_then(holes));
         for range in &self.header.protected_cols {
             for col in range.start..=range.end {
                 let idx = col - 1;
-                let new_char = new_text
-                    .chars()
-                    .nth(idx)
+                let new_char = *new_text
+                    .get(idx)
                     .ok_or_else(|| anyhow!("card text shorter than {} columns", col))?;
-                if let Some(old) = old_text {
-                    let old_char = old
-                        .chars()
-                        .nth(idx)
+                let new_mask = new_holes.as_ref().map(|punch| punch.columns()[idx]);
+                if let Some(old) = &old_text {
+                    let old_char = *old
+                        .get(idx)
                         .ok_or_else(|| anyhow!("card text shorter than {} columns", col))?;
-                    if new_char != old_char {
+                    let old_mask = old_holes.as_ref().map(|punch| punch.columns()[idx]);
+                    let moved = matches!((old_mask, new_mask), (Some(a), Some(b)) if a != b);
+                    if new_char != old_char || moved {
                         return Err(anyhow!(
                             "column {} is protected; attempted to change '{}' -> '{}'",
                             col,
@@ -471,7 +1206,7 @@ impl Deck {
                             new_char
                         ));
                     }
-                } else if new_char != ' ' {
+                } else if new_char != ' ' || new_mask.is_some_and(|mask| mask != CellMask(0)) {
                     return Err(anyhow!(
                         "column {} is protected; new cards must leave it blank",
                         col
@@ -481,28 +1216,164 @@ impl Deck {
         }
         Ok(())
     }
+
+    /// Fail if `card` has something punched in a protected column, so
+    /// removing or moving it would disturb that column of the deck.
+    pub fn enforce_removal(&self, card: &CardRecord) -> Result<()> {
+        if self.header.protected_cols.is_empty() {
+            return Ok(());
+        }
+        let encoder = self.text_encoder();
+        let text: Vec<char> = card.text_view(encoder)?.chars().collect();
+        let holes = card.to_punch_card(encoder).ok();
+        for range in &self.header.protected_cols {
+            for col in range.start..=range.end {
+                let punched = text.get(col - 1).is_some_and(|ch| *ch != ' ')
+                    || holes
+                        .as_ref()
+                        .is_some_and(|punch| punch.columns()[col - 1] != CellMask(0));
+                if punched {
+                    return Err(anyhow!(
+                        "column {} is protected and punched on this card",
+                        col
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Location of the audit sidecar used by canonical decks (`prog.deck` -> `prog.audit.json`).
+pub fn audit_sidecar_path(deck: &Path) -> PathBuf {
+    let mut path = deck.to_path_buf();
+    path.set_extension("audit.json");
+    path
+}
+
+/// Volatile header fields stored beside canonical decks.
+#[derive(Debug, Deserialize)]
+struct AuditSidecar {
+    #[serde(default)]
+    created_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    history: Vec<AuditEvent>,
+}
+
+#[derive(Debug, Serialize)]
+struct AuditSidecarRef<'a> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    created_at: Option<&'a DateTime<Utc>>,
+    history: &'a [AuditEvent],
+}
+
+/// Owned form of a deck file line, used when parsing.
+#[derive(Debug, Clone, Deserialize)]
 #[serde(tag = "kind", rename_all = "snake_case")]
-enum DeckLine {
+pub(crate) enum DeckLine {
     Header(DeckHeader),
-    Card(CardRecord),
+    Card(CardLine),
+}
+
+/// A card line as read; version 2 decks carry the card's hash.
+#[derive(Debug, Clone, Deserialize)]
+pub(crate) struct CardLine {
+    #[serde(flatten)]
+    pub card: CardRecord,
+    #[serde(default)]
+    pub sha256: Option<String>,
+}
+
+impl CardLine {
+    /// How the card, read from `line` as card `card`, disagrees with its
+    /// stored hash; `None` when it matches or has no stored hash.
+    pub(crate) fn hash_mismatch(
+        &self,
+        card: usize,
+        line: usize,
+    ) -> serde_json::Result<Option<CardHashMismatch>> {
+        let Some(stored) = &self.sha256 else {
+            return Ok(None);
+        };
+        let computed = format!("{:02x}", merkle::card_digest(&self.card)?);
+        if stored.eq_ignore_ascii_case(&computed) {
+            return Ok(None);
+        }
+        Ok(Some(CardHashMismatch {
+            card,
+            line,
+            stored: stored.clone(),
+            computed,
+        }))
+    }
+}
+
+/// Read a header line followed by card lines; `origin` names the source in
+/// errors. Cards that no longer match their stored hash are kept and listed.
+fn parse_jsonl(
+    mut lines: impl Iterator<Item = std::io::Result<String>>,
+    origin: &str,
+) -> Result<(DeckHeader, Vec<CardRecord>, Vec<CardHashMismatch>)> {
+    let header_line = lines
+        .next()
+        .ok_or_else(|| anyhow!("deck file {} is empty", origin))??;
+    let deck_line: DeckLine = serde_json::from_str(&header_line)
+        .with_context(|| format!("failed to parse deck header in {}", origin))?;
+    let header = match deck_line {
+        DeckLine::Header(header) => header,
+        DeckLine::Card(_) => return Err(anyhow!("expected deck header as first line")),
+    };
+
+    let mut cards = Vec::new();
+    let mut mismatches = Vec::new();
+    for (idx, raw) in lines.enumerate() {
+        let raw = raw?;
+        if raw.trim().is_empty() {
+            continue;
+        }
+        let line: DeckLine = serde_json::from_str(&raw).with_context(|| {
+            format!(
+                "failed to parse card record at line {} in {}",
+                idx + 2,
+                origin
+            )
+        })?;
+        match line {
+            DeckLine::Header(_) => {
+                return Err(anyhow!(
+                    "multiple deck headers found in {} at line {}",
+                    origin,
+                    idx + 2
+                ));
+            }
+            DeckLine::Card(line) => {
+                mismatches.extend(line.hash_mismatch(cards.len(), idx + 2)?);
+                cards.push(line.card);
+            }
+        }
+    }
+    Ok((header, cards, mismatches))
+}
+
+/// Borrowed mirror of [`DeckLine`] so saving and hashing never clone
+/// records. Card lines serialize without their stored hash.
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub(crate) enum DeckLineRef<'a> {
+    Header(&'a DeckHeader),
+    Card(&'a CardRecord),
 }
 
 fn normalize_card_text(text: &str) -> Result<String> {
-    let mut buffer: VecDeque<char> = text.chars().collect();
-    if buffer.len() > MAX_COLS {
+    let width = text.chars().count();
+    if width > MAX_COLS {
         return Err(anyhow!(
             "card text must not exceed {} columns (got {})",
             MAX_COLS,
-            buffer.len()
+            width
         ));
     }
-    while buffer.len() < MAX_COLS {
-        buffer.push_back(' ');
-    }
-    Ok(buffer.into_iter().collect())
+    Ok(crate::core::text::pad_80(text))
 }
 
 impl fmt::Display for EncodingKind {
@@ -515,6 +1386,50 @@ impl fmt::Display for EncodingKind {
     }
 }
 
+impl fmt::Display for DeckState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(match self {
+            DeckState::Draft => "draft",
+            DeckState::Verified => "verified",
+            DeckState::Frozen => "frozen",
+            DeckState::Submitted => "submitted",
+        })
+    }
+}
+
+impl fmt::Display for CardType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // `pad` so width/alignment flags work in columnar listings.
+        f.pad(match self {
+            CardType::Code => "code",
+            CardType::Data => "data",
+            CardType::Jcl => "jcl",
+            CardType::Comment => "comment",
+            CardType::Separator => "separator",
+            CardType::Patch => "patch",
+        })
+    }
+}
+
+impl FromStr for CardType {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "code" => Ok(CardType::Code),
+            "data" => Ok(CardType::Data),
+            "jcl" => Ok(CardType::Jcl),
+            "comment" => Ok(CardType::Comment),
+            "separator" => Ok(CardType::Separator),
+            "patch" => Ok(CardType::Patch),
+            other => Err(anyhow!(
+                "unknown card type '{}' (expected code, data, jcl, comment, separator or patch)",
+                other
+            )),
+        }
+    }
+}
+
 fn format_ranges(ranges: &[ColumnRange]) -> String {
     if ranges.is_empty() {
         return "-".to_string();
@@ -525,3 +1440,361 @@ fn format_ranges(ranges: &[ColumnRange]) -> String {
         .collect::<Vec<_>>()
         .join(", ")
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cards_can_be_removed_and_moved() {
+        let mut deck = Deck::from_lines(
+            DeckHeader::new(None, None, Vec::new()),
+            ["A", "B", "C", "D"],
+            EncodingKind::Hollerith,
+            CardType::Code,
+        )
+        .unwrap();
+        let first = |deck: &Deck| {
+            deck.as_text()
+                .unwrap()
+                .iter()
+                .map(|t| t.chars().next().unwrap())
+                .collect::<String>()
+        };
+        assert_eq!(deck.remove_card(1).unwrap().text.unwrap().trim_end(), "B");
+        assert_eq!(first(&deck), "ACD");
+        deck.move_card(0, 2).unwrap();
+        assert_eq!(first(&deck), "CDA");
+        deck.move_card(2, 0).unwrap();
+        assert_eq!(first(&deck), "ACD");
+        assert!(deck.remove_card(3).is_err());
+        assert!(deck.move_card(0, 3).is_err());
+        assert!(deck.set_readonly(true));
+        assert!(!deck.set_readonly(true));
+        assert!(deck.remove_card(0).is_err());
+        deck.override_readonly("card remove");
+        deck.override_readonly("card remove");
+        assert!(deck.remove_card(0).is_ok());
+        let actions: Vec<&str> = deck
+            .header
+            .history
+            .iter()
+            .map(|event| event.action.as_str())
+            .collect();
+        assert_eq!(actions, ["deck lock", "readonly override: card remove"]);
+        assert!(deck.set_readonly(false));
+        assert!(!deck.readonly_override);
+    }
+
+    #[test]
+    fn cards_punched_in_protected_columns_stay_put() {
+        let numbered = format!("{:72}00000010", "A");
+        let mut deck = Deck::from_lines(
+            DeckHeader::new(None, None, Vec::new()),
+            [numbered.as_str(), "B", "C"],
+            EncodingKind::Hollerith,
+            CardType::Code,
+        )
+        .unwrap();
+        deck.header.protected_cols = vec![ColumnRange::new(73, 80).unwrap()];
+        let err = deck.remove_card(0).unwrap_err();
+        assert_eq!(
+            format!("{:#}", err),
+            "card 1 cannot be removed: column 73 is protected and punched on this card"
+        );
+        assert!(deck.move_card(0, 2).is_err());
+        assert_eq!(deck.cards.len(), 3);
+        deck.move_card(2, 1).unwrap();
+        assert_eq!(deck.remove_card(2).unwrap().text.unwrap().trim_end(), "B");
+    }
+
+    #[test]
+    fn set_encoding_changes_text_cards_only() {
+        let mut deck = Deck::from_lines(
+            DeckHeader::new(None, None, Vec::new()),
+            ["PROGRAM", "data 1", "data 2"],
+            EncodingKind::Hollerith,
+            CardType::Code,
+        )
+        .unwrap();
+        deck.append_card(CardRecord::from_punches(&[CellMask(1)], CardType::Data))
+            .unwrap();
+        let before = deck.to_punch_deck(&crate::core::encoding::IBM029).unwrap();
+        assert_eq!(
+            deck.set_encoding(&[1, 2, 3], EncodingKind::Ebcdic).unwrap(),
+            2
+        );
+        assert_eq!(deck.cards[1].encoding, EncodingKind::Ebcdic);
+        assert_eq!(deck.cards[3].encoding, EncodingKind::Hollerith);
+        let after = deck.to_punch_deck(&crate::core::encoding::IBM029).unwrap();
+        assert_eq!(before.cards[0].columns(), after.cards[0].columns());
+        assert_ne!(before.cards[1].columns(), after.cards[1].columns());
+        assert_eq!(deck.set_encoding(&[1], EncodingKind::Ebcdic).unwrap(), 0);
+        assert!(deck.set_encoding(&[9], EncodingKind::Ascii).is_err());
+    }
+
+    #[test]
+    fn suggest_sequence_spreads_numbers_across_the_gap() {
+        let header = DeckHeader::new(None, None, Vec::new());
+        let mut deck = Deck::from_lines(
+            header,
+            ["A", "B", "C"],
+            EncodingKind::Hollerith,
+            CardType::Code,
+        )
+        .unwrap();
+        deck.number_sequence(120, 10).unwrap();
+        assert_eq!(
+            deck.suggest_sequence(120, 130, 4).unwrap(),
+            vec![122, 124, 126, 128]
+        );
+        assert!(deck.suggest_sequence(120, 130, 10).is_err());
+        assert!(deck.suggest_sequence(120, 125, 1).is_err());
+    }
+
+    #[test]
+    fn from_lines_and_records_validate_cards() {
+        let header = DeckHeader::new(None, None, vec![ColumnRange::new(73, 80).unwrap()]);
+        let long = "X".repeat(81);
+        let err = Deck::from_lines(
+            header.clone(),
+            ["OK", long.as_str()],
+            EncodingKind::Hollerith,
+            CardType::Code,
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("line 2"));
+
+        let seq = CardRecord::from_text(
+            format!("{:72}00000010", ""),
+            EncodingKind::Hollerith,
+            CardType::Code,
+        )
+        .unwrap();
+        assert!(Deck::from_records(header.clone(), [seq]).is_err());
+        let deck =
+            Deck::from_lines(header, ["A", "B"], EncodingKind::Hollerith, CardType::Data).unwrap();
+        assert_eq!(deck.cards.len(), 2);
+    }
+
+    #[test]
+    fn lifecycle_transitions_lock_frozen_decks() {
+        let header = DeckHeader::new(None, None, Vec::new());
+        let mut deck =
+            Deck::from_lines(header, ["A"], EncodingKind::Hollerith, CardType::Code).unwrap();
+        assert!(deck.transition(DeckState::Frozen).is_err());
+        deck.transition(DeckState::Verified).unwrap();
+        deck.transition(DeckState::Frozen).unwrap();
+        let card = CardRecord::from_text("B", EncodingKind::Hollerith, CardType::Code).unwrap();
+        assert!(deck.append_card(card.clone()).is_err());
+        deck.transition(DeckState::Verified).unwrap();
+        deck.append_card(card).unwrap();
+        assert_eq!(deck.header.history.len(), 3);
+    }
+
+    #[test]
+    fn invalid_punches_flags_masks_outside_the_encoding() {
+        let header = DeckHeader::new(None, None, Vec::new());
+        let mut deck =
+            Deck::from_lines(header, ["A"], EncodingKind::Hollerith, CardType::Code).unwrap();
+        let columns = [
+            CellMask(0),
+            CellMask::from_rows(&[12, 1]).unwrap(),
+            CellMask(0xfff),
+        ];
+        deck.append_card(CardRecord::from_punches(&columns, CardType::Data))
+            .unwrap();
+        let invalid = deck
+            .invalid_punches(&crate::core::encoding::IBM029)
+            .unwrap();
+        assert_eq!(
+            invalid,
+            vec![InvalidPunch {
+                card: 1,
+                column: 3,
+                mask: CellMask(0xfff)
+            }]
+        );
+    }
+
+    #[test]
+    fn punch_only_cards_read_as_text_and_stay_protected() {
+        let ibm029 = &crate::core::encoding::IBM029;
+        let holes = |text: &str| {
+            let card =
+                CardRecord::from_text(text, EncodingKind::Hollerith, CardType::Code).unwrap();
+            card.to_punch_card(ibm029).unwrap().columns().to_vec()
+        };
+        let mut columns = holes("X = 1");
+        let header = DeckHeader::new(None, None, vec![ColumnRange::new(80, 80).unwrap()]);
+        let mut deck = Deck::new(header);
+        deck.append_card(CardRecord::from_punches(&columns, CardType::Code))
+            .unwrap();
+        assert_eq!(deck.card_text(0).unwrap().trim_end(), "X = 1");
+        assert_eq!(deck.as_text().unwrap()[0].trim_end(), "X = 1");
+
+        columns[2] = holes("Y")[0];
+        deck.replace_card(0, CardRecord::from_punches(&columns, CardType::Code))
+            .unwrap();
+        assert_eq!(deck.card_text(0).unwrap().trim_end(), "X Y 1");
+        // An undecodable hole pattern reads as a blank but still counts as a change.
+        columns[79] = CellMask(0xfff);
+        let err = deck
+            .replace_card(0, CardRecord::from_punches(&columns, CardType::Code))
+            .unwrap_err();
+        assert!(err.to_string().contains("column 80 is protected"));
+        assert!(
+            deck.append_card(CardRecord::from_punches(&columns, CardType::Code))
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn punch_only_cards_decode_with_their_own_chart() {
+        let ebcdic = &crate::core::ebcdic::EBCDIC;
+        let holes = crate::core::punchcards::PunchCard::from_str(ebcdic, "lower case")
+            .unwrap()
+            .columns()
+            .to_vec();
+        let mut card = CardRecord::from_punches(&holes, CardType::Data);
+        card.encoding = EncodingKind::Ebcdic;
+        let ibm029 = &crate::core::encoding::IBM029;
+        assert_eq!(card.text_view(ibm029).unwrap().trim_end(), "lower case");
+        assert_eq!(card.to_punch_card(ibm029).unwrap().columns()[..], holes[..]);
+    }
+
+    #[test]
+    fn from_jsonl_reads_what_save_writes() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("prog.deck");
+        let header = DeckHeader::new(Some("fortran".into()), None, Vec::new());
+        let mut deck =
+            Deck::from_lines(header, ["A", "B"], EncodingKind::Hollerith, CardType::Code).unwrap();
+        deck.save(&path).unwrap();
+        let parsed = Deck::from_jsonl(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(parsed.header, deck.header);
+        assert_eq!(parsed.cards, deck.cards);
+        assert!(Deck::from_jsonl("").is_err());
+    }
+
+    #[test]
+    fn tampered_cards_load_with_their_mismatch_recorded() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("prog.deck");
+        let header = DeckHeader::new(None, None, Vec::new());
+        let mut deck =
+            Deck::from_lines(header, ["A", "B"], EncodingKind::Hollerith, CardType::Code).unwrap();
+        deck.save(&path).unwrap();
+        let stored = deck.card_hash(1).unwrap();
+        let raw = std::fs::read_to_string(&path).unwrap();
+        std::fs::write(&path, raw.replacen("\"B ", "\"C ", 1)).unwrap();
+
+        let mut tampered = Deck::load(&path).unwrap();
+        assert_eq!(tampered.card_text(1).unwrap().trim_end(), "C");
+        assert_eq!(
+            tampered.hash_mismatches,
+            [CardHashMismatch {
+                card: 1,
+                line: 3,
+                stored,
+                computed: tampered.card_hash(1).unwrap(),
+            }]
+        );
+        tampered.save(&path).unwrap();
+        assert!(Deck::load(&path).unwrap().hash_mismatches.is_empty());
+    }
+
+    #[test]
+    fn char_usage_counts_text_cards() {
+        let header = DeckHeader::new(None, None, Vec::new());
+        let mut deck = Deck::from_lines(
+            header,
+            ["AB", "BA"],
+            EncodingKind::Hollerith,
+            CardType::Code,
+        )
+        .unwrap();
+        deck.cards[1].text = Some("B{".into());
+        let usage = deck.char_usage();
+        assert_eq!(usage[&'A'], 1);
+        assert_eq!(usage[&'B'], 2);
+        let failing = deck.unsupported_chars(&crate::core::encoding::IBM029);
+        assert_eq!(failing, vec![(1, vec!['{'])]);
+    }
+
+    #[test]
+    fn sample_indices_is_seeded_and_ordered() {
+        let header = DeckHeader::new(None, None, Vec::new());
+        let lines: Vec<String> = (0..50).map(|n| n.to_string()).collect();
+        let deck = Deck::from_lines(
+            header,
+            lines.iter().map(String::as_str),
+            EncodingKind::Hollerith,
+            CardType::Data,
+        )
+        .unwrap();
+        let picked = deck.sample_indices(7, 42);
+        assert_eq!(picked.len(), 7);
+        assert!(picked.windows(2).all(|w| w[0] < w[1]));
+        assert_eq!(picked, deck.sample_indices(7, 42));
+        assert_eq!(deck.sample_indices(80, 1).len(), 50);
+    }
+
+    #[test]
+    fn canonical_save_moves_volatile_fields_to_sidecar() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("prog.deck");
+        let mut deck = Deck::new(DeckHeader::new(None, None, Vec::new()));
+        deck.header.canonical = true;
+        deck.append_card(
+            CardRecord::from_text("HELLO", EncodingKind::Hollerith, CardType::Code).unwrap(),
+        )
+        .unwrap();
+        deck.log_action("test");
+        deck.save(&path).unwrap();
+
+        let raw = std::fs::read_to_string(&path).unwrap();
+        assert!(!raw.contains("created_at"));
+        assert!(!raw.contains("history"));
+        let reloaded = Deck::load(&path).unwrap();
+        assert_eq!(reloaded.header.created_at, deck.header.created_at);
+        assert_eq!(reloaded.header.history, deck.header.history);
+
+        // Copied without its sidecar, the deck has no creation date or
+        // history, yet hashes the same, every time.
+        std::fs::remove_file(audit_sidecar_path(&path)).unwrap();
+        let copied = Deck::load(&path).unwrap();
+        assert_eq!(copied.header.created_at, None);
+        assert!(copied.header.history.is_empty());
+        assert_eq!(copied.hash().unwrap(), deck.hash().unwrap());
+    }
+
+    #[test]
+    fn mapping_file_encoders_resolve_next_to_the_deck() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("site.csv"), "A,12-1\nX,0-8-2\n").unwrap();
+        let path = dir.path().join("prog.deck");
+        let mut deck = Deck::new(DeckHeader::new(None, None, Vec::new()));
+        deck.header.encoder = Some("site.csv".into());
+        deck.append_card(CardRecord::from_punches(
+            &[CellMask::from_rows(&[0, 8, 2]).unwrap()],
+            CardType::Data,
+        ))
+        .unwrap();
+        deck.save(&path).unwrap();
+
+        let loaded = Deck::load(&path).unwrap();
+        assert_eq!(loaded.encoder().unwrap().name(), "SITE");
+        assert_eq!(loaded.card_text(0).unwrap().trim_end(), "X");
+
+        std::fs::remove_file(dir.path().join("site.csv")).unwrap();
+        // Already resolved: the deck in memory keeps its chart.
+        assert_eq!(loaded.card_text(0).unwrap().trim_end(), "X");
+        let err = Deck::load(&path).unwrap_err();
+        assert!(
+            format!("{:#}", err).contains("deck encoder 'site.csv'"),
+            "{:#}",
+            err
+        );
+    }
+}
@@ -0,0 +1,634 @@
+//! Card-sequence alignment used by deck comparison and three-way merging.
+
+use anyhow::{Context, Result};
+
+use crate::core::deck::{CardMeta, CardRecord, CardType, ColumnRange, DeckHeader, EncodingKind};
+use crate::core::encoding::{CellMask, PunchEncoding};
+use crate::core::punchcards::PunchCard;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Pairs of `(left, right)` indices whose cards are equal, in increasing order.
+///
+/// Common prefixes and suffixes are matched directly; the region that
+/// actually changed is aligned with Hirschberg's algorithm, which takes
+/// quadratic time but only linear memory, so large rewrites of big decks
+/// stay affordable.
+pub fn lcs_pairs<T: PartialEq>(left: &[T], right: &[T]) -> Vec<(usize, usize)> {
+    let prefix = left.iter().zip(right).take_while(|(a, b)| a == b).count();
+    let max_suffix = left.len().min(right.len()) - prefix;
+    let suffix = left
+        .iter()
+        .rev()
+        .zip(right.iter().rev())
+        .take(max_suffix)
+        .take_while(|(a, b)| a == b)
+        .count();
+
+    let l = &left[prefix..left.len() - suffix];
+    let r = &right[prefix..right.len() - suffix];
+    let mut pairs: Vec<(usize, usize)> = (0..prefix).map(|i| (i, i)).collect();
+    hirschberg(l, r, (prefix, prefix), &mut pairs);
+    for k in 0..suffix {
+        pairs.push((left.len() - suffix + k, right.len() - suffix + k));
+    }
+    pairs
+}
+
+/// Append the LCS pairs of `l` and `r`, offset by `origin`, to `pairs`.
+fn hirschberg<T: PartialEq>(
+    l: &[T],
+    r: &[T],
+    origin: (usize, usize),
+    pairs: &mut Vec<(usize, usize)>,
+) {
+    if l.is_empty() || r.is_empty() {
+        return;
+    }
+    if l.len() == 1 {
+        if let Some(j) = r.iter().position(|item| *item == l[0]) {
+            pairs.push((origin.0, origin.1 + j));
+        }
+        return;
+    }
+    // Split `l` in half and find where the best alignment crosses `r`.
+    let mid = l.len() / 2;
+    let forward = lcs_lengths(&l[..mid], r, false);
+    let backward = lcs_lengths(&l[mid..], r, true);
+    let split = (0..=r.len())
+        .max_by_key(|&j| (forward[j] + backward[r.len() - j], std::cmp::Reverse(j)))
+        .unwrap_or(0);
+    hirschberg(&l[..mid], &r[..split], origin, pairs);
+    hirschberg(
+        &l[mid..],
+        &r[split..],
+        (origin.0 + mid, origin.1 + split),
+        pairs,
+    );
+}
+
+/// One row of the LCS table: entry `j` is the LCS length of `a` and the
+/// first `j` items of `b`, or with `reversed`, of `a` and the last `j`.
+fn lcs_lengths<T: PartialEq>(a: &[T], b: &[T], reversed: bool) -> Vec<u32> {
+    let pick = |items: &'_ [T], idx: usize| {
+        if reversed { items.len() - 1 - idx } else { idx }
+    };
+    let mut row = vec![0u32; b.len() + 1];
+    for i in 0..a.len() {
+        let item = &a[pick(a, i)];
+        let mut diagonal = 0;
+        for j in 0..b.len() {
+            let above = row[j + 1];
+            row[j + 1] = if *item == b[pick(b, j)] {
+                diagonal + 1
+            } else {
+                above.max(row[j])
+            };
+            diagonal = above;
+        }
+    }
+    row
+}
+
+/// Result of a three-way card merge.
+#[derive(Debug, Clone)]
+pub struct MergeOutcome {
+    /// Merged cards; conflicting regions are wrapped in marker comment cards.
+    pub cards: Vec<CardRecord>,
+    /// Number of regions both sides changed differently.
+    pub conflicts: usize,
+}
+
+/// Merge two descendants of `base` card by card, diff3 style.
+///
+/// Regions changed on only one side are taken from that side. Regions changed
+/// differently on both sides keep both versions between `<<<<<<<`, `=======`
+/// and `>>>>>>>` comment cards so the conflict stays visible inside the deck.
+pub fn merge3(base: &[CardRecord], ours: &[CardRecord], theirs: &[CardRecord]) -> MergeOutcome {
+    let ours_map = index_map(base.len(), &lcs_pairs(base, ours));
+    let theirs_map = index_map(base.len(), &lcs_pairs(base, theirs));
+
+    let mut cards = Vec::new();
+    let mut conflicts = 0;
+    let (mut b0, mut o0, mut t0) = (0, 0, 0);
+    let mut b = 0;
+    loop {
+        // Advance to the next base card kept by both sides (a stable anchor).
+        let anchor = (b..base.len()).find_map(|i| match (ours_map[i], theirs_map[i]) {
+            (Some(o), Some(t)) if o >= o0 && t >= t0 => Some((i, o, t)),
+            _ => None,
+        });
+        let (bi, oi, ti) = anchor.unwrap_or((base.len(), ours.len(), theirs.len()));
+
+        let base_chunk = &base[b0..bi];
+        let ours_chunk = &ours[o0..oi];
+        let theirs_chunk = &theirs[t0..ti];
+        if ours_chunk == base_chunk {
+            cards.extend_from_slice(theirs_chunk);
+        } else if theirs_chunk == base_chunk || ours_chunk == theirs_chunk {
+            cards.extend_from_slice(ours_chunk);
+        } else {
+            conflicts += 1;
+            cards.push(marker_card("<<<<<<< ours"));
+            cards.extend_from_slice(ours_chunk);
+            cards.push(marker_card("======="));
+            cards.extend_from_slice(theirs_chunk);
+            cards.push(marker_card(">>>>>>> theirs"));
+        }
+
+        if bi == base.len() {
+            break;
+        }
+        cards.push(ours[oi].clone());
+        b0 = bi + 1;
+        o0 = oi + 1;
+        t0 = ti + 1;
+        b = b0;
+    }
+    MergeOutcome { cards, conflicts }
+}
+
+/// Result of a three-way header merge.
+#[derive(Debug, Clone)]
+pub struct HeaderMerge {
+    /// Merged header; a field in conflict keeps our value.
+    pub header: DeckHeader,
+    /// Fields both sides changed differently, by their name in the deck file.
+    pub conflicts: Vec<String>,
+}
+
+/// Merge two descendants of the `base` header field by field, as [`merge3`]
+/// merges cards.
+///
+/// A field changed on one side only takes that side's value; one changed
+/// differently on both sides keeps ours and is listed in
+/// [`HeaderMerge::conflicts`]. The audit history is the union of both sides:
+/// ours, then the events only theirs recorded.
+pub fn merge3_header(
+    base: &DeckHeader,
+    ours: &DeckHeader,
+    theirs: &DeckHeader,
+) -> Result<HeaderMerge> {
+    let fields = |header: &DeckHeader| -> Result<serde_json::Map<String, serde_json::Value>> {
+        Ok(serde_json::to_value(header)
+            .context("failed to serialize deck header")?
+            .as_object()
+            .cloned()
+            .unwrap_or_default())
+    };
+    let (base_fields, theirs_fields) = (fields(base)?, fields(theirs)?);
+    let mut merged = fields(ours)?;
+    let mut names: Vec<String> = base_fields
+        .keys()
+        .chain(merged.keys())
+        .chain(theirs_fields.keys())
+        .filter(|name| *name != "history")
+        .cloned()
+        .collect();
+    names.sort();
+    names.dedup();
+    let mut conflicts = Vec::new();
+    for name in names {
+        let (b, t) = (base_fields.get(&name), theirs_fields.get(&name));
+        let o = merged.get(&name);
+        if t == b || o == t {
+            continue;
+        }
+        if o != b {
+            conflicts.push(name);
+            continue;
+        }
+        match t {
+            Some(value) => merged.insert(name, value.clone()),
+            None => merged.remove(&name),
+        };
+    }
+    let mut header: DeckHeader = serde_json::from_value(serde_json::Value::Object(merged))
+        .context("failed to rebuild merged deck header")?;
+    for event in &theirs.history {
+        if !header.history.contains(event) {
+            header.history.push(event.clone());
+        }
+    }
+    Ok(HeaderMerge { header, conflicts })
+}
+
+fn index_map(len: usize, pairs: &[(usize, usize)]) -> Vec<Option<usize>> {
+    let mut map = vec![None; len];
+    for &(l, r) in pairs {
+        map[l] = Some(r);
+    }
+    map
+}
+
+fn marker_card(text: &str) -> CardRecord {
+    let mut card = CardRecord::from_text(text, EncodingKind::Hollerith, CardType::Comment)
+        .expect("marker text fits on a card");
+    card.meta = CardMeta {
+        color: Some("red".to_string()),
+        note: Some("merge conflict".to_string()),
+        ..CardMeta::default()
+    };
+    card
+}
+
+/// A column whose holes differ between two punch-level passes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PunchMismatch {
+    /// Zero-based card index.
+    pub card: usize,
+    /// One-based column number.
+    pub column: usize,
+    pub expected: CellMask,
+    pub actual: CellMask,
+}
+
+/// Compare hole patterns card by card, skipping the columns returned by
+/// `mask_for_card`. A card missing from either side counts as blank.
+pub fn diff_punches(
+    expected: &[PunchCard],
+    actual: &[PunchCard],
+    mask_for_card: impl Fn(usize) -> Vec<ColumnRange>,
+) -> Vec<PunchMismatch> {
+    let blank = [CellMask(0); 80];
+    let mut mismatches = Vec::new();
+    for card in 0..expected.len().max(actual.len()) {
+        let exp = expected.get(card).map_or(&blank, |c| c.columns());
+        let act = actual.get(card).map_or(&blank, |c| c.columns());
+        let mask = mask_for_card(card);
+        for (idx, (e, a)) in exp.iter().zip(act).enumerate() {
+            let column = idx + 1;
+            if e != a && !mask.iter().any(|r| r.contains(column)) {
+                mismatches.push(PunchMismatch {
+                    card,
+                    column,
+                    expected: *e,
+                    actual: *a,
+                });
+            }
+        }
+    }
+    mismatches
+}
+
+/// A run of adjacent columns whose text changed when a card was rewritten.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ColumnChange {
+    pub columns: ColumnRange,
+    pub before: String,
+    pub after: String,
+}
+
+impl fmt::Display for ColumnChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.columns.start == self.columns.end {
+            write!(f, "col {}", self.columns.start)?;
+        } else {
+            write!(f, "cols {}-{}", self.columns.start, self.columns.end)?;
+        }
+        write!(f, ": '{}' -> '{}'", self.before, self.after)
+    }
+}
+
+/// Compare two card images column by column and group the differing columns
+/// into runs. Both sides are treated as padded with blanks to 80 columns.
+pub fn diff_columns(before: &str, after: &str) -> Vec<ColumnChange> {
+    let pad = |text: &str| -> Vec<char> {
+        let mut chars: Vec<char> = text.chars().take(80).collect();
+        chars.resize(80, ' ');
+        chars
+    };
+    let (old, new) = (pad(before), pad(after));
+    let mut changes: Vec<ColumnChange> = Vec::new();
+    for (idx, (o, n)) in old.iter().zip(&new).enumerate() {
+        if o == n {
+            continue;
+        }
+        let column = idx + 1;
+        match changes.last_mut() {
+            Some(run) if run.columns.end + 1 == column => {
+                run.columns.end = column;
+                run.before.push(*o);
+                run.after.push(*n);
+            }
+            _ => changes.push(ColumnChange {
+                columns: ColumnRange {
+                    start: column,
+                    end: column,
+                },
+                before: o.to_string(),
+                after: n.to_string(),
+            }),
+        }
+    }
+    changes
+}
+
+/// One difference between two decks, card by card.
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum CardDiff {
+    /// Card only in the right-hand deck (zero-based index there).
+    Added { right: usize, text: String },
+    /// Card only in the left-hand deck.
+    Removed { left: usize, text: String },
+    /// Card present in both decks with different content.
+    Changed {
+        left: usize,
+        right: usize,
+        /// Column runs whose text differs.
+        columns: Vec<ColumnChange>,
+        /// Other card fields that differ (`type`, `seq`, `encoding`, `punches`, `note`, `color`).
+        fields: Vec<&'static str>,
+    },
+}
+
+/// Card-by-card differences from `left` to `right`.
+///
+/// Identical cards are aligned first; within each gap between them, cards are
+/// paired off in order as changes and any surplus is reported as added or
+/// removed. Punch-only cards are compared by their text under `encoder`
+/// (see [`CardRecord::text_view`]); corrupt stored punches are an error.
+pub fn diff_cards(
+    left: &[CardRecord],
+    right: &[CardRecord],
+    encoder: &dyn PunchEncoding,
+) -> Result<Vec<CardDiff>> {
+    let texts = |side: &str, cards: &[CardRecord]| {
+        cards
+            .iter()
+            .enumerate()
+            .map(|(idx, card)| {
+                card.text_view(encoder)
+                    .map(|text| text.into_owned())
+                    .with_context(|| format!("{} card {}", side, idx + 1))
+            })
+            .collect::<Result<Vec<_>>>()
+    };
+    let (left_text, right_text) = (texts("left", left)?, texts("right", right)?);
+    let mut anchors = lcs_pairs(left, right);
+    anchors.push((left.len(), right.len()));
+    let mut found = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    for (next_i, next_j) in anchors {
+        let paired = (next_i - i).min(next_j - j);
+        for k in 0..paired {
+            let (old, new) = (&left[i + k], &right[j + k]);
+            found.push(CardDiff::Changed {
+                left: i + k,
+                right: j + k,
+                columns: diff_columns(&left_text[i + k], &right_text[j + k]),
+                fields: changed_fields(old, new),
+            });
+        }
+        found.extend((i + paired..next_i).map(|idx| CardDiff::Removed {
+            left: idx,
+            text: left_text[idx].clone(),
+        }));
+        found.extend((j + paired..next_j).map(|idx| CardDiff::Added {
+            right: idx,
+            text: right_text[idx].clone(),
+        }));
+        (i, j) = (next_i + 1, next_j + 1);
+    }
+    Ok(found)
+}
+
+/// One row of two decks laid side by side.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AlignedCard {
+    /// Identical cards at `left` and `right`.
+    Same {
+        left: usize,
+        right: usize,
+    },
+    Differs(CardDiff),
+}
+
+/// Both decks in full, aligned as [`diff_cards`] aligns them: identical
+/// cards side by side and every difference in its place between them.
+pub fn align_cards(
+    left: &[CardRecord],
+    right: &[CardRecord],
+    encoder: &dyn PunchEncoding,
+) -> Result<Vec<AlignedCard>> {
+    let mut rows = Vec::with_capacity(left.len().max(right.len()));
+    let (mut i, mut j) = (0, 0);
+    let same_until = |rows: &mut Vec<AlignedCard>, i: &mut usize, j: &mut usize, (l, r)| {
+        while *i < l && *j < r {
+            rows.push(AlignedCard::Same {
+                left: *i,
+                right: *j,
+            });
+            *i += 1;
+            *j += 1;
+        }
+    };
+    for diff in diff_cards(left, right, encoder)? {
+        match &diff {
+            CardDiff::Changed {
+                left: l, right: r, ..
+            } => {
+                same_until(&mut rows, &mut i, &mut j, (*l, *r));
+                (i, j) = (l + 1, r + 1);
+            }
+            CardDiff::Removed { left: l, .. } => {
+                same_until(&mut rows, &mut i, &mut j, (*l, usize::MAX));
+                i = l + 1;
+            }
+            CardDiff::Added { right: r, .. } => {
+                same_until(&mut rows, &mut i, &mut j, (usize::MAX, *r));
+                j = r + 1;
+            }
+        }
+        rows.push(AlignedCard::Differs(diff));
+    }
+    same_until(&mut rows, &mut i, &mut j, (left.len(), right.len()));
+    Ok(rows)
+}
+
+fn changed_fields(old: &CardRecord, new: &CardRecord) -> Vec<&'static str> {
+    [
+        ("type", old.card_type != new.card_type),
+        ("seq", old.seq != new.seq),
+        ("encoding", old.encoding != new.encoding),
+        ("punches", old.punches != new.punches),
+        ("note", old.meta.note != new.meta.note),
+        ("color", old.meta.color != new.meta.color),
+    ]
+    .into_iter()
+    .filter_map(|(name, differs)| differs.then_some(name))
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::encoding::IBM029;
+
+    fn cards(lines: &[&str]) -> Vec<CardRecord> {
+        lines
+            .iter()
+            .map(|l| CardRecord::from_text(*l, EncodingKind::Hollerith, CardType::Code).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn lcs_pairs_finds_a_longest_common_subsequence() {
+        let mut rng = crate::core::rng::SplitMix64::new(7);
+        for _ in 0..200 {
+            let mut seq = |len| -> Vec<u64> { (0..len).map(|_| rng.below(4)).collect() };
+            let (left, right) = (seq(12), seq(9));
+            // Reference LCS length from the full table.
+            let mut table = vec![vec![0usize; right.len() + 1]; left.len() + 1];
+            for i in 0..left.len() {
+                for j in 0..right.len() {
+                    table[i + 1][j + 1] = if left[i] == right[j] {
+                        table[i][j] + 1
+                    } else {
+                        table[i][j + 1].max(table[i + 1][j])
+                    };
+                }
+            }
+            let pairs = lcs_pairs(&left, &right);
+            assert_eq!(pairs.len(), table[left.len()][right.len()]);
+            assert!(pairs.iter().all(|&(i, j)| left[i] == right[j]));
+            assert!(pairs.windows(2).all(|w| w[0].0 < w[1].0 && w[0].1 < w[1].1));
+        }
+    }
+
+    #[test]
+    fn merge3_header_takes_one_sided_fields_and_flags_conflicts() {
+        let base = DeckHeader::new(Some("fortran".into()), None, Vec::new());
+        let mut ours = base.clone();
+        ours.notes = Some("ours".into());
+        ours.readonly = true;
+        let mut theirs = base.clone();
+        theirs.description = Some("payroll".into());
+        theirs.protected_cols = vec![ColumnRange::new(73, 80).unwrap()];
+        theirs.notes = Some("theirs".into());
+        theirs.language = None;
+
+        let merged = merge3_header(&base, &ours, &theirs).unwrap();
+        assert_eq!(merged.conflicts, ["notes"]);
+        let header = merged.header;
+        assert_eq!(header.description.as_deref(), Some("payroll"));
+        assert_eq!(header.protected_cols, theirs.protected_cols);
+        assert_eq!(header.language, None);
+        assert_eq!(header.notes.as_deref(), Some("ours"));
+        assert!(header.readonly);
+
+        let same = merge3_header(&base, &theirs, &theirs).unwrap();
+        assert!(same.conflicts.is_empty());
+        assert_eq!(same.header, theirs);
+    }
+
+    #[test]
+    fn merge3_takes_one_sided_changes_and_flags_conflicts() {
+        let base = cards(&["A", "B", "C"]);
+        let ours = cards(&["A", "B2", "C"]);
+        let theirs = cards(&["A", "B", "C", "D"]);
+        let merged = merge3(&base, &ours, &theirs);
+        assert_eq!(merged.conflicts, 0);
+        assert_eq!(merged.cards, cards(&["A", "B2", "C", "D"]));
+
+        let theirs = cards(&["A", "B3", "C"]);
+        let merged = merge3(&base, &ours, &theirs);
+        assert_eq!(merged.conflicts, 1);
+        assert_eq!(merged.cards.len(), 7);
+    }
+
+    #[test]
+    fn diff_cards_reports_changes_additions_and_removals() {
+        let left = cards(&["A", "B", "C", "D"]);
+        let mut right = cards(&["A", "B2", "C", "E", "F"]);
+        right[2].card_type = CardType::Data;
+        let found = diff_cards(&left, &right, &IBM029).unwrap();
+        assert_eq!(found.len(), 4);
+        assert!(
+            matches!(&found[0], CardDiff::Changed { left: 1, right: 1, columns, fields }
+            if columns.len() == 1 && fields.is_empty())
+        );
+        assert!(
+            matches!(&found[1], CardDiff::Changed { left: 2, right: 2, columns, fields }
+            if columns.is_empty() && fields == &["type"])
+        );
+        assert!(matches!(
+            &found[2],
+            CardDiff::Changed {
+                left: 3,
+                right: 3,
+                ..
+            }
+        ));
+        assert!(matches!(&found[3], CardDiff::Added { right: 4, .. }));
+        assert!(diff_cards(&left, &left, &IBM029).unwrap().is_empty());
+        assert!(matches!(
+            diff_cards(&left, &left[..3], &IBM029).unwrap()[..],
+            [CardDiff::Removed { left: 3, .. }]
+        ));
+
+        let punched = |card: &CardRecord| {
+            CardRecord::from_punches(
+                card.to_punch_card(&IBM029).unwrap().columns(),
+                CardType::Code,
+            )
+        };
+        let found = diff_cards(&left[..1], &[punched(&right[1])], &IBM029).unwrap();
+        assert!(matches!(
+            &found[0],
+            CardDiff::Changed { columns, fields, .. }
+            if columns[0].to_string() == "cols 1-2: 'A ' -> 'B2'" && fields == &["punches"]
+        ));
+    }
+
+    #[test]
+    fn align_cards_keeps_unchanged_cards_between_differences() {
+        let left = cards(&["A", "B", "C", "D"]);
+        let right = cards(&["A", "X", "B", "C"]);
+        let rows = align_cards(&left, &right, &IBM029).unwrap();
+        assert_eq!(
+            rows,
+            [
+                AlignedCard::Same { left: 0, right: 0 },
+                AlignedCard::Differs(CardDiff::Added {
+                    right: 1,
+                    text: right[1].text.clone().unwrap()
+                }),
+                AlignedCard::Same { left: 1, right: 2 },
+                AlignedCard::Same { left: 2, right: 3 },
+                AlignedCard::Differs(CardDiff::Removed {
+                    left: 3,
+                    text: left[3].text.clone().unwrap()
+                }),
+            ]
+        );
+        assert_eq!(align_cards(&left, &left, &IBM029).unwrap().len(), 4);
+    }
+
+    #[test]
+    fn diff_columns_groups_adjacent_changes() {
+        let changes = diff_columns("      X = 1", "      Y = 10");
+        assert_eq!(changes.len(), 2);
+        assert_eq!(changes[0].to_string(), "col 7: 'X' -> 'Y'");
+        assert_eq!(changes[1].columns, ColumnRange { start: 12, end: 12 });
+        assert_eq!(changes[1].after, "0");
+        assert!(diff_columns("SAME", "SAME    ").is_empty());
+    }
+
+    #[test]
+    fn diff_punches_reports_unmasked_columns() {
+        let enc = crate::core::encoding::IBM029;
+        let deck = [PunchCard::from_str(&enc, "AB      10").unwrap()];
+        let keyed = [
+            PunchCard::from_str(&enc, "AJ      99").unwrap(),
+            PunchCard::from_str(&enc, "X").unwrap(),
+        ];
+        let seq = ColumnRange::new(9, 10).unwrap();
+        let found = diff_punches(&deck, &keyed, |_| vec![seq]);
+        assert_eq!(found.len(), 2);
+        assert_eq!((found[0].card, found[0].column), (0, 2));
+        assert_eq!(found[0].actual.rows(), vec![11, 1]);
+        assert_eq!((found[1].card, found[1].expected), (1, CellMask(0)));
+    }
+}
@@ -0,0 +1,184 @@
+//! EBCDIC punched in the System/360 card code.
+//!
+//! The 360 card code gives each of the 256 EBCDIC byte values its own hole
+//! pattern, so binary data and lowercase text survive a trip through cards.
+//! Characters are mapped to bytes through code page 037; every character the
+//! IBM 029 can punch keeps its 029 holes, while lowercase letters, accented
+//! letters and control characters get the multi-punch codes the 029 lacks.
+
+use std::sync::LazyLock;
+
+use crate::core::encoding::{CellMask, EncodeError, PunchEncoding};
+
+/// Code page 037 (US/Canada EBCDIC), indexed by byte.
+const CP037: [char; 256] = [
+    // 0x0_
+    '\u{00}', '\u{01}', '\u{02}', '\u{03}', '\u{9c}', '\u{09}', '\u{86}', '\u{7f}', '\u{97}',
+    '\u{8d}', '\u{8e}', '\u{0b}', '\u{0c}', '\u{0d}', '\u{0e}', '\u{0f}', // 0x1_
+    '\u{10}', '\u{11}', '\u{12}', '\u{13}', '\u{9d}', '\u{85}', '\u{08}', '\u{87}', '\u{18}',
+    '\u{19}', '\u{92}', '\u{8f}', '\u{1c}', '\u{1d}', '\u{1e}', '\u{1f}', // 0x2_
+    '\u{80}', '\u{81}', '\u{82}', '\u{83}', '\u{84}', '\u{0a}', '\u{17}', '\u{1b}', '\u{88}',
+    '\u{89}', '\u{8a}', '\u{8b}', '\u{8c}', '\u{05}', '\u{06}', '\u{07}', // 0x3_
+    '\u{90}', '\u{91}', '\u{16}', '\u{93}', '\u{94}', '\u{95}', '\u{96}', '\u{04}', '\u{98}',
+    '\u{99}', '\u{9a}', '\u{9b}', '\u{14}', '\u{15}', '\u{9e}', '\u{1a}', // 0x4_
+    ' ', '\u{a0}', 'â', 'ä', 'à', 'á', 'ã', 'å', 'ç', 'ñ', '¢', '.', '<', '(', '+', '|',
+    // 0x5_
+    '&', 'é', 'ê', 'ë', 'è', 'í', 'î', 'ï', 'ì', 'ß', '!', '$', '*', ')', ';', '¬',
+    // 0x6_
+    '-', '/', 'Â', 'Ä', 'À', 'Á', 'Ã', 'Å', 'Ç', 'Ñ', '¦', ',', '%', '_', '>', '?',
+    // 0x7_
+    'ø', 'É', 'Ê', 'Ë', 'È', 'Í', 'Î', 'Ï', 'Ì', '`', ':', '#', '@', '\'', '=', '"',
+    // 0x8_
+    'Ø', 'a', 'b', 'c', 'd', 'e', 'f', 'g', 'h', 'i', '«', '»', 'ð', 'ý', 'þ', '±',
+    // 0x9_
+    '°', 'j', 'k', 'l', 'm', 'n', 'o', 'p', 'q', 'r', 'ª', 'º', 'æ', '¸', 'Æ', '¤',
+    // 0xA_
+    'µ', '~', 's', 't', 'u', 'v', 'w', 'x', 'y', 'z', '¡', '¿', 'Ð', 'Ý', 'Þ', '®',
+    // 0xB_
+    '^', '£', '¥', '·', '©', '§', '¶', '¼', '½', '¾', '[', ']', '¯', '¨', '´', '×',
+    // 0xC_
+    '{', 'A', 'B', 'C', 'D', 'E', 'F', 'G', 'H', 'I', '\u{ad}', 'ô', 'ö', 'ò', 'ó', 'õ',
+    // 0xD_
+    '}', 'J', 'K', 'L', 'M', 'N', 'O', 'P', 'Q', 'R', '¹', 'û', 'ü', 'ù', 'ú', 'ÿ',
+    // 0xE_
+    '\\', '÷', 'S', 'T', 'U', 'V', 'W', 'X', 'Y', 'Z', '²', 'Ô', 'Ö', 'Ò', 'Ó', 'Õ',
+    // 0xF_
+    '0', '1', '2', '3', '4', '5', '6', '7', '8', '9', '³', 'Û', 'Ü', 'Ù', 'Ú', '\u{9f}',
+];
+/// Card code of every byte value.
+static CARD_CODES: LazyLock<[CellMask; 256]> =
+    LazyLock::new(|| std::array::from_fn(|b| card_code(b as u8)));
+
+/// Zone punches selected by the low two bits of the high nibble.
+const ZONES: [&[u8]; 4] = [&[12], &[11], &[0], &[]];
+/// Double zones used where the single zone is taken.
+const DOUBLE_ZONES: [&[u8]; 4] = [&[12, 0], &[12, 11], &[11, 0], &[12, 11, 0]];
+
+/// Holes for `byte` under the System/360 card code.
+///
+/// Low nibbles 1–9 punch their digit and A–F punch 8 plus 2–7; the high
+/// nibble picks the zone punches and whether a 9 is added. A few positions
+/// are swapped so the 029 characters keep their familiar codes (`/` is 0-1,
+/// `\` is 0-8-2).
+pub fn card_code(byte: u8) -> CellMask {
+    let (high, low) = (byte >> 4, byte & 0x0f);
+    let quarter = (high & 0x03) as usize;
+    let digit: &[u8] = match low {
+        1..=9 => &[low],
+        0x0a..=0x0f => &[8, low - 8],
+        _ => &[],
+    };
+    let mut rows: Vec<u8> = Vec::with_capacity(6);
+    match (byte, high, low) {
+        (0x61, ..) => rows.extend([0, 1]),
+        (0x6a, ..) => rows.extend([12, 11]),
+        (0xe0, ..) => rows.extend([0, 8, 2]),
+        (0xe1, ..) => rows.extend([11, 0, 9, 1]),
+        // Control characters: a 9 punch, 8-1 standing in for digit 9.
+        (_, 0x0..=0x3, 0) => rows.extend(DOUBLE_ZONES[quarter].iter().chain(&[9, 8, 1])),
+        (_, 0x0..=0x3, 9) => rows.extend(ZONES[quarter].iter().chain(&[9, 8, 1])),
+        (_, 0x0..=0x3, _) => rows.extend(ZONES[quarter].iter().chain(&[9]).chain(digit)),
+        // Punctuation: specials on the single zone, the rest double-zoned with a 9.
+        (_, 0x4..=0x7, 0) => rows.extend([&[][..], &[12], &[11], &[12, 11, 0]][quarter]),
+        (_, 0x4..=0x7, 1..=8) => rows.extend(DOUBLE_ZONES[quarter].iter().chain(&[9]).chain(digit)),
+        (_, 0x4..=0x7, 9) => rows.extend(ZONES[quarter].iter().chain(&[8, 1])),
+        (_, 0x4..=0x7, _) => rows.extend(ZONES[quarter].iter().chain(digit)),
+        // Lowercase: double zones, 8-1 for the empty digit.
+        (_, 0x8..=0xb, 0) => rows.extend(DOUBLE_ZONES[quarter].iter().chain(&[8, 1])),
+        (_, 0x8..=0xb, _) => rows.extend(DOUBLE_ZONES[quarter].iter().chain(digit)),
+        // Uppercase and digits: the familiar single zones.
+        (_, _, 0) => rows.extend([&[12, 0][..], &[11, 0], &[0, 8, 2], &[0]][quarter]),
+        (_, _, 1..=9) => rows.extend(ZONES[quarter].iter().chain(digit)),
+        (_, _, _) => rows.extend(DOUBLE_ZONES[quarter].iter().chain(&[9]).chain(digit)),
+    }
+    CellMask::from_rows(&rows).expect("card code rows are valid")
+}
+
+/// Byte value whose card code is `mask`.
+pub fn byte_for_card_code(mask: CellMask) -> Option<u8> {
+    CARD_CODES.iter().position(|m| *m == mask).map(|b| b as u8)
+}
+
+/// Punches text as EBCDIC code page 037 in the System/360 card code.
+///
+/// Unlike the keypunch charts, lowercase letters are distinct characters
+/// with their own holes.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct EbcdicEncoder;
+
+/// Shared EBCDIC encoder instance.
+pub const EBCDIC: EbcdicEncoder = EbcdicEncoder;
+
+impl PunchEncoding for EbcdicEncoder {
+    fn name(&self) -> &'static str {
+        "EBCDIC"
+    }
+
+    fn encode_char(&self, ch: char) -> Result<CellMask, EncodeError> {
+        CP037
+            .iter()
+            .position(|c| *c == ch)
+            .map(|b| CARD_CODES[b])
+            .ok_or(EncodeError::Unsupported(ch, ch as u32))
+    }
+
+    fn decode(&self, mask: CellMask) -> Option<char> {
+        byte_for_card_code(mask).map(|b| CP037[b as usize])
+    }
+}
+
+/// Punches 7-bit ASCII text in the System/360 card code, as 360 shops
+/// punched ASCII data: lowercase letters keep their own holes and characters
+/// outside ASCII are rejected.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct AsciiCardEncoder;
+
+/// Shared ASCII card-code encoder instance.
+pub const ASCII: AsciiCardEncoder = AsciiCardEncoder;
+
+impl PunchEncoding for AsciiCardEncoder {
+    fn name(&self) -> &'static str {
+        "ASCII"
+    }
+
+    fn encode_char(&self, ch: char) -> Result<CellMask, EncodeError> {
+        if !ch.is_ascii() {
+            return Err(EncodeError::Unsupported(ch, ch as u32));
+        }
+        EBCDIC.encode_char(ch)
+    }
+
+    fn decode(&self, mask: CellMask) -> Option<char> {
+        EBCDIC.decode(mask).filter(char::is_ascii)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::encoding::{IBM029, VALID_SET};
+    use std::collections::HashSet;
+
+    #[test]
+    fn card_code_is_a_bijection_that_extends_029() {
+        let masks: HashSet<u16> = CARD_CODES.iter().map(|m| m.0).collect();
+        assert_eq!(masks.len(), 256);
+        for ch in VALID_SET.chars() {
+            assert_eq!(
+                EBCDIC.encode_char(ch).unwrap(),
+                IBM029.encode_char(ch).unwrap(),
+                "{ch}"
+            );
+        }
+        let rows = |b: u8| card_code(b).rows();
+        assert_eq!(rows(0x00), vec![12, 0, 1, 8, 9]);
+        assert_eq!(rows(0x81), vec![12, 0, 1]);
+        assert_eq!(rows(0x41), vec![12, 0, 1, 9]);
+        assert_eq!(rows(0xca), vec![12, 0, 2, 8, 9]);
+        assert_eq!(EBCDIC.decode(EBCDIC.encode_char('q').unwrap()), Some('q'));
+        assert_ne!(
+            EBCDIC.encode_char('q').unwrap(),
+            EBCDIC.encode_char('Q').unwrap()
+        );
+    }
+}
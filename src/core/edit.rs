@@ -0,0 +1,335 @@
+//! Plain-text round trip behind `punch deck edit`.
+//!
+//! [`Deck::edit_buffer`] lays the deck out as one `INDEX TYPE [color=NAME]
+//! [note="TEXT"] |TEXT` line per card under a comment preamble;
+//! [`Deck::apply_edit_buffer`] reads an edited buffer back, matching lines to
+//! cards by their index so surviving cards keep their other metadata. Notes
+//! are written as JSON strings, so they may hold blanks, quotes and `|`.
+
+use anyhow::{Context, Result, anyhow};
+
+use crate::core::deck::{CardMeta, CardRecord, CardType, Deck, EncodingKind};
+
+/// Gutter marker for cards added while editing.
+pub const EDIT_NEW_CARD: &str = "+";
+
+/// Counts reported after an edit round trip.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EditSummary {
+    pub changed: usize,
+    pub added: usize,
+    pub removed: usize,
+}
+
+impl Deck {
+    /// Editable text for the deck: comment preamble naming `title`, then one
+    /// `INDEX TYPE [color=NAME] [note="TEXT"] |TEXT` line per card, with the
+    /// gutters padded so every card's text starts under the ruler.
+    pub fn edit_buffer(&self, title: &str) -> Result<String> {
+        let gutters: Vec<String> = self
+            .cards
+            .iter()
+            .enumerate()
+            .map(|(idx, card)| {
+                let mut gutter = format!("{:04} {:<10}", idx + 1, card.card_type);
+                if let Some(color) = &card.meta.color {
+                    gutter.push_str(&format!(" color={}", color));
+                }
+                if let Some(note) = &card.meta.note {
+                    let quoted = serde_json::to_string(note).expect("strings serialize");
+                    gutter.push_str(&format!(" note={}", quoted));
+                }
+                gutter
+            })
+            .collect();
+        let width = gutters
+            .iter()
+            .map(|g| g.chars().count() + 1)
+            .fold(16, usize::max);
+        let mut out = String::new();
+        out.push_str(&format!("# punch deck edit: {}\n", title));
+        out.push_str("# Lines starting with '#' are ignored. Each card is `INDEX TYPE |TEXT`,\n");
+        out.push_str("# optionally with `color=NAME` and `note=\"TEXT\"` before the `|`.\n");
+        out.push_str(&format!(
+            "# Use `{}` as INDEX for new cards; delete a line to remove its card.\n",
+            EDIT_NEW_CARD
+        ));
+        out.push_str(&format!("# {:<w$}|{}\n", "", ruler(), w = width - 2));
+        for (idx, gutter) in gutters.iter().enumerate() {
+            let text = self.card_text(idx)?;
+            out.push_str(&format!("{:<w$}|{}\n", gutter, text.trim_end(), w = width));
+        }
+        Ok(out)
+    }
+
+    /// Rebuild the cards from an edited [`edit_buffer`](Self::edit_buffer).
+    ///
+    /// Lines may be reordered, changed or deleted, and lines indexed
+    /// [`EDIT_NEW_CARD`] add cards. Changes and additions respect protected
+    /// columns, and a card punched in a protected column cannot be deleted
+    /// here. Nothing changes if any line is refused.
+    pub fn apply_edit_buffer(&mut self, edited: &str) -> Result<EditSummary> {
+        self.ensure_mutable()?;
+        let mut cards = Vec::new();
+        let mut seen = vec![false; self.cards.len()];
+        let mut summary = EditSummary {
+            changed: 0,
+            added: 0,
+            removed: 0,
+        };
+        for (line_no, line) in edited.lines().enumerate() {
+            let line_no = line_no + 1;
+            if line.starts_with('#') || line.trim().is_empty() {
+                continue;
+            }
+            let gutter = Gutter::parse(line).with_context(|| format!("line {}", line_no))?;
+            let (index, text) = (gutter.index, gutter.text);
+            let mut record = CardRecord::from_text(text, EncodingKind::Hollerith, gutter.card_type)
+                .with_context(|| format!("line {}", line_no))?;
+            record.meta = CardMeta {
+                color: gutter.color,
+                note: gutter.note,
+                ..CardMeta::default()
+            };
+            if index == EDIT_NEW_CARD {
+                self.enforce_protection(None, &record)
+                    .with_context(|| format!("line {}", line_no))?;
+                summary.added += 1;
+            } else {
+                let idx: usize = index
+                    .parse()
+                    .ok()
+                    .filter(|&n| n >= 1 && n <= self.cards.len())
+                    .ok_or_else(|| {
+                        anyhow!(
+                            "line {}: card index '{}' is not in 1..{}",
+                            line_no,
+                            index,
+                            self.cards.len()
+                        )
+                    })?;
+                if std::mem::replace(&mut seen[idx - 1], true) {
+                    return Err(anyhow!("line {}: card {} appears twice", line_no, idx));
+                }
+                let previous = &self.cards[idx - 1];
+                self.enforce_protection(Some(previous), &record)
+                    .with_context(|| format!("line {} (card {})", line_no, idx))?;
+                let mut updated_text = record.text.take();
+                if previous.text.is_none()
+                    && updated_text.as_deref() == Some(&*self.card_text(idx - 1)?)
+                {
+                    // An untouched punch-only card keeps its exact holes.
+                    updated_text = None;
+                }
+                let updated_type = record.card_type.clone();
+                let updated_meta = CardMeta {
+                    patches: previous.meta.patches,
+                    ..record.meta
+                };
+                record = previous.clone();
+                if record.text != updated_text
+                    || record.card_type != updated_type
+                    || record.meta != updated_meta
+                {
+                    summary.changed += 1;
+                }
+                record.text = updated_text;
+                record.card_type = updated_type;
+                record.meta = updated_meta;
+            }
+            cards.push(record);
+        }
+        for (idx, _) in seen.iter().enumerate().filter(|(_, kept)| !**kept) {
+            self.enforce_removal(&self.cards[idx])
+                .with_context(|| format!("card {} was deleted", idx + 1))?;
+            summary.removed += 1;
+        }
+        self.cards = cards;
+        Ok(summary)
+    }
+}
+
+/// One card line of an edit buffer, split into its gutter fields and text.
+struct Gutter<'a> {
+    index: &'a str,
+    card_type: CardType,
+    color: Option<String>,
+    note: Option<String>,
+    text: &'a str,
+}
+
+impl<'a> Gutter<'a> {
+    fn parse(line: &'a str) -> Result<Self> {
+        let mut rest = line.trim_start();
+        let mut words: Vec<&str> = Vec::new();
+        let (mut color, mut note) = (None, None);
+        loop {
+            if let Some(text) = rest.strip_prefix('|') {
+                let index = *words
+                    .first()
+                    .ok_or_else(|| anyhow!("missing card index in gutter"))?;
+                let card_type = match words.get(1) {
+                    Some(raw) => raw.parse()?,
+                    None => CardType::default(),
+                };
+                if let Some(extra) = words.get(2) {
+                    return Err(anyhow!("unexpected '{}' in gutter", extra));
+                }
+                return Ok(Self {
+                    index,
+                    card_type,
+                    color,
+                    note,
+                    text,
+                });
+            }
+            if rest.is_empty() {
+                return Err(anyhow!("missing '|' between gutter and card text"));
+            }
+            if let Some(quoted) = rest.strip_prefix("note=") {
+                let mut strings = serde_json::Deserializer::from_str(quoted).into_iter::<String>();
+                let value = strings
+                    .next()
+                    .ok_or_else(|| anyhow!("note= needs a quoted string"))?
+                    .context("note= needs a quoted string")?;
+                note = Some(value);
+                rest = quoted[strings.byte_offset()..].trim_start();
+                continue;
+            }
+            let end = rest
+                .find(|ch: char| ch.is_whitespace() || ch == '|')
+                .unwrap_or(rest.len());
+            let word = &rest[..end];
+            match word.strip_prefix("color=") {
+                Some(value) => color = Some(value.to_string()),
+                None => words.push(word),
+            }
+            rest = rest[end..].trim_start();
+        }
+    }
+}
+
+/// Column ruler matching the edit buffer's text area.
+fn ruler() -> String {
+    (1..=80)
+        .map(|col| {
+            if col % 10 == 0 {
+                char::from(b'0' + ((col / 10) % 10) as u8)
+            } else {
+                '.'
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::deck::{ColumnRange, DeckHeader};
+
+    fn deck(protected: Vec<ColumnRange>) -> Deck {
+        let mut deck = Deck::from_lines(
+            DeckHeader::new(None, None, Vec::new()),
+            ["ONE", "TWO", "THREE"],
+            EncodingKind::Hollerith,
+            CardType::Code,
+        )
+        .unwrap();
+        deck.header.protected_cols = protected;
+        deck
+    }
+
+    #[test]
+    fn edit_buffer_round_trips_reorder_delete_and_add() {
+        let mut deck = deck(Vec::new());
+        deck.cards[0].meta.note = Some("first".into());
+        let buffer = deck.edit_buffer("prog.deck").unwrap();
+        assert!(buffer.contains("0001 code       note=\"first\" |ONE\n"));
+        assert!(buffer.contains("0002 code                    |TWO\n"));
+        assert_eq!(deck.clone().apply_edit_buffer(&buffer).unwrap().changed, 0);
+
+        let edited = "# comment\n\n0003 data |THREE\n+ |NEW\n0001 code note=\"first\"|ONE!\n";
+        let summary = deck.apply_edit_buffer(edited).unwrap();
+        assert_eq!(
+            summary,
+            EditSummary {
+                changed: 2,
+                added: 1,
+                removed: 1
+            }
+        );
+        let texts: Vec<String> = (0..3)
+            .map(|idx| deck.card_text(idx).unwrap().trim_end().to_string())
+            .collect();
+        assert_eq!(texts, ["THREE", "NEW", "ONE!"]);
+        assert_eq!(deck.cards[0].card_type, CardType::Data);
+        assert_eq!(deck.cards[2].meta.note.as_deref(), Some("first"));
+    }
+
+    #[test]
+    fn gutter_metadata_round_trips() {
+        let mut deck = deck(Vec::new());
+        deck.cards[0].meta.color = Some("red".into());
+        deck.cards[0].meta.note = Some("check | \"this\" one".into());
+        deck.cards[1].meta.color = Some("amber".into());
+        let buffer = deck.edit_buffer("prog.deck").unwrap();
+        assert!(
+            buffer.contains("0001 code       color=red note=\"check | \\\"this\\\" one\" |ONE\n"),
+            "{}",
+            buffer
+        );
+        let mut copy = deck.clone();
+        assert_eq!(copy.apply_edit_buffer(&buffer).unwrap().changed, 0);
+        assert_eq!(copy.cards, deck.cards);
+
+        let edited = buffer.replace("color=red ", "").replace(
+            "0002 code       color=amber",
+            "0002 code color=blue note=\"new\"",
+        );
+        let summary = deck.apply_edit_buffer(&edited).unwrap();
+        assert_eq!(summary.changed, 2);
+        assert_eq!(deck.cards[0].meta.color, None);
+        assert_eq!(
+            deck.cards[0].meta.note.as_deref(),
+            Some("check | \"this\" one")
+        );
+        assert_eq!(deck.cards[1].meta.color.as_deref(), Some("blue"));
+        assert_eq!(deck.cards[1].meta.note.as_deref(), Some("new"));
+        assert_eq!(deck.cards[2].meta, CardMeta::default());
+
+        let err = deck
+            .apply_edit_buffer("0001 note=first |ONE\n")
+            .unwrap_err();
+        assert!(format!("{:#}", err).contains("note= needs a quoted string"));
+    }
+
+    #[test]
+    fn malformed_and_protected_edits_are_refused() {
+        let mut deck = deck(vec![ColumnRange::new(1, 1).unwrap()]);
+        for (edited, message) in [
+            ("0001 code ONE\n", "line 1: missing '|'"),
+            (" |ONE\n", "line 1: missing card index"),
+            ("0009 |ONE\n", "card index '0009' is not in 1..3"),
+            ("0001 |ONE\n0001 |ONE\n", "line 2: card 1 appears twice"),
+            ("0001 bogus |ONE\n", "line 1"),
+            (
+                "0001 code extra |ONE\n",
+                "line 1: unexpected 'extra' in gutter",
+            ),
+            (
+                "0001 |XNE\n0002 |TWO\n0003 |THREE\n",
+                "column 1 is protected",
+            ),
+            ("0001 |ONE\n0002 |TWO\n", "card 3 was deleted"),
+        ] {
+            let err = deck.apply_edit_buffer(edited).unwrap_err();
+            assert!(
+                format!("{:#}", err).contains(message),
+                "{:?}: {:#}",
+                edited,
+                err
+            );
+        }
+        assert_eq!(deck.cards.len(), 3);
+    }
+}
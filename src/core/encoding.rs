@@ -1,6 +1,13 @@
-use std::collections::HashMap;
+use std::path::Path;
+
+use anyhow::{Result, anyhow};
 use thiserror::Error;
 
+use crate::core::deck::EncodingKind;
+use crate::core::ebcdic::{ASCII, EBCDIC};
+use crate::core::loaded::FileCache;
+use crate::core::table::TableEncoder;
+
 /// There are 12 rows in total: 12, 11, and 0..9.
 /// Each column’s punched holes are represented as a bitmask in a `u16`.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -10,6 +17,83 @@ pub struct CellMask(pub u16);
 pub enum EncodeError {
     #[error("unsupported character: '{0}' (U+{1:04X})")]
     Unsupported(char, u32),
+    #[error("invalid stored punches: {0}")]
+    InvalidPunches(String),
+    #[error("sequence number {0} does not fit in columns 73-80")]
+    SequenceOverflow(usize),
+}
+
+/// Row labels in card order (top to bottom), as printed on the card face.
+pub const ROW_NAMES: [u8; 12] = [12, 11, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9];
+
+impl CellMask {
+    /// Row labels punched in this column, top to bottom (e.g. `[12, 1]` for `A`).
+    pub fn rows(self) -> Vec<u8> {
+        ROW_NAMES
+            .iter()
+            .zip(ROW_BIT_ORDER)
+            .filter(|(_, bit)| (self.0 >> bit) & 1 == 1)
+            .map(|(name, _)| *name)
+            .collect()
+    }
+
+    /// Build a mask from row labels (12, 11, 0–9); returns `None` for unknown rows.
+    pub fn from_rows(rows: &[u8]) -> Option<Self> {
+        let mut value = 0u16;
+        for row in rows {
+            let pos = ROW_NAMES.iter().position(|r| r == row)?;
+            value |= 1u16 << ROW_BIT_ORDER[pos];
+        }
+        Some(CellMask(value))
+    }
+
+    /// Parse chart notation as printed by [`Display`](std::fmt::Display):
+    /// rows joined by `-` (`12-1`), or `blank`.
+    pub fn from_row_spec(spec: &str) -> Option<Self> {
+        let spec = spec.trim();
+        if spec.eq_ignore_ascii_case("blank") {
+            return Some(CellMask(0));
+        }
+        let rows = spec
+            .split('-')
+            .map(|row| row.parse::<u8>().ok())
+            .collect::<Option<Vec<u8>>>()?;
+        Self::from_rows(&rows)
+    }
+
+    /// Digit `digit` (0–9) with a zoned-decimal sign overpunch: a 12 zone for
+    /// positive values, an 11 zone for negative ones.
+    pub fn overpunch(digit: u8, negative: bool) -> Self {
+        assert!(digit <= 9, "overpunch digit must be 0-9");
+        let zone = if negative { 11 } else { 12 };
+        Self::from_rows(&[zone, digit]).expect("zone and digit rows are valid")
+    }
+
+    /// Split a sign-bearing digit column into `(digit, negative)`.
+    ///
+    /// A bare digit reads as positive; anything other than one digit row plus
+    /// an optional single 12 or 11 zone returns `None`.
+    pub fn overpunch_digit(self) -> Option<(u8, bool)> {
+        let rows = self.rows();
+        let (zones, digits): (Vec<u8>, Vec<u8>) = rows.iter().partition(|r| **r >= 11);
+        match (zones.as_slice(), digits.as_slice()) {
+            ([], [d]) | ([12], [d]) => Some((*d, false)),
+            ([11], [d]) => Some((*d, true)),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for CellMask {
+    /// Rows joined with `-` as written on punching charts (`12-1`), or `blank`.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let rows: Vec<String> = self.rows().iter().map(|r| r.to_string()).collect();
+        if rows.is_empty() {
+            f.pad("blank")
+        } else {
+            f.pad(&rows.join("-"))
+        }
+    }
 }
 
 impl std::ops::BitOr for CellMask {
@@ -20,12 +104,69 @@ impl std::ops::BitOr for CellMask {
 }
 
 /// Trait implemented by encoding adapters capable of mapping characters into punch-card bitmasks.
-pub trait PunchEncoding {
-    fn name(&self) -> &'static str;
+///
+/// Encoders are shared between render threads, hence `Send + Sync`.
+pub trait PunchEncoding: Send + Sync {
+    fn name(&self) -> &str;
     fn encode_char(&self, ch: char) -> Result<CellMask, EncodeError>;
     fn is_supported(&self, ch: char) -> bool {
         self.encode_char(ch).is_ok()
     }
+    /// Reverse lookup: the character punched as `mask`, if the encoding has one.
+    fn decode(&self, mask: CellMask) -> Option<char>;
+    /// Whether `mask` is blank or punches a character of this encoding.
+    fn is_legal(&self, mask: CellMask) -> bool {
+        mask.0 == 0 || self.decode(mask).is_some()
+    }
+}
+
+/// Registry of the punch charts known by name.
+///
+/// Deck headers and CLI flags name a chart; the registry turns the name into
+/// an encoder. Names ending in `.toml` or `.csv` load a [`TableEncoder`] from
+/// that mapping file.
+pub struct EncoderRegistry;
+
+impl EncoderRegistry {
+    /// Return the built-in charts.
+    pub fn list() -> Vec<&'static dyn PunchEncoding> {
+        vec![&IBM029, &IBM026, &IBM026_FORTRAN, &EBCDIC, &ASCII]
+    }
+
+    /// Resolve a chart by name (case-insensitive), or load a mapping file.
+    /// A mapping file is read once and reloaded only after it changes.
+    pub fn get(name: &str) -> Result<&'static dyn PunchEncoding> {
+        if Self::is_mapping_file(name) {
+            static TABLES: FileCache<TableEncoder> = FileCache::new();
+            let table = TABLES.get(Path::new(name), |path| {
+                Ok(Box::leak(Box::new(TableEncoder::from_path(path)?)))
+            })?;
+            return Ok(table);
+        }
+        Self::list()
+            .into_iter()
+            .find(|encoder| encoder.name().eq_ignore_ascii_case(name))
+            .ok_or_else(|| {
+                let known: Vec<&str> = Self::list().iter().map(|e| e.name()).collect();
+                anyhow!("unknown encoder '{}' (known: {})", name, known.join(", "))
+            })
+    }
+
+    /// Whether `name` names a mapping file rather than a built-in chart.
+    pub fn is_mapping_file(name: &str) -> bool {
+        let lower = name.to_ascii_lowercase();
+        lower.ends_with(".toml") || lower.ends_with(".csv")
+    }
+
+    /// Chart that punches text stored under `kind`. Hollerith text is punched
+    /// on the IBM 029 unless a deck names another keypunch chart.
+    pub fn for_encoding(kind: EncodingKind) -> &'static dyn PunchEncoding {
+        match kind {
+            EncodingKind::Hollerith => &IBM029,
+            EncodingKind::Ascii => &ASCII,
+            EncodingKind::Ebcdic => &EBCDIC,
+        }
+    }
 }
 
 /// Valid character set (source: original project README)
@@ -101,18 +242,18 @@ const IBM029_TABLE: &[(char, &str)] = &[
 /// - Each column can punch any of 12 rows (12, 11, 0–9).
 /// - Digits, letters, and special characters map to unique hole combinations.
 /// - The table above reproduces the original 029 keypunch chart.
-#[derive(Default)]
-pub struct Ibm029Encoder {
-    map: HashMap<char, CellMask>,
-}
+///
+/// The encoder is zero-sized: lookups go through a table built at compile time,
+/// so prefer the shared [`IBM029`] constant over constructing new instances.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Ibm029Encoder;
+
+/// Shared IBM 029 encoder instance.
+pub const IBM029: Ibm029Encoder = Ibm029Encoder;
 
 impl Ibm029Encoder {
-    pub fn new() -> Self {
-        let mut m = HashMap::new();
-        for (ch, bits) in IBM029_TABLE {
-            m.insert(*ch, mask_from_bits(bits));
-        }
-        Self { map: m }
+    pub const fn new() -> Self {
+        Self
     }
 }
 
@@ -122,33 +263,179 @@ impl PunchEncoding for Ibm029Encoder {
     }
 
     fn encode_char(&self, ch: char) -> Result<CellMask, EncodeError> {
-        let up = if ch.is_ascii_lowercase() {
-            ch.to_ascii_uppercase()
+        let mask = match ch {
+            '¢' => Some(CENT_SIGN_MASK),
+            '¬' => Some(NOT_SIGN_MASK),
+            c if c.is_ascii() => ASCII_LOOKUP[c as usize],
+            _ => None,
+        };
+        mask.ok_or(EncodeError::Unsupported(ch, ch as u32))
+    }
+
+    fn decode(&self, mask: CellMask) -> Option<char> {
+        IBM029_TABLE
+            .iter()
+            .find(|(_, bits)| mask_from_bits(bits) == mask)
+            .map(|(ch, _)| *ch)
+    }
+}
+
+/// Characters the IBM 026 punches the same way in both of its charts:
+/// digits, letters, blank, `-` and `/` match the 029.
+const IBM026_COMMON_TABLE: &[(char, &str)] = &[
+    ('-', "010000000000"),
+    ('/', "001100000000"),
+    (' ', "000000000000"),
+];
+
+/// 026 commercial ("A") chart: the BCD report-writing symbols.
+const IBM026_COMMERCIAL_TABLE: &[(char, &str)] = &[
+    ('&', "100000000000"),
+    ('.', "100001000010"),
+    ('¤', "100000100010"),
+    ('$', "010001000010"),
+    ('*', "010000100010"),
+    (',', "001001000010"),
+    ('%', "001000100010"),
+    ('#', "000001000010"),
+    ('@', "000000100010"),
+];
+
+/// 026 FORTRAN ("H") chart: the same holes read as FORTRAN operators.
+const IBM026_FORTRAN_TABLE: &[(char, &str)] = &[
+    ('+', "100000000000"),
+    ('.', "100001000010"),
+    (')', "100000100010"),
+    ('$', "010001000010"),
+    ('*', "010000100010"),
+    (',', "001001000010"),
+    ('(', "001000100010"),
+    ('=', "000001000010"),
+    ('\'', "000000100010"),
+];
+
+/// Which IBM 026 keyboard a deck was punched on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Ibm026Chart {
+    /// Business keyboard (`&`, `#`, `@`, `%`, `¤`).
+    Commercial,
+    /// Scientific keyboard (`+`, `=`, `'`, `(`, `)`).
+    Fortran,
+}
+
+/// The IBM 026 printing card punch that preceded the 029.
+///
+/// Letters and digits punch as on the 029, but the 026 had only eleven
+/// special characters, all single-zone 8-3/8-4 combinations, and two
+/// keyboards assigning them differently. Decks keyed on a 026 render with
+/// the wrong holes under [`IBM029`] wherever these characters appear.
+#[derive(Debug, Clone, Copy)]
+pub struct Ibm026Encoder {
+    pub chart: Ibm026Chart,
+}
+
+/// Shared IBM 026 commercial encoder instance.
+pub const IBM026: Ibm026Encoder = Ibm026Encoder::new(Ibm026Chart::Commercial);
+/// Shared IBM 026 FORTRAN encoder instance.
+pub const IBM026_FORTRAN: Ibm026Encoder = Ibm026Encoder::new(Ibm026Chart::Fortran);
+
+impl Ibm026Encoder {
+    pub const fn new(chart: Ibm026Chart) -> Self {
+        Self { chart }
+    }
+
+    fn specials(&self) -> &'static [(char, &'static str)] {
+        match self.chart {
+            Ibm026Chart::Commercial => IBM026_COMMERCIAL_TABLE,
+            Ibm026Chart::Fortran => IBM026_FORTRAN_TABLE,
+        }
+    }
+
+    fn table(&self) -> impl Iterator<Item = (char, CellMask)> + '_ {
+        IBM026_COMMON_TABLE
+            .iter()
+            .chain(self.specials())
+            .map(|(ch, bits)| (*ch, mask_from_bits(bits)))
+    }
+}
+
+impl PunchEncoding for Ibm026Encoder {
+    fn name(&self) -> &'static str {
+        match self.chart {
+            Ibm026Chart::Commercial => "IBM026",
+            Ibm026Chart::Fortran => "IBM026-FORTRAN",
+        }
+    }
+
+    fn encode_char(&self, ch: char) -> Result<CellMask, EncodeError> {
+        let up = ch.to_ascii_uppercase();
+        let mask = if up.is_ascii_alphanumeric() {
+            ASCII_LOOKUP[up as usize]
         } else {
-            ch
+            self.table().find(|(c, _)| *c == ch).map(|(_, mask)| mask)
         };
-        self.map
-            .get(&up)
-            .copied()
-            .ok_or(EncodeError::Unsupported(ch, ch as u32))
+        mask.ok_or(EncodeError::Unsupported(ch, ch as u32))
+    }
+
+    fn decode(&self, mask: CellMask) -> Option<char> {
+        self.table()
+            .find(|(_, m)| *m == mask)
+            .map(|(ch, _)| ch)
+            .or_else(|| IBM029.decode(mask).filter(|ch| ch.is_ascii_alphanumeric()))
     }
 }
 
 const ROW_BIT_ORDER: [usize; 12] = [11, 10, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9];
 
-fn mask_from_bits(bits: &str) -> CellMask {
-    assert_eq!(
-        bits.len(),
-        ROW_BIT_ORDER.len(),
+/// ASCII code point -> punches; lowercase letters share their uppercase patterns.
+const ASCII_LOOKUP: [Option<CellMask>; 128] = build_ascii_lookup();
+/// The only two non-ASCII characters on the 029 chart.
+const CENT_SIGN_MASK: CellMask = table_mask('¢');
+const NOT_SIGN_MASK: CellMask = table_mask('¬');
+
+const fn build_ascii_lookup() -> [Option<CellMask>; 128] {
+    let mut table = [None; 128];
+    let mut i = 0;
+    while i < IBM029_TABLE.len() {
+        let (ch, bits) = IBM029_TABLE[i];
+        if ch.is_ascii() {
+            let mask = mask_from_bits(bits);
+            table[ch as usize] = Some(mask);
+            if ch.is_ascii_uppercase() {
+                table[ch.to_ascii_lowercase() as usize] = Some(mask);
+            }
+        }
+        i += 1;
+    }
+    table
+}
+
+const fn table_mask(ch: char) -> CellMask {
+    let mut i = 0;
+    while i < IBM029_TABLE.len() {
+        if IBM029_TABLE[i].0 == ch {
+            return mask_from_bits(IBM029_TABLE[i].1);
+        }
+        i += 1;
+    }
+    panic!("character missing from IBM029 table")
+}
+
+const fn mask_from_bits(bits: &str) -> CellMask {
+    let bytes = bits.as_bytes();
+    assert!(
+        bytes.len() == ROW_BIT_ORDER.len(),
         "IBM029 bit strings must have 12 characters"
     );
     let mut value = 0u16;
-    for (idx, ch) in bits.chars().enumerate() {
-        match ch {
-            '0' => {}
-            '1' => value |= 1u16 << ROW_BIT_ORDER[idx],
-            other => panic!("unexpected character '{}' in IBM029 table", other),
+    let mut idx = 0;
+    while idx < bytes.len() {
+        match bytes[idx] {
+            b'0' => {}
+            b'1' => value |= 1u16 << ROW_BIT_ORDER[idx],
+            _ => panic!("unexpected character in IBM029 table"),
         }
+        idx += 1;
     }
     CellMask(value)
 }
@@ -185,4 +472,76 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn lowercase_and_non_ascii_lookups() {
+        assert_eq!(
+            IBM029.encode_char('q').unwrap(),
+            IBM029.encode_char('Q').unwrap()
+        );
+        // ¢ is 12-2-8.
+        assert_eq!(
+            IBM029.encode_char('¢').unwrap(),
+            CellMask((1 << 11) | (1 << 2) | (1 << 8))
+        );
+        assert!(IBM029.encode_char('~').is_err());
+        assert!(IBM029.encode_char('é').is_err());
+    }
+
+    #[test]
+    fn ibm026_charts_differ_from_029_in_specials_only() {
+        for ch in "ABZ09 -/".chars() {
+            assert_eq!(
+                IBM026.encode_char(ch).unwrap(),
+                IBM029.encode_char(ch).unwrap()
+            );
+        }
+        let eq = IBM026_FORTRAN.encode_char('=').unwrap();
+        assert_eq!(eq, CellMask::from_rows(&[3, 8]).unwrap());
+        assert_eq!(IBM026.decode(eq), Some('#'));
+        assert_eq!(IBM029.decode(eq), Some('#'));
+        assert_eq!(
+            IBM026_FORTRAN.encode_char('+').unwrap(),
+            IBM026.encode_char('&').unwrap()
+        );
+        assert_eq!(
+            IBM026_FORTRAN.decode(CellMask::from_rows(&[12]).unwrap()),
+            Some('+')
+        );
+        assert!(IBM026.encode_char('=').is_err());
+        assert!(IBM026_FORTRAN.encode_char('&').is_err());
+        assert_eq!(IBM026.decode(IBM029.encode_char('q').unwrap()), Some('Q'));
+    }
+
+    #[test]
+    fn registry_resolves_names_and_encodings() {
+        assert_eq!(
+            EncoderRegistry::get("ibm026-fortran").unwrap().name(),
+            "IBM026-FORTRAN"
+        );
+        assert_eq!(EncoderRegistry::get("Ascii").unwrap().name(), "ASCII");
+        assert!(EncoderRegistry::get("ibm1401").is_err());
+        assert!(EncoderRegistry::get("missing.toml").is_err());
+        let ascii = EncoderRegistry::for_encoding(EncodingKind::Ascii);
+        assert_ne!(
+            ascii.encode_char('a').unwrap(),
+            ascii.encode_char('A').unwrap()
+        );
+        assert_eq!(
+            EncoderRegistry::for_encoding(EncodingKind::Hollerith).name(),
+            "IBM029"
+        );
+    }
+
+    #[test]
+    fn mapping_files_load_once() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("site.csv");
+        std::fs::write(&path, "A,12-1\n").unwrap();
+        let name = path.to_str().unwrap();
+        let first = EncoderRegistry::get(name).unwrap();
+        let second = EncoderRegistry::get(name).unwrap();
+        assert!(std::ptr::addr_eq(first, second));
+        assert_eq!(first.name(), "SITE");
+    }
 }
@@ -0,0 +1,618 @@
+//! Interchange formats understood by external emulators and archives.
+
+use anyhow::{Context, Result, anyhow};
+
+use crate::core::deck::{CardRecord, CardType, Deck, EncodingKind};
+use crate::core::ebcdic::{EBCDIC, byte_for_card_code, card_code};
+use crate::core::encoding::{CellMask, PunchEncoding, ROW_NAMES};
+
+/// Plain uppercase 80-column text for keypunch emulators that accept pasted
+/// or uploaded text.
+///
+/// This is not any particular emulator's native deck format: none of the
+/// browser keypunches publishes one, so only their common text input is
+/// covered, and it comes back in through `text80`. Trailing blanks are
+/// trimmed and every character is checked against `encoder` so the emulator
+/// never receives a character it cannot punch.
+pub fn to_keypunch_text(deck: &Deck, encoder: &dyn PunchEncoding) -> Result<String> {
+    let mut out = String::new();
+    for (idx, line) in deck.as_text()?.iter().enumerate() {
+        let upper = line.trim_end().to_uppercase();
+        if let Some((col, ch)) = upper
+            .chars()
+            .enumerate()
+            .find(|(_, ch)| !encoder.is_supported(*ch))
+        {
+            return Err(anyhow!(
+                "card {} column {}: '{}' has no {} punch code",
+                idx + 1,
+                col + 1,
+                ch,
+                encoder.name()
+            ));
+        }
+        out.push_str(&upper);
+        out.push('\n');
+    }
+    Ok(out)
+}
+
+/// Serialize column hole patterns as the `CardRecord::punches` string:
+/// three hex digits (the 12-bit mask) per column.
+pub fn punches_to_hex(columns: &[CellMask]) -> String {
+    columns.iter().map(|m| format!("{:03x}", m.0)).collect()
+}
+
+/// Parse a `CardRecord::punches` string back into at most 80 column masks.
+pub fn punches_from_hex(hex: &str) -> Result<Vec<CellMask>> {
+    let hex = hex.trim();
+    if let Some(pos) = hex.find(|ch: char| !ch.is_ascii_hexdigit()) {
+        let ch = hex[pos..].chars().next().expect("found at pos");
+        return Err(anyhow!("column {}: '{}' is not hex", pos / 3 + 1, ch));
+    }
+    if !hex.len().is_multiple_of(3) || hex.len() > 3 * 80 {
+        return Err(anyhow!(
+            "expected up to 80 groups of 3 hex digits, got {} characters",
+            hex.len()
+        ));
+    }
+    (0..hex.len())
+        .step_by(3)
+        .map(|i| {
+            let group = &hex[i..i + 3];
+            let value = u16::from_str_radix(group, 16)
+                .map_err(|_| anyhow!("column {}: '{}' is not hex", i / 3 + 1, group))?;
+            if value >> 12 != 0 {
+                return Err(anyhow!(
+                    "column {}: {:#05x} sets bits beyond the 12 card rows",
+                    i / 3 + 1,
+                    value
+                ));
+            }
+            Ok(CellMask(value))
+        })
+        .collect()
+}
+
+/// Parse a raw punch specification such as `"1:12-1 2:11-3-8"` into 80
+/// column masks. Each whitespace-separated item is a 1-based column, a colon
+/// and the rows punched there (12, 11, 0–9) joined by `-`; columns not named
+/// stay blank.
+pub fn parse_punch_spec(spec: &str) -> Result<Vec<CellMask>> {
+    let mut columns = vec![CellMask(0); 80];
+    let mut seen = [false; 80];
+    let items: Vec<&str> = spec.split_whitespace().collect();
+    if items.is_empty() {
+        return Err(anyhow!("punch specification is empty"));
+    }
+    for item in items {
+        let (col, rows) = item
+            .split_once(':')
+            .ok_or_else(|| anyhow!("'{}': expected COLUMN:ROWS, e.g. 1:12-1", item))?;
+        let col: usize = col
+            .parse()
+            .ok()
+            .filter(|col| (1..=80).contains(col))
+            .ok_or_else(|| anyhow!("'{}': column must be 1-80", item))?;
+        if std::mem::replace(&mut seen[col - 1], true) {
+            return Err(anyhow!("column {} is given more than once", col));
+        }
+        let rows = CellMask::from_row_spec(rows)
+            .filter(|_| !rows.eq_ignore_ascii_case("blank"))
+            .ok_or_else(|| anyhow!("'{}': rows must be 12, 11 or 0-9 joined by '-'", item))?;
+        columns[col - 1] = rows;
+    }
+    Ok(columns)
+}
+
+/// Data words carried by an IBM 1130 binary card (72 columns × 12 bits = 54 × 16 bits).
+pub const IBM1130_WORDS_PER_CARD: usize = 54;
+/// Columns holding packed 1130 data; 73–80 stay free for identification.
+pub const IBM1130_DATA_COLUMNS: usize = 72;
+
+/// Column image as the 1130 reads it: rows 12, 11, 0–9 left-justified in a
+/// 16-bit word (row 12 = `0x8000`, row 9 = `0x0010`).
+pub fn column_to_1130_word(mask: CellMask) -> u16 {
+    ROW_NAMES
+        .iter()
+        .enumerate()
+        .filter(|(_, row)| mask.rows().contains(row))
+        .fold(0u16, |word, (pos, _)| word | (0x8000 >> pos))
+}
+
+/// Inverse of [`column_to_1130_word`]; the low four bits are ignored.
+pub fn column_from_1130_word(word: u16) -> CellMask {
+    let rows: Vec<u8> = ROW_NAMES
+        .iter()
+        .enumerate()
+        .filter(|(pos, _)| word & (0x8000 >> pos) != 0)
+        .map(|(_, row)| *row)
+        .collect();
+    CellMask::from_rows(&rows).expect("ROW_NAMES are valid rows")
+}
+
+/// Pack up to 54 data words into columns 1–72, treating the card as one
+/// continuous bit stream of 12-bit columns (the 1130 binary card format).
+pub fn pack_1130_words(words: &[u16]) -> Result<Vec<CellMask>> {
+    if words.len() > IBM1130_WORDS_PER_CARD {
+        return Err(anyhow!(
+            "{} words do not fit on one 1130 binary card (max {})",
+            words.len(),
+            IBM1130_WORDS_PER_CARD
+        ));
+    }
+    let mut padded = words.to_vec();
+    padded.resize(IBM1130_WORDS_PER_CARD, 0);
+    let mut columns = Vec::with_capacity(IBM1130_DATA_COLUMNS);
+    // Every 4 columns (48 bits) carry exactly 3 words.
+    for chunk in padded.chunks(3) {
+        let bits = (u64::from(chunk[0]) << 32) | (u64::from(chunk[1]) << 16) | u64::from(chunk[2]);
+        for shift in [36, 24, 12, 0] {
+            let value = ((bits >> shift) & 0xfff) as u16;
+            columns.push(column_from_1130_word(value << 4));
+        }
+    }
+    Ok(columns)
+}
+
+/// Unpack the 54 data words from columns 1–72; missing columns read as blank.
+pub fn unpack_1130_words(columns: &[CellMask]) -> Vec<u16> {
+    let value = |idx: usize| {
+        columns
+            .get(idx)
+            .map(|m| u64::from(column_to_1130_word(*m) >> 4))
+            .unwrap_or(0)
+    };
+    let mut words = Vec::with_capacity(IBM1130_WORDS_PER_CARD);
+    for group in 0..IBM1130_DATA_COLUMNS / 4 {
+        let base = group * 4;
+        let bits = (value(base) << 36)
+            | (value(base + 1) << 24)
+            | (value(base + 2) << 12)
+            | value(base + 3);
+        words.push((bits >> 32) as u16);
+        words.push((bits >> 16) as u16);
+        words.push(bits as u16);
+    }
+    words
+}
+
+/// Column-binary deck file used by 1130 emulators: 80 little-endian 16-bit
+/// column words per card, in the layout of [`column_to_1130_word`].
+pub fn to_1130_binary(deck: &Deck, encoder: &dyn PunchEncoding) -> Result<Vec<u8>> {
+    let mut out = Vec::with_capacity(deck.cards.len() * 160);
+    for (idx, record) in deck.cards.iter().enumerate() {
+        let punch = record
+            .to_punch_card(encoder)
+            .with_context(|| format!("card {}", idx + 1))?;
+        for mask in punch.columns() {
+            out.extend_from_slice(&column_to_1130_word(*mask).to_le_bytes());
+        }
+    }
+    Ok(out)
+}
+
+/// Read a file written by [`to_1130_binary`] into column-binary card records.
+pub fn from_1130_binary(bytes: &[u8], card_type: CardType) -> Result<Vec<CardRecord>> {
+    if !bytes.len().is_multiple_of(160) {
+        return Err(anyhow!(
+            "1130 binary decks are 160 bytes per card, got {} bytes",
+            bytes.len()
+        ));
+    }
+    Ok(bytes
+        .chunks(160)
+        .map(|card| {
+            let columns: Vec<CellMask> = card
+                .chunks(2)
+                .map(|pair| column_from_1130_word(u16::from_le_bytes([pair[0], pair[1]])))
+                .collect();
+            CardRecord::from_punches(&columns, card_type.clone())
+        })
+        .collect())
+}
+
+/// One line of hex data words per card (the listing form of 1130 binary decks).
+pub fn to_1130_word_lines(deck: &Deck, encoder: &dyn PunchEncoding) -> Result<String> {
+    let mut out = String::new();
+    for (idx, record) in deck.cards.iter().enumerate() {
+        let punch = record
+            .to_punch_card(encoder)
+            .with_context(|| format!("card {}", idx + 1))?;
+        let words: Vec<String> = unpack_1130_words(punch.columns())
+            .iter()
+            .map(|w| format!("{:04X}", w))
+            .collect();
+        out.push_str(&words.join(" "));
+        out.push('\n');
+    }
+    Ok(out)
+}
+
+/// Parse [`to_1130_word_lines`] output; short lines are padded with zero words.
+pub fn from_1130_word_lines(input: &str, card_type: CardType) -> Result<Vec<CardRecord>> {
+    input
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(idx, line)| {
+            let words = line
+                .split_whitespace()
+                .map(|w| {
+                    u16::from_str_radix(w, 16).map_err(|_| anyhow!("'{}' is not a hex word", w))
+                })
+                .collect::<Result<Vec<u16>>>()
+                .and_then(|words| pack_1130_words(&words))
+                .with_context(|| format!("line {}", idx + 1))?;
+            Ok(CardRecord::from_punches(&words, card_type.clone()))
+        })
+        .collect()
+}
+
+/// Text dump styles used by card archives: one card per line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CardDump {
+    /// Three hex digits per column (240 per card): each line is exactly a
+    /// [`CardRecord::punches`] string (see [`punches_to_hex`]).
+    Hex,
+    /// Twelve `0`/`1` characters per column (960 per card) in row order 12, 11, 0–9.
+    Bits,
+}
+
+impl CardDump {
+    fn chars_per_column(self) -> usize {
+        match self {
+            CardDump::Hex => 3,
+            CardDump::Bits => 12,
+        }
+    }
+
+    fn is_digit(self, ch: char) -> bool {
+        match self {
+            CardDump::Hex => ch.is_ascii_hexdigit(),
+            CardDump::Bits => ch == '0' || ch == '1',
+        }
+    }
+
+    fn digit_name(self) -> &'static str {
+        match self {
+            CardDump::Hex => "hex",
+            CardDump::Bits => "bit",
+        }
+    }
+}
+
+/// Dump every card in `style`, encoding text cards with `encoder`.
+pub fn to_card_dump(deck: &Deck, encoder: &dyn PunchEncoding, style: CardDump) -> Result<String> {
+    let mut out = String::with_capacity(deck.cards.len() * (80 * style.chars_per_column() + 1));
+    for (idx, record) in deck.cards.iter().enumerate() {
+        let punch = record
+            .to_punch_card(encoder)
+            .with_context(|| format!("card {}", idx + 1))?;
+        match style {
+            CardDump::Hex => out.push_str(&punches_to_hex(punch.columns())),
+            CardDump::Bits => {
+                for mask in punch.columns() {
+                    out.push_str(&format!("{:012b}", column_to_1130_word(*mask) >> 4));
+                }
+            }
+        }
+        out.push('\n');
+    }
+    Ok(out)
+}
+
+/// Parse a [`to_card_dump`] file into column-binary card records.
+///
+/// Lines may be shorter than 80 columns (missing columns are blank) but must
+/// hold whole columns; blank lines are skipped.
+pub fn from_card_dump(
+    input: &str,
+    style: CardDump,
+    card_type: CardType,
+) -> Result<Vec<CardRecord>> {
+    let width = style.chars_per_column();
+    input
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(idx, line)| {
+            let line = line.trim();
+            if let Some(pos) = line.find(|ch| !style.is_digit(ch)) {
+                let ch = line[pos..].chars().next().expect("found at pos");
+                return Err(anyhow!(
+                    "line {} column {}: '{}' is not a {} digit",
+                    idx + 1,
+                    pos / width + 1,
+                    ch,
+                    style.digit_name()
+                ));
+            }
+            if !line.len().is_multiple_of(width) || line.len() > 80 * width {
+                return Err(anyhow!(
+                    "line {}: expected up to 80 columns of {} characters, got {} characters",
+                    idx + 1,
+                    width,
+                    line.len()
+                ));
+            }
+            if style == CardDump::Hex {
+                let columns =
+                    punches_from_hex(line).with_context(|| format!("line {}", idx + 1))?;
+                return Ok(CardRecord::from_punches(&columns, card_type.clone()));
+            }
+            let columns = (0..line.len())
+                .step_by(width)
+                .map(|start| {
+                    let group = &line[start..start + width];
+                    u16::from_str_radix(group, 2)
+                        .ok()
+                        .filter(|value| value >> 12 == 0)
+                        .map(|value| column_from_1130_word(value << 4))
+                        .ok_or_else(|| {
+                            anyhow!(
+                                "line {} column {}: '{}' is not a 12-bit column",
+                                idx + 1,
+                                start / width + 1,
+                                group
+                            )
+                        })
+                })
+                .collect::<Result<Vec<CellMask>>>()?;
+            Ok(CardRecord::from_punches(&columns, card_type.clone()))
+        })
+        .collect()
+}
+
+/// Bytes per card in Hercules EBCDIC card-image (`.crd`) files.
+pub const HERCULES_CARD_BYTES: usize = 80;
+
+/// ASCII card file for the Hercules card reader (`ascii` mode): one line per
+/// card, trailing blanks trimmed. Each column must be the System/360 card
+/// code of a printable ASCII character.
+pub fn to_hercules_ascii(deck: &Deck, encoder: &dyn PunchEncoding) -> Result<String> {
+    let mut out = String::with_capacity(deck.cards.len() * 81);
+    for (idx, record) in deck.cards.iter().enumerate() {
+        let punch = record
+            .to_punch_card(encoder)
+            .with_context(|| format!("card {}", idx + 1))?;
+        let mut line = String::with_capacity(HERCULES_CARD_BYTES);
+        for (col, mask) in punch.columns().iter().enumerate() {
+            let ch = EBCDIC
+                .decode(*mask)
+                .filter(|ch| ch.is_ascii() && !ch.is_ascii_control())
+                .ok_or_else(|| {
+                    anyhow!(
+                        "card {} column {}: punches are not a printable ASCII character",
+                        idx + 1,
+                        col + 1
+                    )
+                })?;
+            line.push(ch);
+        }
+        out.push_str(line.trim_end_matches(' '));
+        out.push('\n');
+    }
+    Ok(out)
+}
+
+/// Read a Hercules ASCII card file. Lines may use CRLF endings and a trailing
+/// DOS end-of-file byte is ignored; cards keep their text as ASCII so the
+/// reader's EBCDIC translation gives back the same holes.
+pub fn from_hercules_ascii(input: &str, card_type: CardType) -> Result<Vec<CardRecord>> {
+    let input = input.strip_suffix('\u{1a}').unwrap_or(input);
+    input
+        .lines()
+        .enumerate()
+        .map(|(idx, line)| {
+            let line = line.strip_suffix('\r').unwrap_or(line);
+            if let Some(ch) = line
+                .chars()
+                .find(|ch| !ch.is_ascii() || ch.is_ascii_control())
+            {
+                return Err(anyhow!(
+                    "line {}: {:?} is not a printable ASCII character",
+                    idx + 1,
+                    ch
+                ));
+            }
+            CardRecord::from_text(line, EncodingKind::Ascii, card_type.clone())
+                .with_context(|| format!("line {}", idx + 1))
+        })
+        .collect()
+}
+
+/// Hercules EBCDIC card-image deck: [`HERCULES_CARD_BYTES`] bytes per card,
+/// each the byte whose System/360 card code is punched in that column.
+pub fn to_hercules_ebcdic(deck: &Deck, encoder: &dyn PunchEncoding) -> Result<Vec<u8>> {
+    let mut out = Vec::with_capacity(deck.cards.len() * HERCULES_CARD_BYTES);
+    for (idx, record) in deck.cards.iter().enumerate() {
+        let punch = record
+            .to_punch_card(encoder)
+            .with_context(|| format!("card {}", idx + 1))?;
+        for (col, mask) in punch.columns().iter().enumerate() {
+            let byte = byte_for_card_code(*mask).ok_or_else(|| {
+                anyhow!(
+                    "card {} column {}: punches are not a System/360 card code",
+                    idx + 1,
+                    col + 1
+                )
+            })?;
+            out.push(byte);
+        }
+    }
+    Ok(out)
+}
+
+/// Read a Hercules EBCDIC card-image deck. Cards whose bytes are all
+/// printable code page 037 characters keep EBCDIC text; any other card is
+/// stored as punches only.
+pub fn from_hercules_ebcdic(bytes: &[u8], card_type: CardType) -> Result<Vec<CardRecord>> {
+    if !bytes.len().is_multiple_of(HERCULES_CARD_BYTES) {
+        return Err(anyhow!(
+            "EBCDIC card images are {} bytes per card, got {} bytes \
+             (files written with short records need the reader's autopad option)",
+            HERCULES_CARD_BYTES,
+            bytes.len()
+        ));
+    }
+    bytes
+        .chunks(HERCULES_CARD_BYTES)
+        .map(|card| {
+            let columns: Vec<CellMask> = card.iter().map(|byte| card_code(*byte)).collect();
+            let text: Option<String> = columns
+                .iter()
+                .map(|mask| EBCDIC.decode(*mask).filter(|ch| !ch.is_control()))
+                .collect();
+            match text {
+                Some(text) => CardRecord::from_text(&text, EncodingKind::Ebcdic, card_type.clone()),
+                None => Ok(CardRecord::from_punches(&columns, card_type.clone())),
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::encoding::IBM029;
+
+    #[test]
+    fn punch_spec_sets_named_columns_only() {
+        let columns = parse_punch_spec("1:12-1  3:11-3-8 80:0").unwrap();
+        assert_eq!(columns.len(), 80);
+        assert_eq!(columns[0], CellMask::from_rows(&[12, 1]).unwrap());
+        assert_eq!(columns[1], CellMask(0));
+        assert_eq!(columns[2], CellMask::from_rows(&[11, 3, 8]).unwrap());
+        assert_eq!(columns[79], CellMask::from_rows(&[0]).unwrap());
+        assert!(parse_punch_spec("81:1").is_err());
+        assert!(parse_punch_spec("1:13").is_err());
+        assert!(parse_punch_spec("1:1 1:2").is_err());
+        assert!(parse_punch_spec("1-12").is_err());
+    }
+
+    #[test]
+    fn keypunch_text_round_trips_through_text80() {
+        use crate::core::deck::DeckHeader;
+
+        let deck = Deck::from_lines(
+            DeckHeader::new(None, None, Vec::new()),
+            ["      call exit", "", "X = (A+B)*2"],
+            EncodingKind::Hollerith,
+            CardType::Code,
+        )
+        .unwrap();
+        let text = to_keypunch_text(&deck, &IBM029).unwrap();
+        assert_eq!(text, "      CALL EXIT\n\nX = (A+B)*2\n");
+        let reimported = Deck::from_lines(
+            DeckHeader::new(None, None, Vec::new()),
+            text.lines(),
+            EncodingKind::Hollerith,
+            CardType::Code,
+        )
+        .unwrap();
+        assert_eq!(to_keypunch_text(&reimported, &IBM029).unwrap(), text);
+
+        let mut bad = deck.clone();
+        bad.cards[1] =
+            CardRecord::from_text("A{", EncodingKind::Hollerith, CardType::Code).unwrap();
+        let err = to_keypunch_text(&bad, &IBM029).unwrap_err();
+        assert!(err.to_string().starts_with("card 2 column 2"), "{}", err);
+    }
+
+    #[test]
+    fn hex_dump_lines_are_stored_punches() {
+        use crate::core::deck::DeckHeader;
+
+        let punched = CardRecord::from_punches(
+            &parse_punch_spec("1:12-1 2:12-11-0 80:0-8-2").unwrap(),
+            CardType::Data,
+        );
+        let text = CardRecord::from_text("A", EncodingKind::Hollerith, CardType::Code).unwrap();
+        let deck = Deck::from_records(
+            DeckHeader::new(None, None, Vec::new()),
+            [punched.clone(), text],
+        )
+        .unwrap();
+
+        let dump = to_card_dump(&deck, &IBM029, CardDump::Hex).unwrap();
+        let lines: Vec<&str> = dump.lines().collect();
+        assert_eq!(Some(lines[0]), punched.punches.as_deref());
+        // `A` is 12-1 in both the dump and `card add --punches 1:12-1`.
+        assert!(lines[0].starts_with("802"));
+        assert!(lines[1].starts_with("802"));
+
+        for style in [CardDump::Hex, CardDump::Bits] {
+            let dump = to_card_dump(&deck, &IBM029, style).unwrap();
+            let back = from_card_dump(&dump, style, CardType::Data).unwrap();
+            assert_eq!(back[0].punches, punched.punches, "{:?}", style);
+            assert_eq!(back[1].punches.as_deref(), Some(lines[1]), "{:?}", style);
+        }
+        assert!(from_card_dump("80G", CardDump::Hex, CardType::Data).is_err());
+    }
+
+    #[test]
+    fn non_ascii_dumps_are_errors_not_panics() {
+        let err = punches_from_hex("aa\u{e9}ab").unwrap_err();
+        assert_eq!(err.to_string(), "column 1: '\u{e9}' is not hex");
+        let err = from_card_dump("802aa\u{e9}ab\n", CardDump::Hex, CardType::Data).unwrap_err();
+        assert_eq!(
+            format!("{:#}", err),
+            "line 1 column 2: '\u{e9}' is not a hex digit"
+        );
+        let bits = format!("{}\u{e9}{}", "0".repeat(12), "1".repeat(11));
+        let err = from_card_dump(&bits, CardDump::Bits, CardType::Data).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "line 1 column 2: '\u{e9}' is not a bit digit"
+        );
+    }
+
+    #[test]
+    fn ibm1130_words_round_trip_through_72_columns() {
+        let words: Vec<u16> = (0..54u16)
+            .map(|i| i.wrapping_mul(0x1357) ^ 0xa5a5)
+            .collect();
+        let columns = pack_1130_words(&words).unwrap();
+        assert_eq!(columns.len(), IBM1130_DATA_COLUMNS);
+        assert_eq!(unpack_1130_words(&columns), words);
+        assert_eq!(
+            column_to_1130_word(CellMask::from_rows(&[12, 9]).unwrap()),
+            0x8010
+        );
+    }
+
+    #[test]
+    fn hercules_card_files_keep_the_holes() {
+        let deck = Deck::from_lines(
+            crate::core::deck::DeckHeader::new(None, None, Vec::new()),
+            ["//GO  EXEC PGM=IEFBR14", "  x = [1, 2]"],
+            EncodingKind::Ascii,
+            CardType::Code,
+        )
+        .unwrap();
+        let ascii = to_hercules_ascii(&deck, &IBM029).unwrap();
+        assert_eq!(ascii, "//GO  EXEC PGM=IEFBR14\n  x = [1, 2]\n");
+        let back = from_hercules_ascii(&ascii.replace('\n', "\r\n"), CardType::Code).unwrap();
+        assert_eq!(back, deck.cards);
+
+        let images = to_hercules_ebcdic(&deck, &IBM029).unwrap();
+        assert_eq!(images.len(), 160);
+        assert_eq!(&images[..2], &[0x61, 0x61]);
+        assert_eq!(images[79], 0x40);
+        let cards = from_hercules_ebcdic(&images, CardType::Code).unwrap();
+        assert_eq!(cards[0].encoding, EncodingKind::Ebcdic);
+        for (card, original) in cards.iter().zip(&deck.cards) {
+            assert_eq!(
+                card.to_punch_card(&IBM029).unwrap().columns(),
+                original.to_punch_card(&IBM029).unwrap().columns()
+            );
+        }
+        let mut binary = images[..80].to_vec();
+        binary[0] = 0x00;
+        let cards = from_hercules_ebcdic(&binary, CardType::Code).unwrap();
+        assert!(cards[0].text.is_none());
+        assert!(from_hercules_ebcdic(&images[..79], CardType::Code).is_err());
+        assert!(from_hercules_ascii("caf\u{e9}", CardType::Code).is_err());
+    }
+}
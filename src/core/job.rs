@@ -0,0 +1,190 @@
+//! Job deck assembly: JCL, program and data decks in submission order.
+
+use anyhow::Result;
+
+use crate::core::deck::{CardMeta, CardRecord, CardType, Deck, DeckHeader, EncodingKind};
+use crate::core::encoding::CellMask;
+
+/// Control cards that end a data stream, job step or job.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DelimiterKind {
+    /// End-of-file card: a 6-7-9 multipunch in column 1.
+    Eof,
+    /// End of job: the `//` null statement.
+    Eoj,
+    /// End of in-stream data: `/*`.
+    SlashAsterisk,
+}
+
+/// Hole pattern of the end-of-file card's first column.
+pub const EOF_MULTIPUNCH: [u8; 3] = [6, 7, 9];
+
+/// Build a correctly punched delimiter card of the given kind.
+///
+/// The EOF card has no character representation, so it is stored as raw
+/// punches; the others are ordinary text cards.
+pub fn delimiter_card(kind: DelimiterKind) -> CardRecord {
+    let mut card = match kind {
+        DelimiterKind::Eof => {
+            let mask = CellMask::from_rows(&EOF_MULTIPUNCH).expect("valid rows");
+            CardRecord::from_punches(&[mask], CardType::Separator)
+        }
+        DelimiterKind::Eoj => jcl_card("//", CardType::Separator),
+        DelimiterKind::SlashAsterisk => jcl_card("/*", CardType::Separator),
+    };
+    card.meta = CardMeta {
+        color: None,
+        note: Some(
+            match kind {
+                DelimiterKind::Eof => "end of file (6-7-9)",
+                DelimiterKind::Eoj => "end of job",
+                DelimiterKind::SlashAsterisk => "end of data",
+            }
+            .to_string(),
+        ),
+        ..CardMeta::default()
+    };
+    card
+}
+
+/// In-stream DD statements inserted when a section has none of its own.
+#[derive(Debug, Clone)]
+pub struct JobOptions {
+    /// Statement introducing the program source, e.g. `//FORT.SYSIN DD *`.
+    pub program_dd: String,
+    /// Statement introducing the data cards, e.g. `//GO.SYSIN DD *`.
+    pub data_dd: String,
+}
+
+impl Default for JobOptions {
+    fn default() -> Self {
+        Self {
+            program_dd: "//SYSIN DD *".to_string(),
+            data_dd: "//GO.SYSIN DD *".to_string(),
+        }
+    }
+}
+
+/// Concatenate `jcl`, `program` and `data` into one submittable deck.
+///
+/// Each in-stream section is introduced by a `DD *` statement (taken from the
+/// decks when present, otherwise from `options`) and closed with a `/*`
+/// delimiter; the job ends with a `//` null statement. Delimiters already at
+/// the end of a section are not duplicated.
+pub fn assemble_job(
+    jcl: &Deck,
+    program: Option<&Deck>,
+    data: Option<&Deck>,
+    options: &JobOptions,
+) -> Result<Deck> {
+    let mut header = DeckHeader::new(
+        jcl.header.language.clone(),
+        jcl.header.template.clone(),
+        Vec::new(),
+    );
+    header.canonical = jcl.header.canonical;
+    let mut job = Deck::new(header);
+
+    let mut jcl_cards = jcl.cards.clone();
+    while jcl_cards
+        .last()
+        .is_some_and(|c| is_null_statement(card_text(c)))
+    {
+        jcl_cards.pop();
+    }
+    job.cards.extend(jcl_cards);
+
+    for (section, dd) in [(program, &options.program_dd), (data, &options.data_dd)] {
+        let Some(section) = section else { continue };
+        let opens_itself = section
+            .cards
+            .first()
+            .is_some_and(|c| is_instream_dd(card_text(c)));
+        let opened = job
+            .cards
+            .last()
+            .is_some_and(|c| is_instream_dd(card_text(c)));
+        if !opens_itself && !opened {
+            job.cards.push(CardRecord::from_text(
+                dd.as_str(),
+                EncodingKind::Hollerith,
+                CardType::Jcl,
+            )?);
+        }
+        job.cards.extend(section.cards.iter().cloned());
+        if !job.cards.last().is_some_and(|c| is_delimiter(card_text(c))) {
+            job.cards.push(delimiter_card(DelimiterKind::SlashAsterisk));
+        }
+    }
+    job.cards.push(delimiter_card(DelimiterKind::Eoj));
+    Ok(job)
+}
+
+/// `true` for `//name DD *` and `//name DD DATA` statements.
+pub fn is_instream_dd(text: &str) -> bool {
+    if !text.starts_with("//") || text.starts_with("//*") {
+        return false;
+    }
+    let upper = text.to_ascii_uppercase();
+    let mut tokens = upper.split_whitespace();
+    tokens.any(|t| t == "DD")
+        && tokens
+            .next()
+            .is_some_and(|operand| operand.starts_with('*') || operand.starts_with("DATA"))
+}
+
+fn is_delimiter(text: &str) -> bool {
+    text.starts_with("/*") && !text.starts_with("/*$")
+}
+
+fn is_null_statement(text: &str) -> bool {
+    text.trim_end() == "//"
+}
+
+fn card_text(card: &CardRecord) -> &str {
+    card.text.as_deref().unwrap_or("")
+}
+
+fn jcl_card(text: &str, card_type: CardType) -> CardRecord {
+    CardRecord::from_text(text, EncodingKind::Hollerith, card_type).expect("short JCL text")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn deck(lines: &[&str]) -> Deck {
+        let mut deck = Deck::new(DeckHeader::new(None, None, Vec::new()));
+        for line in lines {
+            deck.cards.push(jcl_card(line, CardType::Code));
+        }
+        deck
+    }
+
+    #[test]
+    fn assemble_inserts_missing_dd_and_delimiters() {
+        let jcl = deck(&["//HELLO JOB", "//STEP1 EXEC FORTGCLG", "//FORT.SYSIN DD *"]);
+        let program = deck(&["      PRINT 10", "      END"]);
+        let data = deck(&["42"]);
+        let job = assemble_job(&jcl, Some(&program), Some(&data), &JobOptions::default()).unwrap();
+        let texts: Vec<&str> = job.cards.iter().map(|c| card_text(c).trim_end()).collect();
+        assert_eq!(
+            texts,
+            vec![
+                "//HELLO JOB",
+                "//STEP1 EXEC FORTGCLG",
+                "//FORT.SYSIN DD *",
+                "      PRINT 10",
+                "      END",
+                "/*",
+                "//GO.SYSIN DD *",
+                "42",
+                "/*",
+                "//",
+            ]
+        );
+        let eof = delimiter_card(DelimiterKind::Eof);
+        assert_eq!(eof.text, None);
+        assert_eq!(eof.punches.as_deref(), Some("2c0"));
+    }
+}
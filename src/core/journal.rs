@@ -0,0 +1,334 @@
+//! Undo/redo journal for deck files (`punch deck undo/redo`).
+//!
+//! Decks saved with [`save_journaled`] get a journal next to them (see
+//! [`journal_sidecar_path`]) so the last [`JOURNAL_DEPTH`] card additions,
+//! replacements, renumberings and merges can be stepped back and forth. The
+//! CLI saves this way; [`Deck::save`] on its own journals nothing. Each
+//! entry holds the line edits between two versions of the deck's JSONL
+//! rather than a copy of the deck. The journal of an encrypted deck is
+//! sealed with the deck's key like the deck itself.
+//!
+//! [`Deck::save`]: crate::core::deck::Deck::save
+
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result, anyhow, bail};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::core::crypt::{self, DeckKey};
+use crate::core::deck::Deck;
+use crate::core::diff::lcs_pairs;
+
+/// Saves kept for undo; older ones are dropped.
+pub const JOURNAL_DEPTH: usize = 20;
+
+/// Journal path for a deck: `prog.deck` keeps its journal in `prog.journal.json`.
+pub fn journal_sidecar_path(deck: &Path) -> PathBuf {
+    let mut path = deck.to_path_buf();
+    path.set_extension("journal.json");
+    path
+}
+
+/// One journaled save: how to turn the deck on one side of it into the deck
+/// on the other.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JournalEntry {
+    /// Audit action of the save.
+    pub action: String,
+    pub saved_at: DateTime<Utc>,
+    /// SHA-256 of the deck JSONL (plain, history included) the patch applies to.
+    pub applies_to: String,
+    pub patch: Vec<LineEdit>,
+}
+
+/// Lines `at..at + remove` of the patched text, replaced by `insert`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LineEdit {
+    pub at: usize,
+    pub remove: usize,
+    #[serde(default)]
+    pub insert: Vec<String>,
+}
+
+impl JournalEntry {
+    /// Entry turning the deck JSONL `from` back into `to`.
+    fn new(action: String, from: &str, to: &str) -> Self {
+        Self {
+            action,
+            saved_at: Utc::now(),
+            applies_to: digest(from),
+            patch: diff_lines(from, to),
+        }
+    }
+
+    /// The deck JSONL this entry leads to from `text`.
+    fn apply(&self, text: &str) -> Result<String> {
+        let lines: Vec<&str> = text.lines().collect();
+        let mut out: Vec<&str> = Vec::with_capacity(lines.len());
+        let mut cursor = 0;
+        for edit in &self.patch {
+            let end = edit.at + edit.remove;
+            if edit.at < cursor || end > lines.len() {
+                bail!("edit at line {} is out of order", edit.at + 1);
+            }
+            out.extend(&lines[cursor..edit.at]);
+            out.extend(edit.insert.iter().map(String::as_str));
+            cursor = end;
+        }
+        out.extend(&lines[cursor..]);
+        Ok(out.iter().map(|line| format!("{}\n", line)).collect())
+    }
+}
+
+/// Undo and redo stacks of one deck file, most recent last.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Journal {
+    #[serde(default)]
+    pub undo: Vec<JournalEntry>,
+    #[serde(default)]
+    pub redo: Vec<JournalEntry>,
+}
+
+impl Journal {
+    /// Read the journal of the deck at `deck`; a missing journal is empty.
+    pub fn load(deck: &Path, key: Option<&DeckKey>) -> Result<Self> {
+        let path = journal_sidecar_path(deck);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let mut raw =
+            std::fs::read(&path).with_context(|| format!("failed to read {}", path.display()))?;
+        if crypt::is_encrypted(&raw) {
+            let key = key.ok_or_else(|| anyhow!("{} is encrypted", path.display()))?;
+            raw = crypt::decrypt(&raw, key)?;
+        }
+        serde_json::from_slice(&raw).with_context(|| {
+            format!(
+                "undo journal {} is corrupt; delete it to start a new one",
+                path.display()
+            )
+        })
+    }
+
+    /// Write the journal next to `deck`, sealed under `key` when given.
+    pub fn save(&self, deck: &Path, key: Option<&DeckKey>) -> Result<()> {
+        let path = journal_sidecar_path(deck);
+        let mut body = serde_json::to_vec(self).context("failed to serialize journal")?;
+        if let Some(key) = key {
+            body = crypt::encrypt(&body, key)?;
+        }
+        std::fs::write(&path, body).with_context(|| format!("failed to write {}", path.display()))
+    }
+}
+
+/// [`Deck::save`] that journals the change so it can be undone. A new save
+/// empties the redo stack. Writing over a file that is not a deck, or
+/// creating one, starts the journal afresh.
+pub fn save_journaled(deck: &mut Deck, path: &Path) -> Result<()> {
+    let key = deck.encryption.clone();
+    save_rekeyed(deck, path, key.as_ref())
+}
+
+/// [`save_journaled`] for a deck whose key changed since it was loaded, as
+/// `deck encrypt` and `deck decrypt` do: the file on disk and its journal are
+/// read with `old_key`, and the journal is written back under the deck's
+/// new key (in plain text when it has none).
+pub fn save_rekeyed(deck: &mut Deck, path: &Path, old_key: Option<&DeckKey>) -> Result<()> {
+    let key = deck.encryption.clone();
+    let previous = match path.exists() {
+        true => Deck::load_with_key(path, old_key).ok(),
+        false => None,
+    };
+    let Some(previous) = previous else {
+        deck.save(path)?;
+        return clear(path);
+    };
+    // A corrupt journal stops the save before the deck is touched.
+    let mut journal = Journal::load(path, old_key)?;
+    deck.save(path)?;
+    let saved = Deck::load_with_key(path, key.as_ref())?.to_jsonl()?;
+    let action = deck
+        .header
+        .history
+        .last()
+        .map(|event| event.action.clone())
+        .unwrap_or_else(|| "save".to_string());
+    journal
+        .undo
+        .push(JournalEntry::new(action, &saved, &previous.to_jsonl()?));
+    let excess = journal.undo.len().saturating_sub(JOURNAL_DEPTH);
+    journal.undo.drain(..excess);
+    journal.redo.clear();
+    journal.save(path, key.as_ref())
+}
+
+/// Restore the deck at `path` to its state before the last journaled save.
+/// Returns the action undone, or `None` when there is nothing to undo.
+pub fn undo(path: &Path) -> Result<Option<String>> {
+    step(
+        path,
+        |journal| &mut journal.undo,
+        |journal| &mut journal.redo,
+    )
+}
+
+/// Reapply the last undone save. Returns its action, or `None` when there is
+/// nothing to redo.
+pub fn redo(path: &Path) -> Result<Option<String>> {
+    step(
+        path,
+        |journal| &mut journal.redo,
+        |journal| &mut journal.undo,
+    )
+}
+
+fn step(
+    path: &Path,
+    from: fn(&mut Journal) -> &mut Vec<JournalEntry>,
+    to: fn(&mut Journal) -> &mut Vec<JournalEntry>,
+) -> Result<Option<String>> {
+    let current = Deck::load(path)?;
+    current.ensure_mutable()?;
+    let key = current.encryption.clone();
+    let mut journal = Journal::load(path, key.as_ref())?;
+    let Some(entry) = from(&mut journal).pop() else {
+        return Ok(None);
+    };
+    let text = current.to_jsonl()?;
+    if digest(&text) != entry.applies_to {
+        bail!(
+            "{} changed after '{}' was journaled; its undo journal no longer applies",
+            path.display(),
+            entry.action
+        );
+    }
+    let mut restored = entry
+        .apply(&text)
+        .and_then(|jsonl| Deck::from_jsonl(&jsonl))
+        .with_context(|| format!("journal entry for '{}' is corrupt", entry.action))?;
+    restored.encryption = key.clone();
+    restored.save(path)?;
+    let written = Deck::load_with_key(path, key.as_ref())?.to_jsonl()?;
+    to(&mut journal).push(JournalEntry::new(entry.action.clone(), &written, &text));
+    journal.save(path, key.as_ref())?;
+    Ok(Some(entry.action))
+}
+
+/// Delete the journal of the deck at `path`, if any.
+pub fn clear(path: &Path) -> Result<()> {
+    let journal = journal_sidecar_path(path);
+    if journal.exists() {
+        std::fs::remove_file(&journal)
+            .with_context(|| format!("failed to remove {}", journal.display()))?;
+    }
+    Ok(())
+}
+
+fn digest(text: &str) -> String {
+    format!("{:x}", Sha256::digest(text))
+}
+
+/// Line edits turning `from` into `to`.
+fn diff_lines(from: &str, to: &str) -> Vec<LineEdit> {
+    let (from, to): (Vec<&str>, Vec<&str>) = (from.lines().collect(), to.lines().collect());
+    let mut edits = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    for (next_i, next_j) in lcs_pairs(&from, &to)
+        .into_iter()
+        .chain([(from.len(), to.len())])
+    {
+        if next_i > i || next_j > j {
+            edits.push(LineEdit {
+                at: i,
+                remove: next_i - i,
+                insert: to[j..next_j].iter().map(|line| line.to_string()).collect(),
+            });
+        }
+        (i, j) = (next_i + 1, next_j + 1);
+    }
+    edits
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::deck::{CardRecord, CardType, DeckHeader, EncodingKind};
+
+    fn card(text: &str) -> CardRecord {
+        CardRecord::from_text(text, EncodingKind::Hollerith, CardType::Code).unwrap()
+    }
+
+    fn texts(path: &Path) -> Vec<String> {
+        Deck::load(path)
+            .unwrap()
+            .cards
+            .iter()
+            .map(|c| c.text.as_deref().unwrap().trim_end().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn undo_and_redo_step_through_saves() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("prog.deck");
+        let mut deck = Deck::new(DeckHeader::new(None, None, Vec::new()));
+        deck.append_card(card("A")).unwrap();
+        save_journaled(&mut deck, &path).unwrap();
+        assert_eq!(undo(&path).unwrap(), None);
+
+        deck.append_card(card("B")).unwrap();
+        deck.log_action("card add");
+        save_journaled(&mut deck, &path).unwrap();
+        deck.replace_card(0, card("Z")).unwrap();
+        deck.log_action("card replace 1");
+        save_journaled(&mut deck, &path).unwrap();
+        // Entries hold the changed lines only: the header and one card.
+        let journal = Journal::load(&path, None).unwrap();
+        let replace = &journal.undo[1].patch;
+        assert_eq!(replace.iter().map(|edit| edit.remove).sum::<usize>(), 2);
+
+        assert_eq!(undo(&path).unwrap().as_deref(), Some("card replace 1"));
+        assert_eq!(texts(&path), ["A", "B"]);
+        assert_eq!(undo(&path).unwrap().as_deref(), Some("card add"));
+        assert_eq!(texts(&path), ["A"]);
+        assert_eq!(redo(&path).unwrap().as_deref(), Some("card add"));
+        assert_eq!(texts(&path), ["A", "B"]);
+
+        let mut edited = Deck::load(&path).unwrap();
+        edited.append_card(card("C")).unwrap();
+        save_journaled(&mut edited, &path).unwrap();
+        assert_eq!(redo(&path).unwrap(), None);
+        assert_eq!(Journal::load(&path, None).unwrap().undo.len(), 2);
+
+        clear(&path).unwrap();
+        assert_eq!(undo(&path).unwrap(), None);
+    }
+
+    #[test]
+    fn plain_saves_leave_no_journal_and_stale_journals_are_refused() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("prog.deck");
+        let mut deck = Deck::new(DeckHeader::new(None, None, Vec::new()));
+        deck.append_card(card("A")).unwrap();
+        deck.save(&path).unwrap();
+        deck.append_card(card("B")).unwrap();
+        deck.save(&path).unwrap();
+        assert!(!journal_sidecar_path(&path).exists());
+
+        save_journaled(&mut deck, &path).unwrap();
+        deck.append_card(card("C")).unwrap();
+        save_journaled(&mut deck, &path).unwrap();
+        // Changed behind the journal's back.
+        deck.append_card(card("D")).unwrap();
+        deck.save(&path).unwrap();
+        let err = undo(&path).unwrap_err();
+        assert!(err.to_string().contains("no longer applies"), "{:#}", err);
+
+        std::fs::write(journal_sidecar_path(&path), "{not json").unwrap();
+        let err = save_journaled(&mut deck, &path).unwrap_err();
+        assert!(err.to_string().contains("is corrupt"), "{:#}", err);
+        assert_eq!(texts(&path), ["A", "B", "C", "D"]);
+    }
+}
@@ -0,0 +1,408 @@
+//! IBM 029 keypunch emulation (`punch keypunch`).
+//!
+//! An 029 read its field layout from a program card wrapped around a drum
+//! behind the keyboard. Each column of the program card controls the same
+//! column of the card being punched:
+//!
+//! | Punch | Meaning                                              |
+//! |-------|------------------------------------------------------|
+//! | 12    | field definition: the column continues the field     |
+//! | 11    | start of an automatic-skip field                     |
+//! | 0     | start of an automatic-duplication field              |
+//! | 1     | alphabetic shift (otherwise the keyboard is numeric) |
+//!
+//! A column without a 12 punch starts a field, so a program card is written
+//! as plain text: `1AAAA` is a five-column alphabetic field, `-&&&` a
+//! four-column numeric skip field and `/AA` a three-column alphabetic
+//! duplication field. [`DrumCard`] reads that notation (or derives it from a
+//! [`Template`]) and [`Keypunch`] keys cards under it.
+
+use std::fmt;
+
+use anyhow::{Result, anyhow};
+
+use crate::core::deck::ColumnRange;
+use crate::core::templates::{FieldKind, Template};
+
+const COLS: usize = 80;
+
+/// What the punch does when it reaches a field.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DrumAction {
+    /// Stop for the operator.
+    Manual,
+    /// Pass the field over, leaving it blank.
+    Skip,
+    /// Copy the field from the previous card; on the first card the
+    /// operator keys it.
+    Duplicate,
+}
+
+impl fmt::Display for DrumAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(match self {
+            DrumAction::Manual => "manual",
+            DrumAction::Skip => "skip",
+            DrumAction::Duplicate => "dup",
+        })
+    }
+}
+
+/// One field of a program card.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DrumField {
+    pub range: ColumnRange,
+    pub action: DrumAction,
+    /// Alphabetic shift; numeric fields only take digits, blanks and
+    /// the special characters.
+    pub alpha: bool,
+}
+
+/// A program card: the field layout an 029 keys cards under.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DrumCard {
+    pub fields: Vec<DrumField>,
+}
+
+impl DrumCard {
+    /// A drum card without fields: every column manual and alphabetic, as
+    /// on an 029 with the program drum disengaged.
+    pub fn free() -> Self {
+        Self {
+            fields: vec![DrumField {
+                range: ColumnRange {
+                    start: 1,
+                    end: COLS,
+                },
+                action: DrumAction::Manual,
+                alpha: true,
+            }],
+        }
+    }
+
+    /// Read a program card punched as text (see the module docs). Columns
+    /// past the end of `text` continue the last field.
+    pub fn parse(text: &str) -> Result<Self> {
+        let mut fields: Vec<DrumField> = Vec::new();
+        for (idx, ch) in text.chars().take(COLS).enumerate() {
+            let col = idx + 1;
+            let (starts, action, alpha) = match ch {
+                ' ' => (true, DrumAction::Manual, false),
+                '1' => (true, DrumAction::Manual, true),
+                '-' => (true, DrumAction::Skip, false),
+                'J' => (true, DrumAction::Skip, true),
+                '0' => (true, DrumAction::Duplicate, false),
+                '/' => (true, DrumAction::Duplicate, true),
+                '&' => (false, DrumAction::Manual, false),
+                'A' => (false, DrumAction::Manual, true),
+                other => {
+                    return Err(anyhow!(
+                        "program card column {}: {:?} is not a drum code (one of ' 1-J0/&A')",
+                        col,
+                        other
+                    ));
+                }
+            };
+            match fields.last_mut() {
+                // The shift is per column on a real drum; here a field
+                // takes the shift of its first column.
+                Some(field) if !starts => field.range.end = col,
+                _ => fields.push(DrumField {
+                    range: ColumnRange {
+                        start: col,
+                        end: col,
+                    },
+                    action,
+                    alpha,
+                }),
+            }
+        }
+        match fields.last_mut() {
+            Some(last) => last.range.end = COLS,
+            None => return Ok(Self::free()),
+        }
+        Ok(Self { fields })
+    }
+
+    /// Program card for a template: one field per zone, alphabetic unless
+    /// the zone is numeric. Sequence fields (`seq`) are skipped, to be
+    /// numbered later with `punch seq`, and identification fields (`ident`)
+    /// duplicated from card to card.
+    pub fn from_template(template: &Template) -> Self {
+        let mut fields: Vec<DrumField> = template
+            .columns
+            .iter()
+            .map(|zone| DrumField {
+                range: zone.range,
+                action: match zone.name {
+                    "seq" => DrumAction::Skip,
+                    "ident" => DrumAction::Duplicate,
+                    _ => DrumAction::Manual,
+                },
+                alpha: zone.kind != FieldKind::SignedNumeric,
+            })
+            .collect();
+        fields.sort_by_key(|field| field.range.start);
+        // Columns no zone covers are left to the operator.
+        let mut col = 1;
+        let mut filled = Vec::with_capacity(fields.len());
+        for field in fields {
+            if field.range.start > col {
+                filled.push(DrumField {
+                    range: ColumnRange {
+                        start: col,
+                        end: field.range.start - 1,
+                    },
+                    action: DrumAction::Manual,
+                    alpha: true,
+                });
+            }
+            col = field.range.end + 1;
+            filled.push(field);
+        }
+        if col <= COLS {
+            filled.push(DrumField {
+                range: ColumnRange {
+                    start: col,
+                    end: COLS,
+                },
+                action: DrumAction::Manual,
+                alpha: true,
+            });
+        }
+        Self { fields: filled }
+    }
+
+    /// The program card as text, the inverse of [`DrumCard::parse`].
+    pub fn text(&self) -> String {
+        let mut out = String::with_capacity(COLS);
+        for field in &self.fields {
+            out.push(match (field.action, field.alpha) {
+                (DrumAction::Manual, false) => ' ',
+                (DrumAction::Manual, true) => '1',
+                (DrumAction::Skip, false) => '-',
+                (DrumAction::Skip, true) => 'J',
+                (DrumAction::Duplicate, false) => '0',
+                (DrumAction::Duplicate, true) => '/',
+            });
+            let rest = if field.alpha { 'A' } else { '&' };
+            out.extend(std::iter::repeat_n(
+                rest,
+                field.range.end - field.range.start,
+            ));
+        }
+        out
+    }
+
+    /// The field holding the 1-based column `col`.
+    pub fn field_at(&self, col: usize) -> Option<&DrumField> {
+        self.fields
+            .iter()
+            .find(|field| field.range.start <= col && col <= field.range.end)
+    }
+}
+
+/// An 029 keying cards under a [`DrumCard`] with auto-feed on: the card in
+/// the punch station is ejected after column 80 and the next one fed, and
+/// fields are skipped or duplicated as the punch reaches them.
+#[derive(Debug, Clone)]
+pub struct Keypunch {
+    drum: DrumCard,
+    card: [char; COLS],
+    /// Next column to punch, 1-based; 81 once the card has run out.
+    column: usize,
+    /// No key has been pressed since the card was fed.
+    fresh: bool,
+    previous: Option<[char; COLS]>,
+    punched: Vec<String>,
+}
+
+impl Keypunch {
+    /// Feed the first card. Fails when the drum leaves nothing to key.
+    pub fn new(drum: DrumCard) -> Result<Self> {
+        if !drum
+            .fields
+            .iter()
+            .any(|field| field.action == DrumAction::Manual)
+        {
+            return Err(anyhow!("the program card has no manual field to key"));
+        }
+        let mut punch = Self {
+            drum,
+            card: [' '; COLS],
+            column: 1,
+            fresh: true,
+            previous: None,
+            punched: Vec::new(),
+        };
+        punch.feed();
+        Ok(punch)
+    }
+
+    pub fn drum(&self) -> &DrumCard {
+        &self.drum
+    }
+
+    /// Next column the punch will key, 1-based (81 when the card is full).
+    pub fn column(&self) -> usize {
+        self.column
+    }
+
+    /// Whether the card in the punch station is untouched since it was fed.
+    pub fn is_fresh(&self) -> bool {
+        self.fresh
+    }
+
+    /// Text of the cards ejected so far, in order.
+    pub fn cards(&self) -> &[String] {
+        &self.punched
+    }
+
+    /// Take the ejected cards, leaving the card in the punch station.
+    pub fn take_cards(&mut self) -> Vec<String> {
+        std::mem::take(&mut self.punched)
+    }
+
+    /// Punch `ch` in the current column. A character the current field's
+    /// shift cannot key is refused and nothing is punched.
+    pub fn key(&mut self, ch: char) -> Result<()> {
+        if self.column > COLS {
+            self.release();
+        }
+        let field = self
+            .drum
+            .field_at(self.column)
+            .expect("drum covers every column");
+        if !field.alpha && ch.is_ascii_alphabetic() {
+            return Err(anyhow!(
+                "column {} is in a numeric field ({}-{}); {:?} needs alphabetic shift",
+                self.column,
+                field.range.start,
+                field.range.end,
+                ch
+            ));
+        }
+        self.card[self.column - 1] = ch.to_ascii_uppercase();
+        self.fresh = false;
+        self.advance(self.column + 1);
+        Ok(())
+    }
+
+    /// The SKIP key: pass over the rest of the current field.
+    pub fn skip(&mut self) {
+        if self.column > COLS {
+            self.release();
+        }
+        let end = self
+            .drum
+            .field_at(self.column)
+            .expect("drum covers every column")
+            .range
+            .end;
+        self.fresh = false;
+        self.advance(end + 1);
+    }
+
+    /// The REL key: eject the card as punched so far and feed the next.
+    pub fn release(&mut self) {
+        let text: String = self.card.iter().collect();
+        self.punched.push(text.trim_end().to_string());
+        self.previous = Some(self.card);
+        self.feed();
+    }
+
+    /// Throw the card in the punch station away and feed a fresh one.
+    pub fn restart(&mut self) {
+        self.feed();
+    }
+
+    fn feed(&mut self) {
+        self.card = [' '; COLS];
+        self.fresh = true;
+        self.advance(1);
+    }
+
+    /// Move to `col`, running the skip and duplication fields the punch
+    /// reaches on the way to the next manual column, and eject a card that
+    /// was keyed past column 80.
+    fn advance(&mut self, col: usize) {
+        self.column = col;
+        while let Some(field) = self.drum.field_at(self.column) {
+            if field.range.start != self.column {
+                break;
+            }
+            match field.action {
+                DrumAction::Manual => break,
+                DrumAction::Skip => {}
+                DrumAction::Duplicate => {
+                    let Some(previous) = &self.previous else {
+                        break;
+                    };
+                    let cols = field.range.start - 1..field.range.end;
+                    self.card[cols.clone()].copy_from_slice(&previous[cols]);
+                }
+            }
+            self.column = field.range.end + 1;
+        }
+        if self.column > COLS && !self.fresh {
+            self.release();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::templates::TemplateRegistry;
+
+    #[test]
+    fn drum_skips_duplicates_and_feeds() {
+        // Columns 1-3 numeric, 4-6 duplicated, 7-9 skipped, 10-80 alphabetic.
+        let drum = DrumCard::parse(" &&0&&-&&1").unwrap();
+        assert_eq!(drum.fields.len(), 4);
+        assert_eq!(DrumCard::parse(&drum.text()).unwrap(), drum);
+
+        let mut punch = Keypunch::new(drum).unwrap();
+        punch.key('1').unwrap();
+        punch.key('2').unwrap();
+        assert!(punch.key('x').is_err());
+        // The first card stops in the duplication field for the operator.
+        for ch in "3456".chars() {
+            punch.key(ch).unwrap();
+        }
+        assert_eq!(punch.column(), 10);
+        for ch in "ABC".chars() {
+            punch.key(ch).unwrap();
+        }
+        punch.release();
+        punch.key('7').unwrap();
+        punch.skip();
+        assert_eq!(punch.column(), 10);
+        punch.key('D').unwrap();
+        punch.release();
+        assert_eq!(punch.cards(), ["123456   ABC", "7  456   D"]);
+
+        // Keying past column 80 ejects the card on its own.
+        let mut punch = Keypunch::new(DrumCard::free()).unwrap();
+        for _ in 0..81 {
+            punch.key('9').unwrap();
+        }
+        assert_eq!(punch.cards().len(), 1);
+        assert_eq!(punch.column(), 2);
+        assert!(!punch.is_fresh());
+
+        assert!(DrumCard::parse("1AA?").is_err());
+        assert!(Keypunch::new(DrumCard::parse("-").unwrap()).is_err());
+    }
+
+    #[test]
+    fn template_drums_skip_sequence_fields() {
+        let fortran = DrumCard::from_template(TemplateRegistry::get("fortran").unwrap());
+        let text = fortran.text();
+        assert_eq!(text.len(), 80);
+        assert!(text.starts_with("1AAAA11AA"));
+        assert!(text.ends_with("JAAAAAAA"));
+        let cobol = DrumCard::from_template(TemplateRegistry::get("cobol").unwrap());
+        assert_eq!(cobol.field_at(75).unwrap().action, DrumAction::Duplicate);
+    }
+}
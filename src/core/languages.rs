@@ -0,0 +1,526 @@
+//! Known deck languages, their default template and default checks.
+//!
+//! `deck init --language` validates against this registry and `deck info`
+//! runs the language's checks over the deck. Some checks also know a safe
+//! correction, which `deck validate --fix` applies.
+
+use std::fmt;
+
+use anyhow::{Result, anyhow};
+
+use crate::core::deck::{CardRecord, CardType, Deck, SEQUENCE_FIELD};
+use crate::core::diff::{ColumnChange, diff_columns};
+use crate::core::job::is_instream_dd;
+use crate::core::templates::{Template, TemplateRegistry};
+
+/// Card-level rule a language enables by default.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LanguageCheck {
+    /// No lowercase letters; keypunches had none.
+    Uppercase,
+    /// FORTRAN columns 1–5 hold a numeric label or are blank (col 1 `C`/`*` comments).
+    FortranLabel,
+    /// COBOL column 7 is blank or one of `*`, `/`, `-`, `D`.
+    CobolIndicator,
+    /// COBOL columns 1–6 hold a numeric sequence or are blank.
+    CobolSequence,
+    /// JCL cards start with `//` or `/*`.
+    JclStatement,
+    /// FORTRAN columns 73–80 hold a sequence number or are blank; anything
+    /// else there is usually a statement that ran past column 72.
+    FortranSequence,
+}
+
+/// Safe correction for a card that fails a [`LanguageCheck`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CheckFix {
+    /// Corrected 80-column card text.
+    pub text: String,
+    /// Continuation card to insert after the corrected card.
+    pub continuation: Option<String>,
+    pub message: String,
+}
+
+impl LanguageCheck {
+    /// Describe the problem with an 80-column card, or `None` when it passes.
+    pub fn check(self, text: &str) -> Option<String> {
+        let cols: Vec<char> = text.chars().collect();
+        let field = |start: usize, end: usize| -> String {
+            cols.get(start - 1..end.min(cols.len()))
+                .map(|c| c.iter().collect())
+                .unwrap_or_default()
+        };
+        let digits_or_blank = |s: &str| {
+            let s = s.trim();
+            s.is_empty() || s.chars().all(|c| c.is_ascii_digit())
+        };
+        match self {
+            LanguageCheck::Uppercase => cols
+                .iter()
+                .position(|c| c.is_lowercase())
+                .map(|idx| format!("lowercase '{}' in column {}", cols[idx], idx + 1)),
+            LanguageCheck::FortranLabel => {
+                let label = field(1, 5);
+                let comment = matches!(cols.first(), Some('C' | 'c' | '*'));
+                (!comment && !digits_or_blank(&label))
+                    .then(|| format!("label field '{}' is not numeric", label.trim()))
+            }
+            LanguageCheck::CobolIndicator => {
+                let ind = cols.get(6).copied().unwrap_or(' ');
+                (!matches!(ind, ' ' | '*' | '/' | '-' | 'D' | 'd'))
+                    .then(|| format!("invalid indicator '{}' in column 7", ind))
+            }
+            LanguageCheck::CobolSequence => {
+                let seq = field(1, 6);
+                (!digits_or_blank(&seq))
+                    .then(|| format!("sequence area '{}' is not numeric", seq.trim()))
+            }
+            LanguageCheck::JclStatement => {
+                let start = field(1, 2);
+                (start != "//" && start != "/*")
+                    .then(|| format!("statement starts with '{}' instead of //", start))
+            }
+            LanguageCheck::FortranSequence => {
+                let seq = field(SEQUENCE_FIELD.start, SEQUENCE_FIELD.end);
+                let comment = matches!(cols.first(), Some('C' | 'c' | '*'));
+                (!comment && !digits_or_blank(&seq))
+                    .then(|| format!("stray '{}' in sequence field", seq.trim()))
+            }
+        }
+    }
+
+    /// Correct a card this check rejects, when that can be done without
+    /// guessing. `None` leaves the problem for a human.
+    pub fn fix(self, text: &str) -> Option<CheckFix> {
+        self.check(text)?;
+        let mut cols: Vec<char> = text.chars().collect();
+        cols.resize(80, ' ');
+        let statement: String = cols[..72].iter().collect();
+        let card = |body: &str, tail: &[char]| -> Option<String> {
+            let body = body.trim_end();
+            (body.chars().count() <= 72)
+                .then(|| format!("{:<72}{}", body, tail.iter().collect::<String>()))
+        };
+        match self {
+            LanguageCheck::Uppercase => {
+                let mut changed = 0;
+                let text = cols
+                    .iter()
+                    .map(|&c| {
+                        let mut upper = c.to_uppercase();
+                        match (upper.next(), upper.next()) {
+                            (Some(u), None) if u != c => {
+                                changed += 1;
+                                u
+                            }
+                            _ => c,
+                        }
+                    })
+                    .collect();
+                Some(CheckFix {
+                    text,
+                    continuation: None,
+                    message: format!("uppercased {} character(s)", changed),
+                })
+            }
+            LanguageCheck::FortranLabel => {
+                // "10 X = 1" keyed from column 1: split off the label.
+                let trimmed = statement.trim_start();
+                let digits = trimmed.len()
+                    - trimmed
+                        .trim_start_matches(|c: char| c.is_ascii_digit())
+                        .len();
+                let rest = trimmed[digits..].trim_start();
+                if digits == 0 || digits > 5 || rest.is_empty() {
+                    return None;
+                }
+                let label = &trimmed[..digits];
+                Some(CheckFix {
+                    text: card(&format!("{:>5} {}", label, rest), &cols[72..])?,
+                    continuation: None,
+                    message: format!(
+                        "moved label {} to columns 1-5 and the statement to column 7",
+                        label
+                    ),
+                })
+            }
+            LanguageCheck::CobolSequence => {
+                // Source keyed from column 1 instead of area A.
+                if !cols[0].is_ascii_alphabetic() {
+                    return None;
+                }
+                Some(CheckFix {
+                    text: card(&format!("{:7}{}", "", statement), &cols[72..])?,
+                    continuation: None,
+                    message: "shifted source keyed from column 1 to area A (column 8)".to_string(),
+                })
+            }
+            LanguageCheck::FortranSequence => {
+                let stray: String = cols[72..].iter().collect::<String>().trim().to_string();
+                Some(CheckFix {
+                    text: card(&statement, &[' '; 8])?,
+                    continuation: Some(format!("{:5}1{}", "", stray)),
+                    message: format!(
+                        "moved '{}' from columns 73-80 to a continuation card",
+                        stray
+                    ),
+                })
+            }
+            LanguageCheck::CobolIndicator | LanguageCheck::JclStatement => None,
+        }
+    }
+}
+
+impl fmt::Display for LanguageCheck {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(match self {
+            LanguageCheck::Uppercase => "uppercase",
+            LanguageCheck::FortranLabel => "fortran-label",
+            LanguageCheck::CobolIndicator => "cobol-indicator",
+            LanguageCheck::CobolSequence => "cobol-sequence",
+            LanguageCheck::JclStatement => "jcl-statement",
+            LanguageCheck::FortranSequence => "fortran-sequence",
+        })
+    }
+}
+
+/// Problem reported by a [`LanguageCheck`] on one card.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CheckFinding {
+    /// Zero-based card index.
+    pub card: usize,
+    pub check: LanguageCheck,
+    pub message: String,
+}
+
+/// Correction applied by [`Language::fix_deck`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CardFix {
+    /// Zero-based index of the corrected card in the fixed deck.
+    pub card: usize,
+    pub check: LanguageCheck,
+    pub message: String,
+    pub changes: Vec<ColumnChange>,
+}
+
+/// A language a deck can be declared as.
+#[derive(Debug)]
+pub struct Language {
+    pub name: &'static str,
+    pub aliases: &'static [&'static str],
+    pub description: &'static str,
+    /// Name of the column template decks in this language default to.
+    pub template: &'static str,
+    pub checks: &'static [LanguageCheck],
+    /// Column (1-based) and the characters in it that mark a comment card.
+    pub comment: Option<(usize, &'static str)>,
+}
+
+impl Language {
+    /// The language's default column template.
+    pub fn default_template(&self) -> &'static Template {
+        TemplateRegistry::get(self.template).expect("language templates are built in")
+    }
+
+    /// Card type of one line of source: a comment when its comment column
+    /// says so, data when it fails one of the language's layout checks (case
+    /// is not a layout check), otherwise code.
+    pub fn classify(&self, text: &str) -> CardType {
+        if let Some((col, marks)) = self.comment
+            && text
+                .chars()
+                .nth(col - 1)
+                .is_some_and(|ch| marks.contains(ch))
+        {
+            return CardType::Comment;
+        }
+        let misfit = self
+            .checks
+            .iter()
+            .filter(|check| **check != LanguageCheck::Uppercase)
+            .any(|check| check.check(text).is_some());
+        if misfit {
+            CardType::Data
+        } else {
+            CardType::Code
+        }
+    }
+
+    /// Run the default checks over every source card (data and separator
+    /// cards are skipped, as are cards stored only as punches).
+    pub fn check_deck(&self, deck: &Deck) -> Vec<CheckFinding> {
+        let mut findings = Vec::new();
+        for (card, record) in deck.cards.iter().enumerate() {
+            if matches!(record.card_type, CardType::Data | CardType::Separator) {
+                continue;
+            }
+            let Some(text) = record.text.as_deref() else {
+                continue;
+            };
+            for check in self.checks {
+                if let Some(message) = check.check(text) {
+                    findings.push(CheckFinding {
+                        card,
+                        check: *check,
+                        message,
+                    });
+                }
+            }
+        }
+        findings
+    }
+
+    /// Apply every safe [`LanguageCheck::fix`] to the source cards that
+    /// [`Language::check_deck`] would inspect, inserting continuation cards
+    /// where a fix needs one. Findings without a safe fix are left alone.
+    pub fn fix_deck(&self, deck: &mut Deck) -> Result<Vec<CardFix>> {
+        let mut fixes = Vec::new();
+        let mut idx = 0;
+        while idx < deck.cards.len() {
+            let record = &deck.cards[idx];
+            let Some(original) = record.text.clone() else {
+                idx += 1;
+                continue;
+            };
+            if matches!(record.card_type, CardType::Data | CardType::Separator) {
+                idx += 1;
+                continue;
+            }
+            let mut text = original.clone();
+            let mut continuations = Vec::new();
+            for check in self.checks {
+                let Some(fix) = check.fix(&text) else {
+                    continue;
+                };
+                fixes.push(CardFix {
+                    card: idx,
+                    check: *check,
+                    message: fix.message,
+                    changes: diff_columns(&text, &fix.text),
+                });
+                text = fix.text;
+                continuations.extend(fix.continuation);
+            }
+            if text != original {
+                let mut card = deck.cards[idx].clone();
+                card.text = Some(text);
+                deck.replace_card(idx, card)?;
+            }
+            let (encoding, card_type) =
+                (deck.cards[idx].encoding, deck.cards[idx].card_type.clone());
+            for line in continuations {
+                idx += 1;
+                deck.insert_card(
+                    idx,
+                    CardRecord::from_text(line, encoding, card_type.clone())?,
+                )?;
+            }
+            idx += 1;
+        }
+        Ok(fixes)
+    }
+}
+
+/// Card types for lines of a deck read from plain text (`deck import
+/// --infer-types`).
+///
+/// `//*` lines are comments, other `//` and `/*` lines JCL. When the deck
+/// holds JCL, the first in-stream section (`DD *` or `DD DATA`) is taken as
+/// the program and later ones as data. Source lines are classified by
+/// `language` ([`Language::classify`]); without one they get `fallback`.
+pub fn infer_card_types<S: AsRef<str>>(
+    texts: &[S],
+    language: Option<&Language>,
+    fallback: CardType,
+) -> Vec<CardType> {
+    let mut sections = 0;
+    let mut in_stream = false;
+    texts
+        .iter()
+        .map(|text| {
+            let text = text.as_ref();
+            if text.starts_with("//*") {
+                CardType::Comment
+            } else if text.starts_with("//") || text.starts_with("/*") {
+                in_stream = is_instream_dd(text);
+                sections += usize::from(in_stream);
+                CardType::Jcl
+            } else if in_stream && sections > 1 {
+                CardType::Data
+            } else {
+                language.map_or(fallback.clone(), |language| language.classify(text))
+            }
+        })
+        .collect()
+}
+
+/// Registry of languages recognised by `--language`.
+pub struct LanguageRegistry;
+
+impl LanguageRegistry {
+    /// Return the set of known languages.
+    pub fn list() -> Vec<&'static Language> {
+        vec![&FORTRAN, &COBOL, &JCL, &ASSEMBLER]
+    }
+
+    /// Resolve a language by name or alias (case-insensitive).
+    pub fn get(name: &str) -> Result<&'static Language> {
+        Self::list()
+            .into_iter()
+            .find(|lang| {
+                lang.name.eq_ignore_ascii_case(name)
+                    || lang.aliases.iter().any(|a| a.eq_ignore_ascii_case(name))
+            })
+            .ok_or_else(|| {
+                let names: Vec<&str> = Self::list().iter().map(|l| l.name).collect();
+                anyhow!(
+                    "unknown language '{}' (expected {})",
+                    name,
+                    names.join(", ")
+                )
+            })
+    }
+}
+
+static FORTRAN: Language = Language {
+    name: "fortran",
+    aliases: &["fortran-iv", "fortran66"],
+    description: "FORTRAN IV fixed-form source",
+    template: "fortran",
+    checks: &[
+        LanguageCheck::Uppercase,
+        LanguageCheck::FortranLabel,
+        LanguageCheck::FortranSequence,
+    ],
+    comment: Some((1, "Cc*")),
+};
+
+static COBOL: Language = Language {
+    name: "cobol",
+    aliases: &["cobol-68"],
+    description: "COBOL fixed-form source",
+    template: "cobol",
+    checks: &[
+        LanguageCheck::Uppercase,
+        LanguageCheck::CobolSequence,
+        LanguageCheck::CobolIndicator,
+    ],
+    comment: Some((7, "*/")),
+};
+
+static JCL: Language = Language {
+    name: "jcl",
+    aliases: &["os-jcl"],
+    description: "OS/360 job control language",
+    template: "jcl",
+    checks: &[LanguageCheck::Uppercase, LanguageCheck::JclStatement],
+    comment: None,
+};
+
+static ASSEMBLER: Language = Language {
+    name: "assembler",
+    aliases: &["asm", "bal", "hlasm"],
+    description: "System/360 basic assembler language",
+    template: "assembler",
+    checks: &[LanguageCheck::Uppercase],
+    comment: Some((1, "*")),
+};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::deck::{DeckHeader, EncodingKind};
+
+    #[test]
+    fn registry_resolves_aliases_and_runs_checks() {
+        assert_eq!(LanguageRegistry::get("ASM").unwrap().name, "assembler");
+        assert!(LanguageRegistry::get("pascal").is_err());
+
+        let fortran = LanguageRegistry::get("fortran").unwrap();
+        assert_eq!(fortran.default_template().name, "fortran");
+        let deck = Deck::from_lines(
+            DeckHeader::new(None, None, Vec::new()),
+            ["C COMMENT", "   10 CONTINUE", "ABC   X = 1", "      y = 2"],
+            EncodingKind::Hollerith,
+            CardType::Code,
+        )
+        .unwrap();
+        let found: Vec<(usize, LanguageCheck)> = fortran
+            .check_deck(&deck)
+            .iter()
+            .map(|f| (f.card, f.check))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                (2, LanguageCheck::FortranLabel),
+                (3, LanguageCheck::Uppercase)
+            ]
+        );
+    }
+
+    #[test]
+    fn infer_card_types_follows_jcl_sections_and_language() {
+        use CardType::*;
+        let lines = [
+            "//PAYROLL JOB (ACCT)",
+            "//* RUN THE WEEKLY PAYROLL",
+            "//FORT.SYSIN DD *",
+            "C     COMPUTE GROSS PAY",
+            "   10 READ (5,100) HOURS",
+            "/*",
+            "//GO.SYSIN DD *",
+            "   40.0   12.50",
+            "/*",
+        ];
+        let fortran = LanguageRegistry::get("fortran").ok();
+        assert_eq!(
+            infer_card_types(&lines, fortran, Code),
+            [Jcl, Comment, Jcl, Comment, Code, Jcl, Jcl, Data, Jcl]
+        );
+        assert_eq!(
+            infer_card_types(&["SMITH JOHN  40.0", "C     TOTAL"], fortran, Code),
+            [Data, Comment]
+        );
+        let cobol = LanguageRegistry::get("cobol").ok();
+        assert_eq!(
+            infer_card_types(&["000100* NOTE", "000200 PROCEDURE DIVISION."], cobol, Code),
+            [Comment, Code]
+        );
+        assert_eq!(infer_card_types(&["ANYTHING"], None, Data), [Data]);
+    }
+
+    #[test]
+    fn fix_deck_applies_safe_corrections() {
+        let fortran = LanguageRegistry::get("fortran").unwrap();
+        let overflow = format!("      x = {:<62}ABC", "1");
+        let mut deck = Deck::from_lines(
+            DeckHeader::new(None, None, Vec::new()),
+            ["10 CONTINUE", overflow.as_str(), "ABC   Y = 2"],
+            EncodingKind::Hollerith,
+            CardType::Code,
+        )
+        .unwrap();
+        let fixes = fortran.fix_deck(&mut deck).unwrap();
+        let checks: Vec<(usize, LanguageCheck)> = fixes.iter().map(|f| (f.card, f.check)).collect();
+        assert_eq!(
+            checks,
+            vec![
+                (0, LanguageCheck::FortranLabel),
+                (1, LanguageCheck::Uppercase),
+                (1, LanguageCheck::FortranSequence)
+            ]
+        );
+        let text: Vec<String> = deck
+            .as_text()
+            .unwrap()
+            .iter()
+            .map(|l| l.trim_end().to_string())
+            .collect();
+        assert_eq!(text[0], "   10 CONTINUE");
+        assert_eq!(text[1], format!("      X = {}", "1"));
+        assert_eq!(text[2], "     1ABC");
+        assert_eq!(text[3], "ABC   Y = 2");
+        assert_eq!(fortran.check_deck(&deck).len(), 1);
+    }
+}
@@ -0,0 +1,50 @@
+//! Process-wide cache for charts and templates loaded from files.
+//!
+//! The registries hand out `&'static` references, so a file's contents are
+//! leaked when it is first loaded and again only after it changes on disk,
+//! rather than on every lookup.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, PoisonError};
+use std::time::SystemTime;
+
+use anyhow::Result;
+
+/// Values loaded from files, keyed by canonical path.
+pub(crate) struct FileCache<T: 'static> {
+    loaded: Mutex<Option<HashMap<PathBuf, (SystemTime, &'static T)>>>,
+}
+
+impl<T: Sync> FileCache<T> {
+    pub(crate) const fn new() -> Self {
+        Self {
+            loaded: Mutex::new(None),
+        }
+    }
+
+    /// The value loaded from `path`, calling `load` only when the file has not
+    /// been seen or was modified since.
+    pub(crate) fn get(
+        &self,
+        path: &Path,
+        load: impl FnOnce(&Path) -> Result<&'static T>,
+    ) -> Result<&'static T> {
+        let key = std::fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+        let modified = std::fs::metadata(&key)
+            .and_then(|meta| meta.modified())
+            .ok();
+        let mut loaded = self.loaded.lock().unwrap_or_else(PoisonError::into_inner);
+        let loaded = loaded.get_or_insert_with(HashMap::new);
+        if let (Some(modified), Some((stamp, value))) = (modified, loaded.get(&key))
+            && modified == *stamp
+        {
+            return Ok(value);
+        }
+        let value = load(path)?;
+        if let Some(modified) = modified {
+            loaded.insert(key, (modified, value));
+        }
+        Ok(value)
+    }
+}
@@ -0,0 +1,156 @@
+//! Target-machine rules for hole patterns a reader cannot translate.
+//!
+//! A deck bound for an emulated IBM 1401 or 1620 can record how that machine
+//! treats invalid hole combinations, so `deck validate` reports what the reader
+//! would actually do with the deck rather than a generic encoding check.
+
+use std::fmt;
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+use crate::core::deck::{Deck, InvalidPunch};
+use crate::core::encoding::PunchEncoding;
+
+/// Machine a deck is prepared for.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum TargetMachine {
+    Ibm1401,
+    Ibm1620,
+}
+
+impl TargetMachine {
+    /// Model number of the card reader attached to the machine.
+    pub fn reader(self) -> &'static str {
+        match self {
+            TargetMachine::Ibm1401 => "1402",
+            TargetMachine::Ibm1620 => "1622",
+        }
+    }
+}
+
+/// What the machine does when a column punches no valid character.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum InvalidCharMode {
+    /// Stop with a validity check; later cards are never read.
+    #[default]
+    Halt,
+    /// Read the column as the given character and carry on.
+    Substitute(char),
+}
+
+/// Per-deck target machine and its invalid-character behaviour.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub struct MachineRules {
+    pub target: TargetMachine,
+    #[serde(default)]
+    pub on_invalid: InvalidCharMode,
+}
+
+/// Result of feeding a deck through a target machine's reader.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MachineRead {
+    /// Cards read before the machine stopped (all of them if it never halted).
+    pub cards_read: usize,
+    /// The column that stopped the reader, in halt mode.
+    pub halted_at: Option<InvalidPunch>,
+    /// Columns read as the substitute character, in substitute mode.
+    pub substituted: Vec<InvalidPunch>,
+}
+
+impl MachineRules {
+    /// Feed `deck` through the reader, judging columns against `encoder`.
+    pub fn feed(&self, deck: &Deck, encoder: &dyn PunchEncoding) -> Result<MachineRead> {
+        let invalid = deck.invalid_punches(encoder)?;
+        Ok(match self.on_invalid {
+            InvalidCharMode::Halt => match invalid.first() {
+                Some(first) => MachineRead {
+                    cards_read: first.card,
+                    halted_at: Some(*first),
+                    substituted: Vec::new(),
+                },
+                None => MachineRead {
+                    cards_read: deck.cards.len(),
+                    ..MachineRead::default()
+                },
+            },
+            InvalidCharMode::Substitute(_) => MachineRead {
+                cards_read: deck.cards.len(),
+                halted_at: None,
+                substituted: invalid,
+            },
+        })
+    }
+}
+
+impl fmt::Display for TargetMachine {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TargetMachine::Ibm1401 => write!(f, "IBM 1401"),
+            TargetMachine::Ibm1620 => write!(f, "IBM 1620"),
+        }
+    }
+}
+
+impl fmt::Display for InvalidCharMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InvalidCharMode::Halt => write!(f, "halt"),
+            InvalidCharMode::Substitute(ch) => write!(f, "substitute '{}'", ch),
+        }
+    }
+}
+
+impl fmt::Display for MachineRules {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} ({} reader, {} on invalid characters)",
+            self.target,
+            self.target.reader(),
+            self.on_invalid
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::deck::{CardRecord, CardType, DeckHeader, EncodingKind};
+    use crate::core::encoding::{CellMask, IBM029};
+
+    fn deck_with_bad_column() -> Deck {
+        let header = DeckHeader::new(None, None, Vec::new());
+        let mut deck =
+            Deck::from_lines(header, ["A"], EncodingKind::Hollerith, CardType::Code).unwrap();
+        let bad = [CellMask(0xfff)];
+        deck.append_card(CardRecord::from_punches(&bad, CardType::Data))
+            .unwrap();
+        deck.append_card(CardRecord::from_punches(&bad, CardType::Data))
+            .unwrap();
+        deck
+    }
+
+    #[test]
+    fn halt_stops_at_first_invalid_column_and_substitute_reads_on() {
+        let deck = deck_with_bad_column();
+        let halt = MachineRules {
+            target: TargetMachine::Ibm1401,
+            on_invalid: InvalidCharMode::Halt,
+        };
+        let read = halt.feed(&deck, &IBM029).unwrap();
+        assert_eq!(read.cards_read, 1);
+        assert_eq!(read.halted_at.map(|p| (p.card, p.column)), Some((1, 1)));
+
+        let substitute = MachineRules {
+            target: TargetMachine::Ibm1620,
+            on_invalid: InvalidCharMode::Substitute('*'),
+        };
+        let read = substitute.feed(&deck, &IBM029).unwrap();
+        assert_eq!(read.cards_read, 3);
+        assert_eq!(read.substituted.len(), 2);
+        assert!(read.halted_at.is_none());
+    }
+}
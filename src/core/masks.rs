@@ -0,0 +1,216 @@
+//! Named mask sets loaded from TOML files (`--mask-file`).
+//!
+//! Each table in the file is one set. Ranges apply to every card; the
+//! optional `template`/`fields` pair masks named template fields and the
+//! `card_types` table adds ranges for cards of a given [`CardType`]:
+//!
+//! ```toml
+//! [sequence]
+//! ranges = ["73-80"]
+//!
+//! [cobol-listing]
+//! template = "cobol"
+//! fields = ["seq", "indicator"]
+//! card_types = { comment = ["8-72"] }
+//! ```
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use anyhow::{Context, Result, anyhow};
+use serde::Deserialize;
+
+use crate::core::deck::{CardType, ColumnRange, Deck};
+use crate::core::encoding::CellMask;
+use crate::core::formats::{punches_from_hex, punches_to_hex};
+use crate::core::templates::TemplateRegistry;
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct MaskSetSpec {
+    #[serde(default)]
+    ranges: Vec<String>,
+    #[serde(default)]
+    template: Option<String>,
+    #[serde(default)]
+    fields: Vec<String>,
+    #[serde(default)]
+    card_types: BTreeMap<String, Vec<String>>,
+}
+
+/// Column ranges to ignore, optionally refined per card type.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MaskSet {
+    pub name: String,
+    pub ranges: Vec<ColumnRange>,
+    pub by_type: Vec<(CardType, Vec<ColumnRange>)>,
+}
+
+impl MaskSet {
+    /// Build an anonymous set from plain ranges (e.g. repeated `--mask` flags).
+    pub fn from_ranges(ranges: Vec<ColumnRange>) -> Self {
+        Self {
+            name: String::new(),
+            ranges,
+            by_type: Vec::new(),
+        }
+    }
+
+    /// True when the set masks nothing at all.
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty() && self.by_type.is_empty()
+    }
+
+    /// Ranges that apply to a card of the given type.
+    pub fn ranges_for(&self, card_type: &CardType) -> Vec<ColumnRange> {
+        let mut ranges = self.ranges.clone();
+        for (kind, extra) in &self.by_type {
+            if kind == card_type {
+                ranges.extend_from_slice(extra);
+            }
+        }
+        ranges
+    }
+
+    /// Blank every masked column in place: spaces for text cards, no holes
+    /// for column-binary cards.
+    pub fn blank(&self, deck: &mut Deck) -> Result<()> {
+        for card in &mut deck.cards {
+            let ranges = self.ranges_for(&card.card_type);
+            let masked = |col: usize| ranges.iter().any(|r| r.contains(col));
+            if let Some(text) = card.text.as_mut() {
+                *text = text
+                    .chars()
+                    .enumerate()
+                    .map(|(idx, ch)| if masked(idx + 1) { ' ' } else { ch })
+                    .collect();
+            } else if let Some(hex) = card.punches.as_mut() {
+                let mut columns = punches_from_hex(hex)?;
+                for (idx, mask) in columns.iter_mut().enumerate() {
+                    if masked(idx + 1) {
+                        *mask = CellMask(0);
+                    }
+                }
+                *hex = punches_to_hex(&columns);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Collection of named mask sets parsed from a TOML file.
+#[derive(Debug, Clone, Default)]
+pub struct MaskFile {
+    pub sets: Vec<MaskSet>,
+}
+
+impl MaskFile {
+    /// Parse mask definitions from TOML text.
+    pub fn parse(text: &str) -> Result<Self> {
+        let specs: BTreeMap<String, MaskSetSpec> =
+            toml::from_str(text).context("invalid mask file")?;
+        let sets = specs
+            .into_iter()
+            .map(|(name, spec)| {
+                resolve_set(&name, spec).with_context(|| format!("mask set '{}'", name))
+            })
+            .collect::<Result<_>>()?;
+        Ok(Self { sets })
+    }
+
+    /// Read and parse a mask file from disk.
+    pub fn load(path: &Path) -> Result<Self> {
+        let text = std::fs::read_to_string(path)
+            .with_context(|| format!("failed to read mask file {}", path.display()))?;
+        Self::parse(&text).with_context(|| format!("failed to load {}", path.display()))
+    }
+
+    /// Select a set by name; without a name the file must define exactly one
+    /// set or one called `default`.
+    pub fn select(&self, name: Option<&str>) -> Result<&MaskSet> {
+        let wanted = match name {
+            Some(name) => name,
+            None if self.sets.len() == 1 => return Ok(&self.sets[0]),
+            None => "default",
+        };
+        self.sets.iter().find(|s| s.name == wanted).ok_or_else(|| {
+            let names: Vec<&str> = self.sets.iter().map(|s| s.name.as_str()).collect();
+            anyhow!("no mask set '{}' (available: {})", wanted, names.join(", "))
+        })
+    }
+}
+
+fn resolve_set(name: &str, spec: MaskSetSpec) -> Result<MaskSet> {
+    let mut ranges = parse_ranges(&spec.ranges)?;
+    if !spec.fields.is_empty() {
+        let template = spec
+            .template
+            .as_deref()
+            .ok_or_else(|| anyhow!("`fields` requires a `template`"))?;
+        let template = TemplateRegistry::get(template)?;
+        for field in &spec.fields {
+            ranges.push(template.field(field)?.range);
+        }
+    }
+    let by_type = spec
+        .card_types
+        .iter()
+        .map(|(kind, raw)| Ok((kind.parse::<CardType>()?, parse_ranges(raw)?)))
+        .collect::<Result<_>>()?;
+    Ok(MaskSet {
+        name: name.to_string(),
+        ranges,
+        by_type,
+    })
+}
+
+fn parse_ranges(raw: &[String]) -> Result<Vec<ColumnRange>> {
+    raw.iter().map(|r| r.parse()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::deck::{CardRecord, DeckHeader, EncodingKind};
+
+    const SAMPLE: &str = r#"
+[sequence]
+ranges = ["73-80"]
+
+[cobol]
+template = "cobol"
+fields = ["indicator"]
+card_types = { comment = ["8-72"] }
+"#;
+
+    #[test]
+    fn resolves_fields_and_card_types() {
+        let file = MaskFile::parse(SAMPLE).unwrap();
+        let cobol = file.select(Some("cobol")).unwrap();
+        assert_eq!(
+            cobol.ranges_for(&CardType::Code),
+            vec![ColumnRange { start: 7, end: 7 }]
+        );
+        assert_eq!(cobol.ranges_for(&CardType::Comment).len(), 2);
+        assert!(file.select(None).is_err());
+        assert!(MaskFile::parse("[x]\nfields = [\"seq\"]").is_err());
+    }
+
+    #[test]
+    fn blank_clears_masked_columns() {
+        let file = MaskFile::parse(SAMPLE).unwrap();
+        let mut deck = Deck::new(DeckHeader::new(None, None, Vec::new()));
+        let mut text = "X".repeat(72);
+        text.push_str("00000010");
+        deck.cards
+            .push(CardRecord::from_text(text, EncodingKind::Hollerith, CardType::Code).unwrap());
+        file.select(Some("sequence"))
+            .unwrap()
+            .blank(&mut deck)
+            .unwrap();
+        assert_eq!(
+            deck.cards[0].text.as_deref().unwrap().trim_end(),
+            "X".repeat(72)
+        );
+    }
+}
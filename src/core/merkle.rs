@@ -0,0 +1,146 @@
+//! Per-card hashes and the Merkle root behind [`Deck::hash`].
+//!
+//! Each card hashes on its own ([`Deck::card_hash`]: SHA-256 of the card's
+//! deck-file line, without the stored hash), and version 2 deck files store
+//! that hash on every card line. The card hashes are the leaves of a binary
+//! Merkle tree; an interior node is SHA-256 of `0x01`, its left child and
+//! its right child, and a node without a sibling moves up unchanged. Two
+//! decks with different roots can then be compared card hash by card hash
+//! to find exactly the cards that changed.
+
+use anyhow::{Result, anyhow};
+use sha2::digest::Output;
+use sha2::{Digest, Sha256};
+
+use crate::core::deck::{CardRecord, Deck, DeckLineRef};
+
+/// Prefix of interior nodes, so a node can never pass for a card line.
+const NODE_PREFIX: u8 = 0x01;
+
+/// SHA-256 of a card's deck-file line.
+pub(crate) fn card_digest(card: &CardRecord) -> serde_json::Result<Output<Sha256>> {
+    let line = serde_json::to_vec(&DeckLineRef::Card(card))?;
+    Ok(Sha256::digest(&line))
+}
+
+/// A card read from a line whose stored sha256 no longer matches it, most
+/// likely because the line was edited by hand. The card is loaded as read.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CardHashMismatch {
+    /// Zero-based card position.
+    pub card: usize,
+    /// One-based line of the deck file.
+    pub line: usize,
+    /// Hash stored on the line.
+    pub stored: String,
+    /// Hash of the card as read.
+    pub computed: String,
+}
+
+/// Root of the tree over `leaves`; an empty deck's root is the hash of
+/// nothing.
+pub fn merkle_root(leaves: &[Output<Sha256>]) -> Output<Sha256> {
+    if leaves.is_empty() {
+        return Sha256::digest([]);
+    }
+    let mut level = leaves.to_vec();
+    while level.len() > 1 {
+        level = level
+            .chunks(2)
+            .map(|pair| match pair {
+                [left, right] => {
+                    let mut hasher = Sha256::new();
+                    hasher.update([NODE_PREFIX]);
+                    hasher.update(left);
+                    hasher.update(right);
+                    hasher.finalize()
+                }
+                [single] => *single,
+                _ => unreachable!("chunks of two"),
+            })
+            .collect();
+    }
+    level[0]
+}
+
+impl Deck {
+    /// SHA-256 of card `index` (zero-based), as stored on its line in
+    /// version 2 deck files.
+    pub fn card_hash(&self, index: usize) -> Result<String> {
+        let card = self
+            .cards
+            .get(index)
+            .ok_or_else(|| anyhow!("card {} out of range 1..{}", index + 1, self.cards.len()))?;
+        Ok(format!("{:02x}", card_digest(card)?))
+    }
+
+    /// [`Deck::card_hash`] of every card, in order.
+    pub fn card_hashes(&self) -> Result<Vec<String>> {
+        (0..self.cards.len())
+            .map(|idx| self.card_hash(idx))
+            .collect()
+    }
+
+    /// Merkle root over the card hashes.
+    pub fn merkle_root(&self) -> Result<String> {
+        let leaves = self
+            .cards
+            .iter()
+            .map(card_digest)
+            .collect::<serde_json::Result<Vec<_>>>()?;
+        Ok(format!("{:02x}", merkle_root(&leaves)))
+    }
+
+    /// Zero-based positions whose card differs from `other`'s, including
+    /// positions only one of the decks reaches.
+    pub fn changed_cards(&self, other: &Deck) -> Result<Vec<usize>> {
+        let (ours, theirs) = (self.card_hashes()?, other.card_hashes()?);
+        Ok((0..ours.len().max(theirs.len()))
+            .filter(|&idx| ours.get(idx) != theirs.get(idx))
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::deck::{CardType, DeckHeader, EncodingKind};
+
+    #[test]
+    fn root_follows_card_hashes() {
+        let mut deck = Deck::from_lines(
+            DeckHeader::new(None, None, Vec::new()),
+            ["A", "B", "C"],
+            EncodingKind::Hollerith,
+            CardType::Code,
+        )
+        .unwrap();
+        let hashes = deck.card_hashes().unwrap();
+        assert_eq!(hashes.len(), 3);
+        assert_eq!(hashes[0].len(), 64);
+        assert!(deck.card_hash(3).is_err());
+
+        // Three leaves: the first two pair up, the third moves up alone.
+        let leaves: Vec<_> = deck.cards.iter().map(|c| card_digest(c).unwrap()).collect();
+        let mut hasher = Sha256::new();
+        hasher.update([NODE_PREFIX]);
+        hasher.update(leaves[0]);
+        hasher.update(leaves[1]);
+        let pair = hasher.finalize();
+        let mut hasher = Sha256::new();
+        hasher.update([NODE_PREFIX]);
+        hasher.update(pair);
+        hasher.update(leaves[2]);
+        assert_eq!(
+            deck.merkle_root().unwrap(),
+            format!("{:02x}", hasher.finalize())
+        );
+
+        let before = deck.clone();
+        deck.cards[1] =
+            CardRecord::from_text("X", EncodingKind::Hollerith, CardType::Code).unwrap();
+        deck.cards.push(deck.cards[0].clone());
+        assert_ne!(deck.merkle_root().unwrap(), before.merkle_root().unwrap());
+        assert_eq!(deck.changed_cards(&before).unwrap(), [1, 3]);
+    }
+}
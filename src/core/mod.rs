@@ -1,13 +1,100 @@
 //! Core domain primitives for punch card decks, encoding, and templates.
 
+pub mod baseline;
+pub mod captions;
+pub mod crypt;
 pub mod deck;
+pub mod diff;
+pub mod ebcdic;
+pub mod edit;
 pub mod encoding;
+pub mod formats;
+pub mod job;
+pub mod journal;
+pub mod keypunch;
+pub mod languages;
+pub(crate) mod loaded;
+pub mod machine;
+pub mod masks;
+pub mod merkle;
+pub mod notched;
+pub mod ocr;
+pub mod patches;
+pub mod pending;
+pub mod pipeline;
 pub mod punchcards;
+pub mod reader;
+pub mod recode;
+pub mod reconcile;
+pub mod redact;
+pub mod renumber;
+pub(crate) mod rng;
+pub mod search;
+pub mod shift;
+pub mod signing;
+pub mod simh;
+pub mod stats;
+pub mod strict;
+pub mod summary;
+pub mod table;
 pub mod templates;
+pub mod text;
+pub mod trainer;
+pub mod workspace;
 
+pub use baseline::{
+    Baseline, CardResult, DEFAULT_BASELINE, PASS_DIFF_LINES, PASS_HISTORY, Verification,
+    VerifyMismatch, VerifyPass, VerifyReport,
+};
+pub use captions::{Captions, Locale};
+pub use crypt::DeckKey;
 pub use deck::{
-    AuditEvent, CardMeta, CardRecord, CardType, ColumnRange, Deck, DeckHeader, EncodingKind,
+    AuditEvent, CardMeta, CardRecord, CardType, ColumnRange, Deck, DeckHeader, DeckState,
+    EncodingKind, InvalidPunch, SEQUENCE_FIELD, audit_sidecar_path, sequence_field,
+};
+pub use diff::{
+    AlignedCard, CardDiff, ColumnChange, HeaderMerge, MergeOutcome, PunchMismatch, align_cards,
+    diff_cards, diff_columns, diff_punches, lcs_pairs, merge3, merge3_header,
+};
+pub use ebcdic::{ASCII, AsciiCardEncoder, EBCDIC, EbcdicEncoder, byte_for_card_code, card_code};
+pub use edit::{EDIT_NEW_CARD, EditSummary};
+pub use encoding::{
+    CellMask, EncodeError, EncoderRegistry, IBM026, IBM026_FORTRAN, IBM029, Ibm026Chart,
+    Ibm026Encoder, Ibm029Encoder, PunchEncoding, ROW_NAMES, VALID_SET, ValidChar,
+};
+pub use job::{
+    DelimiterKind, EOF_MULTIPUNCH, JobOptions, assemble_job, delimiter_card, is_instream_dd,
 };
-pub use encoding::{Ibm029Encoder, PunchEncoding, ValidChar};
+pub use journal::{
+    JOURNAL_DEPTH, Journal, JournalEntry, LineEdit, journal_sidecar_path, save_journaled,
+    save_rekeyed,
+};
+pub use keypunch::{DrumAction, DrumCard, DrumField, Keypunch};
+pub use languages::{
+    CardFix, CheckFinding, CheckFix, Language, LanguageCheck, LanguageRegistry, infer_card_types,
+};
+pub use machine::{InvalidCharMode, MachineRead, MachineRules, TargetMachine};
+pub use masks::{MaskFile, MaskSet};
+pub use merkle::CardHashMismatch;
+pub use notched::{Keep, Needle, NeedleSort, NotchCoding, NotchField, NotchLayout, SortPass};
+pub use ocr::{OcrMismatch, is_confusable, ocr_cross_check};
+pub use patches::{FoldedPatch, PatchChain, PatchTarget};
+pub use pending::{PendingBatch, pending_sidecar_path};
 pub use punchcards::{CardDeck, PunchCard, RenderStyle};
-pub use templates::{Template, TemplateRegistry};
+pub use reader::{FaultInjection, InjectedFault, ReaderFault, ReaderRun};
+pub use recode::{EncodingConversion, Unmapped};
+pub use reconcile::{DisputedCard, Reconciliation, reconcile};
+pub use redact::{redact_card, scrub_history};
+pub use renumber::{RenumberEntry, RenumberPlan};
+pub use search::{CardMatch, Pattern, Query};
+pub use shift::{ShiftedBlock, detect_shifts, unshift_card};
+pub use signing::{DeckSignature, SignatureStatus};
+pub use stats::ColumnStats;
+pub use strict::{DeckLoadError, LoadLimits};
+pub use summary::{SummaryCard, SummaryCheck};
+pub use table::TableEncoder;
+pub use templates::{
+    FieldKind, FieldSpec, FieldValue, Template, TemplateColumn, TemplateRegistry, TemplateSpec,
+};
+pub use trainer::{Question, Quiz, QuizMode};
+pub use workspace::{DeckRole, WORKSPACE_FILE, Workspace, WorkspaceDeck};
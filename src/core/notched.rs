@@ -0,0 +1,508 @@
+//! Edge-notched (McBee / keysort) cards.
+//!
+//! An edge-notched card carries a row of holes around its border; a category
+//! is recorded by cutting the hole open to the edge. To select cards, an
+//! operator runs a needle through one hole position of the whole stack and
+//! lifts it: notched cards drop out, the rest stay on the needle.
+//!
+//! Cards live in an ordinary [`Deck`], one data card per notched card, whose
+//! text names the notches (`subject=physics,optics year=7 #40`). A
+//! [`NotchLayout`] maps those names onto hole numbers, counted clockwise from
+//! the top-left corner, and is either the built-in plain keysort card or a
+//! TOML file:
+//!
+//! ```toml
+//! name = "library"
+//! top = 20
+//! right = 8
+//! bottom = 20
+//! left = 8
+//!
+//! [[fields]]
+//! name = "subject"
+//! start = 1
+//! coding = "direct"
+//! values = ["physics", "chemistry", "optics"]
+//!
+//! [[fields]]
+//! name = "year"
+//! start = 4
+//! coding = "digit"
+//!
+//! [[fields]]
+//! name = "author"
+//! start = 8
+//! coding = "combination"
+//! holes = 9
+//! notches = 3
+//! values = ["abel", "babbage", "curie"]
+//! ```
+
+use std::path::Path;
+
+use anyhow::{Context, Result, anyhow};
+use serde::Deserialize;
+
+use crate::core::deck::Deck;
+
+/// Hole weights of a [`NotchCoding::Digit`] field, in hole order.
+const DIGIT_WEIGHTS: [u8; 4] = [7, 4, 2, 1];
+
+/// How a field's values are cut into its holes.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(tag = "coding", rename_all = "snake_case")]
+pub enum NotchCoding {
+    /// One hole per value; a card may carry several values.
+    Direct { values: Vec<String> },
+    /// A digit 0–9 in four holes weighted 7-4-2-1, at most two notched
+    /// (0 leaves all four intact).
+    Digit,
+    /// Each value notches its own set of `notches` out of `holes` holes, so
+    /// e.g. 3 of 9 holes tell 84 values apart.
+    Combination {
+        holes: usize,
+        notches: usize,
+        values: Vec<String>,
+    },
+}
+
+/// Named run of consecutive holes.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct NotchField {
+    pub name: String,
+    /// First hole, 1-based.
+    pub start: usize,
+    #[serde(flatten)]
+    pub coding: NotchCoding,
+}
+
+impl NotchField {
+    /// Holes the field occupies.
+    pub fn width(&self) -> usize {
+        match &self.coding {
+            NotchCoding::Direct { values } => values.len(),
+            NotchCoding::Digit => DIGIT_WEIGHTS.len(),
+            NotchCoding::Combination { holes, .. } => *holes,
+        }
+    }
+
+    /// Field-relative offsets notched for `value`.
+    fn offsets(&self, value: &str) -> Result<Vec<usize>> {
+        match &self.coding {
+            NotchCoding::Direct { values } => value
+                .split(',')
+                .map(|v| position(values, v.trim(), &self.name))
+                .collect(),
+            NotchCoding::Digit => {
+                let digit: u8 = value
+                    .parse()
+                    .ok()
+                    .filter(|d| *d <= 9)
+                    .ok_or_else(|| anyhow!("{}: '{}' is not a digit 0-9", self.name, value))?;
+                Ok(digit_offsets(digit))
+            }
+            NotchCoding::Combination {
+                holes,
+                notches,
+                values,
+            } => {
+                let idx = position(values, value, &self.name)?;
+                Ok(nth_combination(*holes, *notches, idx))
+            }
+        }
+    }
+}
+
+/// Hole geometry and field assignments of an edge-notched card.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct NotchLayout {
+    pub name: String,
+    /// Holes along each edge.
+    pub top: usize,
+    pub right: usize,
+    pub bottom: usize,
+    pub left: usize,
+    #[serde(default)]
+    pub fields: Vec<NotchField>,
+}
+
+/// Whether a needle pass keeps the cards that fell or those left on it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Keep {
+    Dropped,
+    Retained,
+}
+
+/// One needle pass of a selection.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Needle {
+    /// 1-based hole number.
+    pub hole: usize,
+    pub keep: Keep,
+}
+
+/// Needle pass as simulated on a deck.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SortPass {
+    pub needle: Needle,
+    pub dropped: usize,
+    pub retained: usize,
+}
+
+/// Outcome of [`NotchLayout::needle_sort`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NeedleSort {
+    pub passes: Vec<SortPass>,
+    /// Indices of the selected cards, in deck order.
+    pub selected: Vec<usize>,
+}
+
+impl Default for NotchLayout {
+    fn default() -> Self {
+        Self::keysort()
+    }
+}
+
+impl NotchLayout {
+    /// Plain 56-hole keysort card without named fields; notches are given
+    /// by hole number (`#12`).
+    pub fn keysort() -> Self {
+        Self {
+            name: "keysort".to_string(),
+            top: 20,
+            right: 8,
+            bottom: 20,
+            left: 8,
+            fields: Vec::new(),
+        }
+    }
+
+    /// Parse and check a TOML layout.
+    pub fn parse(text: &str) -> Result<Self> {
+        let layout: Self = toml::from_str(text).context("invalid notch layout")?;
+        layout.validate()?;
+        Ok(layout)
+    }
+
+    /// Read a TOML layout file.
+    pub fn load(path: &Path) -> Result<Self> {
+        let text = std::fs::read_to_string(path)
+            .with_context(|| format!("failed to read notch layout {}", path.display()))?;
+        Self::parse(&text).with_context(|| format!("failed to load {}", path.display()))
+    }
+
+    /// Holes around the whole border.
+    pub fn holes(&self) -> usize {
+        self.top + self.right + self.bottom + self.left
+    }
+
+    fn validate(&self) -> Result<()> {
+        let mut owner: Vec<Option<&str>> = vec![None; self.holes()];
+        for field in &self.fields {
+            if let NotchCoding::Combination {
+                holes,
+                notches,
+                values,
+            } = &field.coding
+            {
+                if *notches == 0 || notches > holes {
+                    return Err(anyhow!(
+                        "{}: cannot notch {} of {} holes",
+                        field.name,
+                        notches,
+                        holes
+                    ));
+                }
+                if values.len() > binomial(*holes, *notches) {
+                    return Err(anyhow!(
+                        "{}: {} values do not fit {} of {} holes",
+                        field.name,
+                        values.len(),
+                        notches,
+                        holes
+                    ));
+                }
+            }
+            let end = field.start + field.width();
+            if field.start == 0 || end - 1 > self.holes() {
+                return Err(anyhow!(
+                    "{}: holes {}-{} fall outside 1-{}",
+                    field.name,
+                    field.start,
+                    end - 1,
+                    self.holes()
+                ));
+            }
+            for hole in field.start..end {
+                if let Some(other) = owner[hole - 1].replace(&field.name) {
+                    return Err(anyhow!(
+                        "{}: hole {} is already used by {}",
+                        field.name,
+                        hole,
+                        other
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn field(&self, name: &str) -> Result<&NotchField> {
+        self.fields
+            .iter()
+            .find(|f| f.name == name)
+            .ok_or_else(|| anyhow!("layout '{}' has no field '{}'", self.name, name))
+    }
+
+    fn hole(&self, token: &str) -> Result<usize> {
+        token
+            .parse()
+            .ok()
+            .filter(|hole| (1..=self.holes()).contains(hole))
+            .ok_or_else(|| anyhow!("'#{}': hole must be 1-{}", token, self.holes()))
+    }
+
+    /// Sorted 1-based holes notched by a card spec such as
+    /// `subject=physics,optics year=7 #40`.
+    pub fn notches(&self, spec: &str) -> Result<Vec<usize>> {
+        let mut holes = Vec::new();
+        for item in spec.split_whitespace() {
+            if let Some(hole) = item.strip_prefix('#') {
+                holes.push(self.hole(hole)?);
+            } else {
+                let (name, value) = item
+                    .split_once('=')
+                    .ok_or_else(|| anyhow!("'{}': expected FIELD=VALUE or #HOLE", item))?;
+                let field = self.field(name)?;
+                holes.extend(field.offsets(value)?.iter().map(|o| field.start + o));
+            }
+        }
+        holes.sort_unstable();
+        holes.dedup();
+        Ok(holes)
+    }
+
+    /// Needle passes selecting the cards matching one `FIELD=VALUE` or
+    /// `#HOLE` term.
+    pub fn needles(&self, term: &str) -> Result<Vec<Needle>> {
+        if let Some(hole) = term.strip_prefix('#') {
+            return Ok(vec![Needle {
+                hole: self.hole(hole)?,
+                keep: Keep::Dropped,
+            }]);
+        }
+        let (name, value) = term
+            .split_once('=')
+            .ok_or_else(|| anyhow!("'{}': expected FIELD=VALUE or #HOLE", term))?;
+        let field = self.field(name)?;
+        let notched = field.offsets(value)?;
+        let needle = |offset: usize, keep| Needle {
+            hole: field.start + offset,
+            keep,
+        };
+        Ok(match field.coding {
+            NotchCoding::Direct { .. } => notched
+                .into_iter()
+                .map(|o| needle(o, Keep::Dropped))
+                .collect(),
+            // Exact codes also need the field's intact holes to stay intact.
+            NotchCoding::Digit | NotchCoding::Combination { .. } => (0..field.width())
+                .map(|o| {
+                    let keep = if notched.contains(&o) {
+                        Keep::Dropped
+                    } else {
+                        Keep::Retained
+                    };
+                    needle(o, keep)
+                })
+                .collect(),
+        })
+    }
+
+    /// Simulate selecting the cards of `deck` that match every term of
+    /// `query`, one needle pass at a time.
+    pub fn needle_sort(&self, deck: &Deck, query: &str) -> Result<NeedleSort> {
+        let cards: Vec<Vec<usize>> = self.deck_notches(deck)?;
+        let mut selected: Vec<usize> = (0..cards.len()).collect();
+        let mut passes = Vec::new();
+        for term in query.split_whitespace() {
+            for needle in self.needles(term)? {
+                let (dropped, retained): (Vec<usize>, Vec<usize>) = selected
+                    .iter()
+                    .partition(|idx| cards[**idx].contains(&needle.hole));
+                passes.push(SortPass {
+                    needle,
+                    dropped: dropped.len(),
+                    retained: retained.len(),
+                });
+                selected = match needle.keep {
+                    Keep::Dropped => dropped,
+                    Keep::Retained => retained,
+                };
+            }
+        }
+        Ok(NeedleSort { passes, selected })
+    }
+
+    /// Notched holes of every card in `deck`.
+    pub fn deck_notches(&self, deck: &Deck) -> Result<Vec<Vec<usize>>> {
+        deck.cards
+            .iter()
+            .enumerate()
+            .map(|(idx, card)| {
+                let spec = card
+                    .text
+                    .as_deref()
+                    .ok_or_else(|| anyhow!("card {} has no notch text", idx + 1))?;
+                self.notches(spec)
+                    .with_context(|| format!("card {}", idx + 1))
+            })
+            .collect()
+    }
+
+    /// Draw the card outline with `o` for intact and `V` for notched holes.
+    pub fn render(&self, notches: &[usize]) -> String {
+        let mark = |hole: usize| if notches.contains(&hole) { 'V' } else { 'o' };
+        let width = 2 * self.top.max(self.bottom) + 1;
+        let edge = |holes: Vec<usize>| -> String {
+            let line: String = holes.into_iter().map(|h| format!(" {}", mark(h))).collect();
+            format!("  {}", line.trim_end())
+        };
+        let mut out = String::new();
+        out.push_str(&edge((1..=self.top).collect()));
+        out.push('\n');
+        out.push_str(&format!(" +{}+\n", "-".repeat(width)));
+        let right_start = self.top + 1;
+        let left_end = self.holes();
+        for row in 0..self.left.max(self.right) {
+            let left = if row < self.left {
+                mark(left_end - row)
+            } else {
+                ' '
+            };
+            let right = if row < self.right {
+                mark(right_start + row)
+            } else {
+                ' '
+            };
+            out.push_str(&format!("{}|{}|{}\n", left, " ".repeat(width), right));
+        }
+        out.push_str(&format!(" +{}+\n", "-".repeat(width)));
+        let bottom_start = self.top + self.right + 1;
+        out.push_str(&edge(
+            (bottom_start..bottom_start + self.bottom).rev().collect(),
+        ));
+        out.push('\n');
+        out
+    }
+}
+
+fn position(values: &[String], value: &str, field: &str) -> Result<usize> {
+    values.iter().position(|v| v == value).ok_or_else(|| {
+        anyhow!(
+            "{}: unknown value '{}' (expected one of {})",
+            field,
+            value,
+            values.join(", ")
+        )
+    })
+}
+
+/// Offsets of the 7-4-2-1 holes summing to `digit`.
+fn digit_offsets(digit: u8) -> Vec<usize> {
+    let mut rest = digit;
+    let mut offsets = Vec::new();
+    for (offset, weight) in DIGIT_WEIGHTS.iter().enumerate() {
+        if rest >= *weight {
+            rest -= weight;
+            offsets.push(offset);
+        }
+    }
+    offsets
+}
+
+/// The `index`-th `k`-subset of `0..n` in lexicographic order.
+fn nth_combination(n: usize, k: usize, mut index: usize) -> Vec<usize> {
+    let mut out = Vec::with_capacity(k);
+    let mut next = 0;
+    while out.len() < k {
+        let left = k - out.len() - 1;
+        let with_next = binomial(n - next - 1, left);
+        if index < with_next {
+            out.push(next);
+        } else {
+            index -= with_next;
+        }
+        next += 1;
+    }
+    out
+}
+
+fn binomial(n: usize, k: usize) -> usize {
+    if k > n {
+        return 0;
+    }
+    (0..k).fold(1, |acc, i| acc * (n - i) / (i + 1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::deck::{CardType, DeckHeader, EncodingKind};
+
+    const LAYOUT: &str = r#"
+        name = "test"
+        top = 10
+        right = 4
+        bottom = 10
+        left = 4
+
+        [[fields]]
+        name = "subject"
+        start = 1
+        coding = "direct"
+        values = ["physics", "optics"]
+
+        [[fields]]
+        name = "year"
+        start = 3
+        coding = "digit"
+
+        [[fields]]
+        name = "author"
+        start = 7
+        coding = "combination"
+        holes = 5
+        notches = 2
+        values = ["abel", "babbage", "curie"]
+    "#;
+
+    #[test]
+    fn needle_sort_selects_exact_codes() {
+        let layout = NotchLayout::parse(LAYOUT).unwrap();
+        assert_eq!(layout.notches("year=9").unwrap(), vec![3, 5]);
+        assert_eq!(layout.notches("author=curie #20").unwrap(), vec![7, 10, 20]);
+        assert!(layout.notches("year=12").is_err());
+        assert!(NotchLayout::parse(&LAYOUT.replace("start = 7", "start = 5")).is_err());
+
+        let deck = Deck::from_lines(
+            DeckHeader::new(None, None, Vec::new()),
+            [
+                "subject=physics year=7",
+                "subject=physics,optics year=9",
+                "subject=optics year=7 author=abel",
+            ],
+            EncodingKind::Hollerith,
+            CardType::Data,
+        )
+        .unwrap();
+        // 9 = 7 + 2 shares the 7 notch, so the 2 hole must stay intact.
+        let sort = layout.needle_sort(&deck, "year=7").unwrap();
+        assert_eq!(sort.selected, vec![0, 2]);
+        assert_eq!(sort.passes.len(), 4);
+        let sort = layout.needle_sort(&deck, "subject=optics year=9").unwrap();
+        assert_eq!(sort.selected, vec![1]);
+        assert!(layout.render(&[1, 24]).contains('V'));
+    }
+}
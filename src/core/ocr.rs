@@ -0,0 +1,129 @@
+//! Cross-checking the printed interpretation of scanned cards against their
+//! holes (`punch verify ocr`).
+//!
+//! The interpretation line along the top edge of a card is printed by the
+//! keypunch from what it punched, so on an untouched card it agrees with the
+//! holes. Hand annotations, re-punched columns and duplicated-then-corrected
+//! cards break that agreement. The printed line is read by an external OCR
+//! engine; this module lines its output up with the hole-decoded text.
+
+use anyhow::{Context, Result};
+
+use crate::core::deck::Deck;
+use crate::core::encoding::PunchEncoding;
+
+/// Glyph pairs OCR engines commonly mistake for each other on card stock.
+const CONFUSABLE: &[(char, char)] = &[
+    ('0', 'O'),
+    ('0', 'D'),
+    ('1', 'I'),
+    ('1', 'L'),
+    ('2', 'Z'),
+    ('5', 'S'),
+    ('6', 'G'),
+    ('8', 'B'),
+];
+
+/// Card whose printed line and holes disagree.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OcrMismatch {
+    /// Zero-based card index.
+    pub card: usize,
+    /// 1-based disagreeing columns.
+    pub columns: Vec<usize>,
+    /// Text read from the interpretation line, padded to 80 columns.
+    pub printed: String,
+    /// Text decoded from the holes.
+    pub punched: String,
+    /// Every disagreement is a look-alike pair (`0`/`O`, `1`/`I`, ...), so
+    /// the OCR read is the more likely culprit.
+    pub confusable: bool,
+}
+
+/// Whether `a` and `b` are a known OCR look-alike pair.
+pub fn is_confusable(a: char, b: char) -> bool {
+    CONFUSABLE
+        .iter()
+        .any(|(x, y)| (*x, *y) == (a, b) || (*y, *x) == (a, b))
+}
+
+/// Compare OCR output, one line per card in deck order, with the text the
+/// holes of each card decode to under `encoder`.
+///
+/// Letters are compared case-insensitively, as interpreters print capitals.
+/// A missing OCR line counts as a blank interpretation, so unprinted cards
+/// with holes are flagged.
+pub fn ocr_cross_check(
+    deck: &Deck,
+    ocr: &str,
+    encoder: &dyn PunchEncoding,
+) -> Result<Vec<OcrMismatch>> {
+    let lines: Vec<&str> = ocr.lines().collect();
+    let mut found = Vec::new();
+    for (idx, record) in deck.cards.iter().enumerate() {
+        let card = record
+            .to_punch_card(encoder)
+            .with_context(|| format!("card {}", idx + 1))?;
+        let punched: Vec<char> = card.text().iter().map(char::to_ascii_uppercase).collect();
+        let mut printed: Vec<char> = lines
+            .get(idx)
+            .copied()
+            .unwrap_or_default()
+            .chars()
+            .take(punched.len())
+            .map(|ch| ch.to_ascii_uppercase())
+            .collect();
+        printed.resize(punched.len(), ' ');
+        let columns: Vec<usize> = (0..punched.len())
+            .filter(|col| printed[*col] != punched[*col])
+            .map(|col| col + 1)
+            .collect();
+        if columns.is_empty() {
+            continue;
+        }
+        let confusable = columns
+            .iter()
+            .all(|col| is_confusable(printed[col - 1], punched[col - 1]));
+        found.push(OcrMismatch {
+            card: idx,
+            columns,
+            printed: printed.into_iter().collect(),
+            punched: punched.into_iter().collect(),
+            confusable,
+        });
+    }
+    Ok(found)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::deck::{CardRecord, CardType, DeckHeader, EncodingKind};
+    use crate::core::encoding::IBM029;
+    use crate::core::formats::parse_punch_spec;
+
+    #[test]
+    fn cross_check_separates_look_alikes_from_repunches() {
+        let mut deck = Deck::from_lines(
+            DeckHeader::new(None, None, Vec::new()),
+            ["GOTO 10", "x = 1", "SAME"],
+            EncodingKind::Hollerith,
+            CardType::Code,
+        )
+        .unwrap();
+        deck.append_card(CardRecord::from_punches(
+            &parse_punch_spec("1:12-1").unwrap(),
+            CardType::Data,
+        ))
+        .unwrap();
+        let ocr = "G0TO 1O\nX = 7\nsame\n";
+        let found = ocr_cross_check(&deck, ocr, &IBM029).unwrap();
+        assert_eq!(found.len(), 3);
+        assert_eq!((found[0].card, found[0].columns.clone()), (0, vec![2, 7]));
+        assert!(found[0].confusable);
+        assert_eq!(found[1].columns, vec![5]);
+        assert!(!found[1].confusable);
+        assert_eq!((found[2].card, found[2].columns.clone()), (3, vec![1]));
+        assert!(found[2].punched.starts_with('A'));
+    }
+}
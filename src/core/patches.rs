@@ -0,0 +1,207 @@
+//! Provenance links from patch cards to the cards they correct, and folding
+//! patches back into the main sequence (`punch deck apply-patches`).
+
+use std::fmt;
+
+use anyhow::{Result, anyhow};
+use serde::{Deserialize, Serialize};
+
+use crate::core::deck::{CardType, Deck, SEQUENCE_FIELD};
+use crate::core::diff::{ColumnChange, diff_columns};
+
+/// Card a patch corrects. Sequence numbers survive reordering, so they are
+/// preferred; cards without one are referenced by zero-based index.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum PatchTarget {
+    Seq(usize),
+    Index(usize),
+}
+
+impl fmt::Display for PatchTarget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PatchTarget::Seq(seq) => write!(f, "seq {}", seq),
+            PatchTarget::Index(idx) => write!(f, "card {}", idx + 1),
+        }
+    }
+}
+
+/// A card and the patches stacked on it, oldest first.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PatchChain {
+    pub target: PatchTarget,
+    /// Index of the patched card, or `None` when the target is missing.
+    pub card: Option<usize>,
+    pub patches: Vec<usize>,
+}
+
+/// Patch folded into its target by [`Deck::apply_patches`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FoldedPatch {
+    /// Index of the patched card after folding.
+    pub card: usize,
+    pub patches: usize,
+    pub changes: Vec<ColumnChange>,
+}
+
+impl Deck {
+    /// Reference to the card at `index`, by sequence number when it has one.
+    pub fn patch_target(&self, index: usize) -> Result<PatchTarget> {
+        let card = self
+            .cards
+            .get(index)
+            .ok_or_else(|| anyhow!("card {} out of range 1..={}", index + 1, self.cards.len()))?;
+        Ok(match card.seq {
+            Some(seq) => PatchTarget::Seq(seq),
+            None => PatchTarget::Index(index),
+        })
+    }
+
+    /// Index of the card `target` refers to.
+    pub fn resolve_patch_target(&self, target: PatchTarget) -> Option<usize> {
+        match target {
+            PatchTarget::Seq(seq) => self.cards.iter().position(|card| card.seq == Some(seq)),
+            PatchTarget::Index(idx) => (idx < self.cards.len()).then_some(idx),
+        }
+    }
+
+    /// Group linked patch cards by the non-patch card they ultimately
+    /// correct; a patch of a patch joins its target's chain.
+    pub fn patch_chains(&self) -> Vec<PatchChain> {
+        let mut chains: Vec<PatchChain> = Vec::new();
+        for (idx, card) in self.cards.iter().enumerate() {
+            let Some(mut target) = card.meta.patches else {
+                continue;
+            };
+            let mut resolved = self.resolve_patch_target(target);
+            let mut hops = 0;
+            while let Some(next) = resolved
+                .filter(|t| self.cards[*t].card_type == CardType::Patch)
+                .and_then(|t| self.cards[t].meta.patches)
+            {
+                hops += 1;
+                if hops > self.cards.len() {
+                    resolved = None;
+                    break;
+                }
+                target = next;
+                resolved = self.resolve_patch_target(next);
+            }
+            match chains.iter_mut().find(|chain| chain.target == target) {
+                Some(chain) => chain.patches.push(idx),
+                None => chains.push(PatchChain {
+                    target,
+                    card: resolved,
+                    patches: vec![idx],
+                }),
+            }
+        }
+        chains.sort_by_key(|chain| chain.card.unwrap_or(usize::MAX));
+        chains
+    }
+
+    /// Replace each patched card's text with its newest patch and drop the
+    /// patch cards. The target keeps its type, sequence number and, when the
+    /// patch leaves it blank, its sequence field. Chains whose target is
+    /// missing are left in place.
+    pub fn apply_patches(&mut self) -> Result<Vec<FoldedPatch>> {
+        self.ensure_mutable()?;
+        let mut folded = Vec::new();
+        let mut remove = Vec::new();
+        for chain in self.patch_chains() {
+            let Some(target) = chain.card else {
+                continue;
+            };
+            let newest = *chain.patches.last().expect("chains are never empty");
+            let before = self.cards[target].text.clone().unwrap_or_default();
+            let mut card = self.cards[target].clone();
+            card.text = self.cards[newest].text.clone();
+            card.punches = self.cards[newest].punches.clone();
+            if let Some(after) = card.text.as_mut() {
+                keep_sequence_field(after, &before);
+            }
+            let changes = diff_columns(&before, card.text.as_deref().unwrap_or_default());
+            self.replace_card(target, card)?;
+            folded.push(FoldedPatch {
+                card: target,
+                patches: chain.patches.len(),
+                changes,
+            });
+            remove.extend(chain.patches);
+        }
+        remove.sort_unstable();
+        for fold in &mut folded {
+            fold.card -= remove.iter().filter(|idx| **idx < fold.card).count();
+        }
+        for idx in remove.into_iter().rev() {
+            self.cards.remove(idx);
+        }
+        Ok(folded)
+    }
+}
+
+/// Copy the original sequence field into a patch that left it blank.
+fn keep_sequence_field(patched: &mut String, original: &str) {
+    let field = SEQUENCE_FIELD.start - 1..SEQUENCE_FIELD.end;
+    let mut cols: Vec<char> = patched.chars().collect();
+    let original: Vec<char> = original.chars().collect();
+    if cols.len() < field.end || original.len() < field.end {
+        return;
+    }
+    if cols[field.clone()].iter().all(|c| *c == ' ') {
+        cols[field.clone()].copy_from_slice(&original[field]);
+        *patched = cols.into_iter().collect();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::deck::{CardRecord, DeckHeader, EncodingKind};
+
+    fn patch(text: &str, target: PatchTarget) -> CardRecord {
+        let mut card =
+            CardRecord::from_text(text, EncodingKind::Hollerith, CardType::Patch).unwrap();
+        card.meta.patches = Some(target);
+        card
+    }
+
+    #[test]
+    fn patches_chain_and_fold_into_their_target() {
+        let header = DeckHeader::new(None, None, Vec::new());
+        let mut deck = Deck::from_lines(
+            header,
+            ["A = 1", "B = 2", "C = 3"],
+            EncodingKind::Hollerith,
+            CardType::Code,
+        )
+        .unwrap();
+        deck.number_sequence(10, 10).unwrap();
+        let target = deck.patch_target(1).unwrap();
+        assert_eq!(target, PatchTarget::Seq(20));
+        deck.append_card(patch("B = 20", target)).unwrap();
+        deck.append_card(patch("B = 200", PatchTarget::Index(3)))
+            .unwrap();
+        deck.append_card(patch("Z = 0", PatchTarget::Seq(99)))
+            .unwrap();
+
+        let chains = deck.patch_chains();
+        assert_eq!(chains.len(), 2);
+        assert_eq!(
+            (chains[0].card, chains[0].patches.clone()),
+            (Some(1), vec![3, 4])
+        );
+        assert_eq!(chains[1].card, None);
+
+        let folded = deck.apply_patches().unwrap();
+        assert_eq!(folded.len(), 1);
+        assert_eq!(folded[0].patches, 2);
+        assert_eq!(deck.cards.len(), 4);
+        let text = deck.cards[1].text.as_deref().unwrap();
+        assert!(text.starts_with("B = 200"));
+        assert!(text.ends_with("      20"));
+        assert_eq!(deck.cards[1].card_type, CardType::Code);
+        assert_eq!(deck.cards[3].meta.patches, Some(PatchTarget::Seq(99)));
+    }
+}
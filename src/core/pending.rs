@@ -0,0 +1,145 @@
+//! Deferred card additions (`--defer-save`, `punch deck flush`).
+//!
+//! Every [`Deck::save`] rewrites the deck and snapshots it into the undo
+//! journal, so a script adding cards one command at a time rewrites the file
+//! once per card. Deferred commands instead append their cards to a pending
+//! file next to the deck (see [`pending_sidecar_path`]) and
+//! [`Deck::apply_pending`] adds them all before a single save.
+
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result, anyhow};
+use serde::{Deserialize, Serialize};
+
+use crate::core::deck::{CardRecord, Deck};
+
+/// Pending path for a deck: `prog.deck` stages cards in `prog.pending.jsonl`.
+pub fn pending_sidecar_path(deck: &Path) -> PathBuf {
+    let mut path = deck.to_path_buf();
+    path.set_extension("pending.jsonl");
+    path
+}
+
+/// Cards one deferred command appends, with the audit action it logs.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PendingBatch {
+    pub action: String,
+    pub cards: Vec<CardRecord>,
+}
+
+/// Append `batch` to the pending file of the deck at `path`; `deck` is that
+/// deck as loaded, checked for being open to changes and for protected
+/// columns. Encrypted decks are refused, as the pending file would hold
+/// their cards in the clear.
+pub fn stage(deck: &Deck, path: &Path, batch: &PendingBatch) -> Result<()> {
+    deck.ensure_mutable()?;
+    for card in &batch.cards {
+        deck.enforce_protection(None, card)?;
+    }
+    if deck.encryption.is_some() {
+        return Err(anyhow!(
+            "cannot defer changes to an encrypted deck; its cards would be staged unencrypted"
+        ));
+    }
+    let pending = pending_sidecar_path(path);
+    let mut line = serde_json::to_string(batch).context("failed to serialize pending cards")?;
+    line.push('\n');
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&pending)
+        .and_then(|mut file| file.write_all(line.as_bytes()))
+        .with_context(|| format!("failed to write {}", pending.display()))
+}
+
+/// Batches staged for the deck at `path`, oldest first; none when there is
+/// no pending file.
+pub fn read_pending(path: &Path) -> Result<Vec<PendingBatch>> {
+    let pending = pending_sidecar_path(path);
+    if !pending.exists() {
+        return Ok(Vec::new());
+    }
+    let body = fs::read_to_string(&pending)
+        .with_context(|| format!("failed to read {}", pending.display()))?;
+    body.lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(idx, line)| {
+            serde_json::from_str(line)
+                .with_context(|| format!("{} line {}", pending.display(), idx + 1))
+        })
+        .collect()
+}
+
+/// Remove the pending file of the deck at `path`, returning whether there
+/// was one.
+pub fn discard(path: &Path) -> Result<bool> {
+    let pending = pending_sidecar_path(path);
+    if !pending.exists() {
+        return Ok(false);
+    }
+    fs::remove_file(&pending).with_context(|| format!("failed to remove {}", pending.display()))?;
+    Ok(true)
+}
+
+impl Deck {
+    /// Append the cards of every batch, logging each batch's action, and
+    /// return how many cards were added. Nothing is added if any card is
+    /// refused.
+    pub fn apply_pending(&mut self, batches: &[PendingBatch]) -> Result<usize> {
+        self.ensure_mutable()?;
+        let before = self.cards.len();
+        let history = self.header.history.len();
+        for batch in batches {
+            for card in &batch.cards {
+                if let Err(err) = self.append_card(card.clone()) {
+                    self.cards.truncate(before);
+                    self.header.history.truncate(history);
+                    return Err(err);
+                }
+            }
+            self.log_action(format!("{} (deferred)", batch.action));
+        }
+        Ok(self.cards.len() - before)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::deck::{CardType, DeckHeader, EncodingKind};
+
+    #[test]
+    fn staged_cards_land_in_one_flush() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("prog.deck");
+        let mut deck = Deck::new(DeckHeader::new(None, None, Vec::new()));
+        deck.save(&path).unwrap();
+        assert!(read_pending(&path).unwrap().is_empty());
+
+        for text in ["A", "B"] {
+            let batch = PendingBatch {
+                action: "card add".into(),
+                cards: vec![
+                    CardRecord::from_text(text, EncodingKind::Hollerith, CardType::Code).unwrap(),
+                ],
+            };
+            stage(&deck, &path, &batch).unwrap();
+        }
+        assert_eq!(fs::read_to_string(&path).unwrap().lines().count(), 1);
+        let batches = read_pending(&path).unwrap();
+        assert_eq!(batches.len(), 2);
+        assert_eq!(deck.apply_pending(&batches).unwrap(), 2);
+        assert_eq!(deck.card_text(1).unwrap().trim_end(), "B");
+        assert!(
+            deck.header
+                .history
+                .iter()
+                .any(|event| event.action == "card add (deferred)")
+        );
+        assert!(discard(&path).unwrap());
+        assert!(!discard(&path).unwrap());
+    }
+}
@@ -0,0 +1,254 @@
+//! Deck preparation pipelines: an ordered list of [`DeckTransform`] steps,
+//! usually loaded from a TOML file (`punch pipeline run`).
+//!
+//! ```toml
+//! name = "release"
+//!
+//! [[steps]]
+//! kind = "normalize"
+//! uppercase = true
+//! tab_width = 8
+//!
+//! [[steps]]
+//! kind = "strip-comments"
+//!
+//! [[steps]]
+//! kind = "renumber"
+//! start = 100
+//! step = 10
+//!
+//! [[steps]]
+//! kind = "seal"
+//! ```
+
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use serde::Deserialize;
+
+use crate::core::deck::{CardType, Deck, DeckState};
+use crate::core::text::{CARD_COLUMNS, Normalizer, pad_80};
+
+/// One step that rewrites a deck in place.
+pub trait DeckTransform {
+    /// Short description recorded in the audit log.
+    fn describe(&self) -> String;
+    fn apply(&self, deck: &mut Deck) -> Result<()>;
+}
+
+/// Run every text card through a [`Normalizer`], truncating at column 80.
+/// Protected columns must survive unchanged.
+pub struct Normalize(pub Normalizer);
+
+impl DeckTransform for Normalize {
+    fn describe(&self) -> String {
+        "normalize".to_string()
+    }
+
+    fn apply(&self, deck: &mut Deck) -> Result<()> {
+        for idx in 0..deck.cards.len() {
+            let Some(text) = deck.cards[idx].text.as_deref() else {
+                continue;
+            };
+            let line: String = self
+                .0
+                .normalize_line(text)
+                .chars()
+                .take(CARD_COLUMNS)
+                .collect();
+            let line = pad_80(&line);
+            if line != text {
+                let mut card = deck.cards[idx].clone();
+                card.text = Some(line);
+                deck.replace_card(idx, card)
+                    .with_context(|| format!("card {}", idx + 1))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Renumber columns 73–80, like `seq number`.
+pub struct Renumber {
+    pub start: usize,
+    pub step: usize,
+}
+
+impl DeckTransform for Renumber {
+    fn describe(&self) -> String {
+        format!("renumber start={} step={}", self.start, self.step)
+    }
+
+    fn apply(&self, deck: &mut Deck) -> Result<()> {
+        deck.number_sequence(self.start, self.step)
+    }
+}
+
+/// Drop comment cards.
+pub struct StripComments;
+
+impl DeckTransform for StripComments {
+    fn describe(&self) -> String {
+        "strip-comments".to_string()
+    }
+
+    fn apply(&self, deck: &mut Deck) -> Result<()> {
+        deck.cards
+            .retain(|card| card.card_type != CardType::Comment);
+        Ok(())
+    }
+}
+
+/// Freeze the deck; it must already be verified.
+pub struct Seal;
+
+impl DeckTransform for Seal {
+    fn describe(&self) -> String {
+        "seal".to_string()
+    }
+
+    fn apply(&self, deck: &mut Deck) -> Result<()> {
+        deck.transition(DeckState::Frozen)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "kind", rename_all = "kebab-case", deny_unknown_fields)]
+enum StepSpec {
+    Normalize {
+        #[serde(default)]
+        uppercase: bool,
+        #[serde(default)]
+        tab_width: Option<usize>,
+    },
+    Renumber {
+        #[serde(default = "default_seq")]
+        start: usize,
+        #[serde(default = "default_seq")]
+        step: usize,
+    },
+    StripComments,
+    Seal,
+}
+
+fn default_seq() -> usize {
+    10
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct PipelineSpec {
+    #[serde(default)]
+    name: Option<String>,
+    steps: Vec<StepSpec>,
+}
+
+/// Named sequence of transforms applied in order.
+#[derive(Default)]
+pub struct Pipeline {
+    pub name: String,
+    pub steps: Vec<Box<dyn DeckTransform>>,
+}
+
+impl Pipeline {
+    /// Empty pipeline called `name`.
+    pub fn new<S: Into<String>>(name: S) -> Self {
+        Self {
+            name: name.into(),
+            steps: Vec::new(),
+        }
+    }
+
+    /// Append a step.
+    pub fn then<T: DeckTransform + 'static>(mut self, step: T) -> Self {
+        self.steps.push(Box::new(step));
+        self
+    }
+
+    /// Parse a pipeline definition (see the module docs).
+    pub fn parse(text: &str) -> Result<Self> {
+        let spec: PipelineSpec = toml::from_str(text).context("invalid pipeline file")?;
+        let mut pipeline = Self::new(spec.name.unwrap_or_else(|| "pipeline".to_string()));
+        for step in spec.steps {
+            pipeline.steps.push(match step {
+                StepSpec::Normalize {
+                    uppercase,
+                    tab_width,
+                } => {
+                    let mut normalizer = Normalizer::new().uppercase(uppercase);
+                    if let Some(width) = tab_width {
+                        normalizer = normalizer.tab_width(width);
+                    }
+                    Box::new(Normalize(normalizer))
+                }
+                StepSpec::Renumber { start, step } => Box::new(Renumber { start, step }),
+                StepSpec::StripComments => Box::new(StripComments),
+                StepSpec::Seal => Box::new(Seal),
+            });
+        }
+        Ok(pipeline)
+    }
+
+    /// Read and parse a pipeline file from disk.
+    pub fn load(path: &Path) -> Result<Self> {
+        let text = std::fs::read_to_string(path)
+            .with_context(|| format!("failed to read pipeline file {}", path.display()))?;
+        Self::parse(&text).with_context(|| format!("failed to load {}", path.display()))
+    }
+
+    /// Apply every step in order, logging each one. Stops at the first
+    /// failing step; the deck may then be partly transformed.
+    pub fn run(&self, deck: &mut Deck) -> Result<Vec<String>> {
+        deck.ensure_mutable()?;
+        let mut applied = Vec::new();
+        for step in &self.steps {
+            let what = step.describe();
+            step.apply(deck)
+                .with_context(|| format!("pipeline step '{}' failed", what))?;
+            deck.log_action(format!("pipeline {}: {}", self.name, what));
+            applied.push(what);
+        }
+        Ok(applied)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::deck::{CardRecord, DeckHeader, EncodingKind};
+
+    #[test]
+    fn pipeline_file_runs_steps_in_order() {
+        let pipeline = Pipeline::parse(
+            r#"
+            name = "release"
+            [[steps]]
+            kind = "normalize"
+            uppercase = true
+            [[steps]]
+            kind = "strip-comments"
+            [[steps]]
+            kind = "renumber"
+            start = 100
+            "#,
+        )
+        .unwrap();
+        let header = DeckHeader::new(None, None, Vec::new());
+        let mut deck = Deck::from_lines(
+            header,
+            ["x = 1", "y = 2"],
+            EncodingKind::Hollerith,
+            CardType::Code,
+        )
+        .unwrap();
+        let comment = CardRecord::from_text("C NOTE", EncodingKind::Hollerith, CardType::Comment);
+        deck.insert_card(1, comment.unwrap()).unwrap();
+
+        let applied = pipeline.run(&mut deck).unwrap();
+        assert_eq!(applied.len(), 3);
+        assert_eq!(deck.cards.len(), 2);
+        assert!(deck.cards[0].text.as_deref().unwrap().starts_with("X = 1"));
+        assert_eq!(deck.cards[1].seq, Some(110));
+        assert!(Pipeline::parse("[[steps]]\nkind = \"shred\"").is_err());
+    }
+}
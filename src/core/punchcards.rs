@@ -1,19 +1,26 @@
+use crate::core::captions::Captions;
+use crate::core::deck::{ColumnRange, SEQUENCE_FIELD, sequence_field};
 use crate::core::encoding::{CellMask, EncodeError, PunchEncoding};
-use std::fmt::{self, Write};
+use std::fmt;
+use std::io;
 
 const COLS: usize = 80;
-const ROW_LABELS: [&str; 12] = [
-    "12", "11", " 0", " 1", " 2", " 3", " 4", " 5", " 6", " 7", " 8", " 9",
-];
+const ROWS: usize = 12;
 const ROW_BIT_ORDER: [usize; 12] = [11, 10, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9];
 const BLANK_CARD: &str =
     "                                                                                ";
 
 /// In-memory representation of a single punch card column-by-column.
+///
+/// The holes are also kept row by row as bitsets (see [`row_bits`]), so the
+/// ASCII renderers draw a row with bit operations instead of testing 80 cells.
+///
+/// [`row_bits`]: Self::row_bits
 #[derive(Debug, Clone)]
 pub struct PunchCard {
     columns: [CellMask; COLS],
     text: [char; COLS],
+    rows: [u128; ROWS],
 }
 
 impl PunchCard {
@@ -24,32 +31,146 @@ impl PunchCard {
             columns[idx] = enc.encode_char(ch)?;
             text[idx] = ch;
         }
-        Ok(Self { columns, text })
+        Ok(Self::assemble(columns, text))
     }
 
+    /// Build a card directly from hole patterns, e.g. for binary or scanned cards.
+    ///
+    /// `decode` supplies the interpretation printed for each column; columns it
+    /// cannot interpret are left blank, as an interpreter would.
+    pub fn from_columns<F>(masks: &[CellMask], decode: F) -> Self
+    where
+        F: Fn(CellMask) -> Option<char>,
+    {
+        let mut columns = [CellMask(0); COLS];
+        let mut text = [' '; COLS];
+        for (idx, mask) in masks.iter().take(COLS).enumerate() {
+            columns[idx] = *mask;
+            text[idx] = decode(*mask).unwrap_or(' ');
+        }
+        Self::assemble(columns, text)
+    }
+
+    fn assemble(columns: [CellMask; COLS], text: [char; COLS]) -> Self {
+        let mut rows = [0u128; ROWS];
+        for (col, cell) in columns.iter().enumerate() {
+            for (row, bit) in rows.iter_mut().zip(ROW_BIT_ORDER) {
+                *row |= u128::from((cell.0 >> bit) & 1) << col;
+            }
+        }
+        Self {
+            columns,
+            text,
+            rows,
+        }
+    }
+
+    /// Punch `seq` into the sequence field (columns 73–80) using the same layout as
+    /// [`Deck::number_sequence`](crate::core::deck::Deck::number_sequence), leaving
+    /// any non-blank column untouched.
     pub fn with_sequence<E: PunchEncoding + ?Sized>(
         mut self,
         enc: &E,
         seq: usize,
     ) -> Result<Self, EncodeError> {
-        let seq_repr = format!("{:>9}", seq);
-        let start = COLS - seq_repr.len();
-        for (offset, ch) in seq_repr.chars().enumerate() {
+        let start = SEQUENCE_FIELD.start - 1;
+        for (offset, ch) in sequence_field(seq)?.chars().enumerate() {
             let idx = start + offset;
             if self.text[idx] != ' ' {
                 continue;
             }
             self.text[idx] = ch;
-            self.columns[idx] = enc.encode_char(ch)?;
+            let mask = enc.encode_char(ch)?;
+            self.columns[idx] = mask;
+            for (row, bit) in self.rows.iter_mut().zip(ROW_BIT_ORDER) {
+                *row |= u128::from((mask.0 >> bit) & 1) << idx;
+            }
         }
         Ok(self)
     }
 
     pub fn render(&self, style: RenderStyle) -> String {
-        match style {
-            RenderStyle::AsciiX => self.render_ascii('X', ' '),
-            RenderStyle::Ascii01 => self.render_ascii('1', '0'),
+        self.render_highlighted(style, &[])
+    }
+
+    /// Render the card, adding a `^` marker row beneath the text for each highlighted range.
+    pub fn render_highlighted(&self, style: RenderStyle, highlights: &[ColumnRange]) -> String {
+        let mut buffer = Vec::with_capacity(17 * COLS);
+        self.render_highlighted_to(&mut buffer, style, highlights)
+            .expect("writing to a Vec cannot fail");
+        String::from_utf8(buffer).expect("rendered card is valid UTF-8")
+    }
+
+    /// Stream the ASCII rendering into `out` without building an intermediate string.
+    pub fn render_to<W: io::Write + ?Sized>(
+        &self,
+        out: &mut W,
+        style: RenderStyle,
+    ) -> io::Result<()> {
+        self.render_highlighted_to(out, style, &[])
+    }
+
+    /// Streaming counterpart of [`PunchCard::render_highlighted`].
+    pub fn render_highlighted_to<W: io::Write + ?Sized>(
+        &self,
+        out: &mut W,
+        style: RenderStyle,
+        highlights: &[ColumnRange],
+    ) -> io::Result<()> {
+        self.render_annotated_to(out, style, highlights, None)
+    }
+
+    /// Like [`PunchCard::render_highlighted_to`], with an optional extra row
+    /// (such as [`Template::field_ruler`](crate::core::templates::Template::field_ruler))
+    /// printed above the numeric column ruler.
+    pub fn render_annotated_to<W: io::Write + ?Sized>(
+        &self,
+        out: &mut W,
+        style: RenderStyle,
+        highlights: &[ColumnRange],
+        field_ruler: Option<&str>,
+    ) -> io::Result<()> {
+        self.render_captioned_to(out, style, highlights, field_ruler, Captions::english())
+    }
+
+    /// Like [`PunchCard::render_annotated_to`], taking the banner and row
+    /// labels from `captions`.
+    pub fn render_captioned_to<W: io::Write + ?Sized>(
+        &self,
+        out: &mut W,
+        style: RenderStyle,
+        highlights: &[ColumnRange],
+        field_ruler: Option<&str>,
+        captions: &Captions,
+    ) -> io::Result<()> {
+        let (mark, blank) = match style {
+            RenderStyle::AsciiX => ('X', ' '),
+            RenderStyle::Ascii01 => ('1', '0'),
+            RenderStyle::Block | RenderStyle::Braille => {
+                return self.write_compact(out, style, highlights, field_ruler, captions);
+            }
+        };
+        self.write_ascii(out, mark, blank, highlights, field_ruler, captions)
+    }
+
+    /// Locate every occurrence of `pattern` in the card text, ignoring ASCII case.
+    ///
+    /// Matches are returned as 1-based column ranges; overlapping hits are all reported.
+    pub fn find(&self, pattern: &str) -> Vec<ColumnRange> {
+        let needle: Vec<char> = pattern.chars().map(|c| c.to_ascii_uppercase()).collect();
+        if needle.is_empty() || needle.len() > COLS {
+            return Vec::new();
         }
+        let haystack: Vec<char> = self.text.iter().map(|c| c.to_ascii_uppercase()).collect();
+        haystack
+            .windows(needle.len())
+            .enumerate()
+            .filter(|(_, window)| *window == needle.as_slice())
+            .map(|(idx, _)| ColumnRange {
+                start: idx + 1,
+                end: idx + needle.len(),
+            })
+            .collect()
     }
 
     pub fn columns(&self) -> &[CellMask; COLS] {
@@ -60,30 +181,170 @@ impl PunchCard {
         &self.text
     }
 
-    fn render_ascii(&self, mark: char, blank: char) -> String {
-        let mut out = String::with_capacity(16 * COLS);
-        writeln!(&mut out, "IBM 5081 (80 cols) [IBM029]").unwrap();
-        writeln!(&mut out, "     {}", ruler_line()).unwrap();
-        write!(&mut out, "     ").unwrap();
-        out.extend(self.text);
-        writeln!(&mut out).unwrap();
+    /// Holes row by row, top to bottom (12, 11, 0–9); bit `n` is column `n + 1`.
+    pub fn row_bits(&self) -> &[u128; ROWS] {
+        &self.rows
+    }
+
+    fn write_ascii<W: io::Write + ?Sized>(
+        &self,
+        out: &mut W,
+        mark: char,
+        blank: char,
+        highlights: &[ColumnRange],
+        field_ruler: Option<&str>,
+        captions: &Captions,
+    ) -> io::Result<()> {
+        if !captions.banner.is_empty() {
+            writeln!(out, "{}", captions.banner)?;
+        }
+        if let Some(fields) = field_ruler {
+            writeln!(out, "     {}", fields)?;
+        }
+        writeln!(out, "     {}", ruler_line())?;
+        let text: String = self.text.iter().collect();
+        writeln!(out, "     {}", text)?;
+        let mut row = String::with_capacity(COLS);
+        if !highlights.is_empty() {
+            draw_row(&mut row, highlight_bits(highlights), '^', ' ');
+            writeln!(out, "     {}", row.trim_end())?;
+        }
         let separator = "-".repeat(COLS);
-        writeln!(&mut out, "     {}", separator).unwrap();
-        for (row_index, label) in ROW_LABELS.iter().enumerate() {
-            write!(&mut out, "{:>3} |", label).unwrap();
-            let bit = ROW_BIT_ORDER[row_index];
-            for cell in &self.columns {
-                let filled = (cell.0 >> bit) & 1 == 1;
-                out.push(if filled { mark } else { blank });
+        writeln!(out, "     {}", separator)?;
+        for (bits, label) in self.rows.iter().zip(&captions.row_labels) {
+            draw_row(&mut row, *bits, mark, blank);
+            writeln!(out, "{:>3} |{}|", label, row)?;
+        }
+        writeln!(out, "     {}", separator)
+    }
+
+    /// Compact rendering: [`RenderStyle::Block`] packs two rows into each
+    /// line with half blocks, [`RenderStyle::Braille`] packs four rows and two
+    /// columns into each braille cell. A braille card is 40 cells wide, so
+    /// the field ruler (laid out for 80 columns) is left out there.
+    fn write_compact<W: io::Write + ?Sized>(
+        &self,
+        out: &mut W,
+        style: RenderStyle,
+        highlights: &[ColumnRange],
+        field_ruler: Option<&str>,
+        captions: &Captions,
+    ) -> io::Result<()> {
+        let (cols_per_cell, rows_per_line) = match style {
+            RenderStyle::Braille => (2, 4),
+            _ => (1, 2),
+        };
+        let cells = COLS / cols_per_cell;
+        // Cell `i` shows columns `i * cols_per_cell + 1` onwards.
+        let squeeze = |bits: u128| {
+            let group = (1u128 << cols_per_cell) - 1;
+            (0..cells).fold(0u128, |acc, i| {
+                acc | u128::from((bits >> (i * cols_per_cell)) & group != 0) << i
+            })
+        };
+        let indent = "       ";
+
+        if !captions.banner.is_empty() {
+            writeln!(out, "{}", captions.banner)?;
+        }
+        if let Some(fields) = field_ruler.filter(|_| cols_per_cell == 1) {
+            writeln!(out, "{}{}", indent, fields)?;
+        }
+        let ruler: String = (0..cells)
+            .map(|i| {
+                let last = (i + 1) * cols_per_cell;
+                if last % 10 < cols_per_cell {
+                    char::from(b'0' + ((last / 10) % 10) as u8)
+                } else {
+                    '.'
+                }
+            })
+            .collect();
+        writeln!(out, "{}{}", indent, ruler)?;
+        let text: String = self.text.iter().collect();
+        writeln!(out, "{}{}", indent, text)?;
+        if !highlights.is_empty() {
+            let marked = squeeze(highlight_bits(highlights));
+            let row: String = (0..cells)
+                .map(|i| if (marked >> i) & 1 == 1 { '^' } else { ' ' })
+                .collect();
+            writeln!(out, "{}{}", indent, row.trim_end())?;
+        }
+        let separator = "-".repeat(cells);
+        writeln!(out, "{}{}", indent, separator)?;
+        for (band, labels) in self
+            .rows
+            .chunks(rows_per_line)
+            .zip(captions.row_labels.chunks(rows_per_line))
+        {
+            let line: String = (0..cells)
+                .map(|i| compact_cell(style, band, i * cols_per_cell))
+                .collect();
+            let label = format!("{}-{}", labels[0], labels[rows_per_line - 1]);
+            writeln!(out, "{:>5} |{}|", label, line)?;
+        }
+        writeln!(out, "{}{}", indent, separator)
+    }
+}
+
+/// Bits of the columns covered by `highlights`, ignoring ranges off the card.
+fn highlight_bits(highlights: &[ColumnRange]) -> u128 {
+    highlights
+        .iter()
+        .filter(|r| 1 <= r.start && r.start <= r.end && r.end <= COLS)
+        .fold(0u128, |bits, r| {
+            let width = r.end - r.start + 1;
+            bits | (u128::MAX >> (128 - width)) << (r.start - 1)
+        })
+}
+
+/// Braille dot bits for the 4×2 cell, indexed `[row][column]`.
+const BRAILLE_DOTS: [[u32; 2]; 4] = [[0x01, 0x08], [0x02, 0x10], [0x04, 0x20], [0x40, 0x80]];
+
+/// One character of a compact rendering: the holes of `band` (two rows for
+/// [`RenderStyle::Block`], four for [`RenderStyle::Braille`]) from the
+/// zero-based column `col`.
+fn compact_cell(style: RenderStyle, band: &[u128], col: usize) -> char {
+    let hole = |row: usize, col: usize| (band[row] >> col) & 1 == 1;
+    match style {
+        RenderStyle::Braille => {
+            let mut dots = 0;
+            for (row, bits) in BRAILLE_DOTS.iter().enumerate() {
+                for (dx, bit) in bits.iter().enumerate() {
+                    if hole(row, col + dx) {
+                        dots |= bit;
+                    }
+                }
             }
-            writeln!(&mut out, "|").unwrap();
+            char::from_u32(0x2800 + dots).expect("braille patterns are valid chars")
         }
-        writeln!(&mut out, "     {}", separator).unwrap();
-        out
+        _ => match (hole(0, col), hole(1, col)) {
+            (true, true) => '█',
+            (true, false) => '▀',
+            (false, true) => '▄',
+            (false, false) => ' ',
+        },
     }
 }
 
-fn ruler_line() -> String {
+/// Draw the 80 columns of `bits` into `line`: `mark` where a bit is set,
+/// `blank` elsewhere. Only the set bits are visited.
+fn draw_row(line: &mut String, bits: u128, mark: char, blank: char) {
+    line.clear();
+    if mark.is_ascii() && blank.is_ascii() {
+        let mut cells = [blank as u8; COLS];
+        let mut rest = bits;
+        while rest != 0 {
+            cells[rest.trailing_zeros() as usize] = mark as u8;
+            rest &= rest - 1;
+        }
+        line.push_str(std::str::from_utf8(&cells).expect("ASCII cells"));
+    } else {
+        line.extend((0..COLS).map(|col| if (bits >> col) & 1 == 1 { mark } else { blank }));
+    }
+}
+
+pub(crate) fn ruler_line() -> String {
     let mut ruler = String::with_capacity(COLS);
     for col in 1..=COLS {
         if col % 10 == 0 {
@@ -170,6 +431,21 @@ impl CardDeck {
         Ok(())
     }
 
+    /// Stream every card's rendering into `out`, separated by blank lines.
+    pub fn render_to<W: io::Write + ?Sized>(
+        &self,
+        out: &mut W,
+        style: RenderStyle,
+    ) -> io::Result<()> {
+        for (idx, card) in self.cards.iter().enumerate() {
+            if idx > 0 {
+                writeln!(out)?;
+            }
+            card.render_to(out, style)?;
+        }
+        Ok(())
+    }
+
     pub fn render(&self, style: RenderStyle) -> String {
         let mut out = String::new();
         for card in &self.cards {
@@ -187,6 +463,11 @@ impl CardDeck {
 pub enum RenderStyle {
     AsciiX,
     Ascii01,
+    /// Half blocks, two rows per line: six lines of holes instead of twelve.
+    Block,
+    /// Braille patterns, four rows and two columns per cell: three lines of
+    /// 40 cells.
+    Braille,
 }
 
 impl fmt::Display for RenderStyle {
@@ -194,6 +475,101 @@ impl fmt::Display for RenderStyle {
         match self {
             RenderStyle::AsciiX => write!(f, "ascii-x"),
             RenderStyle::Ascii01 => write!(f, "ascii-01"),
+            RenderStyle::Block => write!(f, "block"),
+            RenderStyle::Braille => write!(f, "braille"),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::encoding::IBM029;
+
+    #[test]
+    fn row_bitsets_follow_the_columns() {
+        let card = PunchCard::from_str(&IBM029, "A1")
+            .unwrap()
+            .with_sequence(&IBM029, 7)
+            .unwrap();
+        for (row, bits) in card.row_bits().iter().enumerate() {
+            for (col, cell) in card.columns().iter().enumerate() {
+                let punched = (cell.0 >> ROW_BIT_ORDER[row]) & 1 == 1;
+                assert_eq!(
+                    (bits >> col) & 1 == 1,
+                    punched,
+                    "row {} col {}",
+                    row,
+                    col + 1
+                );
+            }
+        }
+        // A is 12-1: column 1 in the 12 and 1 rows; the 7 lands in column 80.
+        assert_eq!(card.row_bits()[0], 1);
+        assert_eq!(card.row_bits()[3], 0b11);
+        assert_eq!(card.row_bits()[9], 1 << 79);
+
+        let listing =
+            card.render_highlighted(RenderStyle::AsciiX, &[ColumnRange::new(2, 3).unwrap()]);
+        let lines: Vec<&str> = listing.lines().collect();
+        assert!(lines.contains(&"      ^^"));
+        assert!(lines.iter().any(|line| line.starts_with("  1 |XX ")));
+    }
+
+    #[test]
+    fn sequence_stays_in_columns_73_to_80() {
+        let text = |card: &PunchCard| card.text().iter().collect::<String>();
+        let card = PunchCard::from_str(&IBM029, "HELLO")
+            .unwrap()
+            .with_sequence(&IBM029, 123)
+            .unwrap();
+        assert_eq!(&text(&card)[72..], "     123");
+        assert_eq!(card.columns()[71], CellMask(0));
+
+        // A punched column 80 is left alone.
+        let card = PunchCard::from_str(&IBM029, &format!("{:79}X", ""))
+            .unwrap()
+            .with_sequence(&IBM029, 12_345_678)
+            .unwrap();
+        assert_eq!(&text(&card)[71..], " 1234567X");
+
+        // Nine digits would reach into column 72, or repeat an earlier number
+        // if cut down to eight, so they are refused.
+        let err = PunchCard::from_str(&IBM029, "HELLO")
+            .unwrap()
+            .with_sequence(&IBM029, 123_456_789)
+            .unwrap_err();
+        assert!(matches!(err, EncodeError::SequenceOverflow(123_456_789)));
+    }
+
+    #[test]
+    fn find_reports_case_insensitive_column_ranges() {
+        let card = PunchCard::from_str(&IBM029, "      CALL EXIT").unwrap();
+        let hits = card.find("exit");
+        assert_eq!(hits, vec![ColumnRange { start: 12, end: 15 }]);
+        assert!(card.find("").is_empty());
+    }
+
+    #[test]
+    fn compact_styles_pack_rows_into_fewer_lines() {
+        // A is 12-1 and B is 12-2.
+        let card = PunchCard::from_str(&IBM029, "AB").unwrap();
+        let full = card.render(RenderStyle::AsciiX).lines().count();
+
+        let block = card.render(RenderStyle::Block);
+        let lines: Vec<&str> = block.lines().collect();
+        assert_eq!(lines.len(), full - 6);
+        assert!(lines.iter().any(|line| line.starts_with("12-11 |▀▀ ")));
+        assert!(lines.iter().any(|line| line.starts_with("  0-1 |▄  ")));
+        assert!(lines.iter().any(|line| line.starts_with("  2-3 | ▀ ")));
+
+        let braille = card.render(RenderStyle::Braille);
+        let lines: Vec<&str> = braille.lines().collect();
+        assert_eq!(lines.len(), full - 9);
+        // Rows 12 and 1 of column 1 are dots 1 and 7; row 12 of column 2
+        // is dot 4.
+        let top = format!(" 12-1 |\u{2849}{}|", "\u{2800}".repeat(39));
+        assert!(lines.contains(&top.as_str()));
+        assert!(lines.contains(&"       ....1....2....3....4....5....6....7....8"));
+    }
+}
@@ -0,0 +1,176 @@
+//! Card reader output with deliberate faults (`punch read --inject-errors`).
+//!
+//! Emulators and pipelines that consume reader output need to cope with the
+//! ways real readers fail. [`FaultInjection`] corrupts a card stream with
+//! misread holes, registration offsets and jams, reproducibly from a seed, and
+//! reports every fault it introduced so tests can check what was caught.
+
+use std::fmt;
+
+use anyhow::{Result, ensure};
+
+use crate::core::encoding::{CellMask, PunchEncoding, ROW_NAMES};
+use crate::core::punchcards::PunchCard;
+use crate::core::rng::SplitMix64;
+
+const COLS: usize = 80;
+
+/// Ways a card reader corrupts what it reads.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReaderFault {
+    /// One hole dropped or picked up by a dirty brush or photocell.
+    Misread,
+    /// The card travelled skewed and every column read one column off.
+    Offset,
+    /// The card crumpled mid-read: the rest of it reads blank and the reader
+    /// stops with the remaining cards still in the hopper.
+    Jam,
+}
+
+impl fmt::Display for ReaderFault {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            ReaderFault::Misread => "misread",
+            ReaderFault::Offset => "offset",
+            ReaderFault::Jam => "jam",
+        })
+    }
+}
+
+/// How often and which faults to inject.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FaultInjection {
+    rate: f64,
+    kinds: Vec<ReaderFault>,
+    seed: u64,
+}
+
+/// One fault introduced into the stream.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InjectedFault {
+    /// Zero-based index of the affected card.
+    pub card: usize,
+    pub kind: ReaderFault,
+    /// 1-based column where the fault shows (first column for offsets).
+    pub column: usize,
+    pub detail: String,
+}
+
+/// Cards as the faulty reader delivered them.
+#[derive(Debug, Clone)]
+pub struct ReaderRun {
+    pub cards: Vec<PunchCard>,
+    pub faults: Vec<InjectedFault>,
+    /// Cards left unread in the hopper after a jam.
+    pub unread: usize,
+}
+
+impl FaultInjection {
+    /// Fault each card with probability `rate`, picking evenly among `kinds`.
+    pub fn new(rate: f64, kinds: Vec<ReaderFault>, seed: u64) -> Result<Self> {
+        ensure!(
+            (0.0..=1.0).contains(&rate),
+            "error rate {} must be between 0 and 1",
+            rate
+        );
+        ensure!(!kinds.is_empty(), "no fault kinds selected");
+        Ok(Self { rate, kinds, seed })
+    }
+
+    /// Feed `cards` through the faulty reader. Interpretations of corrupted
+    /// columns are re-decoded with `encoder`, blank where it has no character.
+    pub fn apply(&self, cards: &[PunchCard], encoder: &dyn PunchEncoding) -> ReaderRun {
+        let mut rng = SplitMix64::new(self.seed);
+        let mut run = ReaderRun {
+            cards: Vec::with_capacity(cards.len()),
+            faults: Vec::new(),
+            unread: 0,
+        };
+        for (idx, card) in cards.iter().enumerate() {
+            if rng.unit() >= self.rate {
+                run.cards.push(card.clone());
+                continue;
+            }
+            let kind = self.kinds[rng.below(self.kinds.len() as u64) as usize];
+            let mut columns = *card.columns();
+            let (column, detail) = match kind {
+                ReaderFault::Misread => {
+                    let col = rng.below(COLS as u64) as usize;
+                    let row = ROW_NAMES[rng.below(ROW_NAMES.len() as u64) as usize];
+                    let bit = CellMask::from_rows(&[row]).expect("row from ROW_NAMES").0;
+                    let dropped = columns[col].0 & bit != 0;
+                    columns[col].0 ^= bit;
+                    let what = if dropped { "dropped" } else { "picked up" };
+                    (col + 1, format!("row {} hole {}", row, what))
+                }
+                ReaderFault::Offset => {
+                    if rng.below(2) == 0 {
+                        columns.rotate_right(1);
+                        columns[0] = CellMask(0);
+                        (1, "read one column right".to_string())
+                    } else {
+                        columns.rotate_left(1);
+                        columns[COLS - 1] = CellMask(0);
+                        (1, "read one column left".to_string())
+                    }
+                }
+                ReaderFault::Jam => {
+                    let col = rng.below(COLS as u64) as usize;
+                    columns[col..].fill(CellMask(0));
+                    run.unread = cards.len() - idx - 1;
+                    (
+                        col + 1,
+                        format!("{} card(s) left in the hopper", run.unread),
+                    )
+                }
+            };
+            run.cards
+                .push(PunchCard::from_columns(&columns, |m| encoder.decode(m)));
+            run.faults.push(InjectedFault {
+                card: idx,
+                kind,
+                column,
+                detail,
+            });
+            if kind == ReaderFault::Jam {
+                break;
+            }
+        }
+        run
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::encoding::IBM029;
+
+    #[test]
+    fn injection_is_seeded_and_jams_stop_the_reader() {
+        let cards: Vec<PunchCard> = (0..50)
+            .map(|n| PunchCard::from_str(&IBM029, &format!("CARD {:03}", n)).unwrap())
+            .collect();
+        let clean = FaultInjection::new(0.0, vec![ReaderFault::Misread], 1).unwrap();
+        let run = clean.apply(&cards, &IBM029);
+        assert_eq!((run.cards.len(), run.faults.len()), (50, 0));
+
+        let misread = FaultInjection::new(1.0, vec![ReaderFault::Misread], 7).unwrap();
+        let first = misread.apply(&cards, &IBM029);
+        let again = misread.apply(&cards, &IBM029);
+        assert_eq!(first.faults, again.faults);
+        assert_eq!(first.faults.len(), 50);
+        let fault = &first.faults[0];
+        let col = fault.column - 1;
+        assert_ne!(first.cards[0].columns()[col], cards[0].columns()[col]);
+
+        let jam = FaultInjection::new(0.2, vec![ReaderFault::Jam], 3).unwrap();
+        let run = jam.apply(&cards, &IBM029);
+        let last = run.faults.last().unwrap();
+        assert_eq!(run.faults.len(), 1);
+        assert_eq!(run.cards.len(), last.card + 1);
+        assert_eq!(run.unread, 50 - run.cards.len());
+
+        assert!(FaultInjection::new(1.5, vec![ReaderFault::Jam], 0).is_err());
+        assert!(FaultInjection::new(0.1, Vec::new(), 0).is_err());
+    }
+}
@@ -0,0 +1,190 @@
+//! Moving a whole deck from one keypunch chart to another (`punch deck
+//! convert-encoding`).
+//!
+//! A deck keyed on an 026 keeps its characters when it is repunched on an
+//! 029, but several of them get new holes and some (the 026 has no `<` or
+//! `!`) have none at all. [`Deck::convert_encoding`] repunches text cards
+//! and punch-only cards under the target chart, reports every column with no
+//! equivalent and applies an [`InvalidCharMode`] to them.
+
+use anyhow::{Context, Result, anyhow};
+
+use crate::core::deck::{CardRecord, Deck, EncodingKind};
+use crate::core::encoding::{CellMask, PunchEncoding};
+use crate::core::formats::{punches_from_hex, punches_to_hex};
+use crate::core::machine::InvalidCharMode;
+
+/// Column with no equivalent under the target chart.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Unmapped {
+    /// Zero-based card index.
+    pub card: usize,
+    /// 1-based column.
+    pub column: usize,
+    /// The character, or `None` for holes the source chart cannot read.
+    pub ch: Option<char>,
+    /// Holes of the column under the source chart.
+    pub mask: CellMask,
+}
+
+/// Outcome of [`Deck::convert_encoding`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EncodingConversion {
+    /// Cards whose holes differ under the target chart.
+    pub repunched: usize,
+    pub unmapped: Vec<Unmapped>,
+    /// Whether the deck was changed: always in substitute mode, and in halt
+    /// mode only when every column has an equivalent.
+    pub applied: bool,
+}
+
+impl Deck {
+    /// Repunch every Hollerith card under `to` instead of `from`.
+    ///
+    /// Text cards keep their characters; punch-only cards are read under
+    /// `from` and punched again under `to`. Columns with no equivalent are
+    /// replaced by the substitute character in substitute mode; in halt mode
+    /// the deck is left untouched when there are any. ASCII and EBCDIC cards
+    /// have their own charts and are not affected. Protected columns must
+    /// keep their holes. The caller records the new chart in the header.
+    pub fn convert_encoding(
+        &mut self,
+        from: &dyn PunchEncoding,
+        to: &dyn PunchEncoding,
+        on_missing: InvalidCharMode,
+    ) -> Result<EncodingConversion> {
+        let substitute = match on_missing {
+            InvalidCharMode::Substitute(ch) => Some(
+                to.encode_char(ch)
+                    .map(|mask| (ch, mask))
+                    .map_err(|_| anyhow!("substitute '{}' has no {} punch code", ch, to.name()))?,
+            ),
+            InvalidCharMode::Halt => None,
+        };
+        self.ensure_mutable()?;
+        let mut report = EncodingConversion::default();
+        let mut changed = Vec::new();
+        for (idx, card) in self.cards.iter().enumerate() {
+            let before = card.to_punch_card(from).ok();
+            let mut next = card.clone();
+            if card.encoding == EncodingKind::Hollerith {
+                let missing = repunch(&mut next, from, to, substitute)?;
+                report
+                    .unmapped
+                    .extend(missing.into_iter().map(|(column, ch, mask)| Unmapped {
+                        card: idx,
+                        column,
+                        ch,
+                        mask,
+                    }));
+            }
+            let after = next.to_punch_card(to).ok();
+            if before.map(|p| p.columns().to_vec()) != after.map(|p| p.columns().to_vec()) {
+                report.repunched += 1;
+            }
+            if next != *card {
+                changed.push((idx, next));
+            }
+        }
+        report.applied = substitute.is_some() || report.unmapped.is_empty();
+        if report.applied {
+            for (idx, card) in changed {
+                self.replace_card(idx, card)
+                    .with_context(|| format!("card {}", idx + 1))?;
+            }
+        }
+        Ok(report)
+    }
+}
+
+/// Repunch one card in place, returning its columns with no equivalent as
+/// `(column, character, source holes)`.
+fn repunch(
+    card: &mut CardRecord,
+    from: &dyn PunchEncoding,
+    to: &dyn PunchEncoding,
+    substitute: Option<(char, CellMask)>,
+) -> Result<Vec<(usize, Option<char>, CellMask)>> {
+    let mut missing = Vec::new();
+    if let Some(text) = &card.text {
+        let mut chars: Vec<char> = text.chars().collect();
+        for (col, ch) in chars.iter_mut().enumerate() {
+            if !to.is_supported(*ch) {
+                let mask = from.encode_char(*ch).unwrap_or(CellMask(0));
+                missing.push((col + 1, Some(*ch), mask));
+                if let Some((sub, _)) = substitute {
+                    *ch = sub;
+                }
+            }
+        }
+        card.text = Some(chars.into_iter().collect());
+    } else if let Some(hex) = &card.punches {
+        let mut masks = punches_from_hex(hex)?;
+        for (col, mask) in masks.iter_mut().enumerate() {
+            if mask.0 == 0 {
+                continue;
+            }
+            let ch = from.decode(*mask);
+            match ch.and_then(|ch| to.encode_char(ch).ok()) {
+                Some(target) => *mask = target,
+                None => {
+                    missing.push((col + 1, ch, *mask));
+                    if let Some((_, sub)) = substitute {
+                        *mask = sub;
+                    }
+                }
+            }
+        }
+        card.punches = Some(punches_to_hex(&masks));
+    }
+    Ok(missing)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::deck::{CardType, DeckHeader};
+    use crate::core::encoding::{IBM026_FORTRAN, IBM029};
+
+    #[test]
+    fn conversion_repunches_and_reports_missing_characters() {
+        let mut deck = Deck::from_lines(
+            DeckHeader::new(None, None, Vec::new()),
+            ["A=B+C", "IF (X<Y) GO TO 5"],
+            EncodingKind::Hollerith,
+            CardType::Code,
+        )
+        .unwrap();
+        let holes = IBM026_FORTRAN.encode_char('=').unwrap();
+        deck.cards
+            .push(CardRecord::from_punches(&[holes], CardType::Data));
+        let original = deck.cards.clone();
+
+        let report = deck
+            .convert_encoding(&IBM029, &IBM026_FORTRAN, InvalidCharMode::Halt)
+            .unwrap();
+        assert!(!report.applied);
+        assert_eq!(deck.cards, original);
+        let missing: Vec<(usize, usize, Option<char>)> = report
+            .unmapped
+            .iter()
+            .map(|u| (u.card, u.column, u.ch))
+            .collect();
+        assert!(missing.contains(&(1, 6, Some('<'))));
+
+        let report = deck
+            .convert_encoding(&IBM026_FORTRAN, &IBM029, InvalidCharMode::Substitute('*'))
+            .unwrap();
+        assert!(report.applied);
+        // '=' keeps its character on text cards and moves to the 029 holes
+        // on the punch-only card.
+        let card = deck.cards[2].to_punch_card(&IBM029).unwrap();
+        assert_eq!(card.columns()[0], IBM029.encode_char('=').unwrap());
+        assert_eq!(deck.cards[0].text, original[0].text);
+        assert!(report.repunched >= 2);
+        assert!(
+            deck.convert_encoding(&IBM029, &IBM026_FORTRAN, InvalidCharMode::Substitute('<'))
+                .is_err()
+        );
+    }
+}
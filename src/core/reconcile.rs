@@ -0,0 +1,187 @@
+//! Reconciling two independent keyings of the same physical deck.
+//!
+//! Archives were often keyed twice so the copies could be checked against
+//! each other. Cards are aligned on a key field (usually the sequence number
+//! in columns 73–80); aligned cards are compared column by column and a
+//! consensus deck is built in which every disputed column is flagged.
+
+use anyhow::{Context, Result};
+
+use crate::core::deck::{CardMeta, ColumnRange, Deck, DeckHeader};
+use crate::core::diff::lcs_pairs;
+
+/// Columns per card compared by [`reconcile`].
+const COLUMNS: usize = 80;
+
+/// Aligned card pair whose texts differ.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DisputedCard {
+    /// Key field text, trimmed.
+    pub key: String,
+    /// Index of the card in the consensus deck.
+    pub card: usize,
+    /// 1-based columns where the keyings disagree.
+    pub columns: Vec<usize>,
+}
+
+/// Outcome of [`reconcile`].
+#[derive(Debug, Clone)]
+pub struct Reconciliation {
+    pub key_cols: ColumnRange,
+    /// Cards aligned by key.
+    pub matched: usize,
+    /// Keys of cards found only in the first or only in the second keying.
+    pub only_a: Vec<String>,
+    pub only_b: Vec<String>,
+    /// For each column, how many aligned pairs agree on it.
+    pub agreement: Vec<usize>,
+    pub disputed: Vec<DisputedCard>,
+    /// Aligned cards carry the first keying's text; disputed cards are
+    /// colored red with the disputed columns in their note. Unmatched cards
+    /// keep their position and are colored amber.
+    pub consensus: Deck,
+}
+
+impl Reconciliation {
+    /// Share of aligned pairs that agree on `col` (1-based), in percent.
+    pub fn agreement_pct(&self, col: usize) -> f64 {
+        if self.matched == 0 {
+            return 100.0;
+        }
+        self.agreement[col - 1] as f64 * 100.0 / self.matched as f64
+    }
+}
+
+/// Align `a` and `b` on the text in `key_cols` and compare aligned cards.
+///
+/// Cards are matched in order, so repeated keys pair up first-to-first.
+/// Cards stored only as punches are compared by their IBM 029 reading.
+pub fn reconcile(a: &Deck, b: &Deck, key_cols: ColumnRange) -> Result<Reconciliation> {
+    let texts = |deck: &Deck| -> Result<Vec<Vec<char>>> {
+        Ok(deck
+            .as_text()?
+            .iter()
+            .map(|text| {
+                let mut cols: Vec<char> = text.chars().collect();
+                cols.resize(COLUMNS, ' ');
+                cols
+            })
+            .collect())
+    };
+    let left = texts(a).context("deck A")?;
+    let right = texts(b).context("deck B")?;
+    let key = |cols: &[char]| -> String {
+        cols[key_cols.start - 1..key_cols.end]
+            .iter()
+            .collect::<String>()
+            .trim()
+            .to_string()
+    };
+    let left_keys: Vec<String> = left.iter().map(|c| key(c)).collect();
+    let right_keys: Vec<String> = right.iter().map(|c| key(c)).collect();
+    let pairs = lcs_pairs(&left_keys, &right_keys);
+
+    let mut result = Reconciliation {
+        key_cols,
+        matched: pairs.len(),
+        only_a: Vec::new(),
+        only_b: Vec::new(),
+        agreement: vec![0; COLUMNS],
+        disputed: Vec::new(),
+        consensus: Deck::new(DeckHeader::new(
+            a.header.language.clone(),
+            a.header.template.clone(),
+            a.header.protected_cols.clone(),
+        )),
+    };
+    let flag = |color: &str, note: String| CardMeta {
+        color: Some(color.to_string()),
+        note: Some(note),
+        ..CardMeta::default()
+    };
+    let (mut i, mut j) = (0, 0);
+    for (pi, pj) in pairs
+        .into_iter()
+        .chain(std::iter::once((left.len(), right.len())))
+    {
+        for (card, key) in a.cards[i..pi].iter().zip(&left_keys[i..pi]) {
+            let mut card = card.clone();
+            card.meta = flag("amber", "only in A".to_string());
+            result.only_a.push(key.clone());
+            result.consensus.cards.push(card);
+        }
+        for (card, key) in b.cards[j..pj].iter().zip(&right_keys[j..pj]) {
+            let mut card = card.clone();
+            card.meta = flag("amber", "only in B".to_string());
+            result.only_b.push(key.clone());
+            result.consensus.cards.push(card);
+        }
+        if pi == left.len() {
+            break;
+        }
+        let columns: Vec<usize> = (0..COLUMNS)
+            .filter(|col| left[pi][*col] != right[pj][*col])
+            .map(|col| col + 1)
+            .collect();
+        for col in 0..COLUMNS {
+            if !columns.contains(&(col + 1)) {
+                result.agreement[col] += 1;
+            }
+        }
+        let mut card = a.cards[pi].clone();
+        if !columns.is_empty() {
+            let list: Vec<String> = columns.iter().map(|c| c.to_string()).collect();
+            card.meta = flag("red", format!("disputed columns {}", list.join(",")));
+            result.disputed.push(DisputedCard {
+                key: left_keys[pi].clone(),
+                card: result.consensus.cards.len(),
+                columns,
+            });
+        }
+        result.consensus.cards.push(card);
+        (i, j) = (pi + 1, pj + 1);
+    }
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::deck::{CardType, EncodingKind};
+
+    fn keyed(lines: &[&str]) -> Deck {
+        let mut deck = Deck::from_lines(
+            DeckHeader::new(None, None, Vec::new()),
+            lines.iter().copied(),
+            EncodingKind::Hollerith,
+            CardType::Data,
+        )
+        .unwrap();
+        deck.number_sequence(10, 10).unwrap();
+        deck
+    }
+
+    #[test]
+    fn reconcile_aligns_by_key_and_flags_disputes() {
+        let a = keyed(&["ALPHA 100", "BRAVO 200", "CHARLIE 300"]);
+        let mut b = keyed(&["ALPHA 100", "BRAVO 209", "CHARLIE 300", "DELTA 400"]);
+        b.cards.remove(0);
+        let result = reconcile(&a, &b, ColumnRange::new(73, 80).unwrap()).unwrap();
+        assert_eq!(result.matched, 2);
+        assert_eq!(result.only_a, vec!["10"]);
+        assert_eq!(result.only_b, vec!["40"]);
+        assert_eq!(result.disputed.len(), 1);
+        assert_eq!(result.disputed[0].key, "20");
+        assert_eq!(result.disputed[0].columns, vec![9]);
+        assert_eq!(result.agreement[8], 1);
+        assert_eq!(result.agreement_pct(1), 100.0);
+        assert_eq!(result.consensus.cards.len(), 4);
+        assert_eq!(
+            result.consensus.cards[result.disputed[0].card]
+                .meta
+                .color
+                .as_deref(),
+            Some("red")
+        );
+    }
+}
@@ -0,0 +1,113 @@
+//! Overwriting sensitive columns before a deck is published (`punch deck redact`).
+//!
+//! Historical data decks often hold names or account numbers in fixed
+//! fields. Redaction overwrites a column range with one character on the
+//! selected cards and scrubs the same columns from the audit history, where
+//! earlier edits recorded the old text.
+
+use anyhow::{Context, Result};
+
+use crate::core::deck::{AuditEvent, CardRecord, ColumnRange};
+use crate::core::encoding::{CellMask, PunchEncoding};
+use crate::core::formats::{punches_from_hex, punches_to_hex};
+
+/// Overwrite `cols` of `card` with `with`. Text cards get the character;
+/// punches-only cards get its holes under `encoder`.
+pub fn redact_card(
+    card: &CardRecord,
+    cols: ColumnRange,
+    with: char,
+    encoder: &dyn PunchEncoding,
+) -> Result<CardRecord> {
+    let mut redacted = card.clone();
+    if let Some(text) = &card.text {
+        let mut chars: Vec<char> = text.chars().collect();
+        chars.resize(chars.len().max(cols.end), ' ');
+        chars[cols.start - 1..cols.end].fill(with);
+        redacted.text = Some(chars.into_iter().collect());
+    } else if let Some(hex) = &card.punches {
+        let mask = encoder
+            .encode_char(with)
+            .with_context(|| format!("{:?} cannot be punched on {}", with, encoder.name()))?;
+        let mut columns: Vec<CellMask> = punches_from_hex(hex)?;
+        columns.resize(columns.len().max(cols.end), CellMask(0));
+        columns[cols.start - 1..cols.end].fill(mask);
+        redacted.punches = Some(punches_to_hex(&columns));
+    }
+    Ok(redacted)
+}
+
+/// Overwrite `cols` in the column changes recorded in `history`, and in the
+/// card text kept by `card remove` entries. Returns the number of entries
+/// touched.
+pub fn scrub_history(history: &mut [AuditEvent], cols: ColumnRange, with: char) -> usize {
+    let mut touched = 0;
+    for event in history {
+        let mut changed = false;
+        for change in &mut event.changes {
+            for text in [&mut change.before, &mut change.after] {
+                let scrubbed: String = text
+                    .chars()
+                    .enumerate()
+                    .map(|(idx, ch)| {
+                        if cols.contains(change.columns.start + idx) {
+                            with
+                        } else {
+                            ch
+                        }
+                    })
+                    .collect();
+                changed |= scrubbed != *text;
+                *text = scrubbed;
+            }
+        }
+        // `card remove N: TEXT` keeps the removed card's text from column 1.
+        if let Some((prefix, text)) = event.action.split_once(": ")
+            && prefix.starts_with("card remove ")
+        {
+            let mut chars: Vec<char> = text.chars().collect();
+            for col in cols.start..=cols.end.min(chars.len()) {
+                changed |= chars[col - 1] != with;
+                chars[col - 1] = with;
+            }
+            event.action = format!("{}: {}", prefix, chars.into_iter().collect::<String>());
+        }
+        touched += usize::from(changed);
+    }
+    touched
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::deck::{CardType, EncodingKind};
+    use crate::core::diff::diff_columns;
+    use crate::core::encoding::IBM029;
+
+    #[test]
+    fn redaction_covers_cards_and_history() {
+        let cols = ColumnRange::new(6, 10).unwrap();
+        let card =
+            CardRecord::from_text("0001 SMITH    42", EncodingKind::Hollerith, CardType::Data)
+                .unwrap();
+        let redacted = redact_card(&card, cols, 'X', &IBM029).unwrap();
+        assert!(redacted.text.unwrap().starts_with("0001 XXXXX    42"));
+
+        let binary = CardRecord::from_punches(&[CellMask(1); 80], CardType::Data);
+        let redacted = redact_card(&binary, cols, 'X', &IBM029).unwrap();
+        let holes = punches_from_hex(redacted.punches.as_deref().unwrap()).unwrap();
+        assert_eq!(holes[5], IBM029.encode_char('X').unwrap());
+        assert_eq!(holes[10], CellMask(1));
+
+        let mut edit = AuditEvent::new("card replace 1");
+        edit.changes = diff_columns("0001 SMITH", "0001 JONES");
+        let mut history = vec![
+            edit,
+            AuditEvent::new("card remove 2: 0002 BROWN    17"),
+            AuditEvent::new("seq number"),
+        ];
+        assert_eq!(scrub_history(&mut history, cols, 'X'), 2);
+        assert_eq!(history[0].changes[0].before, "XXXXX");
+        assert_eq!(history[1].action, "card remove 2: 0002 XXXXX    17");
+    }
+}
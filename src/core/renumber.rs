@@ -0,0 +1,190 @@
+//! Reviewable renumbering plans (`punch seq plan` / `punch seq apply`).
+//!
+//! When other documents cite a deck's sequence numbers (listings, change
+//! logs, patch cards punched elsewhere), renumbering should be reviewed before
+//! it happens. A [`RenumberPlan`] records the old and new number of every card
+//! as JSON; applying it refuses cards whose number changed since planning.
+
+use std::collections::HashSet;
+use std::path::PathBuf;
+
+use anyhow::{Context, Result, anyhow, bail};
+use serde::{Deserialize, Serialize};
+
+use crate::core::deck::{Deck, sequence_field};
+
+/// Proposed number for one card.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RenumberEntry {
+    /// 1-based position of the card in the deck.
+    pub card: usize,
+    /// Number the card carries now.
+    pub old: Option<usize>,
+    pub new: usize,
+}
+
+impl RenumberEntry {
+    /// Whether applying the entry changes the card's number.
+    pub fn changes(&self) -> bool {
+        self.old != Some(self.new)
+    }
+}
+
+/// Old→new sequence mapping for a deck.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RenumberPlan {
+    /// Deck the plan was made for.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deck: Option<PathBuf>,
+    pub start: usize,
+    pub step: usize,
+    pub cards: Vec<RenumberEntry>,
+}
+
+impl RenumberPlan {
+    /// Plan numbering every card of `deck` from `start` in steps of `step`,
+    /// as `seq number` would.
+    pub fn new(deck: &Deck, start: usize, step: usize) -> Self {
+        let cards = deck
+            .cards
+            .iter()
+            .enumerate()
+            .map(|(idx, card)| RenumberEntry {
+                card: idx + 1,
+                old: card.seq,
+                new: start.saturating_add(idx.saturating_mul(step)),
+            })
+            .collect();
+        Self {
+            deck: deck.path.clone(),
+            start,
+            step,
+            cards,
+        }
+    }
+
+    /// Entries that change a card's number.
+    pub fn changes(&self) -> impl Iterator<Item = &RenumberEntry> {
+        self.cards.iter().filter(|entry| entry.changes())
+    }
+
+    /// Check the plan against `deck`: every card must exist and still carry
+    /// its planned old number, and no two cards may get the same new one or
+    /// one too wide for the sequence field.
+    pub fn check(&self, deck: &Deck) -> Result<()> {
+        let mut seen = HashSet::new();
+        for entry in &self.cards {
+            let card = entry
+                .card
+                .checked_sub(1)
+                .and_then(|idx| deck.cards.get(idx))
+                .ok_or_else(|| {
+                    anyhow!(
+                        "plan names card {}, the deck has {}",
+                        entry.card,
+                        deck.cards.len()
+                    )
+                })?;
+            if card.seq != entry.old {
+                bail!(
+                    "card {} is numbered {} but the plan expects {}; make a new plan",
+                    entry.card,
+                    seq_label(card.seq),
+                    seq_label(entry.old)
+                );
+            }
+            sequence_field(entry.new).with_context(|| format!("card {}", entry.card))?;
+            if !seen.insert(entry.new) {
+                bail!(
+                    "the plan gives sequence {} to more than one card",
+                    entry.new
+                );
+            }
+        }
+        Ok(())
+    }
+
+    /// Renumber `deck` as planned. Returns the number of cards whose number
+    /// changed.
+    pub fn apply(&self, deck: &mut Deck) -> Result<usize> {
+        deck.ensure_mutable()?;
+        self.check(deck)?;
+        for entry in self.changes() {
+            deck.cards[entry.card - 1].set_sequence(entry.new)?;
+        }
+        Ok(self.changes().count())
+    }
+}
+
+/// A sequence number for messages, `none` for unnumbered cards.
+fn seq_label(seq: Option<usize>) -> String {
+    seq.map_or_else(|| "none".to_string(), |seq| seq.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::deck::{CardType, DeckHeader, EncodingKind, sequence_field};
+
+    #[test]
+    fn plans_apply_only_to_the_deck_they_were_made_for() {
+        let mut deck = Deck::from_lines(
+            DeckHeader::new(None, None, Vec::new()),
+            ["A", "B", "C"],
+            EncodingKind::Hollerith,
+            CardType::Code,
+        )
+        .unwrap();
+        deck.number_sequence(10, 10).unwrap();
+        deck.cards[2].set_sequence(25).unwrap();
+
+        let plan = RenumberPlan::new(&deck, 10, 10);
+        let changed: Vec<(usize, usize)> = plan.changes().map(|e| (e.card, e.new)).collect();
+        assert_eq!(changed, [(3, 30)]);
+        let json = serde_json::to_string(&plan).unwrap();
+        assert_eq!(serde_json::from_str::<RenumberPlan>(&json).unwrap(), plan);
+
+        let mut stale = deck.clone();
+        stale.cards[0].set_sequence(5).unwrap();
+        assert!(plan.apply(&mut stale).is_err());
+
+        let mut clash = plan.clone();
+        clash.cards[2].new = 20;
+        assert!(clash.check(&deck).is_err());
+
+        assert_eq!(plan.apply(&mut deck).unwrap(), 1);
+        assert_eq!(deck.cards[2].seq, Some(30));
+        assert!(
+            deck.cards[2]
+                .text
+                .as_deref()
+                .unwrap()
+                .ends_with(&sequence_field(30).unwrap())
+        );
+    }
+
+    #[test]
+    fn numbers_wider_than_the_field_are_refused_up_front() {
+        let mut deck = Deck::from_lines(
+            DeckHeader::new(None, None, Vec::new()),
+            ["A", "B", "C"],
+            EncodingKind::Hollerith,
+            CardType::Code,
+        )
+        .unwrap();
+        let before = deck.cards.clone();
+
+        let err = deck.number_sequence(99_999_990, 5).unwrap_err();
+        assert!(format!("{err:#}").contains("card 3"));
+        assert_eq!(deck.cards, before);
+
+        let plan = RenumberPlan::new(&deck, 99_999_990, 5);
+        assert!(plan.apply(&mut deck).is_err());
+        assert_eq!(deck.cards, before);
+        assert!(
+            RenumberPlan::new(&deck, 1, usize::MAX)
+                .check(&deck)
+                .is_err()
+        );
+    }
+}
@@ -0,0 +1,305 @@
+//! Searching card text (`punch deck grep`, [`Deck::find`]).
+//!
+//! Finding the card that declares a COBOL paragraph or sets a FORTRAN
+//! variable used to mean exporting the deck to text first. A [`Query`]
+//! matches plain text or a regular expression, optionally only within a
+//! column field (so sequence numbers in 73-80 never match) and only on cards
+//! of given types. Cards stored only as punches are searched as read by the
+//! deck's encoder (see [`CardRecord::text_view`](crate::core::deck::CardRecord::text_view)).
+//!
+//! [`Query::punches`] searches holes instead of text (`punch deck
+//! grep-punches`): every column punched with a given multipunch, such as the
+//! 12-0 or 12-11 combinations that mark control cards and binary records.
+
+use anyhow::{Context, Result, anyhow};
+use regex::{Regex, RegexBuilder};
+use serde::Serialize;
+
+use crate::core::deck::{CardType, ColumnRange, Deck};
+use crate::core::encoding::CellMask;
+
+/// What a [`Query`] looks for.
+#[derive(Debug, Clone)]
+pub enum Pattern {
+    /// Literal text.
+    Text {
+        needle: String,
+        ignore_case: bool,
+    },
+    Regex(Regex),
+    /// Columns punched with at least these holes, or exactly these with `exact`.
+    Punches {
+        mask: CellMask,
+        exact: bool,
+    },
+}
+
+/// Search over the cards of a deck.
+#[derive(Debug, Clone)]
+pub struct Query {
+    pub pattern: Pattern,
+    /// Only search these columns; regex anchors match at the field edges.
+    pub cols: Option<ColumnRange>,
+    /// Only search cards of these types (every card when empty).
+    pub card_types: Vec<CardType>,
+}
+
+impl Query {
+    /// Match `needle` literally.
+    pub fn text(needle: &str, ignore_case: bool) -> Self {
+        Self::with_pattern(Pattern::Text {
+            needle: needle.to_string(),
+            ignore_case,
+        })
+    }
+
+    /// Match the regular expression `pattern`.
+    pub fn regex(pattern: &str, ignore_case: bool) -> Result<Self> {
+        let regex = RegexBuilder::new(pattern)
+            .case_insensitive(ignore_case)
+            .build()
+            .with_context(|| format!("invalid regular expression {:?}", pattern))?;
+        Ok(Self::with_pattern(Pattern::Regex(regex)))
+    }
+
+    /// Match columns punched with the rows in `spec` (chart notation such as
+    /// `12-0`), alone or with other holes unless `exact`.
+    pub fn punches(spec: &str, exact: bool) -> Result<Self> {
+        let mask = CellMask::from_row_spec(spec).ok_or_else(|| {
+            anyhow!(
+                "'{}' is not a punch pattern (rows 12, 11, 0-9 joined by '-')",
+                spec
+            )
+        })?;
+        if mask.0 == 0 {
+            return Err(anyhow!("a punch pattern needs at least one row"));
+        }
+        Ok(Self::with_pattern(Pattern::Punches { mask, exact }))
+    }
+
+    fn with_pattern(pattern: Pattern) -> Self {
+        Self {
+            pattern,
+            cols: None,
+            card_types: Vec::new(),
+        }
+    }
+
+    /// Restrict the search to `cols`.
+    pub fn in_cols(mut self, cols: ColumnRange) -> Self {
+        self.cols = Some(cols);
+        self
+    }
+
+    /// Restrict the search to cards of `types`.
+    pub fn of_types(mut self, types: Vec<CardType>) -> Self {
+        self.card_types = types;
+        self
+    }
+
+    /// Matches in one line of card text, as 1-based column ranges.
+    fn find_in(&self, text: &str) -> Vec<ColumnRange> {
+        let chars: Vec<char> = text.chars().collect();
+        let (first, last) = match self.cols {
+            Some(cols) => (cols.start, cols.end.min(chars.len())),
+            None => (1, chars.len()),
+        };
+        if first > last {
+            return Vec::new();
+        }
+        let field: String = chars[first - 1..last].iter().collect();
+        // Byte offsets within `field` to columns.
+        let column = |byte: usize| first + field[..byte].chars().count();
+        let spans: Vec<(usize, usize)> = match &self.pattern {
+            Pattern::Regex(regex) => regex
+                .find_iter(&field)
+                .filter(|found| !found.is_empty())
+                .map(|found| (found.start(), found.end()))
+                .collect(),
+            // Hole patterns are matched by `find_holes`.
+            Pattern::Punches { .. } => Vec::new(),
+            Pattern::Text { needle, .. } if needle.is_empty() => Vec::new(),
+            Pattern::Text {
+                needle,
+                ignore_case,
+            } => {
+                let (haystack, needle) = if *ignore_case {
+                    (field.to_ascii_uppercase(), needle.to_ascii_uppercase())
+                } else {
+                    (field.clone(), needle.clone())
+                };
+                haystack
+                    .match_indices(needle.as_str())
+                    .map(|(start, found)| (start, start + found.len()))
+                    .collect()
+            }
+        };
+        spans
+            .into_iter()
+            .filter_map(|(start, end)| ColumnRange::new(column(start), column(end) - 1).ok())
+            .collect()
+    }
+
+    /// Runs of columns whose holes match a [`Pattern::Punches`] query.
+    fn find_holes(&self, columns: &[CellMask]) -> Vec<ColumnRange> {
+        let Pattern::Punches { mask, exact } = self.pattern else {
+            return Vec::new();
+        };
+        let cols = self
+            .cols
+            .unwrap_or_else(|| ColumnRange::new(1, columns.len()).expect("cards have columns"));
+        let mut runs: Vec<ColumnRange> = Vec::new();
+        for (idx, holes) in columns.iter().enumerate() {
+            let col = idx + 1;
+            let hit = if exact {
+                *holes == mask
+            } else {
+                holes.0 & mask.0 == mask.0
+            };
+            if !hit || col < cols.start || col > cols.end {
+                continue;
+            }
+            match runs.last_mut() {
+                Some(run) if run.end + 1 == col => run.end = col,
+                _ => runs.extend(ColumnRange::new(col, col)),
+            }
+        }
+        runs
+    }
+}
+
+/// Card with at least one match.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CardMatch {
+    /// Zero-based card index.
+    pub card: usize,
+    pub seq: Option<usize>,
+    /// Matched columns, left to right.
+    pub columns: Vec<ColumnRange>,
+}
+
+impl Deck {
+    /// Cards matching `query`, in deck order. A card that cannot be read,
+    /// such as one with corrupt stored punches, fails the search.
+    pub fn find(&self, query: &Query) -> Result<Vec<CardMatch>> {
+        let encoder = self.text_encoder();
+        let mut found = Vec::new();
+        for (idx, card) in self.cards.iter().enumerate() {
+            if !query.card_types.is_empty() && !query.card_types.contains(&card.card_type) {
+                continue;
+            }
+            let columns = match query.pattern {
+                Pattern::Punches { .. } => card
+                    .to_punch_card(encoder)
+                    .map(|punched| query.find_holes(punched.columns()))
+                    .with_context(|| format!("card {}", idx + 1))?,
+                _ => query.find_in(
+                    &card
+                        .text_view(encoder)
+                        .with_context(|| format!("card {}", idx + 1))?,
+                ),
+            };
+            if !columns.is_empty() {
+                found.push(CardMatch {
+                    card: idx,
+                    seq: card.seq,
+                    columns,
+                });
+            }
+        }
+        Ok(found)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::deck::{CardRecord, DeckHeader, EncodingKind};
+
+    #[test]
+    fn queries_match_text_regex_columns_and_types() {
+        let mut deck = Deck::from_lines(
+            DeckHeader::new(None, None, Vec::new()),
+            [
+                "       MAIN-LOOP.",
+                "           PERFORM MAIN-LOOP UNTIL DONE.",
+                "      * MAIN-LOOP READS ONE RECORD",
+            ],
+            EncodingKind::Hollerith,
+            CardType::Code,
+        )
+        .unwrap();
+        deck.cards[2].card_type = CardType::Comment;
+        deck.number_sequence(10, 10).unwrap();
+
+        let cards = |query: &Query| -> Vec<usize> {
+            deck.find(query)
+                .unwrap()
+                .into_iter()
+                .map(|m| m.card)
+                .collect()
+        };
+        assert_eq!(cards(&Query::text("main-loop", true)), [0, 1, 2]);
+        assert!(cards(&Query::text("main-loop", false)).is_empty());
+        let paragraph = Query::regex(r"^ MAIN-LOOP\.", false)
+            .unwrap()
+            .in_cols(ColumnRange::new(7, 72).unwrap());
+        assert_eq!(cards(&paragraph), [0]);
+        let code = Query::text("MAIN-LOOP", false).of_types(vec![CardType::Code]);
+        assert_eq!(cards(&code), [0, 1]);
+        // Sequence numbers sit outside 7-72.
+        assert_eq!(cards(&Query::text("20", false)), [1]);
+        let body = Query::text("20", false).in_cols(ColumnRange::new(7, 72).unwrap());
+        assert!(cards(&body).is_empty());
+
+        let found = deck.find(&Query::text("MAIN-LOOP", false)).unwrap();
+        assert_eq!(found[1].columns, [ColumnRange::new(20, 28).unwrap()]);
+        assert_eq!(found[1].seq, Some(20));
+        assert!(Query::regex("(", false).is_err());
+    }
+
+    #[test]
+    fn punch_queries_match_multipunch_columns() {
+        let mut deck = Deck::from_lines(
+            DeckHeader::new(None, None, Vec::new()),
+            ["A?B?", "ZZ"],
+            EncodingKind::Hollerith,
+            CardType::Code,
+        )
+        .unwrap();
+        // '?' is 0-8-7 on the 029; a binary card with 12-0 and 12-0-1 columns.
+        let mut columns = vec![CellMask(0); 80];
+        for col in [9, 10, 40] {
+            columns[col] = CellMask::from_rows(&[12, 0]).unwrap();
+        }
+        columns[11] = CellMask::from_rows(&[12, 0, 1]).unwrap();
+        deck.cards
+            .push(CardRecord::from_punches(&columns, CardType::Data));
+
+        let found = deck.find(&Query::punches("12-0", false).unwrap()).unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].card, 2);
+        let runs: Vec<(usize, usize)> = found[0].columns.iter().map(|c| (c.start, c.end)).collect();
+        assert_eq!(runs, [(10, 12), (41, 41)]);
+        let exact = deck
+            .find(
+                &Query::punches("12-0", true)
+                    .unwrap()
+                    .in_cols(ColumnRange::new(11, 80).unwrap()),
+            )
+            .unwrap();
+        let runs: Vec<(usize, usize)> = exact[0].columns.iter().map(|c| (c.start, c.end)).collect();
+        assert_eq!(runs, [(11, 11), (41, 41)]);
+        let query = Query::punches("0-8-7", true).unwrap();
+        let found = deck.find(&query).unwrap();
+        assert_eq!(
+            found[0].columns,
+            [
+                ColumnRange::new(2, 2).unwrap(),
+                ColumnRange::new(4, 4).unwrap()
+            ]
+        );
+        assert!(Query::punches("12-13", false).is_err());
+        assert!(Query::punches("blank", false).is_err());
+    }
+}
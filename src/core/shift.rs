@@ -0,0 +1,211 @@
+//! Column shift (registration error) detection and correction.
+//!
+//! A card fed a column off in the keypunch carries every field one or more
+//! columns from where the template puts it. Runs of such cards show up as
+//! blocks whose character classes line up with the rest of the deck only
+//! after sliding them sideways; [`detect_shifts`] finds those blocks and
+//! [`unshift_card`] moves the content back.
+
+use anyhow::{Context, Result};
+
+use crate::core::deck::{CardRecord, ColumnRange, Deck};
+use crate::core::encoding::{CellMask, PunchEncoding};
+use crate::core::formats::{punches_from_hex, punches_to_hex};
+
+const COLS: usize = 80;
+
+/// Columns a shift must explain better than the unshifted card before a
+/// card counts as shifted.
+const MIN_GAIN: usize = 4;
+
+/// Run of consecutive cards that appear shifted by the same amount.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShiftedBlock {
+    /// Zero-based first and last card of the run.
+    pub first: usize,
+    pub last: usize,
+    /// Columns the content sits right of its template position (negative: left).
+    pub by: isize,
+    /// Smallest number of extra field columns the shift explains on a card.
+    pub gain: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Class {
+    Blank,
+    Digit,
+    Letter,
+    Other,
+}
+
+fn class(ch: char) -> Class {
+    match ch {
+        ' ' => Class::Blank,
+        c if c.is_ascii_digit() => Class::Digit,
+        c if c.is_alphabetic() => Class::Letter,
+        _ => Class::Other,
+    }
+}
+
+/// Find blocks of at least `min_run` cards whose content lines up with the
+/// deck's column profile only after shifting by up to `max_shift` columns.
+///
+/// The profile is the majority character class of every column inside
+/// `fields` (typically the template's fields), so a minority of shifted cards
+/// stands out against the rest of the deck.
+pub fn detect_shifts(
+    deck: &Deck,
+    fields: &[ColumnRange],
+    encoder: &dyn PunchEncoding,
+    max_shift: usize,
+    min_run: usize,
+) -> Result<Vec<ShiftedBlock>> {
+    let texts = deck
+        .cards
+        .iter()
+        .enumerate()
+        .map(|(idx, card)| {
+            card.to_punch_card(encoder)
+                .map(|punched| punched.text().map(class))
+                .with_context(|| format!("card {}", idx + 1))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let columns: Vec<usize> = (0..COLS)
+        .filter(|col| fields.iter().any(|f| f.contains(col + 1)))
+        .collect();
+    let mut profile = [Class::Blank; COLS];
+    for &col in &columns {
+        let mut counts = [0usize; 4];
+        for text in &texts {
+            counts[text[col] as usize] += 1;
+        }
+        let best = (0..4).max_by_key(|i| (counts[*i], std::cmp::Reverse(*i)));
+        profile[col] = [Class::Blank, Class::Digit, Class::Letter, Class::Other][best.unwrap_or(0)];
+    }
+    let max_shift = max_shift as isize;
+    let score = |text: &[Class; COLS], by: isize| {
+        columns
+            .iter()
+            .filter(|&&col| {
+                let at = col as isize + by;
+                let got = if (0..COLS as isize).contains(&at) {
+                    text[at as usize]
+                } else {
+                    Class::Blank
+                };
+                got == profile[col]
+            })
+            .count()
+    };
+    let shifts: Vec<Option<(isize, usize)>> = texts
+        .iter()
+        .map(|text| {
+            let base = score(text, 0);
+            (1..=max_shift)
+                .flat_map(|n| [n, -n])
+                .map(|by| (by, score(text, by)))
+                .filter(|(_, s)| *s >= base + MIN_GAIN)
+                .max_by_key(|(by, s)| (*s, std::cmp::Reverse(by.abs())))
+                .map(|(by, s)| (by, s - base))
+        })
+        .collect();
+
+    let mut blocks: Vec<ShiftedBlock> = Vec::new();
+    for (idx, shift) in shifts.iter().enumerate() {
+        let Some((by, gain)) = *shift else { continue };
+        match blocks.last_mut() {
+            Some(block) if block.last + 1 == idx && block.by == by => {
+                block.last = idx;
+                block.gain = block.gain.min(gain);
+            }
+            _ => blocks.push(ShiftedBlock {
+                first: idx,
+                last: idx,
+                by,
+                gain,
+            }),
+        }
+    }
+    blocks.retain(|block| block.last - block.first + 1 >= min_run.max(1));
+    Ok(blocks)
+}
+
+/// Move the content of `cols` back by `by` columns (left when positive);
+/// columns uncovered at the edge of the range are left blank.
+pub fn unshift_card(card: &CardRecord, by: isize, cols: ColumnRange) -> Result<CardRecord> {
+    let source = |col: usize| {
+        let from = col as isize + by;
+        (from >= cols.start as isize - 1 && from < cols.end as isize).then_some(from as usize)
+    };
+    let mut shifted = card.clone();
+    if let Some(text) = &card.text {
+        let old: Vec<char> = text.chars().collect();
+        let new: String = (0..old.len())
+            .map(|col| {
+                if cols.contains(col + 1) {
+                    source(col)
+                        .and_then(|from| old.get(from).copied())
+                        .unwrap_or(' ')
+                } else {
+                    old[col]
+                }
+            })
+            .collect();
+        shifted.text = Some(new);
+    } else if let Some(hex) = &card.punches {
+        let old = punches_from_hex(hex)?;
+        let new: Vec<CellMask> = (0..old.len())
+            .map(|col| {
+                if cols.contains(col + 1) {
+                    source(col)
+                        .and_then(|from| old.get(from).copied())
+                        .unwrap_or(CellMask(0))
+                } else {
+                    old[col]
+                }
+            })
+            .collect();
+        shifted.punches = Some(punches_to_hex(&new));
+    }
+    Ok(shifted)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::deck::{CardType, DeckHeader, EncodingKind};
+    use crate::core::encoding::IBM029;
+
+    #[test]
+    fn shifted_block_is_found_and_undone() {
+        let lines: Vec<String> = (1..=12)
+            .map(|n| {
+                let body = format!("{:<5} X{} = Y{} + 1", n * 10, n, n);
+                let line = format!("{:<72}{:08}", body, n * 10);
+                if (5..=7).contains(&n) {
+                    format!(" {}", &line[..79])
+                } else {
+                    line
+                }
+            })
+            .collect();
+        let deck = Deck::from_lines(
+            DeckHeader::new(None, None, Vec::new()),
+            lines.iter().map(String::as_str),
+            EncodingKind::Hollerith,
+            CardType::Code,
+        )
+        .unwrap();
+        let whole = [ColumnRange::new(1, 80).unwrap()];
+        let blocks = detect_shifts(&deck, &whole, &IBM029, 3, 2).unwrap();
+        assert_eq!(blocks.len(), 1);
+        assert_eq!((blocks[0].first, blocks[0].last, blocks[0].by), (4, 6, 1));
+
+        let fixed = unshift_card(&deck.cards[4], 1, whole[0]).unwrap();
+        assert_eq!(fixed.text.unwrap(), format!("{} ", &lines[4][1..]));
+        let body = unshift_card(&deck.cards[4], 1, ColumnRange::new(1, 72).unwrap()).unwrap();
+        let body = body.text.unwrap();
+        assert_eq!(&body[..71], &lines[4][1..72]);
+        assert_eq!(&body[71..], &format!(" {}", &lines[4][72..]));
+    }
+}
@@ -0,0 +1,227 @@
+//! Ed25519 deck signatures (`punch audit sign/verify-signature`).
+//!
+//! A signature covers the hex text of [`Deck::signing_hash`]: the cards and
+//! the header fields that describe them, but not the lock, workflow state,
+//! verification records or file format, which change while the content does
+//! not. Anyone holding the signer's public key can check it with other tools
+//! as well. Signatures live in the deck header next to the signer's public
+//! key and are left out of the hash themselves, so several archives can
+//! sign the same deck. Any later change to what the hash covers leaves the
+//! signatures standing but no longer matching.
+
+use anyhow::{Context, Result, anyhow};
+use chrono::{DateTime, Utc};
+use ed25519_dalek::pkcs8::{DecodePrivateKey, DecodePublicKey};
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+use serde::{Deserialize, Serialize};
+
+use crate::core::crypt::{from_hex, to_hex};
+use crate::core::deck::Deck;
+
+/// One signer's signature over a deck's [signing hash](Deck::signing_hash).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DeckSignature {
+    /// Signer's Ed25519 public key, 64 hex digits.
+    pub public_key: String,
+    /// Signing hash that was signed.
+    pub hash: String,
+    /// Signature over the hash's hex text, 128 hex digits.
+    pub signature: String,
+    pub signed_at: DateTime<Utc>,
+}
+
+/// Outcome of checking one [`DeckSignature`] against the deck.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SignatureStatus {
+    /// Signed by the key, over the deck as it is now.
+    Valid,
+    /// A genuine signature, but the deck changed after it was made.
+    Stale,
+    /// The signature does not verify under its public key.
+    Forged,
+}
+
+/// Signing key from a PKCS#8 PEM file, as written by
+/// `openssl genpkey -algorithm ed25519`.
+pub fn signing_key_from_pem(pem: &str) -> Result<SigningKey> {
+    SigningKey::from_pkcs8_pem(pem).map_err(|err| anyhow!("not an Ed25519 private key: {}", err))
+}
+
+/// Public key from a PEM file holding either the public key or the private
+/// key it belongs to.
+pub fn verifying_key_from_pem(pem: &str) -> Result<VerifyingKey> {
+    if let Ok(key) = VerifyingKey::from_public_key_pem(pem) {
+        return Ok(key);
+    }
+    signing_key_from_pem(pem)
+        .map(|key| key.verifying_key())
+        .map_err(|_| anyhow!("not an Ed25519 public or private key"))
+}
+
+/// Public key as 64 lowercase hex digits, the form stored in the header.
+pub fn public_key_hex(key: &VerifyingKey) -> String {
+    to_hex(key.as_bytes())
+}
+
+impl DeckSignature {
+    /// Check this signature against the deck's current signing hash.
+    pub fn check(&self, signing_hash: &str) -> Result<SignatureStatus> {
+        let key: [u8; 32] = from_hex(&self.public_key)
+            .context("malformed public key")?
+            .try_into()
+            .map_err(|_| anyhow!("a public key is 64 hex digits"))?;
+        let key = VerifyingKey::from_bytes(&key).context("malformed public key")?;
+        let signature: [u8; 64] = from_hex(&self.signature)
+            .context("malformed signature")?
+            .try_into()
+            .map_err(|_| anyhow!("a signature is 128 hex digits"))?;
+        let signature = Signature::from_bytes(&signature);
+        if key.verify(self.hash.as_bytes(), &signature).is_err() {
+            return Ok(SignatureStatus::Forged);
+        }
+        if self.hash != signing_hash {
+            return Ok(SignatureStatus::Stale);
+        }
+        Ok(SignatureStatus::Valid)
+    }
+}
+
+impl Deck {
+    /// Sign the deck's signing hash, replacing any earlier signature by the
+    /// same key.
+    pub fn sign(&mut self, key: &SigningKey) -> Result<&DeckSignature> {
+        let hash = self.signing_hash()?;
+        let public_key = public_key_hex(&key.verifying_key());
+        let signature = DeckSignature {
+            signature: to_hex(&key.sign(hash.as_bytes()).to_bytes()),
+            public_key,
+            hash,
+            signed_at: Utc::now(),
+        };
+        let signatures = &mut self.header.signatures;
+        signatures.retain(|existing| existing.public_key != signature.public_key);
+        signatures.push(signature);
+        Ok(signatures.last().expect("just pushed"))
+    }
+
+    /// Every stored signature with its status against the deck as it is.
+    pub fn check_signatures(&self) -> Result<Vec<(&DeckSignature, SignatureStatus)>> {
+        let hash = self.signing_hash()?;
+        let content_hash = self.hash()?;
+        self.header
+            .signatures
+            .iter()
+            .map(|signature| {
+                // Earlier releases signed the content hash; such a signature
+                // holds for as long as that hash does.
+                let status = match signature.check(&hash)? {
+                    SignatureStatus::Stale if signature.hash == content_hash => {
+                        SignatureStatus::Valid
+                    }
+                    status => status,
+                };
+                Ok((signature, status))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::baseline::VerifyPass;
+    use crate::core::deck::{
+        CardRecord, CardType, DeckHeader, DeckState, EncodingKind, audit_sidecar_path,
+    };
+
+    fn deck() -> Deck {
+        Deck::from_lines(
+            DeckHeader::new(None, None, Vec::new()),
+            ["HELLO", "WORLD"],
+            EncodingKind::Hollerith,
+            CardType::Code,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn signatures_track_deck_changes() {
+        let mut deck = deck();
+        let before = deck.hash().unwrap();
+        let key = SigningKey::from_bytes(&[7; 32]);
+        deck.sign(&key).unwrap();
+        deck.sign(&key).unwrap();
+        assert_eq!(deck.header.signatures.len(), 1);
+        assert_eq!(deck.hash().unwrap(), before);
+        let checked = deck.check_signatures().unwrap();
+        assert_eq!(checked[0].1, SignatureStatus::Valid);
+        assert_eq!(
+            checked[0].0.public_key,
+            public_key_hex(&key.verifying_key())
+        );
+
+        deck.cards[0] =
+            CardRecord::from_text("HOWDY", EncodingKind::Hollerith, CardType::Code).unwrap();
+        assert_eq!(
+            deck.check_signatures().unwrap()[0].1,
+            SignatureStatus::Stale
+        );
+
+        let mut forged = deck.header.signatures[0].clone();
+        forged.hash = deck.signing_hash().unwrap();
+        assert_eq!(forged.check(&forged.hash).unwrap(), SignatureStatus::Forged);
+    }
+
+    #[test]
+    fn signatures_outlast_locks_states_and_verify_passes() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("prog.deck");
+        let mut deck = deck();
+        deck.header.canonical = true;
+        deck.sign(&SigningKey::from_bytes(&[7; 32])).unwrap();
+        deck.save(&path).unwrap();
+
+        let mut deck = Deck::load(&path).unwrap();
+        deck.header.readonly = true;
+        deck.header.state = DeckState::Verified;
+        deck.header.state_since = Some(Utc::now());
+        deck.header.version = 1;
+        deck.verification_mut()
+            .record_pass(VerifyPass::record(None, 2, Vec::new(), String::new()));
+        deck.log_action("verify pass");
+        assert_eq!(
+            deck.check_signatures().unwrap()[0].1,
+            SignatureStatus::Valid
+        );
+
+        // A canonical deck copied without its audit sidecar.
+        std::fs::remove_file(audit_sidecar_path(&path)).unwrap();
+        let copied = Deck::load(&path).unwrap();
+        assert_eq!(
+            copied.check_signatures().unwrap()[0].1,
+            SignatureStatus::Valid
+        );
+    }
+
+    #[test]
+    fn content_hash_signatures_still_verify() {
+        let mut deck = deck();
+        let key = SigningKey::from_bytes(&[7; 32]);
+        let hash = deck.hash().unwrap();
+        deck.header.signatures.push(DeckSignature {
+            public_key: public_key_hex(&key.verifying_key()),
+            signature: to_hex(&key.sign(hash.as_bytes()).to_bytes()),
+            hash,
+            signed_at: Utc::now(),
+        });
+        assert_eq!(
+            deck.check_signatures().unwrap()[0].1,
+            SignatureStatus::Valid
+        );
+        deck.header.description = Some("changed".into());
+        assert_eq!(
+            deck.check_signatures().unwrap()[0].1,
+            SignatureStatus::Stale
+        );
+    }
+}
@@ -0,0 +1,182 @@
+//! Card deck files for the simh simulators (IBM 1401, 7090 and relatives).
+//!
+//! simh card readers and punches attach files in several modes. The text
+//! mode is an ordinary ASCII file (the `keypunch` export and `text80` import
+//! cover it); the two column-binary modes need their own layout:
+//!
+//! * `-f bin`: 160 bytes per card, each column a little-endian 16-bit word
+//!   holding rows 12, 11, 0–9 left-justified (the same words as
+//!   [`column_to_1130_word`]).
+//! * `-f cbn`: 160 bytes per card, each column split into two 6-bit bytes
+//!   (rows 12–3, then 4–9) with odd parity in bit 6; the first byte of every
+//!   card also carries the record mark `0x80`. This is the 7090 tape image of
+//!   a column-binary card.
+
+use anyhow::{Context, Result, anyhow};
+
+use crate::core::deck::{CardRecord, CardType, Deck};
+use crate::core::encoding::{CellMask, PunchEncoding};
+use crate::core::formats::{column_from_1130_word, column_to_1130_word};
+
+/// Bytes per card in both column-binary modes.
+pub const SIMH_CARD_BYTES: usize = 160;
+
+const RECORD_MARK: u8 = 0x80;
+const PARITY_BIT: u8 = 0x40;
+
+/// Column-binary file mode of a simh card device.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SimhFormat {
+    /// `bin`: one 16-bit little-endian word per column.
+    Binary,
+    /// `cbn`: two 6-bit bytes per column with parity and a record mark.
+    Cbn,
+}
+
+/// Rows 12, 11, 0–9 of a column as a 12-bit value, row 12 highest.
+fn column_value(mask: CellMask) -> u16 {
+    column_to_1130_word(mask) >> 4
+}
+
+fn column_from_value(value: u16) -> CellMask {
+    column_from_1130_word(value << 4)
+}
+
+/// Six data bits with bit 6 set so the seven bits have odd parity.
+fn with_parity(bits: u8) -> u8 {
+    if bits.count_ones().is_multiple_of(2) {
+        bits | PARITY_BIT
+    } else {
+        bits
+    }
+}
+
+/// Write every card in `format`, encoding text cards with `encoder`.
+pub fn to_simh(deck: &Deck, encoder: &dyn PunchEncoding, format: SimhFormat) -> Result<Vec<u8>> {
+    let mut out = Vec::with_capacity(deck.cards.len() * SIMH_CARD_BYTES);
+    for (idx, record) in deck.cards.iter().enumerate() {
+        let punch = record
+            .to_punch_card(encoder)
+            .with_context(|| format!("card {}", idx + 1))?;
+        let start = out.len();
+        for mask in punch.columns() {
+            let value = column_value(*mask);
+            match format {
+                SimhFormat::Binary => out.extend_from_slice(&(value << 4).to_le_bytes()),
+                SimhFormat::Cbn => {
+                    out.push(with_parity((value >> 6) as u8 & 0o77));
+                    out.push(with_parity(value as u8 & 0o77));
+                }
+            }
+        }
+        if format == SimhFormat::Cbn {
+            out[start] |= RECORD_MARK;
+        }
+    }
+    Ok(out)
+}
+
+/// Read a simh column-binary deck into punch-only card records.
+///
+/// Card boundaries are checked in `cbn` files (every card must start with a
+/// record mark and no other byte may carry one), as is byte parity.
+pub fn from_simh(bytes: &[u8], format: SimhFormat, card_type: CardType) -> Result<Vec<CardRecord>> {
+    if !bytes.len().is_multiple_of(SIMH_CARD_BYTES) {
+        return Err(anyhow!(
+            "simh {} decks are {} bytes per card, got {} bytes",
+            format,
+            SIMH_CARD_BYTES,
+            bytes.len()
+        ));
+    }
+    bytes
+        .chunks(SIMH_CARD_BYTES)
+        .enumerate()
+        .map(|(idx, card)| {
+            let columns = match format {
+                SimhFormat::Binary => card
+                    .chunks(2)
+                    .map(|pair| column_from_value(u16::from_le_bytes([pair[0], pair[1]]) >> 4))
+                    .collect(),
+                SimhFormat::Cbn => {
+                    cbn_columns(card).with_context(|| format!("card {}", idx + 1))?
+                }
+            };
+            Ok(CardRecord::from_punches(&columns, card_type.clone()))
+        })
+        .collect()
+}
+
+fn cbn_columns(card: &[u8]) -> Result<Vec<CellMask>> {
+    if card[0] & RECORD_MARK == 0 {
+        return Err(anyhow!("missing record mark; the file is not card-aligned"));
+    }
+    for (pos, byte) in card.iter().enumerate() {
+        let byte = if pos == 0 { byte & !RECORD_MARK } else { *byte };
+        if byte & RECORD_MARK != 0 {
+            return Err(anyhow!("unexpected record mark at byte {}", pos + 1));
+        }
+        if (byte & 0x7f).count_ones().is_multiple_of(2) {
+            return Err(anyhow!("parity error in column {}", pos / 2 + 1));
+        }
+    }
+    Ok(card
+        .chunks(2)
+        .map(|pair| column_from_value(u16::from(pair[0] & 0o77) << 6 | u16::from(pair[1] & 0o77)))
+        .collect())
+}
+
+impl std::fmt::Display for SimhFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SimhFormat::Binary => write!(f, "bin"),
+            SimhFormat::Cbn => write!(f, "cbn"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::deck::{DeckHeader, EncodingKind};
+    use crate::core::encoding::IBM029;
+
+    #[test]
+    fn column_binary_modes_round_trip() {
+        let mut deck = Deck::from_lines(
+            DeckHeader::new(None, None, Vec::new()),
+            ["      PRINT 10, X", "9"],
+            EncodingKind::Hollerith,
+            CardType::Code,
+        )
+        .unwrap();
+        let mut columns = vec![CellMask(0); 80];
+        columns[0] = CellMask::from_rows(&[12, 11, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9]).unwrap();
+        columns[1] = CellMask::from_rows(&[7, 9]).unwrap();
+        deck.cards
+            .push(CardRecord::from_punches(&columns, CardType::Data));
+        let holes = |cards: &[CardRecord]| -> Vec<Vec<CellMask>> {
+            cards
+                .iter()
+                .map(|c| c.to_punch_card(&IBM029).unwrap().columns().to_vec())
+                .collect()
+        };
+
+        for format in [SimhFormat::Binary, SimhFormat::Cbn] {
+            let bytes = to_simh(&deck, &IBM029, format).unwrap();
+            assert_eq!(bytes.len(), 3 * SIMH_CARD_BYTES);
+            let cards = from_simh(&bytes, format, CardType::Code).unwrap();
+            assert_eq!(holes(&cards), holes(&deck.cards));
+            assert!(from_simh(&bytes[1..], format, CardType::Code).is_err());
+        }
+
+        let cbn = to_simh(&deck, &IBM029, SimhFormat::Cbn).unwrap();
+        // Card 2 column 1 is the digit 9: rows 4-9 byte 0o01, odd parity already.
+        assert_eq!(&cbn[160..162], &[RECORD_MARK | PARITY_BIT, 0o01]);
+        let mut corrupt = cbn.clone();
+        corrupt[3] ^= 0o01;
+        let err = from_simh(&corrupt, SimhFormat::Cbn, CardType::Code).unwrap_err();
+        assert!(format!("{:#}", err).contains("parity error in column 2"));
+        assert!(from_simh(&cbn[80..400], SimhFormat::Cbn, CardType::Code).is_err());
+    }
+}
@@ -0,0 +1,75 @@
+//! Per-column statistics over a whole deck (`deck colstats`).
+
+use std::collections::BTreeMap;
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+
+use crate::core::deck::{ColumnRange, Deck};
+use crate::core::encoding::{PunchEncoding, ROW_NAMES};
+
+/// Character and punch-row distribution for a column range.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ColumnStats {
+    pub columns: ColumnRange,
+    /// Cards inspected.
+    pub cards: usize,
+    /// How often each character appears in the range (blank included).
+    pub chars: BTreeMap<char, usize>,
+    /// Holes punched in each row, keyed by row label (12, 11, 0–9).
+    pub rows: BTreeMap<u8, usize>,
+}
+
+impl ColumnStats {
+    /// Tally `columns` over every card of `deck`, punching text with `encoder`.
+    pub fn collect(deck: &Deck, columns: ColumnRange, encoder: &dyn PunchEncoding) -> Result<Self> {
+        let mut chars = BTreeMap::new();
+        let mut rows: BTreeMap<u8, usize> = ROW_NAMES.iter().map(|row| (*row, 0)).collect();
+        for (idx, card) in deck.cards.iter().enumerate() {
+            let punched = card
+                .to_punch_card(encoder)
+                .with_context(|| format!("card {}", idx + 1))?;
+            for col in columns.start - 1..columns.end {
+                *chars.entry(punched.text()[col]).or_insert(0) += 1;
+                for row in punched.columns()[col].rows() {
+                    *rows.entry(row).or_insert(0) += 1;
+                }
+            }
+        }
+        Ok(Self {
+            columns,
+            cards: deck.cards.len(),
+            chars,
+            rows,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::deck::{CardType, DeckHeader, EncodingKind};
+    use crate::core::encoding::IBM029;
+
+    #[test]
+    fn column_stats_counts_chars_and_rows() {
+        let header = DeckHeader::new(None, None, Vec::new());
+        let deck = Deck::from_lines(
+            header,
+            ["A1", "B1", " 2"],
+            EncodingKind::Hollerith,
+            CardType::Data,
+        )
+        .unwrap();
+        let stats = ColumnStats::collect(&deck, ColumnRange::new(1, 1).unwrap(), &IBM029).unwrap();
+        assert_eq!(stats.cards, 3);
+        assert_eq!(stats.chars[&'A'], 1);
+        assert_eq!(stats.chars[&' '], 1);
+        assert_eq!(stats.rows[&12], 2);
+        assert_eq!(stats.rows[&1], 1);
+
+        let stats = ColumnStats::collect(&deck, ColumnRange::new(2, 2).unwrap(), &IBM029).unwrap();
+        assert_eq!(stats.chars[&'1'], 2);
+        assert_eq!(stats.rows[&12], 0);
+    }
+}
@@ -0,0 +1,298 @@
+//! Hardened deck loading for files from untrusted sources.
+//!
+//! [`Deck::load`] trusts its input: lines are read whole and canonical decks
+//! pull in their audit sidecar. The strict loader reads each line through a
+//! length cap, stops at the configured card and history limits, never follows
+//! sidecars or mapping files, checks every card's stored punches, and reports
+//! problems as a typed [`DeckLoadError`].
+
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Read};
+use std::path::Path;
+
+use thiserror::Error;
+
+use crate::core::deck::{Deck, DeckLine};
+use crate::core::encoding::EncoderRegistry;
+use crate::core::formats::punches_from_hex;
+
+/// Upper bounds applied while reading an untrusted deck.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LoadLimits {
+    pub max_cards: usize,
+    /// Longest accepted line in bytes, header included.
+    pub max_line_len: usize,
+    pub max_history: usize,
+}
+
+impl Default for LoadLimits {
+    fn default() -> Self {
+        Self {
+            max_cards: 100_000,
+            max_line_len: 64 * 1024,
+            max_history: 10_000,
+        }
+    }
+}
+
+/// Why a strict load rejected a deck.
+#[derive(Debug, Error)]
+pub enum DeckLoadError {
+    #[error("failed to read deck: {0}")]
+    Io(#[from] io::Error),
+    #[error("deck is empty")]
+    Empty,
+    #[error("deck is encrypted; decrypt it with `punch deck decrypt` first")]
+    Encrypted,
+    #[error("line {line} exceeds {max} bytes")]
+    LineTooLong { line: usize, max: usize },
+    #[error("line {line} is not valid UTF-8")]
+    InvalidUtf8 { line: usize },
+    #[error("line {line}: {source}")]
+    Parse {
+        line: usize,
+        source: serde_json::Error,
+    },
+    #[error("expected deck header on line 1")]
+    MissingHeader,
+    #[error("second deck header on line {line}")]
+    DuplicateHeader { line: usize },
+    #[error("deck has more than {max} cards")]
+    TooManyCards { max: usize },
+    #[error("deck history has {count} entries, limit is {max}")]
+    TooManyHistoryEntries { count: usize, max: usize },
+    #[error("card on line {line} has {len} columns of text")]
+    CardTooWide { line: usize, len: usize },
+    #[error("card on line {line} has malformed punches: {reason}")]
+    InvalidPunches { line: usize, reason: String },
+    #[error("deck encoder '{name}' is not a built-in chart")]
+    UnknownEncoder { name: String },
+    #[error(
+        "deck encoder '{name}' is a mapping file; untrusted decks may only name built-in charts"
+    )]
+    MappingFileEncoder { name: String },
+}
+
+impl Deck {
+    /// Load a deck file with [`LoadLimits`]; see the module docs.
+    pub fn load_strict(path: &Path, limits: &LoadLimits) -> Result<Self, DeckLoadError> {
+        let mut deck = Self::from_reader_strict(BufReader::new(File::open(path)?), limits)?;
+        deck.path = Some(path.to_path_buf());
+        Ok(deck)
+    }
+
+    /// Parse a deck from any reader with [`LoadLimits`].
+    pub fn from_reader_strict<R: BufRead>(
+        mut reader: R,
+        limits: &LoadLimits,
+    ) -> Result<Self, DeckLoadError> {
+        if crate::core::crypt::is_encrypted(reader.fill_buf()?) {
+            return Err(DeckLoadError::Encrypted);
+        }
+        let mut buf = Vec::new();
+        let mut line = 0;
+        let mut header = None;
+        let mut cards = Vec::new();
+        let mut hash_mismatches = Vec::new();
+        while read_bounded_line(&mut reader, &mut buf, limits.max_line_len, line + 1)? {
+            line += 1;
+            let raw = std::str::from_utf8(&buf).map_err(|_| DeckLoadError::InvalidUtf8 { line })?;
+            if raw.trim().is_empty() {
+                continue;
+            }
+            let parsed: DeckLine = serde_json::from_str(raw)
+                .map_err(|source| DeckLoadError::Parse { line, source })?;
+            match (parsed, header.is_some()) {
+                (DeckLine::Header(parsed), false) => {
+                    if parsed.history.len() > limits.max_history {
+                        return Err(DeckLoadError::TooManyHistoryEntries {
+                            count: parsed.history.len(),
+                            max: limits.max_history,
+                        });
+                    }
+                    if let Some(name) = &parsed.encoder {
+                        check_encoder(name)?;
+                    }
+                    header = Some(parsed);
+                }
+                (DeckLine::Header(_), true) => return Err(DeckLoadError::DuplicateHeader { line }),
+                (DeckLine::Card(_), false) => return Err(DeckLoadError::MissingHeader),
+                (DeckLine::Card(card_line), true) => {
+                    if cards.len() == limits.max_cards {
+                        return Err(DeckLoadError::TooManyCards {
+                            max: limits.max_cards,
+                        });
+                    }
+                    let len = card_line
+                        .card
+                        .text
+                        .as_deref()
+                        .map_or(0, |t| t.chars().count());
+                    if len > 80 {
+                        return Err(DeckLoadError::CardTooWide { line, len });
+                    }
+                    if let Some(hex) = &card_line.card.punches {
+                        punches_from_hex(hex).map_err(|err| DeckLoadError::InvalidPunches {
+                            line,
+                            reason: err.to_string(),
+                        })?;
+                    }
+                    let mismatch = card_line
+                        .hash_mismatch(cards.len(), line)
+                        .map_err(|source| DeckLoadError::Parse { line, source })?;
+                    hash_mismatches.extend(mismatch);
+                    cards.push(card_line.card);
+                }
+            }
+        }
+        let header = header.ok_or(DeckLoadError::Empty)?;
+        Ok(Self {
+            header,
+            cards,
+            path: None,
+            encryption: None,
+            readonly_override: false,
+            hash_mismatches,
+            resolved_encoder: Default::default(),
+        })
+    }
+}
+
+/// Accept only a built-in chart: a mapping file would be read from wherever
+/// the deck points.
+fn check_encoder(name: &str) -> Result<(), DeckLoadError> {
+    if EncoderRegistry::is_mapping_file(name) {
+        return Err(DeckLoadError::MappingFileEncoder {
+            name: name.to_string(),
+        });
+    }
+    EncoderRegistry::get(name)
+        .map(drop)
+        .map_err(|_| DeckLoadError::UnknownEncoder {
+            name: name.to_string(),
+        })
+}
+
+/// Read one line into `buf` without its terminator, refusing to buffer more
+/// than `max` bytes. Returns `false` at end of input.
+fn read_bounded_line<R: BufRead>(
+    reader: &mut R,
+    buf: &mut Vec<u8>,
+    max: usize,
+    line: usize,
+) -> Result<bool, DeckLoadError> {
+    buf.clear();
+    let read = reader
+        .by_ref()
+        .take(max as u64 + 2)
+        .read_until(b'\n', buf)?;
+    if read == 0 {
+        return Ok(false);
+    }
+    if buf.last() == Some(&b'\n') {
+        buf.pop();
+        if buf.last() == Some(&b'\r') {
+            buf.pop();
+        }
+    }
+    if buf.len() > max {
+        return Err(DeckLoadError::LineTooLong { line, max });
+    }
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::deck::{CardType, DeckHeader, EncodingKind};
+
+    fn saved(lines: &[&str]) -> Vec<u8> {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("prog.deck");
+        let header = DeckHeader::new(None, None, Vec::new());
+        let mut deck = Deck::from_lines(
+            header,
+            lines.iter().copied(),
+            EncodingKind::Hollerith,
+            CardType::Code,
+        )
+        .unwrap();
+        deck.save(&path).unwrap();
+        std::fs::read(&path).unwrap()
+    }
+
+    #[test]
+    fn strict_load_enforces_limits() {
+        let raw = saved(&["A", "B", "C"]);
+        let deck = Deck::from_reader_strict(raw.as_slice(), &LoadLimits::default()).unwrap();
+        assert_eq!(deck.cards.len(), 3);
+
+        let few = LoadLimits {
+            max_cards: 2,
+            ..LoadLimits::default()
+        };
+        assert!(matches!(
+            Deck::from_reader_strict(raw.as_slice(), &few),
+            Err(DeckLoadError::TooManyCards { max: 2 })
+        ));
+
+        let short = LoadLimits {
+            max_line_len: 16,
+            ..LoadLimits::default()
+        };
+        assert!(matches!(
+            Deck::from_reader_strict(raw.as_slice(), &short),
+            Err(DeckLoadError::LineTooLong { line: 1, .. })
+        ));
+
+        assert!(matches!(
+            Deck::from_reader_strict(&b"{\"kind\":\"card\"}\n"[..], &LoadLimits::default()),
+            Err(DeckLoadError::Parse { line: 1, .. } | DeckLoadError::MissingHeader)
+        ));
+        assert!(matches!(
+            Deck::from_reader_strict(&b""[..], &LoadLimits::default()),
+            Err(DeckLoadError::Empty)
+        ));
+    }
+
+    #[test]
+    fn hostile_punches_and_encoders_are_rejected() {
+        let raw = String::from_utf8(saved(&["A"])).unwrap();
+        let (header, card) = raw.split_once('\n').unwrap();
+        let load = |header: &str, card: &str| {
+            Deck::from_reader_strict(
+                format!("{}\n{}", header, card).as_bytes(),
+                &LoadLimits::default(),
+            )
+        };
+        let punched = r#"{"kind":"card","punches":"aa\u00e9ab","card_type":"data"}"#;
+        assert!(matches!(
+            load(header, punched),
+            Err(DeckLoadError::InvalidPunches { line: 2, .. })
+        ));
+        let header_with =
+            |encoder: &str| header.replacen("{", &format!("{{\"encoder\":\"{}\",", encoder), 1);
+        assert!(matches!(
+            load(&header_with("/etc/passwd"), card),
+            Err(DeckLoadError::UnknownEncoder { name }) if name == "/etc/passwd"
+        ));
+        assert!(matches!(
+            load(&header_with("../chart.toml"), card),
+            Err(DeckLoadError::MappingFileEncoder { .. })
+        ));
+        assert!(load(&header_with("ibm026"), card).is_ok());
+    }
+
+    #[test]
+    fn tampered_cards_are_loaded_and_listed() {
+        let raw = String::from_utf8(saved(&["A", "B"])).unwrap();
+        let tampered = raw.replacen("\"A ", "\"Z ", 1);
+        let deck = Deck::from_reader_strict(tampered.as_bytes(), &LoadLimits::default()).unwrap();
+        assert_eq!(deck.cards.len(), 2);
+        assert_eq!(deck.hash_mismatches.len(), 1);
+        assert_eq!(
+            (deck.hash_mismatches[0].card, deck.hash_mismatches[0].line),
+            (0, 2)
+        );
+    }
+}
@@ -0,0 +1,213 @@
+//! Human-readable summary cards (`punch deck summary-card`).
+//!
+//! Operators closed a deck with a card recording how many cards came before
+//! it and a check total, so a dropped or reshuffled tray could be caught at the
+//! reader. A [`SummaryCard`] punches the card count, the date and a hash of
+//! every preceding card's holes in a fixed layout:
+//!
+//! | Columns | Field                                   |
+//! |---------|-----------------------------------------|
+//! | 1–8     | `*SUMMARY`                              |
+//! | 10–14   | `CARDS`                                 |
+//! | 16–21   | card count, zero padded                 |
+//! | 23–26   | `DATE`                                  |
+//! | 28–35   | date as `YYYYMMDD`                      |
+//! | 37–40   | `HASH`                                  |
+//! | 42–57   | first 16 hex digits of the SHA-256 hash |
+
+use anyhow::{Context, Result, anyhow};
+use chrono::NaiveDate;
+use sha2::{Digest, Sha256};
+
+use crate::core::deck::{CardMeta, CardRecord, CardType, Deck, EncodingKind};
+
+const TAG: &str = "*SUMMARY";
+const MAX_CARDS: usize = 999_999;
+const HASH_DIGITS: usize = 16;
+
+/// Contents of a summary card.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SummaryCard {
+    /// Cards before the summary card.
+    pub cards: usize,
+    pub date: NaiveDate,
+    /// Upper-case hex prefix of the hash of those cards' holes.
+    pub hash: String,
+}
+
+impl SummaryCard {
+    /// The card text in the fixed layout.
+    pub fn text(&self) -> String {
+        format!(
+            "{TAG} CARDS {:06} DATE {} HASH {}",
+            self.cards,
+            self.date.format("%Y%m%d"),
+            self.hash
+        )
+    }
+
+    /// Read a summary card back from its text, or `None` when the text is
+    /// not in the summary layout.
+    pub fn parse(text: &str) -> Option<Self> {
+        let chars: Vec<char> = text.chars().collect();
+        let field = |start: usize, end: usize| -> Option<String> {
+            chars.get(start - 1..end).map(|cols| cols.iter().collect())
+        };
+        let layout_ok = field(1, 8)? == TAG
+            && field(10, 15)? == "CARDS "
+            && field(23, 27)? == "DATE "
+            && field(37, 41)? == "HASH ";
+        if !layout_ok {
+            return None;
+        }
+        let cards = field(16, 21)?.parse().ok()?;
+        let date = NaiveDate::parse_from_str(&field(28, 35)?, "%Y%m%d").ok()?;
+        let hash = field(42, 41 + HASH_DIGITS)?;
+        if !hash.chars().all(|ch| matches!(ch, '0'..='9' | 'A'..='F')) {
+            return None;
+        }
+        Some(Self { cards, date, hash })
+    }
+
+    /// The card record to put in a deck.
+    pub fn to_record(&self) -> Result<CardRecord> {
+        let mut card =
+            CardRecord::from_text(self.text(), EncodingKind::Hollerith, CardType::Comment)?;
+        card.meta = CardMeta {
+            note: Some("deck summary".to_string()),
+            ..CardMeta::default()
+        };
+        Ok(card)
+    }
+}
+
+/// Result of checking a deck's summary card against its contents.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SummaryCheck {
+    /// Zero-based index of the summary card.
+    pub index: usize,
+    /// What the card says.
+    pub found: SummaryCard,
+    /// What it should say for the cards before it (dated as found).
+    pub expected: SummaryCard,
+    /// Cards after the summary card.
+    pub trailing: usize,
+}
+
+impl SummaryCheck {
+    /// Whether the count and hash match and the summary card is the last card.
+    pub fn is_valid(&self) -> bool {
+        self.found == self.expected && self.trailing == 0
+    }
+}
+
+impl Deck {
+    /// Index of the last summary card, if any.
+    pub fn find_summary_card(&self) -> Option<usize> {
+        self.cards
+            .iter()
+            .rposition(|card| card.text.as_deref().and_then(SummaryCard::parse).is_some())
+    }
+
+    /// Summary of the first `count` cards, dated `date`.
+    pub fn summarize(&self, count: usize, date: NaiveDate) -> Result<SummaryCard> {
+        if count > MAX_CARDS {
+            return Err(anyhow!(
+                "a summary card counts at most {} cards, the deck has {}",
+                MAX_CARDS,
+                count
+            ));
+        }
+        let encoder = self.encoder()?;
+        let mut hasher = Sha256::new();
+        for (idx, card) in self.cards[..count].iter().enumerate() {
+            let punched = card
+                .to_punch_card(encoder)
+                .with_context(|| format!("card {}", idx + 1))?;
+            for mask in punched.columns() {
+                hasher.update(mask.0.to_be_bytes());
+            }
+        }
+        let digest = format!("{:02X}", hasher.finalize());
+        Ok(SummaryCard {
+            cards: count,
+            date,
+            hash: digest[..HASH_DIGITS].to_string(),
+        })
+    }
+
+    /// Append a summary card for every card in the deck, or refresh the
+    /// summary card already at the end. Returns whether one was replaced.
+    pub fn append_summary_card(&mut self, date: NaiveDate) -> Result<bool> {
+        let last = self.cards.len().checked_sub(1);
+        let replace = last.filter(|idx| self.find_summary_card() == Some(*idx));
+        let count = replace.unwrap_or(self.cards.len());
+        let card = self.summarize(count, date)?.to_record()?;
+        match replace {
+            Some(idx) => self.replace_card(idx, card)?,
+            None => self.append_card(card)?,
+        }
+        Ok(replace.is_some())
+    }
+
+    /// Check the last summary card against the cards before it, or `None`
+    /// when the deck has no summary card.
+    pub fn check_summary_card(&self) -> Result<Option<SummaryCheck>> {
+        let Some(index) = self.find_summary_card() else {
+            return Ok(None);
+        };
+        let found = self.cards[index]
+            .text
+            .as_deref()
+            .and_then(SummaryCard::parse)
+            .expect("found by find_summary_card");
+        let expected = self.summarize(index, found.date)?;
+        Ok(Some(SummaryCheck {
+            index,
+            found,
+            expected,
+            trailing: self.cards.len() - index - 1,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::deck::DeckHeader;
+
+    #[test]
+    fn summary_card_round_trips_and_catches_changes() {
+        let mut deck = Deck::from_lines(
+            DeckHeader::new(None, None, Vec::new()),
+            ["      PROGRAM MAIN", "      END"],
+            EncodingKind::Hollerith,
+            CardType::Code,
+        )
+        .unwrap();
+        let date = NaiveDate::from_ymd_opt(1969, 7, 20).unwrap();
+        assert!(deck.check_summary_card().unwrap().is_none());
+        assert!(!deck.append_summary_card(date).unwrap());
+        assert_eq!(deck.cards.len(), 3);
+
+        let text = deck.cards[2].text.clone().unwrap();
+        assert!(text.starts_with("*SUMMARY CARDS 000002 DATE 19690720 HASH "));
+        let card = SummaryCard::parse(&text).unwrap();
+        assert_eq!(card.hash.len(), HASH_DIGITS);
+        assert_eq!(card.text().len(), 57);
+        assert!(deck.check_summary_card().unwrap().unwrap().is_valid());
+
+        // Refreshing replaces the trailing summary card instead of stacking.
+        assert!(deck.append_summary_card(date).unwrap());
+        assert_eq!(deck.cards.len(), 3);
+
+        deck.cards[1] =
+            CardRecord::from_text("      STOP", EncodingKind::Hollerith, CardType::Code).unwrap();
+        let check = deck.check_summary_card().unwrap().unwrap();
+        assert!(!check.is_valid());
+        assert_eq!(check.found.cards, check.expected.cards);
+        assert_ne!(check.found.hash, check.expected.hash);
+
+        assert!(SummaryCard::parse("*SUMMARY CARDS 00000X").is_none());
+    }
+}
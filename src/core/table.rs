@@ -0,0 +1,242 @@
+//! Punch charts loaded from mapping files (`--encoder-file`).
+//!
+//! Keypunches were modified site by site, so the built-in charts cannot cover
+//! every deck. A [`TableEncoder`] is built from a TOML or CSV file mapping
+//! characters to punch patterns in chart notation (`12-1`, `0-8-4`, `blank`).
+//!
+//! TOML files may start from a built-in chart and override a few characters:
+//!
+//! ```toml
+//! name = "SITE029"
+//! base = "ibm029"
+//!
+//! [chars]
+//! "¢" = "12-8-2"
+//! "!" = "11-8-2"
+//! ```
+//!
+//! CSV files hold one `CHAR,ROWS` pair per line; `#` starts a comment and a
+//! character may be quoted (`" ",blank`).
+
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+
+use anyhow::{Context, Result, anyhow, bail};
+use serde::Deserialize;
+
+use crate::core::ebcdic::EBCDIC;
+use crate::core::encoding::{CellMask, EncodeError, IBM026, IBM026_FORTRAN, IBM029, PunchEncoding};
+
+/// Encoder backed by an explicit character-to-holes table.
+#[derive(Debug, Clone)]
+pub struct TableEncoder {
+    name: String,
+    chars: BTreeMap<char, CellMask>,
+    /// First character listed for each pattern, for decoding.
+    masks: HashMap<u16, char>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct TableFile {
+    name: Option<String>,
+    base: Option<String>,
+    #[serde(default)]
+    chars: BTreeMap<String, String>,
+}
+
+impl TableEncoder {
+    /// Build a table from `(character, holes)` pairs. A character listed twice
+    /// is an error; a pattern listed twice decodes as its first character.
+    pub fn new(name: &str, entries: impl IntoIterator<Item = (char, CellMask)>) -> Result<Self> {
+        let mut table = Self {
+            name: name.to_string(),
+            chars: BTreeMap::new(),
+            masks: HashMap::new(),
+        };
+        for (ch, mask) in entries {
+            if table.chars.insert(ch, mask).is_some() {
+                bail!("character {:?} is mapped more than once", ch);
+            }
+            table.masks.entry(mask.0).or_insert(ch);
+        }
+        Ok(table)
+    }
+
+    /// Every `(character, holes)` pair, in character order.
+    pub fn entries(&self) -> impl Iterator<Item = (char, CellMask)> + '_ {
+        self.chars.iter().map(|(ch, mask)| (*ch, *mask))
+    }
+
+    /// Parse a TOML mapping; `name` is used when the file names none.
+    pub fn parse_toml(text: &str, name: &str) -> Result<Self> {
+        let file: TableFile = toml::from_str(text).context("invalid encoder file")?;
+        // Listed characters come first so they win the decode of any
+        // pattern they share with the base chart.
+        let mut entries = file
+            .chars
+            .iter()
+            .map(|(key, rows)| {
+                let ch = single_char(key)?;
+                Ok((ch, parse_rows(ch, rows)?))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        if let Some(base) = file.base.as_deref() {
+            let listed: Vec<char> = entries.iter().map(|(ch, _)| *ch).collect();
+            entries.extend(builtin_chart(base)?.filter(|(ch, _)| !listed.contains(ch)));
+        }
+        if entries.is_empty() {
+            bail!("encoder file maps no characters");
+        }
+        Self::new(file.name.as_deref().unwrap_or(name), entries)
+    }
+
+    /// Parse a CSV mapping of `CHAR,ROWS` lines.
+    pub fn parse_csv(text: &str, name: &str) -> Result<Self> {
+        let mut entries = Vec::new();
+        for (idx, line) in text.lines().enumerate() {
+            if line.trim().is_empty() || line.trim_start().starts_with('#') {
+                continue;
+            }
+            let entry = line
+                .rsplit_once(',')
+                .ok_or_else(|| anyhow!("expected CHAR,ROWS"))
+                .and_then(|(ch, rows)| {
+                    let ch = single_char(ch)?;
+                    Ok((ch, parse_rows(ch, rows)?))
+                });
+            match entry {
+                Ok(entry) => entries.push(entry),
+                // An optional header line names the columns.
+                Err(_) if idx == 0 && line.contains(',') => continue,
+                Err(err) => return Err(err.context(format!("line {}", idx + 1))),
+            }
+        }
+        if entries.is_empty() {
+            bail!("encoder file maps no characters");
+        }
+        Self::new(name, entries)
+    }
+
+    /// Load a `.csv` or TOML mapping file; the encoder is named after the file
+    /// stem unless the TOML file sets `name`.
+    pub fn from_path(path: &Path) -> Result<Self> {
+        let text = std::fs::read_to_string(path)
+            .with_context(|| format!("failed to read encoder file {}", path.display()))?;
+        let stem = path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().to_uppercase())
+            .unwrap_or_else(|| "CUSTOM".to_string());
+        let is_csv = path
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("csv"));
+        let table = if is_csv {
+            Self::parse_csv(&text, &stem)
+        } else {
+            Self::parse_toml(&text, &stem)
+        };
+        table.with_context(|| format!("failed to load {}", path.display()))
+    }
+}
+
+impl PunchEncoding for TableEncoder {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Lowercase letters fall back to their capitals when only those are mapped,
+    /// as on the built-in charts.
+    fn encode_char(&self, ch: char) -> Result<CellMask, EncodeError> {
+        self.chars
+            .get(&ch)
+            .or_else(|| self.chars.get(&ch.to_ascii_uppercase()))
+            .copied()
+            .ok_or(EncodeError::Unsupported(ch, ch as u32))
+    }
+
+    fn decode(&self, mask: CellMask) -> Option<char> {
+        self.masks.get(&mask.0).copied()
+    }
+}
+
+/// Characters of a built-in chart usable as a TOML `base`.
+fn builtin_chart(name: &str) -> Result<impl Iterator<Item = (char, CellMask)>> {
+    let chart: &'static dyn PunchEncoding = match name.to_ascii_lowercase().as_str() {
+        "ibm029" => &IBM029,
+        "ibm026" => &IBM026,
+        "ibm026-fortran" => &IBM026_FORTRAN,
+        "ebcdic" => &EBCDIC,
+        _ => bail!(
+            "unknown base chart '{}' (ibm029, ibm026, ibm026-fortran, ebcdic)",
+            name
+        ),
+    };
+    Ok((0..1u16 << 12).filter_map(move |bits| {
+        let mask = CellMask(bits);
+        chart.decode(mask).map(|ch| (ch, mask))
+    }))
+}
+
+fn single_char(field: &str) -> Result<char> {
+    let field = field
+        .strip_prefix('"')
+        .and_then(|f| f.strip_suffix('"'))
+        .unwrap_or(field);
+    let mut chars = field.chars();
+    match (chars.next(), chars.next()) {
+        (Some(ch), None) => Ok(ch),
+        _ => Err(anyhow!("'{}' is not a single character", field)),
+    }
+}
+
+fn parse_rows(ch: char, rows: &str) -> Result<CellMask> {
+    CellMask::from_row_spec(rows).ok_or_else(|| {
+        anyhow!(
+            "{:?}: '{}' is not a punch pattern (rows 12, 11, 0-9 joined by '-', or blank)",
+            ch,
+            rows.trim()
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tables_load_from_toml_and_csv() {
+        let toml = r#"
+            base = "ibm029"
+            [chars]
+            "!" = "12-8-7"
+            "←" = "0-8-5"
+        "#;
+        let site = TableEncoder::parse_toml(toml, "SITE").unwrap();
+        assert_eq!(site.name(), "SITE");
+        assert_eq!(
+            site.encode_char('!').unwrap(),
+            CellMask::from_rows(&[12, 8, 7]).unwrap()
+        );
+        assert_eq!(
+            site.encode_char('a').unwrap(),
+            IBM029.encode_char('A').unwrap()
+        );
+        assert_eq!(
+            site.decode(CellMask::from_rows(&[0, 8, 5]).unwrap()),
+            Some('←')
+        );
+
+        let csv = "char,punches\nA,12-1\n\",\",0-8-3\n\" \",blank\n# comment\n";
+        let small = TableEncoder::parse_csv(csv, "SMALL").unwrap();
+        assert_eq!(small.entries().count(), 3);
+        assert_eq!(
+            small.encode_char(',').unwrap(),
+            IBM029.encode_char(',').unwrap()
+        );
+        assert!(small.encode_char('B').is_err());
+
+        assert!(TableEncoder::parse_csv("A,12-1\nA,12-2\n", "DUP").is_err());
+        assert!(TableEncoder::parse_csv("A,12-1\nB,13\n", "BAD").is_err());
+        assert!(TableEncoder::parse_toml("base = \"ibm1401\"", "X").is_err());
+    }
+}
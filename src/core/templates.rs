@@ -1,5 +1,11 @@
 use crate::core::deck::{CardRecord, CardType, ColumnRange, EncodingKind};
-use anyhow::{Result, anyhow};
+use crate::core::encoding::{CellMask, IBM029, PunchEncoding};
+use crate::core::loaded::FileCache;
+use crate::core::punchcards::{PunchCard, ruler_line};
+use anyhow::{Context, Result, anyhow};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::path::Path;
 
 /// Describes a language or workload-specific punch card layout.
 #[derive(Debug, Clone)]
@@ -14,27 +20,449 @@ pub struct Template {
 #[derive(Debug, Clone)]
 pub struct TemplateColumn {
     pub range: ColumnRange,
+    /// Short identifier used on the command line, e.g. `areaA`.
+    pub name: &'static str,
     pub label: &'static str,
+    pub kind: FieldKind,
+}
+
+/// How the contents of a [`TemplateColumn`] are interpreted.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum FieldKind {
+    /// Free-form characters.
+    #[default]
+    Text,
+    /// Zoned decimal with the sign overpunched on the last digit
+    /// (12 zone = positive, 11 zone = negative).
+    SignedNumeric,
+    /// Sequence number: an all-digit value is right-justified and
+    /// zero-padded to the field width; anything else is punched as text.
+    Sequence,
+}
+
+impl fmt::Display for FieldKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(match self {
+            FieldKind::Text => "text",
+            FieldKind::SignedNumeric => "signed-numeric",
+            FieldKind::Sequence => "sequence",
+        })
+    }
+}
+
+/// Value read from a card field by [`TemplateColumn::extract`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FieldValue {
+    Text(String),
+    Signed(i64),
+}
+
+impl fmt::Display for FieldValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FieldValue::Text(text) => f.pad(text),
+            FieldValue::Signed(value) => write!(f, "{}", value),
+        }
+    }
+}
+
+impl TemplateColumn {
+    /// Read this field from a card's columns.
+    ///
+    /// Signed-numeric fields accept leading blanks and plain digits, with an
+    /// optional 12/11 zone over the last digit; a blank field reads as zero.
+    pub fn extract(&self, card: &PunchCard) -> Result<FieldValue> {
+        let cols = self.range.start - 1..self.range.end;
+        match self.kind {
+            FieldKind::Text | FieldKind::Sequence => Ok(FieldValue::Text(
+                card.text()[cols]
+                    .iter()
+                    .collect::<String>()
+                    .trim_end()
+                    .to_string(),
+            )),
+            FieldKind::SignedNumeric => {
+                let masks = &card.columns()[cols];
+                let blanks = masks.iter().take_while(|m| m.0 == 0).count();
+                let digits = masks[blanks..]
+                    .iter()
+                    .enumerate()
+                    .map(|(idx, mask)| {
+                        mask.overpunch_digit().ok_or_else(|| {
+                            anyhow!(
+                                "column {}: {:?} is not a digit",
+                                self.range.start + blanks + idx,
+                                mask.rows()
+                            )
+                        })
+                    })
+                    .collect::<Result<Vec<(u8, bool)>>>()?;
+                let last = digits.len().saturating_sub(1);
+                let mut value: i64 = 0;
+                let mut negative = false;
+                for (idx, (digit, neg)) in digits.iter().enumerate() {
+                    if *neg && idx != last {
+                        return Err(anyhow!(
+                            "{}: sign overpunch must be on the last digit",
+                            self.label
+                        ));
+                    }
+                    negative |= *neg;
+                    value = value
+                        .checked_mul(10)
+                        .and_then(|v| v.checked_add(i64::from(*digit)))
+                        .ok_or_else(|| anyhow!("{}: value overflows", self.label))?;
+                }
+                Ok(FieldValue::Signed(if negative { -value } else { value }))
+            }
+        }
+    }
+
+    /// Punch `value` right-aligned into this field with a sign overpunch on the last digit.
+    pub fn encode_signed(&self, value: i64) -> Result<Vec<CellMask>> {
+        let width = self.range.end - self.range.start + 1;
+        let digits = value.unsigned_abs().to_string();
+        if digits.len() > width {
+            return Err(anyhow!(
+                "{} does not fit in {} columns ({})",
+                value,
+                width,
+                self.label
+            ));
+        }
+        let mut masks = vec![CellMask(0); width - digits.len()];
+        let last = digits.len() - 1;
+        for (idx, ch) in digits.bytes().enumerate() {
+            let digit = ch - b'0';
+            masks.push(if idx == last {
+                CellMask::overpunch(digit, value < 0)
+            } else {
+                CellMask::from_rows(&[digit]).expect("digit row")
+            });
+        }
+        Ok(masks)
+    }
 }
 
 impl Template {
+    /// Extract every field of `card` as `(label, value)` pairs.
+    pub fn extract_fields(&self, card: &PunchCard) -> Result<Vec<(&'static str, FieldValue)>> {
+        self.columns
+            .iter()
+            .map(|column| Ok((column.label, column.extract(card)?)))
+            .collect()
+    }
+
+    /// Text rendition of the multiple-card layout form: one bracketed span per
+    /// field drawn over the 80-column ruler, followed by a field legend.
+    pub fn layout_form(&self) -> String {
+        let mut diagram = vec![' '; 80];
+        for column in self.columns {
+            let (start, end) = (column.range.start - 1, column.range.end - 1);
+            if start == end {
+                diagram[start] = '|';
+                continue;
+            }
+            diagram[start] = '[';
+            diagram[end] = ']';
+            for (slot, ch) in diagram[start + 1..end].iter_mut().zip(column.label.chars()) {
+                *slot = ch;
+            }
+        }
+        let mut out = format!("Layout: {}\n{}\n\n", self.name, self.description);
+        out.push_str(&diagram.into_iter().collect::<String>());
+        out.push('\n');
+        out.push_str(&ruler_line());
+        out.push_str("\n\nFields:\n");
+        for column in self.columns {
+            let kind = match column.kind {
+                FieldKind::Text => String::new(),
+                kind => format!(" [{}]", kind),
+            };
+            out.push_str(&format!(
+                "  {:>2}-{:>2}  {}{}\n",
+                column.range.start, column.range.end, column.label, kind
+            ));
+        }
+        out
+    }
+
+    /// One 80-column row marking field boundaries, e.g.
+    /// `LABE|CSTATEMENT....|SEQ....|`: each field shows its upper-cased name,
+    /// padded with dots and closed by `|`; one-column fields show an initial.
+    pub fn field_ruler(&self) -> String {
+        let mut row = vec![' '; 80];
+        for column in self.columns {
+            let (start, end) = (column.range.start - 1, column.range.end - 1);
+            let mut name = column.name.chars().map(|c| c.to_ascii_uppercase());
+            if start == end {
+                row[start] = name.next().unwrap_or('|');
+                continue;
+            }
+            for slot in &mut row[start..end] {
+                *slot = name.next().unwrap_or('.');
+            }
+            row[end] = '|';
+        }
+        row.into_iter().collect::<String>().trim_end().to_string()
+    }
+
+    /// Look up a field by its short name (case-insensitive).
+    pub fn field(&self, name: &str) -> Result<&TemplateColumn> {
+        self.columns
+            .iter()
+            .find(|c| c.name.eq_ignore_ascii_case(name))
+            .ok_or_else(|| {
+                let names: Vec<&str> = self.columns.iter().map(|c| c.name).collect();
+                anyhow!(
+                    "template '{}' has no field '{}' (fields: {})",
+                    self.name,
+                    name,
+                    names.join(", ")
+                )
+            })
+    }
+
+    /// Build a card from `base` text with named fields filled in.
+    ///
+    /// Text fields are left-justified and must fit their columns, though they may
+    /// run on into the next field when that field is not set (COBOL division
+    /// headers start in Area A and continue into Area B); signed-numeric
+    /// fields take an integer and are right-justified with a sign overpunch;
+    /// sequence fields zero-pad a number to their width. A card whose
+    /// overpunches have no 029 character is stored as raw punches.
+    pub fn build_card(&self, base: &str, fields: &[(&str, &str)]) -> Result<CardRecord> {
+        let mut text: Vec<char> = self.apply(base)?.text.unwrap_or_default().chars().collect();
+        let mut overpunched: Vec<(usize, CellMask)> = Vec::new();
+        let mut seen: Vec<&str> = Vec::new();
+        for (name, value) in fields {
+            let column = self.field(name)?;
+            if seen.contains(&column.name) {
+                return Err(anyhow!("field '{}' given more than once", column.name));
+            }
+            seen.push(column.name);
+            let start = column.range.start - 1;
+            let mut width = column.range.end - start;
+            let sequence;
+            let value = match column.kind {
+                FieldKind::Sequence
+                    if !value.is_empty() && value.bytes().all(|b| b.is_ascii_digit()) =>
+                {
+                    sequence = format!("{:0>width$}", value);
+                    sequence.as_str()
+                }
+                _ => *value,
+            };
+            match column.kind {
+                FieldKind::Text | FieldKind::Sequence => {
+                    let next = self.columns.iter().find(|c| {
+                        c.range.start == column.range.end + 1
+                            && column.kind == FieldKind::Text
+                            && c.kind == FieldKind::Text
+                            && !fields.iter().any(|(n, _)| c.name.eq_ignore_ascii_case(n))
+                    });
+                    if let Some(next) = next.filter(|_| value.chars().count() > width) {
+                        width = next.range.end - start;
+                    }
+                    if value.chars().count() > width {
+                        return Err(anyhow!(
+                            "field '{}' is {} columns wide; '{}' has {} characters",
+                            column.name,
+                            width,
+                            value,
+                            value.chars().count()
+                        ));
+                    }
+                    if let Some(ch) = value.chars().find(|ch| !IBM029.is_supported(*ch)) {
+                        return Err(anyhow!(
+                            "field '{}': '{}' cannot be punched",
+                            column.name,
+                            ch
+                        ));
+                    }
+                    let padded = value.chars().chain(std::iter::repeat(' ')).take(width);
+                    for (slot, ch) in text[start..start + width].iter_mut().zip(padded) {
+                        *slot = ch;
+                    }
+                }
+                FieldKind::SignedNumeric => {
+                    let number: i64 = value.trim().parse().map_err(|_| {
+                        anyhow!(
+                            "field '{}' expects an integer, got '{}'",
+                            column.name,
+                            value
+                        )
+                    })?;
+                    for (offset, mask) in column.encode_signed(number)?.into_iter().enumerate() {
+                        overpunched.push((start + offset, mask));
+                    }
+                }
+            }
+        }
+        let mut masks = text
+            .iter()
+            .map(|ch| IBM029.encode_char(*ch))
+            .collect::<Result<Vec<CellMask>, _>>()?;
+        for &(col, mask) in &overpunched {
+            masks[col] = mask;
+        }
+        for (col, mask) in overpunched {
+            match IBM029.decode(mask) {
+                Some(ch) => text[col] = ch,
+                None => return Ok(CardRecord::from_punches(&masks, self.default_type.clone())),
+            }
+        }
+        CardRecord::from_text(
+            text.into_iter().collect::<String>(),
+            EncodingKind::Hollerith,
+            self.default_type.clone(),
+        )
+    }
+
     /// Apply the template to raw text, returning a [`CardRecord`] with column padding and defaults.
     pub fn apply(&self, text: &str) -> Result<CardRecord> {
         CardRecord::from_text(text, EncodingKind::Hollerith, self.default_type.clone())
     }
 }
 
+/// Editable on-disk form of a [`Template`], written by `template new` and
+/// accepted anywhere a template name is (`--template mine.toml`).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TemplateSpec {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub default_type: CardType,
+    pub fields: Vec<FieldSpec>,
+}
+
+/// One field of a [`TemplateSpec`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct FieldSpec {
+    pub name: String,
+    pub label: String,
+    /// Column range such as `"1-5"` or `"6"`.
+    pub columns: String,
+    #[serde(default)]
+    pub kind: FieldKind,
+}
+
+impl TemplateSpec {
+    /// Capture a template's layout as an editable spec.
+    pub fn from_template(template: &Template) -> Self {
+        Self {
+            name: template.name.to_string(),
+            description: template.description.to_string(),
+            default_type: template.default_type.clone(),
+            fields: template
+                .columns
+                .iter()
+                .map(|c| FieldSpec {
+                    name: c.name.to_string(),
+                    label: c.label.to_string(),
+                    columns: if c.range.start == c.range.end {
+                        c.range.start.to_string()
+                    } else {
+                        format!("{}-{}", c.range.start, c.range.end)
+                    },
+                    kind: c.kind,
+                })
+                .collect(),
+        }
+    }
+
+    /// Serialize as commented TOML.
+    pub fn to_toml(&self) -> Result<String> {
+        let body = toml::to_string_pretty(self).context("failed to serialize template")?;
+        Ok(format!(
+            "# Punch card template. Field columns are inclusive 1-based ranges;\n\
+             # kind is \"text\", \"signed-numeric\" or \"sequence\"; default_type\n\
+             # is the card type given to cards built from this template.\n\n{}",
+            body
+        ))
+    }
+
+    /// Read a spec from a TOML file.
+    pub fn load(path: &Path) -> Result<Self> {
+        let text = std::fs::read_to_string(path)
+            .with_context(|| format!("failed to read template {}", path.display()))?;
+        toml::from_str(&text).with_context(|| format!("invalid template {}", path.display()))
+    }
+
+    /// Validate the spec and turn it into a usable [`Template`].
+    ///
+    /// Field names must be unique and ranges may not overlap. The template
+    /// lives for the rest of the process, like the built-in ones.
+    pub fn into_template(self) -> Result<&'static Template> {
+        if self.fields.is_empty() {
+            return Err(anyhow!("template '{}' defines no fields", self.name));
+        }
+        let mut columns: Vec<TemplateColumn> = Vec::with_capacity(self.fields.len());
+        for field in self.fields {
+            let range: ColumnRange = field
+                .columns
+                .parse()
+                .with_context(|| format!("field '{}'", field.name))?;
+            if let Some(prev) = columns
+                .iter()
+                .find(|c| c.name.eq_ignore_ascii_case(&field.name))
+            {
+                return Err(anyhow!("field '{}' is defined twice", prev.name));
+            }
+            if let Some(prev) = columns
+                .iter()
+                .find(|c| c.range.start <= range.end && range.start <= c.range.end)
+            {
+                return Err(anyhow!(
+                    "field '{}' overlaps field '{}'",
+                    field.name,
+                    prev.name
+                ));
+            }
+            columns.push(TemplateColumn {
+                range,
+                name: leak(field.name),
+                label: leak(field.label),
+                kind: field.kind,
+            });
+        }
+        columns.sort_by_key(|c| c.range.start);
+        Ok(Box::leak(Box::new(Template {
+            name: leak(self.name),
+            description: leak(self.description),
+            columns: Box::leak(columns.into_boxed_slice()),
+            default_type: self.default_type,
+        })))
+    }
+}
+
+fn leak(value: String) -> &'static str {
+    Box::leak(value.into_boxed_str())
+}
+
 /// Registry of built-in templates recognised by the CLI.
 pub struct TemplateRegistry;
 
 impl TemplateRegistry {
     /// Return the set of available templates.
     pub fn list() -> Vec<&'static Template> {
-        vec![&FORTRAN_IV, &COBOL, &JCL_JOB, &ASSEMBLER_H]
+        vec![&FORTRAN_IV, &COBOL, &JCL_JOB, &ASSEMBLER_H, &LEDGER]
     }
 
-    /// Resolve a template by name (case-insensitive).
+    /// Resolve a template by name (case-insensitive), or load a
+    /// user-defined one when `name` is a path ending in `.toml`. A template
+    /// file is read once and reloaded only after it changes.
     pub fn get(name: &str) -> Result<&'static Template> {
+        if name.ends_with(".toml") {
+            static SPECS: FileCache<Template> = FileCache::new();
+            return SPECS.get(Path::new(name), |path| {
+                TemplateSpec::load(path)?.into_template()
+            });
+        }
         let lname = name.to_ascii_lowercase();
         for tpl in Self::list() {
             if tpl.name.eq_ignore_ascii_case(&lname) {
@@ -46,48 +474,73 @@ impl TemplateRegistry {
 }
 
 macro_rules! tpl_col {
-    ($start:expr, $end:expr, $label:expr) => {
+    ($start:expr, $end:expr, $name:expr, $label:expr) => {
+        tpl_col!($start, $end, $name, $label, FieldKind::Text)
+    };
+    ($start:expr, $end:expr, $name:expr, $label:expr, $kind:expr) => {
         TemplateColumn {
             range: ColumnRange {
                 start: $start,
                 end: $end,
             },
+            name: $name,
             label: $label,
+            kind: $kind,
         }
     };
 }
 
 static FORTRAN_COLUMNS: &[TemplateColumn] = &[
-    tpl_col!(1, 5, "Statement label / comment (C in col 1)"),
-    tpl_col!(6, 6, "Continuation (non-blank for continuation)"),
-    tpl_col!(7, 72, "Source statement"),
-    tpl_col!(73, 80, "Sequence number"),
+    tpl_col!(1, 5, "label", "Statement label / comment (C in col 1)"),
+    tpl_col!(6, 6, "cont", "Continuation (non-blank for continuation)"),
+    tpl_col!(7, 72, "statement", "Source statement"),
+    tpl_col!(73, 80, "seq", "Sequence number", FieldKind::Sequence),
 ];
 
 static COBOL_COLUMNS: &[TemplateColumn] = &[
-    tpl_col!(1, 6, "Sequence number / identification"),
-    tpl_col!(7, 7, "Indicator (e.g., * comment)"),
-    tpl_col!(8, 11, "Area A"),
-    tpl_col!(12, 72, "Area B"),
-    tpl_col!(73, 80, "Identification / sequence"),
+    tpl_col!(
+        1,
+        6,
+        "seq",
+        "Sequence number / identification",
+        FieldKind::Sequence
+    ),
+    tpl_col!(7, 7, "indicator", "Indicator (e.g., * comment)"),
+    tpl_col!(8, 11, "areaA", "Area A"),
+    tpl_col!(12, 72, "areaB", "Area B"),
+    tpl_col!(73, 80, "ident", "Identification / sequence"),
 ];
 
 static JCL_COLUMNS: &[TemplateColumn] = &[
-    tpl_col!(1, 2, "Job card '//'"),
-    tpl_col!(3, 10, "Job/step name"),
-    tpl_col!(11, 15, "Operation (JOB/EXEC/DD)"),
-    tpl_col!(16, 71, "Parameters"),
-    tpl_col!(72, 72, "Continuation indicator"),
-    tpl_col!(73, 80, "Sequence number"),
+    tpl_col!(1, 2, "slashes", "Job card '//'"),
+    tpl_col!(3, 10, "name", "Job/step name"),
+    tpl_col!(11, 15, "operation", "Operation (JOB/EXEC/DD)"),
+    tpl_col!(16, 71, "params", "Parameters"),
+    tpl_col!(72, 72, "cont", "Continuation indicator"),
+    tpl_col!(73, 80, "seq", "Sequence number", FieldKind::Sequence),
 ];
 
 static ASSEMBLER_COLUMNS: &[TemplateColumn] = &[
-    tpl_col!(1, 8, "Label"),
-    tpl_col!(9, 9, "Continuation"),
-    tpl_col!(10, 15, "Operation"),
-    tpl_col!(16, 71, "Operands / comments"),
-    tpl_col!(72, 72, "Continuation"),
-    tpl_col!(73, 80, "Sequence number"),
+    tpl_col!(1, 8, "label", "Label"),
+    tpl_col!(9, 9, "cont9", "Continuation"),
+    tpl_col!(10, 15, "op", "Operation"),
+    tpl_col!(16, 71, "operands", "Operands / comments"),
+    tpl_col!(72, 72, "cont72", "Continuation"),
+    tpl_col!(73, 80, "seq", "Sequence number", FieldKind::Sequence),
+];
+
+static LEDGER_COLUMNS: &[TemplateColumn] = &[
+    tpl_col!(1, 6, "account", "Account number"),
+    tpl_col!(7, 12, "date", "Date (YYMMDD)"),
+    tpl_col!(
+        13,
+        22,
+        "amount",
+        "Amount in cents",
+        FieldKind::SignedNumeric
+    ),
+    tpl_col!(23, 72, "description", "Description"),
+    tpl_col!(73, 80, "seq", "Sequence number", FieldKind::Sequence),
 ];
 
 static FORTRAN_IV: Template = Template {
@@ -117,3 +570,125 @@ static ASSEMBLER_H: Template = Template {
     columns: ASSEMBLER_COLUMNS,
     default_type: CardType::Code,
 };
+
+static LEDGER: Template = Template {
+    name: "ledger",
+    description: "Accounting data card with a zoned-decimal signed amount.",
+    columns: LEDGER_COLUMNS,
+    default_type: CardType::Data,
+};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn signed_numeric_fields_read_overpunched_signs() {
+        let amount = &LEDGER_COLUMNS[2];
+        // 'J' is 11-1: the last digit 1 with a negative overpunch.
+        let card = PunchCard::from_str(&IBM029, "100200690101     1234J").unwrap();
+        assert_eq!(amount.extract(&card).unwrap(), FieldValue::Signed(-12341));
+
+        let masks = amount.encode_signed(-120).unwrap();
+        let mut columns = vec![CellMask(0); 12];
+        columns.extend(masks);
+        let punched = PunchCard::from_columns(&columns, |m| IBM029.decode(m));
+        assert_eq!(amount.extract(&punched).unwrap(), FieldValue::Signed(-120));
+        assert!(amount.encode_signed(12_345_678_901).is_err());
+    }
+
+    #[test]
+    fn build_card_places_named_fields() {
+        let card = COBOL
+            .build_card(
+                "",
+                &[("seq", "000100"), ("areaA", "IDENTIFICATION DIVISION.")],
+            )
+            .unwrap();
+        assert_eq!(
+            card.text.as_deref().unwrap().trim_end(),
+            "000100 IDENTIFICATION DIVISION."
+        );
+        let err = COBOL
+            .build_card("", &[("areaA", "IDENTIFICATION"), ("areaB", "X")])
+            .unwrap_err();
+        assert!(err.to_string().contains("4 columns wide"));
+        let card = COBOL
+            .build_card("", &[("seq", "000100"), ("areab", "PROGRAM-ID. HELLO.")])
+            .unwrap();
+        assert_eq!(
+            card.text.as_deref().unwrap().trim_end(),
+            "000100     PROGRAM-ID. HELLO."
+        );
+        let ledger = LEDGER.build_card("", &[("amount", "-1234")]).unwrap();
+        assert_eq!(&ledger.text.as_deref().unwrap()[12..22], "      123M");
+    }
+
+    #[test]
+    fn build_card_applies_every_overpunch() {
+        let tpl = TemplateSpec {
+            name: "pair".into(),
+            description: String::new(),
+            default_type: CardType::Data,
+            fields: ["1-5", "6-10"]
+                .into_iter()
+                .zip(["a", "b"])
+                .map(|(columns, name)| FieldSpec {
+                    name: name.into(),
+                    label: name.into(),
+                    columns: columns.into(),
+                    kind: FieldKind::SignedNumeric,
+                })
+                .collect(),
+        }
+        .into_template()
+        .unwrap();
+        // +0 (12-0) has no 029 character, so the card is stored as punches.
+        let card = tpl.build_card("", &[("a", "10"), ("b", "-7")]).unwrap();
+        assert!(card.text.is_none());
+        let punched = card.to_punch_card(&IBM029).unwrap();
+        let values: Vec<FieldValue> = tpl
+            .extract_fields(&punched)
+            .unwrap()
+            .into_iter()
+            .map(|(_, value)| value)
+            .collect();
+        assert_eq!(values, [FieldValue::Signed(10), FieldValue::Signed(-7)]);
+    }
+
+    #[test]
+    fn sequence_fields_are_zero_padded() {
+        let card = FORTRAN_IV
+            .build_card("      CONTINUE", &[("seq", "100")])
+            .unwrap();
+        assert_eq!(&card.text.as_deref().unwrap()[72..], "00000100");
+        let card = FORTRAN_IV.build_card("", &[("seq", "PROG0010")]).unwrap();
+        assert_eq!(&card.text.as_deref().unwrap()[72..], "PROG0010");
+        let err = FORTRAN_IV
+            .build_card("", &[("seq", "123456789")])
+            .unwrap_err();
+        assert!(err.to_string().contains("8 columns wide"));
+    }
+
+    #[test]
+    fn field_ruler_marks_boundaries() {
+        let ruler = FORTRAN_IV.field_ruler();
+        assert!(ruler.starts_with("LABE|CSTATEMENT"));
+        assert!(ruler.ends_with("|SEQ....|"));
+        assert_eq!(ruler.chars().count(), 80);
+    }
+
+    #[test]
+    fn template_spec_round_trips_and_validates() {
+        let spec = TemplateSpec::from_template(&LEDGER);
+        let parsed: TemplateSpec = toml::from_str(&spec.to_toml().unwrap()).unwrap();
+        assert_eq!(parsed, spec);
+        let tpl = parsed.into_template().unwrap();
+        assert_eq!(tpl.field("amount").unwrap().kind, FieldKind::SignedNumeric);
+        assert_eq!(tpl.default_type, CardType::Data);
+
+        let mut overlapping = TemplateSpec::from_template(&FORTRAN_IV);
+        overlapping.fields[1].columns = "5-6".into();
+        assert!(overlapping.into_template().is_err());
+    }
+}
@@ -0,0 +1,149 @@
+//! Text normalization and 80-column splitting shared by the CLI and library.
+//!
+//! The CLI feeds every typed or piped line through these helpers, and their
+//! defaults are the CLI's: a line wider than a card is an error unless another
+//! [`Overflow`] policy is chosen. Library consumers that keep the defaults
+//! produce exactly the same cards.
+
+use anyhow::{Result, anyhow};
+
+/// Columns on a card.
+pub const CARD_COLUMNS: usize = 80;
+
+/// What to do with a line wider than [`CARD_COLUMNS`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Overflow {
+    /// Drop everything past column 80.
+    Truncate,
+    /// Continue the remainder on following cards.
+    Wrap,
+    /// Reject the input, as the CLI does unless `--overflow` says otherwise.
+    #[default]
+    Error,
+}
+
+/// Pad `line` with blanks to 80 columns; wider lines are returned unchanged.
+pub fn pad_80(line: &str) -> String {
+    format!("{:<width$}", line, width = CARD_COLUMNS)
+}
+
+/// Split text into padded 80-column card lines, one or more per input line.
+///
+/// Empty input still yields a single blank card.
+pub fn split_80(input: &str, overflow: Overflow) -> Result<Vec<String>> {
+    Normalizer::new().overflow(overflow).normalize(input)
+}
+
+/// Configurable line normalizer used before text becomes cards.
+#[derive(Debug, Clone, Default)]
+pub struct Normalizer {
+    overflow: Overflow,
+    uppercase: bool,
+    tab_width: Option<usize>,
+}
+
+impl Normalizer {
+    /// Normalizer that rejects over-long lines and leaves case and tabs
+    /// untouched.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Choose how over-long lines are handled.
+    pub fn overflow(mut self, overflow: Overflow) -> Self {
+        self.overflow = overflow;
+        self
+    }
+
+    /// Fold lowercase letters to uppercase, as a keypunch would.
+    pub fn uppercase(mut self, uppercase: bool) -> Self {
+        self.uppercase = uppercase;
+        self
+    }
+
+    /// Expand tabs to the next multiple of `width` columns.
+    pub fn tab_width(mut self, width: usize) -> Self {
+        self.tab_width = Some(width.max(1));
+        self
+    }
+
+    /// Normalize a single line without splitting or padding it.
+    pub fn normalize_line(&self, line: &str) -> String {
+        let mut out = String::with_capacity(line.len());
+        for ch in line.chars() {
+            match (ch, self.tab_width) {
+                ('\t', Some(width)) => {
+                    let fill = width - out.chars().count() % width;
+                    out.extend(std::iter::repeat_n(' ', fill));
+                }
+                _ if self.uppercase => out.push(ch.to_ascii_uppercase()),
+                _ => out.push(ch),
+            }
+        }
+        out
+    }
+
+    /// Normalize `input` into padded 80-column card lines.
+    pub fn normalize(&self, input: &str) -> Result<Vec<String>> {
+        let mut cards = Vec::new();
+        for (idx, raw) in input.lines().enumerate() {
+            let chars: Vec<char> = self.normalize_line(raw).chars().collect();
+            if chars.len() <= CARD_COLUMNS {
+                cards.push(pad_80(&chars.iter().collect::<String>()));
+                continue;
+            }
+            match self.overflow {
+                Overflow::Truncate => {
+                    cards.push(chars[..CARD_COLUMNS].iter().collect());
+                }
+                Overflow::Wrap => {
+                    for chunk in chars.chunks(CARD_COLUMNS) {
+                        cards.push(pad_80(&chunk.iter().collect::<String>()));
+                    }
+                }
+                Overflow::Error => {
+                    return Err(anyhow!(
+                        "line {} is {} columns wide (max {})",
+                        idx + 1,
+                        chars.len(),
+                        CARD_COLUMNS
+                    ));
+                }
+            }
+        }
+        if cards.is_empty() {
+            cards.push(pad_80(""));
+        }
+        Ok(cards)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn overflow_policies() {
+        let long = format!("{}TAIL", "X".repeat(80));
+        let cut = split_80(&long, Overflow::Truncate).unwrap();
+        assert_eq!(cut, vec!["X".repeat(80)]);
+        let wrapped = split_80(&long, Overflow::Wrap).unwrap();
+        assert_eq!(wrapped.len(), 2);
+        assert_eq!(wrapped[1], pad_80("TAIL"));
+        assert!(split_80(&long, Overflow::Error).is_err());
+        assert_eq!(split_80("", Overflow::Error).unwrap(), vec![" ".repeat(80)]);
+    }
+
+    #[test]
+    fn defaults_match_the_cli() {
+        let long = "X".repeat(81);
+        assert!(Normalizer::new().normalize(&long).is_err());
+        assert_eq!(Overflow::default(), Overflow::Error);
+    }
+
+    #[test]
+    fn normalizer_expands_tabs_and_folds_case() {
+        let norm = Normalizer::new().uppercase(true).tab_width(6);
+        assert_eq!(norm.normalize_line("c\tx"), "C     X");
+    }
+}
@@ -0,0 +1,214 @@
+//! Hollerith code drills (`punch trainer`).
+//!
+//! A [`Quiz`] draws characters from a punch chart and asks either for the
+//! character a hole pattern punches or for the holes of a character, keeping
+//! score and tallying the characters missed most often. Questions come from a
+//! seeded generator, so a class can work through the same drill.
+
+use std::collections::BTreeMap;
+
+use anyhow::{Result, anyhow};
+
+use crate::core::encoding::{CellMask, PunchEncoding};
+use crate::core::rng::SplitMix64;
+
+/// What the quiz asks for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QuizMode {
+    /// Show holes, ask for the character.
+    Read,
+    /// Show a character, ask for its rows.
+    Punch,
+    /// Either, at random.
+    Mixed,
+}
+
+/// One question; the expected reply is the other half of the pair.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Question {
+    /// Which character is punched as `mask`?
+    Read { mask: CellMask, answer: char },
+    /// Which rows punch `ch`?
+    Punch { ch: char, answer: CellMask },
+}
+
+impl Question {
+    /// The character the question is about, whichever way it is asked.
+    pub fn ch(&self) -> char {
+        match self {
+            Question::Read { answer, .. } => *answer,
+            Question::Punch { ch, .. } => *ch,
+        }
+    }
+
+    /// The holes the question is about.
+    pub fn mask(&self) -> CellMask {
+        match self {
+            Question::Read { mask, .. } => *mask,
+            Question::Punch { answer, .. } => *answer,
+        }
+    }
+}
+
+/// A running drill over one chart.
+#[derive(Debug, Clone)]
+pub struct Quiz {
+    /// Characters asked about with their holes, in chart order.
+    pool: Vec<(char, CellMask)>,
+    mode: QuizMode,
+    rng: SplitMix64,
+    pub asked: usize,
+    pub correct: usize,
+    missed: BTreeMap<char, usize>,
+}
+
+impl Quiz {
+    /// Drill every non-blank character `encoder` can punch, or only those in
+    /// `only` when given.
+    pub fn new(
+        encoder: &dyn PunchEncoding,
+        mode: QuizMode,
+        only: Option<&str>,
+        seed: u64,
+    ) -> Result<Self> {
+        let pool: Vec<(char, CellMask)> = match only {
+            Some(chars) => {
+                let mut pool = Vec::new();
+                for ch in chars.chars().filter(|ch| *ch != ' ') {
+                    let mask = encoder
+                        .encode_char(ch)
+                        .map_err(|_| anyhow!("{:?} has no {} punch code", ch, encoder.name()))?;
+                    if !pool.contains(&(ch, mask)) {
+                        pool.push((ch, mask));
+                    }
+                }
+                pool
+            }
+            None => (1..1u16 << 12)
+                .filter_map(|bits| {
+                    encoder
+                        .decode(CellMask(bits))
+                        .map(|ch| (ch, CellMask(bits)))
+                })
+                .collect(),
+        };
+        if pool.is_empty() {
+            return Err(anyhow!("no characters to drill in {}", encoder.name()));
+        }
+        Ok(Self {
+            pool,
+            mode,
+            rng: SplitMix64::new(seed),
+            asked: 0,
+            correct: 0,
+            missed: BTreeMap::new(),
+        })
+    }
+
+    /// Number of characters the quiz draws from.
+    pub fn pool_size(&self) -> usize {
+        self.pool.len()
+    }
+
+    /// Draw the next question.
+    pub fn next_question(&mut self) -> Question {
+        let (ch, mask) = self.pool[self.rng.below(self.pool.len() as u64) as usize];
+        let read = match self.mode {
+            QuizMode::Read => true,
+            QuizMode::Punch => false,
+            QuizMode::Mixed => self.rng.below(2) == 0,
+        };
+        if read {
+            Question::Read { mask, answer: ch }
+        } else {
+            Question::Punch { ch, answer: mask }
+        }
+    }
+
+    /// Score `reply` to `question` and return whether it was right.
+    ///
+    /// Rows may be given in any order, separated by `-`, spaces or commas. A
+    /// lower-case letter counts for its capital when the chart has no
+    /// lower case.
+    pub fn answer(&mut self, question: &Question, reply: &str) -> bool {
+        let right = match question {
+            Question::Read { answer, .. } => {
+                let mut chars = reply.trim().chars();
+                match (chars.next(), chars.next()) {
+                    (Some(ch), None) => {
+                        ch == *answer
+                            || (ch.to_ascii_uppercase() == *answer
+                                && !self.pool.iter().any(|(known, _)| *known == ch))
+                    }
+                    _ => false,
+                }
+            }
+            Question::Punch { answer, .. } => {
+                let spec: String = reply
+                    .trim()
+                    .split(|c: char| c == '-' || c == ',' || c.is_whitespace())
+                    .filter(|row| !row.is_empty())
+                    .collect::<Vec<_>>()
+                    .join("-");
+                CellMask::from_row_spec(&spec) == Some(*answer)
+            }
+        };
+        self.asked += 1;
+        if right {
+            self.correct += 1;
+        } else {
+            *self.missed.entry(question.ch()).or_default() += 1;
+        }
+        right
+    }
+
+    /// Share of correct replies, 0.0 before the first question.
+    pub fn accuracy(&self) -> f64 {
+        if self.asked == 0 {
+            0.0
+        } else {
+            self.correct as f64 / self.asked as f64
+        }
+    }
+
+    /// Characters answered wrongly, most often missed first.
+    pub fn missed(&self) -> Vec<(char, usize)> {
+        let mut missed: Vec<(char, usize)> = self.missed.iter().map(|(ch, n)| (*ch, *n)).collect();
+        missed.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+        missed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::encoding::IBM029;
+
+    #[test]
+    fn quiz_scores_replies_and_repeats_with_a_seed() {
+        let mut quiz = Quiz::new(&IBM029, QuizMode::Mixed, None, 7).unwrap();
+        assert!(quiz.pool_size() > 60);
+        let first: Vec<Question> = (0..10).map(|_| quiz.next_question()).collect();
+        let mut again = Quiz::new(&IBM029, QuizMode::Mixed, None, 7).unwrap();
+        let second: Vec<Question> = (0..10).map(|_| again.next_question()).collect();
+        assert_eq!(first, second);
+
+        let a = CellMask::from_rows(&[12, 1]).unwrap();
+        let read = Question::Read {
+            mask: a,
+            answer: 'A',
+        };
+        let punch = Question::Punch { ch: 'A', answer: a };
+        assert!(quiz.answer(&read, "a"));
+        assert!(quiz.answer(&punch, "1 12"));
+        assert!(!quiz.answer(&punch, "12-2"));
+        assert!(!quiz.answer(&read, "AB"));
+        assert_eq!((quiz.asked, quiz.correct), (4, 2));
+        assert_eq!(quiz.missed(), [('A', 2)]);
+        assert_eq!(quiz.accuracy(), 0.5);
+
+        let digits = Quiz::new(&IBM029, QuizMode::Read, Some("0123 3"), 1).unwrap();
+        assert_eq!(digits.pool_size(), 4);
+        assert!(Quiz::new(&IBM029, QuizMode::Read, Some("~"), 1).is_err());
+    }
+}
@@ -0,0 +1,280 @@
+//! Workspaces of related decks (`punchcard.workspace.toml`).
+//!
+//! A job's cards used to travel as several trays managed together: the JCL,
+//! the program source and its data. A workspace manifest lists those decks
+//! with their role and what each depends on, so `punch workspace` commands
+//! can report on, check and assemble them as one unit:
+//!
+//! ```toml
+//! name = "PAYROLL"
+//!
+//! [job]
+//! program_dd = "//FORT.SYSIN DD *"
+//!
+//! [[deck]]
+//! name = "jcl"
+//! path = "payroll.jcl.deck"
+//! role = "jcl"
+//!
+//! [[deck]]
+//! name = "source"
+//! path = "src/payroll.deck"
+//! role = "program"
+//!
+//! [[deck]]
+//! name = "week42"
+//! path = "data/week42.deck"
+//! role = "data"
+//! depends_on = ["source"]
+//! ```
+//!
+//! Deck paths are relative to the manifest's directory.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result, anyhow, bail};
+use serde::Deserialize;
+
+use crate::core::job::JobOptions;
+
+/// File name `punch workspace` looks for in the current directory and its parents.
+pub const WORKSPACE_FILE: &str = "punchcard.workspace.toml";
+
+/// Part a deck plays in the job.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DeckRole {
+    /// JOB and EXEC statements; a workspace assembles around exactly one.
+    Jcl,
+    /// Program source, placed after the program `DD *` statement.
+    Program,
+    /// Input data, placed after the data `DD *` statement.
+    Data,
+    /// Related deck that is not part of the submitted job (copybooks, notes).
+    Library,
+}
+
+impl fmt::Display for DeckRole {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(match self {
+            DeckRole::Jcl => "jcl",
+            DeckRole::Program => "program",
+            DeckRole::Data => "data",
+            DeckRole::Library => "library",
+        })
+    }
+}
+
+/// One deck listed in the manifest.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct WorkspaceDeck {
+    pub name: String,
+    pub path: PathBuf,
+    pub role: DeckRole,
+    /// Names of decks that must come before this one.
+    #[serde(default)]
+    pub depends_on: Vec<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct JobSection {
+    program_dd: Option<String>,
+    data_dd: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Manifest {
+    name: Option<String>,
+    #[serde(default)]
+    job: JobSection,
+    #[serde(default, rename = "deck")]
+    decks: Vec<WorkspaceDeck>,
+}
+
+/// Parsed workspace manifest.
+#[derive(Debug, Clone)]
+pub struct Workspace {
+    pub name: Option<String>,
+    /// Directory deck paths are resolved against.
+    pub root: PathBuf,
+    pub decks: Vec<WorkspaceDeck>,
+    /// `DD *` statements used when assembling the job.
+    pub job: JobOptions,
+}
+
+impl Workspace {
+    /// Parse manifest text; `root` is the directory deck paths are relative to.
+    pub fn parse(text: &str, root: &Path) -> Result<Self> {
+        let manifest: Manifest = toml::from_str(text).context("invalid workspace manifest")?;
+        let defaults = JobOptions::default();
+        Ok(Self {
+            name: manifest.name,
+            root: root.to_path_buf(),
+            decks: manifest.decks,
+            job: JobOptions {
+                program_dd: manifest.job.program_dd.unwrap_or(defaults.program_dd),
+                data_dd: manifest.job.data_dd.unwrap_or(defaults.data_dd),
+            },
+        })
+    }
+
+    /// Load a manifest file.
+    pub fn load(path: &Path) -> Result<Self> {
+        let text = std::fs::read_to_string(path)
+            .with_context(|| format!("failed to read {}", path.display()))?;
+        let root = path.parent().unwrap_or(Path::new("."));
+        Self::parse(&text, root).with_context(|| format!("failed to load {}", path.display()))
+    }
+
+    /// Find [`WORKSPACE_FILE`] in `start` or the nearest parent directory.
+    pub fn find(start: &Path) -> Result<PathBuf> {
+        start
+            .ancestors()
+            .map(|dir| dir.join(WORKSPACE_FILE))
+            .find(|candidate| candidate.is_file())
+            .ok_or_else(|| {
+                anyhow!(
+                    "no {} in {} or its parents",
+                    WORKSPACE_FILE,
+                    start.display()
+                )
+            })
+    }
+
+    /// Path of `deck` on disk.
+    pub fn deck_path(&self, deck: &WorkspaceDeck) -> PathBuf {
+        self.root.join(&deck.path)
+    }
+
+    /// Manifest mistakes: duplicate names, unknown or circular dependencies
+    /// and a JCL role held by more than one deck.
+    pub fn problems(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+        let mut seen = HashMap::new();
+        for deck in &self.decks {
+            if seen.insert(deck.name.as_str(), deck).is_some() {
+                problems.push(format!("deck name '{}' is used twice", deck.name));
+            }
+        }
+        for deck in &self.decks {
+            for dep in &deck.depends_on {
+                if !seen.contains_key(dep.as_str()) {
+                    problems.push(format!(
+                        "deck '{}' depends on unknown deck '{}'",
+                        deck.name, dep
+                    ));
+                }
+            }
+        }
+        let jcl = self
+            .decks
+            .iter()
+            .filter(|d| d.role == DeckRole::Jcl)
+            .count();
+        if jcl > 1 {
+            problems.push(format!("{} decks have the jcl role; at most one may", jcl));
+        }
+        if let Err(err) = self.order() {
+            problems.push(err.to_string());
+        }
+        problems
+    }
+
+    /// Decks with every dependency before its dependents, otherwise in
+    /// manifest order. Unknown dependencies are ignored here (see [`problems`]).
+    ///
+    /// [`problems`]: Self::problems
+    pub fn order(&self) -> Result<Vec<&WorkspaceDeck>> {
+        let index: HashMap<&str, usize> = self
+            .decks
+            .iter()
+            .enumerate()
+            .map(|(idx, deck)| (deck.name.as_str(), idx))
+            .collect();
+        // 0 = unvisited, 1 = on the current path, 2 = placed.
+        let mut mark = vec![0u8; self.decks.len()];
+        let mut ordered = Vec::with_capacity(self.decks.len());
+        fn visit<'a>(
+            idx: usize,
+            decks: &'a [WorkspaceDeck],
+            index: &HashMap<&str, usize>,
+            mark: &mut [u8],
+            ordered: &mut Vec<&'a WorkspaceDeck>,
+        ) -> Result<()> {
+            match mark[idx] {
+                2 => return Ok(()),
+                1 => bail!("dependency cycle through deck '{}'", decks[idx].name),
+                _ => {}
+            }
+            mark[idx] = 1;
+            for dep in &decks[idx].depends_on {
+                if let Some(&next) = index.get(dep.as_str()) {
+                    visit(next, decks, index, mark, ordered)?;
+                }
+            }
+            mark[idx] = 2;
+            ordered.push(&decks[idx]);
+            Ok(())
+        }
+        for idx in 0..self.decks.len() {
+            visit(idx, &self.decks, &index, &mut mark, &mut ordered)?;
+        }
+        Ok(ordered)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn manifest_orders_decks_and_reports_problems() {
+        let text = r#"
+            name = "PAYROLL"
+            [job]
+            program_dd = "//FORT.SYSIN DD *"
+            [[deck]]
+            name = "data"
+            path = "data.deck"
+            role = "data"
+            depends_on = ["source"]
+            [[deck]]
+            name = "source"
+            path = "src/payroll.deck"
+            role = "program"
+            [[deck]]
+            name = "jcl"
+            path = "payroll.jcl.deck"
+            role = "jcl"
+        "#;
+        let ws = Workspace::parse(text, Path::new("/trays")).unwrap();
+        assert_eq!(ws.job.program_dd, "//FORT.SYSIN DD *");
+        assert_eq!(ws.job.data_dd, JobOptions::default().data_dd);
+        assert_eq!(
+            ws.deck_path(&ws.decks[1]),
+            Path::new("/trays/src/payroll.deck")
+        );
+        let names: Vec<&str> = ws
+            .order()
+            .unwrap()
+            .iter()
+            .map(|d| d.name.as_str())
+            .collect();
+        assert_eq!(names, ["source", "data", "jcl"]);
+        assert!(ws.problems().is_empty());
+
+        let mut broken = ws.clone();
+        broken.decks[1].depends_on = vec!["data".to_string(), "copybook".to_string()];
+        broken.decks[2].role = DeckRole::Jcl;
+        broken.decks[0].role = DeckRole::Jcl;
+        let problems = broken.problems();
+        assert_eq!(problems.len(), 3, "{:?}", problems);
+        assert!(problems.iter().any(|p| p.contains("cycle")));
+        assert!(Workspace::parse("[[deck]]\nname = \"x\"", Path::new(".")).is_err());
+    }
+}
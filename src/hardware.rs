@@ -0,0 +1,137 @@
+//! Line protocols for hobbyist serial card readers and punches (feature `hardware`).
+//!
+//! Devices are opened as plain character files (e.g. `/dev/ttyUSB0`); configure
+//! the line speed beforehand with `stty -F /dev/ttyUSB0 9600 raw`. Each device
+//! family implements [`CardProtocol`] so new DIY interfaces only need to describe
+//! how one card travels over the wire.
+
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::Path;
+
+use anyhow::{Context, Result, anyhow};
+
+use crate::core::deck::{CardRecord, CardType, EncodingKind};
+use crate::core::encoding::{CellMask, IBM029, PunchEncoding};
+use crate::core::formats::{punches_from_hex, punches_to_hex};
+use crate::core::punchcards::PunchCard;
+
+/// Wire format spoken by a card reader or punch.
+pub trait CardProtocol {
+    /// Short protocol name used in messages.
+    fn name(&self) -> &'static str;
+    /// Read the next card; `Ok(None)` means the hopper is empty.
+    fn read_card(&mut self, port: &mut dyn BufRead) -> Result<Option<CardRecord>>;
+    /// Send one card to the punch.
+    fn punch_card(&mut self, port: &mut dyn Write, card: &PunchCard) -> Result<()>;
+}
+
+/// One line of interpreted text per card; an empty line or `EOF` ends the hopper.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct TextLineProtocol;
+
+/// One line of 240 hex digits per card (three per column, 12-bit hole mask);
+/// an empty line or `EOF` ends the hopper. Preserves non-character punches.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct HexLineProtocol;
+
+impl CardProtocol for TextLineProtocol {
+    fn name(&self) -> &'static str {
+        "text"
+    }
+
+    fn read_card(&mut self, port: &mut dyn BufRead) -> Result<Option<CardRecord>> {
+        let Some(line) = read_line(port)? else {
+            return Ok(None);
+        };
+        CardRecord::from_text(line, EncodingKind::Hollerith, CardType::Data).map(Some)
+    }
+
+    fn punch_card(&mut self, port: &mut dyn Write, card: &PunchCard) -> Result<()> {
+        let text: String = card.text().iter().collect();
+        writeln!(port, "{}", text.trim_end())?;
+        port.flush()?;
+        Ok(())
+    }
+}
+
+impl CardProtocol for HexLineProtocol {
+    fn name(&self) -> &'static str {
+        "hex"
+    }
+
+    fn read_card(&mut self, port: &mut dyn BufRead) -> Result<Option<CardRecord>> {
+        let Some(line) = read_line(port)? else {
+            return Ok(None);
+        };
+        let masks = punches_from_hex(&line)?;
+        Ok(Some(record_from_masks(&masks)))
+    }
+
+    fn punch_card(&mut self, port: &mut dyn Write, card: &PunchCard) -> Result<()> {
+        writeln!(port, "{}", punches_to_hex(card.columns()))?;
+        port.flush()?;
+        Ok(())
+    }
+}
+
+/// Store cards as text when every column is a 029 character, otherwise as raw punches.
+pub fn record_from_masks(masks: &[CellMask]) -> CardRecord {
+    let decoded: Option<String> = masks.iter().map(|m| IBM029.decode(*m)).collect();
+    match decoded {
+        Some(text) => CardRecord::from_text(text, EncodingKind::Hollerith, CardType::Data)
+            .expect("at most 80 decoded columns"),
+        None => CardRecord::from_punches(masks, CardType::Data),
+    }
+}
+
+/// Open a serial device for reading and writing.
+pub fn open_port(path: &Path) -> Result<(BufReader<File>, File)> {
+    let file = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open(path)
+        .with_context(|| format!("failed to open device {}", path.display()))?;
+    let writer = file
+        .try_clone()
+        .with_context(|| format!("failed to share device handle {}", path.display()))?;
+    Ok((BufReader::new(file), writer))
+}
+
+fn read_line(port: &mut dyn BufRead) -> Result<Option<String>> {
+    let mut line = String::new();
+    if port.read_line(&mut line)? == 0 {
+        return Ok(None);
+    }
+    let line = line.trim_end_matches(['\r', '\n']);
+    if line.is_empty() || line == "EOF" {
+        return Ok(None);
+    }
+    if line.chars().count() > 240 {
+        return Err(anyhow!(
+            "device sent an overlong line ({} chars)",
+            line.len()
+        ));
+    }
+    Ok(Some(line.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    #[test]
+    fn hex_protocol_round_trips_binary_columns() {
+        let card =
+            PunchCard::from_columns(&[CellMask(0xfff), CellMask(0x001)], |m| IBM029.decode(m));
+        let mut wire = Vec::new();
+        HexLineProtocol.punch_card(&mut wire, &card).unwrap();
+        let record = HexLineProtocol
+            .read_card(&mut Cursor::new(wire))
+            .unwrap()
+            .unwrap();
+        assert!(record.text.is_none());
+        assert!(record.punches.unwrap().starts_with("fff001"));
+    }
+}
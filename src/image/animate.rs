@@ -0,0 +1,92 @@
+//! Animated flip-through of a deck (`punch render animate`).
+
+use anyhow::{Context, Result, anyhow};
+use image::codecs::gif::{GifEncoder, Repeat};
+use image::{Delay, Frame};
+
+use crate::core::punchcards::PunchCard;
+use crate::image::paint::{
+    CardImageStyle, CardSide, CornerCut, ImageRenderOptions, PageLayout, render_card_image,
+};
+
+/// GIF quantizer speed: 1 is best quality, 30 fastest; card faces have few
+/// colours, so a fast setting loses nothing visible.
+const QUANTIZER_SPEED: i32 = 10;
+
+/// Options for [`render_deck_gif`].
+#[derive(Debug, Clone, Copy)]
+pub struct AnimationOptions {
+    pub style: CardImageStyle,
+    /// Resolution of each frame; GIFs of whole decks get large quickly, so
+    /// screen resolutions (72–150) are usual.
+    pub dpi: u32,
+    /// How long each card stays up, in milliseconds.
+    pub delay_ms: u32,
+    /// Start over after the last card instead of stopping on it.
+    pub looping: bool,
+}
+
+/// Render `cards` as an animated GIF showing one card face per frame.
+pub fn render_deck_gif(cards: &[PunchCard], options: &AnimationOptions) -> Result<Vec<u8>> {
+    if cards.is_empty() {
+        return Err(anyhow!("cannot animate a deck with no cards"));
+    }
+    let frame_options = ImageRenderOptions {
+        style: options.style,
+        dpi: options.dpi,
+        layout: PageLayout::Card,
+        per_page: None,
+        side: CardSide::Front,
+        corner_cut: CornerCut::None,
+    };
+    let mut out = Vec::new();
+    {
+        let mut encoder = GifEncoder::new_with_speed(&mut out, QUANTIZER_SPEED);
+        let repeat = if options.looping {
+            Repeat::Infinite
+        } else {
+            Repeat::Finite(0)
+        };
+        encoder.set_repeat(repeat)?;
+        for (idx, card) in cards.iter().enumerate() {
+            let face = render_card_image(card, &frame_options)?.to_rgba8();
+            let frame =
+                Frame::from_parts(face, 0, 0, Delay::from_numer_denom_ms(options.delay_ms, 1));
+            encoder
+                .encode_frame(frame)
+                .with_context(|| format!("failed to encode card {}", idx + 1))?;
+        }
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::encoding::IBM029;
+    use image::AnimationDecoder;
+    use image::codecs::gif::GifDecoder;
+
+    #[test]
+    fn gif_has_one_frame_per_card() {
+        let cards: Vec<PunchCard> = ["HELLO", "WORLD", ""]
+            .iter()
+            .map(|text| PunchCard::from_str(&IBM029, text).unwrap())
+            .collect();
+        let options = AnimationOptions {
+            style: CardImageStyle::Plain,
+            dpi: 72,
+            delay_ms: 250,
+            looping: true,
+        };
+        let gif = render_deck_gif(&cards, &options).unwrap();
+        let frames = GifDecoder::new(std::io::Cursor::new(gif))
+            .unwrap()
+            .into_frames()
+            .collect_frames()
+            .unwrap();
+        assert_eq!(frames.len(), 3);
+        assert_eq!(frames[0].delay().numer_denom_ms(), (250, 1));
+        assert!(render_deck_gif(&[], &options).is_err());
+    }
+}
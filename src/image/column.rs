@@ -0,0 +1,214 @@
+//! Hole diagrams of single columns, for documentation, quizzes and viewers
+//! that show how a character is punched without drawing a whole card.
+//!
+//! A strip is one or more columns side by side with the row names down the
+//! left edge and each column's character above it. Unpunched positions in
+//! rows 0–9 show their digit, as printed on real card stock.
+
+use std::fmt::Write;
+
+use image::{DynamicImage, ImageBuffer};
+use imageproc::drawing::{draw_filled_rect_mut, draw_hollow_rect_mut};
+use imageproc::rect::Rect;
+
+use crate::core::encoding::{CellMask, ROW_NAMES};
+use crate::image::paint::{
+    CardImageStyle, GLYPH_HEIGHT, GLYPH_WIDTH, ROW_BIT_ORDER, draw_glyph, palette,
+};
+use crate::image::svg::{escape_xml, hex};
+
+/// Pixel scale of the 5×7 glyphs.
+const GLYPH_SCALE: u32 = 2;
+const CELL_WIDTH: u32 = 24;
+const ROW_HEIGHT: u32 = 20;
+/// Room left of the columns for the row names.
+const GUTTER: u32 = 34;
+/// Room above the rows for the printed characters.
+const HEADER: u32 = 26;
+const PADDING: u32 = 6;
+
+/// Render the holes of one column as a small PNG-ready image.
+pub fn render_column(mask: &CellMask, style: CardImageStyle) -> DynamicImage {
+    render_column_strip(&[(' ', *mask)], style)
+}
+
+/// Render columns side by side, each `(character, holes)` pair with its
+/// character printed above it (a blank prints nothing).
+pub fn render_column_strip(columns: &[(char, CellMask)], style: CardImageStyle) -> DynamicImage {
+    let palette = palette(style, true);
+    let (width, height) = strip_size(columns.len());
+    let mut image = ImageBuffer::from_pixel(width, height, palette.card_bg);
+    draw_hollow_rect_mut(
+        &mut image,
+        Rect::at(0, 0).of_size(width, height),
+        palette.border,
+    );
+    let glyph_w = (GLYPH_WIDTH as u32 * GLYPH_SCALE) as i32;
+    let glyph_h = (GLYPH_HEIGHT as u32 * GLYPH_SCALE) as i32;
+    let advance = glyph_w + GLYPH_SCALE as i32;
+
+    for (row, name) in ROW_NAMES.iter().enumerate() {
+        let label = name.to_string();
+        let x = GUTTER as i32 - PADDING as i32 - advance * label.len() as i32;
+        let y = row_center(row) - glyph_h / 2;
+        for (idx, ch) in label.chars().enumerate() {
+            draw_glyph(
+                &mut image,
+                x + idx as i32 * advance,
+                y,
+                ch,
+                palette.text,
+                GLYPH_SCALE,
+            );
+        }
+    }
+    let hole_w = CELL_WIDTH / 2;
+    let hole_h = ROW_HEIGHT * 7 / 10;
+    for (col, (ch, mask)) in columns.iter().enumerate() {
+        let cx = column_center(col);
+        if *ch != ' ' {
+            let y = (HEADER as i32 - glyph_h) / 2;
+            draw_glyph(
+                &mut image,
+                cx - glyph_w / 2,
+                y,
+                *ch,
+                palette.text,
+                GLYPH_SCALE,
+            );
+        }
+        for (row, bit) in ROW_BIT_ORDER.iter().enumerate() {
+            let cy = row_center(row);
+            if (mask.0 >> bit) & 1 == 1 {
+                let rect = Rect::at(cx - hole_w as i32 / 2, cy - hole_h as i32 / 2)
+                    .of_size(hole_w, hole_h);
+                draw_filled_rect_mut(&mut image, rect, palette.hole);
+            } else if row >= 2 {
+                let digit = char::from(b'0' + ROW_NAMES[row]);
+                draw_glyph(
+                    &mut image,
+                    cx - glyph_w / 2,
+                    cy - glyph_h / 2,
+                    digit,
+                    palette.grid,
+                    GLYPH_SCALE,
+                );
+            }
+        }
+    }
+    DynamicImage::ImageRgba8(image)
+}
+
+/// The same strip as [`render_column_strip`] as a standalone SVG document,
+/// sized in pixels so it drops into HTML next to the PNG version.
+pub fn render_column_strip_svg(columns: &[(char, CellMask)], style: CardImageStyle) -> String {
+    let palette = palette(style, true);
+    let (width, height) = strip_size(columns.len());
+    let mut out = String::with_capacity(2048 + columns.len() * 1024);
+    writeln!(
+        out,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{width}" height="{height}" viewBox="0 0 {width} {height}">"#
+    )
+    .unwrap();
+    writeln!(
+        out,
+        r#"<rect x="0.5" y="0.5" width="{}" height="{}" fill="{}" stroke="{}"/>"#,
+        width - 1,
+        height - 1,
+        hex(palette.card_bg),
+        hex(palette.border)
+    )
+    .unwrap();
+    writeln!(
+        out,
+        r#"<g font-family="monospace" font-size="14" dominant-baseline="central">"#
+    )
+    .unwrap();
+    for (row, name) in ROW_NAMES.iter().enumerate() {
+        writeln!(
+            out,
+            r#"<text x="{}" y="{}" fill="{}" text-anchor="end">{}</text>"#,
+            GUTTER - PADDING,
+            row_center(row),
+            hex(palette.text),
+            name
+        )
+        .unwrap();
+    }
+    let (hole_w, hole_h) = (CELL_WIDTH / 2, ROW_HEIGHT * 7 / 10);
+    for (col, (ch, mask)) in columns.iter().enumerate() {
+        let cx = column_center(col);
+        if *ch != ' ' {
+            writeln!(
+                out,
+                r#"<text x="{cx}" y="{}" fill="{}" text-anchor="middle">{}</text>"#,
+                HEADER / 2,
+                hex(palette.text),
+                escape_xml(*ch)
+            )
+            .unwrap();
+        }
+        for (row, bit) in ROW_BIT_ORDER.iter().enumerate() {
+            let cy = row_center(row);
+            if (mask.0 >> bit) & 1 == 1 {
+                writeln!(
+                    out,
+                    r#"<rect x="{}" y="{}" width="{hole_w}" height="{hole_h}" fill="{}"/>"#,
+                    cx - hole_w as i32 / 2,
+                    cy - hole_h as i32 / 2,
+                    hex(palette.hole)
+                )
+                .unwrap();
+            } else if row >= 2 {
+                writeln!(
+                    out,
+                    r#"<text x="{cx}" y="{cy}" fill="{}" text-anchor="middle">{}</text>"#,
+                    hex(palette.grid),
+                    ROW_NAMES[row]
+                )
+                .unwrap();
+            }
+        }
+    }
+    out.push_str("</g>\n</svg>\n");
+    out
+}
+
+fn strip_size(columns: usize) -> (u32, u32) {
+    (
+        GUTTER + columns.max(1) as u32 * CELL_WIDTH + PADDING,
+        HEADER + ROW_NAMES.len() as u32 * ROW_HEIGHT + PADDING,
+    )
+}
+
+fn column_center(col: usize) -> i32 {
+    (GUTTER + col as u32 * CELL_WIDTH + CELL_WIDTH / 2) as i32
+}
+
+fn row_center(row: usize) -> i32 {
+    (HEADER + row as u32 * ROW_HEIGHT + ROW_HEIGHT / 2) as i32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn strips_mark_punched_rows() {
+        let a = CellMask::from_rows(&[12, 1]).unwrap();
+        let style = CardImageStyle::Plain;
+        let image = render_column(&a, style).to_rgba8();
+        assert_eq!(image.dimensions(), strip_size(1));
+        let hole = palette(style, true).hole;
+        let at = |row: usize| *image.get_pixel(column_center(0) as u32, row_center(row) as u32);
+        assert_eq!(at(0), hole);
+        assert_eq!(at(3), hole);
+        assert_ne!(at(1), hole);
+
+        let strip = render_column_strip(&[('A', a), ('<', CellMask(0))], style);
+        assert_eq!(strip.width(), strip_size(2).0);
+        let svg = render_column_strip_svg(&[('A', a), ('<', CellMask(0))], style);
+        assert_eq!(svg.matches("<rect ").count(), 3);
+        assert!(svg.contains("&lt;"));
+    }
+}
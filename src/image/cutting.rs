@@ -0,0 +1,227 @@
+//! Hole coordinates for cutting real cards on a laser cutter or CNC router.
+//!
+//! Coordinates are hole centres measured from the card's lower-left corner
+//! (column 1 on the left, row 9 at the bottom) with Y pointing up, as most
+//! machine controllers expect. Dimensions follow the IBM 5081 card.
+
+use std::fmt::Write as _;
+
+use anyhow::{Context, Result, anyhow};
+
+use crate::core::deck::Deck;
+use crate::core::encoding::{PunchEncoding, ROW_NAMES};
+use crate::image::metrics::{
+    CARD_HEIGHT_IN, CARD_WIDTH_IN, COLUMN_PITCH_IN, FIRST_COLUMN_IN, FIRST_ROW_IN, HOLE_HEIGHT_IN,
+    HOLE_WIDTH_IN, ROW_PITCH_IN, inches_to_mm,
+};
+
+/// Length unit of exported coordinates.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CutUnit {
+    #[default]
+    Millimetres,
+    Inches,
+}
+
+impl CutUnit {
+    /// Short suffix used in CSV headers and comments.
+    pub fn suffix(self) -> &'static str {
+        match self {
+            CutUnit::Millimetres => "mm",
+            CutUnit::Inches => "in",
+        }
+    }
+
+    fn convert(self, inches: f32) -> f32 {
+        match self {
+            CutUnit::Millimetres => inches_to_mm(inches),
+            CutUnit::Inches => inches,
+        }
+    }
+
+    fn decimals(self) -> usize {
+        match self {
+            CutUnit::Millimetres => 3,
+            CutUnit::Inches => 4,
+        }
+    }
+}
+
+/// One hole to cut.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Hole {
+    /// Zero-based card index.
+    pub card: usize,
+    /// 1-based column.
+    pub column: usize,
+    /// Row label (12, 11, 0–9).
+    pub row: u8,
+    /// Centre, in the requested unit.
+    pub x: f32,
+    pub y: f32,
+}
+
+/// Every hole punched in `deck`, card by card, column by column.
+pub fn hole_positions(
+    deck: &Deck,
+    encoder: &dyn PunchEncoding,
+    unit: CutUnit,
+) -> Result<Vec<Hole>> {
+    let mut holes = Vec::new();
+    for (idx, record) in deck.cards.iter().enumerate() {
+        let punched = record
+            .to_punch_card(encoder)
+            .with_context(|| format!("card {}", idx + 1))?;
+        for (col, mask) in punched.columns().iter().enumerate() {
+            for row in mask.rows() {
+                let rank = ROW_NAMES.iter().position(|r| *r == row).ok_or_else(|| {
+                    anyhow!("card {} column {}: no card row {}", idx + 1, col + 1, row)
+                })?;
+                let from_top = FIRST_ROW_IN + rank as f32 * ROW_PITCH_IN;
+                holes.push(Hole {
+                    card: idx,
+                    column: col + 1,
+                    row,
+                    x: unit.convert(FIRST_COLUMN_IN + col as f32 * COLUMN_PITCH_IN),
+                    y: unit.convert(CARD_HEIGHT_IN - from_top),
+                });
+            }
+        }
+    }
+    Ok(holes)
+}
+
+/// CSV with one line per hole: card, column, row, centre and hole size.
+pub fn holes_csv(deck: &Deck, encoder: &dyn PunchEncoding, unit: CutUnit) -> Result<String> {
+    let (u, p) = (unit.suffix(), unit.decimals());
+    let (w, h) = (unit.convert(HOLE_WIDTH_IN), unit.convert(HOLE_HEIGHT_IN));
+    let mut out = format!("card,column,row,x_{u},y_{u},width_{u},height_{u}\n");
+    for hole in hole_positions(deck, encoder, unit)? {
+        writeln!(
+            out,
+            "{},{},{},{:.p$},{:.p$},{w:.p$},{h:.p$}",
+            hole.card + 1,
+            hole.column,
+            hole.row,
+            hole.x,
+            hole.y
+        )
+        .unwrap();
+    }
+    Ok(out)
+}
+
+/// G-code tracing each hole outline with the tool on (`M3`/`M5`), one card
+/// at a time. The program pauses (`M0`) before every card after the first so
+/// the operator can load a fresh blank at the origin.
+pub fn holes_gcode(deck: &Deck, encoder: &dyn PunchEncoding, unit: CutUnit) -> Result<String> {
+    let p = unit.decimals();
+    let (half_w, half_h) = (
+        unit.convert(HOLE_WIDTH_IN) / 2.0,
+        unit.convert(HOLE_HEIGHT_IN) / 2.0,
+    );
+    let feed = match unit {
+        CutUnit::Millimetres => 600.0,
+        CutUnit::Inches => 24.0,
+    };
+    let holes = hole_positions(deck, encoder, unit)?;
+    let mut by_card = vec![Vec::new(); deck.cards.len()];
+    for hole in &holes {
+        by_card[hole.card].push(hole);
+    }
+    let mut out = String::new();
+    writeln!(
+        out,
+        "; punchcard hole program: {} card(s), {} hole(s)",
+        deck.cards.len(),
+        holes.len()
+    )
+    .unwrap();
+    writeln!(
+        out,
+        "; origin at the card's lower-left corner, card {:.p$} x {:.p$} {}",
+        unit.convert(CARD_WIDTH_IN),
+        unit.convert(CARD_HEIGHT_IN),
+        unit.suffix()
+    )
+    .unwrap();
+    out.push_str(match unit {
+        CutUnit::Millimetres => "G21\n",
+        CutUnit::Inches => "G20\n",
+    });
+    out.push_str("G90\nM5\n");
+    for (card, card_holes) in by_card.iter().enumerate() {
+        if card > 0 {
+            out.push_str("M0 ; load next blank card\n");
+        }
+        writeln!(out, "; card {}", card + 1).unwrap();
+        for hole in card_holes {
+            let (x0, y0) = (hole.x - half_w, hole.y - half_h);
+            let (x1, y1) = (hole.x + half_w, hole.y + half_h);
+            writeln!(out, "G0 X{x0:.p$} Y{y0:.p$}").unwrap();
+            out.push_str("M3\n");
+            writeln!(out, "G1 X{x1:.p$} Y{y0:.p$} F{feed}").unwrap();
+            writeln!(out, "G1 X{x1:.p$} Y{y1:.p$}").unwrap();
+            writeln!(out, "G1 X{x0:.p$} Y{y1:.p$}").unwrap();
+            writeln!(out, "G1 X{x0:.p$} Y{y0:.p$}").unwrap();
+            out.push_str("M5\n");
+        }
+    }
+    out.push_str("G0 X0 Y0\nM2\n");
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::deck::{CardType, DeckHeader, EncodingKind};
+    use crate::core::encoding::IBM029;
+
+    fn deck() -> Deck {
+        Deck::from_lines(
+            DeckHeader::new(None, None, Vec::new()),
+            ["A", "", "1"],
+            EncodingKind::Hollerith,
+            CardType::Data,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn csv_lists_every_hole_from_the_lower_left_corner() {
+        let csv = holes_csv(&deck(), &IBM029, CutUnit::Inches).unwrap();
+        assert_eq!(
+            csv,
+            "card,column,row,x_in,y_in,width_in,height_in\n\
+             1,1,12,0.2510,3.0000,0.0550,0.1250\n\
+             1,1,1,0.2510,2.2500,0.0550,0.1250\n\
+             3,1,1,0.2510,2.2500,0.0550,0.1250\n"
+        );
+    }
+
+    #[test]
+    fn gcode_traces_holes_card_by_card() {
+        let trace = |y0: &str, y1: &str| {
+            format!(
+                "G0 X0.2235 Y{y0}\nM3\nG1 X0.2785 Y{y0} F24\nG1 X0.2785 Y{y1}\n\
+                 G1 X0.2235 Y{y1}\nG1 X0.2235 Y{y0}\nM5\n"
+            )
+        };
+        let expected = [
+            "; punchcard hole program: 3 card(s), 3 hole(s)\n",
+            "; origin at the card's lower-left corner, card 7.3750 x 3.2500 in\n",
+            "G20\nG90\nM5\n; card 1\n",
+            &trace("2.9375", "3.0625"),
+            &trace("2.1875", "2.3125"),
+            "M0 ; load next blank card\n; card 2\n",
+            "M0 ; load next blank card\n; card 3\n",
+            &trace("2.1875", "2.3125"),
+            "G0 X0 Y0\nM2\n",
+        ]
+        .concat();
+        assert_eq!(
+            holes_gcode(&deck(), &IBM029, CutUnit::Inches).unwrap(),
+            expected
+        );
+    }
+}
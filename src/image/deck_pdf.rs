@@ -0,0 +1,178 @@
+//! Printable PDF of a whole deck at true card size (`punch render pdf`).
+
+use anyhow::Result;
+use image::Rgba;
+
+use crate::core::punchcards::PunchCard;
+use crate::image::metrics::{CARD_HEIGHT_IN, CARD_WIDTH_IN, POINTS_PER_IN};
+use crate::image::paint::{CardImageStyle, PageLayout, ROW_BIT_ORDER, palette};
+use crate::image::pdf::{PdfDocument, PdfFont, PdfPage};
+
+const COURIER_ADVANCE: f32 = 0.6;
+const FOOTER_GRAY: [u8; 3] = [0x60, 0x60, 0x60];
+
+/// Options for [`render_deck_pdf`].
+#[derive(Debug, Clone, Copy)]
+pub struct DeckPdfOptions {
+    pub style: CardImageStyle,
+    /// `Card` gives one card-sized page per card; `A4` and `Letter` stack
+    /// cards on portrait sheets.
+    pub layout: PageLayout,
+    /// Cards per sheet; `None` fits as many as the sheet holds.
+    pub per_page: Option<usize>,
+}
+
+impl DeckPdfOptions {
+    /// Cards per page for this layout, checked against what the page holds.
+    pub fn cards_per_page(&self) -> Result<usize> {
+        self.layout.cards_per_page(self.per_page)
+    }
+}
+
+/// Render `cards` as a multi-page PDF with every card at its physical size.
+///
+/// Cards are drawn as vectors with the same palette and proportions as the
+/// PNG and SVG renderers. Sheets spread their cards evenly down the page
+/// and note the card range in the footer so printed sheets stay in order.
+pub fn render_deck_pdf(cards: &[PunchCard], options: &DeckPdfOptions) -> Result<Vec<u8>> {
+    let per_page = options.cards_per_page()?;
+    let (card_w, card_h) = (
+        CARD_WIDTH_IN * POINTS_PER_IN,
+        CARD_HEIGHT_IN * POINTS_PER_IN,
+    );
+    let mut doc = PdfDocument::new();
+    for (sheet, chunk) in cards.chunks(per_page).enumerate() {
+        let page = match options.layout.page_size_in() {
+            None => {
+                let mut page = PdfPage::new(card_w, card_h);
+                draw_card(&mut page, &chunk[0], options.style, 0.0, 0.0);
+                page
+            }
+            Some((width_in, height_in)) => {
+                let (page_w, page_h) = (width_in * POINTS_PER_IN, height_in * POINTS_PER_IN);
+                let mut page = PdfPage::new(page_w, page_h);
+                let gap = (page_h - per_page as f32 * card_h) / (per_page as f32 + 1.0);
+                let left = (page_w - card_w) / 2.0;
+                for (slot, card) in chunk.iter().enumerate() {
+                    let bottom = page_h - (slot as f32 + 1.0) * (gap + card_h);
+                    draw_card(&mut page, card, options.style, left, bottom);
+                }
+                let first = sheet * per_page + 1;
+                page.fill_color(FOOTER_GRAY).text(
+                    left,
+                    gap.min(0.5 * POINTS_PER_IN) / 2.0,
+                    PdfFont::Helvetica,
+                    8.0,
+                    &format!(
+                        "Cards {}-{} of {}",
+                        first,
+                        first + chunk.len() - 1,
+                        cards.len()
+                    ),
+                );
+                page
+            }
+        };
+        doc.push(page);
+    }
+    Ok(doc.to_bytes())
+}
+
+/// Draw one card with its bottom-left corner at `(x, y)` points.
+fn draw_card(page: &mut PdfPage, card: &PunchCard, style: CardImageStyle, x: f32, y: f32) {
+    let palette = palette(style, true);
+    let width = CARD_WIDTH_IN * POINTS_PER_IN;
+    let height = CARD_HEIGHT_IN * POINTS_PER_IN;
+    // Same proportions as the PNG painter: 0.18in side margins, 0.55in top, 0.35in bottom.
+    let margin_x = 0.18 * POINTS_PER_IN;
+    let margin_top = 0.55 * POINTS_PER_IN;
+    let margin_bottom = 0.35 * POINTS_PER_IN;
+    let col_count = card.columns().len();
+    let col_spacing = (width - 2.0 * margin_x) / (col_count as f32 - 1.0);
+    let row_spacing = (height - margin_top - margin_bottom) / (ROW_BIT_ORDER.len() as f32 - 1.0);
+    let top = y + height;
+
+    page.fill_color(rgb(palette.card_bg))
+        .fill_rect(x, y, width, height);
+    if let Some(header) = palette.header {
+        let band = 0.4 * POINTS_PER_IN;
+        page.fill_color(rgb(header))
+            .fill_rect(x, top - band, width, band);
+    }
+    page.stroke_color(rgb(palette.border))
+        .line_width(0.75)
+        .stroke_rect(x, y, width, height);
+
+    page.stroke_color(rgb(palette.grid)).line_width(0.35);
+    for col in (0..=col_count).filter(|c| *c == 0 || *c == col_count || c % 10 == 0) {
+        let gx = x + margin_x + col as f32 * col_spacing;
+        page.line(gx, top - margin_top, gx, y + margin_bottom);
+    }
+
+    let hole_w = col_spacing * 0.5;
+    let hole_h = row_spacing * 0.55;
+    page.fill_color(rgb(palette.hole));
+    for (col_idx, cell) in card.columns().iter().enumerate() {
+        let cx = x + margin_x + col_idx as f32 * col_spacing;
+        for (row_idx, bit) in ROW_BIT_ORDER.iter().enumerate() {
+            if (cell.0 >> bit) & 1 == 1 {
+                let cy = top - margin_top - row_idx as f32 * row_spacing;
+                page.fill_rect(cx - hole_w / 2.0, cy - hole_h / 2.0, hole_w, hole_h);
+            }
+        }
+    }
+
+    let size = 6.5;
+    let baseline = top - margin_top + row_spacing * 0.85 - size;
+    page.fill_color(rgb(palette.text));
+    for (col_idx, ch) in card.text().iter().enumerate() {
+        if *ch == ' ' {
+            continue;
+        }
+        let cx = x + margin_x + col_idx as f32 * col_spacing;
+        page.text(
+            cx - size * COURIER_ADVANCE / 2.0,
+            baseline,
+            PdfFont::Courier,
+            size,
+            &ch.to_string(),
+        );
+    }
+}
+
+fn rgb(color: Rgba<u8>) -> [u8; 3] {
+    [color[0], color[1], color[2]]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::encoding::IBM029;
+
+    #[test]
+    fn a4_sheets_hold_up_to_three_cards() {
+        let card = PunchCard::from_str(&IBM029, "HELLO (WORLD)").unwrap();
+        let cards = vec![card; 7];
+        let mut options = DeckPdfOptions {
+            style: CardImageStyle::Interpreter,
+            layout: PageLayout::A4,
+            per_page: None,
+        };
+        let pages = |pdf: Vec<u8>| {
+            String::from_utf8_lossy(&pdf)
+                .matches("/Type /Page /Parent")
+                .count()
+        };
+        assert_eq!(options.cards_per_page().unwrap(), 3);
+        assert_eq!(pages(render_deck_pdf(&cards, &options).unwrap()), 3);
+        options.per_page = Some(2);
+        assert_eq!(pages(render_deck_pdf(&cards, &options).unwrap()), 4);
+        options.per_page = Some(4);
+        assert!(render_deck_pdf(&cards, &options).is_err());
+        options.layout = PageLayout::Card;
+        options.per_page = None;
+        let pdf = render_deck_pdf(&cards, &options).unwrap();
+        assert!(String::from_utf8_lossy(&pdf).contains("/MediaBox [0 0 531.00 234.00]"));
+        assert_eq!(pages(pdf), 7);
+    }
+}
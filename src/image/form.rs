@@ -0,0 +1,158 @@
+//! Pre-printed coding form overlays for card images.
+//!
+//! FORTRAN and COBOL programs were punched on cards printed for the
+//! language: a rule between the statement label, continuation, statement
+//! and sequence fields, the narrow fields shaded and each field named along
+//! the bottom edge (the IBM 5081 general-purpose card left all of that
+//! blank). [`FormStyle`] prints a [`Template`]'s column zones the same way
+//! over any other painter.
+
+use image::{Rgba, RgbaImage};
+use imageproc::drawing::draw_filled_rect_mut;
+use imageproc::rect::Rect;
+
+use crate::core::punchcards::PunchCard;
+use crate::core::templates::Template;
+use crate::image::paint::{
+    CardGeometry, CardPainter, GLYPH_HEIGHT, GLYPH_WIDTH, Palette, draw_glyph,
+};
+
+/// A painter with a template's fields printed on the card as form lines,
+/// shading and field names.
+pub struct FormStyle<'a> {
+    pub base: &'a dyn CardPainter,
+    pub template: &'a Template,
+}
+
+impl CardPainter for FormStyle<'_> {
+    fn palette(&self, card_only: bool) -> Palette {
+        self.base.palette(card_only)
+    }
+
+    fn decorate(&self, image: &mut RgbaImage, geometry: &CardGeometry, card: &PunchCard) {
+        self.base.decorate(image, geometry, card);
+        let colors = self.base.palette(true);
+        // The main field stays unshaded so the statement reads as on a form.
+        let widest = self
+            .template
+            .columns
+            .iter()
+            .map(|zone| zone.range.end - zone.range.start)
+            .max()
+            .unwrap_or(0);
+        let bottom = geometry.row_y(11) + geometry.hole_radius;
+        let rule_width = (geometry.hole_radius / 3).max(1) as u32;
+        let label_scale = (((geometry.height as i32 - bottom) as f32 * 0.4) / GLYPH_HEIGHT as f32)
+            .floor()
+            .max(1.0) as u32;
+        let advance = ((GLYPH_WIDTH as u32 + 1) * label_scale) as i32;
+        let label_y = bottom
+            + (geometry.height as i32 - bottom - (GLYPH_HEIGHT as u32 * label_scale) as i32) / 2;
+
+        for zone in self.template.columns {
+            let left = zone_edge(geometry, zone.range.start - 1);
+            let right = zone_edge(geometry, zone.range.end);
+            if zone.range.end - zone.range.start < widest {
+                shade(image, colors.card_bg, colors.grid, left, right);
+            }
+            for edge in [left, right] {
+                if edge > 0 && edge < geometry.width as i32 - 1 {
+                    let rect = Rect::at(edge - rule_width as i32 / 2, 0)
+                        .of_size(rule_width, geometry.height);
+                    draw_filled_rect_mut(image, rect, colors.border);
+                }
+            }
+            let fits = ((right - left) / advance).max(0) as usize;
+            let name: Vec<char> = zone
+                .name
+                .chars()
+                .map(|ch| ch.to_ascii_uppercase())
+                .take(fits)
+                .collect();
+            let text_width = name.len() as i32 * advance - label_scale as i32;
+            let x = left + (right - left - text_width) / 2;
+            for (idx, ch) in name.iter().enumerate() {
+                draw_glyph(
+                    image,
+                    x + idx as i32 * advance,
+                    label_y,
+                    *ch,
+                    colors.text,
+                    label_scale,
+                );
+            }
+        }
+    }
+}
+
+/// X of the line before the zero-based column `col` (or after the last).
+fn zone_edge(geometry: &CardGeometry, col: usize) -> i32 {
+    (geometry.margin_x as f32 + (col as f32 - 0.5) * geometry.col_spacing).round() as i32
+}
+
+/// Tint the bare stock between `left` and `right` towards `ink`, leaving
+/// holes, printing and grid lines as they are.
+fn shade(image: &mut RgbaImage, stock: Rgba<u8>, ink: Rgba<u8>, left: i32, right: i32) {
+    let tint = Rgba([
+        blend(stock[0], ink[0]),
+        blend(stock[1], ink[1]),
+        blend(stock[2], ink[2]),
+        stock[3],
+    ]);
+    let (left, right) = (left.max(0) as u32, (right.max(0) as u32).min(image.width()));
+    for x in left..right {
+        for y in 0..image.height() {
+            if *image.get_pixel(x, y) == stock {
+                image.put_pixel(x, y, tint);
+            }
+        }
+    }
+}
+
+fn blend(stock: u8, ink: u8) -> u8 {
+    ((stock as u16 * 3 + ink as u16) / 4) as u8
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::encoding::IBM029;
+    use crate::core::templates::TemplateRegistry;
+    use crate::image::paint::{
+        CardImageStyle, CardSide, CornerCut, ImageRenderOptions, PageLayout, render_card_image_with,
+    };
+
+    #[test]
+    fn form_shades_narrow_fields_and_rules_zone_edges() {
+        let card = PunchCard::from_str(&IBM029, "").unwrap();
+        let style = CardImageStyle::Plain;
+        let options = ImageRenderOptions {
+            style,
+            dpi: 120,
+            layout: PageLayout::Card,
+            per_page: None,
+            side: CardSide::Front,
+            corner_cut: CornerCut::None,
+        };
+        let form = FormStyle {
+            base: &style,
+            template: TemplateRegistry::get("fortran").unwrap(),
+        };
+        let plain = render_card_image_with(&card, &style, &options)
+            .unwrap()
+            .to_rgba8();
+        let printed = render_card_image_with(&card, &form, &options)
+            .unwrap()
+            .to_rgba8();
+        assert_ne!(plain, printed);
+
+        let colors = style.palette(true);
+        let y = plain.height() / 2 + 3;
+        // Column 74 lies in the shaded sequence field, column 45 in the
+        // statement field left bare.
+        let column = |col: usize| (plain.width() as f32 * (col as f32 - 0.5) / 80.0) as u32;
+        assert_eq!(*plain.get_pixel(column(74), y), colors.card_bg);
+        assert_ne!(*printed.get_pixel(column(74), y), colors.card_bg);
+        assert_eq!(*printed.get_pixel(column(45), 3), colors.card_bg);
+    }
+}
@@ -0,0 +1,292 @@
+//! Sheet imposition: several cards per A4 page, ordered so that cutting the
+//! printed stack and piling the slots back up yields the deck in order.
+//!
+//! [`render_sheet_pages`] is the plain N-up layout: the deck in reading order,
+//! a few cards down each A4 or Letter page with cut marks at the corners.
+
+use anyhow::{Result, anyhow};
+use image::imageops::overlay;
+use image::{DynamicImage, ImageBuffer, Rgba, RgbaImage};
+use imageproc::drawing::{draw_hollow_rect_mut, draw_line_segment_mut};
+use imageproc::rect::Rect;
+
+use crate::core::punchcards::PunchCard;
+use crate::image::metrics::{A4_HEIGHT_IN, A4_WIDTH_IN, CARD_HEIGHT_IN, CARD_WIDTH_IN};
+use crate::image::paint::{
+    CardPainter, GLYPH_HEIGHT, GLYPH_WIDTH, ImageRenderOptions, PageLayout, draw_glyph,
+    inches_to_px, render_card_image_with,
+};
+
+/// Cards that fit down a portrait A4 sheet.
+pub const A4_CARD_SLOTS: usize = 3;
+
+/// Gap between a card edge and its cut marks.
+const CUT_MARK_OFFSET_IN: f32 = 0.0625;
+/// Length of each cut mark.
+const CUT_MARK_LENGTH_IN: f32 = 0.1875;
+
+/// How imposed sheets are printed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Imposition {
+    /// Fronts only, in cut-stack order.
+    CutStack,
+    /// Cut-stack order with a back page after each front carrying the
+    /// matching card backs (numbered so a dropped stack can be re-sorted).
+    Duplex,
+}
+
+/// Card indices printed in each slot of one sheet, top slot first.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Sheet {
+    pub front: Vec<Option<usize>>,
+    /// Empty unless the imposition is duplex.
+    pub back: Vec<Option<usize>>,
+}
+
+/// Assign `cards` cards to sheets of `slots` slots.
+///
+/// Slot `k` of sheet `s` holds card `k * sheets + s`, so each slot pile, once
+/// cut, is a contiguous run of the deck and the piles stack in order. Backs
+/// sit behind their fronts: slots are centred on the page, so flipping the
+/// sheet on its long edge leaves every slot over itself.
+pub fn impose(cards: usize, slots: usize, mode: Imposition) -> Vec<Sheet> {
+    let slots = slots.max(1);
+    let sheets = cards.div_ceil(slots);
+    (0..sheets)
+        .map(|sheet| {
+            let front: Vec<Option<usize>> = (0..slots)
+                .map(|slot| Some(slot * sheets + sheet).filter(|card| *card < cards))
+                .collect();
+            let back = match mode {
+                Imposition::CutStack => Vec::new(),
+                Imposition::Duplex => front.clone(),
+            };
+            Sheet { front, back }
+        })
+        .collect()
+}
+
+/// Render imposed A4 pages: each sheet's front, followed by its back in
+/// duplex mode. `options.layout` is ignored.
+pub fn render_imposed_pages(
+    cards: &[PunchCard],
+    painter: &dyn CardPainter,
+    options: &ImageRenderOptions,
+    mode: Imposition,
+) -> Result<Vec<DynamicImage>> {
+    let dpi = options.dpi.clamp(72, 1200);
+    let card_options = ImageRenderOptions {
+        dpi,
+        layout: PageLayout::Card,
+        ..*options
+    };
+    let palette = painter.palette(false);
+    let page_w = inches_to_px(A4_WIDTH_IN, dpi);
+    let page_h = inches_to_px(A4_HEIGHT_IN, dpi);
+    let card_w = inches_to_px(CARD_WIDTH_IN, dpi);
+    let card_h = inches_to_px(CARD_HEIGHT_IN, dpi);
+    let gap =
+        (page_h as i64 - A4_CARD_SLOTS as i64 * card_h as i64).max(0) / (A4_CARD_SLOTS as i64 + 1);
+    let origin = |slot: usize| {
+        let x = (page_w as i64 - card_w as i64).max(0) / 2;
+        (x, gap + slot as i64 * (card_h as i64 + gap))
+    };
+
+    let mut pages = Vec::new();
+    for sheet in impose(cards.len(), A4_CARD_SLOTS, mode) {
+        let mut front = ImageBuffer::from_pixel(page_w, page_h, palette.page_bg);
+        for (slot, card) in sheet.front.iter().enumerate() {
+            if let Some(card) = card {
+                let image = render_card_image_with(&cards[*card], painter, &card_options)?;
+                let (x, y) = origin(slot);
+                overlay(&mut front, &image.to_rgba8(), x, y);
+            }
+        }
+        pages.push(DynamicImage::ImageRgba8(front));
+        if sheet.back.is_empty() {
+            continue;
+        }
+        let mut back = ImageBuffer::from_pixel(page_w, page_h, palette.page_bg);
+        for (slot, card) in sheet.back.iter().enumerate() {
+            if let Some(card) = card {
+                let label = format!("CARD {} OF {}", card + 1, cards.len());
+                let image = card_back(card_w, card_h, dpi, palette.card_bg, palette.text, &label);
+                let (x, y) = origin(slot);
+                overlay(&mut back, &image, x, y);
+            }
+        }
+        pages.push(DynamicImage::ImageRgba8(back));
+    }
+    Ok(pages)
+}
+
+/// Render the deck in order, `options.per_page` cards down each page of
+/// `options.layout` (A4 or Letter), with cut marks outside every card corner.
+pub fn render_sheet_pages(
+    cards: &[PunchCard],
+    painter: &dyn CardPainter,
+    options: &ImageRenderOptions,
+) -> Result<Vec<DynamicImage>> {
+    let (page_w_in, page_h_in) = options
+        .layout
+        .page_size_in()
+        .ok_or_else(|| anyhow!("several cards per page need an A4 or Letter page"))?;
+    let per_page = options.layout.cards_per_page(options.per_page)?;
+    let dpi = options.dpi.clamp(72, 1200);
+    let card_options = ImageRenderOptions {
+        dpi,
+        layout: PageLayout::Card,
+        ..*options
+    };
+    let palette = painter.palette(false);
+    let page_w = inches_to_px(page_w_in, dpi);
+    let page_h = inches_to_px(page_h_in, dpi);
+    let card_w = inches_to_px(CARD_WIDTH_IN, dpi);
+    let card_h = inches_to_px(CARD_HEIGHT_IN, dpi);
+    let gap = (page_h as i64 - per_page as i64 * card_h as i64).max(0) / (per_page as i64 + 1);
+    let x = (page_w as i64 - card_w as i64).max(0) / 2;
+
+    let mut pages = Vec::new();
+    for chunk in cards.chunks(per_page) {
+        let mut page = ImageBuffer::from_pixel(page_w, page_h, palette.page_bg);
+        for (slot, card) in chunk.iter().enumerate() {
+            let image = render_card_image_with(card, painter, &card_options)?;
+            let y = gap + slot as i64 * (card_h as i64 + gap);
+            overlay(&mut page, &image.to_rgba8(), x, y);
+            draw_cut_marks(&mut page, (x, y), (card_w, card_h), dpi, palette.border);
+        }
+        pages.push(DynamicImage::ImageRgba8(page));
+    }
+    Ok(pages)
+}
+
+/// Short marks extending each card edge past its corners, offset so they
+/// stay visible after a slightly inaccurate cut.
+fn draw_cut_marks(
+    page: &mut RgbaImage,
+    (x, y): (i64, i64),
+    (width, height): (u32, u32),
+    dpi: u32,
+    ink: Rgba<u8>,
+) {
+    let offset = CUT_MARK_OFFSET_IN * dpi as f32;
+    let length = CUT_MARK_LENGTH_IN * dpi as f32;
+    let (left, top) = (x as f32, y as f32);
+    let (right, bottom) = (left + width as f32 - 1.0, top + height as f32 - 1.0);
+    for edge in [top, bottom] {
+        draw_line_segment_mut(
+            page,
+            (left - offset - length, edge),
+            (left - offset, edge),
+            ink,
+        );
+        draw_line_segment_mut(
+            page,
+            (right + offset, edge),
+            (right + offset + length, edge),
+            ink,
+        );
+    }
+    for edge in [left, right] {
+        draw_line_segment_mut(
+            page,
+            (edge, top - offset - length),
+            (edge, top - offset),
+            ink,
+        );
+        draw_line_segment_mut(
+            page,
+            (edge, bottom + offset),
+            (edge, bottom + offset + length),
+            ink,
+        );
+    }
+}
+
+/// Plain card stock with `label` printed in the middle.
+fn card_back(
+    width: u32,
+    height: u32,
+    dpi: u32,
+    stock: Rgba<u8>,
+    ink: Rgba<u8>,
+    label: &str,
+) -> RgbaImage {
+    let mut image = ImageBuffer::from_pixel(width, height, stock);
+    draw_hollow_rect_mut(&mut image, Rect::at(0, 0).of_size(width, height), ink);
+    let scale = ((dpi as f32 / 100.0).ceil() as u32).max(2);
+    let advance = (GLYPH_WIDTH as u32 + 1) * scale;
+    let text_w = advance * label.chars().count() as u32;
+    let x0 = (width as i32 - text_w as i32) / 2;
+    let y = (height as i32 - (GLYPH_HEIGHT as u32 * scale) as i32) / 2;
+    for (idx, ch) in label.chars().enumerate() {
+        draw_glyph(
+            &mut image,
+            x0 + (idx as u32 * advance) as i32,
+            y,
+            ch,
+            ink,
+            scale,
+        );
+    }
+    image
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cut_stack_piles_come_out_in_deck_order() {
+        let sheets = impose(7, 3, Imposition::Duplex);
+        assert_eq!(sheets.len(), 3);
+        assert_eq!(sheets[0].front, vec![Some(0), Some(3), Some(6)]);
+        assert_eq!(sheets[2].front, vec![Some(2), Some(5), None]);
+        assert_eq!(sheets[1].back, sheets[1].front);
+
+        let piled: Vec<usize> = (0..3)
+            .flat_map(|slot| sheets.iter().filter_map(move |s| s.front[slot]))
+            .collect();
+        assert_eq!(piled, (0..7).collect::<Vec<_>>());
+        assert!(impose(0, 3, Imposition::CutStack).is_empty());
+    }
+
+    #[test]
+    fn sheet_pages_paginate_in_order_with_cut_marks() {
+        use crate::core::encoding::IBM029;
+        use crate::image::paint::{CardImageStyle, CardSide, CornerCut};
+
+        let cards = vec![PunchCard::from_str(&IBM029, "N-UP").unwrap(); 7];
+        let style = CardImageStyle::Plain;
+        let mut options = ImageRenderOptions {
+            style,
+            dpi: 72,
+            layout: PageLayout::Letter,
+            per_page: None,
+            side: CardSide::Front,
+            corner_cut: CornerCut::None,
+        };
+        let pages = render_sheet_pages(&cards, &style, &options).unwrap();
+        assert_eq!(pages.len(), 3);
+        assert_eq!(pages[0].width(), inches_to_px(8.5, 72));
+
+        // A horizontal mark sits left of the top card's top-left corner.
+        let page = pages[0].to_rgba8();
+        let card_h = inches_to_px(CARD_HEIGHT_IN, 72) as i64;
+        let gap = (page.height() as i64 - 3 * card_h) / 4;
+        let x = (page.width() - inches_to_px(CARD_WIDTH_IN, 72)) / 2;
+        let mark = page.get_pixel(x - 8, gap as u32);
+        assert_eq!(*mark, style.palette(false).border);
+
+        options.per_page = Some(2);
+        assert_eq!(
+            render_sheet_pages(&cards, &style, &options).unwrap().len(),
+            4
+        );
+        options.per_page = Some(4);
+        assert!(render_sheet_pages(&cards, &style, &options).is_err());
+        options.layout = PageLayout::Card;
+        options.per_page = None;
+        assert!(render_sheet_pages(&cards, &style, &options).is_err());
+    }
+}
@@ -0,0 +1,202 @@
+//! Edge-on "deck of cards" view: the deck drawn as an isometric stack.
+
+use std::fmt::Write;
+
+use anyhow::{Result, anyhow};
+use image::{DynamicImage, ImageBuffer, Rgba};
+use imageproc::drawing::draw_polygon_mut;
+use imageproc::point::Point;
+
+use crate::core::deck::{CardRecord, CardType, Deck};
+use crate::image::metrics::{CARD_HEIGHT_IN, CARD_THICKNESS_IN, CARD_WIDTH_IN};
+use crate::image::svg::hex;
+
+/// Sizing for [`render_deck_isometric`].
+#[derive(Debug, Clone, Copy)]
+pub struct IsometricOptions {
+    /// Pixels (PNG) or user units (SVG) per inch.
+    pub scale: f32,
+    /// Multiplier applied to card thickness so small decks stay visible.
+    pub exaggeration: f32,
+}
+
+impl Default for IsometricOptions {
+    fn default() -> Self {
+        Self {
+            scale: 100.0,
+            exaggeration: 4.0,
+        }
+    }
+}
+
+const PAGE: Rgba<u8> = Rgba([0xfc, 0xf7, 0xef, 0xff]);
+const CARD_TOP: Rgba<u8> = Rgba([0xf6, 0xe3, 0xc6, 0xff]);
+const OUTLINE: Rgba<u8> = Rgba([0x86, 0x74, 0x5d, 0xff]);
+const MARGIN_IN: f32 = 0.3;
+
+/// Edge color of a card: its `meta.color` when recognised, a manila tone for
+/// separator cards, plain card stock otherwise.
+pub fn edge_color(card: &CardRecord) -> Rgba<u8> {
+    if let Some(color) = card.meta.color.as_deref().and_then(parse_color) {
+        return color;
+    }
+    match card.card_type {
+        CardType::Separator => Rgba([0xc8, 0x9b, 0x4a, 0xff]),
+        _ => Rgba([0xee, 0xd9, 0xb8, 0xff]),
+    }
+}
+
+fn parse_color(name: &str) -> Option<Rgba<u8>> {
+    let rgb = match name.trim().to_ascii_lowercase().as_str() {
+        "red" => [0xc0, 0x39, 0x2b],
+        "amber" | "orange" => [0xe6, 0x9b, 0x22],
+        "yellow" => [0xf1, 0xd3, 0x4a],
+        "green" => [0x3f, 0x9a, 0x5a],
+        "blue" => [0x3b, 0x6e, 0xc4],
+        "pink" => [0xe8, 0x8f, 0xb0],
+        "purple" => [0x8a, 0x5a, 0xb5],
+        "gray" | "grey" => [0x9a, 0x9a, 0x9a],
+        "black" => [0x30, 0x30, 0x30],
+        "white" | "cream" | "manila" => [0xf6, 0xe3, 0xc6],
+        other => {
+            let hex = other.strip_prefix('#')?;
+            if hex.len() != 6 {
+                return None;
+            }
+            let value = u32::from_str_radix(hex, 16).ok()?;
+            [(value >> 16) as u8, (value >> 8) as u8, value as u8]
+        }
+    };
+    Some(Rgba([rgb[0], rgb[1], rgb[2], 0xff]))
+}
+
+fn shade(color: Rgba<u8>, factor: f32) -> Rgba<u8> {
+    let c = |v: u8| (v as f32 * factor).round().clamp(0.0, 255.0) as u8;
+    Rgba([c(color[0]), c(color[1]), c(color[2]), color[3]])
+}
+
+/// A filled screen-space polygon.
+type Face = (Vec<(f32, f32)>, Rgba<u8>);
+
+/// Canvas size and the faces of the stack in painting order.
+fn faces(deck: &Deck, options: &IsometricOptions) -> Result<((f32, f32), Vec<Face>)> {
+    if deck.cards.is_empty() {
+        return Err(anyhow!("deck has no cards to draw"));
+    }
+    if options.scale <= 0.0 || options.exaggeration <= 0.0 {
+        return Err(anyhow!("scale and exaggeration must be positive"));
+    }
+    let (cos30, sin30) = (30f32.to_radians().cos(), 0.5);
+    let (w, d) = (CARD_WIDTH_IN, CARD_HEIGHT_IN);
+    let t = CARD_THICKNESS_IN * options.exaggeration;
+    let h = t * deck.cards.len() as f32;
+    let margin = MARGIN_IN;
+    // Shift so the leftmost (x=0, y=d) and topmost (x=y=0, z=h) points land on the margin.
+    let project = |x: f32, y: f32, z: f32| {
+        (
+            ((x - y) * cos30 + d * cos30 + margin) * options.scale,
+            ((x + y) * sin30 - z + h + margin) * options.scale,
+        )
+    };
+    let size = (
+        ((w + d) * cos30 + 2.0 * margin) * options.scale,
+        ((w + d) * sin30 + h + 2.0 * margin) * options.scale,
+    );
+
+    let mut polygons = Vec::with_capacity(deck.cards.len() * 2 + 1);
+    // Card 1 lies on top of the stack.
+    for (idx, card) in deck.cards.iter().enumerate() {
+        let z1 = h - idx as f32 * t;
+        let z0 = z1 - t;
+        let color = edge_color(card);
+        let texture = if idx % 2 == 0 { 1.0 } else { 0.97 };
+        polygons.push((
+            vec![
+                project(0.0, d, z0),
+                project(w, d, z0),
+                project(w, d, z1),
+                project(0.0, d, z1),
+            ],
+            shade(color, 0.92 * texture),
+        ));
+        polygons.push((
+            vec![
+                project(w, 0.0, z0),
+                project(w, d, z0),
+                project(w, d, z1),
+                project(w, 0.0, z1),
+            ],
+            shade(color, 0.78 * texture),
+        ));
+    }
+    polygons.push((
+        vec![
+            project(0.0, 0.0, h),
+            project(w, 0.0, h),
+            project(w, d, h),
+            project(0.0, d, h),
+        ],
+        deck.cards[0]
+            .meta
+            .color
+            .as_deref()
+            .and_then(parse_color)
+            .unwrap_or(CARD_TOP),
+    ));
+    Ok((size, polygons))
+}
+
+/// Draw `deck` edge-on as an isometric stack: height proportional to the card
+/// count, each card's edge striped in its meta color, separators in manila.
+pub fn render_deck_isometric(deck: &Deck, options: &IsometricOptions) -> Result<DynamicImage> {
+    let ((width, height), polygons) = faces(deck, options)?;
+    let mut canvas = ImageBuffer::from_pixel(width.ceil() as u32, height.ceil() as u32, PAGE);
+    for (points, color) in polygons {
+        let mut points: Vec<Point<i32>> = points
+            .iter()
+            .map(|(x, y)| Point::new(x.round() as i32, y.round() as i32))
+            .collect();
+        // imageproc rejects polygons whose first and last points coincide, which
+        // happens when a stripe rounds to zero height.
+        points.dedup();
+        if points.len() >= 3 && points.first() != points.last() {
+            draw_polygon_mut(&mut canvas, &points, color);
+        }
+    }
+    Ok(DynamicImage::ImageRgba8(canvas))
+}
+
+/// SVG counterpart of [`render_deck_isometric`].
+pub fn render_deck_isometric_svg(deck: &Deck, options: &IsometricOptions) -> Result<String> {
+    let ((width, height), polygons) = faces(deck, options)?;
+    let mut out = String::new();
+    writeln!(
+        out,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{width:.0}" height="{height:.0}" viewBox="0 0 {width:.2} {height:.2}">"#
+    )?;
+    writeln!(
+        out,
+        r#"<rect x="0" y="0" width="100%" height="100%" fill="{}"/>"#,
+        hex(PAGE)
+    )?;
+    let last = polygons.len() - 1;
+    for (idx, (points, color)) in polygons.iter().enumerate() {
+        let coords: Vec<String> = points
+            .iter()
+            .map(|(x, y)| format!("{x:.2},{y:.2}"))
+            .collect();
+        let stroke = if idx == last {
+            format!(r#" stroke="{}" stroke-width="1""#, hex(OUTLINE))
+        } else {
+            String::new()
+        };
+        writeln!(
+            out,
+            r#"<polygon points="{}" fill="{}"{stroke}/>"#,
+            coords.join(" "),
+            hex(*color)
+        )?;
+    }
+    out.push_str("</svg>\n");
+    Ok(out)
+}
@@ -0,0 +1,108 @@
+//! Storage labels for card boxes and drawer guide cards.
+
+use crate::image::metrics::{
+    BOX_LABEL_HEIGHT_IN, BOX_LABEL_WIDTH_IN, CARD_HEIGHT_IN, CARD_WIDTH_IN, POINTS_PER_IN,
+};
+use crate::image::pdf::{PdfDocument, PdfFont, PdfPage};
+
+/// Physical label stock.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LabelKind {
+    /// Small label for the end of a card box.
+    Box,
+    /// Card-sized guide card placed in front of the deck in a drawer.
+    Drawer,
+}
+
+/// Information printed on a storage label.
+#[derive(Debug, Clone)]
+pub struct BoxLabel {
+    pub title: String,
+    pub subtitle: Option<String>,
+    /// 1-based card range held by this box.
+    pub first_card: usize,
+    pub last_card: usize,
+    pub total_cards: usize,
+    pub date: String,
+    pub hash: String,
+    pub box_number: usize,
+    pub box_count: usize,
+}
+
+impl BoxLabel {
+    /// The text lines printed below the title.
+    pub fn detail_lines(&self) -> Vec<String> {
+        let mut lines = Vec::new();
+        if let Some(subtitle) = &self.subtitle {
+            lines.push(subtitle.clone());
+        }
+        lines.push(format!(
+            "Cards {}-{} of {}   Box {} of {}",
+            self.first_card, self.last_card, self.total_cards, self.box_number, self.box_count
+        ));
+        lines.push(format!("Date: {}", self.date));
+        lines.push(format!("SHA-256: {}", short_hash(&self.hash)));
+        lines
+    }
+
+    /// Plain-text rendition suitable for a label printer or a terminal.
+    pub fn render_text(&self) -> String {
+        let mut lines = vec![self.title.clone()];
+        lines.extend(self.detail_lines());
+        let width = lines.iter().map(|l| l.chars().count()).max().unwrap_or(0);
+        let border = format!("+{}+", "-".repeat(width + 2));
+        let mut out = format!("{}\n", border);
+        for line in lines {
+            out.push_str(&format!("| {:<width$} |\n", line, width = width));
+        }
+        out.push_str(&border);
+        out.push('\n');
+        out
+    }
+
+    /// PDF page at the physical size of the chosen label stock.
+    pub fn pdf_page(&self, kind: LabelKind) -> PdfPage {
+        let (w_in, h_in) = match kind {
+            LabelKind::Box => (BOX_LABEL_WIDTH_IN, BOX_LABEL_HEIGHT_IN),
+            LabelKind::Drawer => (CARD_WIDTH_IN, CARD_HEIGHT_IN),
+        };
+        let (w, h) = (w_in * POINTS_PER_IN, h_in * POINTS_PER_IN);
+        let scale = h_in / BOX_LABEL_HEIGHT_IN;
+        let margin = 10.0 * scale;
+        let title_size = 16.0 * scale;
+        let body_size = 8.5 * scale;
+
+        let mut page = PdfPage::new(w, h);
+        page.stroke_color([0x40, 0x40, 0x40])
+            .line_width(1.0)
+            .stroke_rect(2.0, 2.0, w - 4.0, h - 4.0);
+        let mut y = h - margin - title_size;
+        page.fill_color([0, 0, 0])
+            .text(margin, y, PdfFont::HelveticaBold, title_size, &self.title);
+        y -= 4.0 * scale;
+        page.line(margin, y, w - margin, y);
+        for line in self.detail_lines() {
+            y -= body_size * 1.5;
+            let font = if line.starts_with("SHA-256") {
+                PdfFont::Courier
+            } else {
+                PdfFont::Helvetica
+            };
+            page.text(margin, y, font, body_size, &line);
+        }
+        page
+    }
+}
+
+/// Render one PDF page per label.
+pub fn render_labels_pdf(labels: &[BoxLabel], kind: LabelKind) -> Vec<u8> {
+    let mut doc = PdfDocument::new();
+    for label in labels {
+        doc.push(label.pdf_page(kind));
+    }
+    doc.to_bytes()
+}
+
+fn short_hash(hash: &str) -> &str {
+    &hash[..hash.len().min(16)]
+}
@@ -0,0 +1,129 @@
+//! Multiple-card layout forms drawn from column templates.
+
+use crate::core::templates::{FieldKind, Template};
+use crate::image::metrics::{
+    A4_HEIGHT_IN, A4_WIDTH_IN, CARD_HEIGHT_IN, CARD_WIDTH_IN, POINTS_PER_IN,
+};
+use crate::image::pdf::{PdfDocument, PdfFont, PdfPage};
+
+const COURIER_ADVANCE: f32 = 0.6;
+const GRID_GRAY: [u8; 3] = [0xa0, 0xa0, 0xa0];
+const INK: [u8; 3] = [0x10, 0x10, 0x10];
+
+/// Render `template` as a one-page A4 landscape layout form: a full-size card
+/// with every column's 0–9 print positions, field boundaries and labels, and a
+/// legend of the fields underneath.
+pub fn render_template_layout_pdf(template: &Template) -> Vec<u8> {
+    let (page_w, page_h) = (A4_HEIGHT_IN * POINTS_PER_IN, A4_WIDTH_IN * POINTS_PER_IN);
+    let (card_w, card_h) = (
+        CARD_WIDTH_IN * POINTS_PER_IN,
+        CARD_HEIGHT_IN * POINTS_PER_IN,
+    );
+    let left = (page_w - card_w) / 2.0;
+    let top = page_h - 1.2 * POINTS_PER_IN;
+    let bottom = top - card_h;
+
+    let margin_x = 0.18 * POINTS_PER_IN;
+    let margin_top = 0.55 * POINTS_PER_IN;
+    let margin_bottom = 0.35 * POINTS_PER_IN;
+    let col_pitch = (card_w - 2.0 * margin_x) / 79.0;
+    let row_pitch = (card_h - margin_top - margin_bottom) / 11.0;
+    let col_x = |col: usize| left + margin_x + (col - 1) as f32 * col_pitch;
+
+    let mut page = PdfPage::new(page_w, page_h);
+    page.fill_color(INK)
+        .text(
+            left,
+            page_h - 0.6 * POINTS_PER_IN,
+            PdfFont::HelveticaBold,
+            16.0,
+            &format!("Multiple-card layout form: {}", template.name),
+        )
+        .text(
+            left,
+            page_h - 0.85 * POINTS_PER_IN,
+            PdfFont::Helvetica,
+            10.0,
+            template.description,
+        );
+
+    page.stroke_color(INK)
+        .line_width(1.0)
+        .stroke_rect(left, bottom, card_w, card_h);
+
+    // Print positions 0-9 in every column, as on the preprinted card.
+    page.fill_color(GRID_GRAY);
+    let digit_size = 5.0;
+    for col in 1..=80 {
+        let x = col_x(col) - digit_size * COURIER_ADVANCE / 2.0;
+        for digit in 0..=9u8 {
+            let y = top - margin_top - (digit as f32 + 2.0) * row_pitch - digit_size / 3.0;
+            page.text(x, y, PdfFont::Courier, digit_size, &digit.to_string());
+        }
+        let number = col.to_string();
+        let number_size = 3.5;
+        page.text(
+            col_x(col) - number.len() as f32 * number_size * COURIER_ADVANCE / 2.0,
+            bottom + margin_bottom / 2.0,
+            PdfFont::Courier,
+            number_size,
+            &number,
+        );
+    }
+
+    // Field boundaries and labels.
+    let label_size = 6.0;
+    page.stroke_color(INK).line_width(0.8).fill_color(INK);
+    for (idx, column) in template.columns.iter().enumerate() {
+        let x0 = col_x(column.range.start) - col_pitch / 2.0;
+        let x1 = col_x(column.range.end) + col_pitch / 2.0;
+        page.line(x0, bottom, x0, top).line(x1, bottom, x1, top);
+        let fits = ((x1 - x0 - 2.0) / (label_size * COURIER_ADVANCE))
+            .floor()
+            .max(0.0) as usize;
+        let marker = format!("{}", idx + 1);
+        let label: String = if column.label.chars().count() <= fits {
+            column.label.to_string()
+        } else if marker.len() <= fits {
+            marker
+        } else {
+            String::new()
+        };
+        page.text(
+            x0 + 1.5,
+            top - margin_top / 2.0,
+            PdfFont::Courier,
+            label_size,
+            &label,
+        );
+    }
+
+    // Legend.
+    let mut y = bottom - 0.4 * POINTS_PER_IN;
+    page.text(left, y, PdfFont::HelveticaBold, 10.0, "Fields");
+    for (idx, column) in template.columns.iter().enumerate() {
+        y -= 14.0;
+        let kind = match column.kind {
+            FieldKind::Text => String::new(),
+            kind => format!("  [{}]", kind),
+        };
+        page.text(
+            left,
+            y,
+            PdfFont::Courier,
+            9.0,
+            &format!(
+                "{:>2}. {:>2}-{:<2}  {}{}",
+                idx + 1,
+                column.range.start,
+                column.range.end,
+                column.label,
+                kind
+            ),
+        );
+    }
+
+    let mut doc = PdfDocument::new();
+    doc.push(page);
+    doc.to_bytes()
+}
@@ -0,0 +1,51 @@
+//! Physical dimensions of cards, paper and storage used by the renderers.
+
+/// IBM 5081 card width (7⅜ in).
+pub const CARD_WIDTH_IN: f32 = 7.375;
+/// IBM 5081 card height (3¼ in).
+pub const CARD_HEIGHT_IN: f32 = 3.25;
+/// Card stock thickness (0.007 in), i.e. roughly 143 cards per inch of deck.
+pub const CARD_THICKNESS_IN: f32 = 0.007;
+/// Distance between column centres (0.087 in).
+pub const COLUMN_PITCH_IN: f32 = 0.087;
+/// Distance between row centres (¼ in).
+pub const ROW_PITCH_IN: f32 = 0.25;
+/// Centre of column 1 from the card's left edge.
+pub const FIRST_COLUMN_IN: f32 = 0.251;
+/// Centre of row 12 from the card's top edge.
+pub const FIRST_ROW_IN: f32 = 0.25;
+/// Rectangular hole width.
+pub const HOLE_WIDTH_IN: f32 = 0.055;
+/// Rectangular hole height.
+pub const HOLE_HEIGHT_IN: f32 = 0.125;
+/// ISO A4 sheet width.
+pub const A4_WIDTH_IN: f32 = 8.27;
+/// ISO A4 sheet height.
+pub const A4_HEIGHT_IN: f32 = 11.69;
+/// US Letter sheet width.
+pub const LETTER_WIDTH_IN: f32 = 8.5;
+/// US Letter sheet height.
+pub const LETTER_HEIGHT_IN: f32 = 11.0;
+/// Cards held by a standard cardboard card box.
+pub const BOX_CAPACITY: usize = 2000;
+/// End-of-box label width (fits the 3½ in box end with a margin).
+pub const BOX_LABEL_WIDTH_IN: f32 = 3.25;
+/// End-of-box label height.
+pub const BOX_LABEL_HEIGHT_IN: f32 = 1.75;
+/// PostScript/PDF points per inch.
+pub const POINTS_PER_IN: f32 = 72.0;
+
+/// Convert inches to millimetres.
+pub fn inches_to_mm(inches: f32) -> f32 {
+    inches * 25.4
+}
+
+/// Height of a stacked deck of `cards` cards, in inches.
+pub fn deck_thickness_in(cards: usize) -> f32 {
+    cards as f32 * CARD_THICKNESS_IN
+}
+
+/// Number of standard boxes needed to hold `cards` cards (at least one).
+pub fn boxes_needed(cards: usize) -> usize {
+    cards.div_ceil(BOX_CAPACITY).max(1)
+}
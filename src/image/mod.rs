@@ -1,7 +1,38 @@
-//! Rendering helpers for producing PNG output of punch cards.
+//! Rendering helpers for producing PNG, SVG and PDF output of punch cards.
 
+mod animate;
+mod column;
+mod cutting;
+mod deck_pdf;
+mod form;
+mod impose;
+mod isometric;
+mod label;
+mod layout;
+pub mod metrics;
 mod paint;
+pub mod pdf;
+mod stack;
+mod stock;
+mod svg;
 
+pub use animate::{AnimationOptions, render_deck_gif};
+pub use column::{render_column, render_column_strip, render_column_strip_svg};
+pub use cutting::{CutUnit, Hole, hole_positions, holes_csv, holes_gcode};
+pub use deck_pdf::{DeckPdfOptions, render_deck_pdf};
+pub use form::FormStyle;
+pub use impose::{
+    A4_CARD_SLOTS, Imposition, Sheet, impose, render_imposed_pages, render_sheet_pages,
+};
+pub use isometric::{
+    IsometricOptions, edge_color, render_deck_isometric, render_deck_isometric_svg,
+};
+pub use label::{BoxLabel, LabelKind, render_labels_pdf};
+pub use layout::render_template_layout_pdf;
 pub use paint::{
-    CardImageStyle, GLYPH_HEIGHT, GLYPH_WIDTH, ImageRenderOptions, PageLayout, render_card_image,
+    CardGeometry, CardImageStyle, CardPainter, CardSide, CornerCut, GLYPH_HEIGHT, GLYPH_WIDTH,
+    ImageRenderOptions, PageLayout, Palette, render_card_image, render_card_image_with,
 };
+pub use stack::{DeckImageOptions, render_deck_image, render_deck_svg};
+pub use stock::{StockColors, TintedStyle, stock_color};
+pub use svg::render_card_svg;
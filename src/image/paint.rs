@@ -1,20 +1,19 @@
-use anyhow::Result;
+use anyhow::{Result, anyhow};
 use image::imageops::overlay;
-use image::{DynamicImage, ImageBuffer, Rgba};
+use image::{DynamicImage, ImageBuffer, Rgba, RgbaImage};
 use imageproc::drawing::{
     draw_filled_circle_mut, draw_filled_rect_mut, draw_hollow_rect_mut, draw_line_segment_mut,
 };
 use imageproc::rect::Rect;
 
 use crate::core::punchcards::PunchCard;
+use crate::image::metrics::{
+    A4_HEIGHT_IN, A4_WIDTH_IN, CARD_HEIGHT_IN, CARD_WIDTH_IN, LETTER_HEIGHT_IN, LETTER_WIDTH_IN,
+};
 
-const CARD_WIDTH_IN: f32 = 7.375;
-const CARD_HEIGHT_IN: f32 = 3.25;
-const A4_WIDTH_IN: f32 = 8.27;
-const A4_HEIGHT_IN: f32 = 11.69;
 pub const GLYPH_WIDTH: usize = 5;
 pub const GLYPH_HEIGHT: usize = 7;
-const ROW_BIT_ORDER: [usize; 12] = [11, 10, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9];
+pub(crate) const ROW_BIT_ORDER: [usize; 12] = [11, 10, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9];
 
 /// Visual styles for PNG rendering.
 #[derive(Debug, Clone, Copy)]
@@ -29,30 +28,149 @@ pub enum CardImageStyle {
 pub enum PageLayout {
     Card,
     A4,
+    Letter,
 }
 
+impl PageLayout {
+    /// Portrait page width and height in inches; `None` when the page is the card.
+    pub fn page_size_in(self) -> Option<(f32, f32)> {
+        match self {
+            PageLayout::Card => None,
+            PageLayout::A4 => Some((A4_WIDTH_IN, A4_HEIGHT_IN)),
+            PageLayout::Letter => Some((LETTER_WIDTH_IN, LETTER_HEIGHT_IN)),
+        }
+    }
+
+    /// Cards that fit down one page.
+    pub fn card_slots(self) -> usize {
+        self.page_size_in()
+            .map_or(1, |(_, height)| (height / CARD_HEIGHT_IN).floor() as usize)
+    }
+
+    /// Cards per page for a requested count (`None` fits as many as the page
+    /// holds), checked against what the page holds.
+    pub fn cards_per_page(self, requested: Option<usize>) -> Result<usize> {
+        let capacity = self.card_slots();
+        match requested {
+            None => Ok(capacity),
+            Some(n) if (1..=capacity).contains(&n) => Ok(n),
+            Some(n) => Err(anyhow!(
+                "{} cards do not fit on one page (1 to {})",
+                n,
+                capacity
+            )),
+        }
+    }
+}
+
+/// Which face of the card to draw.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CardSide {
+    #[default]
+    Front,
+    /// The reverse: plain stock with the holes mirrored left to right and
+    /// nothing printed.
+    Back,
+}
+
+/// Position of the corner cut that shows which way round a card sits, as
+/// seen from the front.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CornerCut {
+    #[default]
+    None,
+    UpperLeft,
+    UpperRight,
+}
+
+/// Width of a corner cut along the top edge.
+const CORNER_CUT_WIDTH_IN: f32 = 0.25;
+/// Height of a corner cut down the side edge.
+const CORNER_CUT_HEIGHT_IN: f32 = 0.43;
+
 /// Options controlling PNG generation.
 #[derive(Debug, Clone, Copy)]
 pub struct ImageRenderOptions {
     pub style: CardImageStyle,
     pub dpi: u32,
     pub layout: PageLayout,
+    /// Cards per A4 or Letter page when a deck is paginated with
+    /// [`render_sheet_pages`](crate::image::render_sheet_pages); `None` fits as
+    /// many as the page holds. Single-card rendering ignores it.
+    pub per_page: Option<usize>,
+    pub side: CardSide,
+    /// Cut corner, given for the front; it swaps sides on the back.
+    pub corner_cut: CornerCut,
+}
+
+/// Colours a [`CardPainter`] supplies for one card image.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Palette {
+    pub card_bg: Rgba<u8>,
+    /// Background around the card on A4 pages.
+    pub page_bg: Rgba<u8>,
+    pub grid: Rgba<u8>,
+    pub hole: Rgba<u8>,
+    pub text: Rgba<u8>,
+    pub border: Rgba<u8>,
+    /// Band behind the printed line, if the style has one.
+    pub header: Option<Rgba<u8>>,
+}
+
+/// Pixel positions of the column and row grid on a rendered card.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CardGeometry {
+    pub width: u32,
+    pub height: u32,
+    pub margin_x: i32,
+    pub margin_top: i32,
+    pub col_spacing: f32,
+    pub row_spacing: f32,
+    pub hole_radius: i32,
+}
+
+impl CardGeometry {
+    /// Horizontal centre of the zero-based column `col`.
+    pub fn column_x(&self, col: usize) -> i32 {
+        (self.margin_x as f32 + col as f32 * self.col_spacing).round() as i32
+    }
+
+    /// Vertical centre of the zero-based row `row` (0 is the 12 row).
+    pub fn row_y(&self, row: usize) -> i32 {
+        (self.margin_top as f32 + row as f32 * self.row_spacing).round() as i32
+    }
 }
 
-struct Palette {
-    card_bg: Rgba<u8>,
-    page_bg: Rgba<u8>,
-    grid: Rgba<u8>,
-    hole: Rgba<u8>,
-    text: Rgba<u8>,
-    border: Rgba<u8>,
-    header: Option<Rgba<u8>>,
+/// Visual style for card images. Implement it to supply custom colours and
+/// draw extra marks (form printing, annotations) over the rendered card; the
+/// built-in [`CardImageStyle`]s implement it too.
+pub trait CardPainter {
+    /// Colours for the card; `card_only` is false when it sits on an A4 page.
+    fn palette(&self, card_only: bool) -> Palette;
+
+    /// Draw on the card after the grid, holes and printed line. Does nothing by default.
+    fn decorate(&self, _image: &mut RgbaImage, _geometry: &CardGeometry, _card: &PunchCard) {}
+}
+
+impl CardPainter for CardImageStyle {
+    fn palette(&self, card_only: bool) -> Palette {
+        palette(*self, card_only)
+    }
 }
 
 /// Render a punch card into a PNG image using the supplied options.
 pub fn render_card_image(card: &PunchCard, options: &ImageRenderOptions) -> Result<DynamicImage> {
+    render_card_image_with(card, &options.style, options)
+}
+
+/// Render a punch card with a custom [`CardPainter`]; `options.style` is ignored.
+pub fn render_card_image_with(
+    card: &PunchCard,
+    painter: &dyn CardPainter,
+    options: &ImageRenderOptions,
+) -> Result<DynamicImage> {
     let dpi = options.dpi.clamp(72, 1200);
-    let palette = palette(options.style, matches!(options.layout, PageLayout::Card));
+    let palette = painter.palette(matches!(options.layout, PageLayout::Card));
 
     let card_width_px = inches_to_px(CARD_WIDTH_IN, dpi);
     let card_height_px = inches_to_px(CARD_HEIGHT_IN, dpi);
@@ -62,10 +180,10 @@ pub fn render_card_image(card: &PunchCard, options: &ImageRenderOptions) -> Resu
     let margin_top = (0.55 * dpi_f).round() as i32;
     let margin_bottom = (0.35 * dpi_f).round() as i32;
 
-    let mut card_img =
-        ImageBuffer::from_pixel(card_width_px, card_height_px, palette.card_bg.clone());
+    let mut card_img = ImageBuffer::from_pixel(card_width_px, card_height_px, palette.card_bg);
+    let front = options.side == CardSide::Front;
 
-    if let Some(header_color) = palette.header {
+    if let (true, Some(header_color)) = (front, palette.header) {
         let header_height = (0.4 * dpi_f).round() as u32;
         draw_filled_rect_mut(
             &mut card_img,
@@ -84,9 +202,18 @@ pub fn render_card_image(card: &PunchCard, options: &ImageRenderOptions) -> Resu
         / (ROW_BIT_ORDER.len() as f32 - 1.0);
     let hole_radius = (col_spacing.min(row_spacing) * 0.2).round() as i32;
     let hole_radius = hole_radius.max(2);
+    let geometry = CardGeometry {
+        width: card_width_px,
+        height: card_height_px,
+        margin_x,
+        margin_top,
+        col_spacing,
+        row_spacing,
+        hole_radius,
+    };
 
     for col in 0..=col_count {
-        if col == 0 || col == col_count || col % 10 == 0 {
+        if front && (col == 0 || col == col_count || col % 10 == 0) {
             let x = margin_x as f32 + col as f32 * col_spacing;
             draw_line_segment_mut(
                 &mut card_img,
@@ -98,10 +225,14 @@ pub fn render_card_image(card: &PunchCard, options: &ImageRenderOptions) -> Resu
     }
 
     for (col_idx, cell) in card.columns().iter().enumerate() {
-        let center_x = (margin_x as f32 + col_idx as f32 * col_spacing).round() as i32;
+        let center_x = if front {
+            geometry.column_x(col_idx)
+        } else {
+            geometry.column_x(col_count - 1 - col_idx)
+        };
         for (row_idx, bit) in ROW_BIT_ORDER.iter().enumerate() {
             if (cell.0 >> bit) & 1 == 1 {
-                let center_y = (margin_top as f32 + row_idx as f32 * row_spacing).round() as i32;
+                let center_y = geometry.row_y(row_idx);
                 draw_filled_circle_mut(
                     &mut card_img,
                     (center_x, center_y),
@@ -116,9 +247,9 @@ pub fn render_card_image(card: &PunchCard, options: &ImageRenderOptions) -> Resu
     let scale = scale.max(2);
     let glyph_half_width = ((GLYPH_WIDTH as u32 * scale) as f32 / 2.0).round() as i32;
     let text_baseline = (margin_top as f32 - row_spacing * 0.85).round() as i32;
-    for (col_idx, ch) in card.text().iter().enumerate() {
-        let center_x = (margin_x as f32 + col_idx as f32 * col_spacing).round() as i32;
-        let glyph_x = center_x - glyph_half_width;
+    let printed: &[char] = if front { card.text() } else { &[] };
+    for (col_idx, ch) in printed.iter().enumerate() {
+        let glyph_x = geometry.column_x(col_idx) - glyph_half_width;
         draw_glyph(
             &mut card_img,
             glyph_x,
@@ -129,13 +260,28 @@ pub fn render_card_image(card: &PunchCard, options: &ImageRenderOptions) -> Resu
         );
     }
 
-    let final_image = match options.layout {
-        PageLayout::Card => DynamicImage::ImageRgba8(card_img),
-        PageLayout::A4 => {
-            let page_width = inches_to_px(A4_WIDTH_IN, dpi);
-            let page_height = inches_to_px(A4_HEIGHT_IN, dpi);
-            let mut page =
-                ImageBuffer::from_pixel(page_width, page_height, palette.page_bg.clone());
+    if front {
+        painter.decorate(&mut card_img, &geometry, card);
+    }
+    let cut_left = match (options.corner_cut, options.side) {
+        (CornerCut::None, _) => None,
+        (CornerCut::UpperLeft, CardSide::Front) | (CornerCut::UpperRight, CardSide::Back) => {
+            Some(true)
+        }
+        (CornerCut::UpperRight, CardSide::Front) | (CornerCut::UpperLeft, CardSide::Back) => {
+            Some(false)
+        }
+    };
+    if let Some(left) = cut_left {
+        cut_corner(&mut card_img, left, dpi, palette.border);
+    }
+
+    let final_image = match options.layout.page_size_in() {
+        None => DynamicImage::ImageRgba8(card_img),
+        Some((width_in, height_in)) => {
+            let page_width = inches_to_px(width_in, dpi);
+            let page_height = inches_to_px(height_in, dpi);
+            let mut page = ImageBuffer::from_pixel(page_width, page_height, palette.page_bg);
             let offset_x = ((page_width as i32 - card_width_px as i32) / 2).max(0);
             let offset_y = ((page_height as i32 - card_height_px as i32) / 2).max(0);
             overlay(&mut page, &card_img, offset_x as i64, offset_y as i64);
@@ -146,11 +292,31 @@ pub fn render_card_image(card: &PunchCard, options: &ImageRenderOptions) -> Resu
     Ok(final_image)
 }
 
-fn inches_to_px(inches: f32, dpi: u32) -> u32 {
+/// Clear the triangle of an upper corner to transparent and draw the cut edge.
+fn cut_corner(card: &mut RgbaImage, left: bool, dpi: u32, edge: Rgba<u8>) {
+    let width = inches_to_px(CORNER_CUT_WIDTH_IN, dpi) as f32;
+    let height = inches_to_px(CORNER_CUT_HEIGHT_IN, dpi) as f32;
+    let last = card.width() as f32 - 1.0;
+    for y in 0..(height as u32).min(card.height()) {
+        let extent = (width * (1.0 - y as f32 / height)).round() as u32;
+        for dx in 0..extent.min(card.width()) {
+            let x = if left { dx } else { card.width() - 1 - dx };
+            card.put_pixel(x, y, Rgba([0, 0, 0, 0]));
+        }
+    }
+    let (top, side) = if left {
+        ((width, 0.0), (0.0, height))
+    } else {
+        ((last - width, 0.0), (last, height))
+    };
+    draw_line_segment_mut(card, top, side, edge);
+}
+
+pub(crate) fn inches_to_px(inches: f32, dpi: u32) -> u32 {
     (inches * dpi as f32).round() as u32
 }
 
-fn palette(style: CardImageStyle, card_only: bool) -> Palette {
+pub(crate) fn palette(style: CardImageStyle, card_only: bool) -> Palette {
     match style {
         CardImageStyle::Plain => Palette {
             card_bg: rgba(0xf4, 0xe8, 0xcc, 0xff),
@@ -198,7 +364,7 @@ fn rgba(r: u8, g: u8, b: u8, a: u8) -> Rgba<u8> {
     Rgba([r, g, b, a])
 }
 
-fn draw_glyph(
+pub(crate) fn draw_glyph(
     image: &mut ImageBuffer<Rgba<u8>, Vec<u8>>,
     x: i32,
     y: i32,
@@ -0,0 +1,224 @@
+//! Minimal vector PDF writer used for printable output (labels, forms, decks).
+//!
+//! Pages are built from a handful of drawing operations in points (1/72 in)
+//! with the origin at the bottom-left corner. Text uses the built-in Courier
+//! and Helvetica fonts with WinAnsi encoding, so no font embedding is needed.
+
+use std::fmt::Write;
+
+/// Built-in PDF base fonts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PdfFont {
+    Helvetica,
+    HelveticaBold,
+    Courier,
+}
+
+impl PdfFont {
+    fn resource(self) -> &'static str {
+        match self {
+            PdfFont::Helvetica => "F1",
+            PdfFont::HelveticaBold => "F2",
+            PdfFont::Courier => "F3",
+        }
+    }
+}
+
+/// A single page under construction.
+#[derive(Debug, Clone)]
+pub struct PdfPage {
+    width: f32,
+    height: f32,
+    content: Vec<u8>,
+}
+
+impl PdfPage {
+    /// Create an empty page of the given size in points.
+    pub fn new(width: f32, height: f32) -> Self {
+        Self {
+            width,
+            height,
+            content: Vec::new(),
+        }
+    }
+
+    /// Page width in points.
+    pub fn width(&self) -> f32 {
+        self.width
+    }
+
+    /// Page height in points.
+    pub fn height(&self) -> f32 {
+        self.height
+    }
+
+    /// Set the fill colour (0–255 RGB).
+    pub fn fill_color(&mut self, rgb: [u8; 3]) -> &mut Self {
+        self.op(format!("{} rg", rgb_components(rgb)))
+    }
+
+    /// Set the stroke colour (0–255 RGB).
+    pub fn stroke_color(&mut self, rgb: [u8; 3]) -> &mut Self {
+        self.op(format!("{} RG", rgb_components(rgb)))
+    }
+
+    /// Set the stroke width in points.
+    pub fn line_width(&mut self, width: f32) -> &mut Self {
+        self.op(format!("{:.2} w", width))
+    }
+
+    /// Fill a rectangle.
+    pub fn fill_rect(&mut self, x: f32, y: f32, w: f32, h: f32) -> &mut Self {
+        self.op(format!("{:.2} {:.2} {:.2} {:.2} re f", x, y, w, h))
+    }
+
+    /// Outline a rectangle.
+    pub fn stroke_rect(&mut self, x: f32, y: f32, w: f32, h: f32) -> &mut Self {
+        self.op(format!("{:.2} {:.2} {:.2} {:.2} re S", x, y, w, h))
+    }
+
+    /// Draw a straight line.
+    pub fn line(&mut self, x1: f32, y1: f32, x2: f32, y2: f32) -> &mut Self {
+        self.op(format!("{:.2} {:.2} m {:.2} {:.2} l S", x1, y1, x2, y2))
+    }
+
+    /// Draw text with its baseline starting at `(x, y)`.
+    pub fn text(&mut self, x: f32, y: f32, font: PdfFont, size: f32, text: &str) -> &mut Self {
+        let mut op = format!(
+            "BT /{} {:.2} Tf {:.2} {:.2} Td (",
+            font.resource(),
+            size,
+            x,
+            y
+        )
+        .into_bytes();
+        op.extend(encode_pdf_string(text));
+        op.extend_from_slice(b") Tj ET\n");
+        self.content.extend(op);
+        self
+    }
+
+    fn op(&mut self, op: String) -> &mut Self {
+        self.content.extend_from_slice(op.as_bytes());
+        self.content.push(b'\n');
+        self
+    }
+}
+
+/// A multi-page PDF document.
+#[derive(Debug, Clone, Default)]
+pub struct PdfDocument {
+    pages: Vec<PdfPage>,
+}
+
+impl PdfDocument {
+    /// Create an empty document.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append a finished page.
+    pub fn push(&mut self, page: PdfPage) {
+        self.pages.push(page);
+    }
+
+    /// Number of pages added so far.
+    pub fn page_count(&self) -> usize {
+        self.pages.len()
+    }
+
+    /// Serialize the document to PDF 1.4 bytes.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        // Object layout: 1 catalog, 2 page tree, 3–5 fonts, then (page, content) pairs.
+        const FIRST_PAGE_OBJ: usize = 6;
+        let mut objects: Vec<Vec<u8>> = Vec::new();
+        objects.push(b"<< /Type /Catalog /Pages 2 0 R >>".to_vec());
+        let kids: Vec<String> = (0..self.pages.len())
+            .map(|i| format!("{} 0 R", FIRST_PAGE_OBJ + 2 * i))
+            .collect();
+        objects.push(
+            format!(
+                "<< /Type /Pages /Kids [{}] /Count {} >>",
+                kids.join(" "),
+                self.pages.len()
+            )
+            .into_bytes(),
+        );
+        for base in ["Helvetica", "Helvetica-Bold", "Courier"] {
+            objects.push(
+                format!(
+                    "<< /Type /Font /Subtype /Type1 /BaseFont /{} /Encoding /WinAnsiEncoding >>",
+                    base
+                )
+                .into_bytes(),
+            );
+        }
+        for (i, page) in self.pages.iter().enumerate() {
+            let content_obj = FIRST_PAGE_OBJ + 2 * i + 1;
+            objects.push(
+                format!(
+                    "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {:.2} {:.2}] \
+                     /Resources << /Font << /F1 3 0 R /F2 4 0 R /F3 5 0 R >> >> \
+                     /Contents {} 0 R >>",
+                    page.width, page.height, content_obj
+                )
+                .into_bytes(),
+            );
+            let mut stream = format!("<< /Length {} >>\nstream\n", page.content.len()).into_bytes();
+            stream.extend_from_slice(&page.content);
+            stream.extend_from_slice(b"\nendstream");
+            objects.push(stream);
+        }
+
+        let mut out = b"%PDF-1.4\n%\xe2\xe3\xcf\xd3\n".to_vec();
+        let mut offsets = Vec::with_capacity(objects.len());
+        for (i, body) in objects.iter().enumerate() {
+            offsets.push(out.len());
+            out.extend(format!("{} 0 obj\n", i + 1).into_bytes());
+            out.extend_from_slice(body);
+            out.extend_from_slice(b"\nendobj\n");
+        }
+        let xref_at = out.len();
+        let mut xref = String::new();
+        writeln!(xref, "xref\n0 {}", objects.len() + 1).unwrap();
+        xref.push_str("0000000000 65535 f \n");
+        for offset in offsets {
+            writeln!(xref, "{:010} 00000 n ", offset).unwrap();
+        }
+        write!(
+            xref,
+            "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{}\n%%EOF\n",
+            objects.len() + 1,
+            xref_at
+        )
+        .unwrap();
+        out.extend(xref.into_bytes());
+        out
+    }
+}
+
+fn rgb_components(rgb: [u8; 3]) -> String {
+    format!(
+        "{:.3} {:.3} {:.3}",
+        rgb[0] as f32 / 255.0,
+        rgb[1] as f32 / 255.0,
+        rgb[2] as f32 / 255.0
+    )
+}
+
+/// Escape a string for a PDF literal, mapping to WinAnsi (Latin-1 subset).
+fn encode_pdf_string(text: &str) -> Vec<u8> {
+    let mut out = Vec::with_capacity(text.len());
+    for ch in text.chars() {
+        match ch {
+            '(' | ')' | '\\' => {
+                out.push(b'\\');
+                out.push(ch as u8);
+            }
+            c if (c as u32) < 0x20 => out.push(b' '),
+            c if (c as u32) < 0x100 => out.push(c as u32 as u8),
+            _ => out.push(b'?'),
+        }
+    }
+    out
+}
@@ -0,0 +1,133 @@
+//! Whole-deck images: every card stacked top to bottom on one canvas.
+
+use std::fmt::Write;
+
+use anyhow::{Result, anyhow};
+use image::imageops::overlay;
+use image::{DynamicImage, ImageBuffer};
+
+use crate::core::punchcards::CardDeck;
+use crate::image::metrics::{CARD_HEIGHT_IN, CARD_WIDTH_IN};
+use crate::image::paint::{
+    CardImageStyle, CardSide, CornerCut, ImageRenderOptions, PageLayout, palette, render_card_image,
+};
+use crate::image::svg::{UNITS_PER_IN, hex, write_card_body};
+
+/// Layout of a stacked deck image.
+#[derive(Debug, Clone, Copy)]
+pub struct DeckImageOptions {
+    pub style: CardImageStyle,
+    /// Raster resolution; ignored for SVG.
+    pub dpi: u32,
+    /// Vertical space between cards in inches; negative values overlap the
+    /// cards so only the top strip of each one shows.
+    pub gap_in: f32,
+    /// Horizontal shift per card in inches, fanning the deck out to the right.
+    pub fan_in: f32,
+    /// Border around the stack in inches.
+    pub margin_in: f32,
+}
+
+impl Default for DeckImageOptions {
+    fn default() -> Self {
+        Self {
+            style: CardImageStyle::Interpreter,
+            dpi: 150,
+            gap_in: 0.15,
+            fan_in: 0.0,
+            margin_in: 0.25,
+        }
+    }
+}
+
+impl DeckImageOptions {
+    fn validate(&self, deck: &CardDeck) -> Result<()> {
+        if deck.cards.is_empty() {
+            return Err(anyhow!("deck has no cards to render"));
+        }
+        if self.gap_in <= -CARD_HEIGHT_IN {
+            return Err(anyhow!(
+                "gap {}in would hide cards completely (card height is {}in)",
+                self.gap_in,
+                CARD_HEIGHT_IN
+            ));
+        }
+        if self.fan_in < 0.0 || self.margin_in < 0.0 {
+            return Err(anyhow!("fan and margin must not be negative"));
+        }
+        Ok(())
+    }
+
+    /// Canvas size and the top-left corner of card `idx`, in inches.
+    fn geometry(&self, count: usize) -> ((f32, f32), impl Fn(usize) -> (f32, f32) + '_) {
+        let steps = count.saturating_sub(1) as f32;
+        let width = CARD_WIDTH_IN + self.fan_in * steps + 2.0 * self.margin_in;
+        let height = CARD_HEIGHT_IN * count as f32 + self.gap_in * steps + 2.0 * self.margin_in;
+        let origin = move |idx: usize| {
+            (
+                self.margin_in + self.fan_in * idx as f32,
+                self.margin_in + (CARD_HEIGHT_IN + self.gap_in) * idx as f32,
+            )
+        };
+        ((width, height), origin)
+    }
+}
+
+/// Render every card of `deck` onto one tall PNG canvas; later cards are drawn
+/// over earlier ones when they overlap.
+pub fn render_deck_image(deck: &CardDeck, options: &DeckImageOptions) -> Result<DynamicImage> {
+    options.validate(deck)?;
+    let dpi = options.dpi.clamp(72, 1200);
+    let px = |inches: f32| (inches * dpi as f32).round() as i64;
+    let ((width, height), origin) = options.geometry(deck.cards.len());
+    let mut canvas = ImageBuffer::from_pixel(
+        px(width) as u32,
+        px(height) as u32,
+        palette(options.style, false).page_bg,
+    );
+    let card_options = ImageRenderOptions {
+        style: options.style,
+        dpi,
+        layout: PageLayout::Card,
+        per_page: None,
+        side: CardSide::Front,
+        corner_cut: CornerCut::None,
+    };
+    for (idx, card) in deck.cards.iter().enumerate() {
+        let image = render_card_image(card, &card_options)?.to_rgba8();
+        let (x, y) = origin(idx);
+        overlay(&mut canvas, &image, px(x), px(y));
+    }
+    Ok(DynamicImage::ImageRgba8(canvas))
+}
+
+/// SVG counterpart of [`render_deck_image`] at physical size.
+pub fn render_deck_svg(deck: &CardDeck, options: &DeckImageOptions) -> Result<String> {
+    options.validate(deck)?;
+    let ((width, height), origin) = options.geometry(deck.cards.len());
+    let mut out = String::with_capacity(deck.cards.len() * 16 * 1024);
+    writeln!(
+        out,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{width}in" height="{height}in" viewBox="0 0 {} {}">"#,
+        width * UNITS_PER_IN,
+        height * UNITS_PER_IN
+    )?;
+    writeln!(
+        out,
+        r#"<rect x="0" y="0" width="100%" height="100%" fill="{}"/>"#,
+        hex(palette(options.style, false).page_bg)
+    )?;
+    for (idx, card) in deck.cards.iter().enumerate() {
+        let (x, y) = origin(idx);
+        writeln!(
+            out,
+            r#"<g transform="translate({:.2} {:.2})">"#,
+            x * UNITS_PER_IN,
+            y * UNITS_PER_IN
+        )?;
+        write_card_body(&mut out, card, options.style);
+        writeln!(out, "</g>")?;
+    }
+    out.push_str("</svg>\n");
+    Ok(out)
+}
@@ -0,0 +1,171 @@
+//! Coloured card stock: tinting card faces from `meta.color` and card type.
+//!
+//! Shops punched JCL on pink stock, data on blue and patches on amber so a
+//! glance at a deck showed its structure. [`StockColors`] picks a face tint
+//! for each card (its own `meta.color` first, then its type) and
+//! [`TintedStyle`] paints a built-in style on that stock.
+
+use anyhow::{Result, anyhow};
+use image::{Rgba, RgbaImage};
+
+use crate::core::deck::{CardRecord, CardType};
+use crate::core::punchcards::PunchCard;
+use crate::image::paint::{CardGeometry, CardImageStyle, CardPainter, Palette, palette};
+
+/// Face tint of the card stock named `name`: a colour name (pale, as stock
+/// is dyed) or an exact `#rrggbb`.
+pub fn stock_color(name: &str) -> Option<Rgba<u8>> {
+    let rgb = match name.trim().to_ascii_lowercase().as_str() {
+        "red" => [0xf2, 0xc4, 0xbe],
+        "amber" | "orange" => [0xf6, 0xd9, 0xa2],
+        "yellow" => [0xf8, 0xee, 0xb0],
+        "green" => [0xcd, 0xe8, 0xc8],
+        "blue" => [0xc9, 0xdc, 0xf2],
+        "pink" => [0xf6, 0xcb, 0xdc],
+        "purple" => [0xdd, 0xcd, 0xee],
+        "gray" | "grey" => [0xdc, 0xdc, 0xdc],
+        "white" => [0xfa, 0xf8, 0xf2],
+        "cream" | "manila" => [0xf6, 0xe3, 0xc6],
+        other => {
+            let hex = other.strip_prefix('#')?;
+            if hex.len() != 6 {
+                return None;
+            }
+            let value = u32::from_str_radix(hex, 16).ok()?;
+            [(value >> 16) as u8, (value >> 8) as u8, value as u8]
+        }
+    };
+    Some(Rgba([rgb[0], rgb[1], rgb[2], 0xff]))
+}
+
+/// Which stock each card is drawn on.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StockColors {
+    /// Honour each card's `meta.color`.
+    pub use_meta: bool,
+    /// Stock for cards of a type, when the card names no colour.
+    pub by_type: Vec<(CardType, Rgba<u8>)>,
+}
+
+impl Default for StockColors {
+    /// Pink JCL, blue data and amber patch cards; others on plain stock.
+    fn default() -> Self {
+        let named = |name| stock_color(name).expect("built-in stock colour");
+        Self {
+            use_meta: true,
+            by_type: vec![
+                (CardType::Jcl, named("pink")),
+                (CardType::Data, named("blue")),
+                (CardType::Patch, named("amber")),
+            ],
+        }
+    }
+}
+
+impl StockColors {
+    /// Every card on the style's own stock.
+    pub fn plain() -> Self {
+        Self {
+            use_meta: false,
+            by_type: Vec::new(),
+        }
+    }
+
+    /// Draw cards of `card_type` on `color` (see [`stock_color`]), or on
+    /// plain stock for `none`.
+    pub fn set(&mut self, card_type: CardType, color: &str) -> Result<()> {
+        self.by_type.retain(|(kind, _)| *kind != card_type);
+        if color.trim().eq_ignore_ascii_case("none") {
+            return Ok(());
+        }
+        let tint = stock_color(color).ok_or_else(|| {
+            anyhow!(
+                "unknown stock colour '{}' (a colour name, #rrggbb or none)",
+                color
+            )
+        })?;
+        self.by_type.push((card_type, tint));
+        Ok(())
+    }
+
+    /// Tint for `card`, or `None` for the style's own stock.
+    pub fn tint(&self, card: &CardRecord) -> Option<Rgba<u8>> {
+        let own = self
+            .use_meta
+            .then(|| card.meta.color.as_deref().and_then(stock_color))
+            .flatten();
+        own.or_else(|| {
+            self.by_type
+                .iter()
+                .find(|(kind, _)| *kind == card.card_type)
+                .map(|(_, tint)| *tint)
+        })
+    }
+}
+
+/// A built-in style printed on tinted stock.
+#[derive(Debug, Clone, Copy)]
+pub struct TintedStyle {
+    pub style: CardImageStyle,
+    /// Face colour; `None` keeps the style's own stock.
+    pub stock: Option<Rgba<u8>>,
+}
+
+impl CardPainter for TintedStyle {
+    fn palette(&self, card_only: bool) -> Palette {
+        let mut colors = palette(self.style, card_only);
+        if let Some(stock) = self.stock {
+            if card_only {
+                colors.page_bg = stock;
+            }
+            colors.card_bg = stock;
+            colors.header = colors.header.map(|_| darken(stock, 0.93));
+            colors.grid = darken(stock, 0.85);
+        }
+        colors
+    }
+
+    fn decorate(&self, image: &mut RgbaImage, geometry: &CardGeometry, card: &PunchCard) {
+        self.style.decorate(image, geometry, card);
+    }
+}
+
+fn darken(color: Rgba<u8>, factor: f32) -> Rgba<u8> {
+    let c = |v: u8| (v as f32 * factor).round() as u8;
+    Rgba([c(color[0]), c(color[1]), c(color[2]), color[3]])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::deck::CardMeta;
+
+    #[test]
+    fn stock_follows_meta_then_type() {
+        let mut card = CardRecord::from_punches(&[], CardType::Jcl);
+        let stock = StockColors::default();
+        assert_eq!(stock.tint(&card), stock_color("pink"));
+        card.meta = CardMeta {
+            color: Some("#102030".into()),
+            ..CardMeta::default()
+        };
+        assert_eq!(stock.tint(&card), Some(Rgba([0x10, 0x20, 0x30, 0xff])));
+        assert_eq!(StockColors::plain().tint(&card), None);
+
+        let mut custom = StockColors::default();
+        custom.set(CardType::Jcl, "none").unwrap();
+        custom.set(CardType::Code, "green").unwrap();
+        card.meta.color = Some("no such colour".into());
+        assert_eq!(custom.tint(&card), None);
+        card.card_type = CardType::Code;
+        assert_eq!(custom.tint(&card), stock_color("green"));
+        assert!(custom.set(CardType::Data, "#12345").is_err());
+
+        let painter = TintedStyle {
+            style: CardImageStyle::Interpreter,
+            stock: stock_color("blue"),
+        };
+        assert_eq!(painter.palette(true).card_bg, stock_color("blue").unwrap());
+        assert_eq!(painter.palette(true).page_bg, stock_color("blue").unwrap());
+    }
+}
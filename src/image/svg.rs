@@ -0,0 +1,124 @@
+//! Vector (SVG) rendering of punch cards, sharing geometry and palettes with the PNG painter.
+
+use std::fmt::Write;
+
+use image::Rgba;
+
+use crate::core::punchcards::PunchCard;
+use crate::image::metrics::{CARD_HEIGHT_IN, CARD_WIDTH_IN};
+use crate::image::paint::{CardImageStyle, ROW_BIT_ORDER, palette};
+
+/// SVG user units per inch; the document scales to true card size via `width`/`height`.
+pub(crate) const UNITS_PER_IN: f32 = 100.0;
+
+/// Render a punch card as a standalone SVG document at physical card dimensions.
+pub fn render_card_svg(card: &PunchCard, style: CardImageStyle) -> String {
+    let width = CARD_WIDTH_IN * UNITS_PER_IN;
+    let height = CARD_HEIGHT_IN * UNITS_PER_IN;
+    let mut out = String::with_capacity(16 * 1024);
+    writeln!(
+        out,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{CARD_WIDTH_IN}in" height="{CARD_HEIGHT_IN}in" viewBox="0 0 {width} {height}">"#
+    )
+    .unwrap();
+    write_card_body(&mut out, card, style);
+    out.push_str("</svg>\n");
+    out
+}
+
+/// Append the elements of one card, drawn at the origin in [`UNITS_PER_IN`] units.
+pub(crate) fn write_card_body(out: &mut String, card: &PunchCard, style: CardImageStyle) {
+    let palette = palette(style, true);
+    let width = CARD_WIDTH_IN * UNITS_PER_IN;
+    let height = CARD_HEIGHT_IN * UNITS_PER_IN;
+    // Same proportions as the PNG painter: 0.18in side margins, 0.55in top, 0.35in bottom.
+    let margin_x = 0.18 * UNITS_PER_IN;
+    let margin_top = 0.55 * UNITS_PER_IN;
+    let margin_bottom = 0.35 * UNITS_PER_IN;
+    let col_count = card.columns().len();
+    let col_spacing = (width - 2.0 * margin_x) / (col_count as f32 - 1.0);
+    let row_spacing = (height - margin_top - margin_bottom) / (ROW_BIT_ORDER.len() as f32 - 1.0);
+
+    writeln!(
+        out,
+        r#"<rect x="0" y="0" width="{width}" height="{height}" fill="{}" stroke="{}"/>"#,
+        hex(palette.card_bg),
+        hex(palette.border)
+    )
+    .unwrap();
+    if let Some(header) = palette.header {
+        writeln!(
+            out,
+            r#"<rect x="0" y="0" width="{width}" height="{}" fill="{}"/>"#,
+            0.4 * UNITS_PER_IN,
+            hex(header)
+        )
+        .unwrap();
+    }
+    for col in (0..=col_count).filter(|c| *c == 0 || *c == col_count || c % 10 == 0) {
+        let x = margin_x + col as f32 * col_spacing;
+        writeln!(
+            out,
+            r#"<line x1="{x:.2}" y1="{margin_top}" x2="{x:.2}" y2="{}" stroke="{}" stroke-width="0.5"/>"#,
+            height - margin_bottom,
+            hex(palette.grid)
+        )
+        .unwrap();
+    }
+
+    let hole_w = col_spacing * 0.5;
+    let hole_h = row_spacing * 0.55;
+    writeln!(out, r#"<g fill="{}">"#, hex(palette.hole)).unwrap();
+    for (col_idx, cell) in card.columns().iter().enumerate() {
+        let cx = margin_x + col_idx as f32 * col_spacing;
+        for (row_idx, bit) in ROW_BIT_ORDER.iter().enumerate() {
+            if (cell.0 >> bit) & 1 == 1 {
+                let cy = margin_top + row_idx as f32 * row_spacing;
+                writeln!(
+                    out,
+                    r#"<rect x="{:.2}" y="{:.2}" width="{hole_w:.2}" height="{hole_h:.2}"/>"#,
+                    cx - hole_w / 2.0,
+                    cy - hole_h / 2.0
+                )
+                .unwrap();
+            }
+        }
+    }
+    writeln!(out, "</g>").unwrap();
+
+    let baseline = margin_top - row_spacing * 0.85 + 7.0;
+    writeln!(
+        out,
+        r#"<g fill="{}" font-family="monospace" font-size="9" text-anchor="middle">"#,
+        hex(palette.text)
+    )
+    .unwrap();
+    for (col_idx, ch) in card.text().iter().enumerate() {
+        if *ch == ' ' {
+            continue;
+        }
+        let x = margin_x + col_idx as f32 * col_spacing;
+        writeln!(
+            out,
+            r#"<text x="{x:.2}" y="{baseline:.2}">{}</text>"#,
+            escape_xml(*ch)
+        )
+        .unwrap();
+    }
+    writeln!(out, "</g>").unwrap();
+}
+
+pub(crate) fn hex(color: Rgba<u8>) -> String {
+    format!("#{:02x}{:02x}{:02x}", color[0], color[1], color[2])
+}
+
+pub(crate) fn escape_xml(ch: char) -> String {
+    match ch {
+        '&' => "&amp;".to_string(),
+        '<' => "&lt;".to_string(),
+        '>' => "&gt;".to_string(),
+        '"' => "&quot;".to_string(),
+        '\'' => "&apos;".to_string(),
+        other => other.to_string(),
+    }
+}
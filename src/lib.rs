@@ -1,21 +1,105 @@
 //! Core library entrypoint exporting domain types and rendering utilities.
+//!
+//! `core` is the single source of truth for deck, encoding and template types;
+//! everything re-exported here is the same item the CLI uses.
 
+pub mod bench;
 pub mod core;
+#[cfg(feature = "hardware")]
+pub mod hardware;
 pub mod image;
+pub mod testing;
 
+pub use core::formats;
+pub use core::journal;
+pub use core::pending;
+pub use core::pipeline;
+pub use core::signing;
+pub use core::simh;
+pub use core::text;
 pub use core::{
-    AuditEvent, CardDeck, CardMeta, CardRecord, CardType, ColumnRange, Deck, DeckHeader,
-    EncodingKind, Ibm029Encoder, PunchCard, PunchEncoding, RenderStyle, Template, TemplateRegistry,
-    ValidChar,
+    ASCII, AlignedCard, AsciiCardEncoder, AuditEvent, Baseline, Captions, CardDeck, CardDiff,
+    CardFix, CardHashMismatch, CardMatch, CardMeta, CardRecord, CardResult, CardType, CellMask,
+    CheckFinding, CheckFix, ColumnChange, ColumnRange, ColumnStats, DEFAULT_BASELINE, Deck,
+    DeckHeader, DeckKey, DeckLoadError, DeckRole, DeckSignature, DeckState, DelimiterKind,
+    DisputedCard, DrumAction, DrumCard, DrumField, EBCDIC, EDIT_NEW_CARD, EOF_MULTIPUNCH,
+    EbcdicEncoder, EditSummary, EncodeError, EncoderRegistry, EncodingConversion, EncodingKind,
+    FaultInjection, FieldKind, FieldSpec, FieldValue, FoldedPatch, HeaderMerge, IBM026,
+    IBM026_FORTRAN, IBM029, Ibm026Chart, Ibm026Encoder, Ibm029Encoder, InjectedFault,
+    InvalidCharMode, InvalidPunch, JobOptions, Keep, Keypunch, Language, LanguageCheck,
+    LanguageRegistry, LoadLimits, Locale, MachineRead, MachineRules, MaskFile, MaskSet,
+    MergeOutcome, Needle, NeedleSort, NotchCoding, NotchField, NotchLayout, OcrMismatch,
+    PASS_DIFF_LINES, PASS_HISTORY, PatchChain, PatchTarget, Pattern, PunchCard, PunchEncoding,
+    PunchMismatch, Query, Question, Quiz, QuizMode, ROW_NAMES, ReaderFault, ReaderRun,
+    Reconciliation, RenderStyle, RenumberEntry, RenumberPlan, SEQUENCE_FIELD, ShiftedBlock,
+    SignatureStatus, SortPass, SummaryCard, SummaryCheck, TableEncoder, TargetMachine, Template,
+    TemplateColumn, TemplateRegistry, TemplateSpec, Unmapped, VALID_SET, ValidChar, Verification,
+    VerifyMismatch, VerifyPass, VerifyReport, WORKSPACE_FILE, Workspace, WorkspaceDeck,
+    align_cards, assemble_job, audit_sidecar_path, byte_for_card_code, card_code, delimiter_card,
+    detect_shifts, diff_cards, diff_columns, diff_punches, infer_card_types, is_confusable,
+    is_instream_dd, lcs_pairs, merge3, merge3_header, ocr_cross_check, reconcile, redact_card,
+    scrub_history, sequence_field, unshift_card,
 };
 pub use image::{
-    CardImageStyle, GLYPH_HEIGHT, GLYPH_WIDTH, ImageRenderOptions, PageLayout, render_card_image,
+    AnimationOptions, BoxLabel, CardGeometry, CardImageStyle, CardPainter, CardSide, CornerCut,
+    CutUnit, DeckImageOptions, DeckPdfOptions, FormStyle, GLYPH_HEIGHT, GLYPH_WIDTH, Hole,
+    ImageRenderOptions, Imposition, IsometricOptions, LabelKind, PageLayout, Palette, StockColors,
+    TintedStyle, edge_color, hole_positions, holes_csv, holes_gcode, render_card_image,
+    render_card_image_with, render_card_svg, render_column, render_column_strip,
+    render_column_strip_svg, render_deck_gif, render_deck_image, render_deck_isometric,
+    render_deck_isometric_svg, render_deck_pdf, render_deck_svg, render_imposed_pages,
+    render_labels_pdf, render_sheet_pages, render_template_layout_pdf, stock_color,
 };
 
-use anyhow::Result;
+use anyhow::{Context, Result};
+
+/// Compile a deck file into the binary and parse it on use.
+///
+/// The path is resolved like [`include_str!`], relative to the calling file, so
+/// a missing deck fails the build. Parsing happens at runtime and panics if the
+/// embedded file is not a valid deck; call [`check_embedded_deck`] from the
+/// build script to catch that at build time instead. Wrap it in a `LazyLock`
+/// for a static:
+///
+/// ```
+/// static DEMO: std::sync::LazyLock<punchcard::Deck> =
+///     std::sync::LazyLock::new(|| punchcard::embed!("../tests/fixtures/hello.deck"));
+///
+/// assert_eq!(DEMO.cards.len(), 4);
+/// assert_eq!(DEMO.card_text(1).unwrap().trim_end(), "      PRINT 10");
+/// ```
+#[macro_export]
+macro_rules! embed {
+    ($path:expr) => {
+        $crate::Deck::from_jsonl(include_str!($path)).expect(concat!(
+            "embedded deck ",
+            $path,
+            " is not a valid deck"
+        ))
+    };
+}
+
+/// Build-script check for decks passed to [`embed!`]: parses the deck the way
+/// the macro will and asks Cargo to rebuild when the file changes, so an
+/// invalid deck fails the build rather than panicking at runtime.
+///
+/// ```no_run
+/// // build.rs
+/// fn main() -> anyhow::Result<()> {
+///     punchcard::check_embedded_deck("decks/hello.deck")?;
+///     Ok(())
+/// }
+/// ```
+pub fn check_embedded_deck(path: impl AsRef<std::path::Path>) -> Result<Deck> {
+    let path = path.as_ref();
+    println!("cargo:rerun-if-changed={}", path.display());
+    let source = std::fs::read_to_string(path)
+        .with_context(|| format!("failed to read deck file {}", path.display()))?;
+    Deck::from_jsonl(&source).with_context(|| format!("{} is not a valid deck", path.display()))
+}
 
 /// Splits the entire input text into 80-column punch cards and encodes them.
-pub fn encode_text_to_deck<E: PunchEncoding>(
+pub fn encode_text_to_deck<E: PunchEncoding + ?Sized>(
     encoder: &E,
     text: &str,
     with_seq_numbers: bool,
@@ -2,10 +2,26 @@
 
 mod cli;
 
-use anyhow::Result;
+use std::process::ExitCode;
+
 use clap::Parser;
 
-fn main() -> Result<()> {
-    let cli = cli::Cli::parse();
-    cli::run(cli)
+use crate::cli::exit::{EXIT_CLEAN, EXIT_ERROR, report};
+
+fn main() -> ExitCode {
+    let cli = match cli::Cli::try_parse() {
+        Ok(cli) => cli,
+        Err(err) => {
+            let _ = err.print();
+            return if err.use_stderr() {
+                ExitCode::from(EXIT_ERROR)
+            } else {
+                ExitCode::from(EXIT_CLEAN)
+            };
+        }
+    };
+    match cli::run(cli) {
+        Ok(()) => ExitCode::from(EXIT_CLEAN),
+        Err(err) => report(err),
+    }
 }
@@ -0,0 +1,209 @@
+//! Fixture decks and golden-output checks for tests (`punchcard::testing`).
+//!
+//! Each [`Fixture`] is a small canonical deck together with the SHA-256 of its
+//! ASCII rendering and of its card images, as produced by this release.
+//! Downstream crates (and the CLI's own tests) can rebuild a fixture, render it
+//! and compare with [`Fixture::verify`] to catch rendering changes between
+//! releases. Image digests cover decoded RGBA pixels rather than PNG bytes, so
+//! they do not move when the PNG encoder changes its compression.
+
+use anyhow::{Result, bail};
+use sha2::{Digest, Sha256};
+
+use crate::core::deck::{CardRecord, CardType, Deck, DeckHeader, EncodingKind};
+use crate::core::encoding::{CellMask, VALID_SET};
+use crate::core::job::{DelimiterKind, delimiter_card};
+use crate::core::punchcards::RenderStyle;
+use crate::image::{
+    CardImageStyle, CardSide, CornerCut, ImageRenderOptions, PageLayout, render_card_image,
+};
+
+/// Resolution the golden card images are rendered at.
+pub const GOLDEN_DPI: u32 = 100;
+
+/// Canonical deck with the digests of its golden renderings.
+#[derive(Debug, Clone, Copy)]
+pub struct Fixture {
+    pub name: &'static str,
+    pub description: &'static str,
+    /// SHA-256 of [`ascii_render`] output.
+    pub ascii_sha256: &'static str,
+    /// [`image_digest`] of the card images.
+    pub png_sha256: &'static str,
+    build: fn() -> Result<Deck>,
+}
+
+impl Fixture {
+    /// Build a fresh copy of the fixture deck.
+    pub fn deck(&self) -> Deck {
+        (self.build)().unwrap_or_else(|err| panic!("fixture {} is invalid: {err:#}", self.name))
+    }
+
+    /// Render the fixture and compare both digests with the golden values.
+    pub fn verify(&self) -> Result<()> {
+        let deck = self.deck();
+        check_digest(
+            &format!("{} ASCII rendering", self.name),
+            self.ascii_sha256,
+            &sha256_hex(ascii_render(&deck)?.as_bytes()),
+        )?;
+        check_digest(
+            &format!("{} card images", self.name),
+            self.png_sha256,
+            &image_digest(&deck)?,
+        )
+    }
+}
+
+/// Every fixture shipped with this release.
+pub const FIXTURES: &[Fixture] = &[
+    Fixture {
+        name: "fortran-hello",
+        description: "FORTRAN IV hello world, sequenced in columns 73-80",
+        ascii_sha256: "ef9a97fef596187bd0de1110ea28f3e83f93d4893fb74ba48a797239d4a1182c",
+        png_sha256: "6dc7872e23cb17a0bcabb4273efe6b849eefa8cbcda0de5487fbd16431b34934",
+        build: fortran_hello,
+    },
+    Fixture {
+        name: "charset-029",
+        description: "Every character of the IBM 029 chart",
+        ascii_sha256: "51b2d143f234de6bd622582c0f860b52ce886b41b007e1ddc0d586c43c501e31",
+        png_sha256: "ff02ac14ab04042b4acae7597d3e4e991a3d4bb5517e96bf2aa3599c7a7662f2",
+        build: charset_029,
+    },
+    Fixture {
+        name: "binary",
+        description: "Column-binary card, lace column and 6-7-9 end-of-file card",
+        ascii_sha256: "56ed52d10e4d773e0d30716e266d25025db85862988e3226f87ee3e705c8a9f5",
+        png_sha256: "28a79ff430f1384db7215612003c54d55440cef7de8e9adf9842c03f0101a3b5",
+        build: binary,
+    },
+];
+
+/// Look up a fixture by name.
+pub fn fixture(name: &str) -> Option<&'static Fixture> {
+    FIXTURES.iter().find(|fixture| fixture.name == name)
+}
+
+/// Render every card with [`RenderStyle::AsciiX`] on the deck's chart.
+pub fn ascii_render(deck: &Deck) -> Result<String> {
+    Ok(deck
+        .to_punch_deck(deck.encoder()?)?
+        .render(RenderStyle::AsciiX))
+}
+
+/// SHA-256 over the RGBA pixels of every card rendered in the interpreter
+/// style at [`GOLDEN_DPI`].
+pub fn image_digest(deck: &Deck) -> Result<String> {
+    let options = ImageRenderOptions {
+        style: CardImageStyle::Interpreter,
+        dpi: GOLDEN_DPI,
+        layout: PageLayout::Card,
+        per_page: None,
+        side: CardSide::Front,
+        corner_cut: CornerCut::None,
+    };
+    let mut hasher = Sha256::new();
+    for card in deck.to_punch_deck(deck.encoder()?)?.cards {
+        let image = render_card_image(&card, &options)?.to_rgba8();
+        hasher.update(image.width().to_le_bytes());
+        hasher.update(image.height().to_le_bytes());
+        hasher.update(image.as_raw());
+    }
+    Ok(format!("{:02x}", hasher.finalize()))
+}
+
+/// Lowercase hex SHA-256 of `bytes`.
+pub fn sha256_hex(bytes: &[u8]) -> String {
+    format!("{:02x}", Sha256::digest(bytes))
+}
+
+/// Fail with both digests when `actual` differs from `expected`.
+pub fn check_digest(what: &str, expected: &str, actual: &str) -> Result<()> {
+    if expected != actual {
+        bail!(
+            "{} changed: expected sha256 {}, got {}",
+            what,
+            expected,
+            actual
+        );
+    }
+    Ok(())
+}
+
+/// Fail at the first differing line when `actual` differs from `expected`.
+pub fn check_text(what: &str, expected: &str, actual: &str) -> Result<()> {
+    let mut expected_lines = expected.lines();
+    let mut actual_lines = actual.lines();
+    for line in 1.. {
+        match (expected_lines.next(), actual_lines.next()) {
+            (None, None) => return Ok(()),
+            (want, got) if want == got => continue,
+            (want, got) => bail!(
+                "{} differs at line {}:\n  expected: {}\n  actual:   {}",
+                what,
+                line,
+                want.unwrap_or("<end of output>"),
+                got.unwrap_or("<end of output>")
+            ),
+        }
+    }
+    unreachable!("line counter is unbounded")
+}
+
+fn fortran_hello() -> Result<Deck> {
+    let mut deck = Deck::from_lines(
+        DeckHeader::new(Some("fortran".to_string()), None, Vec::new()),
+        [
+            "C     HELLO WORLD IN FORTRAN IV",
+            "      PRINT 10",
+            "   10 FORMAT (12H HELLO WORLD)",
+            "      STOP",
+            "      END",
+        ],
+        EncodingKind::Hollerith,
+        CardType::Code,
+    )?;
+    deck.number_sequence(10, 10)?;
+    Ok(deck)
+}
+
+fn charset_029() -> Result<Deck> {
+    let chars: Vec<char> = VALID_SET.chars().collect();
+    let lines: Vec<String> = chars
+        .chunks(40)
+        .map(|chunk| chunk.iter().collect())
+        .collect();
+    Deck::from_lines(
+        DeckHeader::new(None, None, Vec::new()),
+        lines.iter().map(String::as_str),
+        EncodingKind::Hollerith,
+        CardType::Data,
+    )
+}
+
+fn binary() -> Result<Deck> {
+    let mut deck = Deck::new(DeckHeader::new(None, None, Vec::new()));
+    let words: Vec<CellMask> = (0..80u16).map(|col| CellMask(col * 51 % 4096)).collect();
+    deck.append_card(CardRecord::from_punches(&words, CardType::Data))?;
+    let mut lace = vec![CellMask(0); 80];
+    lace[0] = CellMask(0x0fff);
+    deck.append_card(CardRecord::from_punches(&lace, CardType::Data))?;
+    deck.append_card(delimiter_card(DelimiterKind::Eof))?;
+    Ok(deck)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fixtures_match_their_golden_renderings() {
+        for fixture in FIXTURES {
+            fixture.verify().unwrap();
+        }
+        assert!(check_text("t", "A\nB", "A\nC").is_err());
+        assert!(check_text("t", "A\nB", "A\nB\n").is_ok());
+        assert!(check_text("t", "A", "A\nB").is_err());
+    }
+}
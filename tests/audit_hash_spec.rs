@@ -0,0 +1,81 @@
+//! `punch audit hash` on decks whose cards no longer match their stored
+//! sha256.
+
+mod common;
+
+use common::Scratch;
+
+/// A deck whose second card was retyped by hand after it was saved.
+fn tampered() -> Scratch {
+    let scratch = Scratch::new();
+    scratch.deck("prog.deck", &["      CALL EXIT", "      END"]);
+    scratch.ok(&["deck", "import", "prog.deck.txt", "-o", "orig.deck"]);
+    let raw = std::fs::read_to_string(scratch.path("prog.deck")).unwrap();
+    scratch.write("prog.deck", raw.replacen("      END", "      FIN", 1));
+    scratch
+}
+
+#[test]
+fn tampered_decks_still_load_with_a_warning() {
+    let scratch = tampered();
+    let output = scratch.run(&["deck", "info", "prog.deck"]);
+    assert!(output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(
+        stderr.contains("1 card(s) in prog.deck do not match their stored sha256"),
+        "{}",
+        stderr
+    );
+    assert_eq!(
+        scratch.load("prog.deck").card_text(1).unwrap().trim_end(),
+        "      FIN"
+    );
+}
+
+#[test]
+fn audit_hash_lists_stored_and_recomputed_hashes() {
+    let scratch = tampered();
+    let deck = scratch.load("prog.deck");
+    let output = scratch.run(&["audit", "hash", "prog.deck"]);
+    assert_eq!(output.status.code(), Some(1));
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let mismatch = &deck.hash_mismatches[0];
+    assert!(
+        stdout.contains(&format!(
+            "prog.deck: card    2 (line 3) stored sha256 {} but hashes to {}",
+            mismatch.stored, mismatch.computed
+        )),
+        "{}",
+        stdout
+    );
+    assert_eq!(mismatch.computed, deck.card_hash(1).unwrap());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(
+        stderr.contains("1 card(s) do not match their stored sha256"),
+        "{}",
+        stderr
+    );
+
+    scratch.ok(&["audit", "hash", "orig.deck"]);
+}
+
+#[test]
+fn audit_hash_against_reports_both_problems() {
+    let scratch = tampered();
+    let stderr = scratch.fails(&["audit", "hash", "orig.deck", "--against", "prog.deck"], 1);
+    assert!(
+        stderr.contains(
+            "1 card(s) do not match their stored sha256; 1 card(s) differ from prog.deck"
+        ),
+        "{}",
+        stderr
+    );
+    let output = scratch.run(&["audit", "hash", "orig.deck", "--against", "prog.deck"]);
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(
+        stdout.contains("prog.deck: card    2 (line 3)"),
+        "{}",
+        stdout
+    );
+    assert!(stdout.contains("card    2 changed"), "{}", stdout);
+}
@@ -0,0 +1,83 @@
+//! `render image --side back --corner-cut`: card backs show the holes
+//! mirrored, and the corner cut moves to the other side with them.
+
+mod common;
+
+use std::cell::Cell;
+
+use common::Scratch;
+use image::RgbaImage;
+use punchcard::{
+    CardGeometry, CardImageStyle, CardPainter, CardSide, CornerCut, IBM029, ImageRenderOptions,
+    PageLayout, Palette, PunchCard, render_card_image_with,
+};
+
+/// The interpreter style, noting where the card grid ended up.
+struct Probe(Cell<Option<CardGeometry>>);
+
+impl CardPainter for Probe {
+    fn palette(&self, card_only: bool) -> Palette {
+        CardImageStyle::Interpreter.palette(card_only)
+    }
+
+    fn decorate(&self, _image: &mut RgbaImage, geometry: &CardGeometry, _card: &PunchCard) {
+        self.0.set(Some(*geometry));
+    }
+}
+
+fn geometry(dpi: u32) -> CardGeometry {
+    let probe = Probe(Cell::new(None));
+    let options = ImageRenderOptions {
+        style: CardImageStyle::Interpreter,
+        dpi,
+        layout: PageLayout::Card,
+        per_page: None,
+        side: CardSide::Front,
+        corner_cut: CornerCut::None,
+    };
+    let card = PunchCard::from_str(&IBM029, "").unwrap();
+    render_card_image_with(&card, &probe, &options).unwrap();
+    probe.0.get().expect("decorate ran")
+}
+
+fn render(scratch: &Scratch, name: &str, extra: &[&str]) -> RgbaImage {
+    let mut args = vec!["render", "image", "amp.deck", "-o", name, "--dpi", "150"];
+    args.extend_from_slice(extra);
+    scratch.ok(&args);
+    image::open(scratch.path(name)).unwrap().to_rgba8()
+}
+
+#[test]
+fn backs_mirror_holes_and_the_corner_cut() {
+    let scratch = Scratch::new();
+    // & is a lone 12 punch in column 1.
+    scratch.deck("amp.deck", &["&"]);
+    let geometry = geometry(150);
+    let hole = CardImageStyle::Interpreter.palette(true).hole;
+    let punched = |image: &RgbaImage, col: usize| {
+        *image.get_pixel(geometry.column_x(col) as u32, geometry.row_y(0) as u32) == hole
+    };
+    let cut = |image: &RgbaImage, x: u32| image.get_pixel(x, 1)[3] == 0;
+    let right = geometry.width - 2;
+
+    let front = render(&scratch, "front.png", &["--corner-cut", "upper-left"]);
+    assert!(punched(&front, 0));
+    assert!(!punched(&front, 79));
+    assert!(cut(&front, 1));
+    assert!(!cut(&front, right));
+
+    let back = render(
+        &scratch,
+        "back.png",
+        &["--side", "back", "--corner-cut", "upper-left"],
+    );
+    assert_eq!(back.dimensions(), front.dimensions());
+    assert!(punched(&back, 79));
+    assert!(!punched(&back, 0));
+    assert!(cut(&back, right));
+    assert!(!cut(&back, 1));
+
+    let uncut = render(&scratch, "uncut.png", &["--side", "back"]);
+    assert!(!cut(&uncut, 1));
+    assert!(!cut(&uncut, right));
+}
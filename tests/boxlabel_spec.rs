@@ -0,0 +1,54 @@
+//! `punch deck boxlabel`: storage labels for card boxes and drawers.
+
+mod common;
+
+use common::Scratch;
+
+#[test]
+fn labels_one_box_per_two_thousand_cards() {
+    let scratch = Scratch::new();
+    let lines: Vec<String> = (1..=2001).map(|n| format!("CARD {}", n)).collect();
+    let lines: Vec<&str> = lines.iter().map(String::as_str).collect();
+    scratch.deck("payroll.deck", &lines);
+    let hash = scratch.load("payroll.deck").hash().unwrap();
+
+    let text = scratch.ok(&["deck", "boxlabel", "payroll.deck"]);
+    assert!(text.contains("| payroll "), "{}", text);
+    assert!(text.contains("Cards 1-2000 of 2001"), "{}", text);
+    assert!(text.contains("Box 1 of 2"), "{}", text);
+    assert!(text.contains("Cards 2001-2001 of 2001"), "{}", text);
+    assert!(text.contains("Box 2 of 2"), "{}", text);
+    assert!(
+        text.contains(&format!("SHA-256: {}", &hash[..16])),
+        "{}",
+        text
+    );
+
+    let second = scratch.ok(&[
+        "deck",
+        "boxlabel",
+        "payroll.deck",
+        "--box",
+        "2",
+        "--title",
+        "PAYROLL 1968",
+    ]);
+    assert!(second.contains("| PAYROLL 1968 "), "{}", second);
+    assert!(!second.contains("Box 1 of 2"), "{}", second);
+
+    let stderr = scratch.fails(&["deck", "boxlabel", "payroll.deck", "--box", "3"], 3);
+    assert!(stderr.contains("box 3 out of range"), "{}", stderr);
+}
+
+#[test]
+fn writes_printable_labels_as_pdf() {
+    let scratch = Scratch::new();
+    scratch.deck("prog.deck", &["HELLO"]);
+    for kind in ["box", "drawer"] {
+        let name = format!("{}.pdf", kind);
+        let stdout = scratch.ok(&["deck", "boxlabel", "prog.deck", "-o", &name, "--kind", kind]);
+        assert!(stdout.contains("Wrote 1 label(s)"), "{}", stdout);
+        let pdf = std::fs::read(scratch.path(&name)).unwrap();
+        assert!(pdf.starts_with(b"%PDF-"), "{}", kind);
+    }
+}
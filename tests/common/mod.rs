@@ -0,0 +1,96 @@
+//! Shared helpers for the CLI specs: a scratch directory to run the
+//! `punchcard` binary in, and shortcuts for building decks there.
+
+#![allow(dead_code)]
+
+use std::path::PathBuf;
+use std::process::{Command, Output};
+
+use punchcard::Deck;
+use tempfile::TempDir;
+
+/// Scratch directory the binary runs in, removed when dropped.
+pub struct Scratch {
+    dir: TempDir,
+    env: Vec<(String, String)>,
+}
+
+impl Scratch {
+    pub fn new() -> Self {
+        Self {
+            dir: tempfile::tempdir().expect("scratch directory"),
+            env: Vec::new(),
+        }
+    }
+
+    /// Set `key` to `value` in the environment of every later run.
+    pub fn set_env(&mut self, key: &str, value: &str) {
+        self.env.push((key.to_string(), value.to_string()));
+    }
+
+    /// Path of `name` inside the scratch directory.
+    pub fn path(&self, name: &str) -> PathBuf {
+        self.dir.path().join(name)
+    }
+
+    /// Run `punchcard` with `args` in the scratch directory.
+    pub fn run(&self, args: &[&str]) -> Output {
+        Command::new(env!("CARGO_BIN_EXE_punchcard"))
+            .args(args)
+            .current_dir(self.dir.path())
+            .env_remove("PUNCH_DECK_KEY")
+            .env_remove("PUNCH_DECK_KEYFILE")
+            .env("RUST_BACKTRACE", "0")
+            .envs(self.env.iter().map(|(key, value)| (key, value)))
+            .output()
+            .expect("run punchcard")
+    }
+
+    /// Run `punchcard`, expect success, and return its stdout.
+    pub fn ok(&self, args: &[&str]) -> String {
+        let output = self.run(args);
+        assert!(
+            output.status.success(),
+            "punchcard {:?} failed: {}",
+            args,
+            String::from_utf8_lossy(&output.stderr)
+        );
+        String::from_utf8(output.stdout).expect("UTF-8 stdout")
+    }
+
+    /// Run `punchcard`, expect exit code `code`, and return its stderr.
+    pub fn fails(&self, args: &[&str], code: i32) -> String {
+        let output = self.run(args);
+        assert_eq!(
+            output.status.code(),
+            Some(code),
+            "punchcard {:?}: {}",
+            args,
+            String::from_utf8_lossy(&output.stdout)
+        );
+        String::from_utf8(output.stderr).expect("UTF-8 stderr")
+    }
+
+    /// Write `body` to `name` in the scratch directory, creating its
+    /// parent directories.
+    pub fn write(&self, name: &str, body: impl AsRef<[u8]>) -> PathBuf {
+        let path = self.path(name);
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir).expect("create scratch subdirectory");
+        }
+        std::fs::write(&path, body).expect("write scratch file");
+        path
+    }
+
+    /// Import `lines` as the deck `name`.
+    pub fn deck(&self, name: &str, lines: &[&str]) {
+        let source = format!("{}.txt", name);
+        self.write(&source, lines.join("\n") + "\n");
+        self.ok(&["deck", "import", &source, "-o", name]);
+    }
+
+    /// Load the deck `name` through the library.
+    pub fn load(&self, name: &str) -> Deck {
+        Deck::load(&self.path(name)).expect("load deck")
+    }
+}
@@ -0,0 +1,73 @@
+//! `punch deck encrypt/decrypt` with the undo journal and staged cards.
+
+mod common;
+
+use common::Scratch;
+
+/// A deck with a key file, and the key exported for commands without
+/// `--keyfile`.
+fn keyed() -> Scratch {
+    let mut scratch = Scratch::new();
+    scratch.deck("prog.deck", &["      CALL EXIT"]);
+    scratch.write("deck.key", "11".repeat(32));
+    let key = scratch.path("deck.key").display().to_string();
+    scratch.set_env("PUNCH_DECK_KEYFILE", &key);
+    scratch
+}
+
+fn sealed(scratch: &Scratch, name: &str) -> bool {
+    std::fs::read(scratch.path(name))
+        .unwrap()
+        .starts_with(b"PUNCHCARD-ENC1\n")
+}
+
+#[test]
+fn decrypt_after_editing_an_encrypted_deck() {
+    let scratch = keyed();
+    scratch.ok(&["deck", "encrypt", "prog.deck"]);
+    scratch.ok(&["card", "add", "prog.deck", "--text", "      END"]);
+    assert!(sealed(&scratch, "prog.journal.json"));
+
+    scratch.ok(&["deck", "decrypt", "prog.deck"]);
+    assert!(!sealed(&scratch, "prog.deck"));
+    assert!(!sealed(&scratch, "prog.journal.json"));
+    assert_eq!(scratch.load("prog.deck").cards.len(), 2);
+
+    // The plaintext journal still steps back through the encrypted saves.
+    scratch.ok(&["deck", "undo", "prog.deck"]);
+    scratch.ok(&["deck", "undo", "prog.deck"]);
+    assert_eq!(scratch.load("prog.deck").cards.len(), 1);
+}
+
+#[test]
+fn decrypt_needs_the_key() {
+    let scratch = Scratch::new();
+    scratch.deck("prog.deck", &["      CALL EXIT"]);
+    scratch.write("deck.key", "11".repeat(32));
+    scratch.ok(&["deck", "encrypt", "prog.deck", "--keyfile", "deck.key"]);
+    scratch.fails(&["deck", "decrypt", "prog.deck"], 3);
+    assert!(sealed(&scratch, "prog.deck"));
+}
+
+#[test]
+fn staged_cards_block_encrypt_and_redact() {
+    let scratch = keyed();
+    scratch.ok(&[
+        "card",
+        "add",
+        "prog.deck",
+        "--text",
+        "SECRET",
+        "--defer-save",
+    ]);
+    let err = scratch.fails(&["deck", "encrypt", "prog.deck"], 3);
+    assert!(err.contains("1 staged card(s)"), "{err}");
+    let err = scratch.fails(&["deck", "redact", "prog.deck", "--cols", "1-6"], 3);
+    assert!(err.contains("deck flush"), "{err}");
+    assert!(!sealed(&scratch, "prog.deck"));
+
+    scratch.ok(&["deck", "flush", "prog.deck"]);
+    scratch.ok(&["deck", "encrypt", "prog.deck"]);
+    assert!(!scratch.path("prog.pending.jsonl").exists());
+    assert!(sealed(&scratch, "prog.deck"));
+}
@@ -0,0 +1,85 @@
+//! `punch deck edit`: round-tripping a deck through an external editor.
+
+mod common;
+
+use common::Scratch;
+
+fn scratch() -> Scratch {
+    let scratch = Scratch::new();
+    scratch.deck("prog.deck", &["      CALL EXIT", "      END"]);
+    scratch
+}
+
+fn texts(scratch: &Scratch) -> Vec<String> {
+    let deck = scratch.load("prog.deck");
+    (0..deck.cards.len())
+        .map(|idx| deck.card_text(idx).unwrap().trim_end().to_string())
+        .collect()
+}
+
+/// Remove the buffer an error message says was kept, returning its text.
+fn take_kept_buffer(stderr: &str) -> String {
+    let path = stderr
+        .split("kept in ")
+        .nth(1)
+        .and_then(|rest| rest.split_whitespace().next())
+        .unwrap_or_else(|| panic!("no kept buffer in {:?}", stderr));
+    let text = std::fs::read_to_string(path).unwrap();
+    std::fs::remove_file(path).unwrap();
+    text
+}
+
+#[test]
+fn editor_changes_are_applied_and_logged() {
+    let scratch = scratch();
+    let out = scratch.ok(&[
+        "deck",
+        "edit",
+        "prog.deck",
+        "--editor",
+        "sed -i -e s/EXIT/STOP/ -e $a+|NEW",
+    ]);
+    assert!(out.contains("1 changed, 1 added, 0 removed"), "{}", out);
+    assert_eq!(texts(&scratch), ["      CALL STOP", "      END", "NEW"]);
+    let deck = scratch.load("prog.deck");
+    let last = deck.header.history.last().unwrap();
+    assert_eq!(last.action, "deck edit: 1 changed, 1 added, 0 removed");
+}
+
+#[test]
+fn an_untouched_buffer_changes_nothing() {
+    let scratch = scratch();
+    let out = scratch.ok(&["deck", "edit", "prog.deck", "--editor", "true"]);
+    assert!(out.contains("No changes made"), "{}", out);
+}
+
+#[test]
+fn failed_or_rejected_edits_keep_the_buffer() {
+    let scratch = scratch();
+    let stderr = scratch.fails(&["deck", "edit", "prog.deck", "--editor", "false"], 3);
+    assert!(take_kept_buffer(&stderr).contains("CALL EXIT"));
+
+    let stderr = scratch.fails(
+        &[
+            "deck",
+            "edit",
+            "prog.deck",
+            "--editor",
+            "sed -i s/^0001/0009/",
+        ],
+        3,
+    );
+    assert!(stderr.contains("card index '0009'"), "{}", stderr);
+    assert!(take_kept_buffer(&stderr).starts_with("# punch deck edit"));
+    assert_eq!(texts(&scratch), ["      CALL EXIT", "      END"]);
+}
+
+#[test]
+fn locked_decks_need_force() {
+    let scratch = scratch();
+    scratch.ok(&["deck", "lock", "prog.deck"]);
+    let edit = ["deck", "edit", "prog.deck", "--editor", "sed -i s/END/FIN/"];
+    scratch.fails(&edit, 3);
+    scratch.ok(&[&edit[..], &["--force"]].concat());
+    assert_eq!(texts(&scratch), ["      CALL EXIT", "      FIN"]);
+}
@@ -0,0 +1,61 @@
+//! `punch deck diff`: card-by-card comparison of two decks.
+
+mod common;
+
+use common::Scratch;
+
+fn fortran(scratch: &Scratch, name: &str, lines: &[&str]) {
+    let source = format!("{}.txt", name);
+    scratch.write(&source, lines.join("\n") + "\n");
+    scratch.ok(&[
+        "deck",
+        "import",
+        &source,
+        "-o",
+        name,
+        "--infer-types",
+        "--language",
+        "fortran",
+    ]);
+}
+
+#[test]
+fn masks_apply_per_card_type() {
+    let scratch = Scratch::new();
+    fortran(&scratch, "old.deck", &["C OLD COMMENT", "      X = 1"]);
+    fortran(&scratch, "new.deck", &["C NEW COMMENT", "      X = 2"]);
+    scratch.write(
+        "masks.toml",
+        "[comments]\nranges = [\"73-80\"]\ncard_types = { comment = [\"1-72\"] }\n",
+    );
+
+    let stdout = String::from_utf8(
+        scratch
+            .run(&[
+                "deck",
+                "diff",
+                "old.deck",
+                "new.deck",
+                "--mask-file",
+                "masks.toml",
+            ])
+            .stdout,
+    )
+    .unwrap();
+    assert!(
+        stdout.contains("~ card 2 -> 2: col 11: '1' -> '2'"),
+        "{stdout}"
+    );
+    assert!(!stdout.contains("card 1 -> 1"), "{stdout}");
+
+    scratch.ok(&[
+        "deck",
+        "diff",
+        "old.deck",
+        "new.deck",
+        "--mask-file",
+        "masks.toml",
+        "--mask",
+        "11",
+    ]);
+}
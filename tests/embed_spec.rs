@@ -0,0 +1,32 @@
+//! `embed!` and its build-script check, against a fixture deck.
+
+mod common;
+
+use common::Scratch;
+
+static HELLO: std::sync::LazyLock<punchcard::Deck> =
+    std::sync::LazyLock::new(|| punchcard::embed!("fixtures/hello.deck"));
+
+#[test]
+fn embedded_fixture_parses() {
+    assert_eq!(HELLO.cards.len(), 4);
+    assert_eq!(HELLO.card_text(3).unwrap().trim_end(), "      END");
+    assert!(HELLO.path.is_none());
+}
+
+#[test]
+fn build_check_accepts_the_fixture_and_rejects_a_broken_deck() {
+    let fixture = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/hello.deck");
+    let deck = punchcard::check_embedded_deck(fixture).unwrap();
+    assert_eq!(deck.hash().unwrap(), HELLO.hash().unwrap());
+
+    let scratch = Scratch::new();
+    scratch.write("broken.deck", "{\"kind\":\"card\"}\n");
+    let err = punchcard::check_embedded_deck(scratch.path("broken.deck")).unwrap_err();
+    assert!(
+        format!("{:#}", err).contains("is not a valid deck"),
+        "{:#}",
+        err
+    );
+    assert!(punchcard::check_embedded_deck(scratch.path("missing.deck")).is_err());
+}
@@ -0,0 +1,60 @@
+//! Decks that name a chart mapping file (`--encoder site.csv`).
+
+mod common;
+
+use common::Scratch;
+
+fn scratch() -> Scratch {
+    let scratch = Scratch::new();
+    scratch.write("decks/site.csv", "A,12-1\nX,0-8-2\n");
+    scratch.write("charts/other.csv", "A,12-1\n");
+    scratch
+}
+
+#[test]
+fn mapping_files_are_stored_relative_to_the_deck() {
+    let scratch = scratch();
+    scratch.ok(&[
+        "deck",
+        "init",
+        "decks/prog.deck",
+        "--encoder",
+        "decks/site.csv",
+    ]);
+    let deck = scratch.load("decks/prog.deck");
+    assert_eq!(deck.header.encoder.as_deref(), Some("site.csv"));
+    assert_eq!(deck.encoder().unwrap().name(), "SITE");
+
+    // A chart outside the deck's directory is stored by absolute path.
+    scratch.ok(&[
+        "deck",
+        "init",
+        "decks/two.deck",
+        "--encoder",
+        "charts/other.csv",
+    ]);
+    let stored = scratch.load("decks/two.deck").header.encoder.unwrap();
+    assert_eq!(
+        std::path::Path::new(&stored),
+        scratch.path("charts/other.csv").canonicalize().unwrap()
+    );
+}
+
+#[test]
+fn a_missing_mapping_file_fails_the_load() {
+    let scratch = scratch();
+    scratch.ok(&[
+        "deck",
+        "init",
+        "decks/prog.deck",
+        "--encoder",
+        "decks/site.csv",
+    ]);
+    std::fs::remove_file(scratch.path("decks/site.csv")).unwrap();
+    let stderr = scratch.fails(&["deck", "info", "decks/prog.deck"], 3);
+    assert!(stderr.contains("deck encoder 'site.csv'"), "{}", stderr);
+    scratch.fails(
+        &["deck", "init", "decks/new.deck", "--encoder", "missing.csv"],
+        3,
+    );
+}
@@ -0,0 +1,47 @@
+//! `punch git-merge`: the three-way merge driver for deck files.
+
+mod common;
+
+use common::Scratch;
+
+fn branches(scratch: &Scratch) {
+    scratch.deck("base.deck", &["      X = 1", "      END"]);
+    for side in ["ours.deck", "theirs.deck"] {
+        std::fs::copy(scratch.path("base.deck"), scratch.path(side)).unwrap();
+    }
+}
+
+#[test]
+fn merge_takes_header_changes_from_theirs() {
+    let scratch = Scratch::new();
+    branches(&scratch);
+    scratch.ok(&[
+        "deck",
+        "meta",
+        "theirs.deck",
+        "--description",
+        "payroll run",
+    ]);
+    scratch.ok(&["card", "add", "ours.deck", "--text", "C OURS"]);
+
+    scratch.ok(&["git-merge", "base.deck", "ours.deck", "theirs.deck"]);
+    let merged = scratch.load("ours.deck");
+    assert_eq!(merged.cards.len(), 3);
+    assert_eq!(merged.header.description.as_deref(), Some("payroll run"));
+}
+
+#[test]
+fn header_fields_changed_on_both_sides_conflict() {
+    let scratch = Scratch::new();
+    branches(&scratch);
+    scratch.ok(&["deck", "meta", "ours.deck", "--description", "ours"]);
+    scratch.ok(&["deck", "meta", "theirs.deck", "--description", "theirs"]);
+
+    let stderr = scratch.fails(&["git-merge", "base.deck", "ours.deck", "theirs.deck"], 3);
+    assert!(
+        stderr.contains("header field(s) description changed on both sides"),
+        "{stderr}"
+    );
+    let merged = scratch.load("ours.deck");
+    assert_eq!(merged.header.description.as_deref(), Some("ours"));
+}
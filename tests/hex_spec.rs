@@ -0,0 +1,160 @@
+//! `--format hex`: column dumps out of and back into decks.
+
+mod common;
+
+use common::Scratch;
+use pretty_assertions::assert_eq;
+
+#[test]
+fn hex_dumps_round_trip_through_import() {
+    let scratch = Scratch::new();
+    scratch.deck("prog.deck", &["HELLO, WORLD", "      END"]);
+    scratch.ok(&[
+        "deck",
+        "export",
+        "prog.deck",
+        "--format",
+        "hex",
+        "-o",
+        "prog.hex",
+    ]);
+    let dump = std::fs::read_to_string(scratch.path("prog.hex")).unwrap();
+    assert!(dump.lines().all(|line| line.len() == 240), "{}", dump);
+    assert!(dump.starts_with("900"), "{}", dump);
+
+    scratch.ok(&[
+        "deck",
+        "import",
+        "prog.hex",
+        "--format",
+        "hex",
+        "-o",
+        "back.deck",
+    ]);
+    let original = scratch.load("prog.deck");
+    let back = scratch.load("back.deck");
+    assert_eq!(back.cards.len(), 2);
+    for idx in 0..2 {
+        assert_eq!(
+            back.card_text(idx).unwrap(),
+            original.card_text(idx).unwrap()
+        );
+    }
+}
+
+#[test]
+fn malformed_hex_dumps_are_refused() {
+    let scratch = Scratch::new();
+    scratch.write("bad.hex", "900zzz\n");
+    let stderr = scratch.fails(
+        &[
+            "deck", "import", "bad.hex", "--format", "hex", "-o", "bad.deck",
+        ],
+        3,
+    );
+    assert!(stderr.contains("line 1"), "{}", stderr);
+    assert!(!scratch.path("bad.deck").exists());
+}
+
+#[test]
+fn non_ascii_punches_fail_instead_of_panicking() {
+    let scratch = Scratch::new();
+    scratch.write("bad.hex", "aa\u{e9}ab\n");
+    let stderr = scratch.fails(
+        &[
+            "deck", "import", "bad.hex", "--format", "hex", "-o", "bad.deck",
+        ],
+        3,
+    );
+    assert!(stderr.contains("is not a hex digit"), "{}", stderr);
+
+    scratch.write("ok.hex", "802\n");
+    scratch.ok(&[
+        "deck",
+        "import",
+        "ok.hex",
+        "--format",
+        "hex",
+        "-o",
+        "prog.deck",
+    ]);
+    let raw = std::fs::read_to_string(scratch.path("prog.deck")).unwrap();
+    let punches = scratch.load("prog.deck").cards[0].punches.clone().unwrap();
+    scratch.write("prog.deck", raw.replace(&punches, "aa\u{e9}ab"));
+    let stderr = scratch.fails(&["render", "interpret", "prog.deck"], 3);
+    assert!(stderr.contains("is not hex"), "{}", stderr);
+}
+
+#[test]
+fn removing_a_punch_only_card_logs_its_holes() {
+    let scratch = Scratch::new();
+    scratch.deck("prog.deck", &["HELLO"]);
+    scratch.ok(&[
+        "deck",
+        "export",
+        "prog.deck",
+        "--format",
+        "hex",
+        "-o",
+        "prog.hex",
+    ]);
+    scratch.ok(&[
+        "deck",
+        "import",
+        "prog.hex",
+        "--format",
+        "hex",
+        "-o",
+        "holes.deck",
+    ]);
+    let hex = scratch.load("holes.deck").cards[0].punches.clone().unwrap();
+    scratch.ok(&["card", "remove", "holes.deck", "--index", "1"]);
+    let deck = scratch.load("holes.deck");
+    let last = deck.header.history.last().unwrap();
+    assert_eq!(last.action, format!("card remove 1: punches {}", hex));
+}
+
+#[test]
+fn punch_only_cards_read_as_text_or_fail_loudly() {
+    let scratch = Scratch::new();
+    scratch.deck("prog.deck", &["HELLO"]);
+    scratch.ok(&[
+        "deck",
+        "export",
+        "prog.deck",
+        "--format",
+        "hex",
+        "-o",
+        "prog.hex",
+    ]);
+    scratch.ok(&[
+        "deck",
+        "import",
+        "prog.hex",
+        "--format",
+        "hex",
+        "-o",
+        "holes.deck",
+    ]);
+    let listing = scratch.ok(&["git-textconv", "holes.deck"]);
+    assert!(listing.contains("|HELLO "), "{listing}");
+
+    // A torn column group is an error, not a blank card.
+    let body = std::fs::read_to_string(scratch.path("holes.deck")).unwrap();
+    let hex = scratch.load("holes.deck").cards[0].punches.clone().unwrap();
+    scratch.write("holes.deck", body.replace(&hex, &hex[..11]));
+    let stderr = scratch.fails(
+        &[
+            "deck",
+            "export",
+            "holes.deck",
+            "--format",
+            "text80",
+            "-o",
+            "out.txt",
+        ],
+        3,
+    );
+    assert!(stderr.contains("card 1"), "{stderr}");
+    scratch.fails(&["deck", "diff", "holes.deck", "prog.deck"], 3);
+}
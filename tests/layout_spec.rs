@@ -0,0 +1,64 @@
+//! `punch template layout`: the multiple-card layout form for a template.
+
+mod common;
+
+use common::Scratch;
+
+#[test]
+fn draws_built_in_templates() {
+    let scratch = Scratch::new();
+    let form = scratch.ok(&["template", "layout", "fortran"]);
+    assert!(form.starts_with("Layout: fortran\n"), "{}", form);
+    assert!(
+        form.contains(".........1.........2.........3.........4"),
+        "{}",
+        form
+    );
+    assert!(
+        form.contains("73-80  Sequence number [sequence]"),
+        "{}",
+        form
+    );
+
+    let stderr = scratch.fails(&["template", "layout", "nosuch"], 3);
+    assert!(stderr.contains("nosuch"), "{}", stderr);
+}
+
+#[test]
+fn draws_user_defined_templates_as_text_and_pdf() {
+    let scratch = Scratch::new();
+    scratch.write(
+        "ledger.toml",
+        r#"name = "ledger-card"
+description = "Branch ledger."
+default_type = "data"
+
+[[fields]]
+name = "account"
+label = "Account number"
+columns = "1-8"
+kind = "text"
+
+[[fields]]
+name = "amount"
+label = "Amount"
+columns = "20-29"
+kind = "signed-numeric"
+"#,
+    );
+
+    scratch.ok(&["template", "layout", "ledger.toml", "-o", "ledger.txt"]);
+    let form = std::fs::read_to_string(scratch.path("ledger.txt")).unwrap();
+    assert!(form.starts_with("Layout: ledger-card\n"), "{}", form);
+    assert!(form.contains(" 1- 8  Account number"), "{}", form);
+    assert!(form.contains("20-29  Amount"), "{}", form);
+
+    let stdout = scratch.ok(&["template", "layout", "ledger.toml", "-o", "ledger.pdf"]);
+    assert!(
+        stdout.contains("Wrote layout form for ledger-card"),
+        "{}",
+        stdout
+    );
+    let pdf = std::fs::read(scratch.path("ledger.pdf")).unwrap();
+    assert!(pdf.starts_with(b"%PDF-"));
+}
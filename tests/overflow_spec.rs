@@ -0,0 +1,91 @@
+//! `--overflow`: what happens to text wider than 80 columns.
+
+mod common;
+
+use common::Scratch;
+use pretty_assertions::assert_eq;
+
+fn long_line() -> String {
+    format!("{}{}", "A".repeat(80), "TAIL")
+}
+
+fn texts(scratch: &Scratch, name: &str) -> Vec<String> {
+    let deck = scratch.load(name);
+    (0..deck.cards.len())
+        .map(|idx| deck.card_text(idx).unwrap().trim_end().to_string())
+        .collect()
+}
+
+#[test]
+fn import_rejects_long_lines_by_default() {
+    let scratch = Scratch::new();
+    scratch.write("long.txt", long_line() + "\n");
+    for args in [
+        &["deck", "import", "long.txt", "-o", "long.deck"][..],
+        &[
+            "deck",
+            "import",
+            "long.txt",
+            "-o",
+            "long.deck",
+            "--overflow",
+            "error",
+        ],
+    ] {
+        let stderr = scratch.fails(args, 3);
+        assert!(stderr.contains("--overflow truncate or wrap"), "{}", stderr);
+    }
+    assert!(!scratch.path("long.deck").exists());
+}
+
+#[test]
+fn import_truncates_or_wraps_on_request() {
+    let scratch = Scratch::new();
+    scratch.write("long.txt", long_line() + "\n");
+    scratch.ok(&[
+        "deck",
+        "import",
+        "long.txt",
+        "-o",
+        "cut.deck",
+        "--overflow",
+        "truncate",
+    ]);
+    assert_eq!(texts(&scratch, "cut.deck"), ["A".repeat(80)]);
+    scratch.ok(&[
+        "deck",
+        "import",
+        "long.txt",
+        "-o",
+        "wrap.deck",
+        "--overflow",
+        "wrap",
+    ]);
+    assert_eq!(
+        texts(&scratch, "wrap.deck"),
+        ["A".repeat(80), "TAIL".into()]
+    );
+}
+
+#[test]
+fn card_add_follows_the_same_policies() {
+    let scratch = Scratch::new();
+    scratch.deck("prog.deck", &["FIRST"]);
+    let line = long_line();
+    let add = ["card", "add", "prog.deck", "--text", &line];
+    scratch.fails(&add, 3);
+    scratch.fails(&[&add[..], &["--overflow", "error"]].concat(), 3);
+    assert_eq!(texts(&scratch, "prog.deck"), ["FIRST"]);
+
+    scratch.ok(&[&add[..], &["--overflow", "truncate"]].concat());
+    scratch.ok(&[&add[..], &["--overflow", "wrap"]].concat());
+    assert_eq!(
+        texts(&scratch, "prog.deck"),
+        [
+            "FIRST".to_string(),
+            "A".repeat(80),
+            "A".repeat(80),
+            "TAIL".into()
+        ]
+    );
+}
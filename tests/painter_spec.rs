@@ -0,0 +1,98 @@
+//! `CardPainter`: a downstream style passed to `render_card_image_with`,
+//! and the built-in form painter behind `render image --form`.
+
+mod common;
+
+use std::cell::Cell;
+
+use common::Scratch;
+use image::{Rgba, RgbaImage};
+use punchcard::{
+    CardGeometry, CardImageStyle, CardPainter, CardSide, CornerCut, IBM029, ImageRenderOptions,
+    PageLayout, Palette, PunchCard, render_card_image_with,
+};
+
+const STOCK: Rgba<u8> = Rgba([200, 230, 200, 255]);
+const HOLE: Rgba<u8> = Rgba([160, 0, 0, 255]);
+const STAMP: Rgba<u8> = Rgba([0, 0, 200, 255]);
+
+/// Green stock with red holes and a blue stamp in the top-right corner.
+struct LedgerStyle {
+    geometry: Cell<Option<CardGeometry>>,
+}
+
+impl CardPainter for LedgerStyle {
+    fn palette(&self, card_only: bool) -> Palette {
+        Palette {
+            card_bg: STOCK,
+            hole: HOLE,
+            header: None,
+            ..CardImageStyle::Plain.palette(card_only)
+        }
+    }
+
+    fn decorate(&self, image: &mut RgbaImage, geometry: &CardGeometry, _card: &PunchCard) {
+        for y in 2..12 {
+            for x in geometry.width - 12..geometry.width - 2 {
+                image.put_pixel(x, y, STAMP);
+            }
+        }
+        self.geometry.set(Some(*geometry));
+    }
+}
+
+fn options() -> ImageRenderOptions {
+    ImageRenderOptions {
+        style: CardImageStyle::Interpreter,
+        dpi: 150,
+        layout: PageLayout::Card,
+        per_page: None,
+        side: CardSide::Front,
+        corner_cut: CornerCut::None,
+    }
+}
+
+#[test]
+fn custom_painter_supplies_colours_and_marks() {
+    let scratch = Scratch::new();
+    let card = PunchCard::from_str(&IBM029, "&").unwrap();
+    let painter = LedgerStyle {
+        geometry: Cell::new(None),
+    };
+    let image = render_card_image_with(&card, &painter, &options()).unwrap();
+    image.save(scratch.path("ledger.png")).unwrap();
+
+    let saved = image::open(scratch.path("ledger.png")).unwrap().to_rgba8();
+    let geometry = painter.geometry.get().expect("decorate ran");
+    assert_eq!(
+        (saved.width(), saved.height()),
+        (geometry.width, geometry.height)
+    );
+    // & is a lone 12 punch in column 1; column 2 stays unpunched stock.
+    let hole = |col| *saved.get_pixel(geometry.column_x(col) as u32, geometry.row_y(0) as u32);
+    assert_eq!(hole(0), HOLE);
+    assert_eq!(hole(1), STOCK);
+    assert_eq!(*saved.get_pixel(geometry.width - 7, 7), STAMP);
+}
+
+#[test]
+fn form_flag_prints_the_template_over_the_card() {
+    let scratch = Scratch::new();
+    scratch.write("prog.txt", "      END\n");
+    scratch.ok(&[
+        "deck",
+        "import",
+        "prog.txt",
+        "-o",
+        "prog.deck",
+        "--template",
+        "fortran",
+    ]);
+    scratch.ok(&["render", "image", "prog.deck", "-o", "plain.png"]);
+    scratch.ok(&["render", "image", "prog.deck", "-o", "form.png", "--form"]);
+
+    let plain = image::open(scratch.path("plain.png")).unwrap().to_rgba8();
+    let form = image::open(scratch.path("form.png")).unwrap().to_rgba8();
+    assert_eq!(plain.dimensions(), form.dimensions());
+    assert_ne!(plain, form);
+}
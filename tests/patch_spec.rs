@@ -0,0 +1,44 @@
+//! `punch card patch`: correction cards aimed at a card of the deck.
+
+mod common;
+
+use common::Scratch;
+
+#[test]
+fn patch_target_counts_cards_from_one() {
+    let scratch = Scratch::new();
+    scratch.deck("prog.deck", &["      X = 1", "      END"]);
+    let stderr = scratch.fails(
+        &[
+            "card",
+            "patch",
+            "prog.deck",
+            "--target",
+            "0",
+            "--text",
+            "      X = 2",
+        ],
+        3,
+    );
+    assert!(
+        stderr.contains("card index 0 out of range 1..2"),
+        "{stderr}"
+    );
+    assert_eq!(scratch.load("prog.deck").cards.len(), 2);
+
+    scratch.ok(&[
+        "card",
+        "patch",
+        "prog.deck",
+        "--target",
+        "1",
+        "--text",
+        "      X = 2",
+    ]);
+    let deck = scratch.load("prog.deck");
+    assert_eq!(deck.cards.len(), 3);
+    assert_eq!(
+        deck.resolve_patch_target(deck.cards[2].meta.patches.unwrap()),
+        Some(0)
+    );
+}
@@ -0,0 +1,35 @@
+//! `punch play`: terminal playback of a deck, card by card.
+
+mod common;
+
+use common::Scratch;
+
+#[test]
+fn plays_every_card_in_order() {
+    let scratch = Scratch::new();
+    scratch.deck("hello.deck", &["HELLO", "      END"]);
+
+    let stdout = scratch.ok(&["play", "hello.deck", "--cpm", "6000"]);
+    assert_eq!(stdout.matches("\x1b[2J\x1b[H").count(), 2);
+    let first = stdout.find("Card 1 of 2 @ 6000 cpm").expect("first card");
+    let second = stdout.find("Card 2 of 2 @ 6000 cpm").expect("second card");
+    assert!(first < second);
+    assert!(stdout[first..second].contains("HELLO"));
+    assert!(stdout[second..].contains("      END"));
+    assert!(stdout.ends_with("Played 2 card(s) from hello.deck\n"));
+
+    let kept = scratch.ok(&["play", "hello.deck", "--cpm", "6000", "--no-clear"]);
+    assert!(!kept.contains('\x1b'));
+    assert!(kept.contains("Card 2 of 2"));
+}
+
+#[test]
+fn refuses_speeds_out_of_range() {
+    let scratch = Scratch::new();
+    scratch.deck("hello.deck", &["HELLO"]);
+    for cpm in ["0", "6001"] {
+        let stderr = scratch.fails(&["play", "hello.deck", "--cpm", cpm], 3);
+        assert!(stderr.contains("--cpm"), "{}", stderr);
+    }
+    scratch.fails(&["play", "missing.deck"], 3);
+}
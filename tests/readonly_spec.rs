@@ -0,0 +1,149 @@
+//! `punch deck lock/unlock` and the `--force` override on mutating commands.
+
+mod common;
+
+use common::Scratch;
+use ed25519_dalek::SigningKey;
+use ed25519_dalek::pkcs8::EncodePrivateKey;
+use ed25519_dalek::pkcs8::spki::der::pem::LineEnding;
+
+fn locked() -> Scratch {
+    let scratch = Scratch::new();
+    scratch.deck("prog.deck", &["      CALL EXIT", "      END"]);
+    scratch.ok(&["deck", "lock", "prog.deck"]);
+    scratch
+}
+
+fn card_count(scratch: &Scratch) -> usize {
+    scratch.load("prog.deck").cards.len()
+}
+
+#[test]
+fn lock_and_unlock_toggle_the_readonly_flag() {
+    let scratch = locked();
+    assert!(scratch.load("prog.deck").header.readonly);
+    assert!(
+        scratch
+            .ok(&["deck", "info", "prog.deck"])
+            .contains("Readonly: yes")
+    );
+    scratch.ok(&["deck", "unlock", "prog.deck"]);
+    assert!(!scratch.load("prog.deck").header.readonly);
+    assert!(
+        !scratch
+            .ok(&["deck", "info", "prog.deck"])
+            .contains("Readonly: yes")
+    );
+    scratch.ok(&["card", "add", "prog.deck", "--text", "C ADDED"]);
+    assert_eq!(card_count(&scratch), 3);
+}
+
+#[test]
+fn locked_decks_refuse_changes_without_force() {
+    let scratch = locked();
+    let stderr = scratch.fails(&["card", "add", "prog.deck", "--text", "C NO"], 3);
+    assert!(stderr.contains("readonly"), "{}", stderr);
+    scratch.fails(&["card", "remove", "prog.deck", "1"], 3);
+    scratch.fails(&["seq", "number", "prog.deck"], 3);
+    assert_eq!(card_count(&scratch), 2);
+}
+
+#[test]
+fn force_records_the_override_and_keeps_the_lock() {
+    let scratch = locked();
+    scratch.ok(&["card", "add", "prog.deck", "--text", "C YES", "--force"]);
+    let deck = scratch.load("prog.deck");
+    assert_eq!(deck.cards.len(), 3);
+    assert!(deck.header.readonly);
+    let actions: Vec<&str> = deck
+        .header
+        .history
+        .iter()
+        .map(|event| event.action.as_str())
+        .collect();
+    assert!(
+        actions.contains(&"readonly override: card add"),
+        "{:?}",
+        actions
+    );
+}
+
+#[test]
+fn signing_and_encryption_are_gated() {
+    let scratch = locked();
+    let pem = SigningKey::from_bytes(&[7; 32])
+        .to_pkcs8_pem(LineEnding::LF)
+        .unwrap();
+    scratch.write("key.pem", pem.as_bytes());
+    scratch.write("deck.key", "11".repeat(32));
+
+    let sign = ["audit", "sign", "prog.deck", "--key", "key.pem"];
+    scratch.fails(&sign, 3);
+    assert!(scratch.load("prog.deck").header.signatures.is_empty());
+    scratch.ok(&[&sign[..], &["--force"]].concat());
+    assert_eq!(scratch.load("prog.deck").header.signatures.len(), 1);
+
+    let encrypt = ["deck", "encrypt", "prog.deck", "--keyfile", "deck.key"];
+    scratch.fails(&encrypt, 3);
+    scratch.ok(&[&encrypt[..], &["--force"]].concat());
+    let decrypt = ["deck", "decrypt", "prog.deck", "--keyfile", "deck.key"];
+    scratch.fails(&decrypt, 3);
+    scratch.ok(&[&decrypt[..], &["--force"]].concat());
+    assert_eq!(card_count(&scratch), 2);
+}
+
+#[test]
+fn verification_records_are_gated() {
+    let scratch = Scratch::new();
+    scratch.deck("prog.deck", &["      CALL EXIT", "      END"]);
+    scratch.ok(&["verify", "start", "prog.deck", "--name", "first"]);
+    scratch.ok(&["deck", "lock", "prog.deck"]);
+    scratch.write(
+        "retype.txt",
+        format!("{:<80}\n{:<80}\n", "      CALL EXIT", "      END"),
+    );
+
+    let start = ["verify", "start", "prog.deck"];
+    let pass = [
+        "verify",
+        "pass",
+        "prog.deck",
+        "--baseline",
+        "first",
+        "--from",
+        "retype.txt",
+    ];
+    let delete = ["verify", "baselines", "prog.deck", "--delete", "first"];
+    for args in [&start[..], &pass, &delete] {
+        let stderr = scratch.fails(args, 3);
+        assert!(stderr.contains("readonly"), "{:?}: {}", args, stderr);
+    }
+    let verification = scratch.load("prog.deck").header.verification.unwrap();
+    assert_eq!(verification.baselines.len(), 1);
+    assert!(verification.passes.is_empty());
+
+    for args in [&start[..], &pass, &delete] {
+        scratch.ok(&[args, &["--force"]].concat());
+    }
+    let deck = scratch.load("prog.deck");
+    let verification = deck.header.verification.unwrap();
+    assert_eq!(verification.baselines[0].name, "default");
+    assert_eq!(verification.passes.len(), 1);
+    assert!(deck.header.readonly);
+}
+
+#[test]
+fn protected_columns_block_card_remove_and_move() {
+    let scratch = Scratch::new();
+    scratch.ok(&["deck", "init", "prog.deck", "--protect", "73-80"]);
+    scratch.ok(&["card", "add", "prog.deck", "--text", "      CALL EXIT"]);
+    scratch.ok(&["card", "add", "prog.deck", "--text", "      END"]);
+    scratch.ok(&["seq", "number", "prog.deck"]);
+    let err = scratch.fails(&["card", "remove", "prog.deck", "--index", "2"], 3);
+    assert!(err.contains("is protected and punched"), "{err}");
+    scratch.fails(
+        &["card", "move", "prog.deck", "--from", "2", "--to", "1"],
+        3,
+    );
+    assert_eq!(scratch.load("prog.deck").cards.len(), 2);
+}
@@ -0,0 +1,139 @@
+//! `punch deck renumber-types`: selecting cards and retyping them.
+
+mod common;
+
+use common::Scratch;
+use punchcard::CardType;
+
+fn types(scratch: &Scratch) -> Vec<CardType> {
+    scratch
+        .load("job.deck")
+        .cards
+        .iter()
+        .map(|card| card.card_type.clone())
+        .collect()
+}
+
+fn job() -> Scratch {
+    let scratch = Scratch::new();
+    scratch.deck(
+        "job.deck",
+        &["//JOB1 JOB", "//* NOTE", "      CALL EXIT", "      END"],
+    );
+    scratch
+}
+
+#[test]
+fn dry_run_lists_matches_without_saving() {
+    let scratch = job();
+    let out = scratch.ok(&[
+        "deck",
+        "renumber-types",
+        "job.deck",
+        "--to",
+        "jcl",
+        "--prefix",
+        "//",
+        "--dry-run",
+    ]);
+    assert!(
+        out.contains("    1 code      -> jcl       |//JOB1 JOB"),
+        "{}",
+        out
+    );
+    assert!(out.contains("2 card(s) would change to jcl"), "{}", out);
+    assert_eq!(types(&scratch), vec![CardType::Code; 4]);
+}
+
+#[test]
+fn selectors_combine() {
+    let scratch = job();
+    scratch.ok(&[
+        "deck",
+        "renumber-types",
+        "job.deck",
+        "--to",
+        "jcl",
+        "--prefix",
+        "//",
+    ]);
+    scratch.ok(&[
+        "deck",
+        "renumber-types",
+        "job.deck",
+        "--to",
+        "comment",
+        "--where",
+        "jcl",
+        "--contains",
+        "NOTE",
+    ]);
+    scratch.ok(&[
+        "deck",
+        "renumber-types",
+        "job.deck",
+        "--to",
+        "data",
+        "--range",
+        "3..$",
+        "--contains",
+        "END",
+    ]);
+    assert_eq!(
+        types(&scratch),
+        [
+            CardType::Jcl,
+            CardType::Comment,
+            CardType::Code,
+            CardType::Data
+        ]
+    );
+    let history = &scratch.load("job.deck").header.history;
+    assert!(
+        history
+            .iter()
+            .any(|event| event.action == "deck renumber-types 1 cards -> data")
+    );
+}
+
+#[test]
+fn selection_is_required_and_ranges_are_checked() {
+    let scratch = job();
+    let err = scratch.fails(&["deck", "renumber-types", "job.deck", "--to", "data"], 3);
+    assert!(err.contains("select cards with --range"), "{}", err);
+    let err = scratch.fails(
+        &[
+            "deck",
+            "renumber-types",
+            "job.deck",
+            "--to",
+            "data",
+            "--range",
+            "9",
+        ],
+        3,
+    );
+    assert!(err.contains("9"), "{}", err);
+    assert_eq!(types(&scratch), vec![CardType::Code; 4]);
+}
+
+#[test]
+fn frozen_decks_are_not_retyped() {
+    let scratch = job();
+    scratch.ok(&["deck", "state", "job.deck", "--to", "verified"]);
+    scratch.ok(&["deck", "state", "job.deck", "--to", "frozen"]);
+    let err = scratch.fails(
+        &[
+            "deck",
+            "renumber-types",
+            "job.deck",
+            "--to",
+            "data",
+            "--range",
+            "1",
+        ],
+        3,
+    );
+    assert!(err.contains("frozen"), "{}", err);
+    assert_eq!(types(&scratch), vec![CardType::Code; 4]);
+}
@@ -0,0 +1,52 @@
+//! `punch render site`: a deck published as a small static website.
+
+mod common;
+
+use common::Scratch;
+use pretty_assertions::assert_eq;
+
+#[test]
+fn publishes_index_cards_listing_and_metadata() {
+    let scratch = Scratch::new();
+    scratch.deck("prog.deck", &["      IF (A<B) GO TO 10", "      END"]);
+    scratch.ok(&[
+        "deck",
+        "meta",
+        "prog.deck",
+        "--description",
+        "Payroll & tax",
+    ]);
+
+    let stdout = scratch.ok(&[
+        "render",
+        "site",
+        "prog.deck",
+        "-o",
+        "site",
+        "--title",
+        "Payroll",
+    ]);
+    assert!(stdout.contains("Published 2 card(s)"), "{}", stdout);
+
+    for card in ["card_0001.svg", "card_0002.svg"] {
+        let svg = std::fs::read_to_string(scratch.path("site/cards").join(card)).unwrap();
+        assert!(svg.starts_with("<svg"), "{}", card);
+    }
+    assert!(!scratch.path("site/cards/card_0003.svg").exists());
+
+    let listing = std::fs::read_to_string(scratch.path("site/listing.txt")).unwrap();
+    let lines: Vec<&str> = listing.lines().map(str::trim_end).collect();
+    assert_eq!(lines, ["      IF (A<B) GO TO 10", "      END"]);
+
+    let meta: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(scratch.path("site/deck.json")).unwrap())
+            .unwrap();
+    assert_eq!(meta["description"], "Payroll & tax");
+
+    let index = std::fs::read_to_string(scratch.path("site/index.html")).unwrap();
+    assert!(index.contains("<title>Payroll</title>"));
+    assert!(index.contains("Payroll &amp; tax"));
+    assert!(index.contains("IF (A&lt;B) GO TO 10"));
+    assert!(index.contains("href=\"cards/card_0002.svg\""));
+    assert!(index.contains(&scratch.load("prog.deck").hash().unwrap()));
+}
@@ -0,0 +1,53 @@
+//! `punch deck validate --untrusted` on hostile deck files.
+
+mod common;
+
+use common::Scratch;
+
+fn scratch() -> Scratch {
+    let scratch = Scratch::new();
+    scratch.deck("prog.deck", &["      CALL EXIT"]);
+    scratch
+}
+
+fn rewrite(scratch: &Scratch, edit: impl Fn(&str) -> String) {
+    let raw = std::fs::read_to_string(scratch.path("prog.deck")).unwrap();
+    scratch.write("prog.deck", edit(&raw));
+}
+
+#[test]
+fn clean_decks_validate() {
+    let scratch = scratch();
+    scratch.ok(&["deck", "validate", "prog.deck", "--untrusted"]);
+}
+
+#[test]
+fn malformed_punches_are_rejected() {
+    let scratch = scratch();
+    rewrite(&scratch, |raw| {
+        format!(
+            "{}{}\n",
+            raw, r#"{"kind":"card","punches":"aaéab","card_type":"data"}"#
+        )
+    });
+    let stderr = scratch.fails(&["deck", "validate", "prog.deck", "--untrusted"], 3);
+    assert!(
+        stderr.contains("card on line 3 has malformed punches"),
+        "{}",
+        stderr
+    );
+}
+
+#[test]
+fn foreign_encoders_are_rejected() {
+    let scratch = scratch();
+    rewrite(&scratch, |raw| {
+        raw.replacen("{", r#"{"encoder":"/etc/passwd","#, 1)
+    });
+    let stderr = scratch.fails(&["deck", "validate", "prog.deck", "--untrusted"], 3);
+    assert!(
+        stderr.contains("deck encoder '/etc/passwd' is not a built-in chart"),
+        "{}",
+        stderr
+    );
+}